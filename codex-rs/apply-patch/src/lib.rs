@@ -1,6 +1,8 @@
+mod merge;
 mod parser;
 mod seek_sequence;
 mod standalone_executable;
+mod unified_diff;
 
 use std::collections::HashMap;
 use std::path::Path;
@@ -10,6 +12,7 @@ use std::sync::LazyLock;
 
 use anyhow::Context;
 use anyhow::Result;
+pub use merge::merge_three_way;
 pub use parser::Hunk;
 pub use parser::ParseError;
 use parser::ParseError::*;
@@ -31,6 +34,23 @@ pub const APPLY_PATCH_TOOL_INSTRUCTIONS: &str = include_str!("../apply_patch_too
 
 const APPLY_PATCH_COMMANDS: [&str; 2] = ["apply_patch", "applypatch"];
 
+/// How many lines of leading and trailing context an update chunk may drop
+/// when its full context cannot be found, unless overridden by
+/// [`ApplyPatchOptions::fuzz`].
+pub const DEFAULT_FUZZ: usize = 2;
+
+/// Largest file, in bytes, that a patch may update or create unless overridden
+/// by [`ApplyPatchOptions::max_file_bytes`].
+pub const DEFAULT_MAX_FILE_BYTES: u64 = 10 * 1024 * 1024;
+
+/// Extensions of files that are never text, so an added file with one of them
+/// would not be what the model meant to write.
+const BINARY_EXTENSIONS: &[&str] = &[
+    "7z", "bin", "bmp", "class", "dll", "dylib", "exe", "gif", "gz", "ico", "jar", "jpeg", "jpg",
+    "mp3", "mp4", "o", "otf", "pdf", "png", "pyc", "so", "tar", "tgz", "ttf", "wasm", "webp",
+    "woff", "woff2", "xz", "zip",
+];
+
 #[derive(Debug, Error, PartialEq)]
 pub enum ApplyPatchError {
     #[error(transparent)]
@@ -45,6 +65,22 @@ pub enum ApplyPatchError {
         "patch detected without explicit call to apply_patch. Rerun as [\"apply_patch\", \"<patch>\"]"
     )]
     ImplicitInvocation,
+    /// `--reverse` was asked to undo a file deletion, whose contents the
+    /// patch does not record.
+    #[error("cannot reverse the deletion of {}: the patch does not include its contents", .0.display())]
+    IrreversibleDelete(PathBuf),
+    /// The patch adds or updates a binary file, which its text format cannot
+    /// represent.
+    #[error("{} is a binary file; apply_patch can only write text files", .0.display())]
+    BinaryFile(PathBuf),
+    /// A file the patch reads or writes is larger than
+    /// [`ApplyPatchOptions::max_file_bytes`].
+    #[error("{} is {size} bytes, over apply_patch's {limit}-byte limit", path.display())]
+    FileTooLarge {
+        path: PathBuf,
+        size: u64,
+        limit: u64,
+    },
 }
 
 impl From<std::io::Error> for ApplyPatchError {
@@ -179,6 +215,31 @@ impl ApplyPatchAction {
         &self.changes
     }
 
+    /// Keeps only the changes to `files`, rewriting `patch` to match, and
+    /// returns the paths whose changes were dropped, sorted.
+    pub fn retain_files(&mut self, files: &[PathBuf]) -> Vec<PathBuf> {
+        let mut skipped: Vec<PathBuf> = self
+            .changes
+            .keys()
+            .filter(|path| !files.contains(path))
+            .cloned()
+            .collect();
+        if skipped.is_empty() {
+            return skipped;
+        }
+        skipped.sort();
+        self.changes.retain(|path, _| files.contains(path));
+        // `patch` parsed when the action was built, so it parses again here.
+        if let Ok(ApplyPatchArgs { hunks, .. }) = parse_patch(&self.patch) {
+            let hunks: Vec<Hunk> = hunks
+                .into_iter()
+                .filter(|hunk| files.contains(&hunk.resolve_path(&self.cwd)))
+                .collect();
+            self.patch = patch_from_hunks(&hunks);
+        }
+        skipped
+    }
+
     /// Should be used exclusively for testing. (Not worth the overhead of
     /// creating a feature flag for this.)
     pub fn new_add_for_test(path: &Path, content: String) -> Self {
@@ -214,6 +275,19 @@ impl ApplyPatchAction {
 /// cwd must be an absolute path so that we can resolve relative paths in the
 /// patch.
 pub fn maybe_parse_apply_patch_verified(argv: &[String], cwd: &Path) -> MaybeApplyPatchVerified {
+    maybe_parse_apply_patch_verified_with_base(argv, cwd, |_| None)
+}
+
+/// Like [`maybe_parse_apply_patch_verified`], but an update that no longer
+/// applies to its file is retried against `base(path)`, the file's contents
+/// before the most recent edits, and the result merged into the current
+/// contents with [`merge_three_way`]. When that succeeds, the action's
+/// `patch` is rewritten so that applying it reproduces the merged contents.
+pub fn maybe_parse_apply_patch_verified_with_base(
+    argv: &[String],
+    cwd: &Path,
+    base: impl Fn(&Path) -> Option<String>,
+) -> MaybeApplyPatchVerified {
     // Detect a raw patch body passed directly as the command or as the body of a bash -lc
     // script. In these cases, report an explicit error rather than applying the patch.
     match argv {
@@ -252,15 +326,23 @@ pub fn maybe_parse_apply_patch_verified(argv: &[String], cwd: &Path) -> MaybeApp
                 })
                 .unwrap_or_else(|| cwd.to_path_buf());
             let mut changes = HashMap::new();
+            let mut merged_hunks = Vec::with_capacity(hunks.len());
+            let mut merged_any = false;
             for hunk in hunks {
                 let path = hunk.resolve_path(&effective_cwd);
                 match hunk {
-                    Hunk::AddFile { contents, .. } => {
-                        changes.insert(path, ApplyPatchFileChange::Add { content: contents });
+                    Hunk::AddFile { ref contents, .. } => {
+                        if let Err(e) = check_new_file(&path, contents, DEFAULT_MAX_FILE_BYTES) {
+                            return MaybeApplyPatchVerified::CorrectnessError(e);
+                        }
+                        let content = contents.clone();
+                        merged_hunks.push(hunk);
+                        changes.insert(path, ApplyPatchFileChange::Add { content });
                     }
                     Hunk::DeleteFile { .. } => {
-                        let content = match std::fs::read_to_string(&path) {
-                            Ok(content) => content,
+                        // Deleting a binary file is fine; show what can be shown.
+                        let content = match std::fs::read(&path) {
+                            Ok(bytes) => String::from_utf8_lossy(&bytes).into_owned(),
                             Err(e) => {
                                 return MaybeApplyPatchVerified::CorrectnessError(
                                     ApplyPatchError::IoError(IoError {
@@ -270,31 +352,57 @@ pub fn maybe_parse_apply_patch_verified(argv: &[String], cwd: &Path) -> MaybeApp
                                 );
                             }
                         };
+                        merged_hunks.push(hunk);
                         changes.insert(path, ApplyPatchFileChange::Delete { content });
                     }
                     Hunk::UpdateFile {
-                        move_path, chunks, ..
+                        path: ref hunk_path,
+                        ref move_path,
+                        ref chunks,
                     } => {
+                        let update = match unified_diff_from_chunks(&path, chunks) {
+                            Ok(diff) => {
+                                merged_hunks.push(hunk.clone());
+                                diff
+                            }
+                            Err(e) => match merge_update_with_base(&path, chunks, &base) {
+                                Some((current, update)) => {
+                                    merged_any = true;
+                                    merged_hunks.push(Hunk::UpdateFile {
+                                        path: hunk_path.clone(),
+                                        move_path: move_path.clone(),
+                                        chunks: vec![UpdateFileChunk {
+                                            change_context: None,
+                                            old_lines: split_lines(&current),
+                                            new_lines: split_lines(&update.content),
+                                            is_end_of_file: false,
+                                        }],
+                                    });
+                                    update
+                                }
+                                None => return MaybeApplyPatchVerified::CorrectnessError(e),
+                            },
+                        };
                         let ApplyPatchFileUpdate {
                             unified_diff,
                             content: contents,
-                        } = match unified_diff_from_chunks(&path, &chunks) {
-                            Ok(diff) => diff,
-                            Err(e) => {
-                                return MaybeApplyPatchVerified::CorrectnessError(e);
-                            }
-                        };
+                        } = update;
                         changes.insert(
                             path,
                             ApplyPatchFileChange::Update {
                                 unified_diff,
-                                move_path: move_path.map(|p| cwd.join(p)),
+                                move_path: move_path.as_ref().map(|p| cwd.join(p)),
                                 new_content: contents,
                             },
                         );
                     }
                 }
             }
+            let patch = if merged_any {
+                patch_from_hunks(&merged_hunks)
+            } else {
+                patch
+            };
             MaybeApplyPatchVerified::Body(ApplyPatchAction {
                 changes,
                 patch,
@@ -468,11 +576,47 @@ pub enum ExtractHeredocError {
     FailedToFindHeredocBody,
 }
 
+/// How [`apply_patch_with_options`] treats the patch.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ApplyPatchOptions {
+    /// Only report whether each change would apply; leave files untouched.
+    pub dry_run: bool,
+    /// Undo the patch instead of applying it; see [`reverse_hunks`].
+    pub reverse: bool,
+    /// Lines of context a chunk may ignore at each end when its context has
+    /// drifted, like `patch --fuzz`. Zero requires every context line to match.
+    pub fuzz: usize,
+    /// Largest file, in bytes, the patch may update or create.
+    pub max_file_bytes: u64,
+}
+
+impl Default for ApplyPatchOptions {
+    fn default() -> Self {
+        Self {
+            dry_run: false,
+            reverse: false,
+            fuzz: DEFAULT_FUZZ,
+            max_file_bytes: DEFAULT_MAX_FILE_BYTES,
+        }
+    }
+}
+
 /// Applies the patch and prints the result to stdout/stderr.
 pub fn apply_patch(
     patch: &str,
     stdout: &mut impl std::io::Write,
     stderr: &mut impl std::io::Write,
+) -> Result<(), ApplyPatchError> {
+    apply_patch_with_options(patch, ApplyPatchOptions::default(), stdout, stderr)
+}
+
+/// Like [`apply_patch`], but can check the patch without applying it and/or
+/// apply it in reverse.
+pub fn apply_patch_with_options(
+    patch: &str,
+    options: ApplyPatchOptions,
+    stdout: &mut impl std::io::Write,
+    stderr: &mut impl std::io::Write,
 ) -> Result<(), ApplyPatchError> {
     let hunks = match parse_patch(patch) {
         Ok(source) => source.hunks,
@@ -495,15 +639,154 @@ pub fn apply_patch(
             return Err(ApplyPatchError::ParseError(e));
         }
     };
+    let hunks = if options.reverse {
+        match reverse_hunks(hunks) {
+            Ok(hunks) => hunks,
+            Err(e) => {
+                writeln!(stderr, "{e}").map_err(ApplyPatchError::from)?;
+                return Err(e);
+            }
+        }
+    } else {
+        hunks
+    };
 
-    apply_hunks(&hunks, stdout, stderr)?;
+    if options.dry_run {
+        check_hunks(&hunks, options, stdout, stderr)
+    } else {
+        apply_hunks(&hunks, options, stdout, stderr)
+    }
+}
 
-    Ok(())
+/// Inverts `hunks` so that applying the result undoes the patch: added files
+/// are deleted, updates swap their old and new lines, and moves go back to
+/// the original path. Deleted files cannot be restored because the patch
+/// does not record their contents.
+pub fn reverse_hunks(hunks: Vec<Hunk>) -> Result<Vec<Hunk>, ApplyPatchError> {
+    hunks
+        .into_iter()
+        .rev()
+        .map(|hunk| match hunk {
+            Hunk::AddFile { path, .. } => Ok(Hunk::DeleteFile { path }),
+            Hunk::DeleteFile { path } => Err(ApplyPatchError::IrreversibleDelete(path)),
+            Hunk::UpdateFile {
+                path,
+                move_path,
+                chunks,
+            } => {
+                let chunks = chunks
+                    .into_iter()
+                    .map(|chunk| UpdateFileChunk {
+                        old_lines: chunk.new_lines,
+                        new_lines: chunk.old_lines,
+                        ..chunk
+                    })
+                    .collect();
+                Ok(match move_path {
+                    Some(dest) => Hunk::UpdateFile {
+                        path: dest,
+                        move_path: Some(path),
+                        chunks,
+                    },
+                    None => Hunk::UpdateFile {
+                        path,
+                        move_path: None,
+                        chunks,
+                    },
+                })
+            }
+        })
+        .collect()
+}
+
+/// Reports whether each hunk, and each chunk of an update, would apply
+/// without changing any files. Chunks are checked in order, as they would be
+/// applied, so checking a file stops at its first chunk that does not apply.
+pub fn check_hunks(
+    hunks: &[Hunk],
+    options: ApplyPatchOptions,
+    stdout: &mut impl std::io::Write,
+    stderr: &mut impl std::io::Write,
+) -> Result<(), ApplyPatchError> {
+    if hunks.is_empty() {
+        writeln!(stderr, "No files were modified.").map_err(ApplyPatchError::from)?;
+        return Err(ApplyPatchError::ComputeReplacements(
+            "No files were modified.".to_string(),
+        ));
+    }
+
+    let mut first_error: Option<ApplyPatchError> = None;
+    writeln!(stdout, "Dry run. Checked the following files:")?;
+    for hunk in hunks {
+        match hunk {
+            Hunk::AddFile { path, contents } => {
+                writeln!(stdout, "A {}", path.display())?;
+                if let Err(e) = check_new_file(path, contents, options.max_file_bytes) {
+                    writeln!(stdout, "  {e}")?;
+                    first_error.get_or_insert(e);
+                }
+            }
+            Hunk::DeleteFile { path } => {
+                if path.is_file() {
+                    writeln!(stdout, "D {}", path.display())?;
+                } else {
+                    writeln!(stdout, "D {}: file does not exist", path.display())?;
+                    first_error.get_or_insert(ApplyPatchError::ComputeReplacements(format!(
+                        "Failed to delete file {}: file does not exist",
+                        path.display()
+                    )));
+                }
+            }
+            Hunk::UpdateFile {
+                path,
+                move_path,
+                chunks,
+            } => {
+                match move_path {
+                    Some(dest) => writeln!(stdout, "M {} -> {}", path.display(), dest.display())?,
+                    None => writeln!(stdout, "M {}", path.display())?,
+                }
+                let original_lines = match read_lines_to_update(path, options.max_file_bytes) {
+                    Ok((_, lines)) => lines,
+                    Err(e) => {
+                        writeln!(stdout, "  {e}")?;
+                        first_error.get_or_insert(e);
+                        continue;
+                    }
+                };
+                let total = chunks.len();
+                for checked in 1..=total {
+                    match compute_replacements(
+                        &original_lines,
+                        path,
+                        &chunks[..checked],
+                        options.fuzz,
+                    ) {
+                        Ok(_) => writeln!(stdout, "  hunk {checked}/{total} applies")?,
+                        Err(e) => {
+                            writeln!(stdout, "  hunk {checked}/{total} does not apply")?;
+                            first_error.get_or_insert(e);
+                            break;
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    match first_error {
+        None => Ok(()),
+        Some(e) => {
+            writeln!(stderr, "{e}").map_err(ApplyPatchError::from)?;
+            Err(e)
+        }
+    }
 }
 
 /// Applies hunks and continues to update stdout/stderr
 pub fn apply_hunks(
     hunks: &[Hunk],
+    options: ApplyPatchOptions,
     stdout: &mut impl std::io::Write,
     stderr: &mut impl std::io::Write,
 ) -> Result<(), ApplyPatchError> {
@@ -534,7 +817,7 @@ pub fn apply_hunks(
         .collect::<Vec<&Path>>();
 
     // Delegate to a helper that applies each hunk to the filesystem.
-    match apply_hunks_to_files(hunks) {
+    match apply_hunks_to_files(hunks, options) {
         Ok(affected) => {
             print_summary(&affected, stdout).map_err(ApplyPatchError::from)?;
             Ok(())
@@ -565,7 +848,10 @@ pub struct AffectedPaths {
 
 /// Apply the hunks to the filesystem, returning which files were added, modified, or deleted.
 /// Returns an error if the patch could not be applied.
-fn apply_hunks_to_files(hunks: &[Hunk]) -> anyhow::Result<AffectedPaths> {
+fn apply_hunks_to_files(
+    hunks: &[Hunk],
+    options: ApplyPatchOptions,
+) -> anyhow::Result<AffectedPaths> {
     if hunks.is_empty() {
         anyhow::bail!("No files were modified.");
     }
@@ -576,6 +862,7 @@ fn apply_hunks_to_files(hunks: &[Hunk]) -> anyhow::Result<AffectedPaths> {
     for hunk in hunks {
         match hunk {
             Hunk::AddFile { path, contents } => {
+                check_new_file(path, contents, options.max_file_bytes)?;
                 if let Some(parent) = path.parent()
                     && !parent.as_os_str().is_empty()
                 {
@@ -598,7 +885,7 @@ fn apply_hunks_to_files(hunks: &[Hunk]) -> anyhow::Result<AffectedPaths> {
                 chunks,
             } => {
                 let AppliedPatch { new_contents, .. } =
-                    derive_new_contents_from_chunks(path, chunks)?;
+                    derive_new_contents_from_chunks(path, chunks, options)?;
                 if let Some(dest) = move_path {
                     if let Some(parent) = dest.parent()
                         && !parent.as_os_str().is_empty()
@@ -637,45 +924,111 @@ struct AppliedPatch {
 fn derive_new_contents_from_chunks(
     path: &Path,
     chunks: &[UpdateFileChunk],
+    options: ApplyPatchOptions,
 ) -> std::result::Result<AppliedPatch, ApplyPatchError> {
-    let original_contents = match std::fs::read_to_string(path) {
-        Ok(contents) => contents,
-        Err(err) => {
-            return Err(ApplyPatchError::IoError(IoError {
-                context: format!("Failed to read file to update {}", path.display()),
-                source: err,
-            }));
-        }
+    let (original_contents, original_lines) = read_lines_to_update(path, options.max_file_bytes)?;
+    let new_contents = apply_chunks_to_lines(original_lines, path, chunks, options.fuzz)?;
+    check_size(path, new_contents.len() as u64, options.max_file_bytes)?;
+    Ok(AppliedPatch {
+        original_contents,
+        new_contents,
+    })
+}
+
+/// Apply the chunks to the lines of the file at `path`, returning the new
+/// contents with a trailing newline.
+fn apply_chunks_to_lines(
+    original_lines: Vec<String>,
+    path: &Path,
+    chunks: &[UpdateFileChunk],
+    fuzz: usize,
+) -> std::result::Result<String, ApplyPatchError> {
+    let replacements = compute_replacements(&original_lines, path, chunks, fuzz)?;
+    let mut new_lines = apply_replacements(original_lines, &replacements);
+    if !new_lines.last().is_some_and(String::is_empty) {
+        new_lines.push(String::new());
+    }
+    Ok(new_lines.join("\n"))
+}
+
+/// Read the file at `path` for an update, returning its contents and its
+/// lines without the trailing empty element left by a final newline. Binary
+/// files and files over `max_file_bytes` are refused.
+fn read_lines_to_update(
+    path: &Path,
+    max_file_bytes: u64,
+) -> std::result::Result<(String, Vec<String>), ApplyPatchError> {
+    let io_error = |err| {
+        ApplyPatchError::IoError(IoError {
+            context: format!("Failed to read file to update {}", path.display()),
+            source: err,
+        })
     };
+    let size = std::fs::metadata(path).map_err(io_error)?.len();
+    check_size(path, size, max_file_bytes)?;
+    let bytes = std::fs::read(path).map_err(io_error)?;
+    if looks_binary(&bytes) {
+        return Err(ApplyPatchError::BinaryFile(path.to_path_buf()));
+    }
+    let original_contents =
+        String::from_utf8(bytes).map_err(|_| ApplyPatchError::BinaryFile(path.to_path_buf()))?;
+    let original_lines = split_lines(&original_contents);
+    Ok((original_contents, original_lines))
+}
 
-    let mut original_lines: Vec<String> = original_contents.split('\n').map(String::from).collect();
+/// Refuse to add `contents` at `path` when it is over `max_file_bytes` or
+/// the path names a binary file type.
+fn check_new_file(
+    path: &Path,
+    contents: &str,
+    max_file_bytes: u64,
+) -> std::result::Result<(), ApplyPatchError> {
+    let binary_extension = path
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| BINARY_EXTENSIONS.contains(&ext.to_ascii_lowercase().as_str()));
+    if binary_extension || contents.contains('\0') {
+        return Err(ApplyPatchError::BinaryFile(path.to_path_buf()));
+    }
+    check_size(path, contents.len() as u64, max_file_bytes)
+}
 
-    // Drop the trailing empty element that results from the final newline so
-    // that line counts match the behaviour of standard `diff`.
-    if original_lines.last().is_some_and(String::is_empty) {
-        original_lines.pop();
+fn check_size(path: &Path, size: u64, limit: u64) -> std::result::Result<(), ApplyPatchError> {
+    if size > limit {
+        return Err(ApplyPatchError::FileTooLarge {
+            path: path.to_path_buf(),
+            size,
+            limit,
+        });
     }
+    Ok(())
+}
 
-    let replacements = compute_replacements(&original_lines, path, chunks)?;
-    let new_lines = apply_replacements(original_lines, &replacements);
-    let mut new_lines = new_lines;
-    if !new_lines.last().is_some_and(String::is_empty) {
-        new_lines.push(String::new());
+/// Like git, treat a file as binary when its first 8000 bytes contain a NUL.
+fn looks_binary(bytes: &[u8]) -> bool {
+    bytes.iter().take(8000).any(|byte| *byte == 0)
+}
+
+/// Split `contents` into lines, dropping the trailing empty element that
+/// results from the final newline so that line counts match the behaviour of
+/// standard `diff`.
+fn split_lines(contents: &str) -> Vec<String> {
+    let mut lines: Vec<String> = contents.split('\n').map(String::from).collect();
+    if lines.last().is_some_and(String::is_empty) {
+        lines.pop();
     }
-    let new_contents = new_lines.join("\n");
-    Ok(AppliedPatch {
-        original_contents,
-        new_contents,
-    })
+    lines
 }
 
 /// Compute a list of replacements needed to transform `original_lines` into the
 /// new lines, given the patch `chunks`. Each replacement is returned as
-/// `(start_index, old_len, new_lines)`.
+/// `(start_index, old_len, new_lines)`. A chunk whose lines cannot be found
+/// is retried with up to `fuzz` lines of context dropped from each end.
 fn compute_replacements(
     original_lines: &[String],
     path: &Path,
     chunks: &[UpdateFileChunk],
+    fuzz: usize,
 ) -> std::result::Result<Vec<(usize, usize, Vec<String>)>, ApplyPatchError> {
     let mut replacements: Vec<(usize, usize, Vec<String>)> = Vec::new();
     let mut line_index: usize = 0;
@@ -745,6 +1098,21 @@ fn compute_replacements(
             );
         }
 
+        if found.is_none()
+            && let Some((start_idx, fuzzed_pattern, fuzzed_new)) = seek_with_fuzz(
+                original_lines,
+                pattern,
+                new_slice,
+                line_index,
+                chunk.is_end_of_file,
+                fuzz,
+            )
+        {
+            found = Some(start_idx);
+            pattern = fuzzed_pattern;
+            new_slice = fuzzed_new;
+        }
+
         if let Some(start_idx) = found {
             replacements.push((start_idx, pattern.len(), new_slice.to_vec()));
             line_index = start_idx + pattern.len();
@@ -762,6 +1130,49 @@ fn compute_replacements(
     Ok(replacements)
 }
 
+/// Search for `old_lines` again after dropping up to `fuzz` of its leading
+/// and trailing context lines (lines it shares with `new_lines`), one more
+/// line from each end per attempt, like `patch --fuzz`. Returns where the
+/// trimmed lines were found along with the trimmed old and new lines.
+fn seek_with_fuzz<'a>(
+    original_lines: &[String],
+    old_lines: &'a [String],
+    new_lines: &'a [String],
+    start: usize,
+    eof: bool,
+    fuzz: usize,
+) -> Option<(usize, &'a [String], &'a [String])> {
+    let leading = old_lines
+        .iter()
+        .zip(new_lines)
+        .take_while(|(old, new)| old == new)
+        .count();
+    let trailing = old_lines
+        .iter()
+        .rev()
+        .zip(new_lines.iter().rev())
+        .take_while(|(old, new)| old == new)
+        .count()
+        .min(old_lines.len().min(new_lines.len()) - leading);
+
+    // Past `max(leading, trailing)` there is no more context left to drop.
+    for level in 1..=fuzz.min(leading.max(trailing)) {
+        let drop_front = leading.min(level);
+        let drop_back = trailing.min(level);
+        let old = &old_lines[drop_front..old_lines.len() - drop_back];
+        if old.is_empty() {
+            break;
+        }
+        let new = &new_lines[drop_front..new_lines.len() - drop_back];
+        // Without its trailing context the chunk no longer pins the end of file.
+        let eof = eof && drop_back == 0;
+        if let Some(idx) = seek_sequence::seek_sequence(original_lines, old, start, eof) {
+            return Some((idx, old, new));
+        }
+    }
+    None
+}
+
 /// Apply the `(start_index, old_len, new_lines)` replacements to `original_lines`,
 /// returning the modified file contents as a vector of lines.
 fn apply_replacements(
@@ -812,7 +1223,7 @@ pub fn unified_diff_from_chunks_with_context(
     let AppliedPatch {
         original_contents,
         new_contents,
-    } = derive_new_contents_from_chunks(path, chunks)?;
+    } = derive_new_contents_from_chunks(path, chunks, ApplyPatchOptions::default())?;
     let text_diff = TextDiff::from_lines(&original_contents, &new_contents);
     let unified_diff = text_diff.unified_diff().context_radius(context).to_string();
     Ok(ApplyPatchFileUpdate {
@@ -821,6 +1232,80 @@ pub fn unified_diff_from_chunks_with_context(
     })
 }
 
+/// Apply `chunks` to `base`, an earlier snapshot of the file at `path`, and
+/// merge the result into the file's current contents. Returns the current
+/// contents and the merged update, or `None` when the chunks do not apply to
+/// the snapshot either or the merge conflicts.
+fn merge_update_with_base(
+    path: &Path,
+    chunks: &[UpdateFileChunk],
+    base: impl Fn(&Path) -> Option<String>,
+) -> Option<(String, ApplyPatchFileUpdate)> {
+    let base_contents = base(path)?;
+    let (current, _) = read_lines_to_update(path, DEFAULT_MAX_FILE_BYTES).ok()?;
+    let patched_base =
+        apply_chunks_to_lines(split_lines(&base_contents), path, chunks, DEFAULT_FUZZ).ok()?;
+    let merged = merge_three_way(&base_contents, &current, &patched_base)?;
+    let unified_diff = TextDiff::from_lines(&current, &merged)
+        .unified_diff()
+        .context_radius(1)
+        .to_string();
+    Some((
+        current,
+        ApplyPatchFileUpdate {
+            unified_diff,
+            content: merged,
+        },
+    ))
+}
+
+/// Render `hunks` back into the patch format. Update chunks are written as
+/// removed and added lines without separate context lines, which applies
+/// identically.
+fn patch_from_hunks(hunks: &[Hunk]) -> String {
+    let mut patch = String::from("*** Begin Patch\n");
+    for hunk in hunks {
+        match hunk {
+            Hunk::AddFile { path, contents } => {
+                patch.push_str(&format!("*** Add File: {}\n", path.display()));
+                for line in contents.lines() {
+                    patch.push_str(&format!("+{line}\n"));
+                }
+            }
+            Hunk::DeleteFile { path } => {
+                patch.push_str(&format!("*** Delete File: {}\n", path.display()));
+            }
+            Hunk::UpdateFile {
+                path,
+                move_path,
+                chunks,
+            } => {
+                patch.push_str(&format!("*** Update File: {}\n", path.display()));
+                if let Some(dest) = move_path {
+                    patch.push_str(&format!("*** Move to: {}\n", dest.display()));
+                }
+                for chunk in chunks {
+                    match &chunk.change_context {
+                        Some(context) => patch.push_str(&format!("@@ {context}\n")),
+                        None => patch.push_str("@@\n"),
+                    }
+                    for line in &chunk.old_lines {
+                        patch.push_str(&format!("-{line}\n"));
+                    }
+                    for line in &chunk.new_lines {
+                        patch.push_str(&format!("+{line}\n"));
+                    }
+                    if chunk.is_end_of_file {
+                        patch.push_str("*** End of File\n");
+                    }
+                }
+            }
+        }
+    }
+    patch.push_str("*** End Patch");
+    patch
+}
+
 /// Print the summary of changes in git-style format.
 /// Write a summary of changes to the given writer.
 pub fn print_summary(
@@ -1602,6 +2087,345 @@ g
         );
     }
 
+    #[test]
+    fn test_reverse_undoes_update_move_and_add() {
+        let dir = tempdir().unwrap();
+        let src = dir.path().join("src.txt");
+        let dest = dir.path().join("dest.txt");
+        let added = dir.path().join("added.txt");
+        fs::write(&src, "foo\nbar\n").unwrap();
+        let patch = wrap_patch(&format!(
+            r#"*** Update File: {}
+*** Move to: {}
+@@
+ foo
+-bar
++BAR
+*** Add File: {}
++new"#,
+            src.display(),
+            dest.display(),
+            added.display()
+        ));
+        let mut stdout = Vec::new();
+        let mut stderr = Vec::new();
+        apply_patch(&patch, &mut stdout, &mut stderr).unwrap();
+        assert_eq!(fs::read_to_string(&dest).unwrap(), "foo\nBAR\n");
+
+        let reverse = ApplyPatchOptions {
+            reverse: true,
+            ..Default::default()
+        };
+        apply_patch_with_options(&patch, reverse, &mut stdout, &mut stderr).unwrap();
+        assert_eq!(fs::read_to_string(&src).unwrap(), "foo\nbar\n");
+        assert!(!dest.exists());
+        assert!(!added.exists());
+    }
+
+    #[test]
+    fn test_reverse_rejects_deleted_files() {
+        let hunks = vec![Hunk::DeleteFile {
+            path: PathBuf::from("gone.txt"),
+        }];
+        assert_eq!(
+            reverse_hunks(hunks),
+            Err(ApplyPatchError::IrreversibleDelete(PathBuf::from(
+                "gone.txt"
+            )))
+        );
+    }
+
+    #[test]
+    fn test_dry_run_reports_hunks_without_writing() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("multi.txt");
+        fs::write(&path, "foo\nbar\nbaz\nqux\n").unwrap();
+        let patch = wrap_patch(&format!(
+            r#"*** Update File: {}
+@@
+ foo
+-bar
++BAR
+@@
+ baz
+-missing
++QUX"#,
+            path.display()
+        ));
+        let dry_run = ApplyPatchOptions {
+            dry_run: true,
+            ..Default::default()
+        };
+        let mut stdout = Vec::new();
+        let mut stderr = Vec::new();
+        let result = apply_patch_with_options(&patch, dry_run, &mut stdout, &mut stderr);
+
+        assert!(matches!(
+            result,
+            Err(ApplyPatchError::ComputeReplacements(_))
+        ));
+        assert_eq!(
+            String::from_utf8(stdout).unwrap(),
+            format!(
+                "Dry run. Checked the following files:\nM {}\n  hunk 1/2 applies\n  hunk 2/2 does not apply\n",
+                path.display()
+            )
+        );
+        assert!(
+            String::from_utf8(stderr)
+                .unwrap()
+                .starts_with("Failed to find expected lines")
+        );
+        assert_eq!(fs::read_to_string(&path).unwrap(), "foo\nbar\nbaz\nqux\n");
+    }
+
+    #[test]
+    fn test_fuzz_ignores_drifted_context() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("fuzz.txt");
+        fs::write(&path, "fn one() {\n    keep();\n    old();\n}\n").unwrap();
+        let patch = wrap_patch(&format!(
+            r#"*** Update File: {}
+@@
+ fn renamed() {{
+     keep();
+-    old();
++    new();
+ }}"#,
+            path.display()
+        ));
+        let mut stdout = Vec::new();
+        let mut stderr = Vec::new();
+
+        let strict = ApplyPatchOptions {
+            fuzz: 0,
+            ..Default::default()
+        };
+        let result = apply_patch_with_options(&patch, strict, &mut stdout, &mut stderr);
+        assert!(matches!(result, Err(ApplyPatchError::IoError(_))));
+        assert_eq!(
+            fs::read_to_string(&path).unwrap(),
+            "fn one() {\n    keep();\n    old();\n}\n"
+        );
+
+        apply_patch(&patch, &mut stdout, &mut stderr).unwrap();
+        assert_eq!(
+            fs::read_to_string(&path).unwrap(),
+            "fn one() {\n    keep();\n    new();\n}\n"
+        );
+    }
+
+    #[test]
+    fn test_binary_files_are_refused() {
+        let dir = tempdir().unwrap();
+        let image = dir.path().join("logo.png");
+        let data = dir.path().join("data.txt");
+        fs::write(&data, b"head\0tail\n").unwrap();
+        let mut stdout = Vec::new();
+        let mut stderr = Vec::new();
+
+        let add = wrap_patch(&format!(
+            "*** Add File: {}\n+not really a png",
+            image.display()
+        ));
+        assert!(apply_patch(&add, &mut stdout, &mut stderr).is_err());
+        assert!(
+            String::from_utf8(stderr.clone())
+                .unwrap()
+                .contains("is a binary file")
+        );
+        assert!(!image.exists());
+
+        let update = wrap_patch(&format!(
+            "*** Update File: {}\n@@\n-head\n+HEAD",
+            data.display()
+        ));
+        assert!(apply_patch(&update, &mut stdout, &mut stderr).is_err());
+        assert_eq!(fs::read(&data).unwrap(), b"head\0tail\n");
+
+        let delete = wrap_patch(&format!("*** Delete File: {}", data.display()));
+        apply_patch(&delete, &mut stdout, &mut stderr).unwrap();
+        assert!(!data.exists());
+    }
+
+    #[test]
+    fn test_max_file_bytes_limits_updates_and_adds() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("big.txt");
+        fs::write(&path, "0123456789\n").unwrap();
+        let patch = wrap_patch(&format!(
+            "*** Update File: {}\n@@\n-0123456789\n+x",
+            path.display()
+        ));
+        let options = ApplyPatchOptions {
+            max_file_bytes: 4,
+            ..Default::default()
+        };
+        let mut stdout = Vec::new();
+        let mut stderr = Vec::new();
+
+        let result = apply_patch_with_options(&patch, options, &mut stdout, &mut stderr);
+        assert!(result.is_err());
+        assert!(
+            String::from_utf8(stderr)
+                .unwrap()
+                .contains("over apply_patch's 4-byte limit")
+        );
+        assert_eq!(fs::read_to_string(&path).unwrap(), "0123456789\n");
+
+        let added = dir.path().join("added.txt");
+        let add = wrap_patch(&format!("*** Add File: {}\n+too long", added.display()));
+        let check = ApplyPatchOptions {
+            dry_run: true,
+            ..options
+        };
+        let mut stdout = Vec::new();
+        let mut stderr = Vec::new();
+        assert!(apply_patch_with_options(&add, check, &mut stdout, &mut stderr).is_err());
+        assert_eq!(
+            String::from_utf8(stdout).unwrap(),
+            format!(
+                "Dry run. Checked the following files:\nA {}\n  {} is 9 bytes, over apply_patch's 4-byte limit\n",
+                added.display(),
+                added.display()
+            )
+        );
+    }
+
+    #[test]
+    fn test_three_way_merge_with_base_snapshot() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("merge.txt");
+        let base = "1\n2\n3\n4\n5\n";
+        fs::write(&path, "1\n2\nthree\n4\n5\n").unwrap();
+        let patch = wrap_patch(&format!(
+            r#"*** Update File: {}
+@@
+ 1
+-2
++TWO
+ 3
+-4
++FOUR
+ 5"#,
+            path.display()
+        ));
+        let argv = vec!["apply_patch".to_string(), patch];
+
+        assert!(matches!(
+            maybe_parse_apply_patch_verified(&argv, dir.path()),
+            MaybeApplyPatchVerified::CorrectnessError(_)
+        ));
+
+        let MaybeApplyPatchVerified::Body(action) =
+            maybe_parse_apply_patch_verified_with_base(&argv, dir.path(), |_| {
+                Some(base.to_string())
+            })
+        else {
+            panic!("expected the patch to merge with the base snapshot");
+        };
+        let merged = "1\nTWO\nthree\nFOUR\n5\n";
+        match action.changes().get(&path) {
+            Some(ApplyPatchFileChange::Update { new_content, .. }) => {
+                assert_eq!(new_content, merged);
+            }
+            other => panic!("expected an update, got {other:?}"),
+        }
+
+        let mut stdout = Vec::new();
+        let mut stderr = Vec::new();
+        apply_patch(&action.patch, &mut stdout, &mut stderr).unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), merged);
+    }
+
+    #[test]
+    fn test_retain_files_drops_unapproved_changes() {
+        let dir = tempdir().unwrap();
+        let src = dir.path().join("src.txt");
+        let ci = dir.path().join("ci.yml");
+        fs::write(&src, "old\n").unwrap();
+        fs::write(&ci, "steps: []\n").unwrap();
+        let patch = wrap_patch(&format!(
+            r#"*** Update File: {}
+@@
+-old
++new
+*** Update File: {}
+@@
+-steps: []
++steps: [deploy]"#,
+            src.display(),
+            ci.display()
+        ));
+        let argv = vec!["apply_patch".to_string(), patch];
+        let MaybeApplyPatchVerified::Body(mut action) =
+            maybe_parse_apply_patch_verified(&argv, dir.path())
+        else {
+            panic!("expected a verified patch");
+        };
+
+        assert_eq!(
+            action.retain_files(std::slice::from_ref(&src)),
+            vec![ci.clone()]
+        );
+        assert_eq!(action.changes().keys().collect::<Vec<_>>(), vec![&src]);
+
+        let mut stdout = Vec::new();
+        let mut stderr = Vec::new();
+        apply_patch(&action.patch, &mut stdout, &mut stderr).unwrap();
+        assert_eq!(fs::read_to_string(&src).unwrap(), "new\n");
+        assert_eq!(fs::read_to_string(&ci).unwrap(), "steps: []\n");
+    }
+
+    #[test]
+    fn test_unified_diff_is_converted_and_applied() {
+        let dir = tempdir().unwrap();
+        let old = dir.path().join("old.txt");
+        let new = dir.path().join("new.txt");
+        fs::write(&old, "one\ntwo\nthree\n").unwrap();
+        let diff = format!(
+            r#"diff --git a/old.txt b/new.txt
+similarity index 80%
+rename from {}
+rename to {}
+index 1111111..2222222 100644
+--- a/old.txt
++++ b/new.txt
+@@ -1,3 +1,3 @@
+ one
+-two
++TWO
+ three
+"#,
+            old.display(),
+            new.display()
+        );
+        let argv = vec!["apply_patch".to_string(), diff];
+
+        let MaybeApplyPatchVerified::Body(action) =
+            maybe_parse_apply_patch_verified(&argv, dir.path())
+        else {
+            panic!("expected the unified diff to be accepted");
+        };
+        match action.changes().get(&old) {
+            Some(ApplyPatchFileChange::Update {
+                move_path,
+                new_content,
+                ..
+            }) => {
+                assert_eq!(move_path.as_ref(), Some(&new));
+                assert_eq!(new_content, "one\nTWO\nthree\n");
+            }
+            other => panic!("expected an update, got {other:?}"),
+        }
+
+        let mut stdout = Vec::new();
+        let mut stderr = Vec::new();
+        apply_patch(&action.patch, &mut stdout, &mut stderr).unwrap();
+        assert!(!old.exists());
+        assert_eq!(fs::read_to_string(&new).unwrap(), "one\nTWO\nthree\n");
+    }
+
     #[test]
     fn test_apply_patch_fails_on_write_error() {
         let dir = tempdir().unwrap();
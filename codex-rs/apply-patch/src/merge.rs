@@ -0,0 +1,125 @@
+//! Line-based three-way merge, used to apply an update whose context no
+//! longer matches the file by applying it to an earlier snapshot of the file
+//! instead and merging the result with the file's current contents.
+
+use similar::Algorithm;
+use similar::DiffOp;
+use similar::capture_diff_slices;
+
+/// A replacement of `base[start..end]` with `lines`.
+#[derive(Debug, PartialEq, Eq)]
+struct Edit<'a> {
+    start: usize,
+    end: usize,
+    lines: Vec<&'a str>,
+}
+
+/// Merges the changes from `base` to `ours` with the changes from `base` to
+/// `theirs`. Returns `None` when both sides change the same region of `base`
+/// (or insert at the same place) in different ways.
+pub fn merge_three_way(base: &str, ours: &str, theirs: &str) -> Option<String> {
+    let base_lines: Vec<&str> = base.split_inclusive('\n').collect();
+    let ours_lines: Vec<&str> = ours.split_inclusive('\n').collect();
+    let theirs_lines: Vec<&str> = theirs.split_inclusive('\n').collect();
+    let ours_edits = edits(&base_lines, &ours_lines);
+    let theirs_edits = edits(&base_lines, &theirs_lines);
+
+    let mut merged: Vec<&str> = Vec::with_capacity(base_lines.len());
+    let mut cursor = 0;
+    let mut ours_iter = ours_edits.iter().peekable();
+    let mut theirs_iter = theirs_edits.iter().peekable();
+    loop {
+        let edit = match (ours_iter.peek(), theirs_iter.peek()) {
+            (None, None) => break,
+            (Some(_), None) => ours_iter.next(),
+            (None, Some(_)) => theirs_iter.next(),
+            (Some(ours), Some(theirs)) => {
+                if ours == theirs {
+                    theirs_iter.next();
+                    ours_iter.next()
+                } else if overlaps(ours, theirs) {
+                    return None;
+                } else if (ours.start, ours.end) <= (theirs.start, theirs.end) {
+                    ours_iter.next()
+                } else {
+                    theirs_iter.next()
+                }
+            }
+        }?;
+        merged.extend_from_slice(&base_lines[cursor..edit.start]);
+        merged.extend_from_slice(&edit.lines);
+        cursor = edit.end;
+    }
+    merged.extend_from_slice(&base_lines[cursor..]);
+    Some(merged.concat())
+}
+
+/// The changes that turn `base` into `other`, with adjacent deletions and
+/// insertions combined into a single edit.
+fn edits<'a>(base: &[&'a str], other: &[&'a str]) -> Vec<Edit<'a>> {
+    let mut edits: Vec<Edit<'a>> = Vec::new();
+    for op in capture_diff_slices(Algorithm::Myers, base, other) {
+        if let DiffOp::Equal { .. } = op {
+            continue;
+        }
+        let (_, old_range, new_range) = op.as_tag_tuple();
+        match edits.last_mut() {
+            Some(last) if last.end == old_range.start => {
+                last.end = old_range.end;
+                last.lines.extend_from_slice(&other[new_range]);
+            }
+            _ => edits.push(Edit {
+                start: old_range.start,
+                end: old_range.end,
+                lines: other[new_range].to_vec(),
+            }),
+        }
+    }
+    edits
+}
+
+/// Whether two edits touch the same lines of the base, or insert at the
+/// same position, so that applying both is ambiguous.
+fn overlaps(a: &Edit<'_>, b: &Edit<'_>) -> bool {
+    if a.start == b.start {
+        return true;
+    }
+    let inserts_inside = |insert: &Edit<'_>, range: &Edit<'_>| {
+        insert.start == insert.end && range.start < insert.start && insert.start < range.end
+    };
+    a.start.max(b.start) < a.end.min(b.end) || inserts_inside(a, b) || inserts_inside(b, a)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::merge_three_way;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn merges_changes_to_different_lines() {
+        let base = "a\nb\nc\nd\ne\n";
+        let ours = "a\nB\nc\nd\ne\n";
+        let theirs = "a\nb\nc\nd\nE\nf\n";
+        assert_eq!(
+            merge_three_way(base, ours, theirs),
+            Some("a\nB\nc\nd\nE\nf\n".to_string())
+        );
+    }
+
+    #[test]
+    fn identical_changes_are_applied_once() {
+        let base = "a\nb\nc\n";
+        let changed = "a\nx\nc\n";
+        assert_eq!(
+            merge_three_way(base, changed, changed),
+            Some(changed.to_string())
+        );
+    }
+
+    #[test]
+    fn conflicting_changes_do_not_merge() {
+        let base = "a\nb\nc\n";
+        assert_eq!(merge_three_way(base, "a\nx\nc\n", "a\ny\nc\n"), None);
+        assert_eq!(merge_three_way(base, "a\nx\nb\nc\n", "a\ny\nb\nc\n"), None);
+    }
+}
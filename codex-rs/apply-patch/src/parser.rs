@@ -23,6 +23,7 @@
 //! The parser below is a little more lenient than the explicit spec and allows for
 //! leading/trailing whitespace around patch markers.
 use crate::ApplyPatchArgs;
+use crate::unified_diff;
 use std::path::Path;
 use std::path::PathBuf;
 
@@ -109,7 +110,11 @@ pub fn parse_patch(patch: &str) -> Result<ApplyPatchArgs, ParseError> {
     } else {
         ParseMode::Lenient
     };
-    parse_patch_text(patch, mode)
+    match parse_patch_text(patch, mode) {
+        // Models often write a standard unified diff instead.
+        Err(_) if unified_diff::is_unified_diff(patch) => unified_diff::parse_unified_diff(patch),
+        result => result,
+    }
 }
 
 enum ParseMode {
@@ -312,7 +317,8 @@ fn parse_one_hunk(lines: &[&str], line_number: usize) -> Result<(Hunk, usize), P
             remaining_lines = &remaining_lines[chunk_lines..]
         }
 
-        if chunks.is_empty() {
+        // A pure move needs no chunks.
+        if chunks.is_empty() && move_path.is_none() {
             return Err(InvalidHunkError {
                 message: format!("Update file hunk for path '{path}' is empty"),
                 line_number,
@@ -1,6 +1,10 @@
 use std::io::Read;
 use std::io::Write;
 
+use crate::ApplyPatchOptions;
+
+const USAGE: &str = "Usage: apply_patch [--dry-run] [--reverse] [--fuzz N] [--max-file-size BYTES] ['PATCH' | -]\n       echo 'PATCH' | apply_patch [--dry-run] [--reverse] [--fuzz N] [--max-file-size BYTES]";
+
 pub fn main() -> ! {
     let exit_code = run_main();
     std::process::exit(exit_code);
@@ -9,25 +13,73 @@ pub fn main() -> ! {
 /// We would prefer to return `std::process::ExitCode`, but its `exit_process()`
 /// method is still a nightly API and we want main() to return !.
 pub fn run_main() -> i32 {
-    // Expect either one argument (the full apply_patch payload) or read it from stdin.
+    // Expect at most one PATCH argument; without one (or with `-`) the patch
+    // is read from stdin.
     let mut args = std::env::args_os();
     let _argv0 = args.next();
 
-    let patch_arg = match args.next() {
-        Some(arg) => match arg.into_string() {
-            Ok(s) => s,
-            Err(_) => {
-                eprintln!("Error: apply_patch requires a UTF-8 PATCH argument.");
-                return 1;
+    let mut options = ApplyPatchOptions::default();
+    let mut patch_arg: Option<String> = None;
+    let mut only_positional = false;
+    while let Some(arg) = args.next() {
+        let Ok(arg) = arg.into_string() else {
+            eprintln!("Error: apply_patch requires a UTF-8 PATCH argument.");
+            return 1;
+        };
+        match arg.as_str() {
+            "--dry-run" if !only_positional => options.dry_run = true,
+            "--reverse" if !only_positional => options.reverse = true,
+            "--fuzz" if !only_positional => {
+                match args.next().and_then(|value| value.to_str()?.parse().ok()) {
+                    Some(fuzz) => options.fuzz = fuzz,
+                    None => {
+                        eprintln!(
+                            "Error: --fuzz requires a non-negative number of lines.\n{USAGE}"
+                        );
+                        return 2;
+                    }
+                }
+            }
+            "--max-file-size" if !only_positional => {
+                match args.next().and_then(|value| value.to_str()?.parse().ok()) {
+                    Some(max_file_bytes) => options.max_file_bytes = max_file_bytes,
+                    None => {
+                        eprintln!(
+                            "Error: --max-file-size requires a non-negative number of bytes.\n{USAGE}"
+                        );
+                        return 2;
+                    }
+                }
             }
-        },
-        None => {
+            "--" if !only_positional => only_positional = true,
+            "-h" | "--help" if !only_positional => {
+                println!("{USAGE}");
+                return 0;
+            }
+            flag if !only_positional && flag.starts_with("--") => {
+                eprintln!("Error: unknown option `{flag}`.\n{USAGE}");
+                return 2;
+            }
+            _ => {
+                // Refuse extra args to avoid ambiguity.
+                if patch_arg.is_some() {
+                    eprintln!("Error: apply_patch accepts exactly one PATCH argument.");
+                    return 2;
+                }
+                patch_arg = Some(arg);
+            }
+        }
+    }
+
+    let patch_arg = match patch_arg {
+        Some(patch) if patch != "-" => patch,
+        _ => {
             // No argument provided; attempt to read the patch from stdin.
             let mut buf = String::new();
             match std::io::stdin().read_to_string(&mut buf) {
                 Ok(_) => {
                     if buf.is_empty() {
-                        eprintln!("Usage: apply_patch 'PATCH'\n       echo 'PATCH' | apply-patch");
+                        eprintln!("{USAGE}");
                         return 2;
                     }
                     buf
@@ -40,15 +92,9 @@ pub fn run_main() -> i32 {
         }
     };
 
-    // Refuse extra args to avoid ambiguity.
-    if args.next().is_some() {
-        eprintln!("Error: apply_patch accepts exactly one argument.");
-        return 2;
-    }
-
     let mut stdout = std::io::stdout();
     let mut stderr = std::io::stderr();
-    match crate::apply_patch(&patch_arg, &mut stdout, &mut stderr) {
+    match crate::apply_patch_with_options(&patch_arg, options, &mut stdout, &mut stderr) {
         Ok(()) => {
             // Flush to ensure output ordering when used in pipelines.
             let _ = stdout.flush();
@@ -0,0 +1,427 @@
+//! Converts standard unified diffs (`diff -u`, `git diff`) into patch hunks so
+//! that `apply_patch` accepts them alongside its own patch format.
+//!
+//! Hunk line numbers are ignored: like chunks in the native format, each hunk
+//! is located by its context and removed lines, searching forward from the
+//! previous hunk. Mode changes are ignored; copies and binary diffs are
+//! rejected.
+
+use std::path::PathBuf;
+
+use crate::ApplyPatchArgs;
+use crate::parser::Hunk;
+use crate::parser::ParseError;
+use crate::parser::ParseError::*;
+use crate::parser::UpdateFileChunk;
+use crate::patch_from_hunks;
+
+const DIFF_GIT_PREFIX: &str = "diff --git ";
+const OLD_FILE_PREFIX: &str = "--- ";
+const NEW_FILE_PREFIX: &str = "+++ ";
+const DEV_NULL: &str = "/dev/null";
+
+/// Whether `text` starts like a unified diff rather than an `apply_patch`
+/// envelope, optionally wrapped in a heredoc.
+pub(crate) fn is_unified_diff(text: &str) -> bool {
+    let lines = diff_lines(text);
+    match lines.as_slice() {
+        [first, ..] if first.starts_with(DIFF_GIT_PREFIX) => true,
+        [first, second, ..] => {
+            first.starts_with(OLD_FILE_PREFIX) && second.starts_with(NEW_FILE_PREFIX)
+        }
+        _ => false,
+    }
+}
+
+/// Parses a unified diff into hunks. The returned `patch` is the diff
+/// rewritten in the `apply_patch` format, so that it can be applied with
+/// `apply_patch` as is.
+pub(crate) fn parse_unified_diff(text: &str) -> Result<ApplyPatchArgs, ParseError> {
+    let lines = diff_lines(text);
+    let mut hunks = Vec::new();
+    let mut index = 0;
+    while index < lines.len() {
+        if lines[index].trim().is_empty() {
+            index += 1;
+            continue;
+        }
+        let (hunk, consumed) = parse_file_diff(&lines[index..], index + 1)?;
+        hunks.extend(hunk);
+        index += consumed;
+    }
+    if hunks.is_empty() {
+        return Err(InvalidPatchError(
+            "the diff does not change the contents of any file".to_string(),
+        ));
+    }
+    Ok(ApplyPatchArgs {
+        patch: patch_from_hunks(&hunks),
+        hunks,
+        workdir: None,
+    })
+}
+
+/// The lines of `text` without leading blank lines or a surrounding heredoc.
+fn diff_lines(text: &str) -> Vec<&str> {
+    let lines: Vec<&str> = text.trim_start().lines().collect();
+    match lines.as_slice() {
+        [first, inner @ .., last]
+            if matches!(*first, "<<EOF" | "<<'EOF'" | "<<\"EOF\"") && last.ends_with("EOF") =>
+        {
+            inner.to_vec()
+        }
+        _ => lines,
+    }
+}
+
+/// Parses the diff of a single file, starting at its `diff --git` or `---`
+/// line. Returns the resulting hunk, if the diff changes anything but the
+/// file mode, and the number of lines consumed.
+fn parse_file_diff(
+    lines: &[&str],
+    line_number: usize,
+) -> Result<(Option<Hunk>, usize), ParseError> {
+    let mut index = 0;
+    let mut git_paths = None;
+    if let Some(header) = lines[0].strip_prefix(DIFF_GIT_PREFIX) {
+        git_paths = split_git_header(header);
+        index += 1;
+    }
+
+    let mut old_path: Option<String> = None;
+    let mut new_path: Option<String> = None;
+    let mut rename_from: Option<String> = None;
+    let mut rename_to: Option<String> = None;
+    let mut is_new = false;
+    let mut is_deleted = false;
+    while let Some(line) = lines.get(index) {
+        // Without a `diff --git` header the file header ends at `+++`.
+        if line.starts_with(DIFF_GIT_PREFIX)
+            || line.starts_with("@@")
+            || (git_paths.is_none() && new_path.is_some())
+        {
+            break;
+        }
+        if let Some(path) = line.strip_prefix(OLD_FILE_PREFIX) {
+            old_path = Some(path.to_string());
+        } else if let Some(path) = line.strip_prefix(NEW_FILE_PREFIX) {
+            new_path = Some(path.to_string());
+        } else if let Some(path) = line.strip_prefix("rename from ") {
+            rename_from = Some(path.to_string());
+        } else if let Some(path) = line.strip_prefix("rename to ") {
+            rename_to = Some(path.to_string());
+        } else if line.starts_with("new file mode") {
+            is_new = true;
+        } else if line.starts_with("deleted file mode") {
+            is_deleted = true;
+        } else if line.starts_with("copy from ") || line.starts_with("copy to ") {
+            return Err(InvalidHunkError {
+                message: "copied files are not supported; add the new file instead".to_string(),
+                line_number: line_number + index,
+            });
+        } else if line.starts_with("GIT binary patch") || line.starts_with("Binary files ") {
+            return Err(InvalidHunkError {
+                message: "binary diffs are not supported".to_string(),
+                line_number: line_number + index,
+            });
+        }
+        // `index`, `similarity index`, `old mode`, `new mode` and other
+        // extended header lines carry nothing `apply_patch` can use.
+        index += 1;
+    }
+
+    let mut chunks = Vec::new();
+    while lines.get(index).is_some_and(|line| line.starts_with("@@")) {
+        let (chunk, consumed) = parse_diff_hunk(&lines[index..], line_number + index)?;
+        chunks.push(chunk);
+        index += consumed;
+    }
+
+    let strip_git_prefixes = git_paths.is_some();
+    // `/dev/null` on either side marks an added or deleted file.
+    let is_new = is_new || old_path.as_deref().is_some_and(is_dev_null);
+    let is_deleted = is_deleted || new_path.as_deref().is_some_and(is_dev_null);
+    let old_path = old_path
+        .as_deref()
+        .and_then(|path| diff_path(path, "a/", strip_git_prefixes));
+    let new_path = new_path
+        .as_deref()
+        .and_then(|path| diff_path(path, "b/", strip_git_prefixes));
+    let (git_old, git_new) = git_paths.unzip();
+    let source = rename_from
+        .as_deref()
+        .map(|path| PathBuf::from(unquote(path)))
+        .or(old_path)
+        .or(git_old)
+        .or_else(|| new_path.clone());
+    let dest = rename_to
+        .as_deref()
+        .map(|path| PathBuf::from(unquote(path)))
+        .or(new_path)
+        .or(git_new)
+        .or_else(|| source.clone());
+    let (Some(source), Some(dest)) = (source, dest) else {
+        return Err(InvalidHunkError {
+            message: "could not determine which file the diff changes".to_string(),
+            line_number,
+        });
+    };
+
+    let hunk = if is_new {
+        let mut contents = String::new();
+        for line in chunks.iter().flat_map(|chunk| &chunk.new_lines) {
+            contents.push_str(line);
+            contents.push('\n');
+        }
+        Some(Hunk::AddFile {
+            path: dest,
+            contents,
+        })
+    } else if is_deleted {
+        Some(Hunk::DeleteFile { path: source })
+    } else if chunks.is_empty() && source == dest {
+        // Only the mode changed.
+        None
+    } else {
+        Some(Hunk::UpdateFile {
+            move_path: (source != dest).then_some(dest),
+            path: source,
+            chunks,
+        })
+    };
+    Ok((hunk, index))
+}
+
+/// Splits the paths of a `diff --git a/<old> b/<new>` header. Paths that
+/// contain ` b/` are ambiguous; the `---`/`+++` or `rename` lines, which take
+/// precedence, disambiguate them.
+fn split_git_header(header: &str) -> Option<(PathBuf, PathBuf)> {
+    let (old, new) = header.split_once(" b/")?;
+    let old = unquote(old).strip_prefix("a/")?.to_string();
+    Some((PathBuf::from(old), PathBuf::from(unquote(new))))
+}
+
+fn is_dev_null(path: &str) -> bool {
+    strip_timestamp(path) == DEV_NULL
+}
+
+/// Drops the timestamp `diff -u` appends to the paths in its file header.
+fn strip_timestamp(path: &str) -> &str {
+    unquote(path.split('\t').next().unwrap_or(path).trim_end())
+}
+
+/// The path named by a `---` or `+++` line, or `None` for `/dev/null`. Git's
+/// `a/` or `b/` prefix is dropped when `strip_git_prefix` is set.
+fn diff_path(path: &str, git_prefix: &str, strip_git_prefix: bool) -> Option<PathBuf> {
+    let path = strip_timestamp(path);
+    if path == DEV_NULL {
+        return None;
+    }
+    let path = if strip_git_prefix {
+        path.strip_prefix(git_prefix).unwrap_or(path)
+    } else {
+        path
+    };
+    Some(PathBuf::from(path))
+}
+
+fn unquote(path: &str) -> &str {
+    path.strip_prefix('"')
+        .and_then(|path| path.strip_suffix('"'))
+        .unwrap_or(path)
+}
+
+/// Parses one `@@ -a,b +c,d @@` hunk into a chunk. The line counts in the
+/// header, when present, decide where the hunk ends; otherwise it ends at the
+/// first line that is not part of a hunk.
+fn parse_diff_hunk(
+    lines: &[&str],
+    line_number: usize,
+) -> Result<(UpdateFileChunk, usize), ParseError> {
+    let mut remaining = hunk_line_counts(lines[0]);
+    let mut chunk = UpdateFileChunk {
+        change_context: None,
+        old_lines: Vec::new(),
+        new_lines: Vec::new(),
+        is_end_of_file: false,
+    };
+    let mut index = 1;
+    while let Some(line) = lines.get(index) {
+        if remaining.is_some_and(|(old, new)| old == 0 && new == 0) {
+            break;
+        }
+        let (in_old, in_new) = match line.chars().next() {
+            // Some editors strip the trailing space of empty context lines.
+            None => (true, true),
+            Some(' ') => (true, true),
+            Some('-') if !line.starts_with(OLD_FILE_PREFIX) || remaining.is_some() => (true, false),
+            Some('+') => (false, true),
+            Some('\\') => {
+                // "\ No newline at end of file"
+                index += 1;
+                continue;
+            }
+            _ => break,
+        };
+        let text = line.get(1..).unwrap_or_default();
+        if in_old {
+            chunk.old_lines.push(text.to_string());
+        }
+        if in_new {
+            chunk.new_lines.push(text.to_string());
+        }
+        if let Some((old, new)) = remaining.as_mut() {
+            *old = old.saturating_sub(usize::from(in_old));
+            *new = new.saturating_sub(usize::from(in_new));
+        }
+        index += 1;
+    }
+
+    if remaining.is_none() {
+        // Without counts, trailing blank lines are more likely separators
+        // than empty context lines.
+        while chunk.old_lines.last().is_some_and(String::is_empty)
+            && chunk.new_lines.last().is_some_and(String::is_empty)
+        {
+            chunk.old_lines.pop();
+            chunk.new_lines.pop();
+        }
+    }
+    if chunk.old_lines.is_empty() && chunk.new_lines.is_empty() {
+        return Err(InvalidHunkError {
+            message: "diff hunk does not contain any lines".to_string(),
+            line_number,
+        });
+    }
+    Ok((chunk, index))
+}
+
+/// The old and new line counts of a `@@ -a,b +c,d @@` header. A missing
+/// count means one line.
+fn hunk_line_counts(header: &str) -> Option<(usize, usize)> {
+    let mut ranges = header.strip_prefix("@@ ")?.split_whitespace();
+    let old = ranges.next()?.strip_prefix('-')?;
+    let new = ranges.next()?.strip_prefix('+')?;
+    let count = |range: &str| match range.split_once(',') {
+        Some((_, count)) => count.parse().ok(),
+        None => range.parse::<usize>().ok().map(|_| 1),
+    };
+    Some((count(old)?, count(new)?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn chunk(old: &[&str], new: &[&str]) -> UpdateFileChunk {
+        UpdateFileChunk {
+            change_context: None,
+            old_lines: old.iter().map(ToString::to_string).collect(),
+            new_lines: new.iter().map(ToString::to_string).collect(),
+            is_end_of_file: false,
+        }
+    }
+
+    #[test]
+    fn parses_git_diff_with_update_add_delete_and_rename() {
+        let diff = "diff --git a/src/lib.rs b/src/lib.rs
+index 1111111..2222222 100644
+--- a/src/lib.rs
++++ b/src/lib.rs
+@@ -1,3 +1,3 @@ fn main() {
+ fn one() {}
+-fn two() {}
++fn deux() {}
+ fn three() {}
+diff --git a/new.txt b/new.txt
+new file mode 100644
+index 0000000..3333333
+--- /dev/null
++++ b/new.txt
+@@ -0,0 +1,2 @@
++hello
++world
+diff --git a/gone.txt b/gone.txt
+deleted file mode 100644
+index 4444444..0000000
+--- a/gone.txt
++++ /dev/null
+@@ -1 +0,0 @@
+-bye
+diff --git a/old name.txt b/new name.txt
+similarity index 100%
+rename from old name.txt
+rename to new name.txt
+diff --git a/script.sh b/script.sh
+old mode 100644
+new mode 100755
+";
+        assert!(is_unified_diff(diff));
+        let args = parse_unified_diff(diff).unwrap();
+        assert_eq!(
+            args.hunks,
+            vec![
+                Hunk::UpdateFile {
+                    path: PathBuf::from("src/lib.rs"),
+                    move_path: None,
+                    chunks: vec![chunk(
+                        &["fn one() {}", "fn two() {}", "fn three() {}"],
+                        &["fn one() {}", "fn deux() {}", "fn three() {}"],
+                    )],
+                },
+                Hunk::AddFile {
+                    path: PathBuf::from("new.txt"),
+                    contents: "hello\nworld\n".to_string(),
+                },
+                Hunk::DeleteFile {
+                    path: PathBuf::from("gone.txt"),
+                },
+                Hunk::UpdateFile {
+                    path: PathBuf::from("old name.txt"),
+                    move_path: Some(PathBuf::from("new name.txt")),
+                    chunks: Vec::new(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn parses_plain_diff_u_output() {
+        let diff = "--- notes.txt\t2024-01-01 00:00:00
++++ notes.txt\t2024-01-02 00:00:00
+@@ -1,2 +1,2 @@
+-first
++FIRST
+
+@@ -10,2 +10,2 @@
+ tenth
+-eleventh
++ELEVENTH
+";
+        let args = parse_unified_diff(diff).unwrap();
+        assert_eq!(
+            args.hunks,
+            vec![Hunk::UpdateFile {
+                path: PathBuf::from("notes.txt"),
+                move_path: None,
+                chunks: vec![
+                    chunk(&["first", ""], &["FIRST", ""]),
+                    chunk(&["tenth", "eleventh"], &["tenth", "ELEVENTH"]),
+                ],
+            }]
+        );
+    }
+
+    #[test]
+    fn rejects_binary_diffs() {
+        let diff = "diff --git a/logo.png b/logo.png
+index 1111111..2222222 100644
+Binary files a/logo.png and b/logo.png differ
+";
+        assert!(matches!(
+            parse_unified_diff(diff),
+            Err(InvalidHunkError { .. })
+        ));
+    }
+}
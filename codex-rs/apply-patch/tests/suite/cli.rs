@@ -88,3 +88,81 @@ fn test_apply_patch_cli_stdin_add_and_update() -> anyhow::Result<()> {
 
     Ok(())
 }
+
+#[test]
+fn test_apply_patch_cli_dry_run_and_reverse() -> anyhow::Result<()> {
+    let tmp = tempdir()?;
+    let file = "cli_test_flags.txt";
+    let absolute_path = tmp.path().join(file);
+    fs::write(&absolute_path, "hello\n")?;
+    let patch = format!(
+        r#"*** Begin Patch
+*** Update File: {file}
+@@
+-hello
++world
+*** End Patch"#
+    );
+
+    // 1) A dry run reports the hunk without touching the file.
+    Command::cargo_bin("apply_patch")
+        .expect("should find apply_patch binary")
+        .args(["--dry-run", &patch])
+        .current_dir(tmp.path())
+        .assert()
+        .success()
+        .stdout(format!(
+            "Dry run. Checked the following files:\nM {file}\n  hunk 1/1 applies\n"
+        ));
+    assert_eq!(fs::read_to_string(&absolute_path)?, "hello\n");
+
+    // 2) Apply it, reading the patch from stdin via `-`.
+    let mut cmd =
+        assert_cmd::Command::cargo_bin("apply_patch").expect("should find apply_patch binary");
+    cmd.current_dir(tmp.path());
+    cmd.arg("-")
+        .write_stdin(patch.clone())
+        .assert()
+        .success()
+        .stdout(format!("Success. Updated the following files:\nM {file}\n"));
+    assert_eq!(fs::read_to_string(&absolute_path)?, "world\n");
+
+    // 3) Applying the same patch again no longer works, but reversing it does.
+    Command::cargo_bin("apply_patch")
+        .expect("should find apply_patch binary")
+        .args(["--dry-run", &patch])
+        .current_dir(tmp.path())
+        .assert()
+        .failure()
+        .stdout(format!(
+            "Dry run. Checked the following files:\nM {file}\n  hunk 1/1 does not apply\n"
+        ));
+    Command::cargo_bin("apply_patch")
+        .expect("should find apply_patch binary")
+        .args(["--reverse", &patch])
+        .current_dir(tmp.path())
+        .assert()
+        .success()
+        .stdout(format!("Success. Updated the following files:\nM {file}\n"));
+    assert_eq!(fs::read_to_string(&absolute_path)?, "hello\n");
+
+    Ok(())
+}
+
+#[test]
+fn test_apply_patch_cli_rejects_unknown_option() {
+    Command::cargo_bin("apply_patch")
+        .expect("should find apply_patch binary")
+        .arg("--check")
+        .assert()
+        .code(2);
+}
+
+#[test]
+fn test_apply_patch_cli_requires_fuzz_value() {
+    Command::cargo_bin("apply_patch")
+        .expect("should find apply_patch binary")
+        .args(["--fuzz", "many"])
+        .assert()
+        .code(2);
+}
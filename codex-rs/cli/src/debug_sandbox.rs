@@ -95,6 +95,8 @@ async fn run_command_under_sandbox(
                 sandbox_policy_cwd.as_path(),
                 stdio_policy,
                 env,
+                config.resource_limits,
+                &config.sandbox_extras,
             )
             .await?
         }
@@ -111,6 +113,8 @@ async fn run_command_under_sandbox(
                 sandbox_policy_cwd.as_path(),
                 stdio_policy,
                 env,
+                config.resource_limits,
+                &config.sandbox_extras,
             )
             .await?
         }
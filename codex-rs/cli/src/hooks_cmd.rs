@@ -0,0 +1,205 @@
+use std::path::PathBuf;
+use std::process::Stdio;
+use std::time::Duration;
+use std::time::Instant;
+
+use anyhow::Context;
+use anyhow::Result;
+use anyhow::anyhow;
+use anyhow::bail;
+use codex_common::CliConfigOverrides;
+use codex_core::config::Config;
+use codex_core::config::ConfigOverrides;
+use codex_core::config::HOOK_DRY_RUN_ENV_VAR;
+use codex_core::protocol::HookKind;
+use codex_core::wasm_hooks::run_hook_process;
+use serde_json::Value as JsonValue;
+
+/// Develop and debug hooks without starting a session.
+///
+/// Subcommands:
+/// - `test` — run the configured hooks of one type against a payload file
+#[derive(Debug, clap::Parser)]
+pub struct HooksCli {
+    #[clap(flatten)]
+    pub config_overrides: CliConfigOverrides,
+
+    #[command(subcommand)]
+    pub cmd: HooksSubcommand,
+}
+
+#[derive(Debug, clap::Subcommand)]
+pub enum HooksSubcommand {
+    /// Run the hooks configured for a type with a payload read from a file.
+    Test(TestArgs),
+}
+
+#[derive(Debug, clap::Parser)]
+pub struct TestArgs {
+    /// Hook type to run, e.g. `pre-tool-use` or `stop`.
+    #[arg(value_enum, value_name = "TYPE")]
+    pub hook_type: HookTypeArg,
+
+    /// JSON file passed to the hooks as their payload argument.
+    #[arg(long, value_name = "FILE")]
+    pub payload: PathBuf,
+
+    /// Tool id used to select tool hooks; defaults to the payload's `tool`.
+    #[arg(long)]
+    pub tool: Option<String>,
+
+    /// Run the hooks with `CODEX_HOOK_DRY_RUN=1` in their environment.
+    #[arg(long, default_value_t = false)]
+    pub dry_run: bool,
+}
+
+#[derive(Debug, Clone, Copy, clap::ValueEnum)]
+pub enum HookTypeArg {
+    #[value(alias = "pre_tool_use")]
+    PreToolUse,
+    #[value(alias = "post_tool_use")]
+    PostToolUse,
+    #[value(alias = "user_prompt_submit")]
+    UserPromptSubmit,
+    Stop,
+    #[value(alias = "session_start")]
+    SessionStart,
+    #[value(alias = "pre_model_call")]
+    PreModelCall,
+    #[value(alias = "turn_complete")]
+    TurnComplete,
+    #[value(alias = "post_turn_diff")]
+    PostTurnDiff,
+}
+
+impl From<HookTypeArg> for HookKind {
+    fn from(arg: HookTypeArg) -> Self {
+        match arg {
+            HookTypeArg::PreToolUse => HookKind::PreToolUse,
+            HookTypeArg::PostToolUse => HookKind::PostToolUse,
+            HookTypeArg::UserPromptSubmit => HookKind::UserPromptSubmit,
+            HookTypeArg::Stop => HookKind::Stop,
+            HookTypeArg::SessionStart => HookKind::SessionStart,
+            HookTypeArg::PreModelCall => HookKind::PreModelCall,
+            HookTypeArg::TurnComplete => HookKind::TurnComplete,
+            HookTypeArg::PostTurnDiff => HookKind::PostTurnDiff,
+        }
+    }
+}
+
+/// Payload fields that Codex also exposes to hooks as environment variables.
+const PAYLOAD_ENV: &[(&str, &str)] = &[
+    ("tool", "TOOL_ID"),
+    ("sub_id", "SUB_ID"),
+    ("call_id", "CALL_ID"),
+    ("git_root", "GIT_ROOT"),
+    ("session_id", "SESSION_ID"),
+];
+
+impl HooksCli {
+    pub async fn run(self) -> Result<()> {
+        let HooksCli {
+            config_overrides,
+            cmd,
+        } = self;
+
+        match cmd {
+            HooksSubcommand::Test(args) => run_test(&config_overrides, args).await,
+        }
+    }
+}
+
+async fn run_test(config_overrides: &CliConfigOverrides, args: TestArgs) -> Result<()> {
+    let overrides = config_overrides.parse_overrides().map_err(|e| anyhow!(e))?;
+    let config = Config::load_with_cli_overrides(overrides, ConfigOverrides::default())
+        .context("failed to load configuration")?;
+
+    let TestArgs {
+        hook_type,
+        payload: payload_path,
+        tool,
+        dry_run,
+    } = args;
+    let kind = HookKind::from(hook_type);
+
+    let contents = std::fs::read_to_string(&payload_path)
+        .with_context(|| format!("failed to read payload from {}", payload_path.display()))?;
+    let payload: JsonValue = serde_json::from_str(&contents)
+        .with_context(|| format!("payload in {} is not valid JSON", payload_path.display()))?;
+    let tool = tool.or_else(|| payload["tool"].as_str().map(str::to_string));
+
+    let hooks = config.hooks.commands_for(kind, tool.as_deref());
+    if hooks.is_empty() {
+        println!("No {kind} hooks configured.");
+        return Ok(());
+    }
+
+    let json_arg = serde_json::to_string(&payload)?;
+    let mut failures = 0;
+    for hook in hooks {
+        let Some((program, argv_rest)) = hook.argv.split_first() else {
+            continue;
+        };
+        println!("$ {}", hook.argv.join(" "));
+
+        let mut cmd = tokio::process::Command::new(program);
+        cmd.args(argv_rest).arg(&json_arg);
+        for (field, var) in PAYLOAD_ENV {
+            if let Some(value) = payload[*field].as_str() {
+                cmd.env(var, value);
+            }
+        }
+        if dry_run {
+            cmd.env(HOOK_DRY_RUN_ENV_VAR, "1");
+        }
+        cmd.stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true);
+
+        let timeout_ms = config.hooks.timeout_ms_for(hook.timeout_ms);
+        let start = Instant::now();
+        let output = run_hook_process(
+            &mut cmd,
+            &config.hooks.wasm,
+            Duration::from_millis(timeout_ms),
+        )
+        .await;
+        let elapsed = start.elapsed().as_millis();
+        match output {
+            None => {
+                failures += 1;
+                println!("timed out after {timeout_ms} ms");
+            }
+            Some(Err(e)) => {
+                failures += 1;
+                println!("failed to spawn hook: {e}");
+            }
+            Some(Ok(output)) => {
+                if !output.status.success() {
+                    failures += 1;
+                }
+                println!(
+                    "exit code: {} ({elapsed} ms)",
+                    output.status.code().unwrap_or(-1)
+                );
+                print_stream("stdout", &output.stdout);
+                print_stream("stderr", &output.stderr);
+            }
+        }
+        println!();
+    }
+
+    if failures > 0 {
+        bail!("{failures} {kind} hook(s) failed");
+    }
+    Ok(())
+}
+
+fn print_stream(name: &str, bytes: &[u8]) {
+    let text = String::from_utf8_lossy(bytes);
+    let text = text.trim_end();
+    if !text.is_empty() {
+        println!("{name}:\n{text}");
+    }
+}
@@ -20,11 +20,15 @@ use owo_colors::OwoColorize;
 use std::path::PathBuf;
 use supports_color::Stream;
 
+mod hooks_cmd;
 mod mcp_cmd;
 mod pre_main_hardening;
+mod status_cmd;
 
+use crate::hooks_cmd::HooksCli;
 use crate::mcp_cmd::McpCli;
 use crate::proto::ProtoCli;
+use crate::status_cmd::StatusCli;
 
 /// Codex CLI
 ///
@@ -66,6 +70,12 @@ enum Subcommand {
     /// [experimental] Run Codex as an MCP server and manage MCP servers.
     Mcp(McpCli),
 
+    /// Test configured hooks against a sample payload.
+    Hooks(HooksCli),
+
+    /// Show the sessions running in the current directory.
+    Status(StatusCli),
+
     /// Run the Protocol stream via stdin/stdout
     #[clap(visible_alias = "p")]
     Proto(ProtoCli),
@@ -86,6 +96,10 @@ enum Subcommand {
     /// Internal: generate TypeScript protocol bindings.
     #[clap(hide = true)]
     GenerateTs(GenerateTsCommand),
+
+    /// Internal: generate Python protocol bindings.
+    #[clap(hide = true)]
+    GeneratePy(GeneratePyCommand),
 }
 
 #[derive(Debug, Parser)]
@@ -160,10 +174,18 @@ struct GenerateTsCommand {
     prettier: Option<PathBuf>,
 }
 
+#[derive(Debug, Parser)]
+struct GeneratePyCommand {
+    /// Output directory where codex_protocol.py will be written
+    #[arg(short = 'o', long = "out", value_name = "DIR")]
+    out_dir: PathBuf,
+}
+
 fn format_exit_messages(exit_info: AppExitInfo, color_enabled: bool) -> Vec<String> {
     let AppExitInfo {
         token_usage,
         conversation_id,
+        session_summary,
     } = exit_info;
 
     if token_usage.is_zero() {
@@ -174,6 +196,7 @@ fn format_exit_messages(exit_info: AppExitInfo, color_enabled: bool) -> Vec<Stri
         "{}",
         codex_core::protocol::FinalOutput::from(token_usage)
     )];
+    lines.extend(session_summary.lines());
 
     if let Some(session_id) = conversation_id {
         let resume_cmd = format!("codex resume {session_id}");
@@ -243,8 +266,11 @@ async fn cli_main(codex_linux_sandbox_exe: Option<PathBuf>) -> anyhow::Result<()
                 &mut interactive.config_overrides,
                 root_config_overrides.clone(),
             );
+            let quiet = interactive.quiet;
             let exit_info = codex_tui::run_main(interactive, codex_linux_sandbox_exe).await?;
-            print_exit_messages(exit_info);
+            if !quiet {
+                print_exit_messages(exit_info);
+            }
         }
         Some(Subcommand::Exec(mut exec_cli)) => {
             prepend_config_flags(
@@ -258,6 +284,16 @@ async fn cli_main(codex_linux_sandbox_exe: Option<PathBuf>) -> anyhow::Result<()
             prepend_config_flags(&mut mcp_cli.config_overrides, root_config_overrides.clone());
             mcp_cli.run(codex_linux_sandbox_exe).await?;
         }
+        Some(Subcommand::Hooks(mut hooks_cli)) => {
+            prepend_config_flags(
+                &mut hooks_cli.config_overrides,
+                root_config_overrides.clone(),
+            );
+            hooks_cli.run().await?;
+        }
+        Some(Subcommand::Status(status_cli)) => {
+            status_cli.run()?;
+        }
         Some(Subcommand::Resume(ResumeCommand {
             session_id,
             last,
@@ -341,6 +377,9 @@ async fn cli_main(codex_linux_sandbox_exe: Option<PathBuf>) -> anyhow::Result<()
         Some(Subcommand::GenerateTs(gen_cli)) => {
             codex_protocol_ts::generate_ts(&gen_cli.out_dir, gen_cli.prettier.as_deref())?;
         }
+        Some(Subcommand::GeneratePy(gen_cli)) => {
+            codex_protocol_ts::generate_py(&gen_cli.out_dir)?;
+        }
     }
 
     Ok(())
@@ -412,6 +451,9 @@ fn merge_resume_cli_flags(interactive: &mut TuiCli, resume_cli: TuiCli) {
     if resume_cli.web_search {
         interactive.web_search = true;
     }
+    if resume_cli.quiet {
+        interactive.quiet = true;
+    }
     if !resume_cli.images.is_empty() {
         interactive.images = resume_cli.images;
     }
@@ -436,6 +478,8 @@ mod tests {
     use super::*;
     use codex_core::protocol::TokenUsage;
     use codex_protocol::mcp_protocol::ConversationId;
+    use codex_tui::SessionSummary;
+    use std::time::Duration;
 
     fn finalize_from_args(args: &[&str]) -> TuiCli {
         let cli = MultitoolCli::try_parse_from(args).expect("parse");
@@ -468,6 +512,12 @@ mod tests {
             conversation_id: conversation
                 .map(ConversationId::from_string)
                 .map(Result::unwrap),
+            session_summary: SessionSummary {
+                changed_files: vec!["src/main.rs".to_string()],
+                commands_run: 4,
+                duration: Duration::from_secs(90),
+                estimated_cost_usd: None,
+            },
         }
     }
 
@@ -476,6 +526,7 @@ mod tests {
         let exit_info = AppExitInfo {
             token_usage: TokenUsage::default(),
             conversation_id: None,
+            session_summary: SessionSummary::default(),
         };
         let lines = format_exit_messages(exit_info, false);
         assert!(lines.is_empty());
@@ -489,6 +540,9 @@ mod tests {
             lines,
             vec![
                 "Token usage: total=2 input=0 output=2".to_string(),
+                "Session: duration=1m 30s commands=4".to_string(),
+                "Files changed (1):".to_string(),
+                "  src/main.rs".to_string(),
                 "To continue this session, run codex resume 123e4567-e89b-12d3-a456-426614174000."
                     .to_string(),
             ]
@@ -499,8 +553,8 @@ mod tests {
     fn format_exit_messages_applies_color_when_enabled() {
         let exit_info = sample_exit_info(Some("123e4567-e89b-12d3-a456-426614174000"));
         let lines = format_exit_messages(exit_info, true);
-        assert_eq!(lines.len(), 2);
-        assert!(lines[1].contains("\u{1b}[36m"));
+        assert_eq!(lines.len(), 5);
+        assert!(lines[4].contains("\u{1b}[36m"));
     }
 
     #[test]
@@ -568,7 +622,9 @@ mod tests {
         assert_eq!(interactive.config_profile.as_deref(), Some("my-profile"));
         assert!(matches!(
             interactive.sandbox_mode,
-            Some(codex_common::SandboxModeCliArg::WorkspaceWrite)
+            Some(codex_common::SandboxCliArg::Mode(
+                codex_common::SandboxModeCliArg::WorkspaceWrite
+            ))
         ));
         assert!(matches!(
             interactive.approval_policy,
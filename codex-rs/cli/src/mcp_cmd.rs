@@ -13,6 +13,8 @@ use codex_core::config::find_codex_home;
 use codex_core::config::load_global_mcp_servers;
 use codex_core::config::write_global_mcp_servers;
 use codex_core::config_types::McpServerConfig;
+use codex_core::mcp_oauth::remove_mcp_oauth_tokens;
+use codex_login::start_mcp_device_login;
 
 /// [experimental] Launch Codex as an MCP server or manage configured MCP servers.
 ///
@@ -22,6 +24,8 @@ use codex_core::config_types::McpServerConfig;
 /// - `get`    — show a single server (with `--json`)
 /// - `add`    — add a server launcher entry to `~/.codex/config.toml`
 /// - `remove` — delete a server entry
+/// - `login`  — authorize Codex with an HTTP server that uses OAuth
+/// - `logout` — forget the OAuth tokens for a server
 #[derive(Debug, clap::Parser)]
 pub struct McpCli {
     #[clap(flatten)]
@@ -47,6 +51,12 @@ pub enum McpSubcommand {
 
     /// [experimental] Remove a global MCP server entry.
     Remove(RemoveArgs),
+
+    /// [experimental] Log in to an MCP server that requires OAuth.
+    Login(LoginArgs),
+
+    /// [experimental] Forget the OAuth tokens stored for an MCP server.
+    Logout(LogoutArgs),
 }
 
 #[derive(Debug, clap::Parser)]
@@ -75,8 +85,17 @@ pub struct AddArgs {
     #[arg(long, value_parser = parse_env_pair, value_name = "KEY=VALUE")]
     pub env: Vec<(String, String)>,
 
+    /// URL of a server that speaks the streamable HTTP transport, used
+    /// instead of a launch command.
+    #[arg(long, conflicts_with_all = ["command", "env"])]
+    pub url: Option<String>,
+
+    /// Environment variable holding a bearer token to send to `--url`.
+    #[arg(long, requires = "url", value_name = "ENV_VAR")]
+    pub bearer_token_env_var: Option<String>,
+
     /// Command to launch the MCP server.
-    #[arg(trailing_var_arg = true, num_args = 1..)]
+    #[arg(trailing_var_arg = true, num_args = 1.., required_unless_present = "url")]
     pub command: Vec<String>,
 }
 
@@ -86,6 +105,18 @@ pub struct RemoveArgs {
     pub name: String,
 }
 
+#[derive(Debug, clap::Parser)]
+pub struct LoginArgs {
+    /// Name of the MCP server to log in to.
+    pub name: String,
+}
+
+#[derive(Debug, clap::Parser)]
+pub struct LogoutArgs {
+    /// Name of the MCP server to log out of.
+    pub name: String,
+}
+
 impl McpCli {
     pub async fn run(self, codex_linux_sandbox_exe: Option<PathBuf>) -> Result<()> {
         let McpCli {
@@ -110,6 +141,12 @@ impl McpCli {
             McpSubcommand::Remove(args) => {
                 run_remove(&config_overrides, args)?;
             }
+            McpSubcommand::Login(args) => {
+                run_login(&config_overrides, args).await?;
+            }
+            McpSubcommand::Logout(args) => {
+                run_logout(&config_overrides, args)?;
+            }
         }
 
         Ok(())
@@ -120,14 +157,22 @@ fn run_add(config_overrides: &CliConfigOverrides, add_args: AddArgs) -> Result<(
     // Validate any provided overrides even though they are not currently applied.
     config_overrides.parse_overrides().map_err(|e| anyhow!(e))?;
 
-    let AddArgs { name, env, command } = add_args;
+    let AddArgs {
+        name,
+        env,
+        url,
+        bearer_token_env_var,
+        command,
+    } = add_args;
 
     validate_server_name(&name)?;
 
     let mut command_parts = command.into_iter();
-    let command_bin = command_parts
-        .next()
-        .ok_or_else(|| anyhow!("command is required"))?;
+    let command_bin = match command_parts.next() {
+        Some(command_bin) => command_bin,
+        None if url.is_some() => String::new(),
+        None => bail!("command is required"),
+    };
     let command_args: Vec<String> = command_parts.collect();
 
     let env_map = if env.is_empty() {
@@ -148,8 +193,18 @@ fn run_add(config_overrides: &CliConfigOverrides, add_args: AddArgs) -> Result<(
         command: command_bin,
         args: command_args,
         env: env_map,
+        url,
+        headers: None,
+        bearer_token_env_var,
+        oauth: None,
+        include_tools: Vec::new(),
+        exclude_tools: Vec::new(),
         startup_timeout_sec: None,
         tool_timeout_sec: None,
+        tool_timeouts_ms: BTreeMap::new(),
+        max_concurrent_calls: None,
+        lazy: false,
+        idle_timeout_sec: None,
     };
 
     servers.insert(name.clone(), new_entry);
@@ -189,6 +244,50 @@ fn run_remove(config_overrides: &CliConfigOverrides, remove_args: RemoveArgs) ->
     Ok(())
 }
 
+async fn run_login(config_overrides: &CliConfigOverrides, login_args: LoginArgs) -> Result<()> {
+    let overrides = config_overrides.parse_overrides().map_err(|e| anyhow!(e))?;
+    let config = Config::load_with_cli_overrides(overrides, ConfigOverrides::default())
+        .context("failed to load configuration")?;
+
+    let name = login_args.name;
+    let Some(server) = config.mcp_servers.get(&name) else {
+        bail!("No MCP server named '{name}' found.");
+    };
+    let (Some(url), Some(oauth)) = (&server.url, &server.oauth) else {
+        bail!("MCP server '{name}' must set both `url` and `oauth` to log in.");
+    };
+
+    let login = start_mcp_device_login(url, oauth)
+        .await
+        .with_context(|| format!("failed to start login for MCP server '{name}'"))?;
+    let link = login
+        .verification_uri_complete
+        .as_deref()
+        .unwrap_or(&login.verification_uri);
+    println!("To authorize Codex with '{name}', open {link}");
+    println!("and enter the code: {}", login.user_code);
+    login
+        .complete(&config.codex_home, &name)
+        .await
+        .with_context(|| format!("failed to log in to MCP server '{name}'"))?;
+
+    println!("Logged in to MCP server '{name}'.");
+    Ok(())
+}
+
+fn run_logout(config_overrides: &CliConfigOverrides, logout_args: LogoutArgs) -> Result<()> {
+    config_overrides.parse_overrides().map_err(|e| anyhow!(e))?;
+
+    let name = logout_args.name;
+    let codex_home = find_codex_home().context("failed to resolve CODEX_HOME")?;
+    if remove_mcp_oauth_tokens(&codex_home, &name)? {
+        println!("Logged out of MCP server '{name}'.");
+    } else {
+        println!("Not logged in to MCP server '{name}'.");
+    }
+    Ok(())
+}
+
 fn run_list(config_overrides: &CliConfigOverrides, list_args: ListArgs) -> Result<()> {
     let overrides = config_overrides.parse_overrides().map_err(|e| anyhow!(e))?;
     let config = Config::load_with_cli_overrides(overrides, ConfigOverrides::default())
@@ -211,6 +310,8 @@ fn run_list(config_overrides: &CliConfigOverrides, list_args: ListArgs) -> Resul
                     "command": cfg.command,
                     "args": cfg.args,
                     "env": env,
+                    "url": cfg.url,
+                    "bearer_token_env_var": cfg.bearer_token_env_var,
                     "startup_timeout_sec": cfg
                         .startup_timeout_sec
                         .map(|timeout| timeout.as_secs_f64()),
@@ -252,7 +353,8 @@ fn run_list(config_overrides: &CliConfigOverrides, list_args: ListArgs) -> Resul
             }
         };
 
-        rows.push([name.clone(), cfg.command.clone(), args, env]);
+        let command = cfg.url.clone().unwrap_or_else(|| cfg.command.clone());
+        rows.push([name.clone(), command, args, env]);
     }
 
     let mut widths = ["Name".len(), "Command".len(), "Args".len(), "Env".len()];
@@ -311,6 +413,8 @@ fn run_get(config_overrides: &CliConfigOverrides, get_args: GetArgs) -> Result<(
             "command": server.command,
             "args": server.args,
             "env": env,
+            "url": server.url,
+            "bearer_token_env_var": server.bearer_token_env_var,
             "startup_timeout_sec": server
                 .startup_timeout_sec
                 .map(|timeout| timeout.as_secs_f64()),
@@ -323,7 +427,14 @@ fn run_get(config_overrides: &CliConfigOverrides, get_args: GetArgs) -> Result<(
     }
 
     println!("{}", get_args.name);
-    println!("  command: {}", server.command);
+    if let Some(url) = &server.url {
+        println!("  url: {url}");
+        if let Some(var) = &server.bearer_token_env_var {
+            println!("  bearer_token_env_var: {var}");
+        }
+    } else {
+        println!("  command: {}", server.command);
+    }
     let args = if server.args.is_empty() {
         "-".to_string()
     } else {
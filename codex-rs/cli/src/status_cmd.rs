@@ -0,0 +1,71 @@
+use anyhow::Context;
+use anyhow::Result;
+use codex_core::config::find_codex_home;
+use codex_core::live_sessions::LiveSession;
+use codex_core::live_sessions::list_live_sessions;
+use codex_core::path_utils::display_path;
+use codex_core::path_utils::path_starts_with;
+
+/// Show the Codex sessions running in the current directory.
+///
+/// A session counts when the current directory is its working directory or
+/// inside it.
+#[derive(Debug, clap::Parser)]
+pub struct StatusCli {
+    /// Print one tab-separated line per session: state (`running`,
+    /// `waiting-approval` or `idle`), session id, pid and working directory.
+    /// Prints nothing when no session is running.
+    #[arg(long, default_value_t = false)]
+    pub porcelain: bool,
+
+    /// Include sessions in every directory.
+    #[arg(long, default_value_t = false)]
+    pub all: bool,
+}
+
+impl StatusCli {
+    pub fn run(self) -> Result<()> {
+        let codex_home = find_codex_home().context("failed to locate CODEX_HOME")?;
+        let mut sessions = list_live_sessions(&codex_home).context("failed to list sessions")?;
+        let cwd = std::env::current_dir().context("failed to read the current directory")?;
+        if !self.all {
+            sessions.retain(|session| path_starts_with(&cwd, &session.cwd));
+        }
+
+        if self.porcelain {
+            for session in &sessions {
+                println!("{}", porcelain_line(session));
+            }
+            return Ok(());
+        }
+
+        if sessions.is_empty() {
+            if self.all {
+                println!("No Codex sessions are running.");
+            } else {
+                println!("No Codex sessions are running in {}.", display_path(&cwd));
+            }
+            return Ok(());
+        }
+        for session in &sessions {
+            println!(
+                "{:<16}  {}  pid {:<7}  {}",
+                session.state.to_string(),
+                session.conversation_id,
+                session.pid,
+                display_path(&session.cwd),
+            );
+        }
+        Ok(())
+    }
+}
+
+fn porcelain_line(session: &LiveSession) -> String {
+    format!(
+        "{}\t{}\t{}\t{}",
+        session.state,
+        session.conversation_id,
+        session.pid,
+        session.cwd.display()
+    )
+}
@@ -0,0 +1,86 @@
+#![cfg(unix)]
+
+use std::os::unix::fs::PermissionsExt;
+use std::path::Path;
+
+use anyhow::Result;
+use predicates::prelude::PredicateBooleanExt;
+use predicates::str::contains;
+use tempfile::TempDir;
+
+fn codex_command(codex_home: &Path) -> Result<assert_cmd::Command> {
+    let mut cmd = assert_cmd::Command::cargo_bin("codex")?;
+    cmd.env("CODEX_HOME", codex_home);
+    Ok(cmd)
+}
+
+fn write_script(path: &Path, body: &str) -> Result<()> {
+    std::fs::write(path, format!("#!/bin/sh\n{body}\n"))?;
+    std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o755))?;
+    Ok(())
+}
+
+#[test]
+fn test_runs_matching_hooks_with_payload() -> Result<()> {
+    let codex_home = TempDir::new()?;
+    let shell_hook = codex_home.path().join("shell-hook");
+    let patch_hook = codex_home.path().join("patch-hook");
+    write_script(
+        &shell_hook,
+        r#"echo "dry_run=$CODEX_HOOK_DRY_RUN tool=$TOOL_ID payload=$1""#,
+    )?;
+    write_script(&patch_hook, "echo patch hook ran")?;
+    std::fs::write(
+        codex_home.path().join("config.toml"),
+        format!(
+            r#"
+[[hooks.pre_tool_use]]
+matcher = "shell"
+command = ["{}"]
+
+[[hooks.pre_tool_use]]
+matcher = "apply_patch"
+command = ["{}"]
+"#,
+            shell_hook.display(),
+            patch_hook.display()
+        ),
+    )?;
+    let payload = codex_home.path().join("payload.json");
+    std::fs::write(&payload, r#"{ "tool": "shell" }"#)?;
+
+    let mut cmd = codex_command(codex_home.path())?;
+    cmd.args(["hooks", "test", "pre-tool-use", "--dry-run", "--payload"])
+        .arg(&payload)
+        .assert()
+        .success()
+        .stdout(contains("exit code: 0"))
+        .stdout(contains(r#"dry_run=1 tool=shell payload={"tool":"shell"}"#))
+        .stdout(contains("patch hook ran").not());
+
+    Ok(())
+}
+
+#[test]
+fn test_fails_when_a_hook_exits_non_zero() -> Result<()> {
+    let codex_home = TempDir::new()?;
+    let stop_hook = codex_home.path().join("stop-hook");
+    write_script(&stop_hook, "echo nope >&2; exit 3")?;
+    std::fs::write(
+        codex_home.path().join("config.toml"),
+        format!("[hooks]\nstop = [\"{}\"]\n", stop_hook.display()),
+    )?;
+    let payload = codex_home.path().join("payload.json");
+    std::fs::write(&payload, r#"{"type": "stop", "sub_id": "1"}"#)?;
+
+    let mut cmd = codex_command(codex_home.path())?;
+    cmd.args(["hooks", "test", "stop", "--payload"])
+        .arg(&payload)
+        .assert()
+        .failure()
+        .stdout(contains("exit code: 3"))
+        .stdout(contains("nope"))
+        .stderr(contains("1 stop hook(s) failed"));
+
+    Ok(())
+}
@@ -84,3 +84,33 @@ fn add_with_env_preserves_key_order_and_values() -> Result<()> {
 
     Ok(())
 }
+
+#[test]
+fn add_with_url_records_http_server() -> Result<()> {
+    let codex_home = TempDir::new()?;
+
+    let mut add_cmd = codex_command(codex_home.path())?;
+    add_cmd
+        .args([
+            "mcp",
+            "add",
+            "hosted",
+            "--url",
+            "https://example.com/mcp",
+            "--bearer-token-env-var",
+            "HOSTED_MCP_TOKEN",
+        ])
+        .assert()
+        .success();
+
+    let servers = load_global_mcp_servers(codex_home.path())?;
+    let hosted = servers.get("hosted").expect("server should exist");
+    assert_eq!(hosted.url.as_deref(), Some("https://example.com/mcp"));
+    assert_eq!(
+        hosted.bearer_token_env_var.as_deref(),
+        Some("HOSTED_MCP_TOKEN")
+    );
+    assert!(hosted.command.is_empty());
+
+    Ok(())
+}
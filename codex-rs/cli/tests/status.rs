@@ -0,0 +1,81 @@
+use std::path::Path;
+
+use anyhow::Result;
+use pretty_assertions::assert_eq;
+use serde_json::json;
+use tempfile::TempDir;
+
+fn codex_command(codex_home: &Path) -> Result<assert_cmd::Command> {
+    let mut cmd = assert_cmd::Command::cargo_bin("codex")?;
+    cmd.env("CODEX_HOME", codex_home);
+    Ok(cmd)
+}
+
+fn write_live_session(codex_home: &Path, id: &str, cwd: &Path, state: &str) -> Result<()> {
+    let dir = codex_home.join("live");
+    std::fs::create_dir_all(&dir)?;
+    let session = json!({
+        "conversation_id": id,
+        // The test process itself, so the session counts as alive.
+        "pid": std::process::id(),
+        "cwd": cwd,
+        "state": state,
+        "updated_at": "2025-01-01T00:00:00Z",
+    });
+    std::fs::write(dir.join(format!("{id}.json")), session.to_string())?;
+    Ok(())
+}
+
+#[test]
+fn porcelain_prints_nothing_without_sessions() -> Result<()> {
+    let codex_home = TempDir::new()?;
+    let workdir = TempDir::new()?;
+
+    let output = codex_command(codex_home.path())?
+        .current_dir(workdir.path())
+        .args(["status", "--porcelain"])
+        .output()?;
+    assert!(output.status.success());
+    assert_eq!(String::from_utf8(output.stdout)?, "");
+
+    Ok(())
+}
+
+#[test]
+fn porcelain_lists_sessions_covering_the_current_directory() -> Result<()> {
+    let codex_home = TempDir::new()?;
+    let workdir = TempDir::new()?;
+    let repo = workdir.path().canonicalize()?;
+    let subdir = repo.join("src");
+    std::fs::create_dir_all(&subdir)?;
+    let elsewhere = TempDir::new()?;
+
+    let id = "0199a213-81c0-7800-8aa1-bbab2a035a53";
+    write_live_session(codex_home.path(), id, &repo, "waiting-approval")?;
+    write_live_session(
+        codex_home.path(),
+        "0199a213-81c0-7800-8aa1-bbab2a035a54",
+        &elsewhere.path().canonicalize()?,
+        "running",
+    )?;
+
+    let output = codex_command(codex_home.path())?
+        .current_dir(&subdir)
+        .args(["status", "--porcelain"])
+        .output()?;
+    assert!(output.status.success());
+    let pid = std::process::id();
+    assert_eq!(
+        String::from_utf8(output.stdout)?,
+        format!("waiting-approval\t{id}\t{pid}\t{}\n", repo.display())
+    );
+
+    let output = codex_command(codex_home.path())?
+        .current_dir(&subdir)
+        .args(["status", "--porcelain", "--all"])
+        .output()?;
+    assert!(output.status.success());
+    assert_eq!(String::from_utf8(output.stdout)?.lines().count(), 2);
+
+    Ok(())
+}
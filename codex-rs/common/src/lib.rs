@@ -10,6 +10,9 @@ pub use approval_mode_cli_arg::ApprovalModeCliArg;
 #[cfg(feature = "cli")]
 mod sandbox_mode_cli_arg;
 
+#[cfg(feature = "cli")]
+pub use sandbox_mode_cli_arg::SandboxCliArg;
+
 #[cfg(feature = "cli")]
 pub use sandbox_mode_cli_arg::SandboxModeCliArg;
 
@@ -4,7 +4,10 @@
 //! without any of the associated data so it can be expressed as a simple flag
 //! on the command-line. Users that need to tweak the advanced options for
 //! `workspace-write` can continue to do so via `-c` overrides or their
-//! `config.toml`.
+//! `config.toml`, or name one of their `[sandbox_presets]` instead.
+
+use std::convert::Infallible;
+use std::str::FromStr;
 
 use clap::ValueEnum;
 use codex_protocol::config_types::SandboxMode;
@@ -26,3 +29,40 @@ impl From<SandboxModeCliArg> for SandboxMode {
         }
     }
 }
+
+/// Value of `--sandbox`: a built-in mode, or any other name, which is looked
+/// up in the config's `[sandbox_presets]`.
+#[derive(Clone, Debug)]
+pub enum SandboxCliArg {
+    Mode(SandboxModeCliArg),
+    Preset(String),
+}
+
+impl SandboxCliArg {
+    /// The built-in sandbox mode, if this names one.
+    pub fn sandbox_mode(&self) -> Option<SandboxMode> {
+        match self {
+            Self::Mode(mode) => Some((*mode).into()),
+            Self::Preset(_) => None,
+        }
+    }
+
+    /// The sandbox preset name, if this is not a built-in mode.
+    pub fn preset(&self) -> Option<String> {
+        match self {
+            Self::Mode(_) => None,
+            Self::Preset(name) => Some(name.clone()),
+        }
+    }
+}
+
+impl FromStr for SandboxCliArg {
+    type Err = Infallible;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match SandboxModeCliArg::from_str(s, false) {
+            Ok(mode) => Self::Mode(mode),
+            Err(_) => Self::Preset(s.to_string()),
+        })
+    }
+}
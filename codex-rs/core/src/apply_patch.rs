@@ -1,13 +1,27 @@
 use crate::codex::Session;
 use crate::codex::TurnContext;
+use crate::error::CodexErr;
+use crate::error::SandboxErr;
+use crate::exec::ExecParams;
+use crate::exec::process_exec_tool_call;
 use crate::function_tool::FunctionCallError;
+use crate::protocol::AskForApproval;
 use crate::protocol::FileChange;
+use crate::protocol::PatchPathEscape;
+use crate::protocol::PatchStats;
 use crate::protocol::ReviewDecision;
+use crate::protocol::SandboxPolicy;
 use crate::safety::SafetyCheck;
 use crate::safety::assess_patch_safety;
+use crate::safety::assess_safety_for_untrusted_command;
+use crate::safety::describe_patch_path_escapes;
+use crate::safety::find_patch_path_escapes;
 use codex_apply_patch::ApplyPatchAction;
 use codex_apply_patch::ApplyPatchFileChange;
+use codex_apply_patch::MaybeApplyPatchVerified;
+use codex_apply_patch::maybe_parse_apply_patch_verified;
 use std::collections::HashMap;
+use std::path::Path;
 use std::path::PathBuf;
 
 pub const CODEX_APPLY_PATCH_ARG1: &str = "--codex-run-as-apply-patch";
@@ -30,6 +44,12 @@ pub(crate) enum InternalApplyPatchInvocation {
 pub(crate) struct ApplyPatchExec {
     pub(crate) action: ApplyPatchAction,
     pub(crate) user_explicitly_approved_this_action: bool,
+    /// Files dropped from the patch because the user approved only the
+    /// changes to other files.
+    pub(crate) skipped_files: Vec<PathBuf>,
+    /// Writes dropped from the patch because they resolve outside the
+    /// writable roots.
+    pub(crate) rejected_paths: Vec<PatchPathEscape>,
 }
 
 pub(crate) async fn apply_patch(
@@ -39,16 +59,52 @@ pub(crate) async fn apply_patch(
     call_id: &str,
     action: ApplyPatchAction,
 ) -> InternalApplyPatchInvocation {
-    match assess_patch_safety(
-        &action,
-        turn_context.approval_policy,
-        &turn_context.sandbox_policy,
-        &turn_context.cwd,
-    ) {
+    // Judge against the policy commands actually run under, so patches that
+    // only touch the scratch directory are auto-approved.
+    let (sandbox_policy, sandbox_cwd) = sess.exec_sandbox_policy(turn_context);
+
+    // Writes that resolve outside the writable roots through a symlink or
+    // `..` are never applied. Whatever else the patch does still goes to the
+    // user, so they see what was rejected and why.
+    let mut action = action;
+    let rejected_paths = find_patch_path_escapes(&action, &sandbox_policy, &sandbox_cwd);
+    let safety = if rejected_paths.is_empty() {
+        assess_patch_safety(
+            &action,
+            turn_context.approval_policy,
+            &sandbox_policy,
+            &sandbox_cwd,
+        )
+    } else {
+        let rejected = |path: &PathBuf| rejected_paths.iter().any(|escape| escape.path() == path);
+        let kept: Vec<PathBuf> = action
+            .changes()
+            .iter()
+            .filter(|(path, change)| {
+                let move_path = match change {
+                    ApplyPatchFileChange::Update { move_path, .. } => move_path.as_ref(),
+                    _ => None,
+                };
+                !rejected(path) && !move_path.is_some_and(rejected)
+            })
+            .map(|(path, _)| path.clone())
+            .collect();
+        action.retain_files(&kept);
+        if action.is_empty() || turn_context.approval_policy == AskForApproval::Never {
+            SafetyCheck::Reject {
+                reason: describe_patch_path_escapes(&rejected_paths),
+            }
+        } else {
+            SafetyCheck::AskUser
+        }
+    };
+    match safety {
         SafetyCheck::AutoApprove { .. } => {
             InternalApplyPatchInvocation::DelegateToExec(ApplyPatchExec {
                 action,
                 user_explicitly_approved_this_action: false,
+                skipped_files: Vec::new(),
+                rejected_paths,
             })
         }
         SafetyCheck::AskUser => {
@@ -60,13 +116,37 @@ pub(crate) async fn apply_patch(
             // that similar patches can be auto-approved in the future during
             // this session.
             let rx_approve = sess
-                .request_patch_approval(sub_id.to_owned(), call_id.to_owned(), &action, None, None)
+                .request_patch_approval(
+                    sub_id.to_owned(),
+                    call_id.to_owned(),
+                    &action,
+                    None,
+                    None,
+                    rejected_paths.clone(),
+                )
                 .await;
             match rx_approve.await.unwrap_or_default() {
-                ReviewDecision::Approved | ReviewDecision::ApprovedForSession => {
+                ReviewDecision::Approved
+                | ReviewDecision::ApprovedForSession
+                | ReviewDecision::ApprovedAlways => {
+                    let mut action = action;
+                    let skipped_files = match sess.take_approved_patch_files(sub_id).await {
+                        Some(files) => action.retain_files(&files),
+                        None => Vec::new(),
+                    };
+                    if action.is_empty() {
+                        return InternalApplyPatchInvocation::Output(Err(
+                            FunctionCallError::RespondToModel(
+                                "patch rejected by user: none of its files were approved"
+                                    .to_string(),
+                            ),
+                        ));
+                    }
                     InternalApplyPatchInvocation::DelegateToExec(ApplyPatchExec {
                         action,
                         user_explicitly_approved_this_action: true,
+                        skipped_files,
+                        rejected_paths,
                     })
                 }
                 ReviewDecision::Denied | ReviewDecision::Abort => {
@@ -82,11 +162,126 @@ pub(crate) async fn apply_patch(
     }
 }
 
+/// Applies `patch` outside of any conversation, for embedders such as the MCP
+/// server. Relative paths resolve against `cwd`. Nobody is around to approve
+/// the patch, so it must stay within the writable roots of `sandbox_policy`;
+/// it is then applied by a sandboxed `apply_patch` child process, like an
+/// auto-approved patch from the model. Returns the `apply_patch` output on
+/// success, or why the patch was not applied.
+pub async fn apply_patch_standalone(
+    patch: String,
+    cwd: &Path,
+    sandbox_policy: &SandboxPolicy,
+    codex_linux_sandbox_exe: &Option<PathBuf>,
+) -> Result<String, String> {
+    let argv = vec!["apply_patch".to_string(), patch];
+    let action = match maybe_parse_apply_patch_verified(&argv, cwd) {
+        MaybeApplyPatchVerified::Body(action) => action,
+        MaybeApplyPatchVerified::CorrectnessError(e) => return Err(format!("invalid patch: {e}")),
+        MaybeApplyPatchVerified::ShellParseError(e) => {
+            return Err(format!("invalid patch: {e:?}"));
+        }
+        MaybeApplyPatchVerified::NotApplyPatch => {
+            return Err("invalid patch: expected `*** Begin Patch`".to_string());
+        }
+    };
+    match assess_patch_safety(&action, AskForApproval::Never, sandbox_policy, cwd) {
+        SafetyCheck::AutoApprove { .. } => {}
+        SafetyCheck::AskUser => {
+            return Err("patch rejected: it cannot be applied in a sandbox".to_string());
+        }
+        SafetyCheck::Reject { reason } => return Err(format!("patch rejected: {reason}")),
+    }
+    // Pick the sandbox the same way as for an auto-approved patch in a
+    // session.
+    let sandbox_type =
+        match assess_safety_for_untrusted_command(AskForApproval::Never, sandbox_policy, false) {
+            SafetyCheck::AutoApprove { sandbox_type } => sandbox_type,
+            SafetyCheck::AskUser | SafetyCheck::Reject { .. } => {
+                return Err("patch rejected: no sandbox is available on this platform".to_string());
+            }
+        };
+
+    let path_to_codex = std::env::current_exe()
+        .map_err(|e| format!("failed to determine path to codex executable: {e}"))?;
+    let params = ExecParams {
+        command: vec![
+            path_to_codex.to_string_lossy().to_string(),
+            CODEX_APPLY_PATCH_ARG1.to_string(),
+            action.patch,
+        ],
+        cwd: action.cwd,
+        timeout_ms: None,
+        env: HashMap::new(),
+        with_escalated_permissions: None,
+        justification: None,
+        tty: false,
+        resource_limits: Default::default(),
+        sandbox_extras: Default::default(),
+    };
+    let output = match process_exec_tool_call(
+        params,
+        sandbox_type,
+        sandbox_policy,
+        cwd,
+        codex_linux_sandbox_exe,
+        None,
+        None,
+        None,
+    )
+    .await
+    {
+        Ok(output) => output,
+        Err(CodexErr::Sandbox(SandboxErr::Denied { output })) => *output,
+        Err(e) => return Err(format!("failed to apply patch: {e}")),
+    };
+    if output.exit_code == 0 {
+        Ok(output.stdout.text)
+    } else {
+        Err(format!(
+            "failed to apply patch: {}",
+            output.stderr.text.trim()
+        ))
+    }
+}
+
+/// Tells the model which parts of its patch were left out: writes that
+/// resolve outside the writable roots, and files the user did not approve.
+pub(crate) fn skipped_files_note(exec: &ApplyPatchExec) -> Option<String> {
+    let mut notes = Vec::new();
+    if !exec.rejected_paths.is_empty() {
+        let writes = exec
+            .rejected_paths
+            .iter()
+            .map(|escape| format!("- {escape}"))
+            .collect::<Vec<_>>()
+            .join("\n");
+        notes.push(format!(
+            "These writes resolve outside the writable roots and were rejected:\n{writes}"
+        ));
+    }
+    if !exec.skipped_files.is_empty() {
+        let files = exec
+            .skipped_files
+            .iter()
+            .map(|path| format!("- {}", path.display()))
+            .collect::<Vec<_>>()
+            .join("\n");
+        notes.push(format!(
+            "The user approved only part of this patch. Changes to these files were skipped and not applied:\n{files}"
+        ));
+    }
+    (!notes.is_empty()).then(|| notes.join("\n\n"))
+}
+
+/// The patch's changes in protocol form, along with how many lines each one
+/// adds and removes.
 pub(crate) fn convert_apply_patch_to_protocol(
     action: &ApplyPatchAction,
-) -> HashMap<PathBuf, FileChange> {
+) -> (HashMap<PathBuf, FileChange>, PatchStats) {
     let changes = action.changes();
     let mut result = HashMap::with_capacity(changes.len());
+    let mut stats = PatchStats::default();
     for (path, change) in changes {
         let protocol_change = match change {
             ApplyPatchFileChange::Add { content } => FileChange::Add {
@@ -104,7 +299,8 @@ pub(crate) fn convert_apply_patch_to_protocol(
                 move_path: move_path.clone(),
             },
         };
+        stats.add_file(path.clone(), protocol_change.line_counts());
         result.insert(path.clone(), protocol_change);
     }
-    result
+    (result, stats)
 }
@@ -2,6 +2,7 @@ use chrono::DateTime;
 use chrono::Utc;
 use serde::Deserialize;
 use serde::Serialize;
+use std::collections::BTreeMap;
 use std::env;
 use std::fs::File;
 use std::fs::OpenOptions;
@@ -17,6 +18,7 @@ use std::time::Duration;
 
 use codex_protocol::mcp_protocol::AuthMode;
 
+use crate::mcp_oauth::McpOAuthTokens;
 use crate::token_data::PlanType;
 use crate::token_data::TokenData;
 use crate::token_data::parse_id_token;
@@ -160,6 +162,7 @@ impl CodexAuth {
                 account_id: Some("account_id".to_string()),
             }),
             last_refresh: Some(Utc::now()),
+            mcp_servers: BTreeMap::new(),
         };
 
         let auth_dot_json = Arc::new(Mutex::new(Some(auth_dot_json)));
@@ -201,9 +204,22 @@ pub fn get_auth_file(codex_home: &Path) -> PathBuf {
 }
 
 /// Delete the auth.json file inside `codex_home` if it exists. Returns `Ok(true)`
-/// if a file was removed, `Ok(false)` if no auth file was present.
+/// if a file was removed, `Ok(false)` if no auth file was present. Tokens for
+/// MCP servers are kept: if there are any, the file is rewritten to hold only
+/// those.
 pub fn logout(codex_home: &Path) -> std::io::Result<bool> {
     let auth_file = get_auth_file(codex_home);
+    let mcp_servers = existing_mcp_server_tokens(&auth_file);
+    if !mcp_servers.is_empty() {
+        let auth_dot_json = AuthDotJson {
+            openai_api_key: None,
+            tokens: None,
+            last_refresh: None,
+            mcp_servers,
+        };
+        write_auth_json(&auth_file, &auth_dot_json)?;
+        return Ok(true);
+    }
     match std::fs::remove_file(&auth_file) {
         Ok(_) => Ok(true),
         Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(false),
@@ -211,14 +227,25 @@ pub fn logout(codex_home: &Path) -> std::io::Result<bool> {
     }
 }
 
-/// Writes an `auth.json` that contains only the API key.
+/// Writes an `auth.json` that contains only the API key (and any tokens
+/// already stored for MCP servers).
 pub fn login_with_api_key(codex_home: &Path, api_key: &str) -> std::io::Result<()> {
+    let auth_file = get_auth_file(codex_home);
     let auth_dot_json = AuthDotJson {
         openai_api_key: Some(api_key.to_string()),
         tokens: None,
         last_refresh: None,
+        mcp_servers: existing_mcp_server_tokens(&auth_file),
     };
-    write_auth_json(&get_auth_file(codex_home), &auth_dot_json)
+    write_auth_json(&auth_file, &auth_dot_json)
+}
+
+/// MCP server tokens stored in `auth_file`, so that replacing the OpenAI
+/// credentials does not log the user out of their MCP servers.
+pub fn existing_mcp_server_tokens(auth_file: &Path) -> BTreeMap<String, McpOAuthTokens> {
+    try_read_auth_json(auth_file)
+        .map(|auth| auth.mcp_servers)
+        .unwrap_or_default()
 }
 
 fn load_auth(codex_home: &Path) -> std::io::Result<Option<CodexAuth>> {
@@ -235,6 +262,7 @@ fn load_auth(codex_home: &Path) -> std::io::Result<Option<CodexAuth>> {
         openai_api_key: auth_json_api_key,
         tokens,
         last_refresh,
+        mcp_servers,
     } = auth_dot_json;
 
     // Prefer AuthMode.ApiKey if it's set in the auth.json.
@@ -242,6 +270,11 @@ fn load_auth(codex_home: &Path) -> std::io::Result<Option<CodexAuth>> {
         return Ok(Some(CodexAuth::from_api_key_with_client(api_key, client)));
     }
 
+    // An auth.json that only holds MCP server tokens does not log in to OpenAI.
+    if tokens.is_none() {
+        return Ok(None);
+    }
+
     Ok(Some(CodexAuth {
         api_key: None,
         mode: AuthMode::ChatGPT,
@@ -250,6 +283,7 @@ fn load_auth(codex_home: &Path) -> std::io::Result<Option<CodexAuth>> {
             openai_api_key: None,
             tokens,
             last_refresh,
+            mcp_servers,
         }))),
         client,
     }))
@@ -361,6 +395,10 @@ pub struct AuthDotJson {
 
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub last_refresh: Option<DateTime<Utc>>,
+
+    /// OAuth tokens for MCP servers, keyed by server name.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub mcp_servers: BTreeMap<String, McpOAuthTokens>,
 }
 
 // Shared constant for token refresh (client id used for oauth token refresh flow)
@@ -476,6 +514,7 @@ mod tests {
                         .unwrap()
                         .with_timezone(&Utc)
                 ),
+                mcp_servers: BTreeMap::new(),
             },
             auth_dot_json
         )
@@ -505,6 +544,7 @@ mod tests {
             openai_api_key: Some("sk-test-key".to_string()),
             tokens: None,
             last_refresh: None,
+            mcp_servers: BTreeMap::new(),
         };
         write_auth_json(&get_auth_file(dir.path()), &auth_dot_json)?;
         assert!(dir.path().join("auth.json").exists());
@@ -0,0 +1,492 @@
+//! Config-driven reactions to protocol events (`[[automations]]`).
+//!
+//! Each rule names an event type (the snake_case `type` of an [`EventMsg`]),
+//! optional filters on fields of that event, and one action: run a command,
+//! send a notification through the `notify` program, or approve an approval
+//! request. Rules are evaluated for every event the session emits, before it
+//! reaches clients.
+
+use std::collections::BTreeMap;
+use std::process::Stdio;
+use std::sync::atomic::AtomicBool;
+use std::sync::atomic::Ordering;
+use std::time::Duration;
+
+use regex_lite::Regex;
+use serde::Deserialize;
+use serde_json::Value;
+use tracing::warn;
+
+use crate::config::WasmHooksConfig;
+use crate::protocol::EventMsg;
+use crate::wasm_hooks::run_hook_process;
+
+/// Timeout for `run` actions that do not set their own `timeout_ms`.
+const DEFAULT_RUN_TIMEOUT_MS: u64 = 10_000;
+
+/// Events that an `approve` action can answer.
+const APPROVAL_EVENTS: &[&str] = &["exec_approval_request", "apply_patch_approval_request"];
+
+/// One `[[automations]]` entry as written in `config.toml`.
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct AutomationToml {
+    /// Shown in logs and notifications; defaults to the event type.
+    #[serde(default)]
+    pub name: Option<String>,
+    /// Event type to react to, e.g. `stream_error` or `token_count`.
+    pub on: String,
+    /// Filters keyed by a dotted path into the event, e.g.
+    /// `info.total_token_usage.total_tokens`. All must match.
+    #[serde(default)]
+    pub when: BTreeMap<String, FieldFilterToml>,
+    /// Fire at most once per session.
+    #[serde(default)]
+    pub once: Option<bool>,
+    /// Command to run with the event JSON appended as its last argument.
+    #[serde(default)]
+    pub run: Option<Vec<String>>,
+    #[serde(default)]
+    pub timeout_ms: Option<u64>,
+    /// Message to send through the `notify` program.
+    #[serde(default)]
+    pub notify: Option<String>,
+    /// Approve the matching approval request without asking the user.
+    #[serde(default)]
+    pub approve: Option<bool>,
+}
+
+/// A filter on one event field: a bool or number that must be equal, a
+/// string that must match (`/regex/` or exact text), or a numeric range.
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[serde(untagged)]
+pub enum FieldFilterToml {
+    Bool(bool),
+    Number(f64),
+    Text(String),
+    Range {
+        #[serde(default)]
+        gt: Option<f64>,
+        #[serde(default)]
+        gte: Option<f64>,
+        #[serde(default)]
+        lt: Option<f64>,
+        #[serde(default)]
+        lte: Option<f64>,
+    },
+}
+
+#[derive(Debug, Clone)]
+pub enum FieldFilter {
+    Bool(bool),
+    Number(f64),
+    Exact(String),
+    Regex(Regex),
+    Range {
+        gt: Option<f64>,
+        gte: Option<f64>,
+        lt: Option<f64>,
+        lte: Option<f64>,
+    },
+}
+
+impl FieldFilter {
+    fn from_toml(toml: FieldFilterToml) -> std::io::Result<Self> {
+        Ok(match toml {
+            FieldFilterToml::Bool(value) => FieldFilter::Bool(value),
+            FieldFilterToml::Number(value) => FieldFilter::Number(value),
+            FieldFilterToml::Text(text) => match text
+                .strip_prefix('/')
+                .and_then(|rest| rest.strip_suffix('/'))
+            {
+                Some(source) => FieldFilter::Regex(Regex::new(source).map_err(|e| {
+                    invalid_data(format!("invalid automation filter `{text}`: {e}"))
+                })?),
+                None => FieldFilter::Exact(text),
+            },
+            FieldFilterToml::Range { gt, gte, lt, lte } => FieldFilter::Range { gt, gte, lt, lte },
+        })
+    }
+
+    /// Strings match text filters; an array of strings (such as a command's
+    /// argv) is matched joined with spaces.
+    fn matches(&self, value: &Value) -> bool {
+        match self {
+            FieldFilter::Bool(expected) => value.as_bool() == Some(*expected),
+            FieldFilter::Number(expected) => value.as_f64() == Some(*expected),
+            FieldFilter::Exact(expected) => text_of(value).is_some_and(|text| text == *expected),
+            FieldFilter::Regex(regex) => text_of(value).is_some_and(|text| regex.is_match(&text)),
+            FieldFilter::Range { gt, gte, lt, lte } => value.as_f64().is_some_and(|n| {
+                gt.is_none_or(|bound| n > bound)
+                    && gte.is_none_or(|bound| n >= bound)
+                    && lt.is_none_or(|bound| n < bound)
+                    && lte.is_none_or(|bound| n <= bound)
+            }),
+        }
+    }
+}
+
+impl PartialEq for FieldFilter {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (FieldFilter::Bool(a), FieldFilter::Bool(b)) => a == b,
+            (FieldFilter::Number(a), FieldFilter::Number(b)) => a == b,
+            (FieldFilter::Exact(a), FieldFilter::Exact(b)) => a == b,
+            (FieldFilter::Regex(a), FieldFilter::Regex(b)) => a.as_str() == b.as_str(),
+            (
+                FieldFilter::Range { gt, gte, lt, lte },
+                FieldFilter::Range {
+                    gt: other_gt,
+                    gte: other_gte,
+                    lt: other_lt,
+                    lte: other_lte,
+                },
+            ) => gt == other_gt && gte == other_gte && lt == other_lt && lte == other_lte,
+            _ => false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AutomationAction {
+    Run { argv: Vec<String>, timeout_ms: u64 },
+    Notify(String),
+    Approve,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Automation {
+    pub name: String,
+    pub on: String,
+    pub when: Vec<(String, FieldFilter)>,
+    pub once: bool,
+    pub action: AutomationAction,
+}
+
+impl Automation {
+    pub(crate) fn vec_from_toml(toml: Option<Vec<AutomationToml>>) -> std::io::Result<Vec<Self>> {
+        toml.unwrap_or_default()
+            .into_iter()
+            .map(Automation::from_toml)
+            .collect()
+    }
+
+    fn from_toml(toml: AutomationToml) -> std::io::Result<Self> {
+        let AutomationToml {
+            name,
+            on,
+            when,
+            once,
+            run,
+            timeout_ms,
+            notify,
+            approve,
+        } = toml;
+        let name = name.unwrap_or_else(|| on.clone());
+        let mut actions = Vec::new();
+        if let Some(argv) = run.filter(|argv| !argv.is_empty()) {
+            actions.push(AutomationAction::Run {
+                argv,
+                timeout_ms: timeout_ms.unwrap_or(DEFAULT_RUN_TIMEOUT_MS),
+            });
+        }
+        if let Some(message) = notify {
+            actions.push(AutomationAction::Notify(message));
+        }
+        if approve == Some(true) {
+            if !APPROVAL_EVENTS.contains(&on.as_str()) {
+                return Err(invalid_data(format!(
+                    "automation `{name}`: `approve` only applies to {}",
+                    APPROVAL_EVENTS.join(" and ")
+                )));
+            }
+            actions.push(AutomationAction::Approve);
+        }
+        let action = match <[AutomationAction; 1]>::try_from(actions) {
+            Ok([action]) => action,
+            Err(_) => {
+                return Err(invalid_data(format!(
+                    "automation `{name}` must set exactly one of `run`, `notify` or `approve`"
+                )));
+            }
+        };
+        let when = when
+            .into_iter()
+            .map(|(path, filter)| Ok((path, FieldFilter::from_toml(filter)?)))
+            .collect::<std::io::Result<_>>()?;
+        Ok(Automation {
+            name,
+            on,
+            when,
+            once: once.unwrap_or(false),
+            action,
+        })
+    }
+
+    fn matches(&self, event_type: &str, event: &Value) -> bool {
+        self.on == event_type
+            && self
+                .when
+                .iter()
+                .all(|(path, filter)| lookup(event, path).is_some_and(|v| filter.matches(v)))
+    }
+}
+
+/// The session's automation rules and which `once` rules already fired.
+#[derive(Debug, Default)]
+pub(crate) struct Automations {
+    rules: Vec<Automation>,
+    fired: Vec<AtomicBool>,
+}
+
+impl Automations {
+    pub(crate) fn new(rules: Vec<Automation>) -> Self {
+        let fired = rules.iter().map(|_| AtomicBool::new(false)).collect();
+        Self { rules, fired }
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    /// The rules that fire for `msg`, marking `once` rules as used.
+    pub(crate) fn matching(&self, msg: &EventMsg) -> Vec<&Automation> {
+        if self.rules.is_empty() {
+            return Vec::new();
+        }
+        let event_type = msg.to_string();
+        let Ok(event) = serde_json::to_value(msg) else {
+            return Vec::new();
+        };
+        self.rules
+            .iter()
+            .zip(&self.fired)
+            .filter(|(rule, fired)| {
+                rule.matches(&event_type, &event)
+                    && !(rule.once && fired.swap(true, Ordering::SeqCst))
+            })
+            .map(|(rule, _)| rule)
+            .collect()
+    }
+}
+
+/// Starts a `run` action in the background with the event JSON as its last
+/// argument and `CODEX_EVENT`/`SUB_ID` in its environment. Failures are
+/// logged.
+pub(crate) fn spawn_run_action(
+    automation: &Automation,
+    sub_id: &str,
+    msg: &EventMsg,
+    wasm: &WasmHooksConfig,
+) {
+    let AutomationAction::Run { argv, timeout_ms } = &automation.action else {
+        return;
+    };
+    let Some((program, args)) = argv.split_first() else {
+        return;
+    };
+    let json = match serde_json::to_string(msg) {
+        Ok(json) => json,
+        Err(e) => {
+            warn!(
+                "failed to serialize event for automation `{}`: {e}",
+                automation.name
+            );
+            return;
+        }
+    };
+    let mut cmd = tokio::process::Command::new(program);
+    cmd.args(args)
+        .arg(json)
+        .env("CODEX_EVENT", msg.to_string())
+        .env("SUB_ID", sub_id)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true);
+
+    let name = automation.name.clone();
+    let timeout_ms = *timeout_ms;
+    let wasm = wasm.clone();
+    tokio::spawn(async move {
+        match run_hook_process(&mut cmd, &wasm, Duration::from_millis(timeout_ms)).await {
+            None => warn!("automation `{name}` timed out after {timeout_ms} ms"),
+            Some(Err(e)) => warn!("automation `{name}` failed to spawn: {e}"),
+            Some(Ok(output)) if !output.status.success() => {
+                let code = output.status.code().unwrap_or(-1);
+                let stderr = String::from_utf8_lossy(&output.stderr);
+                let snippet: String = stderr.chars().take(512).collect();
+                warn!("automation `{name}` exited with code {code}: {snippet}");
+            }
+            Some(Ok(_)) => {}
+        }
+    });
+}
+
+/// Follows a dotted path such as `info.total_token_usage.total_tokens`;
+/// numeric segments index into arrays.
+fn lookup<'a>(value: &'a Value, path: &str) -> Option<&'a Value> {
+    path.split('.')
+        .try_fold(value, |value, segment| match value {
+            Value::Object(map) => map.get(segment),
+            Value::Array(items) => items.get(segment.parse::<usize>().ok()?),
+            _ => None,
+        })
+}
+
+fn text_of(value: &Value) -> Option<String> {
+    match value {
+        Value::String(text) => Some(text.clone()),
+        Value::Array(items) => items
+            .iter()
+            .map(|item| item.as_str())
+            .collect::<Option<Vec<_>>>()
+            .map(|parts| parts.join(" ")),
+        _ => None,
+    }
+}
+
+fn invalid_data(message: String) -> std::io::Error {
+    std::io::Error::new(std::io::ErrorKind::InvalidData, message)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::protocol::ExecApprovalRequestEvent;
+    use crate::protocol::StreamErrorEvent;
+    use pretty_assertions::assert_eq;
+    use std::path::PathBuf;
+
+    fn automations(toml_src: &str) -> std::io::Result<Vec<Automation>> {
+        #[derive(Deserialize)]
+        struct Root {
+            automations: Option<Vec<AutomationToml>>,
+        }
+        let root: Root = toml::from_str(toml_src).expect("valid toml");
+        Automation::vec_from_toml(root.automations)
+    }
+
+    fn names(matching: Vec<&Automation>) -> Vec<&str> {
+        matching.iter().map(|rule| rule.name.as_str()).collect()
+    }
+
+    #[test]
+    fn rules_match_event_type_and_filters() -> std::io::Result<()> {
+        let rules = Automations::new(automations(
+            r#"
+            [[automations]]
+            name = "any-stream-error"
+            on = "stream_error"
+            notify = "stream error"
+
+            [[automations]]
+            name = "timeouts"
+            on = "stream_error"
+            when = { message = "/timed? ?out/" }
+            run = ["log-timeout"]
+
+            [[automations]]
+            name = "cargo"
+            on = "exec_approval_request"
+            when = { command = "/^cargo (test|check)( |$)/" }
+            approve = true
+            "#,
+        )?);
+
+        let stream_error = |message: &str| {
+            EventMsg::StreamError(StreamErrorEvent {
+                message: message.to_string(),
+            })
+        };
+        assert_eq!(
+            names(rules.matching(&stream_error("stream timed out"))),
+            vec!["any-stream-error", "timeouts"]
+        );
+        assert_eq!(
+            names(rules.matching(&stream_error("disconnected"))),
+            vec!["any-stream-error"]
+        );
+
+        let approval = |command: &[&str]| {
+            EventMsg::ExecApprovalRequest(ExecApprovalRequestEvent {
+                call_id: "call-1".to_string(),
+                command: command.iter().map(|arg| arg.to_string()).collect(),
+                cwd: PathBuf::from("/repo"),
+                reason: None,
+            })
+        };
+        assert_eq!(
+            names(rules.matching(&approval(&["cargo", "test", "-p", "core"]))),
+            vec!["cargo"]
+        );
+        assert!(rules.matching(&approval(&["cargo", "publish"])).is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn range_filters_and_once() -> std::io::Result<()> {
+        let rules = Automations::new(automations(
+            r#"
+            [[automations]]
+            on = "token_count"
+            when = { "info.total_token_usage.total_tokens" = { gte = 100 } }
+            notify = "over budget"
+
+            [[automations]]
+            name = "first-error"
+            on = "stream_error"
+            once = true
+            notify = "first error"
+            "#,
+        )?);
+
+        let budget = &rules.rules[0];
+        assert_eq!(budget.name, "token_count");
+        let tokens = |total: u64| serde_json::json!({ "info": { "total_token_usage": { "total_tokens": total } } });
+        assert!(budget.matches("token_count", &tokens(150)));
+        assert!(!budget.matches("token_count", &tokens(99)));
+        assert!(!budget.matches("token_count", &serde_json::json!({ "info": null })));
+
+        let msg = EventMsg::StreamError(StreamErrorEvent {
+            message: "boom".to_string(),
+        });
+        assert_eq!(names(rules.matching(&msg)), vec!["first-error"]);
+        assert!(rules.matching(&msg).is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn invalid_rules_are_rejected() {
+        let err = automations(
+            r#"
+            [[automations]]
+            on = "stream_error"
+            notify = "a"
+            run = ["b"]
+            "#,
+        )
+        .expect_err("two actions");
+        assert!(err.to_string().contains("exactly one of"));
+
+        let err = automations(
+            r#"
+            [[automations]]
+            on = "stream_error"
+            approve = true
+            "#,
+        )
+        .expect_err("approve on a non-approval event");
+        assert!(err.to_string().contains("`approve` only applies to"));
+
+        let err = automations(
+            r#"
+            [[automations]]
+            on = "stream_error"
+            when = { message = "/(/" }
+            notify = "a"
+            "#,
+        )
+        .expect_err("invalid regex");
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+    }
+}
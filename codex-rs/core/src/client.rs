@@ -32,6 +32,7 @@ use crate::client_common::ResponsesApiRequest;
 use crate::client_common::create_reasoning_param_for_request;
 use crate::client_common::create_text_param_for_request;
 use crate::config::Config;
+use crate::config_types::ModelPricing;
 use crate::default_client::create_client;
 use crate::error::CodexErr;
 use crate::error::Result;
@@ -108,10 +109,28 @@ impl ModelClient {
             .or_else(|| get_model_info(&self.config.model_family).map(|info| info.context_window))
     }
 
+    /// Prices of the model, from `model_pricing` or the built-in list prices.
+    pub fn get_model_pricing(&self) -> Option<ModelPricing> {
+        self.config
+            .model_pricing
+            .or_else(|| get_model_info(&self.config.model_family).and_then(|info| info.pricing))
+    }
+
+    /// The lower of the configured (or built-in) token limit and the share
+    /// of the context window set by `model_auto_compact_context_percent`.
     pub fn get_auto_compact_token_limit(&self) -> Option<i64> {
-        self.config.model_auto_compact_token_limit.or_else(|| {
+        let token_limit = self.config.model_auto_compact_token_limit.or_else(|| {
             get_model_info(&self.config.model_family).and_then(|info| info.auto_compact_token_limit)
-        })
+        });
+        let context_limit = self
+            .config
+            .model_auto_compact_context_percent
+            .zip(self.get_model_context_window())
+            .map(|(percent, window)| (window.saturating_mul(u64::from(percent)) / 100) as i64);
+        match (token_limit, context_limit) {
+            (Some(tokens), Some(context)) => Some(tokens.min(context)),
+            (tokens, context) => tokens.or(context),
+        }
     }
 
     /// Dispatches to either the Responses or Chat implementation depending on
@@ -216,7 +235,7 @@ impl ModelClient {
             input: &input_with_instructions,
             tools: &tools_json,
             tool_choice: "auto",
-            parallel_tool_calls: false,
+            parallel_tool_calls: self.config.max_parallel_tool_calls > 1,
             reasoning,
             store: azure_workaround,
             stream: true,
@@ -1,41 +1,72 @@
 use std::borrow::Cow;
+use std::collections::BTreeMap;
 use std::collections::HashMap;
+use std::io::Write;
 use std::path::Path;
 use std::path::PathBuf;
 use std::process::Stdio;
 use std::sync::Arc;
+use std::sync::OnceLock;
 use std::sync::atomic::AtomicU64;
 use std::time::Duration;
+use std::time::Instant;
 
 use crate::AuthManager;
+use crate::automations::AutomationAction;
+use crate::automations::Automations;
+use crate::automations::spawn_run_action;
 use crate::client_common::REVIEW_PROMPT;
 use crate::event_mapping::map_response_item_to_event_messages;
 use crate::function_tool::FunctionCallError;
 use crate::review_format::format_review_findings_block;
 use crate::user_notification::UserNotifier;
+use crate::wasm_hooks::run_hook_process;
 use async_channel::Receiver;
 use async_channel::Sender;
 use codex_apply_patch::ApplyPatchAction;
 use codex_apply_patch::MaybeApplyPatchVerified;
-use codex_apply_patch::maybe_parse_apply_patch_verified;
+use codex_apply_patch::maybe_parse_apply_patch_verified_with_base;
+use codex_git_tooling::CreateGhostCommitOptions;
+use codex_git_tooling::GhostCommitRetention;
+use codex_git_tooling::GitToolingError;
+use codex_git_tooling::changed_paths;
+use codex_git_tooling::create_ghost_commit;
+use codex_git_tooling::diff_ghost_commits;
+use codex_git_tooling::leave_work_branch;
+use codex_git_tooling::prune_ghost_commits;
+use codex_git_tooling::read_file_at_commit;
+use codex_git_tooling::record_ghost_commit;
+use codex_git_tooling::repo_status;
+use codex_git_tooling::restore_ghost_commit;
+use codex_git_tooling::start_work_branch;
 use codex_protocol::mcp_protocol::ConversationId;
 use codex_protocol::protocol::ConversationPathResponseEvent;
 use codex_protocol::protocol::ExitedReviewModeEvent;
+use codex_protocol::protocol::ListSnapshotsResponseEvent;
 use codex_protocol::protocol::ReviewRequest;
+use codex_protocol::protocol::RewoundItem;
 use codex_protocol::protocol::RolloutItem;
+use codex_protocol::protocol::SessionDisposition;
+use codex_protocol::protocol::SessionRemovedEvent;
+use codex_protocol::protocol::SessionStateSnapshot;
+use codex_protocol::protocol::SnapshotDiffEvent;
+use codex_protocol::protocol::SnapshotInfo;
 use codex_protocol::protocol::TaskStartedEvent;
 use codex_protocol::protocol::TurnAbortReason;
 use codex_protocol::protocol::TurnAbortedEvent;
 use codex_protocol::protocol::TurnContextItem;
 use futures::prelude::*;
 use mcp_types::CallToolResult;
+use mcp_types::ReadResourceResultContents;
 use serde::Deserialize;
 use serde::Serialize;
 use serde_json;
 use serde_json::Value;
 use tokio::sync::Mutex;
 use tokio::sync::oneshot;
-use tokio::task::AbortHandle;
+use tokio::task::JoinHandle;
+use tokio_util::sync::CancellationToken;
+use tokio_util::sync::DropGuard;
 use tracing::debug;
 use tracing::error;
 use tracing::info;
@@ -51,11 +82,30 @@ use crate::apply_patch::convert_apply_patch_to_protocol;
 use crate::client::ModelClient;
 use crate::client_common::Prompt;
 use crate::client_common::ResponseEvent;
+use crate::codex_ignore::CODEX_IGNORE_FILENAME;
+use crate::codex_ignore::CodexIgnore;
+use crate::command_log;
+use crate::command_pattern::pattern_for_command;
+use crate::command_pattern::pattern_matches_any;
 use crate::config::Config;
+use crate::config::HOOK_DRY_RUN_ENV_VAR;
+use crate::config::HookCommand;
+use crate::config::HookDryRun;
+use crate::config::HookRule;
 use crate::config::HooksConfig;
+use crate::config::add_approved_command_pattern;
+use crate::config_types::BusyInput;
+use crate::config_types::ExecConfig;
+use crate::config_types::McpServerConfig;
+use crate::config_types::PermissionDecision;
+use crate::config_types::ResourceLimits;
+use crate::config_types::SandboxExtras;
 use crate::config_types::ShellEnvironmentPolicy;
+use crate::config_types::TaskBudget;
 use crate::conversation_history::ConversationHistory;
 use crate::environment_context::EnvironmentContext;
+use crate::environment_context::GitStatusContext;
+use crate::environment_context::LocaleContext;
 use crate::error::CodexErr;
 use crate::error::Result as CodexResult;
 use crate::error::SandboxErr;
@@ -66,69 +116,133 @@ use crate::exec::SandboxType;
 use crate::exec::StdoutStream;
 use crate::exec::StreamOutput;
 use crate::exec::process_exec_tool_call;
+use crate::exec_cache::ExecOutputCache;
 use crate::exec_command::EXEC_COMMAND_TOOL_NAME;
 use crate::exec_command::ExecCommandParams;
+use crate::exec_command::ExecCommandSpawn;
 use crate::exec_command::ExecSessionManager;
+use crate::exec_command::JOB_OUTPUT_TOOL_NAME;
+use crate::exec_command::JobOutputParams;
+use crate::exec_command::KILL_JOB_TOOL_NAME;
+use crate::exec_command::KillJobParams;
+use crate::exec_command::LIST_JOBS_TOOL_NAME;
 use crate::exec_command::WRITE_STDIN_TOOL_NAME;
 use crate::exec_command::WriteStdinParams;
+use crate::exec_env::apply_session_env;
 use crate::exec_env::create_env;
+use crate::exec_env::create_env_with_overrides;
+use crate::exec_env::is_sensitive_env_var;
+use crate::is_safe_command::is_known_safe_command;
+use crate::live_sessions::LiveSessionFile;
+use crate::loop_detector::LoopDetector;
+use crate::loop_detector::TurnToolCall;
 use crate::mcp_connection_manager::McpConnectionManager;
 use crate::mcp_tool_call::handle_mcp_tool_call;
 use crate::model_family::find_family_for_model;
 use crate::openai_model_info::get_model_info;
 use crate::openai_tools::ApplyPatchToolArgs;
+use crate::openai_tools::OpenAiTool;
 use crate::openai_tools::ToolsConfig;
 use crate::openai_tools::ToolsConfigParams;
+use crate::openai_tools::create_mcp_resource_tools;
 use crate::openai_tools::get_openai_tools;
 use crate::parse_command::parse_command;
+use crate::path_utils;
+use crate::path_utils::display_path;
+use crate::permission_gate::PermissionGate;
+use crate::permission_gate::command_detail;
+use crate::permission_gate::is_network_command;
+use crate::permission_gate::paths_outside;
+use crate::plan_tool::UpdatePlanArgs;
 use crate::plan_tool::handle_update_plan;
 use crate::project_doc::get_user_instructions;
+use crate::prompt_preview::build_prompt_preview;
 use crate::protocol::AgentMessageDeltaEvent;
+use crate::protocol::AgentMessageEvent;
 use crate::protocol::AgentReasoningDeltaEvent;
 use crate::protocol::AgentReasoningRawContentDeltaEvent;
 use crate::protocol::AgentReasoningSectionBreakEvent;
 use crate::protocol::ApplyPatchApprovalRequestEvent;
 use crate::protocol::AskForApproval;
+use crate::protocol::AutoCompactEvent;
 use crate::protocol::BackgroundEventEvent;
+use crate::protocol::CheckpointCreatedEvent;
+use crate::protocol::CheckpointRewoundEvent;
 use crate::protocol::ErrorEvent;
+use crate::protocol::EscalatedPermission;
+use crate::protocol::EscalationRequestEvent;
 use crate::protocol::Event;
 use crate::protocol::EventMsg;
 use crate::protocol::ExecApprovalRequestEvent;
 use crate::protocol::ExecCommandBeginEvent;
+use crate::protocol::ExecCommandDryRunEvent;
 use crate::protocol::ExecCommandEndEvent;
+use crate::protocol::ExecSessionKilledEvent;
 use crate::protocol::FileChange;
+use crate::protocol::GuidedPermission;
+use crate::protocol::HookBeginEvent;
+use crate::protocol::HookEndEvent;
+use crate::protocol::HookKind;
 use crate::protocol::InputItem;
 use crate::protocol::ListCustomPromptsResponseEvent;
 use crate::protocol::Op;
 use crate::protocol::PatchApplyBeginEvent;
 use crate::protocol::PatchApplyEndEvent;
+use crate::protocol::PatchPathEscape;
+use crate::protocol::PermissionConsentRequestEvent;
+use crate::protocol::QueuedTask;
 use crate::protocol::RateLimitSnapshot;
 use crate::protocol::ReviewDecision;
 use crate::protocol::ReviewOutputEvent;
 use crate::protocol::SandboxPolicy;
 use crate::protocol::SessionConfiguredEvent;
+use crate::protocol::SessionEnvResponseEvent;
+use crate::protocol::SpendLimitApprovalRequestEvent;
 use crate::protocol::StreamErrorEvent;
 use crate::protocol::Submission;
+use crate::protocol::TaskBudgetLimit;
 use crate::protocol::TaskCompleteEvent;
+use crate::protocol::TaskQueueUpdatedEvent;
+use crate::protocol::TaskStalledEvent;
 use crate::protocol::TokenCountEvent;
 use crate::protocol::TokenUsage;
+use crate::protocol::ToolCallCancelledEvent;
 use crate::protocol::TurnDiffEvent;
+use crate::protocol::UndoCompletedEvent;
+use crate::protocol::WarningEvent;
 use crate::protocol::WebSearchBeginEvent;
 use crate::rollout::RolloutRecorder;
 use crate::rollout::RolloutRecorderParams;
+use crate::rollout::trash::archive_rollout;
+use crate::rollout::trash::purge_trash;
+use crate::rollout::trash::trash_rollout;
 use crate::safety::SafetyCheck;
 use crate::safety::assess_command_safety;
 use crate::safety::assess_safety_for_untrusted_command;
+use crate::safety::read_only_sandbox_for;
+use crate::scratch_dir::ScratchDir;
 use crate::shell;
 use crate::state::ActiveTurn;
+use crate::state::Checkpoint;
+use crate::state::GhostSnapshot;
+use crate::state::PendingTask;
 use crate::state::SessionServices;
+use crate::state::TaskWorkBranch;
+use crate::storage_guard::StorageGuard;
+use crate::storage_guard::StorageTransition;
+use crate::storage_guard::low_storage_warning;
+use crate::storage_guard::reduce_for_low_storage;
+use crate::truncate::truncate_middle;
 use crate::turn_diff_tracker::TurnDiffTracker;
 use crate::unified_exec::UnifiedExecSessionManager;
 use crate::user_instructions::UserInstructions;
 use crate::user_notification::UserNotification;
 use crate::util::backoff;
+use crate::vendored_tools;
+use crate::windows_sandbox::unenforced_network_warning;
 use codex_protocol::config_types::ReasoningEffort as ReasoningEffortConfig;
 use codex_protocol::config_types::ReasoningSummary as ReasoningSummaryConfig;
+use codex_protocol::config_types::StallAction;
 use codex_protocol::custom_prompts::CustomPrompt;
 use codex_protocol::models::ContentItem;
 use codex_protocol::models::FunctionCallOutputPayload;
@@ -139,8 +253,13 @@ use codex_protocol::models::ShellToolCallParams;
 use codex_protocol::protocol::InitialHistory;
 
 pub mod compact;
+mod completion;
+mod final_output;
+pub(crate) mod sub_agent;
 use self::compact::build_compacted_history;
 use self::compact::collect_user_messages;
+use self::compact::recent_messages;
+use self::sub_agent::SubAgentManager;
 
 /// The high-level interface to the Codex system.
 /// It operates as a queue pair where you send submissions and receive events.
@@ -283,16 +402,25 @@ pub(crate) struct TurnContext {
     pub(crate) approval_policy: AskForApproval,
     pub(crate) sandbox_policy: SandboxPolicy,
     pub(crate) shell_environment_policy: ShellEnvironmentPolicy,
+    pub(crate) resource_limits: ResourceLimits,
+    pub(crate) sandbox_extras: SandboxExtras,
     pub(crate) tools_config: ToolsConfig,
     pub(crate) is_review_mode: bool,
     pub(crate) final_output_json_schema: Option<Value>,
+    /// Report shell commands instead of executing them.
+    pub(crate) dry_run: bool,
+    /// Timeouts for `shell` calls that do not set `timeout_ms`.
+    pub(crate) exec: ExecConfig,
+    /// Limits checked by `run_task` before each model request.
+    pub(crate) task_budget: TaskBudget,
 }
 
 impl TurnContext {
     fn resolve_path(&self, path: Option<String>) -> PathBuf {
-        path.as_ref()
-            .map(PathBuf::from)
-            .map_or_else(|| self.cwd.clone(), |p| self.cwd.join(p))
+        path.as_ref().map(PathBuf::from).map_or_else(
+            || self.cwd.clone(),
+            |p| path_utils::resolve_path(&self.cwd, &p),
+        )
     }
 }
 
@@ -383,7 +511,11 @@ impl Session {
         // - load history metadata
         let rollout_fut = RolloutRecorder::new(&config, rollout_params);
 
-        let mcp_fut = McpConnectionManager::new(config.mcp_servers.clone());
+        let mcp_fut = McpConnectionManager::new(
+            config.mcp_servers.clone(),
+            config.codex_home.clone(),
+            tx_event.clone(),
+        );
         let default_shell_fut = shell::default_user_shell();
         let history_meta_fut = crate::message_history::history_metadata(&config);
 
@@ -397,7 +529,16 @@ impl Session {
         })?;
         let rollout_path = rollout_recorder.rollout_path.clone();
         // Create the mutable state for the Session.
-        let state = SessionState::new();
+        let mut state = SessionState::new();
+        for pattern in &config.approved_command_patterns {
+            state.add_approved_command_pattern(pattern.clone());
+        }
+        let restored_snapshot = match &initial_history {
+            InitialHistory::Resumed(resumed_history) => {
+                last_state_snapshot(&resumed_history.history)
+            }
+            InitialHistory::New | InitialHistory::Forked(_) => None,
+        };
 
         // Handle MCP manager result and record any startup failures.
         let (mcp_connection_manager, failed_clients) = match mcp_res {
@@ -444,6 +585,8 @@ impl Session {
                 include_web_search_request: config.tools_web_search_request,
                 use_streamable_shell_tool: config.use_experimental_streamable_shell_tool,
                 include_view_image_tool: config.include_view_image_tool,
+                include_scratch_path_tool: config.scratch_dir,
+                include_delegate_tool: config.tools_delegate,
                 experimental_unified_exec_tool: config.use_experimental_unified_exec_tool,
             }),
             user_instructions,
@@ -451,9 +594,25 @@ impl Session {
             approval_policy,
             sandbox_policy,
             shell_environment_policy: config.shell_environment_policy.clone(),
+            resource_limits: config.resource_limits,
+            sandbox_extras: config.sandbox_extras.clone(),
             cwd,
             is_review_mode: false,
             final_output_json_schema: None,
+            dry_run: false,
+            exec: config.exec.clone(),
+            task_budget: config.task_budget,
+        };
+        let scratch_dir = if config.scratch_dir {
+            match ScratchDir::create(config.retain_scratch_dir) {
+                Ok(scratch_dir) => Some(scratch_dir),
+                Err(err) => {
+                    warn!("failed to create scratch dir: {err}");
+                    None
+                }
+            }
+        } else {
+            None
         };
         let services = SessionServices {
             mcp_connection_manager,
@@ -465,7 +624,69 @@ impl Session {
             user_shell: default_shell,
             show_raw_agent_reasoning: config.show_raw_agent_reasoning,
             hooks,
+            repeated_command_failure_limit: config.repeated_command_failure_limit,
+            max_parallel_tool_calls: config.max_parallel_tool_calls,
+            sub_agents: SubAgentManager::default(),
+            stall_detection_turns: config.stall_detection_turns,
+            stall_action: config.stall_action,
+            busy_input: config.busy_input,
+            max_session_cost_usd: config.max_session_cost_usd,
+            auto_compact_preserve_messages: config.auto_compact_preserve_messages,
+            task_completion_assessment: config.task_completion_assessment,
+            storage_guard: StorageGuard::new(config.codex_home.clone(), config.storage),
+            codex_home: config.codex_home.clone(),
+            permission_gate: PermissionGate::new(&config),
+            scratch_dir,
+            locale_context: config.locale_context,
+            git_auto_branch: config.git_auto_branch,
+            command_provenance: config.command_provenance,
+            output_streaming: config.output_streaming,
+            automations: Automations::new(config.automations.clone()),
+            vendored_bin_dir: Arc::new(OnceLock::new()),
+            live_session: Some(LiveSessionFile::create(
+                &config.codex_home,
+                conversation_id,
+                turn_context.cwd.clone(),
+            )),
+            exec_output_cache: config
+                .exec
+                .cache_read_only_commands
+                .then(ExecOutputCache::new),
         };
+        if config.tools_download_ripgrep {
+            let codex_home = config.codex_home.clone();
+            let vendored_bin_dir = Arc::clone(&services.vendored_bin_dir);
+            tokio::spawn(async move {
+                match vendored_tools::ensure_ripgrep(&codex_home).await {
+                    Ok(Some(dir)) => {
+                        let _ = vendored_bin_dir.set(dir);
+                    }
+                    Ok(None) => {}
+                    Err(err) => warn!("failed to download ripgrep: {err}"),
+                }
+            });
+        }
+        // Check storage up front so the warning follows SessionConfigured
+        // rather than being sent while the initial history is persisted.
+        if let StorageTransition::BecameLow(reason) = services.storage_guard.poll().await {
+            warn!("session storage is low: {reason}");
+            post_session_configured_error_events.push(Event {
+                id: INITIAL_SUBMIT_ID.to_owned(),
+                msg: EventMsg::Warning(WarningEvent {
+                    message: low_storage_warning(&reason),
+                }),
+            });
+        }
+        if let Some(message) = unenforced_network_warning(&turn_context.sandbox_policy) {
+            post_session_configured_error_events.push(Event {
+                id: INITIAL_SUBMIT_ID.to_owned(),
+                msg: EventMsg::Warning(WarningEvent { message }),
+            });
+        }
+        if let Some(snapshot) = restored_snapshot {
+            post_session_configured_error_events.extend(restored_state_events(&snapshot));
+            state.restore_state_snapshot(snapshot);
+        }
 
         let sess = Arc::new(Session {
             conversation_id,
@@ -499,6 +720,8 @@ impl Session {
             sess.send_event(event).await;
         }
 
+        sess.run_session_start_hook(&turn_context).await;
+
         Ok((sess, turn_context))
     }
 
@@ -507,6 +730,9 @@ impl Session {
         if let Some(current_task) = state.current_task.take() {
             current_task.abort(TurnAbortReason::Replaced);
         }
+        // A new turn may follow changes made outside the agent, so earlier
+        // failures no longer predict the outcome of a retry.
+        state.clear_failed_commands();
         state.current_task = Some(task);
         if let Some(current_task) = &state.current_task {
             let mut active = self.active_turn.lock().await;
@@ -519,6 +745,87 @@ impl Session {
         }
     }
 
+    /// With `busy_input = "queue"`, queues `items` as a task of its own while
+    /// a task is running or others are still waiting. Returns the input when
+    /// it should be handled right away instead.
+    async fn queue_task(
+        &self,
+        sub_id: &str,
+        items: Vec<InputItem>,
+        turn_context: &Arc<TurnContext>,
+    ) -> Result<(), Vec<InputItem>> {
+        if self.services.busy_input != BusyInput::Queue {
+            return Err(items);
+        }
+        let tasks = {
+            let mut state = self.state.lock().await;
+            if state.current_task.is_none() && state.queued_tasks.is_empty() {
+                return Err(items);
+            }
+            state.queued_tasks.push_back(PendingTask {
+                sub_id: sub_id.to_string(),
+                items,
+                turn_context: Arc::clone(turn_context),
+            });
+            state.queued_task_infos()
+        };
+        self.send_task_queue_updated(sub_id, tasks).await;
+        Ok(())
+    }
+
+    /// Removes the queued task submitted as `id`, if it has not started yet.
+    async fn cancel_queued_task(&self, sub_id: &str, id: &str) {
+        let tasks = {
+            let mut state = self.state.lock().await;
+            state.queued_tasks.retain(|task| task.sub_id != id);
+            state.queued_task_infos()
+        };
+        self.send_task_queue_updated(sub_id, tasks).await;
+    }
+
+    /// Starts the oldest queued task unless a task is running. The task stays
+    /// at the head of the queue until it is set, so input submitted meanwhile
+    /// is queued behind it rather than started first.
+    async fn start_next_queued_task(self: &Arc<Self>) {
+        let next = {
+            let state = self.state.lock().await;
+            if state.current_task.is_some() {
+                return;
+            }
+            match state.queued_tasks.front() {
+                Some(next) => next.clone(),
+                None => return,
+            }
+        };
+        let PendingTask {
+            sub_id,
+            items,
+            turn_context,
+        } = next;
+        let task = AgentTask::spawn(Arc::clone(self), turn_context, sub_id.clone(), items);
+        self.set_task(task).await;
+        let tasks = {
+            let mut state = self.state.lock().await;
+            if state
+                .queued_tasks
+                .front()
+                .is_some_and(|task| task.sub_id == sub_id)
+            {
+                state.queued_tasks.pop_front();
+            }
+            state.queued_task_infos()
+        };
+        self.send_task_queue_updated(&sub_id, tasks).await;
+    }
+
+    async fn send_task_queue_updated(&self, sub_id: &str, tasks: Vec<QueuedTask>) {
+        self.send_event(Event {
+            id: sub_id.to_string(),
+            msg: EventMsg::TaskQueueUpdated(TaskQueueUpdatedEvent { tasks }),
+        })
+        .await;
+    }
+
     pub async fn remove_task(&self, sub_id: &str) {
         let mut state = self.state.lock().await;
         if let Some(task) = &state.current_task
@@ -573,14 +880,88 @@ impl Session {
 
     /// Persist the event to rollout and send it to clients.
     pub(crate) async fn send_event(&self, event: Event) {
+        let event = self.run_automations(event).await;
+        if let Some(live_session) = &self.services.live_session {
+            live_session.observe(&event.msg);
+        }
+        // Rollout records written while a task runs carry its submission id.
+        if matches!(event.msg, EventMsg::TaskStarted(_)) {
+            self.set_rollout_turn_id(Some(event.id.clone())).await;
+        }
         // Persist the event into rollout (recorder filters as needed)
         let rollout_items = vec![RolloutItem::EventMsg(event.msg.clone())];
         self.persist_rollout_items(&rollout_items).await;
+        if matches!(
+            event.msg,
+            EventMsg::TaskComplete(_) | EventMsg::TurnAborted(_)
+        ) {
+            self.set_rollout_turn_id(None).await;
+        }
         if let Err(e) = self.tx_event.send(event).await {
             error!("failed to send tool call event: {e}");
         }
     }
 
+    /// Applies the `[[automations]]` rules that match `event`. An approval
+    /// request answered by an `approve` rule is resolved here and replaced by
+    /// a background event saying so, since no one needs to answer it.
+    async fn run_automations(&self, event: Event) -> Event {
+        let automations = &self.services.automations;
+        if automations.is_empty() {
+            return event;
+        }
+        let mut approved_by = None;
+        for automation in automations.matching(&event.msg) {
+            match &automation.action {
+                AutomationAction::Run { .. } => {
+                    spawn_run_action(automation, &event.id, &event.msg, &self.hooks().wasm);
+                }
+                AutomationAction::Notify(message) => {
+                    self.notifier().notify(&UserNotification::Automation {
+                        name: automation.name.clone(),
+                        event: event.msg.to_string(),
+                        message: message.clone(),
+                    });
+                }
+                AutomationAction::Approve => {
+                    approved_by.get_or_insert_with(|| automation.name.clone());
+                }
+            }
+        }
+        let Some(name) = approved_by else {
+            return event;
+        };
+        self.notify_approval(&event.id, ReviewDecision::Approved)
+            .await;
+        let what = match &event.msg {
+            EventMsg::ExecApprovalRequest(request) => format!("`{}`", request.command.join(" ")),
+            _ => "the patch".to_string(),
+        };
+        Event {
+            id: event.id,
+            msg: EventMsg::BackgroundEvent(BackgroundEventEvent {
+                message: format!("Automation `{name}` approved {what}"),
+            }),
+        }
+    }
+
+    /// Registers a pending approval for `sub_id` on the active turn. The
+    /// receiver resolves when [`Session::notify_approval`] answers it.
+    async fn register_pending_approval(&self, sub_id: &str) -> oneshot::Receiver<ReviewDecision> {
+        let (tx_approve, rx_approve) = oneshot::channel();
+        let mut active = self.active_turn.lock().await;
+        if let Some(at) = active.as_mut() {
+            let mut ts = at.turn_state.lock().await;
+            if ts
+                .insert_pending_approval(sub_id.to_string(), tx_approve)
+                .is_some()
+            {
+                warn!("Overwriting existing pending approval for sub_id: {sub_id}");
+            }
+        }
+        rx_approve
+    }
+
     pub async fn request_command_approval(
         &self,
         sub_id: String,
@@ -589,25 +970,10 @@ impl Session {
         cwd: PathBuf,
         reason: Option<String>,
     ) -> ReviewDecision {
-        // Add the tx_approve callback to the map before sending the request.
-        let (tx_approve, rx_approve) = oneshot::channel();
-        let event_id = sub_id.clone();
-        let prev_entry = {
-            let mut active = self.active_turn.lock().await;
-            match active.as_mut() {
-                Some(at) => {
-                    let mut ts = at.turn_state.lock().await;
-                    ts.insert_pending_approval(sub_id, tx_approve)
-                }
-                None => None,
-            }
-        };
-        if prev_entry.is_some() {
-            warn!("Overwriting existing pending approval for sub_id: {event_id}");
-        }
+        let rx_approve = self.register_pending_approval(&sub_id).await;
 
         let event = Event {
-            id: event_id,
+            id: sub_id,
             msg: EventMsg::ExecApprovalRequest(ExecApprovalRequestEvent {
                 call_id,
                 command,
@@ -619,175 +985,953 @@ impl Session {
         rx_approve.await.unwrap_or_default()
     }
 
-    pub async fn request_patch_approval(
+    /// Like [`Session::request_command_approval`], but for a command the
+    /// model asked to run outside the sandbox. The user's answer arrives via
+    /// `Op::ExecApproval`.
+    pub async fn request_escalation(
         &self,
         sub_id: String,
         call_id: String,
-        action: &ApplyPatchAction,
-        reason: Option<String>,
-        grant_root: Option<PathBuf>,
-    ) -> oneshot::Receiver<ReviewDecision> {
-        // Add the tx_approve callback to the map before sending the request.
-        let (tx_approve, rx_approve) = oneshot::channel();
-        let event_id = sub_id.clone();
-        let prev_entry = {
-            let mut active = self.active_turn.lock().await;
-            match active.as_mut() {
-                Some(at) => {
-                    let mut ts = at.turn_state.lock().await;
-                    ts.insert_pending_approval(sub_id, tx_approve)
-                }
-                None => None,
-            }
-        };
-        if prev_entry.is_some() {
-            warn!("Overwriting existing pending approval for sub_id: {event_id}");
-        }
+        command: Vec<String>,
+        cwd: PathBuf,
+        justification: String,
+        requested_permissions: Vec<EscalatedPermission>,
+    ) -> ReviewDecision {
+        let rx_approve = self.register_pending_approval(&sub_id).await;
 
         let event = Event {
-            id: event_id,
-            msg: EventMsg::ApplyPatchApprovalRequest(ApplyPatchApprovalRequestEvent {
+            id: sub_id,
+            msg: EventMsg::EscalationRequest(EscalationRequestEvent {
                 call_id,
-                changes: convert_apply_patch_to_protocol(action),
-                reason,
-                grant_root,
+                command,
+                cwd,
+                justification,
+                requested_permissions,
             }),
         };
         self.send_event(event).await;
-        rx_approve
-    }
-
-    pub async fn notify_approval(&self, sub_id: &str, decision: ReviewDecision) {
-        let entry = {
-            let mut active = self.active_turn.lock().await;
-            match active.as_mut() {
-                Some(at) => {
-                    let mut ts = at.turn_state.lock().await;
-                    ts.remove_pending_approval(sub_id)
-                }
-                None => None,
-            }
-        };
-        match entry {
-            Some(tx_approve) => {
-                tx_approve.send(decision).ok();
-            }
-            None => {
-                warn!("No pending approval found for sub_id: {sub_id}");
-            }
-        }
-    }
-
-    pub async fn add_approved_command(&self, cmd: Vec<String>) {
-        let mut state = self.state.lock().await;
-        state.add_approved_command(cmd);
-    }
-
-    /// Records input items: always append to conversation history and
-    /// persist these response items to rollout.
-    async fn record_conversation_items(&self, items: &[ResponseItem]) {
-        self.record_into_history(items).await;
-        self.persist_rollout_response_items(items).await;
+        rx_approve.await.unwrap_or_default()
     }
 
-    fn reconstruct_history_from_rollout(
+    /// Sandbox policy (and the cwd its writable roots are relative to) that
+    /// commands run under: the turn's policy, widened so the scratch
+    /// directory stays writable.
+    pub(crate) fn exec_sandbox_policy(
         &self,
         turn_context: &TurnContext,
-        rollout_items: &[RolloutItem],
-    ) -> Vec<ResponseItem> {
-        let mut history = ConversationHistory::new();
-        for item in rollout_items {
-            match item {
-                RolloutItem::ResponseItem(response_item) => {
-                    history.record_items(std::iter::once(response_item));
-                }
-                RolloutItem::Compacted(compacted) => {
-                    let snapshot = history.contents();
-                    let user_messages = collect_user_messages(&snapshot);
-                    let rebuilt = build_compacted_history(
-                        self.build_initial_context(turn_context),
-                        &user_messages,
-                        &compacted.message,
-                    );
-                    history.replace(rebuilt);
-                }
-                _ => {}
+    ) -> (SandboxPolicy, PathBuf) {
+        match &self.services.scratch_dir {
+            Some(scratch_dir) => {
+                scratch_dir.sandbox_policy(&turn_context.sandbox_policy, &turn_context.cwd)
             }
+            None => (
+                turn_context.sandbox_policy.clone(),
+                turn_context.cwd.clone(),
+            ),
         }
-        history.contents()
     }
 
-    /// Append ResponseItems to the in-memory conversation history only.
-    async fn record_into_history(&self, items: &[ResponseItem]) {
-        let mut state = self.state.lock().await;
-        state.record_items(items.iter());
+    /// Appends a command about to run to the project's command log when
+    /// `command_provenance` is enabled. Failures are logged, not fatal.
+    async fn log_command_provenance(
+        &self,
+        session_cwd: &Path,
+        call_id: &str,
+        cwd: &Path,
+        command: &[String],
+    ) {
+        if !self.services.command_provenance {
+            return;
+        }
+        if let Err(e) =
+            command_log::append_command(session_cwd, &self.conversation_id, call_id, cwd, command)
+                .await
+        {
+            warn!("failed to append to command log: {e}");
+        }
     }
 
-    async fn replace_history(&self, items: Vec<ResponseItem>) {
-        let mut state = self.state.lock().await;
-        state.replace_history(items);
-    }
+    /// Check out a `codex/<slug-of-prompt>` branch for the task when
+    /// `git.auto_branch` is enabled and `cwd` is a clean git repository.
+    /// Returns the branch name.
+    async fn start_task_work_branch(
+        &self,
+        sub_id: &str,
+        cwd: &Path,
+        input: &[InputItem],
+    ) -> Option<String> {
+        if !self.services.git_auto_branch {
+            return None;
+        }
 
-    async fn persist_rollout_response_items(&self, items: &[ResponseItem]) {
-        let rollout_items: Vec<RolloutItem> = items
+        let prompt = input
             .iter()
-            .cloned()
-            .map(RolloutItem::ResponseItem)
-            .collect();
-        self.persist_rollout_items(&rollout_items).await;
-    }
-
-    pub(crate) fn build_initial_context(&self, turn_context: &TurnContext) -> Vec<ResponseItem> {
-        let mut items = Vec::<ResponseItem>::with_capacity(2);
-        if let Some(user_instructions) = turn_context.user_instructions.as_deref() {
-            items.push(UserInstructions::new(user_instructions.to_string()).into());
+            .filter_map(|item| match item {
+                InputItem::Text { text } => Some(text.as_str()),
+                _ => None,
+            })
+            .collect::<Vec<_>>()
+            .join(" ");
+        let repo_path = cwd.to_path_buf();
+        let result =
+            tokio::task::spawn_blocking(move || start_work_branch(&repo_path, &prompt)).await;
+        match result {
+            Ok(Ok(Some(branch))) => {
+                let name = branch.name().to_string();
+                self.state.lock().await.work_branch = Some(TaskWorkBranch {
+                    branch,
+                    cwd: cwd.to_path_buf(),
+                    sub_id: sub_id.to_string(),
+                });
+                Some(name)
+            }
+            Ok(Ok(None)) | Ok(Err(GitToolingError::NotAGitRepository { .. })) => None,
+            Ok(Err(err)) => {
+                warn!("failed to create work branch: {err}");
+                None
+            }
+            Err(err) => {
+                warn!("work branch task failed: {err}");
+                None
+            }
         }
-        items.push(ResponseItem::from(EnvironmentContext::new(
-            Some(turn_context.cwd.clone()),
-            Some(turn_context.approval_policy),
-            Some(turn_context.sandbox_policy.clone()),
-            Some(self.user_shell().clone()),
-        )));
-        items
     }
 
-    async fn persist_rollout_items(&self, items: &[RolloutItem]) {
-        let recorder = {
-            let guard = self.services.rollout.lock().await;
-            guard.clone()
+    /// Check the previous branch out again if `sub_id` created a work branch.
+    async fn leave_task_work_branch(&self, sub_id: &str) {
+        let Some(TaskWorkBranch { branch, cwd, .. }) =
+            self.state.lock().await.take_work_branch(sub_id)
+        else {
+            return;
         };
-        if let Some(rec) = recorder
-            && let Err(e) = rec.record_items(items).await
-        {
-            error!("failed to record rollout items: {e:#}");
+        let result = tokio::task::spawn_blocking(move || leave_work_branch(&cwd, &branch)).await;
+        match result {
+            Ok(Ok(())) => {}
+            Ok(Err(err)) => warn!("failed to leave work branch: {err}"),
+            Err(err) => warn!("work branch task failed: {err}"),
         }
     }
 
-    pub(crate) async fn history_snapshot(&self) -> Vec<ResponseItem> {
-        let state = self.state.lock().await;
-        state.history_snapshot()
-    }
-
-    async fn update_token_usage_info(
+    /// Records a ghost commit of the working tree at `cwd` so the changes a
+    /// patch or command makes can be rolled back. Returns the commit id, or
+    /// `None` when snapshots are unavailable (e.g. outside a git repository);
+    /// after the first failure no further snapshots are attempted.
+    async fn capture_ghost_snapshot(
         &self,
+        cwd: &Path,
         sub_id: &str,
-        turn_context: &TurnContext,
-        token_usage: Option<&TokenUsage>,
-    ) {
-        {
-            let mut state = self.state.lock().await;
-            if let Some(token_usage) = token_usage {
-                state.update_token_info_from_usage(
-                    token_usage,
-                    turn_context.client.get_model_context_window(),
-                );
-            }
+        call_id: &str,
+    ) -> Option<String> {
+        if self.state.lock().await.ghost_snapshots_disabled {
+            return None;
         }
-        self.send_token_count_event(sub_id).await;
-    }
 
-    async fn update_rate_limits(&self, sub_id: &str, new_rate_limits: RateLimitSnapshot) {
-        {
+        let repo_path = cwd.to_path_buf();
+        let result = tokio::task::spawn_blocking(move || {
+            let commit = create_ghost_commit(&CreateGhostCommitOptions::new(&repo_path))?;
+            record_ghost_commit(&repo_path, &commit)?;
+            Ok::<_, GitToolingError>(commit)
+        })
+        .await;
+        let mut state = self.state.lock().await;
+        match result {
+            Ok(Ok(commit)) => {
+                let id = commit.id().to_string();
+                state.record_ghost_snapshot(
+                    commit,
+                    cwd.to_path_buf(),
+                    sub_id.to_string(),
+                    call_id.to_string(),
+                );
+                Some(id)
+            }
+            Ok(Err(GitToolingError::NotAGitRepository { .. })) => {
+                state.ghost_snapshots_disabled = true;
+                None
+            }
+            Ok(Err(err)) => {
+                warn!("failed to create ghost snapshot: {err}");
+                state.ghost_snapshots_disabled = true;
+                None
+            }
+            Err(err) => {
+                warn!("ghost snapshot task failed: {err}");
+                None
+            }
+        }
+    }
+
+    /// Restores the most recent ghost snapshot and reports which files the
+    /// restore changed. A snapshot that fails to restore is kept so the undo
+    /// can be retried.
+    async fn undo_last_change(&self) -> UndoCompletedEvent {
+        let Some(snapshot) = self.state.lock().await.take_last_ghost_snapshot() else {
+            return UndoCompletedEvent {
+                snapshot_id: None,
+                reverted_files: Vec::new(),
+                message: Some("No snapshot available to undo.".to_string()),
+            };
+        };
+
+        let GhostSnapshot { commit, cwd, .. } = snapshot.clone();
+        let result = tokio::task::spawn_blocking(move || {
+            // Bracket the restore with snapshots so the summary lists exactly
+            // the files it touched.
+            let before = create_ghost_commit(&CreateGhostCommitOptions::new(&cwd))?;
+            restore_ghost_commit(&cwd, &commit)?;
+            let after = create_ghost_commit(&CreateGhostCommitOptions::new(&cwd))?;
+            changed_paths(&cwd, before.id(), after.id())
+        })
+        .await;
+        let error = match result {
+            Ok(Ok(reverted_files)) => {
+                return UndoCompletedEvent {
+                    snapshot_id: Some(snapshot.commit.id().to_string()),
+                    reverted_files,
+                    message: None,
+                };
+            }
+            Ok(Err(err)) => err.to_string(),
+            Err(err) => err.to_string(),
+        };
+        warn!("failed to restore ghost snapshot: {error}");
+        self.state.lock().await.ghost_snapshots.push(snapshot);
+        UndoCompletedEvent {
+            snapshot_id: None,
+            reverted_files: Vec::new(),
+            message: Some(format!("Failed to restore snapshot: {error}")),
+        }
+    }
+
+    /// Diffs two ghost snapshots. `from` defaults to the first snapshot of the
+    /// most recent task that took one, and `to` to the current working tree.
+    async fn snapshot_diff(
+        &self,
+        from: Option<String>,
+        to: Option<String>,
+        cwd: &Path,
+    ) -> SnapshotDiffEvent {
+        let snapshots = self.state.lock().await.ghost_snapshots.clone();
+        let from_snapshot = match &from {
+            Some(id) => snapshots.iter().find(|snapshot| snapshot.commit.id() == id),
+            None => snapshots.last().and_then(|last| {
+                snapshots
+                    .iter()
+                    .find(|snapshot| snapshot.sub_id == last.sub_id)
+            }),
+        };
+        let Some(from) = from.or_else(|| from_snapshot.map(|s| s.commit.id().to_string())) else {
+            return SnapshotDiffEvent {
+                from: None,
+                to: None,
+                unified_diff: String::new(),
+                message: Some("No snapshot to diff against yet.".to_string()),
+            };
+        };
+
+        let repo_path = from_snapshot.map_or_else(|| cwd.to_path_buf(), |s| s.cwd.clone());
+        let from_id = from.clone();
+        let to_id = to.clone();
+        let result = tokio::task::spawn_blocking(move || {
+            let to = match to_id {
+                Some(to) => to,
+                None => create_ghost_commit(&CreateGhostCommitOptions::new(&repo_path))?
+                    .id()
+                    .to_string(),
+            };
+            diff_ghost_commits(&repo_path, &from_id, &to)
+        })
+        .await;
+        let error = match result {
+            Ok(Ok(unified_diff)) => {
+                return SnapshotDiffEvent {
+                    from: Some(from),
+                    to,
+                    unified_diff,
+                    message: None,
+                };
+            }
+            Ok(Err(err)) => err.to_string(),
+            Err(err) => err.to_string(),
+        };
+        warn!("failed to diff ghost snapshots: {error}");
+        SnapshotDiffEvent {
+            from: Some(from),
+            to,
+            unified_diff: String::new(),
+            message: Some(format!("Failed to diff snapshots: {error}")),
+        }
+    }
+
+    /// Saves the conversation history and, when `include_workspace` is set
+    /// and `cwd` is in a git repository, a ghost commit of the working tree.
+    async fn create_checkpoint(
+        &self,
+        label: Option<String>,
+        include_workspace: bool,
+        cwd: &Path,
+    ) -> CheckpointCreatedEvent {
+        let workspace = if include_workspace {
+            let repo_path = cwd.to_path_buf();
+            let result = tokio::task::spawn_blocking(move || {
+                let commit = create_ghost_commit(&CreateGhostCommitOptions::new(&repo_path))?;
+                record_ghost_commit(&repo_path, &commit)?;
+                Ok::<_, GitToolingError>(commit)
+            })
+            .await;
+            match result {
+                Ok(Ok(commit)) => Some((commit, cwd.to_path_buf())),
+                Ok(Err(GitToolingError::NotAGitRepository { .. })) => None,
+                Ok(Err(err)) => {
+                    warn!("failed to snapshot working tree for checkpoint: {err}");
+                    None
+                }
+                Err(err) => {
+                    warn!("checkpoint snapshot task failed: {err}");
+                    None
+                }
+            }
+        } else {
+            None
+        };
+        let snapshot_id = workspace
+            .as_ref()
+            .map(|(commit, _)| commit.id().to_string());
+        let checkpoint_id = self
+            .state
+            .lock()
+            .await
+            .add_checkpoint(label.clone(), workspace);
+        CheckpointCreatedEvent {
+            checkpoint_id,
+            label,
+            snapshot_id,
+        }
+    }
+
+    /// Restores the history, and the working tree if it was saved, recorded
+    /// by checkpoint `checkpoint_id`, and discards it and every later
+    /// checkpoint. Refused while a task is running. If the working tree
+    /// fails to restore, nothing is rewound and the checkpoint is kept.
+    async fn rewind_to_checkpoint(&self, checkpoint_id: String) -> CheckpointRewoundEvent {
+        let not_rewound = |message: String| CheckpointRewoundEvent {
+            checkpoint_id: checkpoint_id.clone(),
+            rewound: false,
+            label: None,
+            restored_files: Vec::new(),
+            message: Some(message),
+        };
+        let checkpoint = {
+            let state = self.state.lock().await;
+            if state.current_task.is_some() {
+                return not_rewound("Cannot rewind while a task is running.".to_string());
+            }
+            state
+                .checkpoints
+                .iter()
+                .find(|checkpoint| checkpoint.id == checkpoint_id)
+                .cloned()
+        };
+        let Some(Checkpoint {
+            label,
+            history,
+            workspace,
+            ..
+        }) = checkpoint
+        else {
+            return not_rewound(format!("Unknown checkpoint {checkpoint_id}."));
+        };
+
+        let mut restored_files = Vec::new();
+        if let Some((commit, cwd)) = workspace {
+            let result = tokio::task::spawn_blocking(move || {
+                // Bracket the restore with snapshots so the summary lists
+                // exactly the files it touched.
+                let before = create_ghost_commit(&CreateGhostCommitOptions::new(&cwd))?;
+                restore_ghost_commit(&cwd, &commit)?;
+                let after = create_ghost_commit(&CreateGhostCommitOptions::new(&cwd))?;
+                changed_paths(&cwd, before.id(), after.id())
+            })
+            .await;
+            let error = match result {
+                Ok(Ok(files)) => {
+                    restored_files = files;
+                    None
+                }
+                Ok(Err(err)) => Some(err.to_string()),
+                Err(err) => Some(err.to_string()),
+            };
+            if let Some(error) = error {
+                warn!("failed to restore checkpoint {checkpoint_id}: {error}");
+                return not_rewound(format!("Failed to restore the working tree: {error}"));
+            }
+        }
+
+        {
+            let mut state = self.state.lock().await;
+            state.take_checkpoint(&checkpoint_id);
+            state.replace_history(history.clone());
+        }
+        self.persist_rollout_items(&[RolloutItem::Rewound(RewoundItem {
+            checkpoint_id: checkpoint_id.clone(),
+            history,
+        })])
+        .await;
+        CheckpointRewoundEvent {
+            checkpoint_id,
+            rewound: true,
+            label,
+            restored_files,
+            message: None,
+        }
+    }
+
+    /// Applies the default ghost commit retention policy to every repository
+    /// this session took snapshots in.
+    async fn prune_ghost_snapshots(&self) {
+        let mut repos: Vec<PathBuf> = self
+            .state
+            .lock()
+            .await
+            .ghost_snapshots
+            .iter()
+            .map(|snapshot| snapshot.cwd.clone())
+            .collect();
+        repos.sort();
+        repos.dedup();
+        if repos.is_empty() {
+            return;
+        }
+        let result = tokio::task::spawn_blocking(move || {
+            let retention = GhostCommitRetention::default();
+            for repo in repos {
+                match prune_ghost_commits(&repo, &retention) {
+                    Ok(pruned) if !pruned.is_empty() => {
+                        debug!(
+                            "pruned {} ghost commits in {}",
+                            pruned.len(),
+                            repo.display()
+                        );
+                    }
+                    Ok(_) => {}
+                    Err(err) => warn!("failed to prune ghost commits: {err}"),
+                }
+            }
+        })
+        .await;
+        if let Err(err) = result {
+            warn!("ghost commit pruning task failed: {err}");
+        }
+    }
+
+    /// Describes the recorded ghost snapshots, oldest first. The files listed
+    /// for each are those changed by the time the next snapshot (or, for the
+    /// latest, the current working tree) was taken.
+    async fn list_snapshots(&self) -> ListSnapshotsResponseEvent {
+        let snapshots = self.state.lock().await.ghost_snapshots.clone();
+        let result = tokio::task::spawn_blocking(move || {
+            // Ghost commits of the current tree, taken at most once per cwd.
+            let mut current: HashMap<PathBuf, Option<String>> = HashMap::new();
+            let mut infos = Vec::with_capacity(snapshots.len());
+            for (index, snapshot) in snapshots.iter().enumerate() {
+                let next = snapshots
+                    .get(index + 1)
+                    .filter(|next| next.cwd == snapshot.cwd)
+                    .map(|next| next.commit.id().to_string());
+                let to = next.or_else(|| {
+                    current
+                        .entry(snapshot.cwd.clone())
+                        .or_insert_with(|| {
+                            create_ghost_commit(&CreateGhostCommitOptions::new(&snapshot.cwd))
+                                .map(|commit| commit.id().to_string())
+                                .map_err(|err| warn!("failed to snapshot working tree: {err}"))
+                                .ok()
+                        })
+                        .clone()
+                });
+                let files = to
+                    .and_then(|to| {
+                        changed_paths(&snapshot.cwd, snapshot.commit.id(), &to)
+                            .map_err(|err| warn!("failed to diff ghost snapshots: {err}"))
+                            .ok()
+                    })
+                    .unwrap_or_default();
+                infos.push(SnapshotInfo {
+                    id: snapshot.commit.id().to_string(),
+                    timestamp: snapshot
+                        .created_at
+                        .to_rfc3339_opts(chrono::SecondsFormat::Secs, true),
+                    call_id: snapshot.call_id.clone(),
+                    files,
+                });
+            }
+            infos
+        })
+        .await;
+        let snapshots = result.unwrap_or_else(|err| {
+            warn!("listing ghost snapshots failed: {err}");
+            Vec::new()
+        });
+        ListSnapshotsResponseEvent { snapshots }
+    }
+
+    /// Asks the user once per project before `permission` is first used and
+    /// applies the remembered answer afterwards. `Err` holds the message for
+    /// the model when the permission is not granted.
+    /// Whether `check_guided_permission` would have to ask the user about
+    /// `permission` before going ahead.
+    fn guided_permission_needs_prompt(
+        &self,
+        turn_context: &TurnContext,
+        permission: &GuidedPermission,
+    ) -> bool {
+        let gate = &self.services.permission_gate;
+        gate.is_enabled()
+            && !matches!(turn_context.approval_policy, AskForApproval::Never)
+            && gate.decision(&permission.key()).is_none()
+    }
+
+    async fn check_guided_permission(
+        &self,
+        turn_context: &TurnContext,
+        sub_id: &str,
+        call_id: &str,
+        permission: GuidedPermission,
+        detail: String,
+    ) -> Result<(), String> {
+        let gate = &self.services.permission_gate;
+        // Without approvals there is nobody to ask.
+        if !gate.is_enabled() || matches!(turn_context.approval_policy, AskForApproval::Never) {
+            return Ok(());
+        }
+        let key = permission.key();
+        let declined = || {
+            format!(
+                "the user has not allowed `{key}` for this project; do not retry, and ask the user if it is needed"
+            )
+        };
+        match gate.decision(&key) {
+            Some(PermissionDecision::Allow) => return Ok(()),
+            Some(PermissionDecision::Deny) => return Err(declined()),
+            None => {}
+        }
+
+        let rx_approve = self.register_pending_approval(sub_id).await;
+        let event = Event {
+            id: sub_id.to_string(),
+            msg: EventMsg::PermissionConsentRequest(PermissionConsentRequestEvent {
+                call_id: call_id.to_string(),
+                permission,
+                detail,
+            }),
+        };
+        self.send_event(event).await;
+
+        // A dropped channel means the request was cleared without an answer,
+        // e.g. by an interrupt; that must not be saved as a decision.
+        match rx_approve.await.unwrap_or(ReviewDecision::Abort) {
+            ReviewDecision::Approved
+            | ReviewDecision::ApprovedForSession
+            | ReviewDecision::ApprovedAlways => {
+                gate.remember(&key, PermissionDecision::Allow);
+                Ok(())
+            }
+            ReviewDecision::Denied => {
+                gate.remember(&key, PermissionDecision::Deny);
+                Err(declined())
+            }
+            ReviewDecision::Abort => Err(format!("the user did not allow `{key}` this time")),
+        }
+    }
+
+    pub async fn request_patch_approval(
+        &self,
+        sub_id: String,
+        call_id: String,
+        action: &ApplyPatchAction,
+        reason: Option<String>,
+        grant_root: Option<PathBuf>,
+        rejected_paths: Vec<PatchPathEscape>,
+    ) -> oneshot::Receiver<ReviewDecision> {
+        let rx_approve = self.register_pending_approval(&sub_id).await;
+
+        let (changes, stats) = convert_apply_patch_to_protocol(action);
+        let event = Event {
+            id: sub_id,
+            msg: EventMsg::ApplyPatchApprovalRequest(ApplyPatchApprovalRequestEvent {
+                call_id,
+                changes,
+                reason,
+                grant_root,
+                stats,
+                rejected_paths,
+            }),
+        };
+        self.send_event(event).await;
+        rx_approve
+    }
+
+    pub async fn notify_approval(&self, sub_id: &str, decision: ReviewDecision) {
+        let entry = {
+            let mut active = self.active_turn.lock().await;
+            match active.as_mut() {
+                Some(at) => {
+                    let mut ts = at.turn_state.lock().await;
+                    ts.remove_pending_approval(sub_id)
+                }
+                None => None,
+            }
+        };
+        match entry {
+            Some(tx_approve) => {
+                tx_approve.send(decision).ok();
+                if let Some(live_session) = &self.services.live_session {
+                    live_session.approval_answered();
+                }
+            }
+            None => {
+                warn!("No pending approval found for sub_id: {sub_id}");
+            }
+        }
+    }
+
+    /// Like [`Session::notify_approval`], but first records which files of
+    /// the patch the user approved when they approved only some of them.
+    pub async fn notify_patch_approval(
+        &self,
+        sub_id: &str,
+        decision: ReviewDecision,
+        approved_files: Option<Vec<PathBuf>>,
+    ) {
+        if let Some(files) = approved_files {
+            let active = self.active_turn.lock().await;
+            if let Some(at) = active.as_ref() {
+                at.turn_state
+                    .lock()
+                    .await
+                    .set_approved_patch_files(sub_id.to_string(), files);
+            }
+        }
+        self.notify_approval(sub_id, decision).await;
+    }
+
+    /// The files the user approved from the patch approval for `sub_id`, if
+    /// they approved only some of them.
+    pub(crate) async fn take_approved_patch_files(&self, sub_id: &str) -> Option<Vec<PathBuf>> {
+        let active = self.active_turn.lock().await;
+        let at = active.as_ref()?;
+        at.turn_state.lock().await.take_approved_patch_files(sub_id)
+    }
+
+    pub async fn add_approved_command(&self, cmd: Vec<String>) {
+        self.state.lock().await.add_approved_command(cmd);
+        self.record_state_snapshot().await;
+    }
+
+    /// Approves every command matching `command`'s pattern, now and in later
+    /// sessions. A command no single pattern can cover is only approved for
+    /// this session.
+    pub async fn approve_command_always(&self, command: Vec<String>) {
+        let Some(pattern) = pattern_for_command(&command) else {
+            self.add_approved_command(command).await;
+            return;
+        };
+        if let Err(e) = add_approved_command_pattern(&self.services.codex_home, &pattern) {
+            warn!("failed to save approved command pattern `{pattern}`: {e}");
+        }
+        {
+            let mut state = self.state.lock().await;
+            state.add_approved_command(command);
+            state.add_approved_command_pattern(pattern);
+        }
+        self.record_state_snapshot().await;
+    }
+
+    pub(crate) async fn set_plan(&self, plan: UpdatePlanArgs) {
+        self.state.lock().await.plan = Some(plan);
+        self.record_state_snapshot().await;
+    }
+
+    /// Records the session-scoped state (approvals, plan, token usage and
+    /// background processes) in the rollout when it changed since it was
+    /// last recorded, so that resuming after a crash restores it.
+    pub(crate) async fn record_state_snapshot(&self) {
+        let background_processes = self.services.session_manager.running_jobs().await;
+        let snapshot = self
+            .state
+            .lock()
+            .await
+            .changed_state_snapshot(background_processes);
+        if let Some(snapshot) = snapshot {
+            self.persist_rollout_items(&[RolloutItem::SessionState(snapshot)])
+                .await;
+        }
+    }
+
+    /// Records input items: always append to conversation history and
+    /// persist these response items to rollout.
+    async fn record_conversation_items(&self, items: &[ResponseItem]) {
+        self.record_into_history(items).await;
+        self.persist_rollout_response_items(items).await;
+    }
+
+    fn reconstruct_history_from_rollout(
+        &self,
+        turn_context: &TurnContext,
+        rollout_items: &[RolloutItem],
+    ) -> Vec<ResponseItem> {
+        let mut history = ConversationHistory::new();
+        for item in rollout_items {
+            match item {
+                RolloutItem::ResponseItem(response_item) => {
+                    history.record_items(std::iter::once(response_item));
+                }
+                RolloutItem::Compacted(compacted) => {
+                    let snapshot = history.contents();
+                    let user_messages = collect_user_messages(&snapshot);
+                    let mut rebuilt = build_compacted_history(
+                        self.build_initial_context(turn_context),
+                        &user_messages,
+                        &compacted.message,
+                    );
+                    rebuilt.extend(recent_messages(&snapshot, compacted.preserved_messages));
+                    history.replace(rebuilt);
+                }
+                RolloutItem::Rewound(rewound) => {
+                    history.replace(rewound.history.clone());
+                }
+                _ => {}
+            }
+        }
+        history.contents()
+    }
+
+    /// Append ResponseItems to the in-memory conversation history only.
+    async fn record_into_history(&self, items: &[ResponseItem]) {
+        let mut state = self.state.lock().await;
+        state.record_items(items.iter());
+    }
+
+    async fn replace_history(&self, items: Vec<ResponseItem>) {
+        let mut state = self.state.lock().await;
+        state.replace_history(items);
+    }
+
+    async fn persist_rollout_response_items(&self, items: &[ResponseItem]) {
+        let rollout_items: Vec<RolloutItem> = items
+            .iter()
+            .cloned()
+            .map(RolloutItem::ResponseItem)
+            .collect();
+        self.persist_rollout_items(&rollout_items).await;
+    }
+
+    pub(crate) fn build_initial_context(&self, turn_context: &TurnContext) -> Vec<ResponseItem> {
+        let mut items = Vec::<ResponseItem>::with_capacity(2);
+        if let Some(user_instructions) = turn_context.user_instructions.as_deref() {
+            items.push(UserInstructions::new(user_instructions.to_string()).into());
+        }
+        items.push(ResponseItem::from(EnvironmentContext::new(
+            Some(turn_context.cwd.clone()),
+            Some(turn_context.approval_policy),
+            Some(turn_context.sandbox_policy.clone()),
+            Some(self.user_shell().clone()),
+            self.services
+                .scratch_dir
+                .as_ref()
+                .map(|scratch_dir| scratch_dir.path().to_path_buf()),
+        )));
+        items
+    }
+
+    /// Records the user's current locale, timezone and local time ahead of a
+    /// new turn, unless they match what the model was last told.
+    async fn refresh_locale_context(&self) {
+        if !self.services.locale_context {
+            return;
+        }
+        let current = LocaleContext::current();
+        {
+            let mut state = self.state.lock().await;
+            if state.last_locale_context.as_ref() == Some(&current) {
+                return;
+            }
+            state.last_locale_context = Some(current.clone());
+        }
+        self.record_conversation_items(&[ResponseItem::from(EnvironmentContext::from(current))])
+            .await;
+    }
+
+    /// Records the branch, dirty file count and upstream distance of the
+    /// repository containing `cwd` ahead of a new turn, unless they match what
+    /// the model was last told. Nothing is sent outside a git repository.
+    async fn refresh_git_status(&self, cwd: &Path) {
+        let repo_path = cwd.to_path_buf();
+        let current = match tokio::task::spawn_blocking(move || repo_status(&repo_path)).await {
+            Ok(Ok(status)) => GitStatusContext::from(&status),
+            Ok(Err(GitToolingError::NotAGitRepository { .. })) => return,
+            Ok(Err(err)) => {
+                warn!("failed to read git status: {err}");
+                return;
+            }
+            Err(err) => {
+                warn!("git status task failed: {err}");
+                return;
+            }
+        };
+        {
+            let mut state = self.state.lock().await;
+            if state.last_git_status.as_ref() == Some(&current) {
+                return;
+            }
+            state.last_git_status = Some(current.clone());
+        }
+        self.record_conversation_items(&[ResponseItem::from(EnvironmentContext::from(current))])
+            .await;
+    }
+
+    async fn persist_rollout_items(&self, items: &[RolloutItem]) {
+        let recorder = {
+            let guard = self.services.rollout.lock().await;
+            guard.clone()
+        };
+        let Some(rec) = recorder else {
+            return;
+        };
+
+        let storage = &self.services.storage_guard;
+        match storage.poll().await {
+            StorageTransition::BecameLow(reason) => {
+                warn!("session storage is low: {reason}");
+                self.send_unpersisted_warning(low_storage_warning(&reason))
+                    .await;
+            }
+            StorageTransition::Recovered => info!("session storage recovered"),
+            StorageTransition::Unchanged => {}
+        }
+        let reduced: Vec<RolloutItem>;
+        let items = if storage.is_low() {
+            reduced = items.iter().filter_map(reduce_for_low_storage).collect();
+            &reduced
+        } else {
+            items
+        };
+
+        if let Err(e) = rec.record_items(items).await {
+            error!("failed to record rollout items: {e:#}");
+            if storage.mark_write_failed() {
+                self.send_unpersisted_warning(format!(
+                    "This session is no longer being saved: {e}"
+                ))
+                .await;
+            }
+        }
+    }
+
+    /// Emits a warning without going through the rollout, which is what the
+    /// warning is usually about.
+    async fn send_unpersisted_warning(&self, message: String) {
+        let event = Event {
+            id: INITIAL_SUBMIT_ID.to_owned(),
+            msg: EventMsg::Warning(WarningEvent { message }),
+        };
+        if let Err(e) = self.tx_event.send(event).await {
+            error!("failed to send warning event: {e}");
+        }
+    }
+
+    async fn set_rollout_turn_id(&self, turn_id: Option<String>) {
+        let recorder = {
+            let guard = self.services.rollout.lock().await;
+            guard.clone()
+        };
+        if let Some(rec) = recorder
+            && let Err(e) = rec.set_turn_id(turn_id).await
+        {
+            error!("failed to record rollout turn id: {e:#}");
+        }
+    }
+
+    pub(crate) async fn history_snapshot(&self) -> Vec<ResponseItem> {
+        let state = self.state.lock().await;
+        state.history_snapshot()
+    }
+
+    async fn update_token_usage_info(
+        &self,
+        sub_id: &str,
+        turn_context: &TurnContext,
+        token_usage: Option<&TokenUsage>,
+    ) {
+        {
+            let mut state = self.state.lock().await;
+            if let Some(token_usage) = token_usage {
+                state.update_token_info_from_usage(
+                    token_usage,
+                    turn_context.client.get_model_context_window(),
+                    turn_context.client.get_model_pricing(),
+                );
+            }
+        }
+        self.send_token_count_event(sub_id).await;
+    }
+
+    pub(crate) async fn estimated_cost_usd(&self) -> Option<f64> {
+        self.state.lock().await.estimated_cost_usd()
+    }
+
+    /// Once the estimated cost of the session reaches `max_session_cost_usd`,
+    /// asks the user whether to keep going. Approving once asks again after
+    /// another `max_session_cost_usd` is spent; approving for the session
+    /// stops asking. Returns the exceeded budget when the task should stop.
+    async fn approve_spend_over_limit(
+        &self,
+        turn_context: &TurnContext,
+        sub_id: &str,
+    ) -> Option<ExceededBudget> {
+        let max_session_cost_usd = self.services.max_session_cost_usd?;
+        let (estimated_cost_usd, limit_usd) = self
+            .state
+            .lock()
+            .await
+            .spend_limit_reached(max_session_cost_usd)?;
+        let exceeded = ExceededBudget {
+            limit: TaskBudgetLimit::MaxSessionCost,
+            used: estimated_cost_usd,
+            allowed: limit_usd,
+        };
+        // Without approvals there is nobody to ask.
+        if matches!(turn_context.approval_policy, AskForApproval::Never) {
+            return Some(exceeded);
+        }
+
+        let rx_approve = self.register_pending_approval(sub_id).await;
+        let event = Event {
+            id: sub_id.to_string(),
+            msg: EventMsg::SpendLimitApprovalRequest(SpendLimitApprovalRequestEvent {
+                estimated_cost_usd,
+                limit_usd,
+            }),
+        };
+        self.send_event(event).await;
+
+        let decision = rx_approve.await.unwrap_or_default();
+        let mut state = self.state.lock().await;
+        match decision {
+            ReviewDecision::Approved => {
+                state.approved_extra_spend_usd = estimated_cost_usd;
+                None
+            }
+            ReviewDecision::ApprovedForSession | ReviewDecision::ApprovedAlways => {
+                state.spend_limit_lifted = true;
+                None
+            }
+            ReviewDecision::Denied | ReviewDecision::Abort => Some(exceeded),
+        }
+    }
+
+    async fn update_rate_limits(&self, sub_id: &str, new_rate_limits: RateLimitSnapshot) {
+        {
             let mut state = self.state.lock().await;
             state.set_rate_limits(new_rate_limits);
         }
@@ -833,6 +1977,7 @@ impl Session {
         &self,
         turn_diff_tracker: &mut TurnDiffTracker,
         exec_command_context: ExecCommandContext,
+        sandbox_policy: Option<SandboxPolicy>,
     ) {
         let ExecCommandContext {
             sub_id,
@@ -845,6 +1990,7 @@ impl Session {
             Some(ApplyPatchCommandContext {
                 user_explicitly_approved_this_action,
                 changes,
+                snapshot_id,
             }) => {
                 turn_diff_tracker.on_patch_begin(&changes);
 
@@ -852,6 +1998,7 @@ impl Session {
                     call_id,
                     auto_approved: !user_explicitly_approved_this_action,
                     changes,
+                    snapshot_id,
                 })
             }
             None => EventMsg::ExecCommandBegin(ExecCommandBeginEvent {
@@ -862,6 +2009,7 @@ impl Session {
                     .into_iter()
                     .map(Into::into)
                     .collect(),
+                sandbox_policy,
             }),
         };
         let event = Event {
@@ -876,6 +2024,7 @@ impl Session {
         turn_diff_tracker: &mut TurnDiffTracker,
         sub_id: &str,
         call_id: &str,
+        cwd: &Path,
         output: &ExecToolCallOutput,
         is_apply_patch: bool,
     ) {
@@ -921,15 +2070,32 @@ impl Session {
         // If this is an apply_patch, after we emit the end patch, emit a second event
         // with the full turn diff if there is one.
         if is_apply_patch {
-            let unified_diff = turn_diff_tracker.get_unified_diff();
-            if let Ok(Some(unified_diff)) = unified_diff {
-                let msg = EventMsg::TurnDiff(TurnDiffEvent { unified_diff });
-                let event = Event {
-                    id: sub_id.into(),
-                    msg,
-                };
-                self.send_event(event).await;
-            }
+            self.send_turn_diff(sub_id, cwd, turn_diff_tracker).await;
+        }
+    }
+
+    /// Sends the turn's aggregated diff, if there is one, as a `TurnDiff`
+    /// event and passes it to the `post_turn_diff` hook.
+    async fn send_turn_diff(
+        &self,
+        sub_id: &str,
+        cwd: &Path,
+        turn_diff_tracker: &mut TurnDiffTracker,
+    ) {
+        let Ok(Some(unified_diff)) = turn_diff_tracker.get_unified_diff() else {
+            return;
+        };
+        self.send_event(Event {
+            id: sub_id.to_string(),
+            msg: EventMsg::TurnDiff(TurnDiffEvent {
+                unified_diff: unified_diff.clone(),
+            }),
+        })
+        .await;
+        if self.hooks().post_turn_diff.is_some() {
+            let files = turn_diff_tracker.changed_paths();
+            self.run_post_turn_diff_hook(sub_id, cwd, &unified_diff, &files)
+                .await;
         }
     }
     /// Runs the exec tool call and emits events for the begin and end of the
@@ -945,8 +2111,11 @@ impl Session {
         let is_apply_patch = begin_ctx.apply_patch.is_some();
         let sub_id = begin_ctx.sub_id.clone();
         let call_id = begin_ctx.call_id.clone();
+        let cwd = begin_ctx.cwd.clone();
 
-        self.on_exec_command_begin(turn_diff_tracker, begin_ctx.clone())
+        let effective_sandbox =
+            (exec_args.sandbox_type != SandboxType::None).then(|| exec_args.sandbox_policy.clone());
+        self.on_exec_command_begin(turn_diff_tracker, begin_ctx.clone(), effective_sandbox)
             .await;
 
         let result = process_exec_tool_call(
@@ -956,6 +2125,8 @@ impl Session {
             exec_args.sandbox_cwd,
             exec_args.codex_linux_sandbox_exe,
             exec_args.stdout_stream,
+            Some(exec_args.cancellation_token.clone()),
+            exec_args.output_cache,
         )
         .await;
 
@@ -979,6 +2150,7 @@ impl Session {
             turn_diff_tracker,
             &sub_id,
             &call_id,
+            &cwd,
             borrowed,
             is_apply_patch,
         )
@@ -1035,6 +2207,31 @@ impl Session {
         }
     }
 
+    /// Queues `input` from `Op::Steer` for the next model request of the
+    /// running task, as a developer message. Returns the input if there was
+    /// no turn running to steer, so that it is not silently dropped.
+    pub async fn steer(&self, input: Vec<InputItem>) -> Result<(), Vec<InputItem>> {
+        let mut active = self.active_turn.lock().await;
+        let Some(at) = active.as_mut() else {
+            return Err(input);
+        };
+        let mut message = ResponseInputItem::from(input);
+        if let ResponseInputItem::Message { role, .. } = &mut message {
+            *role = "developer".to_string();
+        }
+        let mut ts = at.turn_state.lock().await;
+        ts.push_pending_steering(message);
+        Ok(())
+    }
+
+    async fn has_pending_steering(&self) -> bool {
+        let active = self.active_turn.lock().await;
+        match active.as_ref() {
+            Some(at) => at.turn_state.lock().await.has_pending_steering(),
+            None => false,
+        }
+    }
+
     pub async fn get_pending_input(&self) -> Vec<ResponseInputItem> {
         let mut active = self.active_turn.lock().await;
         if let Some(at) = active.as_mut() {
@@ -1045,28 +2242,89 @@ impl Session {
         }
     }
 
+    /// Makes the running tool call `call_id` cancellable through `token`.
+    async fn register_tool_call(&self, call_id: &str, token: CancellationToken) {
+        let mut active = self.active_turn.lock().await;
+        if let Some(at) = active.as_mut() {
+            let mut ts = at.turn_state.lock().await;
+            ts.insert_running_tool_call(call_id.to_string(), token);
+        }
+    }
+
+    async fn unregister_tool_call(&self, call_id: &str) {
+        let mut active = self.active_turn.lock().await;
+        if let Some(at) = active.as_mut() {
+            let mut ts = at.turn_state.lock().await;
+            ts.remove_running_tool_call(call_id);
+        }
+    }
+
+    /// Cancels the running tool call `call_id`, returning whether there was one.
+    async fn cancel_tool_call(&self, call_id: &str) -> bool {
+        let mut active = self.active_turn.lock().await;
+        match active.as_mut() {
+            Some(at) => {
+                let mut ts = at.turn_state.lock().await;
+                ts.cancel_running_tool_call(call_id)
+            }
+            None => false,
+        }
+    }
+
     pub async fn call_tool(
         &self,
         server: &str,
         tool: &str,
         arguments: Option<serde_json::Value>,
+        call_id: &str,
     ) -> anyhow::Result<CallToolResult> {
         self.services
             .mcp_connection_manager
-            .call_tool(server, tool, arguments)
+            .call_tool(server, tool, arguments, call_id)
             .await
     }
 
+    pub(crate) fn mcp_tool_output_schema(
+        &self,
+        server: &str,
+        tool: &str,
+    ) -> Option<serde_json::Value> {
+        self.services
+            .mcp_connection_manager
+            .tool_output_schema(server, tool)
+    }
+
     pub async fn interrupt_task(&self) {
         info!("interrupt received: abort current task, if any");
-        let mut state = self.state.lock().await;
+        let cleared_queue_id = {
+            let mut state = self.state.lock().await;
+            let mut active = self.active_turn.lock().await;
+            if let Some(at) = active.as_mut() {
+                let mut ts = at.turn_state.lock().await;
+                ts.clear_pending();
+            }
+            // Queued tasks are dropped too, like input queued in a client.
+            let discarded = std::mem::take(&mut state.queued_tasks);
+            let task = state.current_task.take();
+            let sub_id = task
+                .as_ref()
+                .map(|task| task.sub_id.clone())
+                .or_else(|| discarded.front().map(|task| task.sub_id.clone()));
+            if let Some(task) = task {
+                task.abort(TurnAbortReason::Interrupted);
+            }
+            sub_id.filter(|_| !discarded.is_empty())
+        };
+        if let Some(sub_id) = cleared_queue_id {
+            self.send_task_queue_updated(&sub_id, Vec::new()).await;
+        }
+    }
+
+    /// Drops the approvals the running turn is waiting on, denying its calls.
+    async fn clear_pending_approvals(&self) {
         let mut active = self.active_turn.lock().await;
         if let Some(at) = active.as_mut() {
-            let mut ts = at.turn_state.lock().await;
-            ts.clear_pending();
-        }
-        if let Some(task) = state.current_task.take() {
-            task.abort(TurnAbortReason::Interrupted);
+            at.turn_state.lock().await.clear_pending_approvals();
         }
     }
 
@@ -1096,8 +2354,47 @@ impl Session {
         self.services.show_raw_agent_reasoning
     }
 
-    fn hooks(&self) -> &HooksConfig {
-        &self.services.hooks
+    fn hooks(&self) -> &HooksConfig {
+        &self.services.hooks
+    }
+
+    /// Describes one run of a hook, resolving its timeout against
+    /// `hooks.timeout_ms`.
+    fn hook_run<'a>(
+        &self,
+        sub_id: &'a str,
+        hook: HookKind,
+        tool: Option<&'a str>,
+        timeout_ms: Option<u64>,
+        retries: u32,
+    ) -> HookRun<'a> {
+        HookRun {
+            sub_id,
+            hook,
+            tool,
+            timeout_ms: self.hooks().timeout_ms_for(timeout_ms),
+            retries,
+        }
+    }
+
+    /// Stamps a hook payload with `schema_version` and drops the fields
+    /// added after the version selected by `hooks.payload_version`.
+    fn hook_payload(&self, mut payload: serde_json::Value) -> serde_json::Value {
+        let version = self.hooks().payload_version;
+        if let Some(fields) = payload.as_object_mut() {
+            let kind = fields
+                .get("type")
+                .and_then(serde_json::Value::as_str)
+                .unwrap_or_default()
+                .to_string();
+            for (since, payload_type, field) in HOOK_PAYLOAD_FIELDS_SINCE {
+                if *since > version && *payload_type == kind {
+                    fields.remove(*field);
+                }
+            }
+            fields.insert("schema_version".to_string(), version.into());
+        }
+        payload
     }
 
     async fn send_error_event(&self, sub_id: &str, message: String) {
@@ -1108,45 +2405,88 @@ impl Session {
         .await;
     }
 
-    async fn run_hook_argv(&self, argv: &[String], json_arg: &str) -> Result<(), String> {
-        if argv.is_empty() {
-            return Ok(());
+    /// Runs a hook process under its timeout, bracketed by `HookBegin` and
+    /// `HookEnd` events so clients can show that the turn is waiting on it.
+    /// A hook that exits non-zero is run again up to `run.retries` times.
+    /// Returns `None` if the hook timed out.
+    ///
+    /// With `hooks.dry_run = "simulate"` the hook is not started and behaves
+    /// as if it exited 0 with no output; with `"env"` it runs with
+    /// [`HOOK_DRY_RUN_ENV_VAR`] set.
+    async fn run_hook_command(
+        &self,
+        run: &HookRun<'_>,
+        mut cmd: tokio::process::Command,
+    ) -> Option<std::io::Result<std::process::Output>> {
+        let tool = run.tool.map(str::to_string);
+        let timeout_dur = Duration::from_millis(run.timeout_ms);
+        let hooks = self.hooks();
+        let simulate = hooks.dry_run == Some(HookDryRun::Simulate);
+        if hooks.dry_run == Some(HookDryRun::Env) {
+            cmd.env(HOOK_DRY_RUN_ENV_VAR, "1");
         }
-        let mut cmd = tokio::process::Command::new(&argv[0]);
-        if argv.len() > 1 {
-            cmd.args(&argv[1..]);
+        if hooks.should_trace() {
+            trace_hook_invocation(run.hook, run.tool, cmd.as_std(), simulate);
         }
-        cmd.arg(json_arg);
-        cmd.stdout(Stdio::piped()).stderr(Stdio::piped());
+        let mut attempt = 0;
+        loop {
+            self.send_event(Event {
+                id: run.sub_id.to_string(),
+                msg: EventMsg::HookBegin(HookBeginEvent {
+                    hook: run.hook,
+                    tool: tool.clone(),
+                }),
+            })
+            .await;
 
-        let timeout_dur = Duration::from_millis(self.hooks().timeout_ms);
-        match tokio::time::timeout(timeout_dur, cmd.output()).await {
-            Err(_) => Err(format!(
-                "hook timed out after {} ms",
-                self.hooks().timeout_ms
-            )),
-            Ok(Err(e)) => Err(format!("failed to spawn hook: {e}")),
-            Ok(Ok(output)) => {
-                if output.status.success() {
-                    Ok(())
-                } else {
-                    let code = output.status.code().unwrap_or(-1);
-                    let stderr = String::from_utf8_lossy(&output.stderr);
-                    let snippet: String = stderr.chars().take(512).collect();
-                    Err(format!("hook exited with code {code}: {snippet}"))
-                }
+            let start = Instant::now();
+            let result = if simulate {
+                Some(Ok(simulated_hook_output()))
+            } else {
+                run_hook_process(&mut cmd, &hooks.wasm, timeout_dur).await
+            };
+            if hooks.should_trace() && !simulate {
+                trace_hook_result(run.hook, &result);
+            }
+            let exit_code = match &result {
+                Some(Ok(output)) => output.status.code(),
+                _ => None,
+            };
+            self.send_event(Event {
+                id: run.sub_id.to_string(),
+                msg: EventMsg::HookEnd(HookEndEvent {
+                    hook: run.hook,
+                    tool: tool.clone(),
+                    duration: start.elapsed(),
+                    exit_code,
+                    timed_out: result.is_none(),
+                }),
+            })
+            .await;
+
+            let failed = matches!(&result, Some(Ok(output)) if !output.status.success());
+            if !failed || attempt >= run.retries {
+                return result;
             }
+            attempt += 1;
+            warn!(
+                "{} hook exited with code {}; retrying ({attempt}/{})",
+                run.hook,
+                exit_code.unwrap_or(-1),
+                run.retries
+            );
         }
     }
 
     async fn run_hook_argv_with_env(
         &self,
+        run: HookRun<'_>,
         argv: &[String],
         json_arg: &str,
         extra_env: &[(&str, String)],
-    ) -> Result<(), String> {
+    ) -> Result<String, String> {
         if argv.is_empty() {
-            return Ok(());
+            return Ok(String::new());
         }
         let mut cmd = tokio::process::Command::new(&argv[0]);
         if argv.len() > 1 {
@@ -1158,16 +2498,12 @@ impl Session {
         }
         cmd.stdout(Stdio::piped()).stderr(Stdio::piped());
 
-        let timeout_dur = Duration::from_millis(self.hooks().timeout_ms);
-        match tokio::time::timeout(timeout_dur, cmd.output()).await {
-            Err(_) => Err(format!(
-                "hook timed out after {} ms",
-                self.hooks().timeout_ms
-            )),
-            Ok(Err(e)) => Err(format!("failed to spawn hook: {e}")),
-            Ok(Ok(output)) => {
+        match self.run_hook_command(&run, cmd).await {
+            None => Err(format!("hook timed out after {} ms", run.timeout_ms)),
+            Some(Err(e)) => Err(format!("failed to spawn hook: {e}")),
+            Some(Ok(output)) => {
                 if output.status.success() {
-                    Ok(())
+                    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
                 } else {
                     let code = output.status.code().unwrap_or(-1);
                     let stderr = String::from_utf8_lossy(&output.stderr);
@@ -1178,17 +2514,159 @@ impl Session {
         }
     }
 
+    /// Starts a non-blocking hook and returns immediately. The hook is still
+    /// killed after its timeout and retried on a non-zero exit; failures are
+    /// only logged since the caller has already moved on.
+    fn spawn_detached_hook(
+        &self,
+        hook: HookKind,
+        rule: &HookRule,
+        json_arg: String,
+        extra_env: &[(&str, String)],
+    ) {
+        let Some((program, args)) = rule.argv.split_first() else {
+            return;
+        };
+        let mut cmd = tokio::process::Command::new(program);
+        cmd.args(args).arg(json_arg);
+        for (k, v) in extra_env.iter() {
+            cmd.env(k, v);
+        }
+        cmd.stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true);
+
+        let hooks = self.hooks();
+        let simulate = hooks.dry_run == Some(HookDryRun::Simulate);
+        if hooks.dry_run == Some(HookDryRun::Env) {
+            cmd.env(HOOK_DRY_RUN_ENV_VAR, "1");
+        }
+        if hooks.should_trace() {
+            trace_hook_invocation(hook, None, cmd.as_std(), simulate);
+        }
+        if simulate {
+            return;
+        }
+
+        let timeout_ms = self.hooks().timeout_ms_for(rule.timeout_ms);
+        let retries = rule.retries;
+        let wasm = hooks.wasm.clone();
+        tokio::spawn(async move {
+            for attempt in 0..=retries {
+                let timeout = Duration::from_millis(timeout_ms);
+                match run_hook_process(&mut cmd, &wasm, timeout).await {
+                    None => warn!("{hook} hook timed out after {timeout_ms} ms"),
+                    Some(Err(e)) => warn!("{hook} hook failed to spawn: {e}"),
+                    Some(Ok(output)) if !output.status.success() => {
+                        let code = output.status.code().unwrap_or(-1);
+                        let stderr = String::from_utf8_lossy(&output.stderr);
+                        let snippet: String = stderr.chars().take(512).collect();
+                        warn!("{hook} hook exited with code {code}: {snippet}");
+                        if attempt < retries {
+                            continue;
+                        }
+                    }
+                    Some(Ok(_)) => {}
+                }
+                break;
+            }
+        });
+    }
+
     async fn maybe_run_hook_json(
         &self,
-        argv: &Option<Vec<String>>,
+        sub_id: &str,
+        kind: HookKind,
+        hook: &Option<HookCommand>,
         payload: serde_json::Value,
     ) -> Result<(), String> {
-        match argv {
+        match hook {
             None => Ok(()),
-            Some(cmd) => {
-                let json = serde_json::to_string(&payload)
+            Some(hook) => {
+                let json = serde_json::to_string(&self.hook_payload(payload))
                     .map_err(|e| format!("failed to serialize hook payload: {e}"))?;
-                self.run_hook_argv(cmd, &json).await
+                let run = self.hook_run(sub_id, kind, None, hook.timeout_ms, hook.retries);
+                self.run_hook_argv_with_env(run, &hook.argv, &json, &[])
+                    .await
+                    .map(|_| ())
+            }
+        }
+    }
+
+    /// Runs the `session_start` hook once the session is configured. If the
+    /// hook prints `{"additional_context": "..."}`, that text is added to the
+    /// conversation as a user message.
+    async fn run_session_start_hook(&self, turn_context: &TurnContext) {
+        let Some(hook) = &self.hooks().session_start else {
+            return;
+        };
+        let cwd = &turn_context.cwd;
+        let git_root = find_git_root_for(cwd).unwrap_or_else(|| cwd.to_path_buf());
+        let payload = serde_json::json!({
+            "type": "session-start",
+            "session_id": self.conversation_id,
+            "cwd": cwd.to_string_lossy(),
+            "git_root": git_root.to_string_lossy(),
+            "model": turn_context.client.get_model(),
+            "sandbox_policy": turn_context.sandbox_policy,
+        });
+        let json = match serde_json::to_string(&self.hook_payload(payload)) {
+            Ok(s) => s,
+            Err(e) => {
+                self.send_error_event(
+                    INITIAL_SUBMIT_ID,
+                    format!("failed to serialize hook payload: {e}"),
+                )
+                .await;
+                return;
+            }
+        };
+        let stdout = match self
+            .run_hook_argv_with_env(
+                self.hook_run(
+                    INITIAL_SUBMIT_ID,
+                    HookKind::SessionStart,
+                    None,
+                    hook.timeout_ms,
+                    hook.retries,
+                ),
+                &hook.argv,
+                &json,
+                &[("SESSION_ID", self.conversation_id.to_string())],
+            )
+            .await
+        {
+            Ok(stdout) => stdout,
+            Err(e) => {
+                self.send_error_event(INITIAL_SUBMIT_ID, format!("session_start hook failed: {e}"))
+                    .await;
+                return;
+            }
+        };
+
+        let stdout = stdout.trim();
+        if stdout.is_empty() {
+            return;
+        }
+        match serde_json::from_str::<SessionStartHookOutput>(stdout) {
+            Ok(SessionStartHookOutput {
+                additional_context: Some(context),
+            }) if !context.trim().is_empty() => {
+                self.record_conversation_items(&[ResponseItem::Message {
+                    id: None,
+                    role: "user".to_string(),
+                    content: vec![ContentItem::InputText { text: context }],
+                }])
+                .await;
+            }
+            Ok(_) => {}
+            Err(e) => {
+                self.send_error_event(
+                    INITIAL_SUBMIT_ID,
+                    format!("session_start hook returned invalid JSON: {e}"),
+                )
+                .await;
             }
         }
     }
@@ -1214,7 +2692,12 @@ impl Session {
             "images": images,
         });
         if let Err(e) = self
-            .maybe_run_hook_json(&self.hooks().user_prompt_submit, payload)
+            .maybe_run_hook_json(
+                sub_id,
+                HookKind::UserPromptSubmit,
+                &self.hooks().user_prompt_submit,
+                payload,
+            )
             .await
         {
             self.send_error_event(sub_id, format!("user_prompt_submit hook failed: {e}"))
@@ -1223,6 +2706,10 @@ impl Session {
     }
 
     #[allow(clippy::too_many_arguments)]
+    /// Runs the matching pre-tool hooks. A hook may print a JSON decision on
+    /// stdout; see [`PreToolHookOutput`]. Returns the replacement arguments
+    /// when a hook modified them, or the message to send back to the model
+    /// when the call must not run.
     pub async fn run_pre_tool_hook(
         &self,
         sub_id: &str,
@@ -1231,9 +2718,9 @@ impl Session {
         cwd: &Path,
         arguments: serde_json::Value,
         targets: Option<Vec<PathBuf>>,
-    ) -> Result<(), String> {
+    ) -> Result<Option<serde_json::Value>, String> {
         if !self.hooks().pre_tool_use_match.should_run_for(tool) {
-            return Ok(());
+            return Ok(None);
         }
         let git_root = find_git_root_for(cwd).unwrap_or_else(|| cwd.to_path_buf());
         let target_list = targets.map(|v| {
@@ -1241,33 +2728,54 @@ impl Session {
                 .map(|p| p.canonicalize().unwrap_or(p).to_string_lossy().to_string())
                 .collect::<Vec<String>>()
         });
-        let payload = serde_json::json!({
-            "type": "pre-tool-use",
-            "sub_id": sub_id,
-            "call_id": call_id,
-            "tool": tool,
-            "cwd": cwd.to_string_lossy(),
-            "git_root": git_root.to_string_lossy(),
-            "targets": target_list,
-            "arguments": arguments,
-        });
-        let json = serde_json::to_string(&payload)
-            .map_err(|e| format!("failed to serialize hook payload: {e}"))?;
+        let hook_env = [
+            ("TOOL_ID", tool.to_string()),
+            ("SUB_ID", sub_id.to_string()),
+            ("CALL_ID", call_id.to_string()),
+            ("GIT_ROOT", git_root.to_string_lossy().to_string()),
+        ];
+        let mut arguments = arguments;
+        let mut modified = false;
+        let mut ask: Option<Option<String>> = None;
         for rule in &self.hooks().pre_tool_use_rules {
-            if rule.matcher.should_run_for(tool) {
-                if let Err(e) = self
-                    .run_hook_argv_with_env(
-                        &rule.argv,
-                        &json,
-                        &[
-                            ("TOOL_ID", tool.to_string()),
-                            ("SUB_ID", sub_id.to_string()),
-                            ("CALL_ID", call_id.to_string()),
-                            ("GIT_ROOT", git_root.to_string_lossy().to_string()),
-                        ],
-                    )
-                    .await
-                {
+            if !rule.matcher.should_run_for(tool) {
+                continue;
+            }
+            // Later hooks see the arguments as modified by earlier ones.
+            let payload = serde_json::json!({
+                "type": "pre-tool-use",
+                "sub_id": sub_id,
+                "call_id": call_id,
+                "tool": tool,
+                "cwd": cwd.to_string_lossy(),
+                "git_root": git_root.to_string_lossy(),
+                "targets": target_list,
+                "arguments": arguments,
+            });
+            let json = serde_json::to_string(&self.hook_payload(payload)).map_err(|e| {
+                format!("pre_tool_use hook failed: failed to serialize hook payload: {e}")
+            })?;
+            if !rule.blocking {
+                self.spawn_detached_hook(HookKind::PreToolUse, rule, json, &hook_env);
+                continue;
+            }
+            let stdout = match self
+                .run_hook_argv_with_env(
+                    self.hook_run(
+                        sub_id,
+                        HookKind::PreToolUse,
+                        Some(tool),
+                        rule.timeout_ms,
+                        rule.retries,
+                    ),
+                    &rule.argv,
+                    &json,
+                    &hook_env,
+                )
+                .await
+            {
+                Ok(stdout) => stdout,
+                Err(e) => {
                     let el = e.to_lowercase();
                     let looks_like_missing = el.contains("failed to spawn hook")
                         && (el.contains("no such file or directory") || el.contains("not found"));
@@ -1276,11 +2784,71 @@ impl Session {
                             .await;
                         continue;
                     }
-                    return Err(e);
+                    return Err(format!("pre_tool_use hook failed: {e}"));
+                }
+            };
+
+            let stdout = stdout.trim();
+            if stdout.is_empty() {
+                continue;
+            }
+            let PreToolHookOutput {
+                decision,
+                reason,
+                modified_arguments,
+            } = match serde_json::from_str(stdout) {
+                Ok(output) => output,
+                Err(e) => {
+                    self.send_error_event(
+                        sub_id,
+                        format!("pre_tool_use hook returned invalid JSON: {e}"),
+                    )
+                    .await;
+                    continue;
+                }
+            };
+            if let Some(modified_arguments) = modified_arguments {
+                arguments = modified_arguments;
+                modified = true;
+            }
+            match decision {
+                Some(PreToolHookDecision::Deny) => {
+                    return Err(match reason {
+                        Some(reason) => {
+                            format!("{tool} call denied by pre_tool_use hook: {reason}")
+                        }
+                        None => format!("{tool} call denied by pre_tool_use hook"),
+                    });
                 }
+                Some(PreToolHookDecision::Ask) => {
+                    ask = Some(reason.or(ask.flatten()));
+                }
+                Some(PreToolHookDecision::Allow) | None => {}
             }
         }
-        Ok(())
+
+        if let Some(reason) = ask {
+            let command = hook_approval_command(tool, &arguments);
+            let decision = self
+                .request_command_approval(
+                    sub_id.to_string(),
+                    call_id.to_string(),
+                    command.clone(),
+                    cwd.to_path_buf(),
+                    reason,
+                )
+                .await;
+            match decision {
+                ReviewDecision::Approved => {}
+                ReviewDecision::ApprovedForSession => self.add_approved_command(command).await,
+                ReviewDecision::ApprovedAlways => self.approve_command_always(command).await,
+                ReviewDecision::Denied | ReviewDecision::Abort => {
+                    return Err(format!("{tool} call rejected by user"));
+                }
+            }
+        }
+
+        Ok(modified.then_some(arguments))
     }
 
     #[allow(clippy::too_many_arguments)]
@@ -1292,6 +2860,7 @@ impl Session {
         cwd: &Path,
         success: Option<bool>,
         output: Option<&str>,
+        duration: Duration,
         arguments: serde_json::Value,
         edited: Option<Vec<PathBuf>>,
         deleted: Option<Vec<PathBuf>>,
@@ -1331,13 +2900,14 @@ impl Session {
             "git_root": git_root.to_string_lossy(),
             "success": success,
             "output": limited,
+            "duration_ms": u64::try_from(duration.as_millis()).unwrap_or(u64::MAX),
             "arguments": arguments,
             "edited": map_paths(edited),
             "deleted": map_paths(deleted),
             "created": map_paths(created),
             "renamed": map_ren(renamed),
         });
-        let json = match serde_json::to_string(&payload) {
+        let json = match serde_json::to_string(&self.hook_payload(payload)) {
             Ok(s) => s,
             Err(e) => {
                 self.send_error_event(sub_id, format!("failed to serialize hook payload: {e}"))
@@ -1345,37 +2915,87 @@ impl Session {
                 return;
             }
         };
+        let hook_env = [
+            ("TOOL_ID", tool.to_string()),
+            ("SUB_ID", sub_id.to_string()),
+            ("CALL_ID", call_id.to_string()),
+            ("GIT_ROOT", git_root.to_string_lossy().to_string()),
+        ];
+        let mut additional_context = Vec::new();
         for rule in &self.hooks().post_tool_use_rules {
-            if rule.matcher.should_run_for(tool)
-                && let Err(e) = self
-                    .run_hook_argv_with_env(
-                        &rule.argv,
-                        &json,
-                        &[
-                            ("TOOL_ID", tool.to_string()),
-                            ("SUB_ID", sub_id.to_string()),
-                            ("CALL_ID", call_id.to_string()),
-                            ("GIT_ROOT", git_root.to_string_lossy().to_string()),
-                        ],
-                    )
-                    .await
+            if !rule.matcher.should_run_for(tool) {
+                continue;
+            }
+            if !rule.blocking {
+                self.spawn_detached_hook(HookKind::PostToolUse, rule, json.clone(), &hook_env);
+                continue;
+            }
+            let stdout = match self
+                .run_hook_argv_with_env(
+                    self.hook_run(
+                        sub_id,
+                        HookKind::PostToolUse,
+                        Some(tool),
+                        rule.timeout_ms,
+                        rule.retries,
+                    ),
+                    &rule.argv,
+                    &json,
+                    &hook_env,
+                )
+                .await
             {
-                self.send_error_event(sub_id, format!("post_tool_use hook failed: {e}"))
+                Ok(stdout) => stdout,
+                Err(e) => {
+                    self.send_error_event(sub_id, format!("post_tool_use hook failed: {e}"))
+                        .await;
+                    continue;
+                }
+            };
+
+            let stdout = stdout.trim();
+            if stdout.is_empty() {
+                continue;
+            }
+            match serde_json::from_str::<PostToolHookOutput>(stdout) {
+                Ok(PostToolHookOutput {
+                    additional_context: Some(context),
+                }) if !context.trim().is_empty() => additional_context.push(context),
+                Ok(_) => {}
+                Err(e) => {
+                    self.send_error_event(
+                        sub_id,
+                        format!("post_tool_use hook returned invalid JSON: {e}"),
+                    )
                     .await;
+                }
+            }
+        }
+
+        // Queue the context as a note on this call so the model sees it on
+        // its next request, right after the tool output.
+        if !additional_context.is_empty() {
+            let items = vec![InputItem::Text {
+                text: post_tool_hook_note(tool, call_id, &additional_context),
+            }];
+            if let Err(items) = self.inject_input(items).await {
+                let message = ResponseInputItem::from(items);
+                self.record_conversation_items(&[message.into()]).await;
             }
         }
     }
 
     pub async fn check_stop_hook(&self, sub_id: &str) -> StopHookDecision {
-        let Some(argv) = &self.hooks().stop else {
+        let Some(hook) = &self.hooks().stop else {
             return StopHookDecision::Approve;
         };
+        let argv = &hook.argv;
 
         let payload = serde_json::json!({
             "type": "stop",
             "sub_id": sub_id,
         });
-        let json_arg = match serde_json::to_string(&payload) {
+        let json_arg = match serde_json::to_string(&self.hook_payload(payload)) {
             Ok(s) => s,
             Err(e) => {
                 self.send_error_event(sub_id, format!("stop hook payload serialize failed: {e}"))
@@ -1391,22 +3011,22 @@ impl Session {
         cmd.arg(json_arg);
         cmd.stdout(Stdio::piped()).stderr(Stdio::piped());
 
-        let timeout_dur = Duration::from_millis(self.hooks().timeout_ms);
-        let output = match tokio::time::timeout(timeout_dur, cmd.output()).await {
-            Err(_) => {
+        let run = self.hook_run(sub_id, HookKind::Stop, None, hook.timeout_ms, hook.retries);
+        let output = match self.run_hook_command(&run, cmd).await {
+            None => {
                 self.send_error_event(
                     sub_id,
-                    format!("stop hook timed out after {} ms", self.hooks().timeout_ms),
+                    format!("stop hook timed out after {} ms", run.timeout_ms),
                 )
                 .await;
                 return StopHookDecision::Approve;
             }
-            Ok(Err(e)) => {
+            Some(Err(e)) => {
                 self.send_error_event(sub_id, format!("failed to spawn stop hook: {e}"))
                     .await;
                 return StopHookDecision::Approve;
             }
-            Ok(Ok(o)) => o,
+            Some(Ok(o)) => o,
         };
 
         if !output.status.success() {
@@ -1417,36 +3037,452 @@ impl Session {
                 sub_id,
                 format!("stop hook exited with code {code}: {snippet}"),
             )
-            .await;
-            return StopHookDecision::Approve;
+            .await;
+            return StopHookDecision::Approve;
+        }
+
+        let stdout = String::from_utf8_lossy(&output.stdout).trim().to_string();
+        if stdout.is_empty() {
+            return StopHookDecision::Approve;
+        }
+
+        let parsed: Result<StopHookOutput, _> = serde_json::from_str(&stdout);
+        match parsed {
+            Ok(StopHookOutput { decision, reason }) => match decision.as_deref() {
+                Some("block") => StopHookDecision::Block(reason.unwrap_or_default()),
+                _ => StopHookDecision::Approve,
+            },
+            Err(e) => {
+                self.send_error_event(sub_id, format!("stop hook returned invalid JSON: {e}"))
+                    .await;
+                StopHookDecision::Approve
+            }
+        }
+    }
+
+    /// Runs the `turn_complete` hook once a task has finished, before
+    /// `TaskComplete` is sent so that clients which exit on that event (such
+    /// as `codex exec`) wait for it. Its output is ignored.
+    async fn run_turn_complete_hook(
+        &self,
+        sub_id: &str,
+        turn_context: &TurnContext,
+        last_agent_message: Option<&str>,
+        turn_diff: Option<String>,
+        token_usage: &TokenUsage,
+    ) {
+        let Some(hook) = &self.hooks().turn_complete else {
+            return;
+        };
+        let cwd = &turn_context.cwd;
+        let git_root = find_git_root_for(cwd).unwrap_or_else(|| cwd.to_path_buf());
+        let total_token_usage = self
+            .state
+            .lock()
+            .await
+            .token_info
+            .as_ref()
+            .map(|info| info.total_token_usage.clone());
+        let payload = serde_json::json!({
+            "type": "turn-complete",
+            "sub_id": sub_id,
+            "cwd": cwd.to_string_lossy(),
+            "git_root": git_root.to_string_lossy(),
+            "model": turn_context.client.get_model(),
+            "last_agent_message": last_agent_message,
+            "turn_diff": turn_diff,
+            "token_usage": token_usage,
+            "total_token_usage": total_token_usage,
+        });
+        let json = match serde_json::to_string(&self.hook_payload(payload)) {
+            Ok(json) => json,
+            Err(e) => {
+                self.send_error_event(sub_id, format!("failed to serialize hook payload: {e}"))
+                    .await;
+                return;
+            }
+        };
+        if let Err(e) = self
+            .run_hook_argv_with_env(
+                self.hook_run(
+                    sub_id,
+                    HookKind::TurnComplete,
+                    None,
+                    hook.timeout_ms,
+                    hook.retries,
+                ),
+                &hook.argv,
+                &json,
+                &[("SUB_ID", sub_id.to_string())],
+            )
+            .await
+        {
+            self.send_error_event(sub_id, format!("turn_complete hook failed: {e}"))
+                .await;
+        }
+    }
+
+    /// Runs the `post_turn_diff` hook with the turn's diff so far. Diffs over
+    /// `hooks.diff_inline_max_bytes` are written to a temp file that is
+    /// removed once the hook exits. Its output is ignored.
+    async fn run_post_turn_diff_hook(
+        &self,
+        sub_id: &str,
+        cwd: &Path,
+        unified_diff: &str,
+        files: &[PathBuf],
+    ) {
+        let Some(hook) = &self.hooks().post_turn_diff else {
+            return;
+        };
+        let git_root = find_git_root_for(cwd).unwrap_or_else(|| cwd.to_path_buf());
+        let diff_file = if unified_diff.len() > self.hooks().diff_inline_max_bytes {
+            let written = tempfile::Builder::new()
+                .prefix("codex-turn-diff-")
+                .suffix(".diff")
+                .tempfile()
+                .and_then(|mut file| {
+                    file.write_all(unified_diff.as_bytes())?;
+                    Ok(file)
+                });
+            match written {
+                Ok(file) => Some(file),
+                Err(e) => {
+                    self.send_error_event(
+                        sub_id,
+                        format!("post_turn_diff hook failed: could not write diff file: {e}"),
+                    )
+                    .await;
+                    return;
+                }
+            }
+        } else {
+            None
+        };
+        let payload = serde_json::json!({
+            "type": "post-turn-diff",
+            "sub_id": sub_id,
+            "cwd": cwd.to_string_lossy(),
+            "git_root": git_root.to_string_lossy(),
+            "files": files.iter().map(|path| path.to_string_lossy()).collect::<Vec<_>>(),
+            "unified_diff": diff_file.is_none().then_some(unified_diff),
+            "unified_diff_path": diff_file.as_ref().map(|file| file.path().to_string_lossy()),
+        });
+        let json = match serde_json::to_string(&self.hook_payload(payload)) {
+            Ok(json) => json,
+            Err(e) => {
+                self.send_error_event(sub_id, format!("failed to serialize hook payload: {e}"))
+                    .await;
+                return;
+            }
+        };
+        if let Err(e) = self
+            .run_hook_argv_with_env(
+                self.hook_run(
+                    sub_id,
+                    HookKind::PostTurnDiff,
+                    None,
+                    hook.timeout_ms,
+                    hook.retries,
+                ),
+                &hook.argv,
+                &json,
+                &[("SUB_ID", sub_id.to_string())],
+            )
+            .await
+        {
+            self.send_error_event(sub_id, format!("post_turn_diff hook failed: {e}"))
+                .await;
         }
+    }
 
-        let stdout = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    /// Runs the `pre_model_call` hook with a summary of `prompt` (see
+    /// [`pre_model_call_summary`]). Returns `Err` with the reason when the
+    /// hook blocks the request or fails.
+    async fn run_pre_model_call_hook(
+        &self,
+        sub_id: &str,
+        turn_context: &TurnContext,
+        prompt: &Prompt,
+    ) -> Result<(), String> {
+        let Some(hook) = &self.hooks().pre_model_call else {
+            return Ok(());
+        };
+        let mut payload = pre_model_call_summary(prompt);
+        payload["type"] = "pre-model-call".into();
+        payload["sub_id"] = sub_id.into();
+        payload["model"] = turn_context.client.get_model().into();
+        let json = serde_json::to_string(&self.hook_payload(payload))
+            .map_err(|e| format!("failed to serialize pre_model_call hook payload: {e}"))?;
+        let stdout = self
+            .run_hook_argv_with_env(
+                self.hook_run(
+                    sub_id,
+                    HookKind::PreModelCall,
+                    None,
+                    hook.timeout_ms,
+                    hook.retries,
+                ),
+                &hook.argv,
+                &json,
+                &[("SUB_ID", sub_id.to_string())],
+            )
+            .await
+            .map_err(|e| format!("pre_model_call hook failed: {e}"))?;
+
+        let stdout = stdout.trim();
         if stdout.is_empty() {
-            return StopHookDecision::Approve;
+            return Ok(());
         }
-
-        let parsed: Result<StopHookOutput, _> = serde_json::from_str(&stdout);
-        match parsed {
-            Ok(StopHookOutput { decision, reason }) => match decision.as_deref() {
-                Some("block") => StopHookDecision::Block(reason.unwrap_or_default()),
-                _ => StopHookDecision::Approve,
-            },
-            Err(e) => {
-                self.send_error_event(sub_id, format!("stop hook returned invalid JSON: {e}"))
-                    .await;
-                StopHookDecision::Approve
-            }
+        match serde_json::from_str::<PreModelCallHookOutput>(stdout) {
+            Ok(PreModelCallHookOutput {
+                decision: Some(decision),
+                reason,
+            }) if decision == "block" => Err(match reason {
+                Some(reason) => format!("model request blocked by pre_model_call hook: {reason}"),
+                None => "model request blocked by pre_model_call hook".to_string(),
+            }),
+            Ok(_) => Ok(()),
+            Err(e) => Err(format!("pre_model_call hook returned invalid JSON: {e}")),
         }
     }
 }
 
+/// Logs a hook about to run. The payload is the last argument and is
+/// logged on its own line so it can be copied into `codex hooks test`.
+fn trace_hook_invocation(
+    hook: HookKind,
+    tool: Option<&str>,
+    cmd: &std::process::Command,
+    simulated: bool,
+) {
+    let mut args: Vec<String> = cmd
+        .get_args()
+        .map(|arg| arg.to_string_lossy().into_owned())
+        .collect();
+    let payload = args.pop().unwrap_or_default();
+    let program = cmd.get_program().to_string_lossy();
+    let tool = tool.map(|tool| format!(" for {tool}")).unwrap_or_default();
+    let verb = if simulated { "simulating" } else { "running" };
+    info!("{verb} {hook} hook{tool}: {program} {args:?}\npayload: {payload}");
+}
+
+fn trace_hook_result(hook: HookKind, result: &Option<std::io::Result<std::process::Output>>) {
+    match result {
+        None => info!("{hook} hook timed out"),
+        Some(Err(e)) => info!("{hook} hook failed to spawn: {e}"),
+        Some(Ok(output)) => info!(
+            "{hook} hook exited with code {}\nstdout: {}\nstderr: {}",
+            output.status.code().unwrap_or(-1),
+            String::from_utf8_lossy(&output.stdout),
+            String::from_utf8_lossy(&output.stderr),
+        ),
+    }
+}
+
+/// What a simulated hook returns: a successful exit with no output.
+fn simulated_hook_output() -> std::process::Output {
+    std::process::Output {
+        status: std::process::ExitStatus::default(),
+        stdout: Vec::new(),
+        stderr: Vec::new(),
+    }
+}
+
+/// Payload fields added after version 1, as `(version, payload type, field)`.
+/// Hooks pinned to an older `hooks.payload_version` do not receive them.
+const HOOK_PAYLOAD_FIELDS_SINCE: &[(u32, &str, &str)] = &[(2, "post-tool-use", "duration_ms")];
+
+/// The note that carries `additional_context` from the post_tool_use hooks of
+/// one call to the model. It names the call so the model reads it as output
+/// of that tool call rather than as a request from the user.
+fn post_tool_hook_note(tool: &str, call_id: &str, contexts: &[String]) -> String {
+    format!(
+        "Note from post_tool_use hooks on the `{tool}` call {call_id}:\n{}",
+        contexts.join("\n\n")
+    )
+}
+
+/// Which hook is running, for the `HookBegin`/`HookEnd` events.
+struct HookRun<'a> {
+    sub_id: &'a str,
+    hook: HookKind,
+    tool: Option<&'a str>,
+    timeout_ms: u64,
+    retries: u32,
+}
+
 #[derive(Debug)]
 pub(crate) enum StopHookDecision {
     Approve,
     Block(String),
 }
 
+#[derive(serde::Deserialize)]
+struct SessionStartHookOutput {
+    #[serde(default)]
+    additional_context: Option<String>,
+}
+
+/// Output a post-tool hook may print to stdout as JSON. Empty output means
+/// there is nothing to add.
+#[derive(serde::Deserialize)]
+struct PostToolHookOutput {
+    /// Appended as a user message before the model's next turn.
+    #[serde(default)]
+    additional_context: Option<String>,
+}
+
+/// Decision a pre-tool hook may print to stdout as JSON. Empty output means
+/// the call is allowed unchanged.
+#[derive(serde::Deserialize)]
+struct PreToolHookOutput {
+    #[serde(default)]
+    decision: Option<PreToolHookDecision>,
+    #[serde(default)]
+    reason: Option<String>,
+    /// Replaces the `arguments` the hook was given.
+    #[serde(default)]
+    modified_arguments: Option<serde_json::Value>,
+}
+
+#[derive(serde::Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+enum PreToolHookDecision {
+    Allow,
+    Deny,
+    Ask,
+}
+
+/// Tool call arguments longer than this are truncated in the
+/// `pre_model_call` hook payload.
+const PRE_MODEL_CALL_MAX_ARGUMENT_BYTES: usize = 1024;
+
+/// Compact description of a prompt for the `pre_model_call` hook: how many
+/// input items of each type it holds, an estimate of its size in tokens, the
+/// tools offered to the model, and the tool calls (with their arguments)
+/// already in the conversation.
+fn pre_model_call_summary(prompt: &Prompt) -> serde_json::Value {
+    let mut item_counts = BTreeMap::<String, u64>::new();
+    let mut input_bytes = 0u64;
+    let mut tool_calls = Vec::new();
+    for item in &prompt.input {
+        let value = serde_json::to_value(item).unwrap_or_default();
+        let item_type = value["type"].as_str().unwrap_or("unknown").to_string();
+        *item_counts.entry(item_type).or_default() += 1;
+        input_bytes += value.to_string().len() as u64;
+
+        let (name, arguments) = match item {
+            ResponseItem::FunctionCall {
+                name, arguments, ..
+            } => (name.as_str(), arguments.clone()),
+            ResponseItem::CustomToolCall { name, input, .. } => (name.as_str(), input.clone()),
+            ResponseItem::LocalShellCall { action, .. } => (
+                "local_shell",
+                serde_json::to_string(action).unwrap_or_default(),
+            ),
+            _ => continue,
+        };
+        tool_calls.push(serde_json::json!({
+            "name": name,
+            "arguments": truncate_middle(&arguments, PRE_MODEL_CALL_MAX_ARGUMENT_BYTES).0,
+        }));
+    }
+    let tools = prompt
+        .tools
+        .iter()
+        .map(|tool| match tool {
+            OpenAiTool::Function(tool) => tool.name.as_str(),
+            OpenAiTool::Freeform(tool) => tool.name.as_str(),
+            OpenAiTool::LocalShell {} => "local_shell",
+            OpenAiTool::WebSearch {} => "web_search",
+        })
+        .collect::<Vec<_>>();
+
+    serde_json::json!({
+        "item_counts": item_counts,
+        "estimated_tokens": input_bytes.div_ceil(4),
+        "tools": tools,
+        "tool_calls": tool_calls,
+    })
+}
+
+/// Arguments passed to pre/post tool hooks for shell calls.
+fn shell_hook_arguments(params: &ExecParams) -> serde_json::Value {
+    serde_json::json!({
+        "command": params.command.join(" "),
+        "argv": params.command.clone(),
+        "workdir": params.cwd.clone(),
+        "timeout_ms": params.timeout_ms,
+    })
+}
+
+/// Applies `modified_arguments` returned by a pre-tool hook for a shell call.
+/// Fields are the same as in [`shell_hook_arguments`]; `argv` is authoritative
+/// and omitted fields keep their current values.
+fn apply_shell_hook_arguments(
+    params: &mut ExecParams,
+    modified: serde_json::Value,
+    turn_context: &TurnContext,
+) -> Result<(), String> {
+    #[derive(Deserialize)]
+    struct ShellHookArguments {
+        #[serde(default)]
+        argv: Option<Vec<String>>,
+        #[serde(default)]
+        workdir: Option<PathBuf>,
+        #[serde(default)]
+        timeout_ms: Option<u64>,
+    }
+
+    let args: ShellHookArguments = serde_json::from_value(modified)
+        .map_err(|e| format!("pre_tool_use hook returned invalid modified_arguments: {e}"))?;
+    if let Some(argv) = args.argv {
+        if argv.is_empty() {
+            return Err(
+                "pre_tool_use hook returned invalid modified_arguments: empty argv".to_string(),
+            );
+        }
+        params.command = argv;
+    }
+    if let Some(workdir) = args.workdir {
+        params.cwd = turn_context.cwd.join(workdir);
+    }
+    if let Some(timeout_ms) = args.timeout_ms {
+        params.timeout_ms = Some(timeout_ms);
+    }
+    Ok(())
+}
+
+fn parse_hook_modified_arguments<T: serde::de::DeserializeOwned>(
+    modified: serde_json::Value,
+) -> Result<T, FunctionCallError> {
+    serde_json::from_value(modified).map_err(|e| {
+        FunctionCallError::RespondToModel(format!(
+            "pre_tool_use hook returned invalid modified_arguments: {e}"
+        ))
+    })
+}
+
+/// Command shown to the user when a pre-tool hook asks for approval. Shell
+/// calls show their argv; other tools show the tool name and arguments.
+fn hook_approval_command(tool: &str, arguments: &serde_json::Value) -> Vec<String> {
+    arguments
+        .get("argv")
+        .and_then(|argv| serde_json::from_value::<Vec<String>>(argv.clone()).ok())
+        .unwrap_or_else(|| vec![tool.to_string(), arguments.to_string()])
+}
+
+/// Output a `pre_model_call` hook may print to stdout as JSON. Only
+/// `"decision": "block"` has an effect.
+#[derive(serde::Deserialize)]
+struct PreModelCallHookOutput {
+    #[serde(default)]
+    decision: Option<String>,
+    #[serde(default)]
+    reason: Option<String>,
+}
+
 #[derive(serde::Deserialize)]
 struct StopHookOutput {
     #[serde(default)]
@@ -1474,6 +3510,7 @@ pub(crate) struct ExecCommandContext {
 pub(crate) struct ApplyPatchCommandContext {
     pub(crate) user_explicitly_approved_this_action: bool,
     pub(crate) changes: HashMap<PathBuf, FileChange>,
+    pub(crate) snapshot_id: Option<String>,
 }
 
 #[derive(Clone, Debug, Eq, PartialEq)]
@@ -1483,11 +3520,16 @@ enum AgentTaskKind {
     Compact,
 }
 
+/// How long an interrupted task gets to stop its tools and record their
+/// partial output before it is aborted outright.
+const TASK_CANCELLATION_GRACE: Duration = Duration::from_secs(2);
+
 /// A series of Turns in response to user input.
 pub(crate) struct AgentTask {
     sess: Arc<Session>,
     sub_id: String,
-    handle: AbortHandle,
+    handle: JoinHandle<()>,
+    cancellation_token: CancellationToken,
     kind: AgentTaskKind,
 }
 
@@ -1498,16 +3540,22 @@ impl AgentTask {
         sub_id: String,
         input: Vec<InputItem>,
     ) -> Self {
+        let cancellation_token = CancellationToken::new();
         let handle = {
             let sess = sess.clone();
             let sub_id = sub_id.clone();
             let tc = Arc::clone(&turn_context);
-            tokio::spawn(async move { run_task(sess, tc, sub_id, input).await }).abort_handle()
+            let token = cancellation_token.clone();
+            tokio::spawn(async move {
+                run_task(sess.clone(), tc, sub_id, input, token).await;
+                sess.start_next_queued_task().await;
+            })
         };
         Self {
             sess,
             sub_id,
             handle,
+            cancellation_token,
             kind: AgentTaskKind::Regular,
         }
     }
@@ -1518,16 +3566,22 @@ impl AgentTask {
         sub_id: String,
         input: Vec<InputItem>,
     ) -> Self {
+        let cancellation_token = CancellationToken::new();
         let handle = {
             let sess = sess.clone();
             let sub_id = sub_id.clone();
             let tc = Arc::clone(&turn_context);
-            tokio::spawn(async move { run_task(sess, tc, sub_id, input).await }).abort_handle()
+            let token = cancellation_token.clone();
+            tokio::spawn(async move {
+                run_task(sess.clone(), tc, sub_id, input, token).await;
+                sess.start_next_queued_task().await;
+            })
         };
         Self {
             sess,
             sub_id,
             handle,
+            cancellation_token,
             kind: AgentTaskKind::Review,
         }
     }
@@ -1542,34 +3596,59 @@ impl AgentTask {
             let sess = sess.clone();
             let sub_id = sub_id.clone();
             let tc = Arc::clone(&turn_context);
-            tokio::spawn(async move { compact::run_compact_task(sess, tc, sub_id, input).await })
-                .abort_handle()
+            tokio::spawn(async move {
+                compact::run_compact_task(sess.clone(), tc, sub_id, input).await;
+                sess.start_next_queued_task().await;
+            })
         };
         Self {
             sess,
             sub_id,
             handle,
+            cancellation_token: CancellationToken::new(),
             kind: AgentTaskKind::Compact,
         }
     }
 
+    /// Cancels the task so its running tools stop and their partial output is
+    /// recorded, aborting it if it has not finished within
+    /// [`TASK_CANCELLATION_GRACE`]. Compaction does not observe the token and
+    /// is aborted right away.
     fn abort(self, reason: TurnAbortReason) {
         // TOCTOU?
         if !self.handle.is_finished() {
-            self.handle.abort();
+            self.cancellation_token.cancel();
+            let abort_handle = self.handle.abort_handle();
+            if self.kind == AgentTaskKind::Compact {
+                abort_handle.abort();
+            }
+            let handle = self.handle;
             let sub_id = self.sub_id.clone();
             let is_review = self.kind == AgentTaskKind::Review;
             let sess = self.sess;
             let event = Event {
                 id: sub_id.clone(),
-                msg: EventMsg::TurnAborted(TurnAbortedEvent { reason }),
+                msg: EventMsg::TurnAborted(TurnAbortedEvent {
+                    reason,
+                    exceeded_limit: None,
+                    budget_used: None,
+                    budget_allowed: None,
+                }),
             };
             tokio::spawn(async move {
+                if tokio::time::timeout(TASK_CANCELLATION_GRACE, handle)
+                    .await
+                    .is_err()
+                {
+                    abort_handle.abort();
+                }
                 if is_review {
                     exit_review_mode(sess.clone(), sub_id.clone(), None).await;
                 }
+                sess.leave_task_work_branch(&sub_id).await;
                 // Ensure active turn state is cleared when a task is aborted.
                 sess.remove_task(&sub_id).await;
+                sess.record_state_snapshot().await;
                 sess.send_event(event).await;
             });
         }
@@ -1595,13 +3674,45 @@ async fn submission_loop(
                 cwd,
                 approval_policy,
                 sandbox_policy,
+                sandbox_preset,
                 model,
                 effort,
                 summary,
+                dry_run,
             } => {
                 // Recalculate the persistent turn context with provided overrides.
                 let prev = Arc::clone(&turn_context);
-                let provider = prev.client.get_provider();
+                let preset = match sandbox_preset {
+                    Some(name) => match config.sandbox_presets.get(&name) {
+                        Some(preset) => Some(preset),
+                        None => {
+                            sess.send_error_event(
+                                &sub.id,
+                                format!("sandbox preset `{name}` not found"),
+                            )
+                            .await;
+                            continue;
+                        }
+                    },
+                    None => None,
+                };
+                let approval_policy =
+                    approval_policy.or_else(|| preset.and_then(|preset| preset.approval_policy));
+                let sandbox_policy =
+                    sandbox_policy.or_else(|| preset.map(|preset| preset.sandbox_policy.clone()));
+                let alias = model.as_ref().and_then(|m| config.model_aliases.get(m));
+                let model = alias.map(|alias| alias.model.clone()).or(model);
+                let effort = effort.or_else(|| alias?.model_reasoning_effort.map(Some));
+                let provider = match alias.and_then(|alias| alias.model_provider.as_ref()) {
+                    Some(id) => match config.model_providers.get(id) {
+                        Some(provider) => provider.clone(),
+                        None => {
+                            warn!("model alias refers to unknown provider `{id}`");
+                            prev.client.get_provider()
+                        }
+                    },
+                    None => prev.client.get_provider(),
+                };
 
                 // Effective model + family
                 let (effective_model, effective_family) = if let Some(ref m) = model {
@@ -1648,6 +3759,8 @@ async fn submission_loop(
                     include_web_search_request: config.tools_web_search_request,
                     use_streamable_shell_tool: config.use_experimental_streamable_shell_tool,
                     include_view_image_tool: config.include_view_image_tool,
+                    include_scratch_path_tool: config.scratch_dir,
+                    include_delegate_tool: config.tools_delegate,
                     experimental_unified_exec_tool: config.use_experimental_unified_exec_tool,
                 });
 
@@ -1658,14 +3771,34 @@ async fn submission_loop(
                     base_instructions: prev.base_instructions.clone(),
                     approval_policy: new_approval_policy,
                     sandbox_policy: new_sandbox_policy.clone(),
-                    shell_environment_policy: prev.shell_environment_policy.clone(),
+                    shell_environment_policy: preset
+                        .and_then(|preset| preset.shell_environment_policy.clone())
+                        .unwrap_or_else(|| prev.shell_environment_policy.clone()),
+                    resource_limits: prev.resource_limits,
+                    sandbox_extras: prev.sandbox_extras.clone(),
                     cwd: new_cwd.clone(),
                     is_review_mode: false,
                     final_output_json_schema: None,
+                    dry_run: dry_run.unwrap_or(prev.dry_run),
+                    exec: prev.exec.clone(),
+                    task_budget: prev.task_budget,
                 };
 
                 // Install the new persistent context for subsequent tasks/turns.
                 turn_context = Arc::new(new_turn_context);
+                if let Some(live_session) = &sess.services.live_session {
+                    live_session.set_cwd(&turn_context.cwd);
+                }
+
+                if sandbox_policy.is_some()
+                    && let Some(message) = unenforced_network_warning(&turn_context.sandbox_policy)
+                {
+                    sess.send_event(Event {
+                        id: sub.id.clone(),
+                        msg: EventMsg::Warning(WarningEvent { message }),
+                    })
+                    .await;
+                }
 
                 // Optionally persist changes to model / effort
                 if cwd.is_some() || approval_policy.is_some() || sandbox_policy.is_some() {
@@ -1675,21 +3808,44 @@ async fn submission_loop(
                         sandbox_policy,
                         // Shell is not configurable from turn to turn
                         None,
+                        None,
                     ))])
                     .await;
                 }
             }
             Op::UserInput { items } => {
+                let items = CodexIgnore::load(&turn_context.cwd).filter_input(items);
                 sess.run_user_prompt_submit_hook(&sub.id, &items, &turn_context.cwd)
                     .await;
-                // attempt to inject input into current task
-                if let Err(items) = sess.inject_input(items).await {
+                // queue or inject the input if a task is running
+                if let Err(items) = sess.queue_task(&sub.id, items, &turn_context).await
+                    && let Err(items) = sess.inject_input(items).await
+                {
                     // no current task, spawn a new one
                     let task =
                         AgentTask::spawn(sess.clone(), Arc::clone(&turn_context), sub.id, items);
                     sess.set_task(task).await;
                 }
             }
+            Op::Steer { items } => {
+                let items = CodexIgnore::load(&turn_context.cwd).filter_input(items);
+                sess.run_user_prompt_submit_hook(&sub.id, &items, &turn_context.cwd)
+                    .await;
+                if let Err(items) = sess.steer(items).await {
+                    // nothing to steer, so start a turn with the guidance instead
+                    sess.notify_background_event(
+                        &sub.id,
+                        "No turn is running to steer; starting a new turn with the guidance.",
+                    )
+                    .await;
+                    let task =
+                        AgentTask::spawn(sess.clone(), Arc::clone(&turn_context), sub.id, items);
+                    sess.set_task(task).await;
+                }
+            }
+            Op::CancelQueuedTask { id } => {
+                sess.cancel_queued_task(&sub.id, &id).await;
+            }
             Op::UserTurn {
                 items,
                 cwd,
@@ -1699,7 +3855,11 @@ async fn submission_loop(
                 effort,
                 summary,
                 final_output_json_schema,
+                max_turns,
+                max_total_tokens,
+                max_duration,
             } => {
+                let items = CodexIgnore::load(&cwd).filter_input(items);
                 sess.run_user_prompt_submit_hook(&sub.id, &items, &turn_context.cwd)
                     .await;
                 // attempt to inject input into current task
@@ -1741,6 +3901,8 @@ async fn submission_loop(
                             use_streamable_shell_tool: config
                                 .use_experimental_streamable_shell_tool,
                             include_view_image_tool: config.include_view_image_tool,
+                            include_scratch_path_tool: config.scratch_dir,
+                            include_delegate_tool: config.tools_delegate,
                             experimental_unified_exec_tool: config
                                 .use_experimental_unified_exec_tool,
                         }),
@@ -1749,9 +3911,18 @@ async fn submission_loop(
                         approval_policy,
                         sandbox_policy,
                         shell_environment_policy: turn_context.shell_environment_policy.clone(),
+                        resource_limits: turn_context.resource_limits,
+                        sandbox_extras: turn_context.sandbox_extras.clone(),
                         cwd,
                         is_review_mode: false,
                         final_output_json_schema,
+                        dry_run: turn_context.dry_run,
+                        exec: turn_context.exec.clone(),
+                        task_budget: config.task_budget.with_overrides(
+                            max_turns,
+                            max_total_tokens,
+                            max_duration,
+                        ),
                     };
 
                     // if the environment context has changed, record it in the conversation history
@@ -1764,6 +3935,9 @@ async fn submission_loop(
 
                     // Install the new persistent context for subsequent tasks/turns.
                     turn_context = Arc::new(fresh_turn_context);
+                    if let Some(live_session) = &sess.services.live_session {
+                        live_session.set_cwd(&turn_context.cwd);
+                    }
 
                     // no current task, spawn a new one with the per‑turn context
                     let task =
@@ -1777,11 +3951,15 @@ async fn submission_loop(
                 }
                 other => sess.notify_approval(&id, other).await,
             },
-            Op::PatchApproval { id, decision } => match decision {
+            Op::PatchApproval {
+                id,
+                decision,
+                approved_files,
+            } => match decision {
                 ReviewDecision::Abort => {
                     sess.interrupt_task().await;
                 }
-                other => sess.notify_approval(&id, other).await,
+                other => sess.notify_patch_approval(&id, other, approved_files).await,
             },
             Op::AddToHistory { text } => {
                 let id = sess.conversation_id;
@@ -1840,24 +4018,215 @@ async fn submission_loop(
                 };
                 sess.send_event(event).await;
             }
-            Op::ListCustomPrompts => {
+            Op::ListMcpResources => {
                 let sub_id = sub.id.clone();
+                let sess_clone = sess.clone();
 
-                let custom_prompts: Vec<CustomPrompt> =
-                    if let Some(dir) = crate::custom_prompts::default_prompts_dir() {
-                        crate::custom_prompts::discover_prompts_in(&dir).await
-                    } else {
-                        Vec::new()
+                // Unlike tools, resources are not cached, so ask the servers
+                // off the submission loop.
+                tokio::spawn(async move {
+                    let resources = sess_clone
+                        .services
+                        .mcp_connection_manager
+                        .list_all_resources()
+                        .await;
+                    let event = Event {
+                        id: sub_id,
+                        msg: EventMsg::McpListResourcesResponse(
+                            crate::protocol::McpListResourcesResponseEvent { resources },
+                        ),
+                    };
+                    sess_clone.send_event(event).await;
+                });
+            }
+            Op::AddMcpServer { name, config } => {
+                let sub_id = sub.id.clone();
+                let sess_clone = sess.clone();
+
+                // Starting the server can take up to its startup timeout, so
+                // do it off the submission loop.
+                tokio::spawn(async move {
+                    let manager = &sess_clone.services.mcp_connection_manager;
+                    let result = match serde_json::from_value::<McpServerConfig>(config) {
+                        Ok(config) => manager.add_server(name.clone(), config).await,
+                        Err(e) => Err(anyhow::anyhow!("invalid MCP server config: {e}")),
+                    };
+                    match result {
+                        Ok(()) => {
+                            let event = Event {
+                                id: sub_id,
+                                msg: EventMsg::McpListToolsResponse(
+                                    crate::protocol::McpListToolsResponseEvent {
+                                        tools: manager.list_all_tools(),
+                                    },
+                                ),
+                            };
+                            sess_clone.send_event(event).await;
+                        }
+                        Err(e) => {
+                            let message = format!("Failed to add MCP server `{name}`: {e:#}");
+                            error!("{message}");
+                            sess_clone.send_error_event(&sub_id, message).await;
+                        }
+                    }
+                });
+            }
+            Op::RemoveMcpServer { name } => {
+                let manager = &sess.services.mcp_connection_manager;
+                if manager.remove_server(&name) {
+                    let event = Event {
+                        id: sub.id.clone(),
+                        msg: EventMsg::McpListToolsResponse(
+                            crate::protocol::McpListToolsResponseEvent {
+                                tools: manager.list_all_tools(),
+                            },
+                        ),
                     };
+                    sess.send_event(event).await;
+                } else {
+                    sess.send_error_event(&sub.id, format!("No MCP server named `{name}`"))
+                        .await;
+                }
+            }
+            Op::ListCustomPrompts => {
+                let sub_id = sub.id.clone();
+                let sess_clone = sess.clone();
+
+                // MCP servers may be slow to render their prompts, so answer
+                // off the submission loop.
+                tokio::spawn(async move {
+                    let mut custom_prompts: Vec<CustomPrompt> =
+                        if let Some(dir) = crate::custom_prompts::default_prompts_dir() {
+                            crate::custom_prompts::discover_prompts_in(&dir).await
+                        } else {
+                            Vec::new()
+                        };
+                    custom_prompts.extend(
+                        crate::custom_prompts::discover_mcp_prompts(
+                            &sess_clone.services.mcp_connection_manager,
+                        )
+                        .await,
+                    );
 
+                    let event = Event {
+                        id: sub_id,
+                        msg: EventMsg::ListCustomPromptsResponse(ListCustomPromptsResponseEvent {
+                            custom_prompts,
+                        }),
+                    };
+                    sess_clone.send_event(event).await;
+                });
+            }
+            Op::SetSessionEnv {
+                key,
+                value,
+                approved,
+            } => {
+                let message = if key.is_empty() || key.contains('=') || key.contains('\0') {
+                    Some(format!("invalid environment variable name: {key:?}"))
+                } else if is_sensitive_env_var(&key) && !approved {
+                    Some(format!(
+                        "setting sensitive environment variable {key} requires approval"
+                    ))
+                } else {
+                    None
+                };
+                match message {
+                    Some(message) => sess.send_error_event(&sub.id, message).await,
+                    None => {
+                        sess.state.lock().await.set_session_env(key, Some(value));
+                    }
+                }
+            }
+            Op::UnsetSessionEnv { key } => {
+                sess.state.lock().await.set_session_env(key, None);
+            }
+            Op::GetSessionEnv => {
+                let overrides = sess.state.lock().await.session_env().clone();
+                let mut env = create_env(&turn_context.shell_environment_policy);
+                apply_session_env(&mut env, &overrides);
                 let event = Event {
-                    id: sub_id,
-                    msg: EventMsg::ListCustomPromptsResponse(ListCustomPromptsResponseEvent {
-                        custom_prompts,
+                    id: sub.id.clone(),
+                    msg: EventMsg::SessionEnvResponse(SessionEnvResponseEvent {
+                        env: env.into_iter().collect(),
+                        overrides,
                     }),
                 };
                 sess.send_event(event).await;
             }
+            Op::GetNextPromptPreview => {
+                let prompt = build_turn_prompt(&sess, &turn_context, sess.history_snapshot().await);
+                let preview = build_prompt_preview(
+                    turn_context.client.get_model(),
+                    &turn_context.client.get_model_family(),
+                    &prompt,
+                );
+                let event = Event {
+                    id: sub.id.clone(),
+                    msg: EventMsg::NextPromptPreview(preview),
+                };
+                sess.send_event(event).await;
+            }
+            Op::KillExecSession { session_id } => {
+                let killed = sess.services.session_manager.kill_session(session_id).await;
+                let event = Event {
+                    id: sub.id.clone(),
+                    msg: EventMsg::ExecSessionKilled(ExecSessionKilledEvent { session_id, killed }),
+                };
+                sess.send_event(event).await;
+            }
+            Op::CancelToolCall { call_id } => {
+                let cancelled = sess.cancel_tool_call(&call_id).await;
+                let event = Event {
+                    id: sub.id.clone(),
+                    msg: EventMsg::ToolCallCancelled(ToolCallCancelledEvent { call_id, cancelled }),
+                };
+                sess.send_event(event).await;
+            }
+            Op::UndoLastChange => {
+                let event = Event {
+                    id: sub.id.clone(),
+                    msg: EventMsg::UndoCompleted(sess.undo_last_change().await),
+                };
+                sess.send_event(event).await;
+            }
+            Op::ListSnapshots => {
+                let event = Event {
+                    id: sub.id.clone(),
+                    msg: EventMsg::ListSnapshotsResponse(sess.list_snapshots().await),
+                };
+                sess.send_event(event).await;
+            }
+            Op::SnapshotDiff { from, to } => {
+                let diff = sess.snapshot_diff(from, to, &turn_context.cwd).await;
+                let event = Event {
+                    id: sub.id.clone(),
+                    msg: EventMsg::SnapshotDiff(diff),
+                };
+                sess.send_event(event).await;
+            }
+            Op::Checkpoint {
+                label,
+                include_workspace,
+            } => {
+                let checkpoint = sess
+                    .create_checkpoint(label, include_workspace, &turn_context.cwd)
+                    .await;
+                let event = Event {
+                    id: sub.id.clone(),
+                    msg: EventMsg::CheckpointCreated(checkpoint),
+                };
+                sess.send_event(event).await;
+            }
+            Op::RewindTo { checkpoint_id } => {
+                let event = Event {
+                    id: sub.id.clone(),
+                    msg: EventMsg::CheckpointRewound(
+                        sess.rewind_to_checkpoint(checkpoint_id).await,
+                    ),
+                };
+                sess.send_event(event).await;
+            }
             Op::Compact => {
                 // Attempt to inject input into current task
                 if let Err(items) = sess
@@ -1878,6 +4247,14 @@ async fn submission_loop(
             Op::Shutdown => {
                 info!("Shutting down Codex instance");
 
+                if let Some(scratch_dir) = &sess.services.scratch_dir {
+                    scratch_dir.remove();
+                }
+                if let Some(live_session) = &sess.services.live_session {
+                    live_session.remove();
+                }
+                sess.prune_ghost_snapshots().await;
+
                 // Gracefully flush and shutdown rollout recorder on session end so tests
                 // that inspect the rollout file do not race with the background writer.
                 let recorder_opt = {
@@ -1904,6 +4281,64 @@ async fn submission_loop(
                 sess.send_event(event).await;
                 break;
             }
+            op @ (Op::ArchiveSession | Op::DeleteSession) => {
+                let disposition = match op {
+                    Op::DeleteSession => SessionDisposition::Trashed,
+                    _ => SessionDisposition::Archived,
+                };
+                let recorder_opt = {
+                    let mut guard = sess.services.rollout.lock().await;
+                    guard.take()
+                };
+                let Some(rec) = recorder_opt else {
+                    sess.send_error_event(
+                        &sub.id,
+                        "session has no rollout file to remove".to_string(),
+                    )
+                    .await;
+                    continue;
+                };
+                // Let the writer finish before the file is moved.
+                if let Err(e) = rec.shutdown().await {
+                    warn!("failed to shutdown rollout recorder: {e}");
+                }
+                let rollout_path = rec.get_rollout_path();
+                let moved = match disposition {
+                    SessionDisposition::Archived => {
+                        archive_rollout(&config.codex_home, &rollout_path).await
+                    }
+                    SessionDisposition::Trashed => {
+                        trash_rollout(&config.codex_home, &rollout_path).await
+                    }
+                };
+                match moved {
+                    Ok(path) => {
+                        sess.send_event(Event {
+                            id: sub.id.clone(),
+                            msg: EventMsg::SessionRemoved(SessionRemovedEvent {
+                                conversation_id: sess.conversation_id,
+                                path,
+                                disposition,
+                            }),
+                        })
+                        .await;
+                    }
+                    Err(e) => {
+                        sess.send_error_event(&sub.id, format!("failed to remove session: {e}"))
+                            .await;
+                    }
+                }
+                if disposition == SessionDisposition::Trashed {
+                    purge_trash(&config.codex_home, config.session_trash_retention_days).await;
+                }
+
+                sess.send_event(Event {
+                    id: sub.id.clone(),
+                    msg: EventMsg::ShutdownComplete,
+                })
+                .await;
+                break;
+            }
             Op::GetPath => {
                 let sub_id = sub.id.clone();
                 // Flush rollout writes before returning the path so readers observe a consistent file.
@@ -1967,6 +4402,8 @@ async fn spawn_review_thread(
         include_web_search_request: false,
         use_streamable_shell_tool: false,
         include_view_image_tool: false,
+        include_scratch_path_tool: false,
+        include_delegate_tool: false,
         experimental_unified_exec_tool: config.use_experimental_unified_exec_tool,
     });
 
@@ -2004,9 +4441,14 @@ async fn spawn_review_thread(
         approval_policy: parent_turn_context.approval_policy,
         sandbox_policy: parent_turn_context.sandbox_policy.clone(),
         shell_environment_policy: parent_turn_context.shell_environment_policy.clone(),
+        resource_limits: parent_turn_context.resource_limits,
+        sandbox_extras: parent_turn_context.sandbox_extras.clone(),
         cwd: parent_turn_context.cwd.clone(),
         is_review_mode: true,
         final_output_json_schema: None,
+        dry_run: parent_turn_context.dry_run,
+        exec: parent_turn_context.exec.clone(),
+        task_budget: parent_turn_context.task_budget,
     };
 
     // Seed the child task with the review prompt as the initial user message.
@@ -2050,14 +4492,26 @@ async fn run_task(
     turn_context: Arc<TurnContext>,
     sub_id: String,
     input: Vec<InputItem>,
+    interrupt: CancellationToken,
 ) {
     if input.is_empty() {
         return;
     }
+    // Cancelled by an interrupt or once `max_duration` has passed, so a turn
+    // that is still streaming or running tools stops at the deadline rather
+    // than before the next model request.
+    let cancellation_token = interrupt.child_token();
+    let branch = if turn_context.is_review_mode {
+        None
+    } else {
+        sess.start_task_work_branch(&sub_id, &turn_context.cwd, &input)
+            .await
+    };
     let event = Event {
         id: sub_id.clone(),
         msg: EventMsg::TaskStarted(TaskStartedEvent {
             model_context_window: turn_context.client.get_model_context_window(),
+            branch,
         }),
     };
     sess.send_event(event).await;
@@ -2073,6 +4527,8 @@ async fn run_task(
         review_thread_history.extend(sess.build_initial_context(turn_context.as_ref()));
         review_thread_history.push(initial_input_for_turn.into());
     } else {
+        sess.refresh_locale_context().await;
+        sess.refresh_git_status(&turn_context.cwd).await;
         sess.record_input_and_rollout_usermsg(&initial_input_for_turn)
             .await;
     }
@@ -2082,8 +4538,51 @@ async fn run_task(
     // many turns, from the perspective of the user, it is a single turn.
     let mut turn_diff_tracker = TurnDiffTracker::new();
     let mut auto_compact_recently_attempted = false;
+    let mut loop_detector = LoopDetector::new(sess.services.stall_detection_turns);
+    let mut last_turn_diff: Option<String> = None;
+    // Usage of every model request made for this task, for the
+    // `turn_complete` hook.
+    let mut task_token_usage = TokenUsage::default();
+    // Model requests made so far and the start of the task, checked against
+    // the task budget.
+    let mut turns: u32 = 0;
+    let task_started = Instant::now();
+    let _deadline = turn_context
+        .task_budget
+        .max_duration
+        .and_then(|max_duration| task_started.checked_add(max_duration))
+        .map(|deadline| start_task_deadline(sess.clone(), cancellation_token.clone(), deadline));
 
     loop {
+        // Once interrupted, the input queued for the next task is not ours to
+        // take; `AgentTask::abort` finishes up for us. Past the deadline, the
+        // budget check below ends the task.
+        if interrupt.is_cancelled() {
+            return;
+        }
+
+        // A cancelled token means the deadline timer has fired.
+        let elapsed = match turn_context.task_budget.max_duration {
+            Some(max_duration) if cancellation_token.is_cancelled() => {
+                task_started.elapsed().max(max_duration)
+            }
+            _ => task_started.elapsed(),
+        };
+        let exceeded = match exceeded_task_budget(
+            &turn_context.task_budget,
+            turns,
+            &task_token_usage,
+            elapsed,
+        ) {
+            Some(exceeded) => Some(exceeded),
+            None => sess.approve_spend_over_limit(&turn_context, &sub_id).await,
+        };
+        if let Some(exceeded) = exceeded {
+            end_task_over_budget(&sess, &sub_id, is_review_mode, exceeded).await;
+            return;
+        }
+        turns += 1;
+
         // Note that pending_input would be something like a message the user
         // submitted through the UI while the model was running. Though the UI
         // may support this, the model might not.
@@ -2133,6 +4632,7 @@ async fn run_task(
             &mut turn_diff_tracker,
             sub_id.clone(),
             turn_input,
+            &cancellation_token,
         )
         .await
         {
@@ -2141,6 +4641,9 @@ async fn run_task(
                     processed_items,
                     total_token_usage,
                 } = turn_output;
+                if let Some(usage) = &total_token_usage {
+                    task_token_usage.add_assign(usage);
+                }
                 let limit = turn_context
                     .client
                     .get_auto_compact_token_limit()
@@ -2153,92 +4656,14 @@ async fn run_task(
                     .unwrap_or(false);
                 let mut items_to_record_in_conversation_history = Vec::<ResponseItem>::new();
                 let mut responses = Vec::<ResponseInputItem>::new();
+                let mut tool_calls = Vec::<TurnToolCall>::new();
                 for processed_response_item in processed_items {
                     let ProcessedResponseItem { item, response } = processed_response_item;
-                    match (&item, &response) {
-                        (ResponseItem::Message { role, .. }, None) if role == "assistant" => {
-                            // If the model returned a message, we need to record it.
-                            items_to_record_in_conversation_history.push(item);
-                        }
-                        (
-                            ResponseItem::LocalShellCall { .. },
-                            Some(ResponseInputItem::FunctionCallOutput { call_id, output }),
-                        ) => {
-                            items_to_record_in_conversation_history.push(item);
-                            items_to_record_in_conversation_history.push(
-                                ResponseItem::FunctionCallOutput {
-                                    call_id: call_id.clone(),
-                                    output: output.clone(),
-                                },
-                            );
-                        }
-                        (
-                            ResponseItem::FunctionCall { .. },
-                            Some(ResponseInputItem::FunctionCallOutput { call_id, output }),
-                        ) => {
-                            items_to_record_in_conversation_history.push(item);
-                            items_to_record_in_conversation_history.push(
-                                ResponseItem::FunctionCallOutput {
-                                    call_id: call_id.clone(),
-                                    output: output.clone(),
-                                },
-                            );
-                        }
-                        (
-                            ResponseItem::CustomToolCall { .. },
-                            Some(ResponseInputItem::CustomToolCallOutput { call_id, output }),
-                        ) => {
-                            items_to_record_in_conversation_history.push(item);
-                            items_to_record_in_conversation_history.push(
-                                ResponseItem::CustomToolCallOutput {
-                                    call_id: call_id.clone(),
-                                    output: output.clone(),
-                                },
-                            );
-                        }
-                        (
-                            ResponseItem::FunctionCall { .. },
-                            Some(ResponseInputItem::McpToolCallOutput { call_id, result }),
-                        ) => {
-                            items_to_record_in_conversation_history.push(item);
-                            let output = match result {
-                                Ok(call_tool_result) => {
-                                    convert_call_tool_result_to_function_call_output_payload(
-                                        call_tool_result,
-                                    )
-                                }
-                                Err(err) => FunctionCallOutputPayload {
-                                    content: err.clone(),
-                                    success: Some(false),
-                                },
-                            };
-                            items_to_record_in_conversation_history.push(
-                                ResponseItem::FunctionCallOutput {
-                                    call_id: call_id.clone(),
-                                    output,
-                                },
-                            );
-                        }
-                        (
-                            ResponseItem::Reasoning {
-                                id,
-                                summary,
-                                content,
-                                encrypted_content,
-                            },
-                            None,
-                        ) => {
-                            items_to_record_in_conversation_history.push(ResponseItem::Reasoning {
-                                id: id.clone(),
-                                summary: summary.clone(),
-                                content: content.clone(),
-                                encrypted_content: encrypted_content.clone(),
-                            });
-                        }
-                        _ => {
-                            warn!("Unexpected response item: {item:?} with response: {response:?}");
-                        }
-                    };
+                    if let Some(response) = &response {
+                        tool_calls.push(TurnToolCall::new(&item, response));
+                    }
+                    items_to_record_in_conversation_history
+                        .extend(history_items_for(item, response.as_ref()));
                     if let Some(response) = response {
                         responses.push(response);
                     }
@@ -2255,6 +4680,12 @@ async fn run_task(
                     }
                 }
 
+                // The output of tools that ran before the interrupt or the
+                // deadline is now in the history; do not start another turn.
+                if cancellation_token.is_cancelled() {
+                    continue;
+                }
+
                 if token_limit_reached {
                     if auto_compact_recently_attempted {
                         let limit_str = limit.to_string();
@@ -2273,16 +4704,64 @@ async fn run_task(
                         break;
                     }
                     auto_compact_recently_attempted = true;
-                    compact::run_inline_auto_compact_task(sess.clone(), turn_context.clone()).await;
+                    let preserve_messages = sess.services.auto_compact_preserve_messages;
+                    sess.send_event(Event {
+                        id: sub_id.clone(),
+                        msg: EventMsg::AutoCompact(AutoCompactEvent {
+                            tokens_in_context_window: total_usage_tokens.unwrap_or_default(),
+                            token_limit: u64::try_from(limit).unwrap_or_default(),
+                            preserved_messages: preserve_messages,
+                        }),
+                    })
+                    .await;
+                    compact::run_inline_auto_compact_task(
+                        sess.clone(),
+                        turn_context.clone(),
+                        preserve_messages,
+                    )
+                    .await;
                     continue;
                 }
 
                 auto_compact_recently_attempted = false;
 
+                let turn_diff = turn_diff_tracker.get_unified_diff().ok().flatten();
+                let changed_files = turn_diff != last_turn_diff;
+                last_turn_diff = turn_diff;
+                if let Some(stall) = loop_detector.record_turn(tool_calls, changed_files) {
+                    let action = sess.services.stall_action;
+                    let recovery_prompt = stall.recovery_prompt();
+                    sess.send_event(Event {
+                        id: sub_id.clone(),
+                        msg: EventMsg::TaskStalled(TaskStalledEvent {
+                            repeated_turns: stall.repeated_turns,
+                            failing_tool: stall.failing_tool,
+                            action,
+                        }),
+                    })
+                    .await;
+                    loop_detector.reset();
+                    match action {
+                        StallAction::Pause => break,
+                        StallAction::Recover => {
+                            let _ = sess
+                                .inject_input(vec![InputItem::Text {
+                                    text: recovery_prompt,
+                                }])
+                                .await;
+                        }
+                    }
+                }
+
                 if responses.is_empty() {
                     last_agent_message = get_last_assistant_message_from_turn(
                         &items_to_record_in_conversation_history,
                     );
+                    // Steering that arrived while the model was answering
+                    // still belongs to this task.
+                    if sess.has_pending_steering().await {
+                        continue;
+                    }
                     match sess.check_stop_hook(&sub_id).await {
                         StopHookDecision::Block(reason) => {
                             let _ = sess
@@ -2303,6 +4782,7 @@ async fn run_task(
                 }
                 continue;
             }
+            Err(_) if cancellation_token.is_cancelled() => continue,
             Err(e) => {
                 info!("Turn error: {e:#}");
                 let event = Event {
@@ -2327,6 +4807,10 @@ async fn run_task(
         }
     }
 
+    if interrupt.is_cancelled() {
+        return;
+    }
+
     // If this was a review thread and we have a final assistant message,
     // try to parse it as a ReviewOutput.
     //
@@ -2343,14 +4827,287 @@ async fn run_task(
         .await;
     }
 
+    // Hold the final answer to the schema the turn asked for, so scripts can
+    // rely on it.
+    let mut output_schema_error = None;
+    if let Some(schema) = turn_context.final_output_json_schema.as_ref()
+        && !turn_context.is_review_mode
+    {
+        match final_output::enforce_output_schema(
+            &sess,
+            &turn_context,
+            schema,
+            last_agent_message.as_deref(),
+        )
+        .await
+        {
+            Ok(text) => {
+                if last_agent_message.as_deref() != Some(text.as_str()) {
+                    sess.send_event(Event {
+                        id: sub_id.clone(),
+                        msg: EventMsg::AgentMessage(AgentMessageEvent {
+                            message: text.clone(),
+                        }),
+                    })
+                    .await;
+                }
+                last_agent_message = Some(text);
+            }
+            Err(error) => output_schema_error = Some(error),
+        }
+    }
+
+    let assessment = if sess.services.task_completion_assessment
+        && !turn_context.is_review_mode
+        && last_agent_message.is_some()
+    {
+        completion::assess_completion(&sess, &turn_context).await
+    } else {
+        None
+    };
+
+    sess.run_turn_complete_hook(
+        &sub_id,
+        &turn_context,
+        last_agent_message.as_deref(),
+        turn_diff_tracker.get_unified_diff().ok().flatten(),
+        &task_token_usage,
+    )
+    .await;
+
     sess.remove_task(&sub_id).await;
+    sess.record_state_snapshot().await;
     let event = Event {
         id: sub_id,
-        msg: EventMsg::TaskComplete(TaskCompleteEvent { last_agent_message }),
+        msg: EventMsg::TaskComplete(TaskCompleteEvent {
+            last_agent_message,
+            assessment,
+            estimated_cost_usd: sess.estimated_cost_usd().await,
+            output_schema_error,
+        }),
     };
     sess.send_event(event).await;
 }
 
+/// The items to record in the conversation history for an item of a model
+/// response: the item itself followed by the output of the tool call it
+/// made, if any.
+fn history_items_for(
+    item: ResponseItem,
+    response: Option<&ResponseInputItem>,
+) -> Vec<ResponseItem> {
+    let mut items = Vec::with_capacity(2);
+    match (&item, response) {
+        (ResponseItem::Message { role, .. }, None) if role == "assistant" => {
+            // If the model returned a message, we need to record it.
+            items.push(item);
+        }
+        (
+            ResponseItem::LocalShellCall { .. },
+            Some(ResponseInputItem::FunctionCallOutput { call_id, output }),
+        ) => {
+            items.push(item);
+            items.push(ResponseItem::FunctionCallOutput {
+                call_id: call_id.clone(),
+                output: output.clone(),
+            });
+        }
+        (
+            ResponseItem::FunctionCall { .. },
+            Some(ResponseInputItem::FunctionCallOutput { call_id, output }),
+        ) => {
+            items.push(item);
+            items.push(ResponseItem::FunctionCallOutput {
+                call_id: call_id.clone(),
+                output: output.clone(),
+            });
+        }
+        (
+            ResponseItem::CustomToolCall { .. },
+            Some(ResponseInputItem::CustomToolCallOutput { call_id, output }),
+        ) => {
+            items.push(item);
+            items.push(ResponseItem::CustomToolCallOutput {
+                call_id: call_id.clone(),
+                output: output.clone(),
+            });
+        }
+        (
+            ResponseItem::FunctionCall { .. },
+            Some(ResponseInputItem::McpToolCallOutput { call_id, result }),
+        ) => {
+            items.push(item);
+            let output = match result {
+                Ok(call_tool_result) => {
+                    convert_call_tool_result_to_function_call_output_payload(call_tool_result)
+                }
+                Err(err) => FunctionCallOutputPayload {
+                    content: err.clone(),
+                    success: Some(false),
+                },
+            };
+            items.push(ResponseItem::FunctionCallOutput {
+                call_id: call_id.clone(),
+                output,
+            });
+        }
+        (
+            ResponseItem::Reasoning {
+                id,
+                summary,
+                content,
+                encrypted_content,
+            },
+            None,
+        ) => {
+            items.push(ResponseItem::Reasoning {
+                id: id.clone(),
+                summary: summary.clone(),
+                content: content.clone(),
+                encrypted_content: encrypted_content.clone(),
+            });
+        }
+        _ => {
+            warn!("Unexpected response item: {item:?} with response: {response:?}");
+        }
+    }
+    items
+}
+
+/// A limit a task reached, with how much the task used and was allowed in
+/// the limit's unit (see `TurnAbortedEvent::budget_used`).
+#[derive(Debug, PartialEq)]
+struct ExceededBudget {
+    limit: TaskBudgetLimit,
+    used: f64,
+    allowed: f64,
+}
+
+/// The limit of `budget` that a task has reached after making `turns` model
+/// requests that used `usage` in total and running for `elapsed`.
+fn exceeded_task_budget(
+    budget: &TaskBudget,
+    turns: u32,
+    usage: &TokenUsage,
+    elapsed: Duration,
+) -> Option<ExceededBudget> {
+    let (limit, used, allowed) = if let Some(max) = budget.max_turns
+        && turns >= max
+    {
+        (TaskBudgetLimit::MaxTurns, f64::from(turns), f64::from(max))
+    } else if let Some(max) = budget.max_total_tokens
+        && usage.total_tokens >= max
+    {
+        (
+            TaskBudgetLimit::MaxTotalTokens,
+            usage.total_tokens as f64,
+            max as f64,
+        )
+    } else if let Some(max) = budget.max_duration
+        && elapsed >= max
+    {
+        (
+            TaskBudgetLimit::MaxDuration,
+            elapsed.as_secs_f64(),
+            max.as_secs_f64(),
+        )
+    } else {
+        return None;
+    };
+    Some(ExceededBudget {
+        limit,
+        used,
+        allowed,
+    })
+}
+
+/// Cancels `cancellation_token` at `deadline` and denies the approvals the
+/// task is waiting on, so its running turn winds down. Dropping the returned
+/// guard stops the timer.
+fn start_task_deadline(
+    sess: Arc<Session>,
+    cancellation_token: CancellationToken,
+    deadline: Instant,
+) -> DropGuard {
+    let stop = CancellationToken::new();
+    let stopped = stop.clone();
+    tokio::spawn(async move {
+        tokio::select! {
+            _ = tokio::time::sleep_until(deadline.into()) => {
+                cancellation_token.cancel();
+                sess.clear_pending_approvals().await;
+            }
+            _ = stopped.cancelled() => {}
+        }
+    });
+    stop.drop_guard()
+}
+
+/// End a task that exceeded its budget, like an interrupt would. The outputs of
+/// its last tool calls are already in the history, so the conversation can
+/// be continued.
+async fn end_task_over_budget(
+    sess: &Arc<Session>,
+    sub_id: &str,
+    is_review_mode: bool,
+    exceeded: ExceededBudget,
+) {
+    info!("task {sub_id} exceeded its budget: {}", exceeded.limit);
+    if is_review_mode {
+        exit_review_mode(sess.clone(), sub_id.to_string(), None).await;
+    }
+    sess.leave_task_work_branch(sub_id).await;
+    sess.remove_task(sub_id).await;
+    sess.record_state_snapshot().await;
+    sess.send_event(Event {
+        id: sub_id.to_string(),
+        msg: EventMsg::TurnAborted(TurnAbortedEvent {
+            reason: TurnAbortReason::BudgetExceeded,
+            exceeded_limit: Some(exceeded.limit),
+            budget_used: Some(exceeded.used),
+            budget_allowed: Some(exceeded.allowed),
+        }),
+    })
+    .await;
+}
+
+/// The session state recorded last in a rollout.
+fn last_state_snapshot(items: &[RolloutItem]) -> Option<SessionStateSnapshot> {
+    items.iter().rev().find_map(|item| match item {
+        RolloutItem::SessionState(snapshot) => Some(snapshot.clone()),
+        _ => None,
+    })
+}
+
+/// Events telling clients about the state a resumed session got back: its
+/// plan, and the background processes that did not survive.
+fn restored_state_events(snapshot: &SessionStateSnapshot) -> Vec<Event> {
+    let mut events = Vec::new();
+    if let Some(plan) = &snapshot.plan {
+        events.push(Event {
+            id: INITIAL_SUBMIT_ID.to_owned(),
+            msg: EventMsg::PlanUpdate(plan.clone()),
+        });
+    }
+    if !snapshot.background_processes.is_empty() {
+        let processes = snapshot
+            .background_processes
+            .iter()
+            .map(|process| format!("{} (`{}`)", process.session_id, process.command))
+            .collect::<Vec<_>>()
+            .join(", ");
+        events.push(Event {
+            id: INITIAL_SUBMIT_ID.to_owned(),
+            msg: EventMsg::Warning(WarningEvent {
+                message: format!(
+                    "Background processes from before the session was resumed are no longer running: {processes}"
+                ),
+            }),
+        });
+    }
+    events
+}
+
 /// Parse the review output; when not valid JSON, build a structured
 /// fallback that carries the plain text as the overall explanation.
 ///
@@ -2376,29 +5133,96 @@ fn parse_review_output_event(text: &str) -> ReviewOutputEvent {
     }
 }
 
-async fn run_turn(
+/// Turn the contents of an MCP resource into the function call output: text is
+/// returned inline and images are attached to the conversation, like
+/// `view_image` does for local files.
+async fn read_resource_into_context(
+    sess: &Session,
+    result: mcp_types::ReadResourceResult,
+) -> Result<String, FunctionCallError> {
+    let mut output = Vec::with_capacity(result.contents.len());
+    let mut images = Vec::new();
+    for contents in result.contents {
+        match contents {
+            ReadResourceResultContents::TextResourceContents(text) => output.push(text.text),
+            ReadResourceResultContents::BlobResourceContents(blob) => {
+                let mime_type = blob.mime_type.unwrap_or_default();
+                if mime_type.starts_with("image/") {
+                    images.push(InputItem::Image {
+                        image_url: format!("data:{mime_type};base64,{}", blob.blob),
+                    });
+                    output.push(format!("attached image {}", blob.uri));
+                } else {
+                    output.push(format!(
+                        "{} is binary ({}) and cannot be shown",
+                        blob.uri,
+                        if mime_type.is_empty() {
+                            "unknown type"
+                        } else {
+                            &mime_type
+                        }
+                    ));
+                }
+            }
+        }
+    }
+    if !images.is_empty() {
+        sess.inject_input(images).await.map_err(|_| {
+            FunctionCallError::RespondToModel("unable to attach image (no active task)".to_string())
+        })?;
+    }
+    Ok(output.join("\n\n"))
+}
+
+/// The prompt sent to the model for a turn with the given input.
+fn build_turn_prompt(
     sess: &Session,
     turn_context: &TurnContext,
-    turn_diff_tracker: &mut TurnDiffTracker,
-    sub_id: String,
     input: Vec<ResponseItem>,
-) -> CodexResult<TurnRunResult> {
-    let tools = get_openai_tools(
+) -> Prompt {
+    let mut tools = get_openai_tools(
         &turn_context.tools_config,
         Some(sess.services.mcp_connection_manager.list_all_tools()),
     );
+    if sess.services.mcp_connection_manager.has_resources() {
+        tools.extend(create_mcp_resource_tools());
+    }
+    if let Some(allowed_tools) = &turn_context.tools_config.allowed_tools {
+        tools.retain(|tool| allowed_tools.iter().any(|name| name == tool.name()));
+    }
 
-    let prompt = Prompt {
+    Prompt {
         input,
         tools,
         base_instructions_override: turn_context.base_instructions.clone(),
         output_schema: turn_context.final_output_json_schema.clone(),
-    };
+    }
+}
+
+async fn run_turn(
+    sess: &Session,
+    turn_context: &TurnContext,
+    turn_diff_tracker: &mut TurnDiffTracker,
+    sub_id: String,
+    input: Vec<ResponseItem>,
+    cancellation_token: &CancellationToken,
+) -> CodexResult<TurnRunResult> {
+    let prompt = build_turn_prompt(sess, turn_context, input);
 
     let mut retries = 0;
     loop {
-        match try_run_turn(sess, turn_context, turn_diff_tracker, &sub_id, &prompt).await {
+        match try_run_turn(
+            sess,
+            turn_context,
+            turn_diff_tracker,
+            &sub_id,
+            &prompt,
+            cancellation_token,
+        )
+        .await
+        {
             Ok(output) => return Ok(output),
+            Err(_) if cancellation_token.is_cancelled() => return Err(CodexErr::Interrupted),
             Err(CodexErr::Interrupted) => return Err(CodexErr::Interrupted),
             Err(CodexErr::EnvVar(var)) => return Err(CodexErr::EnvVar(var)),
             Err(CodexErr::UsageLimitReached(e)) => {
@@ -2409,6 +5233,7 @@ async fn run_turn(
                 return Err(CodexErr::UsageLimitReached(e));
             }
             Err(CodexErr::UsageNotIncluded) => return Err(CodexErr::UsageNotIncluded),
+            Err(CodexErr::HookBlocked(reason)) => return Err(CodexErr::HookBlocked(reason)),
             Err(e) => {
                 // Use the configured provider-specific stream retry budget.
                 let max_retries = turn_context.client.get_provider().stream_max_retries();
@@ -2433,7 +5258,10 @@ async fn run_turn(
                     )
                     .await;
 
-                    tokio::time::sleep(delay).await;
+                    tokio::select! {
+                        _ = tokio::time::sleep(delay) => {}
+                        _ = cancellation_token.cancelled() => return Err(CodexErr::Interrupted),
+                    }
                 } else {
                     return Err(e);
                 }
@@ -2464,6 +5292,7 @@ async fn try_run_turn(
     turn_diff_tracker: &mut TurnDiffTracker,
     sub_id: &str,
     prompt: &Prompt,
+    cancellation_token: &CancellationToken,
 ) -> CodexResult<TurnRunResult> {
     // call_ids that are part of this response.
     let completed_call_ids = prompt
@@ -2529,15 +5358,41 @@ async fn try_run_turn(
         summary: turn_context.client.get_reasoning_summary(),
     });
     sess.persist_rollout_items(&[rollout_item]).await;
-    let mut stream = turn_context.client.clone().stream(&prompt).await?;
+    sess.run_pre_model_call_hook(sub_id, turn_context, &prompt)
+        .await
+        .map_err(CodexErr::HookBlocked)?;
+    let mut stream = tokio::select! {
+        stream = turn_context.client.clone().stream(&prompt) => stream?,
+        _ = cancellation_token.cancelled() => return Err(CodexErr::Interrupted),
+    };
 
     let mut output = Vec::new();
+    // Tool calls waiting to run alongside the ones that follow them; see
+    // `is_parallel_safe_tool_call`.
+    let max_parallel_tool_calls = sess.services.max_parallel_tool_calls;
+    let mut pending_parallel: Vec<ResponseItem> = Vec::new();
 
     loop {
         // Poll the next item from the model stream. We must inspect *both* Ok and Err
         // cases so that transient stream failures (e.g., dropped SSE connection before
         // `response.completed`) bubble up and trigger the caller's retry logic.
-        let event = stream.next().await;
+        //
+        // On interrupt, drop the stream and hand back the tool calls that
+        // already ran so their output is recorded.
+        let event = tokio::select! {
+            biased;
+            _ = cancellation_token.cancelled() => {
+                output.extend(pending_parallel.into_iter().map(|item| {
+                    let response = aborted_tool_call_output(&item, "aborted");
+                    ProcessedResponseItem { item, response }
+                }));
+                return Ok(TurnRunResult {
+                    processed_items: output,
+                    total_token_usage: None,
+                });
+            }
+            event = stream.next() => event,
+        };
         let Some(event) = event else {
             // Channel closed without yielding a final Completed event or explicit error.
             // Treat as a disconnected stream so the caller can retry.
@@ -2559,12 +5414,30 @@ async fn try_run_turn(
         match event {
             ResponseEvent::Created => {}
             ResponseEvent::OutputItemDone(item) => {
+                if max_parallel_tool_calls > 1
+                    && is_parallel_safe_tool_call(sess, turn_context, &item)
+                {
+                    pending_parallel.push(item);
+                    continue;
+                }
+                output.extend(
+                    run_parallel_tool_calls(
+                        sess,
+                        turn_context,
+                        sub_id,
+                        std::mem::take(&mut pending_parallel),
+                        max_parallel_tool_calls,
+                        cancellation_token,
+                    )
+                    .await?,
+                );
                 let response = handle_response_item(
                     sess,
                     turn_context,
                     turn_diff_tracker,
                     sub_id,
                     item.clone(),
+                    cancellation_token,
                 )
                 .await?;
                 output.push(ProcessedResponseItem { item, response });
@@ -2587,18 +5460,22 @@ async fn try_run_turn(
                 response_id: _,
                 token_usage,
             } => {
+                output.extend(
+                    run_parallel_tool_calls(
+                        sess,
+                        turn_context,
+                        sub_id,
+                        std::mem::take(&mut pending_parallel),
+                        max_parallel_tool_calls,
+                        cancellation_token,
+                    )
+                    .await?,
+                );
                 sess.update_token_usage_info(sub_id, turn_context, token_usage.as_ref())
                     .await;
 
-                let unified_diff = turn_diff_tracker.get_unified_diff();
-                if let Ok(Some(unified_diff)) = unified_diff {
-                    let msg = EventMsg::TurnDiff(TurnDiffEvent { unified_diff });
-                    let event = Event {
-                        id: sub_id.to_string(),
-                        msg,
-                    };
-                    sess.send_event(event).await;
-                }
+                sess.send_turn_diff(sub_id, &turn_context.cwd, turn_diff_tracker)
+                    .await;
 
                 let result = TurnRunResult {
                     processed_items: output,
@@ -2649,12 +5526,153 @@ async fn try_run_turn(
     }
 }
 
+/// Whether `item` is a tool call that can run at the same time as other such
+/// calls: a call to an MCP tool its server marks with `readOnlyHint`, or a
+/// known read-only shell command that does not ask for escalated
+/// permissions. None of these apply patches, so they never touch the turn's
+/// diff. MCP calls that still need a permission prompt are left out, since
+/// pending approvals are tracked per turn rather than per call.
+fn is_parallel_safe_tool_call(
+    sess: &Session,
+    turn_context: &TurnContext,
+    item: &ResponseItem,
+) -> bool {
+    match item {
+        ResponseItem::FunctionCall {
+            name, arguments, ..
+        } => {
+            let mcp = &sess.services.mcp_connection_manager;
+            if let Some((server, tool)) = mcp.parse_tool_name(name) {
+                return mcp.is_read_only_tool(name)
+                    && !sess.guided_permission_needs_prompt(
+                        turn_context,
+                        &GuidedPermission::McpTool { server, tool },
+                    );
+            }
+            matches!(name.as_str(), "container.exec" | "shell")
+                && serde_json::from_str::<ShellToolCallParams>(arguments).is_ok_and(|params| {
+                    !params.with_escalated_permissions.unwrap_or(false)
+                        && is_known_safe_command(&params.command)
+                })
+        }
+        ResponseItem::LocalShellCall {
+            action: LocalShellAction::Exec(action),
+            ..
+        } => is_known_safe_command(&action.command),
+        _ => false,
+    }
+}
+
+/// Run `items` (all accepted by `is_parallel_safe_tool_call`) with at most
+/// `limit` in flight, returning their results in the order the model issued
+/// them.
+async fn run_parallel_tool_calls(
+    sess: &Session,
+    turn_context: &TurnContext,
+    sub_id: &str,
+    items: Vec<ResponseItem>,
+    limit: usize,
+    cancellation_token: &CancellationToken,
+) -> CodexResult<Vec<ProcessedResponseItem>> {
+    futures::stream::iter(items.into_iter().map(|item| async move {
+        // These calls never apply patches, so there is no diff to track.
+        let mut turn_diff_tracker = TurnDiffTracker::new();
+        let response = handle_response_item(
+            sess,
+            turn_context,
+            &mut turn_diff_tracker,
+            sub_id,
+            item.clone(),
+            cancellation_token,
+        )
+        .await?;
+        Ok::<_, CodexErr>(ProcessedResponseItem { item, response })
+    }))
+    .buffered(limit)
+    .try_collect()
+    .await
+}
+
+/// The call id the output of tool call `item` must carry.
+fn tool_call_id(item: &ResponseItem) -> Option<&str> {
+    match item {
+        ResponseItem::FunctionCall { call_id, .. }
+        | ResponseItem::CustomToolCall { call_id, .. } => Some(call_id),
+        ResponseItem::LocalShellCall {
+            call_id: Some(call_id),
+            ..
+        } => Some(call_id),
+        ResponseItem::LocalShellCall { id: Some(id), .. } => Some(id),
+        _ => None,
+    }
+}
+
+/// Output recorded in place of the result of a tool call that was cancelled.
+fn aborted_tool_call_output(item: &ResponseItem, content: &str) -> Option<ResponseInputItem> {
+    let call_id = tool_call_id(item)?.to_string();
+    Some(match item {
+        ResponseItem::CustomToolCall { .. } => ResponseInputItem::CustomToolCallOutput {
+            call_id,
+            output: content.to_string(),
+        },
+        _ => ResponseInputItem::FunctionCallOutput {
+            call_id,
+            output: FunctionCallOutputPayload {
+                content: content.to_string(),
+                success: Some(false),
+            },
+        },
+    })
+}
+
+/// Handles `item` under a cancellation token of its own, so that a tool call
+/// can be cancelled with `Op::CancelToolCall` while the turn goes on. The
+/// model is then told the call was aborted by the user.
 async fn handle_response_item(
     sess: &Session,
     turn_context: &TurnContext,
     turn_diff_tracker: &mut TurnDiffTracker,
     sub_id: &str,
     item: ResponseItem,
+    cancellation_token: &CancellationToken,
+) -> CodexResult<Option<ResponseInputItem>> {
+    let Some(call_id) = tool_call_id(&item).map(str::to_string) else {
+        return dispatch_response_item(
+            sess,
+            turn_context,
+            turn_diff_tracker,
+            sub_id,
+            item,
+            cancellation_token,
+        )
+        .await;
+    };
+    let aborted = aborted_tool_call_output(&item, "aborted by user");
+    let call_token = cancellation_token.child_token();
+    sess.register_tool_call(&call_id, call_token.clone()).await;
+    let output = dispatch_response_item(
+        sess,
+        turn_context,
+        turn_diff_tracker,
+        sub_id,
+        item,
+        &call_token,
+    )
+    .await;
+    sess.unregister_tool_call(&call_id).await;
+    if call_token.is_cancelled() && !cancellation_token.is_cancelled() {
+        return Ok(aborted);
+    }
+    output
+}
+
+async fn dispatch_response_item(
+    sess: &Session,
+    turn_context: &TurnContext,
+    turn_diff_tracker: &mut TurnDiffTracker,
+    sub_id: &str,
+    item: ResponseItem,
+    cancellation_token: &CancellationToken,
 ) -> CodexResult<Option<ResponseInputItem>> {
     debug!(?item, "Output item");
     let output = match item {
@@ -2669,28 +5687,54 @@ async fn handle_response_item(
                 sess.services.mcp_connection_manager.parse_tool_name(&name)
             {
                 let tool_id = format!("mcp:{server}.{tool_name}");
-                let arg_json = serde_json::from_str::<serde_json::Value>(&arguments)
-                    .unwrap_or_else(|_| serde_json::json!({ "raw": arguments }));
                 if let Err(e) = sess
-                    .run_pre_tool_hook(
+                    .check_guided_permission(
+                        turn_context,
                         sub_id,
                         &call_id,
-                        &tool_id,
-                        &turn_context.cwd,
-                        arg_json.clone(),
-                        None,
+                        GuidedPermission::McpTool {
+                            server: server.clone(),
+                            tool: tool_name.clone(),
+                        },
+                        arguments.clone(),
                     )
                     .await
                 {
                     return Ok(Some(ResponseInputItem::FunctionCallOutput {
                         call_id,
                         output: FunctionCallOutputPayload {
-                            content: format!("pre_tool_use hook failed: {e}"),
+                            content: e,
                             success: Some(false),
                         },
                     }));
                 }
+                let arg_json = serde_json::from_str::<serde_json::Value>(&arguments)
+                    .unwrap_or_else(|_| serde_json::json!({ "raw": arguments }));
+                let arguments = match sess
+                    .run_pre_tool_hook(
+                        sub_id,
+                        &call_id,
+                        &tool_id,
+                        &turn_context.cwd,
+                        arg_json,
+                        None,
+                    )
+                    .await
+                {
+                    Ok(Some(modified)) => modified.to_string(),
+                    Ok(None) => arguments,
+                    Err(e) => {
+                        return Ok(Some(ResponseInputItem::FunctionCallOutput {
+                            call_id,
+                            output: FunctionCallOutputPayload {
+                                content: e,
+                                success: Some(false),
+                            },
+                        }));
+                    }
+                };
 
+                let tool_started = Instant::now();
                 let resp = handle_mcp_tool_call(
                     sess,
                     sub_id,
@@ -2698,6 +5742,7 @@ async fn handle_response_item(
                     server,
                     tool_name,
                     arguments,
+                    cancellation_token,
                 )
                 .await;
 
@@ -2725,6 +5770,7 @@ async fn handle_response_item(
                     &turn_context.cwd,
                     success,
                     output_str.as_deref(),
+                    tool_started.elapsed(),
                     serde_json::json!({}),
                     None,
                     None,
@@ -2743,6 +5789,7 @@ async fn handle_response_item(
                     name,
                     arguments,
                     call_id.clone(),
+                    cancellation_token,
                 )
                 .await;
 
@@ -2773,6 +5820,8 @@ async fn handle_response_item(
                 timeout_ms: action.timeout_ms,
                 with_escalated_permissions: None,
                 justification: None,
+                env: action.env,
+                tty: None,
             };
             let effective_call_id = match (call_id, id) {
                 (Some(call_id), _) => call_id,
@@ -2789,22 +5838,15 @@ async fn handle_response_item(
                 }
             };
 
-            let exec_params = to_exec_params(params, turn_context);
-            let command_joined = exec_params.command.join(" ");
-            let hook_args = serde_json::json!({
-                "command": command_joined.clone(),
-                "argv": exec_params.command.clone(),
-                "workdir": exec_params.cwd.clone(),
-                "timeout_ms": exec_params.timeout_ms,
-            });
+            let mut exec_params = to_exec_params(params, turn_context);
             let rm_targets = expand_rm_targets_for_pre(&exec_params.command, &turn_context.cwd);
-            if let Err(e) = sess
+            let hook_result = match sess
                 .run_pre_tool_hook(
-                    &sub_id,
+                    sub_id,
                     &effective_call_id,
                     "shell",
                     &turn_context.cwd,
-                    hook_args.clone(),
+                    shell_hook_arguments(&exec_params),
                     if rm_targets.is_empty() {
                         None
                     } else {
@@ -2813,15 +5855,23 @@ async fn handle_response_item(
                 )
                 .await
             {
+                Ok(Some(modified)) => {
+                    apply_shell_hook_arguments(&mut exec_params, modified, turn_context)
+                }
+                Ok(None) => Ok(()),
+                Err(e) => Err(e),
+            };
+            if let Err(e) = hook_result {
                 return Ok(Some(ResponseInputItem::FunctionCallOutput {
                     call_id: effective_call_id,
                     output: FunctionCallOutputPayload {
-                        content: format!("pre_tool_use hook failed: {e}"),
+                        content: e,
                         success: Some(false),
                     },
                 }));
             }
             {
+                let tool_started = Instant::now();
                 let result = handle_container_exec_with_params(
                     exec_params.clone(),
                     sess,
@@ -2829,6 +5879,7 @@ async fn handle_response_item(
                     turn_diff_tracker,
                     sub_id.to_string(),
                     effective_call_id.clone(),
+                    cancellation_token,
                 )
                 .await;
                 let (success, output_text, output_payload) = match result {
@@ -2861,18 +5912,14 @@ async fn handle_response_item(
                     None
                 };
                 sess.run_post_tool_hook(
-                    &sub_id,
+                    sub_id,
                     &effective_call_id,
                     "shell",
                     &turn_context.cwd,
                     success,
                     output_text.as_deref(),
-                    serde_json::json!({
-                        "command": command_joined,
-                        "argv": exec_params.command.clone(),
-                        "workdir": exec_params.cwd.clone(),
-                        "timeout_ms": exec_params.timeout_ms,
-                    }),
+                    tool_started.elapsed(),
+                    shell_hook_arguments(&exec_params),
                     None,
                     rm_deleted,
                     None,
@@ -2901,6 +5948,7 @@ async fn handle_response_item(
                 name,
                 input,
                 call_id.clone(),
+                cancellation_token,
             )
             .await;
 
@@ -3003,24 +6051,26 @@ async fn handle_function_call(
     name: String,
     arguments: String,
     call_id: String,
+    cancellation_token: &CancellationToken,
 ) -> Result<String, FunctionCallError> {
+    if let Some(allowed_tools) = &turn_context.tools_config.allowed_tools
+        && !allowed_tools.contains(&name)
+    {
+        return Err(FunctionCallError::RespondToModel(format!(
+            "tool `{name}` is not available"
+        )));
+    }
     match name.as_str() {
         "container.exec" | "shell" => {
-            let params = parse_container_exec_arguments(arguments, turn_context, &call_id)?;
-            let hook_args = serde_json::json!({
-                "command": params.command.join(" "),
-                "argv": params.command.clone(),
-                "workdir": params.cwd.clone(),
-                "timeout_ms": params.timeout_ms,
-            });
+            let mut params = parse_container_exec_arguments(arguments, turn_context, &call_id)?;
             let rm_targets = expand_rm_targets_for_pre(&params.command, &turn_context.cwd);
-            if let Err(e) = sess
+            if let Some(modified) = sess
                 .run_pre_tool_hook(
                     &sub_id,
                     &call_id,
                     "shell",
                     &turn_context.cwd,
-                    hook_args.clone(),
+                    shell_hook_arguments(&params),
                     if rm_targets.is_empty() {
                         None
                     } else {
@@ -3028,12 +6078,14 @@ async fn handle_function_call(
                     },
                 )
                 .await
+                .map_err(FunctionCallError::RespondToModel)?
             {
-                return Err(FunctionCallError::RespondToModel(format!(
-                    "pre_tool_use hook failed: {e}"
-                )));
+                apply_shell_hook_arguments(&mut params, modified, turn_context)
+                    .map_err(FunctionCallError::RespondToModel)?;
             }
+            let hook_args = shell_hook_arguments(&params);
 
+            let tool_started = Instant::now();
             let result = handle_container_exec_with_params(
                 params.clone(),
                 sess,
@@ -3041,6 +6093,7 @@ async fn handle_function_call(
                 turn_diff_tracker,
                 sub_id.clone(),
                 call_id.clone(),
+                cancellation_token,
             )
             .await;
 
@@ -3065,6 +6118,7 @@ async fn handle_function_call(
                 &turn_context.cwd,
                 success,
                 output_text.as_deref(),
+                tool_started.elapsed(),
                 hook_args,
                 None,
                 rm_deleted,
@@ -3104,6 +6158,12 @@ async fn handle_function_call(
                 ))
             })?;
             let abs = turn_context.resolve_path(Some(args.path));
+            if CodexIgnore::load(&turn_context.cwd).is_ignored(&abs) {
+                return Err(FunctionCallError::RespondToModel(format!(
+                    "{} is excluded by {CODEX_IGNORE_FILENAME}; do not read it",
+                    display_path(&abs)
+                )));
+            }
             sess.inject_input(vec![InputItem::LocalImage { path: abs }])
                 .await
                 .map_err(|_| {
@@ -3114,16 +6174,77 @@ async fn handle_function_call(
 
             Ok("attached local image path".to_string())
         }
+        "list_mcp_resources" => {
+            #[derive(serde::Deserialize)]
+            struct ListResourcesArgs {
+                server: Option<String>,
+            }
+            let args: ListResourcesArgs = serde_json::from_str(&arguments).map_err(|e| {
+                FunctionCallError::RespondToModel(format!(
+                    "failed to parse function arguments: {e:?}"
+                ))
+            })?;
+            let mut resources = sess
+                .services
+                .mcp_connection_manager
+                .list_all_resources()
+                .await;
+            if let Some(server) = args.server {
+                resources.retain(|name, _| *name == server);
+            }
+            serde_json::to_string(&resources).map_err(|e| {
+                FunctionCallError::RespondToModel(format!("failed to serialize resources: {e}"))
+            })
+        }
+        "read_mcp_resource" => {
+            #[derive(serde::Deserialize)]
+            struct ReadResourceArgs {
+                server: String,
+                uri: String,
+            }
+            let args: ReadResourceArgs = serde_json::from_str(&arguments).map_err(|e| {
+                FunctionCallError::RespondToModel(format!(
+                    "failed to parse function arguments: {e:?}"
+                ))
+            })?;
+            let result = sess
+                .services
+                .mcp_connection_manager
+                .read_resource(&args.server, &args.uri)
+                .await
+                .map_err(|e| FunctionCallError::RespondToModel(format!("{e:#}")))?;
+            read_resource_into_context(sess, result).await
+        }
+        "delegate" => {
+            sess.services
+                .sub_agents
+                .run(
+                    sess,
+                    turn_context,
+                    turn_diff_tracker,
+                    &sub_id,
+                    call_id,
+                    arguments,
+                    cancellation_token,
+                )
+                .await
+        }
+        "scratch_path" => match &sess.services.scratch_dir {
+            Some(scratch_dir) => Ok(scratch_dir.path().display().to_string()),
+            None => Err(FunctionCallError::RespondToModel(
+                "no scratch directory is available in this session".to_string(),
+            )),
+        },
         "apply_patch" => {
-            let args: ApplyPatchToolArgs = serde_json::from_str(&arguments).map_err(|e| {
+            let mut args: ApplyPatchToolArgs = serde_json::from_str(&arguments).map_err(|e| {
                 FunctionCallError::RespondToModel(format!(
                     "failed to parse function arguments: {e:?}"
                 ))
             })?;
-            let arg_json = serde_json::from_str::<serde_json::Value>(&arguments)
+            let mut arg_json = serde_json::from_str::<serde_json::Value>(&arguments)
                 .unwrap_or_else(|_| serde_json::json!({ "raw": arguments }));
             let pre_targets = extract_targets_from_patch(&args.input, &turn_context.cwd);
-            if let Err(e) = sess
+            if let Some(modified) = sess
                 .run_pre_tool_hook(
                     &sub_id,
                     &call_id,
@@ -3137,10 +6258,10 @@ async fn handle_function_call(
                     },
                 )
                 .await
+                .map_err(FunctionCallError::RespondToModel)?
             {
-                return Err(FunctionCallError::RespondToModel(format!(
-                    "pre_tool_use hook failed: {e}"
-                )));
+                args = parse_hook_modified_arguments(modified.clone())?;
+                arg_json = modified;
             }
 
             let exec_params = ExecParams {
@@ -3150,8 +6271,12 @@ async fn handle_function_call(
                 env: HashMap::new(),
                 with_escalated_permissions: None,
                 justification: None,
+                tty: false,
+                resource_limits: turn_context.resource_limits,
+                sandbox_extras: turn_context.sandbox_extras.clone(),
             };
 
+            let tool_started = Instant::now();
             let result = handle_container_exec_with_params(
                 exec_params,
                 sess,
@@ -3159,6 +6284,7 @@ async fn handle_function_call(
                 turn_diff_tracker,
                 sub_id.clone(),
                 call_id.clone(),
+                cancellation_token,
             )
             .await;
 
@@ -3180,6 +6306,7 @@ async fn handle_function_call(
                 &turn_context.cwd,
                 success,
                 output_text.as_deref(),
+                tool_started.elapsed(),
                 arg_json,
                 if edited.is_empty() {
                     None
@@ -3207,9 +6334,10 @@ async fn handle_function_call(
             result
         }
         "update_plan" => {
-            let arg_json = serde_json::from_str::<serde_json::Value>(&arguments)
+            let mut arg_json = serde_json::from_str::<serde_json::Value>(&arguments)
                 .unwrap_or_else(|_| serde_json::json!({ "raw": arguments.clone() }));
-            if let Err(e) = sess
+            let mut arguments = arguments;
+            if let Some(modified) = sess
                 .run_pre_tool_hook(
                     &sub_id,
                     &call_id,
@@ -3219,12 +6347,13 @@ async fn handle_function_call(
                     None,
                 )
                 .await
+                .map_err(FunctionCallError::RespondToModel)?
             {
-                return Err(FunctionCallError::RespondToModel(format!(
-                    "pre_tool_use hook failed: {e}"
-                )));
+                arguments = modified.to_string();
+                arg_json = modified;
             }
 
+            let tool_started = Instant::now();
             let result = handle_update_plan(sess, arguments, sub_id.clone(), call_id.clone()).await;
             let (success, output_text) = match &result {
                 Ok(content) => (Some(true), Some(content.clone())),
@@ -3237,6 +6366,7 @@ async fn handle_function_call(
                 &turn_context.cwd,
                 success,
                 output_text.as_deref(),
+                tool_started.elapsed(),
                 arg_json,
                 None,
                 None,
@@ -3248,15 +6378,15 @@ async fn handle_function_call(
             result
         }
         EXEC_COMMAND_TOOL_NAME => {
-            // TODO(mbolin): Sandbox check.
-            let exec_params: ExecCommandParams = serde_json::from_str(&arguments).map_err(|e| {
-                FunctionCallError::RespondToModel(format!(
-                    "failed to parse function arguments: {e:?}"
-                ))
-            })?;
-            let arg_json = serde_json::from_str::<serde_json::Value>(&arguments)
+            let mut exec_params: ExecCommandParams =
+                serde_json::from_str(&arguments).map_err(|e| {
+                    FunctionCallError::RespondToModel(format!(
+                        "failed to parse function arguments: {e:?}"
+                    ))
+                })?;
+            let mut arg_json = serde_json::from_str::<serde_json::Value>(&arguments)
                 .unwrap_or_else(|_| serde_json::json!({ "raw": arguments.clone() }));
-            if let Err(e) = sess
+            if let Some(modified) = sess
                 .run_pre_tool_hook(
                     &sub_id,
                     &call_id,
@@ -3266,21 +6396,42 @@ async fn handle_function_call(
                     None,
                 )
                 .await
+                .map_err(FunctionCallError::RespondToModel)?
             {
-                return Err(FunctionCallError::RespondToModel(format!(
-                    "pre_tool_use hook failed: {e}"
-                )));
+                exec_params = parse_hook_modified_arguments(modified.clone())?;
+                arg_json = modified;
             }
 
-            let result = sess
-                .services
-                .session_manager
-                .handle_exec_command_request(exec_params)
-                .await;
-            let result = match result {
-                Ok(output) => Ok(output.to_text_output()),
-                Err(err) => Err(FunctionCallError::RespondToModel(err)),
+            let tool_started = Instant::now();
+            let result = if turn_context.dry_run {
+                Ok(report_dry_run(
+                    sess,
+                    sub_id.clone(),
+                    call_id.clone(),
+                    exec_params.command(),
+                    turn_context.cwd.clone(),
+                )
+                .await)
+            } else {
+                match exec_command_spawn(
+                    &exec_params,
+                    sess,
+                    turn_context,
+                    sub_id.clone(),
+                    call_id.clone(),
+                )
+                .await
+                {
+                    Ok(spawn) => sess
+                        .services
+                        .session_manager
+                        .handle_exec_command_request(exec_params, spawn)
+                        .await
+                        .map(|output| output.to_text_output()),
+                    Err(err) => Err(err),
+                }
             };
+            let result = result.map_err(FunctionCallError::RespondToModel);
 
             let (success, output_text) = match &result {
                 Ok(content) => (Some(true), Some(content.clone())),
@@ -3293,6 +6444,7 @@ async fn handle_function_call(
                 &turn_context.cwd,
                 success,
                 output_text.as_deref(),
+                tool_started.elapsed(),
                 arg_json,
                 None,
                 None,
@@ -3304,41 +6456,199 @@ async fn handle_function_call(
             result
         }
         WRITE_STDIN_TOOL_NAME => {
-            let write_stdin_params =
-                serde_json::from_str::<WriteStdinParams>(&arguments).map_err(|e| {
+            let mut write_stdin_params = serde_json::from_str::<WriteStdinParams>(&arguments)
+                .map_err(|e| {
                     FunctionCallError::RespondToModel(format!(
                         "failed to parse function arguments: {e:?}"
                     ))
                 })?;
 
-            let arg_json = serde_json::from_str::<serde_json::Value>(&arguments)
+            let mut arg_json = serde_json::from_str::<serde_json::Value>(&arguments)
+                .unwrap_or_else(|_| serde_json::json!({ "raw": arguments.clone() }));
+            if let Some(modified) = sess
+                .run_pre_tool_hook(
+                    &sub_id,
+                    &call_id,
+                    WRITE_STDIN_TOOL_NAME,
+                    &turn_context.cwd,
+                    arg_json.clone(),
+                    None,
+                )
+                .await
+                .map_err(FunctionCallError::RespondToModel)?
+            {
+                write_stdin_params = parse_hook_modified_arguments(modified.clone())?;
+                arg_json = modified;
+            }
+
+            let tool_started = Instant::now();
+            let result = sess
+                .services
+                .session_manager
+                .handle_write_stdin_request(write_stdin_params)
+                .await;
+
+            let result = match result {
+                Ok(output) => Ok(output.to_text_output()),
+                Err(err) => Err(FunctionCallError::RespondToModel(err)),
+            };
+
+            let (success, output_text) = match &result {
+                Ok(text) => (Some(true), Some(text.as_str())),
+                Err(FunctionCallError::RespondToModel(msg)) => (Some(false), Some(msg.as_str())),
+            };
+            sess.run_post_tool_hook(
+                &sub_id,
+                &call_id,
+                WRITE_STDIN_TOOL_NAME,
+                &turn_context.cwd,
+                success,
+                output_text,
+                tool_started.elapsed(),
+                arg_json,
+                None,
+                None,
+                None,
+                None,
+            )
+            .await;
+
+            result
+        }
+        LIST_JOBS_TOOL_NAME => {
+            let mut arg_json = serde_json::from_str::<serde_json::Value>(&arguments)
+                .unwrap_or_else(|_| serde_json::json!({ "raw": arguments.clone() }));
+            if let Some(modified) = sess
+                .run_pre_tool_hook(
+                    &sub_id,
+                    &call_id,
+                    LIST_JOBS_TOOL_NAME,
+                    &turn_context.cwd,
+                    arg_json.clone(),
+                    None,
+                )
+                .await
+                .map_err(FunctionCallError::RespondToModel)?
+            {
+                arg_json = modified;
+            }
+
+            let tool_started = Instant::now();
+            let result: Result<String, FunctionCallError> = Ok(sess
+                .services
+                .session_manager
+                .handle_list_jobs_request()
+                .await);
+
+            let (success, output_text) = match &result {
+                Ok(text) => (Some(true), Some(text.as_str())),
+                Err(FunctionCallError::RespondToModel(msg)) => (Some(false), Some(msg.as_str())),
+            };
+            sess.run_post_tool_hook(
+                &sub_id,
+                &call_id,
+                LIST_JOBS_TOOL_NAME,
+                &turn_context.cwd,
+                success,
+                output_text,
+                tool_started.elapsed(),
+                arg_json,
+                None,
+                None,
+                None,
+                None,
+            )
+            .await;
+
+            result
+        }
+        JOB_OUTPUT_TOOL_NAME => {
+            let mut params = serde_json::from_str::<JobOutputParams>(&arguments).map_err(|e| {
+                FunctionCallError::RespondToModel(format!(
+                    "failed to parse function arguments: {e:?}"
+                ))
+            })?;
+            let mut arg_json = serde_json::from_str::<serde_json::Value>(&arguments)
+                .unwrap_or_else(|_| serde_json::json!({ "raw": arguments.clone() }));
+            if let Some(modified) = sess
+                .run_pre_tool_hook(
+                    &sub_id,
+                    &call_id,
+                    JOB_OUTPUT_TOOL_NAME,
+                    &turn_context.cwd,
+                    arg_json.clone(),
+                    None,
+                )
+                .await
+                .map_err(FunctionCallError::RespondToModel)?
+            {
+                params = parse_hook_modified_arguments(modified.clone())?;
+                arg_json = modified;
+            }
+
+            let tool_started = Instant::now();
+            let result = sess
+                .services
+                .session_manager
+                .handle_job_output_request(params)
+                .await
+                .map(|output| output.to_text_output())
+                .map_err(FunctionCallError::RespondToModel);
+
+            let (success, output_text) = match &result {
+                Ok(text) => (Some(true), Some(text.as_str())),
+                Err(FunctionCallError::RespondToModel(msg)) => (Some(false), Some(msg.as_str())),
+            };
+            sess.run_post_tool_hook(
+                &sub_id,
+                &call_id,
+                JOB_OUTPUT_TOOL_NAME,
+                &turn_context.cwd,
+                success,
+                output_text,
+                tool_started.elapsed(),
+                arg_json,
+                None,
+                None,
+                None,
+                None,
+            )
+            .await;
+
+            result
+        }
+        KILL_JOB_TOOL_NAME => {
+            let mut params = serde_json::from_str::<KillJobParams>(&arguments).map_err(|e| {
+                FunctionCallError::RespondToModel(format!(
+                    "failed to parse function arguments: {e:?}"
+                ))
+            })?;
+            let mut arg_json = serde_json::from_str::<serde_json::Value>(&arguments)
                 .unwrap_or_else(|_| serde_json::json!({ "raw": arguments.clone() }));
-            if let Err(e) = sess
+            if let Some(modified) = sess
                 .run_pre_tool_hook(
                     &sub_id,
                     &call_id,
-                    WRITE_STDIN_TOOL_NAME,
+                    KILL_JOB_TOOL_NAME,
                     &turn_context.cwd,
                     arg_json.clone(),
                     None,
                 )
                 .await
+                .map_err(FunctionCallError::RespondToModel)?
             {
-                return Err(FunctionCallError::RespondToModel(format!(
-                    "pre_tool_use hook failed: {e}"
-                )));
+                params = parse_hook_modified_arguments(modified.clone())?;
+                arg_json = modified;
             }
 
+            let tool_started = Instant::now();
             let result = sess
                 .services
                 .session_manager
-                .handle_write_stdin_request(write_stdin_params)
-                .await;
-
-            let result = match result {
-                Ok(output) => Ok(output.to_text_output()),
-                Err(err) => Err(FunctionCallError::RespondToModel(err)),
-            };
+                .handle_kill_job_request(params)
+                .await
+                .map(|output| output.to_text_output())
+                .map_err(FunctionCallError::RespondToModel);
 
             let (success, output_text) = match &result {
                 Ok(text) => (Some(true), Some(text.as_str())),
@@ -3347,10 +6657,11 @@ async fn handle_function_call(
             sess.run_post_tool_hook(
                 &sub_id,
                 &call_id,
-                WRITE_STDIN_TOOL_NAME,
+                KILL_JOB_TOOL_NAME,
                 &turn_context.cwd,
                 success,
                 output_text,
+                tool_started.elapsed(),
                 arg_json,
                 None,
                 None,
@@ -3375,12 +6686,14 @@ async fn handle_custom_tool_call(
     name: String,
     input: String,
     call_id: String,
+    cancellation_token: &CancellationToken,
 ) -> Result<String, FunctionCallError> {
     info!("CustomToolCall: {name} {input}");
     match name.as_str() {
         "apply_patch" => {
-            let hook_args = serde_json::json!({ "raw": input });
-            if let Err(e) = sess
+            let mut hook_args = serde_json::json!({ "raw": input });
+            let mut input = input;
+            if let Some(modified) = sess
                 .run_pre_tool_hook(
                     &sub_id,
                     &call_id,
@@ -3390,10 +6703,16 @@ async fn handle_custom_tool_call(
                     None,
                 )
                 .await
+                .map_err(FunctionCallError::RespondToModel)?
             {
-                return Err(FunctionCallError::RespondToModel(format!(
-                    "pre_tool_use hook failed: {e}"
-                )));
+                let Some(raw) = modified.get("raw").and_then(serde_json::Value::as_str) else {
+                    return Err(FunctionCallError::RespondToModel(
+                        "pre_tool_use hook returned invalid modified_arguments: expected a `raw` string"
+                            .to_string(),
+                    ));
+                };
+                input = raw.to_string();
+                hook_args = modified;
             }
 
             let exec_params = ExecParams {
@@ -3403,8 +6722,12 @@ async fn handle_custom_tool_call(
                 env: HashMap::new(),
                 with_escalated_permissions: None,
                 justification: None,
+                tty: false,
+                resource_limits: turn_context.resource_limits,
+                sandbox_extras: turn_context.sandbox_extras.clone(),
             };
 
+            let tool_started = Instant::now();
             let result = handle_container_exec_with_params(
                 exec_params,
                 sess,
@@ -3412,6 +6735,7 @@ async fn handle_custom_tool_call(
                 turn_diff_tracker,
                 sub_id.clone(),
                 call_id.clone(),
+                cancellation_token,
             )
             .await;
 
@@ -3426,6 +6750,7 @@ async fn handle_custom_tool_call(
                 &turn_context.cwd,
                 success,
                 output_text.as_deref(),
+                tool_started.elapsed(),
                 hook_args,
                 None,
                 None,
@@ -3446,16 +6771,36 @@ async fn handle_custom_tool_call(
 }
 
 fn to_exec_params(params: ShellToolCallParams, turn_context: &TurnContext) -> ExecParams {
+    let timeout_ms = params
+        .timeout_ms
+        .or_else(|| configured_timeout_ms(&turn_context.exec, &params.command));
     ExecParams {
         command: params.command,
         cwd: turn_context.resolve_path(params.workdir.clone()),
-        timeout_ms: params.timeout_ms,
-        env: create_env(&turn_context.shell_environment_policy),
+        timeout_ms,
+        env: create_env_with_overrides(
+            &turn_context.shell_environment_policy,
+            &params.env.unwrap_or_default(),
+        ),
         with_escalated_permissions: params.with_escalated_permissions,
         justification: params.justification,
+        tty: params.tty.unwrap_or(false),
+        resource_limits: turn_context.resource_limits,
+        sandbox_extras: turn_context.sandbox_extras.clone(),
     }
 }
 
+/// Timeout from `[exec]` for a call that did not set one: the longest
+/// `exec.timeouts` pattern matching `command`, else `exec.default_timeout_ms`.
+fn configured_timeout_ms(exec: &ExecConfig, command: &[String]) -> Option<u64> {
+    exec.timeouts
+        .iter()
+        .filter(|(pattern, _)| pattern_matches_any(pattern, command))
+        .max_by_key(|(pattern, _)| (pattern.len(), pattern.as_str()))
+        .map(|(_, timeout_ms)| *timeout_ms)
+        .or(exec.default_timeout_ms)
+}
+
 fn parse_container_exec_arguments(
     arguments: String,
     turn_context: &TurnContext,
@@ -3475,6 +6820,8 @@ pub struct ExecInvokeArgs<'a> {
     pub sandbox_cwd: &'a Path,
     pub codex_linux_sandbox_exe: &'a Option<PathBuf>,
     pub stdout_stream: Option<StdoutStream>,
+    pub cancellation_token: &'a CancellationToken,
+    pub output_cache: Option<&'a ExecOutputCache>,
 }
 
 fn maybe_translate_shell_command(
@@ -3495,14 +6842,128 @@ fn maybe_translate_shell_command(
     params
 }
 
+/// Runs an `exec_command` call through the same safety assessment and
+/// approval flow as the shell tool and resolves the sandbox it runs under.
+/// Reports `command` as skipped because the session is in dry-run mode and
+/// returns the response for the model.
+async fn report_dry_run(
+    sess: &Session,
+    sub_id: String,
+    call_id: String,
+    command: Vec<String>,
+    cwd: PathBuf,
+) -> String {
+    let detail = command_detail(&command);
+    sess.send_event(Event {
+        id: sub_id,
+        msg: EventMsg::ExecCommandDryRun(ExecCommandDryRunEvent {
+            call_id,
+            command,
+            cwd,
+        }),
+    })
+    .await;
+    format!(
+        "dry run: `{detail}` was not executed because the session is in dry-run mode. Carry on with the task as if it had succeeded, without relying on its output."
+    )
+}
+
+async fn exec_command_spawn(
+    params: &ExecCommandParams,
+    sess: &Session,
+    turn_context: &TurnContext,
+    sub_id: String,
+    call_id: String,
+) -> Result<ExecCommandSpawn, String> {
+    let command = params.command();
+    let safety = {
+        let state = sess.state.lock().await;
+        assess_command_safety(
+            &command,
+            turn_context.approval_policy,
+            &turn_context.sandbox_policy,
+            state.approved_commands_ref(),
+            false,
+        )
+    };
+    let sandbox_type = match safety {
+        SafetyCheck::AutoApprove { sandbox_type } => sandbox_type,
+        SafetyCheck::AskUser => {
+            let decision = sess
+                .request_command_approval(
+                    sub_id,
+                    call_id.clone(),
+                    command.clone(),
+                    turn_context.cwd.clone(),
+                    None,
+                )
+                .await;
+            match decision {
+                ReviewDecision::Approved => (),
+                ReviewDecision::ApprovedForSession => {
+                    sess.add_approved_command(command.clone()).await;
+                }
+                ReviewDecision::ApprovedAlways => {
+                    sess.approve_command_always(command.clone()).await;
+                }
+                ReviewDecision::Denied | ReviewDecision::Abort => {
+                    return Err("exec command rejected by user".to_string());
+                }
+            }
+            // As with the shell tool, explicit approval runs unsandboxed.
+            SandboxType::None
+        }
+        SafetyCheck::Reject { reason } => {
+            return Err(format!("exec command rejected: {reason:?}"));
+        }
+    };
+
+    let mut env = create_env(&turn_context.shell_environment_policy);
+    {
+        let state = sess.state.lock().await;
+        apply_session_env(&mut env, state.session_env());
+    }
+    if sess.services.command_provenance {
+        command_log::stamp_env(&mut env, &sess.conversation_id, &call_id);
+    }
+    if let Some(dir) = sess.services.vendored_bin_dir.get() {
+        vendored_tools::append_to_path(&mut env, dir);
+    }
+    sess.log_command_provenance(&turn_context.cwd, &call_id, &turn_context.cwd, &command)
+        .await;
+    let (sandbox_policy, sandbox_cwd) = sess.exec_sandbox_policy(turn_context);
+    Ok(ExecCommandSpawn {
+        cwd: turn_context.cwd.clone(),
+        env,
+        sandbox_type,
+        sandbox_policy,
+        sandbox_cwd,
+        codex_linux_sandbox_exe: sess.services.codex_linux_sandbox_exe.clone(),
+        resource_limits: turn_context.resource_limits,
+        sandbox_extras: turn_context.sandbox_extras.clone(),
+    })
+}
+
 async fn handle_container_exec_with_params(
-    params: ExecParams,
+    mut params: ExecParams,
     sess: &Session,
     turn_context: &TurnContext,
     turn_diff_tracker: &mut TurnDiffTracker,
     sub_id: String,
     call_id: String,
+    cancellation_token: &CancellationToken,
 ) -> Result<String, FunctionCallError> {
+    {
+        let state = sess.state.lock().await;
+        apply_session_env(&mut params.env, state.session_env());
+    }
+    if sess.services.command_provenance {
+        command_log::stamp_env(&mut params.env, &sess.conversation_id, &call_id);
+    }
+    if let Some(dir) = sess.services.vendored_bin_dir.get() {
+        vendored_tools::append_to_path(&mut params.env, dir);
+    }
+
     if params.with_escalated_permissions.unwrap_or(false)
         && !matches!(turn_context.approval_policy, AskForApproval::OnRequest)
     {
@@ -3512,35 +6973,131 @@ async fn handle_container_exec_with_params(
         )));
     }
 
-    // check if this was a patch, and apply it if so
-    let apply_patch_exec = match maybe_parse_apply_patch_verified(&params.command, &params.cwd) {
-        MaybeApplyPatchVerified::Body(changes) => {
-            match apply_patch::apply_patch(sess, turn_context, &sub_id, &call_id, changes).await {
-                InternalApplyPatchInvocation::Output(item) => return item,
-                InternalApplyPatchInvocation::DelegateToExec(apply_patch_exec) => {
-                    Some(apply_patch_exec)
-                }
+    let escalation_justification = if params.with_escalated_permissions.unwrap_or(false) {
+        match params
+            .justification
+            .as_deref()
+            .map(str::trim)
+            .filter(|j| !j.is_empty())
+        {
+            Some(justification) => Some(justification.to_string()),
+            None => {
+                return Err(FunctionCallError::RespondToModel(
+                    "with_escalated_permissions requires a justification explaining why the command cannot run in the sandbox".to_string(),
+                ));
             }
         }
-        MaybeApplyPatchVerified::CorrectnessError(parse_error) => {
-            // It looks like an invocation of `apply_patch`, but we
-            // could not resolve it into a patch that would apply
-            // cleanly. Return to model for resample.
+    } else {
+        None
+    };
+
+    if let Some(path) =
+        CodexIgnore::load(&turn_context.cwd).ignored_read_operand(&params.command, &params.cwd)
+    {
+        return Err(FunctionCallError::RespondToModel(format!(
+            "{} is excluded by {CODEX_IGNORE_FILENAME}; do not read it",
+            display_path(&path)
+        )));
+    }
+
+    // check if this was a patch, and apply it if so. A patch whose context no
+    // longer matches is merged against the file as of the last ghost snapshot.
+    let base_snapshot = sess.state.lock().await.ghost_snapshots.last().cloned();
+    let read_base = |path: &Path| {
+        let snapshot = base_snapshot.as_ref()?;
+        read_file_at_commit(&snapshot.cwd, snapshot.commit.id(), path)
+            .ok()
+            .flatten()
+    };
+    let apply_patch_exec =
+        match maybe_parse_apply_patch_verified_with_base(&params.command, &params.cwd, read_base) {
+            MaybeApplyPatchVerified::Body(changes) => {
+                let session_cwd = sess.services.permission_gate.session_cwd();
+                let mut outside = paths_outside(&changes, session_cwd);
+                if let Some(scratch_dir) = &sess.services.scratch_dir {
+                    outside.retain(|path| !scratch_dir.contains(path));
+                }
+                if !outside.is_empty() {
+                    let detail = outside
+                        .iter()
+                        .map(|path| path.display().to_string())
+                        .collect::<Vec<_>>()
+                        .join("\n");
+                    sess.check_guided_permission(
+                        turn_context,
+                        &sub_id,
+                        &call_id,
+                        GuidedPermission::WriteOutsideCwd {
+                            cwd: session_cwd.to_path_buf(),
+                        },
+                        detail,
+                    )
+                    .await
+                    .map_err(FunctionCallError::RespondToModel)?;
+                }
+                match apply_patch::apply_patch(sess, turn_context, &sub_id, &call_id, changes).await
+                {
+                    InternalApplyPatchInvocation::Output(item) => return item,
+                    InternalApplyPatchInvocation::DelegateToExec(apply_patch_exec) => {
+                        Some(apply_patch_exec)
+                    }
+                }
+            }
+            MaybeApplyPatchVerified::CorrectnessError(parse_error) => {
+                // It looks like an invocation of `apply_patch`, but we
+                // could not resolve it into a patch that would apply
+                // cleanly. Return to model for resample.
+                return Err(FunctionCallError::RespondToModel(format!(
+                    "error: {parse_error:#?}"
+                )));
+            }
+            MaybeApplyPatchVerified::ShellParseError(error) => {
+                trace!("Failed to parse shell command, {error:?}");
+                None
+            }
+            MaybeApplyPatchVerified::NotApplyPatch => None,
+        };
+
+    // In dry-run mode the command is only reported; nothing asks for approval
+    // because nothing runs.
+    if turn_context.dry_run && apply_patch_exec.is_none() {
+        return Ok(report_dry_run(sess, sub_id, call_id, params.command, params.cwd).await);
+    }
+
+    let network_available = turn_context.sandbox_policy.has_full_network_access()
+        || params.with_escalated_permissions.unwrap_or(false);
+    if apply_patch_exec.is_none() && network_available && is_network_command(&params.command) {
+        sess.check_guided_permission(
+            turn_context,
+            &sub_id,
+            &call_id,
+            GuidedPermission::Network,
+            command_detail(&params.command),
+        )
+        .await
+        .map_err(FunctionCallError::RespondToModel)?;
+    }
+
+    if apply_patch_exec.is_none() {
+        let limit = sess.services.repeated_command_failure_limit;
+        let state = sess.state.lock().await;
+        if let Some(failed) = state.failed_command(&params.command, &params.cwd)
+            && limit > 0
+            && failed.count >= limit
+        {
             return Err(FunctionCallError::RespondToModel(format!(
-                "error: {parse_error:#?}"
+                "command not run: it already failed {count} times in a row with identical output, so retrying it will not change the result. Try a different approach instead.\n\nLast output:\n{output}",
+                count = failed.count,
+                output = failed.output,
             )));
         }
-        MaybeApplyPatchVerified::ShellParseError(error) => {
-            trace!("Failed to parse shell command, {error:?}");
-            None
-        }
-        MaybeApplyPatchVerified::NotApplyPatch => None,
-    };
+    }
 
     let (params, safety, command_for_display) = match &apply_patch_exec {
         Some(ApplyPatchExec {
             action: ApplyPatchAction { patch, cwd, .. },
             user_explicitly_approved_this_action,
+            ..
         }) => {
             let path_to_codex = std::env::current_exe()
                 .ok()
@@ -3562,6 +7119,9 @@ async fn handle_container_exec_with_params(
                 env: HashMap::new(),
                 with_escalated_permissions: params.with_escalated_permissions,
                 justification: params.justification.clone(),
+                tty: false,
+                resource_limits: params.resource_limits,
+                sandbox_extras: params.sandbox_extras.clone(),
             };
             let safety = if *user_explicitly_approved_this_action {
                 SafetyCheck::AutoApprove {
@@ -3596,23 +7156,49 @@ async fn handle_container_exec_with_params(
         }
     };
 
+    // Only commands the user did not approve may be moved into a stricter
+    // sandbox: an approval may be because the command needs to run outside one.
+    let approved_for_session = {
+        let state = sess.state.lock().await;
+        state.approved_commands_ref().contains(&params.command)
+    };
+    let may_narrow_sandbox = apply_patch_exec.is_none()
+        && !approved_for_session
+        && matches!(safety, SafetyCheck::AutoApprove { .. });
     let sandbox_type = match safety {
         SafetyCheck::AutoApprove { sandbox_type } => sandbox_type,
         SafetyCheck::AskUser => {
-            let decision = sess
-                .request_command_approval(
-                    sub_id.clone(),
-                    call_id.clone(),
-                    params.command.clone(),
-                    params.cwd.clone(),
-                    params.justification.clone(),
-                )
-                .await;
+            let decision = match escalation_justification {
+                Some(justification) => {
+                    sess.request_escalation(
+                        sub_id.clone(),
+                        call_id.clone(),
+                        params.command.clone(),
+                        params.cwd.clone(),
+                        justification,
+                        turn_context.sandbox_policy.escalated_permissions(),
+                    )
+                    .await
+                }
+                None => {
+                    sess.request_command_approval(
+                        sub_id.clone(),
+                        call_id.clone(),
+                        params.command.clone(),
+                        params.cwd.clone(),
+                        params.justification.clone(),
+                    )
+                    .await
+                }
+            };
             match decision {
                 ReviewDecision::Approved => (),
                 ReviewDecision::ApprovedForSession => {
                     sess.add_approved_command(params.command.clone()).await;
                 }
+                ReviewDecision::ApprovedAlways => {
+                    sess.approve_command_always(params.command.clone()).await;
+                }
                 ReviewDecision::Denied | ReviewDecision::Abort => {
                     return Err(FunctionCallError::RespondToModel(
                         "exec command rejected by user".to_string(),
@@ -3632,6 +7218,17 @@ async fn handle_container_exec_with_params(
         }
     };
 
+    // Snapshot the working tree before anything that may write to it.
+    let snapshot_id = if apply_patch_exec.is_some() || !is_known_safe_command(&params.command) {
+        sess.capture_ghost_snapshot(&params.cwd, &sub_id, &call_id)
+            .await
+    } else {
+        None
+    };
+
+    let skipped_note = apply_patch_exec
+        .as_ref()
+        .and_then(apply_patch::skipped_files_note);
     let exec_command_context = ExecCommandContext {
         sub_id: sub_id.clone(),
         call_id: call_id.clone(),
@@ -3641,13 +7238,30 @@ async fn handle_container_exec_with_params(
             |ApplyPatchExec {
                  action,
                  user_explicitly_approved_this_action,
+                 ..
              }| ApplyPatchCommandContext {
                 user_explicitly_approved_this_action,
-                changes: convert_apply_patch_to_protocol(&action),
+                changes: convert_apply_patch_to_protocol(&action).0,
+                snapshot_id,
             },
         ),
     };
 
+    // Remember the command as the model issued it so retries can be matched.
+    let failure_key = exec_command_context
+        .apply_patch
+        .is_none()
+        .then(|| (params.command.clone(), params.cwd.clone()));
+    let (sandbox_policy, sandbox_cwd) = sess.exec_sandbox_policy(turn_context);
+    let (sandbox_type, sandbox_policy) =
+        match read_only_sandbox_for(&params.command, &sandbox_policy) {
+            Some(narrowed) if may_narrow_sandbox => narrowed,
+            _ => (sandbox_type, sandbox_policy),
+        };
+    if failure_key.is_some() {
+        sess.log_command_provenance(&turn_context.cwd, &call_id, &params.cwd, &params.command)
+            .await;
+    }
     let params = maybe_translate_shell_command(params, sess, turn_context);
     let output_result = sess
         .run_exec_with_events(
@@ -3656,8 +7270,8 @@ async fn handle_container_exec_with_params(
             ExecInvokeArgs {
                 params: params.clone(),
                 sandbox_type,
-                sandbox_policy: &turn_context.sandbox_policy,
-                sandbox_cwd: &turn_context.cwd,
+                sandbox_policy: &sandbox_policy,
+                sandbox_cwd: &sandbox_cwd,
                 codex_linux_sandbox_exe: &sess.services.codex_linux_sandbox_exe,
                 stdout_stream: if exec_command_context.apply_patch.is_some() {
                     None
@@ -3666,16 +7280,37 @@ async fn handle_container_exec_with_params(
                         sub_id: sub_id.clone(),
                         call_id: call_id.clone(),
                         tx_event: sess.tx_event.clone(),
+                        streaming: sess.services.output_streaming,
                     })
                 },
+                cancellation_token,
+                output_cache: sess.services.exec_output_cache.as_ref(),
             },
         )
         .await;
 
     match output_result {
-        Ok(output) => {
+        Ok(mut output) => {
+            if let Some(note) = &skipped_note {
+                output
+                    .aggregated_output
+                    .text
+                    .push_str(&format!("\n{note}\n"));
+            }
             let ExecToolCallOutput { exit_code, .. } = &output;
             let content = format_exec_output(&output);
+            let mut state = sess.state.lock().await;
+            match failure_key {
+                Some((command, cwd)) if *exit_code != 0 => {
+                    state.record_command_failure(command, cwd, format_exec_output_str(&output));
+                }
+                Some((command, cwd)) => state.record_command_success(&command, &cwd),
+                // A successful patch changes the workspace, so earlier
+                // failures may no longer reproduce.
+                None if *exit_code == 0 => state.clear_failed_commands(),
+                None => {}
+            }
+            drop(state);
             if *exit_code == 0 {
                 Ok(content)
             } else {
@@ -3691,6 +7326,7 @@ async fn handle_container_exec_with_params(
                 sandbox_type,
                 sess,
                 turn_context,
+                cancellation_token,
             )
             .await
         }
@@ -3708,6 +7344,7 @@ async fn handle_sandbox_error(
     sandbox_type: SandboxType,
     sess: &Session,
     turn_context: &TurnContext,
+    cancellation_token: &CancellationToken,
 ) -> Result<String, FunctionCallError> {
     let call_id = exec_command_context.call_id.clone();
     let sub_id = exec_command_context.sub_id.clone();
@@ -3752,7 +7389,9 @@ async fn handle_sandbox_error(
         .await;
 
     match decision {
-        ReviewDecision::Approved | ReviewDecision::ApprovedForSession => {
+        ReviewDecision::Approved
+        | ReviewDecision::ApprovedForSession
+        | ReviewDecision::ApprovedAlways => {
             // Persist this command as pre‑approved for the
             // remainder of the session so future
             // executions skip the sandbox directly.
@@ -3781,8 +7420,11 @@ async fn handle_sandbox_error(
                                 sub_id: sub_id.clone(),
                                 call_id: call_id.clone(),
                                 tx_event: sess.tx_event.clone(),
+                                streaming: sess.services.output_streaming,
                             })
                         },
+                        cancellation_token,
+                        output_cache: sess.services.exec_output_cache.as_ref(),
                     },
                 )
                 .await;
@@ -4075,8 +7717,8 @@ fn parse_patch_effects(
     for line in patch.lines() {
         if let Some(rest) = line.strip_prefix("diff --git a/") {
             let mut parts = rest.splitn(2, " b/");
-            cur_a = parts.next().map(|s| s.to_string());
-            cur_b = parts.next().map(|s| s.to_string());
+            cur_a = parts.next().map(str::to_string);
+            cur_b = parts.next().map(str::to_string);
         } else if line.starts_with("new file mode ") {
             if let Some(b) = &cur_b {
                 created.insert(cwd.join(b));
@@ -4122,7 +7764,7 @@ fn is_rm_command(argv: &[String]) -> bool {
     if argv[0] == "rm" {
         return true;
     }
-    if argv[0] == "git" && argv.get(1).map(|s| s.as_str()) == Some("rm") {
+    if argv[0] == "git" && argv.get(1).map(String::as_str) == Some("rm") {
         return true;
     }
     false
@@ -4271,6 +7913,143 @@ mod tests {
     use std::sync::Arc;
     use std::time::Duration as StdDuration;
 
+    #[test]
+    fn only_read_only_commands_run_in_parallel() {
+        let (session, turn_context) = make_session_and_context();
+        let shell_call = |arguments: serde_json::Value| ResponseItem::FunctionCall {
+            id: None,
+            name: "shell".to_string(),
+            arguments: arguments.to_string(),
+            call_id: "call-1".to_string(),
+        };
+
+        assert!(is_parallel_safe_tool_call(
+            &session,
+            &turn_context,
+            &shell_call(json!({ "command": ["cat", "README.md"] })),
+        ));
+        assert!(!is_parallel_safe_tool_call(
+            &session,
+            &turn_context,
+            &shell_call(json!({ "command": ["rm", "README.md"] })),
+        ));
+        assert!(!is_parallel_safe_tool_call(
+            &session,
+            &turn_context,
+            &shell_call(json!({
+                "command": ["cat", "README.md"],
+                "with_escalated_permissions": true,
+            })),
+        ));
+        assert!(!is_parallel_safe_tool_call(
+            &session,
+            &turn_context,
+            &ResponseItem::FunctionCall {
+                id: None,
+                name: "apply_patch".to_string(),
+                arguments: "{}".to_string(),
+                call_id: "call-2".to_string(),
+            },
+        ));
+    }
+
+    #[test]
+    fn only_mcp_tools_marked_read_only_run_in_parallel() {
+        let (mut session, mut turn_context) = make_session_and_context();
+        let tool = |name: &str, read_only_hint: Option<bool>| mcp_types::Tool {
+            annotations: Some(mcp_types::ToolAnnotations {
+                destructive_hint: None,
+                idempotent_hint: None,
+                open_world_hint: None,
+                read_only_hint,
+                title: None,
+            }),
+            description: None,
+            input_schema: mcp_types::ToolInputSchema {
+                properties: None,
+                required: None,
+                r#type: "object".to_string(),
+            },
+            name: name.to_string(),
+            output_schema: None,
+            title: None,
+        };
+        session.services.mcp_connection_manager = McpConnectionManager::with_tools_for_test(vec![
+            ("docs", tool("search", Some(true))),
+            ("docs", tool("create_page", Some(false))),
+            ("docs", tool("sync", None)),
+        ]);
+        let mcp_call = |name: &str| ResponseItem::FunctionCall {
+            id: None,
+            name: name.to_string(),
+            arguments: "{}".to_string(),
+            call_id: "call-1".to_string(),
+        };
+
+        // The first call of a tool may prompt for permission, which cannot
+        // happen while other calls run.
+        assert!(!is_parallel_safe_tool_call(
+            &session,
+            &turn_context,
+            &mcp_call("docs__search")
+        ));
+
+        turn_context.approval_policy = AskForApproval::Never;
+        assert!(is_parallel_safe_tool_call(
+            &session,
+            &turn_context,
+            &mcp_call("docs__search")
+        ));
+        assert!(!is_parallel_safe_tool_call(
+            &session,
+            &turn_context,
+            &mcp_call("docs__create_page"),
+        ));
+        assert!(!is_parallel_safe_tool_call(
+            &session,
+            &turn_context,
+            &mcp_call("docs__sync")
+        ));
+    }
+
+    #[test]
+    fn interrupted_parallel_calls_are_recorded_as_aborted() {
+        let item = ResponseItem::FunctionCall {
+            id: None,
+            name: "shell".to_string(),
+            arguments: "{}".to_string(),
+            call_id: "call-1".to_string(),
+        };
+        assert_eq!(
+            aborted_tool_call_output(&item, "aborted"),
+            Some(ResponseInputItem::FunctionCallOutput {
+                call_id: "call-1".to_string(),
+                output: FunctionCallOutputPayload {
+                    content: "aborted".to_string(),
+                    success: Some(false),
+                },
+            })
+        );
+    }
+
+    #[test]
+    fn cancelled_custom_tool_call_gets_custom_output() {
+        let item = ResponseItem::CustomToolCall {
+            id: None,
+            status: None,
+            call_id: "call-1".to_string(),
+            name: "apply_patch".to_string(),
+            input: String::new(),
+        };
+        assert_eq!(
+            aborted_tool_call_output(&item, "aborted by user"),
+            Some(ResponseInputItem::CustomToolCallOutput {
+                call_id: "call-1".to_string(),
+                output: "aborted by user".to_string(),
+            })
+        );
+    }
+
     #[test]
     fn reconstruct_history_matches_live_compactions() {
         let (session, turn_context) = make_session_and_context();
@@ -4434,6 +8213,33 @@ mod tests {
         );
     }
 
+    #[test]
+    fn configured_timeout_prefers_the_most_specific_pattern() {
+        let exec = ExecConfig {
+            default_timeout_ms: Some(60_000),
+            timeouts: HashMap::from([
+                ("cargo *".to_string(), 120_000),
+                ("cargo test *".to_string(), 600_000),
+            ]),
+            ..Default::default()
+        };
+        let argv = |args: &[&str]| args.iter().map(ToString::to_string).collect::<Vec<_>>();
+
+        assert_eq!(
+            configured_timeout_ms(&exec, &argv(&["bash", "-lc", "cargo test -p core"])),
+            Some(600_000)
+        );
+        assert_eq!(
+            configured_timeout_ms(&exec, &argv(&["cargo", "build"])),
+            Some(120_000)
+        );
+        assert_eq!(configured_timeout_ms(&exec, &argv(&["ls"])), Some(60_000));
+        assert_eq!(
+            configured_timeout_ms(&ExecConfig::default(), &argv(&["ls"])),
+            None
+        );
+    }
+
     #[test]
     fn falls_back_to_content_when_structured_is_null() {
         let ctr = CallToolResult {
@@ -4520,6 +8326,8 @@ mod tests {
             include_web_search_request: config.tools_web_search_request,
             use_streamable_shell_tool: config.use_experimental_streamable_shell_tool,
             include_view_image_tool: config.include_view_image_tool,
+            include_scratch_path_tool: config.scratch_dir,
+            include_delegate_tool: config.tools_delegate,
             experimental_unified_exec_tool: config.use_experimental_unified_exec_tool,
         });
         let turn_context = TurnContext {
@@ -4530,9 +8338,14 @@ mod tests {
             approval_policy: config.approval_policy,
             sandbox_policy: config.sandbox_policy.clone(),
             shell_environment_policy: config.shell_environment_policy.clone(),
+            resource_limits: config.resource_limits,
+            sandbox_extras: config.sandbox_extras.clone(),
             tools_config,
             is_review_mode: false,
             final_output_json_schema: None,
+            dry_run: false,
+            exec: config.exec.clone(),
+            task_budget: config.task_budget,
         };
         let services = SessionServices {
             mcp_connection_manager: McpConnectionManager::default(),
@@ -4544,6 +8357,27 @@ mod tests {
             user_shell: shell::Shell::Unknown,
             show_raw_agent_reasoning: config.show_raw_agent_reasoning,
             hooks: config.hooks.clone(),
+            repeated_command_failure_limit: config.repeated_command_failure_limit,
+            max_parallel_tool_calls: config.max_parallel_tool_calls,
+            sub_agents: SubAgentManager::default(),
+            stall_detection_turns: config.stall_detection_turns,
+            stall_action: config.stall_action,
+            busy_input: config.busy_input,
+            max_session_cost_usd: config.max_session_cost_usd,
+            auto_compact_preserve_messages: config.auto_compact_preserve_messages,
+            task_completion_assessment: config.task_completion_assessment,
+            storage_guard: StorageGuard::new(config.codex_home.clone(), config.storage),
+            codex_home: config.codex_home.clone(),
+            permission_gate: PermissionGate::new(&config),
+            scratch_dir: None,
+            locale_context: false,
+            git_auto_branch: false,
+            command_provenance: false,
+            output_streaming: config.output_streaming,
+            automations: Automations::new(Vec::new()),
+            vendored_bin_dir: Arc::new(OnceLock::new()),
+            live_session: None,
+            exec_output_cache: None,
         };
         let session = Session {
             conversation_id,
@@ -4600,6 +8434,7 @@ mod tests {
         live_history.replace(rebuilt1);
         rollout_items.push(RolloutItem::Compacted(CompactedItem {
             message: summary1.to_string(),
+            preserved_messages: 0,
         }));
 
         let user2 = ResponseItem::Message {
@@ -4633,6 +8468,7 @@ mod tests {
         live_history.replace(rebuilt2);
         rollout_items.push(RolloutItem::Compacted(CompactedItem {
             message: summary2.to_string(),
+            preserved_messages: 0,
         }));
 
         let user3 = ResponseItem::Message {
@@ -4689,6 +8525,9 @@ mod tests {
             env: HashMap::new(),
             with_escalated_permissions: Some(true),
             justification: Some("test".to_string()),
+            tty: false,
+            resource_limits: ResourceLimits::default(),
+            sandbox_extras: SandboxExtras::default(),
         };
 
         let params2 = ExecParams {
@@ -4708,6 +8547,7 @@ mod tests {
             &mut turn_diff_tracker,
             sub_id,
             call_id,
+            &CancellationToken::new(),
         )
         .await;
 
@@ -4733,6 +8573,7 @@ mod tests {
             &mut turn_diff_tracker,
             "test-sub".to_string(),
             "test-call-2".to_string(),
+            &CancellationToken::new(),
         )
         .await;
 
@@ -4755,4 +8596,81 @@ mod tests {
         pretty_assertions::assert_eq!(exec_output.metadata, ResponseExecMetadata { exit_code: 0 });
         assert!(exec_output.output.contains("hi"));
     }
+
+    #[test]
+    fn pre_model_call_summary_counts_items_and_lists_tool_calls() {
+        let prompt = Prompt {
+            input: vec![
+                ResponseItem::Message {
+                    id: None,
+                    role: "user".to_string(),
+                    content: vec![ContentItem::InputText {
+                        text: "show me the key".to_string(),
+                    }],
+                },
+                ResponseItem::FunctionCall {
+                    id: None,
+                    name: "shell".to_string(),
+                    arguments: r#"{"command":["cat","id.pem"]}"#.to_string(),
+                    call_id: "call-1".to_string(),
+                },
+            ],
+            tools: vec![OpenAiTool::LocalShell {}, OpenAiTool::WebSearch {}],
+            ..Prompt::default()
+        };
+
+        let summary = pre_model_call_summary(&prompt);
+
+        assert_eq!(
+            summary["item_counts"],
+            json!({ "function_call": 1, "message": 1 })
+        );
+        assert_eq!(summary["tools"], json!(["local_shell", "web_search"]));
+        assert_eq!(
+            summary["tool_calls"],
+            json!([{ "name": "shell", "arguments": r#"{"command":["cat","id.pem"]}"# }])
+        );
+        assert!(summary["estimated_tokens"].as_u64().is_some_and(|n| n > 0));
+    }
+
+    #[test]
+    fn task_budget_reports_the_first_limit_reached() {
+        let budget = TaskBudget {
+            max_turns: Some(3),
+            max_total_tokens: Some(1_000),
+            max_duration: Some(StdDuration::from_secs(60)),
+        };
+        let usage = TokenUsage {
+            total_tokens: 999,
+            ..TokenUsage::default()
+        };
+        let elapsed = StdDuration::from_secs(59);
+
+        assert_eq!(exceeded_task_budget(&budget, 2, &usage, elapsed), None);
+        assert_eq!(
+            exceeded_task_budget(&budget, 3, &usage, elapsed),
+            Some(ExceededBudget {
+                limit: TaskBudgetLimit::MaxTurns,
+                used: 3.0,
+                allowed: 3.0,
+            })
+        );
+        let spent = TokenUsage {
+            total_tokens: 1_000,
+            ..TokenUsage::default()
+        };
+        assert_eq!(
+            exceeded_task_budget(&budget, 2, &spent, elapsed).map(|exceeded| exceeded.limit),
+            Some(TaskBudgetLimit::MaxTotalTokens)
+        );
+        assert_eq!(
+            exceeded_task_budget(&budget, 2, &usage, StdDuration::from_secs(60))
+                .map(|exceeded| exceeded.limit),
+            Some(TaskBudgetLimit::MaxDuration)
+        );
+        assert_eq!(
+            exceeded_task_budget(&TaskBudget::default(), u32::MAX, &spent, StdDuration::MAX),
+            None
+        );
+    }
 }
@@ -47,15 +47,18 @@ pub(super) async fn spawn_compact_task(
     sess.set_task(task).await;
 }
 
+/// Compact the history in the middle of a task, keeping the last
+/// `preserve_messages` user and assistant messages after the summary.
 pub(super) async fn run_inline_auto_compact_task(
     sess: Arc<Session>,
     turn_context: Arc<TurnContext>,
+    preserve_messages: u32,
 ) {
     let sub_id = sess.next_internal_sub_id();
     let input = vec![InputItem::Text {
         text: SUMMARIZATION_PROMPT.to_string(),
     }];
-    run_compact_task_inner(sess, turn_context, sub_id, input, false).await;
+    run_compact_task_inner(sess, turn_context, sub_id, input, false, preserve_messages).await;
 }
 
 pub(super) async fn run_compact_task(
@@ -68,14 +71,18 @@ pub(super) async fn run_compact_task(
         id: sub_id.clone(),
         msg: EventMsg::TaskStarted(TaskStartedEvent {
             model_context_window: turn_context.client.get_model_context_window(),
+            branch: None,
         }),
     };
     sess.send_event(start_event).await;
-    run_compact_task_inner(sess.clone(), turn_context, sub_id.clone(), input, true).await;
+    run_compact_task_inner(sess.clone(), turn_context, sub_id.clone(), input, true, 0).await;
     let event = Event {
         id: sub_id,
         msg: EventMsg::TaskComplete(TaskCompleteEvent {
             last_agent_message: None,
+            assessment: None,
+            estimated_cost_usd: sess.estimated_cost_usd().await,
+            output_schema_error: None,
         }),
     };
     sess.send_event(event).await;
@@ -87,11 +94,14 @@ async fn run_compact_task_inner(
     sub_id: String,
     input: Vec<InputItem>,
     remove_task_on_completion: bool,
+    preserve_messages: u32,
 ) {
     let initial_input_for_turn: ResponseInputItem = ResponseInputItem::from(input);
     let turn_input = sess
         .turn_input_with_history(vec![initial_input_for_turn.clone().into()])
         .await;
+    // Taken before the summary is recorded into the history.
+    let preserved = recent_messages(&sess.history_snapshot().await, preserve_messages);
 
     let prompt = Prompt {
         input: turn_input,
@@ -155,11 +165,13 @@ async fn run_compact_task_inner(
     let summary_text = get_last_assistant_message_from_turn(&history_snapshot).unwrap_or_default();
     let user_messages = collect_user_messages(&history_snapshot);
     let initial_context = sess.build_initial_context(turn_context.as_ref());
-    let new_history = build_compacted_history(initial_context, &user_messages, &summary_text);
+    let mut new_history = build_compacted_history(initial_context, &user_messages, &summary_text);
+    new_history.extend(preserved);
     sess.replace_history(new_history).await;
 
     let rollout_item = RolloutItem::Compacted(CompactedItem {
         message: summary_text.clone(),
+        preserved_messages: preserve_messages,
     });
     sess.persist_rollout_items(&[rollout_item]).await;
 
@@ -211,6 +223,26 @@ pub fn is_session_prefix_message(text: &str) -> bool {
     )
 }
 
+/// The last `count` user and assistant messages of `items`, oldest first,
+/// leaving out the session prefix (user instructions and environment context).
+pub(crate) fn recent_messages(items: &[ResponseItem], count: u32) -> Vec<ResponseItem> {
+    let mut recent: Vec<ResponseItem> = items
+        .iter()
+        .rev()
+        .filter(|item| match item {
+            ResponseItem::Message { role, content, .. } if role == "user" => {
+                !content_items_to_text(content).is_some_and(|text| is_session_prefix_message(&text))
+            }
+            ResponseItem::Message { role, .. } => role == "assistant",
+            _ => false,
+        })
+        .take(count as usize)
+        .cloned()
+        .collect();
+    recent.reverse();
+    recent
+}
+
 pub(crate) fn build_compacted_history(
     initial_context: Vec<ResponseItem>,
     user_messages: &[String],
@@ -404,4 +436,41 @@ mod tests {
             "bridge should include the provided summary text"
         );
     }
+
+    #[test]
+    fn recent_messages_keeps_the_last_user_and_assistant_messages_in_order() {
+        let message = |role: &str, text: &str| ResponseItem::Message {
+            id: None,
+            role: role.to_string(),
+            content: vec![ContentItem::InputText {
+                text: text.to_string(),
+            }],
+        };
+        let items = vec![
+            message(
+                "user",
+                "<ENVIRONMENT_CONTEXT>cwd=/tmp</ENVIRONMENT_CONTEXT>",
+            ),
+            message("user", "first"),
+            message("assistant", "first reply"),
+            message("user", "second"),
+            ResponseItem::FunctionCall {
+                id: None,
+                name: "shell".to_string(),
+                arguments: "{}".to_string(),
+                call_id: "call-1".to_string(),
+            },
+            message("assistant", "second reply"),
+        ];
+
+        assert_eq!(
+            recent_messages(&items, 2),
+            vec![
+                message("user", "second"),
+                message("assistant", "second reply")
+            ]
+        );
+        assert_eq!(recent_messages(&items, 10).len(), 4);
+        assert_eq!(recent_messages(&items, 0), Vec::<ResponseItem>::new());
+    }
 }
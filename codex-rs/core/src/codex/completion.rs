@@ -0,0 +1,168 @@
+//! Final structured-output request that asks the model to assess a finished
+//! task and suggest follow-up actions.
+
+use super::Session;
+use super::TurnContext;
+use super::get_last_assistant_message_from_turn;
+use crate::Prompt;
+use crate::client_common::ResponseEvent;
+use crate::error::CodexErr;
+use crate::error::Result as CodexResult;
+use crate::protocol::CompletionAssessment;
+use codex_protocol::models::ContentItem;
+use codex_protocol::models::ResponseItem;
+use futures::prelude::*;
+use serde_json::Value;
+use serde_json::json;
+use tracing::warn;
+
+pub const COMPLETION_ASSESSMENT_PROMPT: &str = include_str!("../../templates/completion/prompt.md");
+
+/// Upper bound on the follow-ups forwarded to clients.
+const MAX_FOLLOW_UPS: usize = 3;
+
+/// Ask the model to assess the task that just finished. The request and its
+/// answer are not recorded in the conversation history. Failures are logged
+/// and yield `None` so they never affect the task itself.
+pub(super) async fn assess_completion(
+    sess: &Session,
+    turn_context: &TurnContext,
+) -> Option<CompletionAssessment> {
+    let request = ResponseItem::Message {
+        id: None,
+        role: "user".to_string(),
+        content: vec![ContentItem::InputText {
+            text: COMPLETION_ASSESSMENT_PROMPT.to_string(),
+        }],
+    };
+    let prompt = Prompt {
+        input: sess.turn_input_with_history(vec![request]).await,
+        output_schema: Some(assessment_schema()),
+        ..Default::default()
+    };
+
+    let text = match collect_final_message(turn_context, &prompt).await {
+        Ok(Some(text)) => text,
+        Ok(None) => {
+            warn!("completion assessment returned no message");
+            return None;
+        }
+        Err(e) => {
+            warn!("completion assessment failed: {e}");
+            return None;
+        }
+    };
+    match parse_assessment(&text) {
+        Some(assessment) => Some(assessment),
+        None => {
+            warn!("failed to parse completion assessment: {text}");
+            None
+        }
+    }
+}
+
+fn assessment_schema() -> Value {
+    json!({
+        "type": "object",
+        "properties": {
+            "confidence": {
+                "type": "string",
+                "enum": ["low", "medium", "high"],
+            },
+            "verification": {
+                "type": "string",
+                "enum": ["verified", "partially_verified", "unverified"],
+            },
+            "follow_ups": {
+                "type": "array",
+                "items": {
+                    "type": "object",
+                    "properties": {
+                        "label": { "type": "string" },
+                        "prompt": { "type": "string" },
+                    },
+                    "required": ["label", "prompt"],
+                    "additionalProperties": false,
+                },
+            },
+        },
+        "required": ["confidence", "verification", "follow_ups"],
+        "additionalProperties": false,
+    })
+}
+
+fn parse_assessment(text: &str) -> Option<CompletionAssessment> {
+    let mut assessment: CompletionAssessment = serde_json::from_str(text.trim()).ok()?;
+    assessment
+        .follow_ups
+        .retain(|f| !f.label.trim().is_empty() && !f.prompt.trim().is_empty());
+    assessment.follow_ups.truncate(MAX_FOLLOW_UPS);
+    Some(assessment)
+}
+
+pub(super) async fn collect_final_message(
+    turn_context: &TurnContext,
+    prompt: &Prompt,
+) -> CodexResult<Option<String>> {
+    let mut stream = turn_context.client.clone().stream(prompt).await?;
+    let mut items = Vec::new();
+    loop {
+        let Some(event) = stream.next().await else {
+            return Err(CodexErr::Stream(
+                "stream closed before response.completed".into(),
+                None,
+            ));
+        };
+        match event {
+            Ok(ResponseEvent::OutputItemDone(item)) => items.push(item),
+            Ok(ResponseEvent::Completed { .. }) => {
+                return Ok(get_last_assistant_message_from_turn(&items));
+            }
+            Ok(_) => continue,
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::protocol::CompletionConfidence;
+    use crate::protocol::FollowUpSuggestion;
+    use crate::protocol::VerificationStatus;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn parses_assessment_and_drops_empty_follow_ups() {
+        let text = r#"{
+            "confidence": "medium",
+            "verification": "partially_verified",
+            "follow_ups": [
+                {"label": "Run tests", "prompt": "Run the full test suite."},
+                {"label": "", "prompt": "ignored"},
+                {"label": "Docs", "prompt": "Document the change."},
+                {"label": "Bench", "prompt": "Benchmark it."},
+                {"label": "Extra", "prompt": "One too many."}
+            ]
+        }"#;
+        let assessment = parse_assessment(text).expect("valid assessment");
+        assert_eq!(assessment.confidence, CompletionConfidence::Medium);
+        assert_eq!(
+            assessment.verification,
+            VerificationStatus::PartiallyVerified
+        );
+        assert_eq!(
+            assessment.follow_ups.first(),
+            Some(&FollowUpSuggestion {
+                label: "Run tests".to_string(),
+                prompt: "Run the full test suite.".to_string(),
+            })
+        );
+        assert_eq!(assessment.follow_ups.len(), MAX_FOLLOW_UPS);
+    }
+
+    #[test]
+    fn rejects_non_json_output() {
+        assert_eq!(parse_assessment("Looks good to me!"), None);
+    }
+}
@@ -0,0 +1,243 @@
+//! Checks the final answer of a task against the `final_output_json_schema`
+//! of its `Op::UserTurn`, and asks the model once to repair an answer that
+//! does not match.
+
+use super::Session;
+use super::TurnContext;
+use super::completion::collect_final_message;
+use crate::Prompt;
+use codex_protocol::models::ContentItem;
+use codex_protocol::models::ResponseItem;
+use serde_json::Value;
+use tracing::warn;
+
+/// The final answer as JSON text matching `schema`, or why it does not
+/// match even after a repair request. The repair request and its answer
+/// are not recorded in the conversation history.
+pub(super) async fn enforce_output_schema(
+    sess: &Session,
+    turn_context: &TurnContext,
+    schema: &Value,
+    message: Option<&str>,
+) -> Result<String, String> {
+    let errors = match check_final_output(schema, message) {
+        Ok(text) => return Ok(text),
+        Err(errors) => errors,
+    };
+    warn!("final answer does not match the output schema: {errors:?}");
+
+    let request = ResponseItem::Message {
+        id: None,
+        role: "user".to_string(),
+        content: vec![ContentItem::InputText {
+            text: repair_prompt(&errors),
+        }],
+    };
+    let prompt = Prompt {
+        input: sess.turn_input_with_history(vec![request]).await,
+        output_schema: Some(schema.clone()),
+        ..Default::default()
+    };
+    let repaired = match collect_final_message(turn_context, &prompt).await {
+        Ok(repaired) => repaired,
+        Err(e) => {
+            warn!("output schema repair request failed: {e}");
+            return Err(errors.join("; "));
+        }
+    };
+    check_final_output(schema, repaired.as_deref()).map_err(|errors| errors.join("; "))
+}
+
+fn repair_prompt(errors: &[String]) -> String {
+    let errors = errors
+        .iter()
+        .map(|error| format!("- {error}"))
+        .collect::<Vec<_>>()
+        .join("\n");
+    format!(
+        "Your final answer does not match the required JSON schema:\n{errors}\n\nReply with only the corrected JSON."
+    )
+}
+
+/// The JSON text of `message` when it matches `schema`, otherwise what is
+/// wrong with it. A Markdown code fence around the JSON is dropped.
+fn check_final_output(schema: &Value, message: Option<&str>) -> Result<String, Vec<String>> {
+    let Some(message) = message else {
+        return Err(vec!["there is no final answer".to_string()]);
+    };
+    let text = strip_code_fence(message.trim());
+    let value: Value =
+        serde_json::from_str(text).map_err(|e| vec![format!("the answer is not JSON: {e}")])?;
+    let mut errors = Vec::new();
+    validate(schema, &value, "", &mut errors);
+    if errors.is_empty() {
+        Ok(text.to_string())
+    } else {
+        Err(errors)
+    }
+}
+
+fn strip_code_fence(text: &str) -> &str {
+    let Some(body) = text
+        .strip_prefix("```")
+        .and_then(|body| body.strip_suffix("```"))
+    else {
+        return text;
+    };
+    // Drop the language tag on the opening line.
+    body.split_once('\n').map_or(body, |(_, rest)| rest).trim()
+}
+
+/// Checks `value` against the parts of JSON Schema that structured outputs
+/// use: `type`, `enum`, `const`, `anyOf`, `properties`, `required`,
+/// `additionalProperties` and `items`. Other keywords, such as `$ref`, are
+/// not checked. Errors name the offending value by its JSON pointer.
+fn validate(schema: &Value, value: &Value, path: &str, errors: &mut Vec<String>) {
+    // `true` and `{}` accept anything.
+    let Some(schema) = schema.as_object() else {
+        return;
+    };
+    let at = if path.is_empty() { "/" } else { path };
+
+    let types: Vec<&str> = match schema.get("type") {
+        Some(Value::String(ty)) => vec![ty.as_str()],
+        Some(Value::Array(types)) => types.iter().filter_map(Value::as_str).collect(),
+        _ => Vec::new(),
+    };
+    if !types.is_empty() && !types.iter().any(|ty| has_type(value, ty)) {
+        errors.push(format!("{at}: expected {}", types.join(" or ")));
+        return;
+    }
+    if let Some(Value::Array(allowed)) = schema.get("enum")
+        && !allowed.contains(value)
+    {
+        errors.push(format!(
+            "{at}: must be one of {}",
+            Value::from(allowed.clone())
+        ));
+    }
+    if let Some(expected) = schema.get("const")
+        && expected != value
+    {
+        errors.push(format!("{at}: must be {expected}"));
+    }
+    if let Some(Value::Array(options)) = schema.get("anyOf")
+        && !options.iter().any(|option| {
+            let mut option_errors = Vec::new();
+            validate(option, value, path, &mut option_errors);
+            option_errors.is_empty()
+        })
+    {
+        errors.push(format!("{at}: matches none of the allowed schemas"));
+    }
+
+    match value {
+        Value::Object(object) => {
+            if let Some(Value::Array(required)) = schema.get("required") {
+                for name in required.iter().filter_map(Value::as_str) {
+                    if !object.contains_key(name) {
+                        errors.push(format!("{at}: missing required property `{name}`"));
+                    }
+                }
+            }
+            let properties = schema.get("properties").and_then(Value::as_object);
+            for (name, child) in object {
+                let child_path = format!("{path}/{name}");
+                match properties.and_then(|properties| properties.get(name)) {
+                    Some(child_schema) => validate(child_schema, child, &child_path, errors),
+                    None => match schema.get("additionalProperties") {
+                        Some(Value::Bool(false)) => {
+                            errors.push(format!("{child_path}: unexpected property"));
+                        }
+                        Some(additional) => validate(additional, child, &child_path, errors),
+                        None => {}
+                    },
+                }
+            }
+        }
+        Value::Array(items) => {
+            if let Some(item_schema) = schema.get("items") {
+                for (index, item) in items.iter().enumerate() {
+                    validate(item_schema, item, &format!("{path}/{index}"), errors);
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+fn has_type(value: &Value, ty: &str) -> bool {
+    match ty {
+        "string" => value.is_string(),
+        "number" => value.is_number(),
+        "integer" => {
+            value.is_i64() || value.is_u64() || value.as_f64().is_some_and(|n| n.fract() == 0.0)
+        }
+        "boolean" => value.is_boolean(),
+        "object" => value.is_object(),
+        "array" => value.is_array(),
+        "null" => value.is_null(),
+        _ => true,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use serde_json::json;
+
+    fn schema() -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "status": { "type": "string", "enum": ["ok", "failed"] },
+                "count": { "type": "integer" },
+                "files": { "type": "array", "items": { "type": "string" } },
+                "note": { "anyOf": [{ "type": "string" }, { "type": "null" }] },
+            },
+            "required": ["status", "count", "files", "note"],
+            "additionalProperties": false,
+        })
+    }
+
+    #[test]
+    fn accepts_a_matching_answer_inside_a_code_fence() {
+        let message = "```json\n{\"status\": \"ok\", \"count\": 2, \"files\": [\"a.rs\"], \"note\": null}\n```";
+        assert_eq!(
+            check_final_output(&schema(), Some(message)),
+            Ok(r#"{"status": "ok", "count": 2, "files": ["a.rs"], "note": null}"#.to_string())
+        );
+    }
+
+    #[test]
+    fn reports_every_mismatch_by_path() {
+        let message = r#"{"status": "maybe", "count": 1.5, "files": ["a.rs", 3], "extra": true}"#;
+        // Property order depends on serde_json's `preserve_order` feature.
+        let mut errors = check_final_output(&schema(), Some(message)).unwrap_err();
+        errors.sort();
+        assert_eq!(
+            errors,
+            vec![
+                r#"/: missing required property `note`"#.to_string(),
+                r#"/count: expected integer"#.to_string(),
+                r#"/extra: unexpected property"#.to_string(),
+                r#"/files/1: expected string"#.to_string(),
+                r#"/status: must be one of ["ok","failed"]"#.to_string(),
+            ]
+        );
+    }
+
+    #[test]
+    fn rejects_a_missing_or_non_json_answer() {
+        assert_eq!(
+            check_final_output(&schema(), None),
+            Err(vec!["there is no final answer".to_string()])
+        );
+        let errors = check_final_output(&schema(), Some("All done!")).unwrap_err();
+        assert!(
+            errors[0].starts_with("the answer is not JSON"),
+            "{errors:?}"
+        );
+    }
+}
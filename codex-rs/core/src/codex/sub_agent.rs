@@ -0,0 +1,222 @@
+//! The `delegate` tool: runs a subtask in a child agent that has its own
+//! conversation, a subset of the parent's tools and a turn budget, and
+//! returns the child's final message as the tool output.
+
+use std::sync::atomic::AtomicU64;
+use std::sync::atomic::Ordering;
+
+use codex_protocol::models::ResponseInputItem;
+use codex_protocol::models::ResponseItem;
+use serde::Deserialize;
+use tokio_util::sync::CancellationToken;
+
+use super::ProcessedResponseItem;
+use super::Session;
+use super::TurnContext;
+use super::TurnRunResult;
+use super::get_last_assistant_message_from_turn;
+use super::history_items_for;
+use super::run_turn;
+use crate::function_tool::FunctionCallError;
+use crate::protocol::Event;
+use crate::protocol::EventMsg;
+use crate::protocol::InputItem;
+use crate::protocol::SUB_AGENT_ID_SEPARATOR;
+use crate::protocol::SubAgentBeginEvent;
+use crate::protocol::SubAgentEndEvent;
+use crate::turn_diff_tracker::TurnDiffTracker;
+
+/// Turn budget of a child when the model does not set `max_turns`.
+pub(crate) const DEFAULT_MAX_TURNS: u32 = 10;
+
+/// Upper bound on the `max_turns` the model may ask for.
+pub(crate) const MAX_TURNS_LIMIT: u32 = 50;
+
+/// Name of the tool; a child is never offered it, so children cannot nest.
+const DELEGATE_TOOL_NAME: &str = "delegate";
+
+#[derive(Debug, Deserialize)]
+struct DelegateToolArgs {
+    prompt: String,
+    #[serde(default)]
+    tools: Option<Vec<String>>,
+    #[serde(default)]
+    max_turns: Option<u32>,
+}
+
+/// Runs the children started by the `delegate` calls of a session.
+///
+/// Each child gets an id namespaced under the submission that started it
+/// (`<sub_id>/delegate-<n>`), which is the `id` of every event it sends, so
+/// clients can tell its progress apart from the parent's.
+#[derive(Debug, Default)]
+pub(crate) struct SubAgentManager {
+    next_id: AtomicU64,
+}
+
+impl SubAgentManager {
+    fn next_agent_id(&self, sub_id: &str) -> String {
+        let n = self.next_id.fetch_add(1, Ordering::Relaxed) + 1;
+        format!("{sub_id}{SUB_AGENT_ID_SEPARATOR}delegate-{n}")
+    }
+
+    /// Handle a `delegate` call: run the child to completion and return its
+    /// final message. Edits the child makes are tracked in the parent's
+    /// `turn_diff_tracker`.
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) async fn run(
+        &self,
+        sess: &Session,
+        parent: &TurnContext,
+        turn_diff_tracker: &mut TurnDiffTracker,
+        sub_id: &str,
+        call_id: String,
+        arguments: String,
+        cancellation_token: &CancellationToken,
+    ) -> Result<String, FunctionCallError> {
+        let args: DelegateToolArgs = serde_json::from_str(&arguments).map_err(|e| {
+            FunctionCallError::RespondToModel(format!("failed to parse function arguments: {e:?}"))
+        })?;
+        if args.prompt.trim().is_empty() {
+            return Err(FunctionCallError::RespondToModel(
+                "prompt must not be empty".to_string(),
+            ));
+        }
+        let max_turns = args
+            .max_turns
+            .unwrap_or(DEFAULT_MAX_TURNS)
+            .clamp(1, MAX_TURNS_LIMIT);
+        let tools = args.tools.map(|tools| {
+            tools
+                .into_iter()
+                .filter(|name| name != DELEGATE_TOOL_NAME)
+                .collect::<Vec<_>>()
+        });
+
+        let agent_id = self.next_agent_id(sub_id);
+        let turn_context = child_turn_context(parent, tools.clone());
+        sess.send_event(Event {
+            id: sub_id.to_string(),
+            msg: EventMsg::SubAgentBegin(SubAgentBeginEvent {
+                call_id: call_id.clone(),
+                agent_id: agent_id.clone(),
+                prompt: args.prompt.clone(),
+                tools,
+                max_turns,
+            }),
+        })
+        .await;
+
+        // Like review threads, the child sees a fresh conversation seeded
+        // with the environment context rather than the parent's history.
+        let mut history = sess.build_initial_context(&turn_context);
+        history.push(ResponseInputItem::from(vec![InputItem::Text { text: args.prompt }]).into());
+
+        let mut turns = 0;
+        let mut last_agent_message = None;
+        let mut error = None;
+        loop {
+            if turns == max_turns {
+                break;
+            }
+            turns += 1;
+            let TurnRunResult {
+                processed_items, ..
+            } = match run_turn(
+                sess,
+                &turn_context,
+                turn_diff_tracker,
+                agent_id.clone(),
+                history.clone(),
+                cancellation_token,
+            )
+            .await
+            {
+                Ok(result) => result,
+                Err(e) => {
+                    error = Some(e.to_string());
+                    break;
+                }
+            };
+
+            let mut made_tool_calls = false;
+            let mut turn_items = Vec::<ResponseItem>::new();
+            for ProcessedResponseItem { item, response } in processed_items {
+                made_tool_calls |= response.is_some();
+                turn_items.extend(history_items_for(item, response.as_ref()));
+            }
+            history.extend(turn_items.iter().cloned());
+            if cancellation_token.is_cancelled() {
+                error = Some("interrupted".to_string());
+                break;
+            }
+            if !made_tool_calls {
+                last_agent_message = get_last_assistant_message_from_turn(&turn_items);
+                break;
+            }
+        }
+
+        let budget_exhausted = error.is_none() && last_agent_message.is_none();
+        sess.send_event(Event {
+            id: sub_id.to_string(),
+            msg: EventMsg::SubAgentEnd(SubAgentEndEvent {
+                call_id,
+                agent_id,
+                turns,
+                budget_exhausted,
+                last_agent_message: last_agent_message.clone(),
+            }),
+        })
+        .await;
+
+        match (last_agent_message, error) {
+            (Some(message), _) => Ok(message),
+            (None, Some(error)) => Err(FunctionCallError::RespondToModel(format!(
+                "the sub-agent failed after {turns} turn(s): {error}"
+            ))),
+            (None, None) => Err(FunctionCallError::RespondToModel(format!(
+                "the sub-agent used all {max_turns} of its turns without finishing; delegate a smaller subtask or raise max_turns"
+            ))),
+        }
+    }
+}
+
+/// The parent's turn context with the child's tool subset. The child is not
+/// offered `delegate`, so children cannot nest, nor `update_plan`, which
+/// would replace the parent's plan.
+fn child_turn_context(parent: &TurnContext, tools: Option<Vec<String>>) -> TurnContext {
+    let mut tools_config = parent.tools_config.clone();
+    tools_config.include_delegate_tool = false;
+    tools_config.plan_tool = false;
+    tools_config.allowed_tools = tools;
+    TurnContext {
+        client: parent.client.clone(),
+        cwd: parent.cwd.clone(),
+        base_instructions: parent.base_instructions.clone(),
+        user_instructions: parent.user_instructions.clone(),
+        approval_policy: parent.approval_policy,
+        sandbox_policy: parent.sandbox_policy.clone(),
+        shell_environment_policy: parent.shell_environment_policy.clone(),
+        resource_limits: parent.resource_limits,
+        sandbox_extras: parent.sandbox_extras.clone(),
+        tools_config,
+        is_review_mode: false,
+        final_output_json_schema: None,
+        dry_run: parent.dry_run,
+        exec: parent.exec.clone(),
+        task_budget: parent.task_budget,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn agent_ids_are_namespaced_under_the_submission() {
+        let manager = SubAgentManager::default();
+        assert_eq!(manager.next_agent_id("3"), "3/delegate-1");
+        assert_eq!(manager.next_agent_id("4"), "4/delegate-2");
+    }
+}
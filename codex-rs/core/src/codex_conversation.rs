@@ -2,17 +2,28 @@ use crate::codex::Codex;
 use crate::error::Result as CodexResult;
 use crate::protocol::Event;
 use crate::protocol::Op;
+use crate::protocol::SessionConfiguredEvent;
 use crate::protocol::Submission;
 
 pub struct CodexConversation {
     codex: Codex,
+    session_configured: SessionConfiguredEvent,
 }
 
 /// Conduit for the bidirectional stream of messages that compose a conversation
 /// in Codex.
 impl CodexConversation {
-    pub(crate) fn new(codex: Codex) -> Self {
-        Self { codex }
+    pub(crate) fn new(codex: Codex, session_configured: SessionConfiguredEvent) -> Self {
+        Self {
+            codex,
+            session_configured,
+        }
+    }
+
+    /// The `SessionConfigured` event the conversation started with, without
+    /// its `initial_messages`.
+    pub fn session_configured(&self) -> &SessionConfiguredEvent {
+        &self.session_configured
     }
 
     pub async fn submit(&self, op: Op) -> CodexResult<String> {
@@ -0,0 +1,223 @@
+//! `.codexignore`: paths that must never enter the model's context.
+//!
+//! The files use `.gitignore` syntax and are read from the repository root
+//! down to the working directory (or from the working directory alone
+//! outside a repository); a pattern applies relative to the directory of the
+//! file that holds it, and deeper files take precedence. Matching paths are
+//! refused to read-only shell commands (`cat`, `rg`, `ls`, ...) that name
+//! them and to `view_image`, dropped from attached images, and skipped by
+//! file search, which backs `@` mention completion.
+
+use std::path::Path;
+use std::path::PathBuf;
+
+use ignore::Match;
+use ignore::gitignore::Gitignore;
+use ignore::gitignore::GitignoreBuilder;
+use tracing::warn;
+
+use crate::command_safety::command_pattern::bash_lc_commands;
+use crate::git_info::get_git_repo_root;
+use crate::parse_command::ParsedCommand;
+use crate::parse_command::parse_command;
+use crate::path_utils::display_path;
+use crate::path_utils::normalize_path;
+use crate::path_utils::path_starts_with;
+use crate::path_utils::resolve_path;
+use crate::protocol::InputItem;
+
+pub const CODEX_IGNORE_FILENAME: &str = ".codexignore";
+
+/// The `.codexignore` rules in effect for a working directory.
+#[derive(Debug, Default)]
+pub struct CodexIgnore {
+    /// One matcher per `.codexignore`, deepest directory first.
+    matchers: Vec<Gitignore>,
+}
+
+impl CodexIgnore {
+    pub fn load(cwd: &Path) -> Self {
+        let cwd = normalize_path(cwd);
+        let root = get_git_repo_root(&cwd).unwrap_or_else(|| cwd.clone());
+        let mut matchers = Vec::new();
+        for dir in cwd.ancestors() {
+            let file = dir.join(CODEX_IGNORE_FILENAME);
+            if file.is_file() {
+                let mut builder = GitignoreBuilder::new(dir);
+                if let Some(err) = builder.add(&file) {
+                    warn!("failed to read {}: {err}", file.display());
+                }
+                match builder.build() {
+                    Ok(matcher) => matchers.push(matcher),
+                    Err(err) => warn!("invalid {}: {err}", file.display()),
+                }
+            }
+            if dir == root {
+                break;
+            }
+        }
+        Self { matchers }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.matchers.is_empty()
+    }
+
+    /// Whether `path` (absolute) or any directory containing it is ignored.
+    pub fn is_ignored(&self, path: &Path) -> bool {
+        let path = normalize_path(path);
+        let is_dir = path.is_dir();
+        for matcher in &self.matchers {
+            if !path_starts_with(&path, matcher.path()) {
+                continue;
+            }
+            match matcher.matched_path_or_any_parents(&path, is_dir) {
+                Match::Ignore(_) => return true,
+                Match::Whitelist(_) => return false,
+                Match::None => {}
+            }
+        }
+        false
+    }
+
+    /// The first ignored path named by a read-only part of `command`, e.g.
+    /// `secrets/prod.env` in `cat secrets/prod.env | head`. Every argument
+    /// that is not a flag counts as a path, so search patterns never match.
+    pub(crate) fn ignored_read_operand(&self, command: &[String], cwd: &Path) -> Option<PathBuf> {
+        if self.is_empty() {
+            return None;
+        }
+        let commands = bash_lc_commands(command).unwrap_or_else(|| vec![command.to_vec()]);
+        commands
+            .iter()
+            .filter(|cmd| is_read_only(cmd))
+            .flat_map(|cmd| cmd.iter().skip(1))
+            .filter(|arg| !arg.starts_with('-'))
+            .map(|arg| resolve_path(cwd, Path::new(arg)))
+            .find(|path| self.is_ignored(path))
+    }
+
+    /// Replaces attached local images that are ignored with a note saying
+    /// they were left out.
+    pub(crate) fn filter_input(&self, items: Vec<InputItem>) -> Vec<InputItem> {
+        if self.is_empty() {
+            return items;
+        }
+        items
+            .into_iter()
+            .map(|item| match item {
+                InputItem::LocalImage { path } if self.is_ignored(&path) => InputItem::Text {
+                    text: format!(
+                        "[image {} omitted: excluded by {CODEX_IGNORE_FILENAME}]",
+                        display_path(&path)
+                    ),
+                },
+                other => other,
+            })
+            .collect()
+    }
+}
+
+fn is_read_only(command: &[String]) -> bool {
+    let parsed = parse_command(command);
+    !parsed.is_empty()
+        && parsed
+            .iter()
+            .all(|cmd| !matches!(cmd, ParsedCommand::Unknown { .. }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use tempfile::TempDir;
+
+    fn argv(words: &[&str]) -> Vec<String> {
+        words.iter().map(|s| (*s).to_string()).collect()
+    }
+
+    fn repo() -> TempDir {
+        let repo = TempDir::new().expect("tempdir");
+        std::fs::create_dir(repo.path().join(".git")).expect("create .git");
+        std::fs::write(
+            repo.path().join(CODEX_IGNORE_FILENAME),
+            "secrets/\n*.pem\ngenerated/\n",
+        )
+        .expect("write .codexignore");
+        std::fs::create_dir(repo.path().join("generated")).expect("create generated");
+        repo
+    }
+
+    #[test]
+    fn patterns_apply_to_paths_and_their_contents() {
+        let repo = repo();
+        let ignore = CodexIgnore::load(repo.path());
+        assert!(ignore.is_ignored(&repo.path().join("secrets/prod.env")));
+        assert!(ignore.is_ignored(&repo.path().join("certs/server.pem")));
+        assert!(!ignore.is_ignored(&repo.path().join("src/main.rs")));
+    }
+
+    #[test]
+    fn nested_files_are_read_up_to_the_repository_root() {
+        let repo = repo();
+        let app = repo.path().join("app");
+        std::fs::create_dir(&app).expect("create app");
+        std::fs::write(app.join(CODEX_IGNORE_FILENAME), "dist/\n!keep.pem\n")
+            .expect("write nested .codexignore");
+
+        let ignore = CodexIgnore::load(&app);
+        assert!(ignore.is_ignored(&app.join("dist/bundle.js")));
+        assert!(ignore.is_ignored(&repo.path().join("secrets/key")));
+        // The deeper file re-includes what the root file excludes.
+        assert!(!ignore.is_ignored(&app.join("keep.pem")));
+    }
+
+    #[test]
+    fn read_only_commands_naming_ignored_paths_are_caught() {
+        let repo = repo();
+        let ignore = CodexIgnore::load(repo.path());
+        let cwd = repo.path();
+
+        assert_eq!(
+            ignore.ignored_read_operand(&argv(&["cat", "secrets/prod.env"]), cwd),
+            Some(cwd.join("secrets/prod.env"))
+        );
+        assert_eq!(
+            ignore.ignored_read_operand(&argv(&["bash", "-lc", "rg -n token generated"]), cwd),
+            Some(cwd.join("generated"))
+        );
+        assert_eq!(
+            ignore.ignored_read_operand(&argv(&["bash", "-lc", "cat src/lib.rs | head"]), cwd),
+            None
+        );
+        // Writes are not read tools.
+        assert_eq!(
+            ignore.ignored_read_operand(&argv(&["rm", "-rf", "generated"]), cwd),
+            None
+        );
+    }
+
+    #[test]
+    fn ignored_images_are_replaced_with_a_note() {
+        let repo = repo();
+        let ignore = CodexIgnore::load(repo.path());
+        let items = ignore.filter_input(vec![
+            InputItem::LocalImage {
+                path: repo.path().join("secrets/diagram.png"),
+            },
+            InputItem::LocalImage {
+                path: repo.path().join("docs/diagram.png"),
+            },
+        ]);
+        assert!(matches!(&items[0], InputItem::Text { text } if text.contains("omitted")));
+        assert!(matches!(&items[1], InputItem::LocalImage { .. }));
+    }
+
+    #[test]
+    fn no_file_means_nothing_is_ignored() {
+        let dir = TempDir::new().expect("tempdir");
+        let ignore = CodexIgnore::load(dir.path());
+        assert!(ignore.is_empty());
+        assert!(!ignore.is_ignored(&dir.path().join("anything")));
+    }
+}
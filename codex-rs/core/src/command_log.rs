@@ -0,0 +1,141 @@
+//! Provenance stamping for executed commands, enabled by the
+//! `command_provenance` config option.
+//!
+//! Every command gets [`CODEX_SESSION_ID_ENV_VAR`] and [`CODEX_CALL_ID_ENV_VAR`]
+//! in its environment and is appended to `.codex/command_log` in the project,
+//! the root of the git repository containing the session's cwd (or the cwd
+//! itself outside a repository). The log has one JSON object per line:
+//!
+//! ````text
+//! {"session_id":"<uuid>","call_id":"<id>","ts":<unix_seconds>,"cwd":"<path>","command":["<arg>",...]}
+//! ````
+//!
+//! Like the message history, each line is written with a single `write(2)`
+//! on a file opened with `O_APPEND`, so concurrent sessions do not interleave.
+
+use std::collections::HashMap;
+use std::fs::OpenOptions;
+use std::io::Result;
+use std::io::Write;
+use std::path::Path;
+use std::path::PathBuf;
+
+use codex_protocol::mcp_protocol::ConversationId;
+use serde::Deserialize;
+use serde::Serialize;
+
+use crate::git_info::get_git_repo_root;
+
+/// Set to the id of the session that ran the command.
+pub const CODEX_SESSION_ID_ENV_VAR: &str = "CODEX_SESSION_ID";
+
+/// Set to the id of the tool call that ran the command.
+pub const CODEX_CALL_ID_ENV_VAR: &str = "CODEX_CALL_ID";
+
+/// Path of the log, relative to the project root.
+const COMMAND_LOG_PATH: &str = ".codex/command_log";
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct CommandLogEntry {
+    pub session_id: String,
+    pub call_id: String,
+    pub ts: u64,
+    pub cwd: PathBuf,
+    pub command: Vec<String>,
+}
+
+/// Adds the session and call ids to a command's environment.
+pub(crate) fn stamp_env(
+    env: &mut HashMap<String, String>,
+    conversation_id: &ConversationId,
+    call_id: &str,
+) {
+    env.insert(
+        CODEX_SESSION_ID_ENV_VAR.to_string(),
+        conversation_id.to_string(),
+    );
+    env.insert(CODEX_CALL_ID_ENV_VAR.to_string(), call_id.to_string());
+}
+
+/// The command log for the project containing `session_cwd`.
+fn command_log_path(session_cwd: &Path) -> PathBuf {
+    get_git_repo_root(session_cwd)
+        .unwrap_or_else(|| session_cwd.to_path_buf())
+        .join(COMMAND_LOG_PATH)
+}
+
+/// Appends `command` to the project's command log.
+pub(crate) async fn append_command(
+    session_cwd: &Path,
+    conversation_id: &ConversationId,
+    call_id: &str,
+    cwd: &Path,
+    command: &[String],
+) -> Result<()> {
+    let ts = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_err(|e| std::io::Error::other(format!("system clock before Unix epoch: {e}")))?
+        .as_secs();
+    let entry = CommandLogEntry {
+        session_id: conversation_id.to_string(),
+        call_id: call_id.to_string(),
+        ts,
+        cwd: cwd.to_path_buf(),
+        command: command.to_vec(),
+    };
+    let mut line = serde_json::to_string(&entry).map_err(|e| {
+        std::io::Error::other(format!("failed to serialise command log entry: {e}"))
+    })?;
+    line.push('\n');
+
+    let path = command_log_path(session_cwd);
+    tokio::task::spawn_blocking(move || -> Result<()> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let mut file = OpenOptions::new().append(true).create(true).open(&path)?;
+        file.write_all(line.as_bytes())
+    })
+    .await?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[tokio::test]
+    async fn appends_one_json_line_per_command_at_the_repo_root() {
+        let repo = tempfile::tempdir().expect("tempdir");
+        std::fs::create_dir(repo.path().join(".git")).expect("create .git");
+        let nested = repo.path().join("src");
+        std::fs::create_dir(&nested).expect("create src");
+        let conversation_id = ConversationId::default();
+
+        for call_id in ["call-1", "call-2"] {
+            append_command(
+                &nested,
+                &conversation_id,
+                call_id,
+                &nested,
+                &["ls".to_string(), "-la".to_string()],
+            )
+            .await
+            .expect("append");
+        }
+
+        let log = std::fs::read_to_string(repo.path().join(COMMAND_LOG_PATH)).expect("read log");
+        let entries: Vec<CommandLogEntry> = log
+            .lines()
+            .map(|line| serde_json::from_str(line).expect("entry"))
+            .collect();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[1].call_id, "call-2");
+        assert_eq!(entries[1].session_id, conversation_id.to_string());
+        assert_eq!(entries[1].cwd, nested);
+        assert_eq!(
+            entries[1].command,
+            vec!["ls".to_string(), "-la".to_string()]
+        );
+    }
+}
@@ -0,0 +1,270 @@
+//! Approved command patterns such as `cargo test *`, so that approving a
+//! command can cover its near-identical variants instead of only the exact
+//! argv.
+//!
+//! A pattern is split into words like a shell command line. Each word must
+//! equal the corresponding argument, except for a bare `*`, which matches any
+//! number of arguments (including none).
+
+use std::collections::HashSet;
+
+use crate::bash::try_parse_bash;
+use crate::bash::try_parse_word_only_commands_sequence;
+use crate::is_safe_command::is_known_safe_command;
+
+/// Programs whose arguments are themselves a program, so a pattern derived
+/// from them would approve arbitrary commands.
+const SHELL_PROGRAMS: &[&str] = &[
+    "bash",
+    "sh",
+    "zsh",
+    "dash",
+    "fish",
+    "cmd",
+    "cmd.exe",
+    "powershell",
+    "powershell.exe",
+    "pwsh",
+    "env",
+    "sudo",
+    "xargs",
+];
+
+/// Commands the user approved, either exactly for this session or through a
+/// pattern from `approved_command_patterns`.
+#[derive(Debug, Default)]
+pub struct ApprovedCommands {
+    exact: HashSet<Vec<String>>,
+    patterns: Vec<String>,
+}
+
+impl ApprovedCommands {
+    pub fn new(patterns: Vec<String>) -> Self {
+        Self {
+            exact: HashSet::new(),
+            patterns,
+        }
+    }
+
+    pub fn insert(&mut self, command: Vec<String>) {
+        self.exact.insert(command);
+    }
+
+    pub fn add_pattern(&mut self, pattern: String) {
+        if !self.patterns.contains(&pattern) {
+            self.patterns.push(pattern);
+        }
+    }
+
+    /// The exactly approved commands, sorted.
+    pub fn exact_commands(&self) -> Vec<Vec<String>> {
+        let mut commands: Vec<Vec<String>> = self.exact.iter().cloned().collect();
+        commands.sort();
+        commands
+    }
+
+    pub fn patterns(&self) -> &[String] {
+        &self.patterns
+    }
+
+    /// Whether `command` was approved. A `bash -lc` script of plain commands
+    /// is approved when every command in it is known safe or matches a
+    /// pattern, so `cargo test *` does not approve `cargo test; rm -rf ~`.
+    pub fn contains(&self, command: &[String]) -> bool {
+        if self.exact.contains(command) {
+            return true;
+        }
+        if self.patterns.is_empty() {
+            return false;
+        }
+        match bash_lc_commands(command) {
+            Some(commands) => {
+                !commands.is_empty()
+                    && commands
+                        .iter()
+                        .all(|cmd| is_known_safe_command(cmd) || self.matches_pattern(cmd))
+            }
+            None => self.matches_pattern(command),
+        }
+    }
+
+    fn matches_pattern(&self, command: &[String]) -> bool {
+        self.patterns
+            .iter()
+            .any(|pattern| pattern_matches(pattern, command))
+    }
+}
+
+/// Whether `pattern` matches `command` word for word.
+pub fn pattern_matches(pattern: &str, command: &[String]) -> bool {
+    match shlex::split(pattern) {
+        Some(words) if !words.is_empty() => words_match(&words, command),
+        _ => false,
+    }
+}
+
+/// Whether `pattern` matches `command` or, for a `bash -lc` script, any one
+/// of the commands in it, so `cargo test *` matches `cd core && cargo test`.
+pub fn pattern_matches_any(pattern: &str, command: &[String]) -> bool {
+    match bash_lc_commands(command) {
+        Some(commands) => commands.iter().any(|cmd| pattern_matches(pattern, cmd)),
+        None => pattern_matches(pattern, command),
+    }
+}
+
+/// The pattern offered when the user approves `command` for every session:
+/// the program and its subcommand followed by `*`, e.g. `cargo test *` for
+/// `cargo test -p core`. `None` when no single pattern would be safe to offer,
+/// such as for shells or scripts that run several different programs, and for
+/// known-safe commands, which never need approval.
+pub fn pattern_for_command(command: &[String]) -> Option<String> {
+    if is_known_safe_command(command) {
+        return None;
+    }
+    let Some(commands) = bash_lc_commands(command) else {
+        return pattern_for_argv(command);
+    };
+    // Known-safe commands such as `cd` are approved anyway, so the pattern
+    // only needs to cover the rest of the script.
+    let mut patterns = commands
+        .iter()
+        .filter(|cmd| !is_known_safe_command(cmd))
+        .map(|cmd| pattern_for_argv(cmd));
+    let first = patterns.next()??;
+    patterns
+        .all(|pattern| pattern.as_deref() == Some(first.as_str()))
+        .then_some(first)
+}
+
+fn pattern_for_argv(command: &[String]) -> Option<String> {
+    let program = command.first()?;
+    let name = program.rsplit(['/', '\\']).next().unwrap_or(program);
+    if SHELL_PROGRAMS.contains(&name) {
+        return None;
+    }
+    let mut words = vec![shlex::try_quote(program).ok()?.into_owned()];
+    if let Some(subcommand) = command.get(1)
+        && !subcommand.starts_with('-')
+    {
+        words.push(shlex::try_quote(subcommand).ok()?.into_owned());
+    }
+    words.push("*".to_string());
+    Some(words.join(" "))
+}
+
+/// The commands in a `bash -lc` script, or `None` when `command` is not one.
+/// A script that is not a plain sequence of commands yields `Some(vec![])`
+/// so that it never matches a pattern by accident.
+pub(crate) fn bash_lc_commands(command: &[String]) -> Option<Vec<Vec<String>>> {
+    let [bash, flag, script] = command else {
+        return None;
+    };
+    if bash != "bash" || flag != "-lc" {
+        return None;
+    }
+    Some(
+        try_parse_bash(script)
+            .and_then(|tree| try_parse_word_only_commands_sequence(&tree, script))
+            .unwrap_or_default(),
+    )
+}
+
+fn words_match(pattern: &[String], command: &[String]) -> bool {
+    match pattern.split_first() {
+        None => command.is_empty(),
+        Some((word, rest)) if word == "*" => {
+            (0..=command.len()).any(|skip| words_match(rest, &command[skip..]))
+        }
+        Some((word, rest)) => command
+            .split_first()
+            .is_some_and(|(arg, args)| arg == word && words_match(rest, args)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn vec_str(args: &[&str]) -> Vec<String> {
+        args.iter().map(ToString::to_string).collect()
+    }
+
+    #[test]
+    fn star_matches_any_number_of_arguments() {
+        assert!(pattern_matches(
+            "cargo test *",
+            &vec_str(&["cargo", "test"])
+        ));
+        assert!(pattern_matches(
+            "cargo test *",
+            &vec_str(&["cargo", "test", "-p", "codex-core"])
+        ));
+        assert!(pattern_matches(
+            "npm run * --silent",
+            &vec_str(&["npm", "run", "lint", "--silent"])
+        ));
+        assert!(!pattern_matches(
+            "cargo test *",
+            &vec_str(&["cargo", "build"])
+        ));
+        assert!(!pattern_matches(
+            "cargo test",
+            &vec_str(&["cargo", "test", "-q"])
+        ));
+    }
+
+    #[test]
+    fn pattern_matches_any_command_in_a_script() {
+        assert!(pattern_matches_any(
+            "cargo test *",
+            &vec_str(&["bash", "-lc", "cd core && cargo test -q"])
+        ));
+        assert!(!pattern_matches_any(
+            "cargo test *",
+            &vec_str(&["bash", "-lc", "cargo build"])
+        ));
+    }
+
+    #[test]
+    fn bash_scripts_need_every_command_covered() {
+        let approved = ApprovedCommands::new(vec!["cargo test *".to_string()]);
+        assert!(approved.contains(&vec_str(&["bash", "-lc", "cargo test --all"])));
+        assert!(approved.contains(&vec_str(&["bash", "-lc", "cd core && cargo test"])));
+        assert!(!approved.contains(&vec_str(&["bash", "-lc", "cargo test; rm -rf ~"])));
+        assert!(!approved.contains(&vec_str(&["bash", "-lc", "cargo test $(rm -rf ~)"])));
+    }
+
+    #[test]
+    fn exact_approvals_still_match() {
+        let mut approved = ApprovedCommands::default();
+        approved.insert(vec_str(&["make", "lint"]));
+        assert!(approved.contains(&vec_str(&["make", "lint"])));
+        assert!(!approved.contains(&vec_str(&["make", "test"])));
+    }
+
+    #[test]
+    fn offered_pattern_keeps_the_subcommand() {
+        assert_eq!(
+            pattern_for_command(&vec_str(&["cargo", "test", "-p", "codex-core"])),
+            Some("cargo test *".to_string())
+        );
+        assert_eq!(
+            pattern_for_command(&vec_str(&["make", "-j8"])),
+            Some("make *".to_string())
+        );
+        assert_eq!(pattern_for_command(&vec_str(&["ls", "-la"])), None);
+        assert_eq!(
+            pattern_for_command(&vec_str(&["bash", "-lc", "cd core && cargo test -q"])),
+            Some("cargo test *".to_string())
+        );
+        assert_eq!(
+            pattern_for_command(&vec_str(&["bash", "-lc", "cargo fmt && cargo test"])),
+            None
+        );
+        assert_eq!(
+            pattern_for_command(&vec_str(&["/bin/sh", "-c", "make"])),
+            None
+        );
+    }
+}
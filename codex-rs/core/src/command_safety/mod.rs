@@ -1,3 +1,4 @@
+pub mod command_pattern;
 pub mod is_safe_command;
 #[cfg(target_os = "windows")]
 pub mod windows_safe_commands;
@@ -1,11 +1,26 @@
+use crate::automations::Automation;
+use crate::automations::AutomationToml;
 use crate::config_profile::ConfigProfile;
+use crate::config_types::BusyInput;
+use crate::config_types::ExecConfig;
 use crate::config_types::History;
 use crate::config_types::McpServerConfig;
+use crate::config_types::ModelAlias;
+use crate::config_types::ModelPricing;
 use crate::config_types::Notifications;
+use crate::config_types::OutputStreaming;
+use crate::config_types::PermissionDecision;
+use crate::config_types::QuietHours;
 use crate::config_types::ReasoningSummaryFormat;
+use crate::config_types::ResourceLimits;
+use crate::config_types::SandboxExtras;
+use crate::config_types::SandboxPreset;
+use crate::config_types::SandboxPresetToml;
 use crate::config_types::SandboxWorkspaceWrite;
 use crate::config_types::ShellEnvironmentPolicy;
 use crate::config_types::ShellEnvironmentPolicyToml;
+use crate::config_types::Storage;
+use crate::config_types::TaskBudget;
 use crate::config_types::Tui;
 use crate::config_types::UriBasedFileOpener;
 use crate::git_info::resolve_root_git_project_for_trust;
@@ -16,15 +31,18 @@ use crate::model_provider_info::ModelProviderInfo;
 use crate::model_provider_info::built_in_model_providers;
 use crate::openai_model_info::get_model_info;
 use crate::protocol::AskForApproval;
+use crate::protocol::HookKind;
 use crate::protocol::SandboxPolicy;
 use anyhow::Context;
 use codex_protocol::config_types::ReasoningEffort;
 use codex_protocol::config_types::ReasoningSummary;
 use codex_protocol::config_types::SandboxMode;
+use codex_protocol::config_types::StallAction;
 use codex_protocol::config_types::Verbosity;
 use codex_protocol::mcp_protocol::Tools;
 use codex_protocol::mcp_protocol::UserSavedConfig;
 use dirs::home_dir;
+use regex_lite::Regex;
 use serde::Deserialize;
 use std::collections::BTreeMap;
 use std::collections::HashMap;
@@ -46,6 +64,18 @@ pub const GPT_5_CODEX_MEDIUM_MODEL: &str = "gpt-5-codex";
 /// the context window.
 pub(crate) const PROJECT_DOC_MAX_BYTES: usize = 32 * 1024; // 32 KiB
 
+/// Number of identical consecutive failures after which a retried command is
+/// no longer executed.
+pub(crate) const REPEATED_COMMAND_FAILURE_LIMIT: u32 = 3;
+
+/// Number of consecutive turns that repeat earlier tool calls with the same
+/// results, or retry a call that keeps failing, before a task is considered
+/// stalled.
+pub(crate) const STALL_DETECTION_TURNS: u32 = 3;
+
+/// Days a deleted session stays in the trash before it is purged.
+pub(crate) const SESSION_TRASH_RETENTION_DAYS: u64 = 30;
+
 pub(crate) const CONFIG_TOML_FILE: &str = "config.toml";
 
 /// Application configuration loaded from disk and merged with overrides.
@@ -68,6 +98,18 @@ pub struct Config {
     /// Token usage threshold triggering auto-compaction of conversation history.
     pub model_auto_compact_token_limit: Option<i64>,
 
+    /// Share of the model's context window, in percent, whose use triggers
+    /// auto-compaction; applies alongside `model_auto_compact_token_limit`.
+    pub model_auto_compact_context_percent: Option<u8>,
+
+    /// Number of most recent user and assistant messages kept verbatim after
+    /// the summary when the history is compacted automatically.
+    pub auto_compact_preserve_messages: u32,
+
+    /// Prices used to estimate session cost, or `None` when unknown for the
+    /// model.
+    pub model_pricing: Option<ModelPricing>,
+
     /// Key into the model_providers map that specifies which provider to use.
     pub model_provider_id: String,
 
@@ -79,8 +121,23 @@ pub struct Config {
 
     pub sandbox_policy: SandboxPolicy,
 
+    /// Extra seatbelt rules and Landlock paths merged into the sandbox.
+    pub sandbox_extras: SandboxExtras,
+
     pub shell_environment_policy: ShellEnvironmentPolicy,
 
+    /// Memory, CPU and output limits applied to every spawned command.
+    pub resource_limits: ResourceLimits,
+
+    /// How live command output is batched before it is sent to the client.
+    pub output_streaming: OutputStreaming,
+
+    /// Default and per-command timeouts for `shell` tool calls.
+    pub exec: ExecConfig,
+
+    /// Limits on the work of a single task.
+    pub task_budget: TaskBudget,
+
     /// When `true`, `AgentReasoning` events emitted by the backend will be
     /// suppressed from the frontend output. This can reduce visual noise when
     /// users are only interested in the final agent responses.
@@ -122,6 +179,16 @@ pub struct Config {
     /// and turn completions when not focused.
     pub tui_notifications: Notifications,
 
+    /// Which events ring the terminal bell (or run `tui_sound_command`) when
+    /// the terminal is unfocused.
+    pub tui_bell: Notifications,
+
+    /// Command run in place of the bell, with the event type appended.
+    pub tui_sound_command: Option<Vec<String>>,
+
+    /// Local-time window during which the bell and sound command stay silent.
+    pub tui_quiet_hours: Option<QuietHours>,
+
     /// The directory that should be treated as the current working directory
     /// for the session. All relative paths inside the business-logic layer are
     /// resolved against this path.
@@ -133,9 +200,85 @@ pub struct Config {
     /// Combined provider map (defaults merged with user-defined overrides).
     pub model_providers: HashMap<String, ModelProviderInfo>,
 
+    /// Named sandbox presets from `[sandbox_presets]`, offered alongside the
+    /// built-in modes.
+    pub sandbox_presets: BTreeMap<String, SandboxPreset>,
+
+    /// Short names for models from `[model_aliases]`. `model` is already
+    /// resolved; aliases passed later (e.g. to `/model`) are looked up here.
+    pub model_aliases: HashMap<String, ModelAlias>,
+
     /// Maximum number of bytes to include from an AGENTS.md project doc file.
     pub project_doc_max_bytes: usize,
 
+    /// After a command has failed this many times in a row with identical
+    /// output, further retries are answered without running it. `0` disables
+    /// the check.
+    pub repeated_command_failure_limit: u32,
+
+    /// Most tool calls from one model response that may run at the same time.
+    /// Only calls that cannot interfere with each other (MCP tools marked
+    /// `readOnlyHint` and read-only commands) are run concurrently. `1` runs every call in turn.
+    pub max_parallel_tool_calls: usize,
+
+    /// Consecutive turns that repeat earlier tool calls with identical results,
+    /// or retry a call that failed with the same arguments, without changing
+    /// any files before the task is treated as stalled. `0` disables loop
+    /// detection.
+    pub stall_detection_turns: u32,
+
+    /// What to do once a task is detected to be stalled.
+    pub stall_action: StallAction,
+
+    /// What happens to user input submitted while a task is running.
+    pub busy_input: BusyInput,
+
+    /// Estimated session cost, in USD, at which the user is asked whether
+    /// to keep going.
+    pub max_session_cost_usd: Option<f64>,
+
+    /// When `true`, a finished task is followed by a structured-output request
+    /// asking the model to rate its confidence and suggest follow-ups.
+    pub task_completion_assessment: bool,
+
+    /// Days a deleted session is kept in the trash before it is purged.
+    pub session_trash_retention_days: u64,
+
+    /// Ask once per project before the agent first uses the network, an MCP
+    /// tool, or writes outside the session's starting directory.
+    pub permission_prompts: bool,
+
+    /// Command patterns, such as `cargo test *`, that are approved without
+    /// asking. A bare `*` word matches any number of arguments.
+    pub approved_command_patterns: Vec<String>,
+
+    /// Provision a per-session scratch directory, writable under every
+    /// sandbox policy, and expose it through the `scratch_path` tool.
+    pub scratch_dir: bool,
+
+    /// Keep the scratch directory when the session shuts down.
+    pub retain_scratch_dir: bool,
+
+    /// Tell the model the user's locale, timezone and local time at the
+    /// start of each turn.
+    pub locale_context: bool,
+
+    /// Create a `codex/<slug-of-prompt>` branch when a task starts in a clean
+    /// git repository, and check the previous branch out again if the task
+    /// is aborted.
+    pub git_auto_branch: bool,
+
+    /// Export `CODEX_SESSION_ID` and `CODEX_CALL_ID` to every command and
+    /// append it to the project's `.codex/command_log`.
+    pub command_provenance: bool,
+
+    /// Rules that react to protocol events (`[[automations]]`).
+    pub automations: Vec<Automation>,
+
+    /// Remembered answers to those prompts for the project containing `cwd`
+    /// (see [`permissions_project_root`]).
+    pub project_permissions: HashMap<String, PermissionDecision>,
+
     /// Directory containing all Codex state (defaults to `~/.codex` but can be
     /// overridden by the `CODEX_HOME` environment variable).
     pub codex_home: PathBuf,
@@ -143,6 +286,9 @@ pub struct Config {
     /// Settings that govern if and what will be written to `~/.codex/history.jsonl`.
     pub history: History,
 
+    /// Disk thresholds for session storage.
+    pub storage: Storage,
+
     /// Optional URI-based file opener. If set, citations to files in the model
     /// output will be hyperlinked using the specified URI scheme.
     pub file_opener: UriBasedFileOpener,
@@ -187,6 +333,14 @@ pub struct Config {
     /// Include the `view_image` tool that lets the agent attach a local image path to context.
     pub include_view_image_tool: bool,
 
+    /// Download a pinned ripgrep into `$CODEX_HOME/bin` for the agent's
+    /// commands when `rg` is not installed. Opt-in.
+    pub tools_download_ripgrep: bool,
+
+    /// Include the `delegate` tool that lets the agent hand a subtask to a
+    /// child agent.
+    pub tools_delegate: bool,
+
     /// The active profile name used to derive this `Config` (if any).
     pub active_profile: Option<String>,
 
@@ -313,7 +467,9 @@ pub fn write_global_mcp_servers(
         for (name, config) in servers {
             let mut entry = TomlTable::new();
             entry.set_implicit(false);
-            entry["command"] = toml_edit::value(config.command.clone());
+            if !config.command.is_empty() {
+                entry["command"] = toml_edit::value(config.command.clone());
+            }
 
             if !config.args.is_empty() {
                 let mut args = TomlArray::new();
@@ -336,6 +492,57 @@ pub fn write_global_mcp_servers(
                 entry["env"] = TomlItem::Table(env_table);
             }
 
+            if let Some(url) = &config.url {
+                entry["url"] = toml_edit::value(url.clone());
+            }
+
+            if let Some(headers) = &config.headers
+                && !headers.is_empty()
+            {
+                let mut headers_table = TomlTable::new();
+                headers_table.set_implicit(false);
+                let mut pairs: Vec<_> = headers.iter().collect();
+                pairs.sort_by(|(a, _), (b, _)| a.cmp(b));
+                for (key, value) in pairs {
+                    headers_table.insert(key, toml_edit::value(value.clone()));
+                }
+                entry["headers"] = TomlItem::Table(headers_table);
+            }
+
+            if let Some(var) = &config.bearer_token_env_var {
+                entry["bearer_token_env_var"] = toml_edit::value(var.clone());
+            }
+
+            if let Some(oauth) = &config.oauth {
+                let mut oauth_table = TomlTable::new();
+                oauth_table.set_implicit(false);
+                oauth_table.insert("client_id", toml_edit::value(oauth.client_id.clone()));
+                if !oauth.scopes.is_empty() {
+                    let mut scopes = TomlArray::new();
+                    for scope in &oauth.scopes {
+                        scopes.push(scope.clone());
+                    }
+                    oauth_table.insert("scopes", TomlItem::Value(scopes.into()));
+                }
+                if let Some(issuer) = &oauth.authorization_server {
+                    oauth_table.insert("authorization_server", toml_edit::value(issuer.clone()));
+                }
+                entry["oauth"] = TomlItem::Table(oauth_table);
+            }
+
+            for (key, globs) in [
+                ("include_tools", &config.include_tools),
+                ("exclude_tools", &config.exclude_tools),
+            ] {
+                if !globs.is_empty() {
+                    let mut array = TomlArray::new();
+                    for glob in globs {
+                        array.push(glob.clone());
+                    }
+                    entry[key] = TomlItem::Value(array.into());
+                }
+            }
+
             if let Some(timeout) = config.startup_timeout_sec {
                 entry["startup_timeout_sec"] = toml_edit::value(timeout.as_secs_f64());
             }
@@ -344,6 +551,31 @@ pub fn write_global_mcp_servers(
                 entry["tool_timeout_sec"] = toml_edit::value(timeout.as_secs_f64());
             }
 
+            if !config.tool_timeouts_ms.is_empty() {
+                let mut timeouts_table = TomlTable::new();
+                timeouts_table.set_implicit(false);
+                for (tool, ms) in &config.tool_timeouts_ms {
+                    timeouts_table.insert(
+                        tool,
+                        toml_edit::value(i64::try_from(*ms).unwrap_or(i64::MAX)),
+                    );
+                }
+                entry["tool_timeouts_ms"] = TomlItem::Table(timeouts_table);
+            }
+
+            if let Some(limit) = config.max_concurrent_calls {
+                entry["max_concurrent_calls"] =
+                    toml_edit::value(i64::try_from(limit.get()).unwrap_or(i64::MAX));
+            }
+
+            if config.lazy {
+                entry["lazy"] = toml_edit::value(true);
+            }
+
+            if let Some(timeout) = config.idle_timeout_sec {
+                entry["idle_timeout_sec"] = toml_edit::value(timeout.as_secs_f64());
+            }
+
             doc["mcp_servers"][name.as_str()] = TomlItem::Table(entry);
         }
     }
@@ -357,6 +589,17 @@ pub fn write_global_mcp_servers(
 }
 
 fn set_project_trusted_inner(doc: &mut DocumentMut, project_path: &Path) -> anyhow::Result<()> {
+    let proj_tbl = ensure_project_table(doc, project_path)?;
+    proj_tbl["trust_level"] = toml_edit::value("trusted");
+    Ok(())
+}
+
+/// Returns the explicit `[projects."<project_path>"]` table, creating it (and
+/// `[projects]`) as needed.
+fn ensure_project_table<'a>(
+    doc: &'a mut DocumentMut,
+    project_path: &Path,
+) -> anyhow::Result<&'a mut TomlTable> {
     // Ensure we render a human-friendly structure:
     //
     // [projects]
@@ -417,8 +660,14 @@ fn set_project_trusted_inner(doc: &mut DocumentMut, project_path: &Path) -> anyh
         return Err(anyhow::anyhow!("project table missing for {}", project_key));
     };
     proj_tbl.set_implicit(false);
-    proj_tbl["trust_level"] = toml_edit::value("trusted");
-    Ok(())
+    Ok(proj_tbl)
+}
+
+/// Project whose `[projects."<path>"]` entry records guided permission
+/// answers for `cwd`: the root of its git repository (the main worktree for
+/// linked worktrees), or `cwd` itself outside a repository.
+pub fn permissions_project_root(cwd: &Path) -> PathBuf {
+    resolve_root_git_project_for_trust(cwd).unwrap_or_else(|| cwd.to_path_buf())
 }
 
 /// Patch `CODEX_HOME/config.toml` project state.
@@ -447,6 +696,70 @@ pub fn set_project_trusted(codex_home: &Path, project_path: &Path) -> anyhow::Re
     Ok(())
 }
 
+/// Append `pattern` to `approved_command_patterns` in
+/// `CODEX_HOME/config.toml`, unless it is already there.
+pub fn add_approved_command_pattern(codex_home: &Path, pattern: &str) -> anyhow::Result<()> {
+    let config_path = codex_home.join(CONFIG_TOML_FILE);
+    let mut doc = match std::fs::read_to_string(config_path.clone()) {
+        Ok(s) => s.parse::<DocumentMut>()?,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => DocumentMut::new(),
+        Err(e) => return Err(e.into()),
+    };
+
+    if !doc
+        .get("approved_command_patterns")
+        .is_some_and(TomlItem::is_array)
+    {
+        doc["approved_command_patterns"] = toml_edit::value(toml_edit::Array::new());
+    }
+    let Some(patterns) = doc["approved_command_patterns"].as_array_mut() else {
+        return Err(anyhow::anyhow!("approved_command_patterns is not an array"));
+    };
+    if patterns
+        .iter()
+        .any(|existing| existing.as_str() == Some(pattern))
+    {
+        return Ok(());
+    }
+    patterns.push(pattern);
+
+    std::fs::create_dir_all(codex_home)?;
+    let tmp_file = NamedTempFile::new_in(codex_home)?;
+    std::fs::write(tmp_file.path(), doc.to_string())?;
+    tmp_file.persist(config_path)?;
+
+    Ok(())
+}
+
+/// Record the answer to a guided permission prompt for `project_path` in
+/// `CODEX_HOME/config.toml`.
+pub fn set_project_permission(
+    codex_home: &Path,
+    project_path: &Path,
+    permission: &str,
+    decision: PermissionDecision,
+) -> anyhow::Result<()> {
+    let config_path = codex_home.join(CONFIG_TOML_FILE);
+    let mut doc = match std::fs::read_to_string(config_path.clone()) {
+        Ok(s) => s.parse::<DocumentMut>()?,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => DocumentMut::new(),
+        Err(e) => return Err(e.into()),
+    };
+
+    let proj_tbl = ensure_project_table(&mut doc, project_path)?;
+    if !proj_tbl.get("permissions").is_some_and(TomlItem::is_table) {
+        proj_tbl.insert("permissions", toml_edit::table());
+    }
+    proj_tbl["permissions"][permission] = toml_edit::value(decision.as_str());
+
+    std::fs::create_dir_all(codex_home)?;
+    let tmp_file = NamedTempFile::new_in(codex_home)?;
+    std::fs::write(tmp_file.path(), doc.to_string())?;
+    tmp_file.persist(config_path)?;
+
+    Ok(())
+}
+
 fn ensure_profile_table<'a>(
     doc: &'a mut DocumentMut,
     profile_name: &str,
@@ -621,18 +934,49 @@ pub struct ConfigToml {
     /// Token usage threshold triggering auto-compaction of conversation history.
     pub model_auto_compact_token_limit: Option<i64>,
 
+    /// Share of the context window, in percent (1-100), whose use triggers
+    /// auto-compaction.
+    pub model_auto_compact_context_percent: Option<u8>,
+
+    /// Number of most recent user and assistant messages kept verbatim after
+    /// an automatic compaction. Defaults to 0.
+    pub auto_compact_preserve_messages: Option<u32>,
+
+    /// Prices used to estimate session cost; built-in list prices otherwise.
+    pub model_pricing: Option<ModelPricing>,
+
     /// Default approval policy for executing commands.
     pub approval_policy: Option<AskForApproval>,
 
     #[serde(default)]
     pub shell_environment_policy: ShellEnvironmentPolicyToml,
 
+    /// Memory, CPU and output limits applied to every spawned command.
+    pub resource_limits: Option<ResourceLimits>,
+
+    /// How live command output is batched before it is sent to the client.
+    pub output_streaming: Option<OutputStreaming>,
+
+    /// Default and per-command timeouts for `shell` tool calls.
+    pub exec: Option<ExecConfig>,
+
+    /// Limits on the work of a single task.
+    pub task_budget: Option<TaskBudget>,
+
     /// Sandbox mode to use.
     pub sandbox_mode: Option<SandboxMode>,
 
     /// Sandbox configuration to apply if `sandbox` is `WorkspaceWrite`.
     pub sandbox_workspace_write: Option<SandboxWorkspaceWrite>,
 
+    /// Extra rules merged into the generated sandbox profile.
+    pub sandbox: Option<SandboxExtras>,
+
+    /// User-defined sandbox presets bundling a sandbox, approval policy and
+    /// shell environment policy under a name.
+    #[serde(default)]
+    pub sandbox_presets: BTreeMap<String, SandboxPresetToml>,
+
     /// Optional external command to spawn for end-user notifications.
     #[serde(default)]
     pub notify: Option<Vec<String>>,
@@ -648,9 +992,67 @@ pub struct ConfigToml {
     #[serde(default)]
     pub model_providers: HashMap<String, ModelProviderInfo>,
 
+    /// Short names that expand to a model and, optionally, its provider and
+    /// reasoning effort.
+    #[serde(default)]
+    pub model_aliases: HashMap<String, ModelAlias>,
+
     /// Maximum number of bytes to include from an AGENTS.md project doc file.
     pub project_doc_max_bytes: Option<usize>,
 
+    /// Identical consecutive failures allowed before a retried command is
+    /// intercepted. `0` disables the check.
+    pub repeated_command_failure_limit: Option<u32>,
+
+    /// Most independent tool calls run at once. `1` disables parallel calls.
+    pub max_parallel_tool_calls: Option<usize>,
+
+    /// Repeated turns before a task is treated as stalled. `0` disables loop
+    /// detection.
+    pub stall_detection_turns: Option<u32>,
+
+    /// Whether a stalled task is paused for user input or told to recover.
+    pub stall_action: Option<StallAction>,
+
+    /// `inject` (default) adds input submitted while a task is running to
+    /// that task; `queue` runs it as a separate task afterwards.
+    pub busy_input: Option<BusyInput>,
+
+    /// Ask before continuing once the estimated cost of the session reaches
+    /// this many USD.
+    pub max_session_cost_usd: Option<f64>,
+
+    /// Ask the model for a confidence rating and follow-up suggestions once a
+    /// task completes.
+    pub task_completion_assessment: Option<bool>,
+
+    /// Days a deleted session is kept in the trash before it is purged.
+    pub session_trash_retention_days: Option<u64>,
+
+    /// Ask once per project before the agent first uses the network, an MCP
+    /// tool, or writes outside its starting directory. Defaults to `true`.
+    pub permission_prompts: Option<bool>,
+
+    /// Command patterns, such as `cargo test *`, that are approved without
+    /// asking. Approving a command "always" appends its pattern here.
+    pub approved_command_patterns: Option<Vec<String>>,
+
+    /// Provision a per-session scratch directory the agent can always write
+    /// to. Defaults to `true`.
+    pub scratch_dir: Option<bool>,
+
+    /// Keep the scratch directory after the session shuts down instead of
+    /// deleting it. Defaults to `false`.
+    pub retain_scratch_dir: Option<bool>,
+
+    /// Include the user's locale, timezone and local time in the environment
+    /// context sent with each turn. Defaults to `true`.
+    pub locale_context: Option<bool>,
+
+    /// Stamp executed commands with the session and call that ran them and
+    /// log them to `.codex/command_log`. Defaults to `false`.
+    pub command_provenance: Option<bool>,
+
     /// Profile to use from the `profiles` map.
     pub profile: Option<String>,
 
@@ -662,6 +1064,10 @@ pub struct ConfigToml {
     #[serde(default)]
     pub history: Option<History>,
 
+    /// Disk thresholds below which session rollouts are reduced.
+    #[serde(default)]
+    pub storage: Option<Storage>,
+
     /// Optional URI-based file opener. If set, citations to files in the model
     /// output will be hyperlinked using the specified URI scheme.
     pub file_opener: Option<UriBasedFileOpener>,
@@ -702,6 +1108,12 @@ pub struct ConfigToml {
     /// Nested tools section for feature toggles
     pub tools: Option<ToolsToml>,
 
+    /// Git integration settings.
+    pub git: Option<GitToml>,
+
+    /// Rules that run a command, notify or approve when an event matches.
+    pub automations: Option<Vec<AutomationToml>>,
+
     /// When true, disables burst-paste detection for typed input entirely.
     /// All characters are inserted as they are received, and no buffering
     /// or placeholder replacement will occur for fast keypress bursts.
@@ -734,9 +1146,14 @@ impl From<ConfigToml> for UserSavedConfig {
     }
 }
 
-#[derive(Deserialize, Debug, Clone, PartialEq, Eq)]
+#[derive(Deserialize, Debug, Clone, Default, PartialEq, Eq)]
 pub struct ProjectConfig {
     pub trust_level: Option<String>,
+
+    /// Answers to guided permission prompts, keyed by permission (e.g.
+    /// `network` or `mcp:<server>.<tool>`).
+    #[serde(default)]
+    pub permissions: HashMap<String, PermissionDecision>,
 }
 
 #[derive(Deserialize, Debug, Clone, Default, PartialEq)]
@@ -747,83 +1164,335 @@ pub struct ToolsToml {
     /// Enable the `view_image` tool that lets the agent attach local images.
     #[serde(default)]
     pub view_image: Option<bool>,
+
+    /// Download ripgrep when the host does not have it. Defaults to `false`.
+    #[serde(default)]
+    pub download_ripgrep: Option<bool>,
+
+    /// Enable the `delegate` tool that runs a subtask in a child agent.
+    /// Defaults to `false`.
+    #[serde(default)]
+    pub delegate: Option<bool>,
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Default)]
+#[derive(Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct GitToml {
+    /// Work on a fresh `codex/<slug-of-prompt>` branch per task. Defaults to
+    /// `false`.
+    #[serde(default)]
+    pub auto_branch: Option<bool>,
+}
+
+/// Directory, relative to a project, holding hook scripts that are found by
+/// file name rather than configured; see [`HooksConfig::add_project_hooks`].
+pub const PROJECT_HOOKS_DIR: &str = ".codex/hooks";
+
+/// Timeout for hooks when neither the hook nor `hooks.timeout_ms` sets one.
+const DEFAULT_HOOK_TIMEOUT_MS: u64 = 10_000;
+
+/// Default for [`HooksConfig::diff_inline_max_bytes`].
+const DEFAULT_DIFF_INLINE_MAX_BYTES: usize = 64 * 1024;
+
+/// Latest hook payload schema, sent as `schema_version` in every payload.
+/// Version 2 added `duration_ms` to `post-tool-use` payloads.
+pub const HOOK_PAYLOAD_VERSION: u32 = 2;
+
+/// Set to `1` in the environment of hooks run under
+/// [`HookDryRun::Env`], so scripts can skip their side effects.
+pub const HOOK_DRY_RUN_ENV_VAR: &str = "CODEX_HOOK_DRY_RUN";
+
+/// How hooks run when `hooks.dry_run` is set.
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum HookDryRun {
+    /// Do not start the hook; it behaves as if it exited 0 with no output.
+    Simulate,
+    /// Run the hook with [`HOOK_DRY_RUN_ENV_VAR`] set to `1`.
+    Env,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct HooksConfig {
     pub pre_tool_use: Option<Vec<String>>,
     pub post_tool_use: Option<Vec<String>>,
-    pub user_prompt_submit: Option<Vec<String>>,
-    pub stop: Option<Vec<String>>,
+    pub user_prompt_submit: Option<HookCommand>,
+    pub stop: Option<HookCommand>,
+    pub session_start: Option<HookCommand>,
+    /// Runs before every model request with a summary of the prompt and may
+    /// abort the turn.
+    pub pre_model_call: Option<HookCommand>,
+    /// Runs when a task finishes with its last message, diff and token usage.
+    pub turn_complete: Option<HookCommand>,
+    /// Runs after every `TurnDiff` event with the turn's diff so far and the
+    /// files it touches.
+    pub post_turn_diff: Option<HookCommand>,
     pub pre_tool_use_match: HookToolMatcher,
     pub post_tool_use_match: HookToolMatcher,
     pub pre_tool_use_rules: Vec<HookRule>,
     pub post_tool_use_rules: Vec<HookRule>,
+    /// Timeout for hooks that do not set their own `timeout_ms`.
     pub timeout_ms: u64,
+    /// Log every hook invocation with its full payload and its output.
+    pub trace: bool,
+    pub dry_run: Option<HookDryRun>,
+    /// Payload schema sent to hooks; fields added in later versions are
+    /// omitted. Defaults to [`HOOK_PAYLOAD_VERSION`].
+    pub payload_version: u32,
+    /// Diffs larger than this are passed to `post_turn_diff` as a temp file
+    /// path instead of inline.
+    pub diff_inline_max_bytes: usize,
+    /// Filesystem access granted to hooks that are `.wasm` modules.
+    pub wasm: WasmHooksConfig,
+}
+
+/// Directories a WASM hook may use; see [`crate::wasm_hooks`]. Each is
+/// mounted at its own absolute path inside the hook. By default a WASM hook
+/// has no filesystem access at all.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct WasmHooksConfig {
+    pub read_dirs: Vec<PathBuf>,
+    pub write_dirs: Vec<PathBuf>,
+}
+
+impl WasmHooksConfig {
+    fn from_toml(toml: Option<WasmHooksToml>, cwd: &Path) -> Self {
+        let WasmHooksToml {
+            read_dirs,
+            write_dirs,
+        } = toml.unwrap_or_default();
+        let resolve = |dirs: Option<Vec<PathBuf>>| {
+            dirs.unwrap_or_default()
+                .into_iter()
+                .map(|dir| cwd.join(dir))
+                .collect()
+        };
+        WasmHooksConfig {
+            read_dirs: resolve(read_dirs),
+            write_dirs: resolve(write_dirs),
+        }
+    }
+}
+
+impl Default for HooksConfig {
+    fn default() -> Self {
+        HooksConfig {
+            pre_tool_use: None,
+            post_tool_use: None,
+            user_prompt_submit: None,
+            stop: None,
+            session_start: None,
+            pre_model_call: None,
+            turn_complete: None,
+            post_turn_diff: None,
+            pre_tool_use_match: HookToolMatcher::default(),
+            post_tool_use_match: HookToolMatcher::default(),
+            pre_tool_use_rules: Vec::new(),
+            post_tool_use_rules: Vec::new(),
+            timeout_ms: DEFAULT_HOOK_TIMEOUT_MS,
+            trace: false,
+            dry_run: None,
+            payload_version: HOOK_PAYLOAD_VERSION,
+            diff_inline_max_bytes: DEFAULT_DIFF_INLINE_MAX_BYTES,
+            wasm: WasmHooksConfig::default(),
+        }
+    }
 }
 
 impl HooksConfig {
-    fn from_toml(toml: Option<HooksToml>) -> Self {
-        let default_timeout = 10_000u64;
+    fn from_toml(toml: Option<HooksToml>) -> std::io::Result<Self> {
         match toml {
             Some(HooksToml {
                 pre_tool_use,
                 post_tool_use,
                 user_prompt_submit,
                 stop,
+                session_start,
+                pre_model_call,
+                turn_complete,
+                post_turn_diff,
                 pre_tool_use_match,
                 post_tool_use_match,
                 pre_tool_use_rules,
                 post_tool_use_rules,
                 timeout_ms,
+                trace,
+                dry_run,
+                payload_version,
+                diff_inline_max_bytes,
+                // Needs the session cwd, so the `Config` loader resolves it.
+                wasm: _,
             }) => {
-                let mut pre_rules = Vec::new();
-                if let Some(argv) = pre_tool_use.clone() {
-                    pre_rules.push(HookRule {
-                        argv,
-                        matcher: HookToolMatcher::from_toml(pre_tool_use_match.clone()),
-                    });
+                let payload_version = payload_version.unwrap_or(HOOK_PAYLOAD_VERSION);
+                if !(1..=HOOK_PAYLOAD_VERSION).contains(&payload_version) {
+                    return Err(std::io::Error::new(
+                        std::io::ErrorKind::InvalidData,
+                        format!(
+                            "unsupported hooks.payload_version {payload_version}; expected 1 to {HOOK_PAYLOAD_VERSION}"
+                        ),
+                    ));
                 }
-                let mut post_rules = Vec::new();
-                if let Some(argv) = post_tool_use.clone() {
-                    post_rules.push(HookRule {
-                        argv,
-                        matcher: HookToolMatcher::from_toml(post_tool_use_match.clone()),
-                    });
-                }
-                pre_rules.extend(HookRule::vec_from_toml(pre_tool_use_rules));
-                post_rules.extend(HookRule::vec_from_toml(post_tool_use_rules));
-
-                HooksConfig {
+                let pre_tool_use_match = HookToolMatcher::from_toml(pre_tool_use_match)?;
+                let post_tool_use_match = HookToolMatcher::from_toml(post_tool_use_match)?;
+                let (pre_tool_use, mut pre_rules) =
+                    ToolHookToml::into_rules(pre_tool_use, &pre_tool_use_match)?;
+                let (post_tool_use, mut post_rules) =
+                    ToolHookToml::into_rules(post_tool_use, &post_tool_use_match)?;
+                pre_rules.extend(HookRule::vec_from_toml(pre_tool_use_rules)?);
+                post_rules.extend(HookRule::vec_from_toml(post_tool_use_rules)?);
+
+                Ok(HooksConfig {
                     pre_tool_use,
                     post_tool_use,
-                    user_prompt_submit,
-                    stop,
-                    pre_tool_use_match: HookToolMatcher::from_toml(pre_tool_use_match),
-                    post_tool_use_match: HookToolMatcher::from_toml(post_tool_use_match),
+                    user_prompt_submit: user_prompt_submit.map(HookCommand::from),
+                    stop: stop.map(HookCommand::from),
+                    session_start: session_start.map(HookCommand::from),
+                    pre_model_call: pre_model_call.map(HookCommand::from),
+                    turn_complete: turn_complete.map(HookCommand::from),
+                    post_turn_diff: post_turn_diff.map(HookCommand::from),
+                    pre_tool_use_match,
+                    post_tool_use_match,
                     pre_tool_use_rules: pre_rules,
                     post_tool_use_rules: post_rules,
-                    timeout_ms: timeout_ms.unwrap_or(default_timeout),
-                }
+                    timeout_ms: timeout_ms.unwrap_or(DEFAULT_HOOK_TIMEOUT_MS),
+                    trace: trace.unwrap_or(false),
+                    dry_run,
+                    payload_version,
+                    diff_inline_max_bytes: diff_inline_max_bytes
+                        .unwrap_or(DEFAULT_DIFF_INLINE_MAX_BYTES),
+                    wasm: WasmHooksConfig::default(),
+                })
             }
-            None => HooksConfig {
-                timeout_ms: default_timeout,
-                ..Default::default()
-            },
+            None => Ok(HooksConfig::default()),
+        }
+    }
+
+    /// The timeout for a hook whose own `timeout_ms` is `timeout_ms`.
+    pub fn timeout_ms_for(&self, timeout_ms: Option<u64>) -> u64 {
+        timeout_ms.unwrap_or(self.timeout_ms)
+    }
+
+    /// Whether hook invocations should be logged with their payloads.
+    pub fn should_trace(&self) -> bool {
+        self.trace || self.dry_run.is_some()
+    }
+
+    /// The hooks that run for `kind`, in order. For tool hooks, `tool`
+    /// selects the rules whose matcher accepts it; `None` returns every rule.
+    pub fn commands_for(&self, kind: HookKind, tool: Option<&str>) -> Vec<HookCommand> {
+        let single = |hook: &Option<HookCommand>| hook.iter().cloned().collect();
+        let rules = |rules: &[HookRule]| {
+            rules
+                .iter()
+                .filter(|rule| tool.is_none_or(|tool| rule.matcher.should_run_for(tool)))
+                .map(|rule| HookCommand {
+                    argv: rule.argv.clone(),
+                    timeout_ms: rule.timeout_ms,
+                    retries: rule.retries,
+                })
+                .collect()
+        };
+        match kind {
+            HookKind::PreToolUse => rules(&self.pre_tool_use_rules),
+            HookKind::PostToolUse => rules(&self.post_tool_use_rules),
+            HookKind::UserPromptSubmit => single(&self.user_prompt_submit),
+            HookKind::Stop => single(&self.stop),
+            HookKind::SessionStart => single(&self.session_start),
+            HookKind::PreModelCall => single(&self.pre_model_call),
+            HookKind::TurnComplete => single(&self.turn_complete),
+            HookKind::PostTurnDiff => single(&self.post_turn_diff),
         }
     }
+
+    /// Layers the executable scripts (or `<name>.wasm` modules) in a project's
+    /// hooks directory on top of the configured hooks. `pre-tool-use` and `post-tool-use` run for every
+    /// tool after the configured tool hooks. `user-prompt-submit`, `stop`,
+    /// `session-start`, `pre-model-call`, `turn-complete` and `post-turn-diff`
+    /// replace the configured hook of the same kind.
+    fn add_project_hooks(&mut self, dir: &Path) {
+        let script = |name: &str| {
+            let path = dir.join(name);
+            let wasm = dir.join(format!("{name}.wasm"));
+            if is_executable_file(&path) {
+                Some(vec![path.to_string_lossy().into_owned()])
+            } else if wasm.is_file() {
+                Some(vec![wasm.to_string_lossy().into_owned()])
+            } else {
+                None
+            }
+        };
+        for (name, rules) in [
+            ("pre-tool-use", &mut self.pre_tool_use_rules),
+            ("post-tool-use", &mut self.post_tool_use_rules),
+        ] {
+            if let Some(argv) = script(name) {
+                rules.push(HookRule {
+                    argv,
+                    matcher: HookToolMatcher::default(),
+                    blocking: true,
+                    timeout_ms: None,
+                    retries: 0,
+                });
+            }
+        }
+        for (name, hook) in [
+            ("user-prompt-submit", &mut self.user_prompt_submit),
+            ("stop", &mut self.stop),
+            ("session-start", &mut self.session_start),
+            ("pre-model-call", &mut self.pre_model_call),
+            ("turn-complete", &mut self.turn_complete),
+            ("post-turn-diff", &mut self.post_turn_diff),
+        ] {
+            if let Some(argv) = script(name) {
+                *hook = Some(HookCommand::from(argv));
+            }
+        }
+    }
+}
+
+/// The nearest [`PROJECT_HOOKS_DIR`] between `cwd` and the root of the git
+/// repository containing it.
+fn find_project_hooks_dir(cwd: &Path) -> Option<PathBuf> {
+    for dir in cwd.ancestors() {
+        let hooks_dir = dir.join(PROJECT_HOOKS_DIR);
+        if hooks_dir.is_dir() {
+            return Some(hooks_dir);
+        }
+        if dir.join(".git").exists() {
+            break;
+        }
+    }
+    None
+}
+
+#[cfg(unix)]
+fn is_executable_file(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    std::fs::metadata(path).is_ok_and(|m| m.is_file() && m.permissions().mode() & 0o111 != 0)
+}
+
+#[cfg(not(unix))]
+fn is_executable_file(path: &Path) -> bool {
+    path.is_file()
 }
 
 #[derive(Deserialize, Debug, Clone, Default, PartialEq, Eq)]
 pub struct HooksToml {
     #[serde(default)]
-    pub pre_tool_use: Option<Vec<String>>,
+    pub pre_tool_use: Option<ToolHookToml>,
     #[serde(default)]
-    pub post_tool_use: Option<Vec<String>>,
+    pub post_tool_use: Option<ToolHookToml>,
+    #[serde(default)]
+    pub user_prompt_submit: Option<HookCommandToml>,
+    #[serde(default)]
+    pub stop: Option<HookCommandToml>,
+    #[serde(default)]
+    pub session_start: Option<HookCommandToml>,
     #[serde(default)]
-    pub user_prompt_submit: Option<Vec<String>>,
+    pub pre_model_call: Option<HookCommandToml>,
     #[serde(default)]
-    pub stop: Option<Vec<String>>,
+    pub turn_complete: Option<HookCommandToml>,
+    #[serde(default)]
+    pub post_turn_diff: Option<HookCommandToml>,
     #[serde(default)]
     pub pre_tool_use_match: Option<HookToolMatchToml>,
     #[serde(default)]
@@ -834,6 +1503,207 @@ pub struct HooksToml {
     pub post_tool_use_rules: Option<Vec<HookRuleToml>>,
     #[serde(default)]
     pub timeout_ms: Option<u64>,
+    #[serde(default)]
+    pub trace: Option<bool>,
+    #[serde(default)]
+    pub dry_run: Option<HookDryRun>,
+    #[serde(default)]
+    pub payload_version: Option<u32>,
+    #[serde(default)]
+    pub diff_inline_max_bytes: Option<usize>,
+    #[serde(default)]
+    pub wasm: Option<WasmHooksToml>,
+}
+
+/// `[hooks.wasm]`: directories, relative to the session cwd, that WASM hooks
+/// may read or write.
+#[derive(Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+pub struct WasmHooksToml {
+    #[serde(default)]
+    pub read_dirs: Option<Vec<PathBuf>>,
+    #[serde(default)]
+    pub write_dirs: Option<Vec<PathBuf>>,
+}
+
+/// `user_prompt_submit`, `stop`, `session_start`, `pre_model_call`,
+/// `turn_complete` and `post_turn_diff` accept either an argv or a table that also sets the hook's
+/// timeout and retries.
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(untagged)]
+pub enum HookCommandToml {
+    Argv(Vec<String>),
+    Command {
+        command: Vec<String>,
+        #[serde(default)]
+        timeout_ms: Option<u64>,
+        #[serde(default)]
+        retries: Option<u32>,
+    },
+}
+
+/// A hook that runs a single command.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HookCommand {
+    pub argv: Vec<String>,
+    /// Overrides [`HooksConfig::timeout_ms`] for this hook.
+    pub timeout_ms: Option<u64>,
+    /// How many times to run the hook again after it exits non-zero.
+    pub retries: u32,
+}
+
+impl From<Vec<String>> for HookCommand {
+    fn from(argv: Vec<String>) -> Self {
+        HookCommand {
+            argv,
+            timeout_ms: None,
+            retries: 0,
+        }
+    }
+}
+
+impl From<HookCommandToml> for HookCommand {
+    fn from(toml: HookCommandToml) -> Self {
+        match toml {
+            HookCommandToml::Argv(argv) => HookCommand::from(argv),
+            HookCommandToml::Command {
+                command,
+                timeout_ms,
+                retries,
+            } => HookCommand {
+                argv: command,
+                timeout_ms,
+                retries: retries.unwrap_or(0),
+            },
+        }
+    }
+}
+
+/// `pre_tool_use` / `post_tool_use` accept either a single argv or a list of
+/// hooks, each scoped to the tools its `matcher` selects.
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(untagged)]
+pub enum ToolHookToml {
+    Argv(Vec<String>),
+    Matchers(Vec<ToolHookMatcherToml>),
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct ToolHookMatcherToml {
+    /// Tool id pattern; see [`ToolPattern`]. Omitted means every tool.
+    #[serde(default)]
+    pub matcher: Option<String>,
+    pub command: Vec<String>,
+    /// Set to `false` to run the hook detached; see [`HookRule::blocking`].
+    #[serde(default)]
+    pub blocking: Option<bool>,
+    #[serde(default)]
+    pub timeout_ms: Option<u64>,
+    #[serde(default)]
+    pub retries: Option<u32>,
+}
+
+impl ToolHookToml {
+    /// Split into the legacy single argv (if any) and the rules to run. A
+    /// single argv is filtered by the top-level `*_match` table; entries with
+    /// a `matcher` are filtered by their own pattern only.
+    fn into_rules(
+        toml: Option<Self>,
+        legacy_matcher: &HookToolMatcher,
+    ) -> std::io::Result<(Option<Vec<String>>, Vec<HookRule>)> {
+        match toml {
+            None => Ok((None, Vec::new())),
+            Some(ToolHookToml::Argv(argv)) => Ok((
+                Some(argv.clone()),
+                vec![HookRule {
+                    argv,
+                    matcher: legacy_matcher.clone(),
+                    blocking: true,
+                    timeout_ms: None,
+                    retries: 0,
+                }],
+            )),
+            Some(ToolHookToml::Matchers(entries)) => {
+                let rules = entries
+                    .into_iter()
+                    .map(|entry| {
+                        Ok(HookRule {
+                            argv: entry.command,
+                            matcher: HookToolMatcher {
+                                include: entry
+                                    .matcher
+                                    .as_deref()
+                                    .map(ToolPattern::parse)
+                                    .transpose()?
+                                    .into_iter()
+                                    .collect(),
+                                exclude: Vec::new(),
+                            },
+                            blocking: entry.blocking.unwrap_or(true),
+                            timeout_ms: entry.timeout_ms,
+                            retries: entry.retries.unwrap_or(0),
+                        })
+                    })
+                    .collect::<std::io::Result<Vec<_>>>()?;
+                Ok((None, rules))
+            }
+        }
+    }
+}
+
+/// A pattern matched against a tool id such as `shell` or
+/// `mcp:github.create_issue`. Patterns wrapped in slashes (`/^mcp:github\./`)
+/// are regular expressions; anything else is a glob where `*` matches any run
+/// of characters and `?` a single one. Both must match the whole tool id.
+#[derive(Debug, Clone)]
+pub enum ToolPattern {
+    Glob(String),
+    Regex(Regex),
+}
+
+impl ToolPattern {
+    fn parse(pattern: &str) -> std::io::Result<Self> {
+        match pattern
+            .strip_prefix('/')
+            .and_then(|rest| rest.strip_suffix('/'))
+        {
+            Some(source) => Regex::new(&format!("^(?:{source})$"))
+                .map(ToolPattern::Regex)
+                .map_err(|e| {
+                    std::io::Error::new(
+                        std::io::ErrorKind::InvalidData,
+                        format!("invalid hook matcher `{pattern}`: {e}"),
+                    )
+                }),
+            None => Ok(ToolPattern::Glob(pattern.to_string())),
+        }
+    }
+
+    fn matches(&self, tool: &str) -> bool {
+        match self {
+            ToolPattern::Glob(pattern) => wildcard_match(pattern, tool),
+            ToolPattern::Regex(regex) => regex.is_match(tool),
+        }
+    }
+}
+
+impl PartialEq for ToolPattern {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (ToolPattern::Glob(a), ToolPattern::Glob(b)) => a == b,
+            (ToolPattern::Regex(a), ToolPattern::Regex(b)) => a.as_str() == b.as_str(),
+            _ => false,
+        }
+    }
+}
+
+impl Eq for ToolPattern {}
+
+fn parse_tool_patterns(patterns: Option<Vec<String>>) -> std::io::Result<Vec<ToolPattern>> {
+    patterns
+        .unwrap_or_default()
+        .iter()
+        .map(|pattern| ToolPattern::parse(pattern))
+        .collect()
 }
 
 #[derive(Deserialize, Debug, Clone, Default, PartialEq, Eq)]
@@ -846,18 +1716,18 @@ pub struct HookToolMatchToml {
 
 #[derive(Debug, Clone, PartialEq, Eq, Default)]
 pub struct HookToolMatcher {
-    include: Vec<String>,
-    exclude: Vec<String>,
+    include: Vec<ToolPattern>,
+    exclude: Vec<ToolPattern>,
 }
 
 impl HookToolMatcher {
-    fn from_toml(t: Option<HookToolMatchToml>) -> Self {
+    fn from_toml(t: Option<HookToolMatchToml>) -> std::io::Result<Self> {
         match t {
-            Some(HookToolMatchToml { include, exclude }) => HookToolMatcher {
-                include: include.unwrap_or_default(),
-                exclude: exclude.unwrap_or_default(),
-            },
-            None => HookToolMatcher::default(),
+            Some(HookToolMatchToml { include, exclude }) => Ok(HookToolMatcher {
+                include: parse_tool_patterns(include)?,
+                exclude: parse_tool_patterns(exclude)?,
+            }),
+            None => Ok(HookToolMatcher::default()),
         }
     }
 
@@ -865,12 +1735,12 @@ impl HookToolMatcher {
         let included = if self.include.is_empty() {
             true
         } else {
-            self.include.iter().any(|pat| wildcard_match(pat, tool))
+            self.include.iter().any(|pat| pat.matches(tool))
         };
         if !included {
             return false;
         }
-        if self.exclude.iter().any(|pat| wildcard_match(pat, tool)) {
+        if self.exclude.iter().any(|pat| pat.matches(tool)) {
             return false;
         }
         true
@@ -884,26 +1754,42 @@ pub struct HookRuleToml {
     pub include: Option<Vec<String>>,
     #[serde(default)]
     pub exclude: Option<Vec<String>>,
+    #[serde(default)]
+    pub blocking: Option<bool>,
+    #[serde(default)]
+    pub timeout_ms: Option<u64>,
+    #[serde(default)]
+    pub retries: Option<u32>,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct HookRule {
     pub argv: Vec<String>,
     pub matcher: HookToolMatcher,
+    /// When false the hook is spawned detached: the tool call does not wait
+    /// for it, and its exit status and output are ignored.
+    pub blocking: bool,
+    /// Overrides [`HooksConfig::timeout_ms`] for this hook.
+    pub timeout_ms: Option<u64>,
+    /// How many times to run the hook again after it exits non-zero.
+    pub retries: u32,
 }
 
 impl HookRule {
-    fn from_toml(t: HookRuleToml) -> Self {
-        HookRule {
+    fn from_toml(t: HookRuleToml) -> std::io::Result<Self> {
+        Ok(HookRule {
             argv: t.argv,
             matcher: HookToolMatcher {
-                include: t.include.unwrap_or_default(),
-                exclude: t.exclude.unwrap_or_default(),
+                include: parse_tool_patterns(t.include)?,
+                exclude: parse_tool_patterns(t.exclude)?,
             },
-        }
+            blocking: t.blocking.unwrap_or(true),
+            timeout_ms: t.timeout_ms,
+            retries: t.retries.unwrap_or(0),
+        })
     }
 
-    fn vec_from_toml(v: Option<Vec<HookRuleToml>>) -> Vec<Self> {
+    fn vec_from_toml(v: Option<Vec<HookRuleToml>>) -> std::io::Result<Vec<Self>> {
         v.unwrap_or_default()
             .into_iter()
             .map(Self::from_toml)
@@ -961,11 +1847,13 @@ impl ConfigToml {
                 Some(SandboxWorkspaceWrite {
                     writable_roots,
                     network_access,
+                    allowed_domains,
                     exclude_tmpdir_env_var,
                     exclude_slash_tmp,
                 }) => SandboxPolicy::WorkspaceWrite {
                     writable_roots: writable_roots.clone(),
                     network_access: *network_access,
+                    allowed_domains: allowed_domains.clone(),
                     exclude_tmpdir_env_var: *exclude_tmpdir_env_var,
                     exclude_slash_tmp: *exclude_slash_tmp,
                 },
@@ -1031,6 +1919,9 @@ pub struct ConfigOverrides {
     pub cwd: Option<PathBuf>,
     pub approval_policy: Option<AskForApproval>,
     pub sandbox_mode: Option<SandboxMode>,
+    /// Name of a `[sandbox_presets]` entry; takes precedence over
+    /// `sandbox_mode`.
+    pub sandbox_preset: Option<String>,
     pub model_provider: Option<String>,
     pub config_profile: Option<String>,
     pub codex_linux_sandbox_exe: Option<PathBuf>,
@@ -1059,6 +1950,7 @@ impl Config {
             cwd,
             approval_policy,
             sandbox_mode,
+            sandbox_preset,
             model_provider,
             config_profile: config_profile_key,
             codex_linux_sandbox_exe,
@@ -1088,29 +1980,26 @@ impl Config {
             None => ConfigProfile::default(),
         };
 
-        let sandbox_policy = cfg.derive_sandbox_policy(sandbox_mode);
-
-        let mut model_providers = built_in_model_providers();
-        // Merge user-defined providers into the built-in list.
-        for (key, provider) in cfg.model_providers.into_iter() {
-            model_providers.entry(key).or_insert(provider);
-        }
-
-        let model_provider_id = model_provider
-            .or(config_profile.model_provider)
-            .or(cfg.model_provider)
-            .unwrap_or_else(|| "openai".to_string());
-        let model_provider = model_providers
-            .get(&model_provider_id)
-            .ok_or_else(|| {
+        let sandbox_presets: BTreeMap<String, SandboxPreset> = cfg
+            .sandbox_presets
+            .clone()
+            .into_iter()
+            .map(|(name, preset)| (name, preset.into()))
+            .collect();
+        let sandbox_preset = match sandbox_preset {
+            Some(name) => Some(sandbox_presets.get(&name).cloned().ok_or_else(|| {
                 std::io::Error::new(
                     std::io::ErrorKind::NotFound,
-                    format!("Model provider `{model_provider_id}` not found"),
+                    format!("sandbox preset `{name}` not found"),
                 )
-            })?
-            .clone();
+            })?),
+            None => None,
+        };
 
-        let shell_environment_policy = cfg.shell_environment_policy.into();
+        let sandbox_policy = match &sandbox_preset {
+            Some(preset) => preset.sandbox_policy.clone(),
+            None => cfg.derive_sandbox_policy(sandbox_mode),
+        };
 
         let resolved_cwd = {
             use std::env;
@@ -1131,6 +2020,66 @@ impl Config {
             }
         };
 
+        // Hook scripts checked into a project only run once the user has
+        // trusted that project.
+        let project_hooks_dir = if cfg.is_cwd_trusted(&resolved_cwd) {
+            find_project_hooks_dir(&resolved_cwd)
+        } else {
+            None
+        };
+        let project_permissions = cfg
+            .projects
+            .as_ref()
+            .and_then(|projects| {
+                let root = permissions_project_root(&resolved_cwd);
+                projects.get(root.to_string_lossy().as_ref())
+            })
+            .map(|project| project.permissions.clone())
+            .unwrap_or_default();
+
+        let mut model_providers = built_in_model_providers();
+        // Merge user-defined providers into the built-in list.
+        for (key, provider) in cfg.model_providers.into_iter() {
+            model_providers.entry(key).or_insert(provider);
+        }
+
+        let requested_model = model
+            .or(config_profile.model)
+            .or(cfg.model)
+            .unwrap_or_else(default_model);
+        let model_alias = cfg.model_aliases.get(&requested_model).cloned();
+        let model = match &model_alias {
+            Some(alias) => alias.model.clone(),
+            None => requested_model,
+        };
+
+        // A provider forced on the command line (`--oss`) still wins over the
+        // alias.
+        let model_provider_id = model_provider
+            .or(model_alias
+                .as_ref()
+                .and_then(|alias| alias.model_provider.clone()))
+            .or(config_profile.model_provider)
+            .or(cfg.model_provider)
+            .unwrap_or_else(|| "openai".to_string());
+        let model_provider = model_providers
+            .get(&model_provider_id)
+            .ok_or_else(|| {
+                std::io::Error::new(
+                    std::io::ErrorKind::NotFound,
+                    format!("Model provider `{model_provider_id}` not found"),
+                )
+            })?
+            .clone();
+
+        let shell_environment_policy = match sandbox_preset
+            .as_ref()
+            .and_then(|preset| preset.shell_environment_policy.clone())
+        {
+            Some(policy) => policy,
+            None => cfg.shell_environment_policy.into(),
+        };
+
         let history = cfg.history.unwrap_or_default();
 
         let tools_web_search_request = override_tools_web_search_request
@@ -1141,10 +2090,11 @@ impl Config {
             .or(cfg.tools.as_ref().and_then(|t| t.view_image))
             .unwrap_or(true);
 
-        let model = model
-            .or(config_profile.model)
-            .or(cfg.model)
-            .unwrap_or_else(default_model);
+        let tools_download_ripgrep = cfg
+            .tools
+            .as_ref()
+            .and_then(|t| t.download_ripgrep)
+            .unwrap_or(false);
 
         let mut model_family =
             find_family_for_model(&model).unwrap_or_else(|| derive_default_model_family(&model));
@@ -1170,6 +2120,9 @@ impl Config {
                 .as_ref()
                 .and_then(|info| info.auto_compact_token_limit)
         });
+        let model_pricing = cfg
+            .model_pricing
+            .or_else(|| openai_model_info.as_ref().and_then(|info| info.pricing));
 
         // Load base instructions override from a file if specified. If the
         // path is relative, resolve it against the effective cwd so the
@@ -1187,6 +2140,15 @@ impl Config {
             .or(cfg.review_model)
             .unwrap_or_else(default_review_model);
 
+        let mut hooks = HooksConfig::from_toml(cfg.hooks.clone())?;
+        hooks.wasm = WasmHooksConfig::from_toml(
+            cfg.hooks.as_ref().and_then(|hooks| hooks.wasm.clone()),
+            &resolved_cwd,
+        );
+        if let Some(dir) = &project_hooks_dir {
+            hooks.add_project_hooks(dir);
+        }
+
         let config = Self {
             model,
             review_model,
@@ -1194,23 +2156,63 @@ impl Config {
             model_context_window,
             model_max_output_tokens,
             model_auto_compact_token_limit,
+            model_auto_compact_context_percent: cfg
+                .model_auto_compact_context_percent
+                .filter(|percent| *percent > 0)
+                .map(|percent| percent.min(100)),
+            auto_compact_preserve_messages: cfg.auto_compact_preserve_messages.unwrap_or(0),
+            model_pricing,
             model_provider_id,
             model_provider,
             cwd: resolved_cwd,
             approval_policy: approval_policy
+                .or(sandbox_preset.and_then(|preset| preset.approval_policy))
                 .or(config_profile.approval_policy)
                 .or(cfg.approval_policy)
                 .unwrap_or_else(AskForApproval::default),
             sandbox_policy,
+            sandbox_extras: cfg.sandbox.unwrap_or_default(),
             shell_environment_policy,
+            resource_limits: cfg.resource_limits.unwrap_or_default(),
+            output_streaming: cfg.output_streaming.unwrap_or_default(),
+            exec: cfg.exec.unwrap_or_default(),
+            task_budget: cfg.task_budget.unwrap_or_default(),
             notify: cfg.notify,
             user_instructions,
             base_instructions,
             mcp_servers: cfg.mcp_servers,
             model_providers,
+            sandbox_presets,
+            model_aliases: cfg.model_aliases,
             project_doc_max_bytes: cfg.project_doc_max_bytes.unwrap_or(PROJECT_DOC_MAX_BYTES),
+            repeated_command_failure_limit: cfg
+                .repeated_command_failure_limit
+                .unwrap_or(REPEATED_COMMAND_FAILURE_LIMIT),
+            max_parallel_tool_calls: cfg.max_parallel_tool_calls.unwrap_or(1).max(1),
+            stall_detection_turns: cfg.stall_detection_turns.unwrap_or(STALL_DETECTION_TURNS),
+            stall_action: cfg.stall_action.unwrap_or_default(),
+            busy_input: cfg.busy_input.unwrap_or_default(),
+            max_session_cost_usd: cfg.max_session_cost_usd.filter(|limit| *limit > 0.0),
+            task_completion_assessment: cfg.task_completion_assessment.unwrap_or(false),
+            session_trash_retention_days: cfg
+                .session_trash_retention_days
+                .unwrap_or(SESSION_TRASH_RETENTION_DAYS),
+            permission_prompts: cfg.permission_prompts.unwrap_or(true),
+            approved_command_patterns: cfg.approved_command_patterns.unwrap_or_default(),
+            scratch_dir: cfg.scratch_dir.unwrap_or(true),
+            retain_scratch_dir: cfg.retain_scratch_dir.unwrap_or(false),
+            locale_context: cfg.locale_context.unwrap_or(true),
+            git_auto_branch: cfg
+                .git
+                .as_ref()
+                .and_then(|git| git.auto_branch)
+                .unwrap_or(false),
+            command_provenance: cfg.command_provenance.unwrap_or(false),
+            automations: Automation::vec_from_toml(cfg.automations.clone())?,
+            project_permissions,
             codex_home,
             history,
+            storage: cfg.storage.unwrap_or_default(),
             file_opener: cfg.file_opener.unwrap_or(UriBasedFileOpener::VsCode),
             codex_linux_sandbox_exe,
 
@@ -1219,8 +2221,10 @@ impl Config {
                 .show_raw_agent_reasoning
                 .or(show_raw_agent_reasoning)
                 .unwrap_or(false),
-            model_reasoning_effort: config_profile
-                .model_reasoning_effort
+            model_reasoning_effort: model_alias
+                .as_ref()
+                .and_then(|alias| alias.model_reasoning_effort)
+                .or(config_profile.model_reasoning_effort)
                 .or(cfg.model_reasoning_effort),
             model_reasoning_summary: config_profile
                 .model_reasoning_summary
@@ -1241,6 +2245,8 @@ impl Config {
                 .experimental_use_unified_exec_tool
                 .unwrap_or(false),
             include_view_image_tool,
+            tools_download_ripgrep,
+            tools_delegate: cfg.tools.as_ref().and_then(|t| t.delegate).unwrap_or(false),
             active_profile: active_profile_name,
             disable_paste_burst: cfg.disable_paste_burst.unwrap_or(false),
             tui_notifications: cfg
@@ -1248,7 +2254,10 @@ impl Config {
                 .as_ref()
                 .map(|t| t.notifications.clone())
                 .unwrap_or_default(),
-            hooks: HooksConfig::from_toml(cfg.hooks.clone()),
+            tui_bell: cfg.tui.as_ref().map(|t| t.bell.clone()).unwrap_or_default(),
+            tui_sound_command: cfg.tui.as_ref().and_then(|t| t.sound_command.clone()),
+            tui_quiet_hours: cfg.tui.as_ref().and_then(|t| t.quiet_hours),
+            hooks,
         };
         Ok(config)
     }
@@ -1359,11 +2368,14 @@ pub fn log_dir(cfg: &Config) -> std::io::Result<PathBuf> {
 #[cfg(test)]
 mod tests {
     use crate::config_types::HistoryPersistence;
+    use crate::config_types::McpServerOAuthConfig;
     use crate::config_types::Notifications;
+    use crate::config_types::ShellEnvironmentPolicyInherit;
 
     use super::*;
     use pretty_assertions::assert_eq;
 
+    use std::num::NonZeroUsize;
     use std::time::Duration;
     use tempfile::TempDir;
 
@@ -1412,6 +2424,44 @@ persistence = "none"
         assert_eq!(tui.notifications, Notifications::Enabled(false));
     }
 
+    #[test]
+    fn exec_config_parses_default_and_per_command_timeouts() {
+        let cfg = r#"
+[exec]
+default_timeout_ms = 60000
+
+[exec.timeouts]
+"cargo test *" = 600000
+"#;
+
+        let parsed = toml::from_str::<ConfigToml>(cfg).expect("exec config should parse");
+        let exec = parsed.exec.expect("config should include exec section");
+
+        assert_eq!(exec.default_timeout_ms, Some(60_000));
+        assert_eq!(exec.timeouts.get("cargo test *"), Some(&600_000));
+    }
+
+    #[test]
+    fn sandbox_extras_parse() {
+        let cfg = r#"
+[sandbox]
+extra_seatbelt_rules = ['(allow network-outbound (literal "/private/var/run/docker.sock"))']
+extra_landlock_paths = ["/var/cache/build"]
+"#;
+
+        let parsed = toml::from_str::<ConfigToml>(cfg).expect("sandbox extras should parse");
+        assert_eq!(
+            parsed.sandbox,
+            Some(SandboxExtras {
+                extra_seatbelt_rules: vec![
+                    r#"(allow network-outbound (literal "/private/var/run/docker.sock"))"#
+                        .to_string()
+                ],
+                extra_landlock_paths: vec![PathBuf::from("/var/cache/build")],
+            })
+        );
+    }
+
     #[test]
     fn test_sandbox_config_parsing() {
         let sandbox_full_access = r#"
@@ -1461,6 +2511,7 @@ exclude_slash_tmp = true
             SandboxPolicy::WorkspaceWrite {
                 writable_roots: vec![PathBuf::from("/my/workspace")],
                 network_access: false,
+                allowed_domains: vec![],
                 exclude_tmpdir_env_var: true,
                 exclude_slash_tmp: true,
             },
@@ -1489,20 +2540,64 @@ exclude_slash_tmp = true
                 command: "echo".to_string(),
                 args: vec!["hello".to_string()],
                 env: None,
+                url: None,
+                headers: None,
+                bearer_token_env_var: None,
+                oauth: None,
+                include_tools: vec!["search_*".to_string()],
+                exclude_tools: vec!["search_admin*".to_string()],
                 startup_timeout_sec: Some(Duration::from_secs(3)),
                 tool_timeout_sec: Some(Duration::from_secs(5)),
+                tool_timeouts_ms: BTreeMap::from([("reindex".to_string(), 600_000)]),
+                max_concurrent_calls: NonZeroUsize::new(2),
+                lazy: true,
+                idle_timeout_sec: Some(Duration::from_secs(300)),
+            },
+        );
+        servers.insert(
+            "hosted".to_string(),
+            McpServerConfig {
+                command: String::new(),
+                args: Vec::new(),
+                env: None,
+                url: Some("https://example.com/mcp".to_string()),
+                headers: Some(HashMap::from([("X-Team".to_string(), "codex".to_string())])),
+                bearer_token_env_var: Some("HOSTED_MCP_TOKEN".to_string()),
+                oauth: Some(McpServerOAuthConfig {
+                    client_id: "codex".to_string(),
+                    scopes: vec!["docs:read".to_string()],
+                    authorization_server: None,
+                }),
+                include_tools: Vec::new(),
+                exclude_tools: Vec::new(),
+                startup_timeout_sec: None,
+                tool_timeout_sec: None,
+                tool_timeouts_ms: BTreeMap::new(),
+                max_concurrent_calls: None,
+                lazy: false,
+                idle_timeout_sec: None,
             },
         );
 
         write_global_mcp_servers(codex_home.path(), &servers)?;
 
         let loaded = load_global_mcp_servers(codex_home.path())?;
-        assert_eq!(loaded.len(), 1);
+        assert_eq!(loaded.len(), 2);
+        assert_eq!(loaded.get("hosted"), servers.get("hosted"));
         let docs = loaded.get("docs").expect("docs entry");
         assert_eq!(docs.command, "echo");
         assert_eq!(docs.args, vec!["hello".to_string()]);
+        assert_eq!(docs.include_tools, vec!["search_*".to_string()]);
+        assert_eq!(docs.exclude_tools, vec!["search_admin*".to_string()]);
         assert_eq!(docs.startup_timeout_sec, Some(Duration::from_secs(3)));
         assert_eq!(docs.tool_timeout_sec, Some(Duration::from_secs(5)));
+        assert_eq!(
+            docs.tool_timeouts_ms,
+            BTreeMap::from([("reindex".to_string(), 600_000)])
+        );
+        assert_eq!(docs.max_concurrent_calls, NonZeroUsize::new(2));
+        assert!(docs.lazy);
+        assert_eq!(docs.idle_timeout_sec, Some(Duration::from_secs(300)));
 
         let empty = BTreeMap::new();
         write_global_mcp_servers(codex_home.path(), &empty)?;
@@ -1524,12 +2619,31 @@ exclude_slash_tmp = true
 [mcp_servers.docs]
 command = "echo"
 startup_timeout_ms = 2500
+tool_timeout_ms = 1500
 "#,
         )?;
 
         let servers = load_global_mcp_servers(codex_home.path())?;
         let docs = servers.get("docs").expect("docs entry");
         assert_eq!(docs.startup_timeout_sec, Some(Duration::from_millis(2500)));
+        assert_eq!(docs.tool_timeout_sec, Some(Duration::from_millis(1500)));
+
+        Ok(())
+    }
+
+    #[test]
+    fn load_global_mcp_servers_requires_command_or_url() -> anyhow::Result<()> {
+        let codex_home = TempDir::new()?;
+        let config_path = codex_home.path().join(CONFIG_TOML_FILE);
+
+        for entry in [
+            "args = [\"x\"]",
+            "command = \"echo\"\nurl = \"https://example.com/mcp\"",
+        ] {
+            std::fs::write(&config_path, format!("[mcp_servers.docs]\n{entry}\n"))?;
+            let err = load_global_mcp_servers(codex_home.path()).expect_err("invalid entry");
+            assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+        }
 
         Ok(())
     }
@@ -1822,19 +2936,51 @@ model_verbosity = "high"
                 model_context_window: Some(200_000),
                 model_max_output_tokens: Some(100_000),
                 model_auto_compact_token_limit: None,
+                model_auto_compact_context_percent: None,
+                auto_compact_preserve_messages: 0,
+                model_pricing: Some(ModelPricing {
+                    input: 2.00,
+                    cached_input: Some(0.50),
+                    output: 8.00,
+                }),
                 model_provider_id: "openai".to_string(),
                 model_provider: fixture.openai_provider.clone(),
                 approval_policy: AskForApproval::Never,
                 sandbox_policy: SandboxPolicy::new_read_only_policy(),
+                sandbox_extras: SandboxExtras::default(),
                 shell_environment_policy: ShellEnvironmentPolicy::default(),
+                resource_limits: ResourceLimits::default(),
+                output_streaming: OutputStreaming::default(),
+                exec: ExecConfig::default(),
+                task_budget: TaskBudget::default(),
                 user_instructions: None,
                 notify: None,
                 cwd: fixture.cwd(),
                 mcp_servers: HashMap::new(),
                 model_providers: fixture.model_provider_map.clone(),
+                sandbox_presets: BTreeMap::new(),
+                model_aliases: HashMap::new(),
                 project_doc_max_bytes: PROJECT_DOC_MAX_BYTES,
+                repeated_command_failure_limit: REPEATED_COMMAND_FAILURE_LIMIT,
+                max_parallel_tool_calls: 1,
+                stall_detection_turns: STALL_DETECTION_TURNS,
+                stall_action: StallAction::default(),
+                busy_input: BusyInput::default(),
+                max_session_cost_usd: None,
+                task_completion_assessment: false,
+                session_trash_retention_days: SESSION_TRASH_RETENTION_DAYS,
+                permission_prompts: true,
+                approved_command_patterns: Vec::new(),
+                scratch_dir: true,
+                retain_scratch_dir: false,
+                locale_context: true,
+                git_auto_branch: false,
+                command_provenance: false,
+                automations: Vec::new(),
+                project_permissions: HashMap::new(),
                 codex_home: fixture.codex_home(),
                 history: History::default(),
+                storage: Storage::default(),
                 file_opener: UriBasedFileOpener::VsCode,
                 codex_linux_sandbox_exe: None,
                 hide_agent_reasoning: false,
@@ -1850,10 +2996,15 @@ model_verbosity = "high"
                 use_experimental_streamable_shell_tool: false,
                 use_experimental_unified_exec_tool: false,
                 include_view_image_tool: true,
+                tools_download_ripgrep: false,
+                tools_delegate: false,
                 active_profile: Some("o3".to_string()),
                 disable_paste_burst: false,
                 tui_notifications: Default::default(),
-                hooks: HooksConfig::from_toml(None),
+                tui_bell: Default::default(),
+                tui_sound_command: None,
+                tui_quiet_hours: None,
+                hooks: HooksConfig::from_toml(None)?,
             },
             o3_profile_config
         );
@@ -1881,19 +3032,51 @@ model_verbosity = "high"
             model_context_window: Some(16_385),
             model_max_output_tokens: Some(4_096),
             model_auto_compact_token_limit: None,
+            model_auto_compact_context_percent: None,
+            auto_compact_preserve_messages: 0,
+            model_pricing: Some(ModelPricing {
+                input: 0.50,
+                cached_input: Some(0.50),
+                output: 1.50,
+            }),
             model_provider_id: "openai-chat-completions".to_string(),
             model_provider: fixture.openai_chat_completions_provider.clone(),
             approval_policy: AskForApproval::UnlessTrusted,
             sandbox_policy: SandboxPolicy::new_read_only_policy(),
+            sandbox_extras: SandboxExtras::default(),
             shell_environment_policy: ShellEnvironmentPolicy::default(),
+            resource_limits: ResourceLimits::default(),
+            output_streaming: OutputStreaming::default(),
+            exec: ExecConfig::default(),
+            task_budget: TaskBudget::default(),
             user_instructions: None,
             notify: None,
             cwd: fixture.cwd(),
             mcp_servers: HashMap::new(),
             model_providers: fixture.model_provider_map.clone(),
+            sandbox_presets: BTreeMap::new(),
+            model_aliases: HashMap::new(),
             project_doc_max_bytes: PROJECT_DOC_MAX_BYTES,
+            repeated_command_failure_limit: REPEATED_COMMAND_FAILURE_LIMIT,
+            max_parallel_tool_calls: 1,
+            stall_detection_turns: STALL_DETECTION_TURNS,
+            stall_action: StallAction::default(),
+            busy_input: BusyInput::default(),
+            max_session_cost_usd: None,
+            task_completion_assessment: false,
+            session_trash_retention_days: SESSION_TRASH_RETENTION_DAYS,
+            permission_prompts: true,
+            approved_command_patterns: Vec::new(),
+            scratch_dir: true,
+            retain_scratch_dir: false,
+            locale_context: true,
+            git_auto_branch: false,
+            command_provenance: false,
+            automations: Vec::new(),
+            project_permissions: HashMap::new(),
             codex_home: fixture.codex_home(),
             history: History::default(),
+            storage: Storage::default(),
             file_opener: UriBasedFileOpener::VsCode,
             codex_linux_sandbox_exe: None,
             hide_agent_reasoning: false,
@@ -1909,10 +3092,15 @@ model_verbosity = "high"
             use_experimental_streamable_shell_tool: false,
             use_experimental_unified_exec_tool: false,
             include_view_image_tool: true,
+            tools_download_ripgrep: false,
+            tools_delegate: false,
             active_profile: Some("gpt3".to_string()),
             disable_paste_burst: false,
             tui_notifications: Default::default(),
-            hooks: HooksConfig::from_toml(None),
+            tui_bell: Default::default(),
+            tui_sound_command: None,
+            tui_quiet_hours: None,
+            hooks: HooksConfig::from_toml(None)?,
         };
 
         assert_eq!(expected_gpt3_profile_config, gpt3_profile_config);
@@ -1955,19 +3143,51 @@ model_verbosity = "high"
             model_context_window: Some(200_000),
             model_max_output_tokens: Some(100_000),
             model_auto_compact_token_limit: None,
+            model_auto_compact_context_percent: None,
+            auto_compact_preserve_messages: 0,
+            model_pricing: Some(ModelPricing {
+                input: 2.00,
+                cached_input: Some(0.50),
+                output: 8.00,
+            }),
             model_provider_id: "openai".to_string(),
             model_provider: fixture.openai_provider.clone(),
             approval_policy: AskForApproval::OnFailure,
             sandbox_policy: SandboxPolicy::new_read_only_policy(),
+            sandbox_extras: SandboxExtras::default(),
             shell_environment_policy: ShellEnvironmentPolicy::default(),
+            resource_limits: ResourceLimits::default(),
+            output_streaming: OutputStreaming::default(),
+            exec: ExecConfig::default(),
+            task_budget: TaskBudget::default(),
             user_instructions: None,
             notify: None,
             cwd: fixture.cwd(),
             mcp_servers: HashMap::new(),
             model_providers: fixture.model_provider_map.clone(),
+            sandbox_presets: BTreeMap::new(),
+            model_aliases: HashMap::new(),
             project_doc_max_bytes: PROJECT_DOC_MAX_BYTES,
+            repeated_command_failure_limit: REPEATED_COMMAND_FAILURE_LIMIT,
+            max_parallel_tool_calls: 1,
+            stall_detection_turns: STALL_DETECTION_TURNS,
+            stall_action: StallAction::default(),
+            busy_input: BusyInput::default(),
+            max_session_cost_usd: None,
+            task_completion_assessment: false,
+            session_trash_retention_days: SESSION_TRASH_RETENTION_DAYS,
+            permission_prompts: true,
+            approved_command_patterns: Vec::new(),
+            scratch_dir: true,
+            retain_scratch_dir: false,
+            locale_context: true,
+            git_auto_branch: false,
+            command_provenance: false,
+            automations: Vec::new(),
+            project_permissions: HashMap::new(),
             codex_home: fixture.codex_home(),
             history: History::default(),
+            storage: Storage::default(),
             file_opener: UriBasedFileOpener::VsCode,
             codex_linux_sandbox_exe: None,
             hide_agent_reasoning: false,
@@ -1983,10 +3203,15 @@ model_verbosity = "high"
             use_experimental_streamable_shell_tool: false,
             use_experimental_unified_exec_tool: false,
             include_view_image_tool: true,
+            tools_download_ripgrep: false,
+            tools_delegate: false,
             active_profile: Some("zdr".to_string()),
             disable_paste_burst: false,
             tui_notifications: Default::default(),
-            hooks: HooksConfig::from_toml(None),
+            tui_bell: Default::default(),
+            tui_sound_command: None,
+            tui_quiet_hours: None,
+            hooks: HooksConfig::from_toml(None)?,
         };
 
         assert_eq!(expected_zdr_profile_config, zdr_profile_config);
@@ -2015,19 +3240,51 @@ model_verbosity = "high"
             model_context_window: Some(272_000),
             model_max_output_tokens: Some(128_000),
             model_auto_compact_token_limit: None,
+            model_auto_compact_context_percent: None,
+            auto_compact_preserve_messages: 0,
+            model_pricing: Some(ModelPricing {
+                input: 1.25,
+                cached_input: Some(0.125),
+                output: 10.00,
+            }),
             model_provider_id: "openai".to_string(),
             model_provider: fixture.openai_provider.clone(),
             approval_policy: AskForApproval::OnFailure,
             sandbox_policy: SandboxPolicy::new_read_only_policy(),
+            sandbox_extras: SandboxExtras::default(),
             shell_environment_policy: ShellEnvironmentPolicy::default(),
+            resource_limits: ResourceLimits::default(),
+            output_streaming: OutputStreaming::default(),
+            exec: ExecConfig::default(),
+            task_budget: TaskBudget::default(),
             user_instructions: None,
             notify: None,
             cwd: fixture.cwd(),
             mcp_servers: HashMap::new(),
             model_providers: fixture.model_provider_map.clone(),
+            sandbox_presets: BTreeMap::new(),
+            model_aliases: HashMap::new(),
             project_doc_max_bytes: PROJECT_DOC_MAX_BYTES,
+            repeated_command_failure_limit: REPEATED_COMMAND_FAILURE_LIMIT,
+            max_parallel_tool_calls: 1,
+            stall_detection_turns: STALL_DETECTION_TURNS,
+            stall_action: StallAction::default(),
+            busy_input: BusyInput::default(),
+            max_session_cost_usd: None,
+            task_completion_assessment: false,
+            session_trash_retention_days: SESSION_TRASH_RETENTION_DAYS,
+            permission_prompts: true,
+            approved_command_patterns: Vec::new(),
+            scratch_dir: true,
+            retain_scratch_dir: false,
+            locale_context: true,
+            git_auto_branch: false,
+            command_provenance: false,
+            automations: Vec::new(),
+            project_permissions: HashMap::new(),
             codex_home: fixture.codex_home(),
             history: History::default(),
+            storage: Storage::default(),
             file_opener: UriBasedFileOpener::VsCode,
             codex_linux_sandbox_exe: None,
             hide_agent_reasoning: false,
@@ -2043,10 +3300,15 @@ model_verbosity = "high"
             use_experimental_streamable_shell_tool: false,
             use_experimental_unified_exec_tool: false,
             include_view_image_tool: true,
+            tools_download_ripgrep: false,
+            tools_delegate: false,
             active_profile: Some("gpt5".to_string()),
             disable_paste_burst: false,
             tui_notifications: Default::default(),
-            hooks: HooksConfig::from_toml(None),
+            tui_bell: Default::default(),
+            tui_sound_command: None,
+            tui_quiet_hours: None,
+            hooks: HooksConfig::from_toml(None)?,
         };
 
         assert_eq!(expected_gpt5_profile_config, gpt5_profile_config);
@@ -2149,11 +3411,481 @@ trust_level = "trusted"
 
         Ok(())
     }
+
+    #[test]
+    fn add_approved_command_pattern_appends_once() -> anyhow::Result<()> {
+        let codex_home = TempDir::new()?;
+        std::fs::write(
+            codex_home.path().join(CONFIG_TOML_FILE),
+            "model = \"o3\"\n\n[projects.\"/repo\"]\ntrust_level = \"trusted\"\n",
+        )?;
+
+        add_approved_command_pattern(codex_home.path(), "cargo test *")?;
+        add_approved_command_pattern(codex_home.path(), "npm run *")?;
+        add_approved_command_pattern(codex_home.path(), "cargo test *")?;
+
+        let contents = std::fs::read_to_string(codex_home.path().join(CONFIG_TOML_FILE))?;
+        let cfg: ConfigToml = toml::from_str(&contents)?;
+        assert_eq!(cfg.model.as_deref(), Some("o3"));
+        assert_eq!(
+            cfg.approved_command_patterns,
+            Some(vec!["cargo test *".to_string(), "npm run *".to_string()])
+        );
+
+        Ok(())
+    }
+
+    fn hooks_from_toml(toml: &str) -> std::io::Result<HooksConfig> {
+        let cfg = toml::from_str::<ConfigToml>(toml).expect("TOML deserialization should succeed");
+        HooksConfig::from_toml(cfg.hooks)
+    }
+
+    fn pre_tool_argv_for(hooks: &HooksConfig, tool: &str) -> Vec<String> {
+        hooks
+            .pre_tool_use_rules
+            .iter()
+            .filter(|rule| rule.matcher.should_run_for(tool))
+            .flat_map(|rule| rule.argv.clone())
+            .collect()
+    }
+
+    #[test]
+    fn pre_tool_use_accepts_matcher_entries() -> std::io::Result<()> {
+        let hooks = hooks_from_toml(
+            r#"
+            [[hooks.pre_tool_use]]
+            matcher = "shell"
+            command = ["shell-hook"]
+
+            [[hooks.pre_tool_use]]
+            matcher = "mcp:github.*"
+            command = ["github-hook"]
+
+            [[hooks.pre_tool_use]]
+            matcher = "/apply_patch|update_plan/"
+            command = ["edit-hook"]
+
+            [[hooks.pre_tool_use]]
+            command = ["audit-hook"]
+            "#,
+        )?;
+
+        assert_eq!(hooks.pre_tool_use, None);
+        assert_eq!(
+            pre_tool_argv_for(&hooks, "shell"),
+            vec!["shell-hook", "audit-hook"]
+        );
+        assert_eq!(
+            pre_tool_argv_for(&hooks, "mcp:github.create_issue"),
+            vec!["github-hook", "audit-hook"]
+        );
+        assert_eq!(
+            pre_tool_argv_for(&hooks, "mcp:gitlab.create_issue"),
+            vec!["audit-hook"]
+        );
+        assert_eq!(
+            pre_tool_argv_for(&hooks, "update_plan"),
+            vec!["edit-hook", "audit-hook"]
+        );
+        // Regex matchers are anchored to the whole tool id.
+        assert_eq!(
+            pre_tool_argv_for(&hooks, "apply_patch_v2"),
+            vec!["audit-hook"]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn pre_tool_use_single_argv_keeps_top_level_matcher() -> std::io::Result<()> {
+        let hooks = hooks_from_toml(
+            r#"
+            [hooks]
+            pre_tool_use = ["pre-hook"]
+
+            [hooks.pre_tool_use_match]
+            exclude = ["/exec_command|write_stdin/"]
+            "#,
+        )?;
+
+        assert_eq!(hooks.pre_tool_use, Some(vec!["pre-hook".to_string()]));
+        assert_eq!(pre_tool_argv_for(&hooks, "shell"), vec!["pre-hook"]);
+        assert!(pre_tool_argv_for(&hooks, "write_stdin").is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn tool_hooks_default_to_blocking() -> std::io::Result<()> {
+        let hooks = hooks_from_toml(
+            r#"
+            [[hooks.pre_tool_use]]
+            command = ["policy-hook"]
+
+            [[hooks.pre_tool_use]]
+            command = ["telemetry-hook"]
+            blocking = false
+
+            [[hooks.post_tool_use_rules]]
+            argv = ["audit-hook"]
+            blocking = false
+            "#,
+        )?;
+
+        let blocking = |rules: &[HookRule]| -> Vec<(String, bool)> {
+            rules
+                .iter()
+                .map(|rule| (rule.argv.join(" "), rule.blocking))
+                .collect()
+        };
+        assert_eq!(
+            blocking(&hooks.pre_tool_use_rules),
+            vec![
+                ("policy-hook".to_string(), true),
+                ("telemetry-hook".to_string(), false),
+            ]
+        );
+        assert_eq!(
+            blocking(&hooks.post_tool_use_rules),
+            vec![("audit-hook".to_string(), false)]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn hooks_accept_per_hook_timeout_and_retries() -> std::io::Result<()> {
+        let hooks = hooks_from_toml(
+            r#"
+            [hooks]
+            timeout_ms = 2000
+            session_start = ["load-context"]
+            stop = { command = ["notify-done"], timeout_ms = 500, retries = 2 }
+
+            [[hooks.post_tool_use]]
+            command = ["format"]
+            timeout_ms = 30000
+
+            [[hooks.pre_tool_use_rules]]
+            argv = ["remote-policy"]
+            retries = 1
+            "#,
+        )?;
+
+        assert_eq!(hooks.timeout_ms, 2000);
+        assert_eq!(
+            hooks.session_start,
+            Some(HookCommand::from(vec!["load-context".to_string()]))
+        );
+        assert_eq!(
+            hooks.stop,
+            Some(HookCommand {
+                argv: vec!["notify-done".to_string()],
+                timeout_ms: Some(500),
+                retries: 2,
+            })
+        );
+        let limits = |rules: &[HookRule]| -> Vec<(Option<u64>, u32)> {
+            rules
+                .iter()
+                .map(|rule| (rule.timeout_ms, rule.retries))
+                .collect()
+        };
+        assert_eq!(limits(&hooks.post_tool_use_rules), vec![(Some(30_000), 0)]);
+        assert_eq!(limits(&hooks.pre_tool_use_rules), vec![(None, 1)]);
+        Ok(())
+    }
+
+    #[test]
+    fn hooks_dry_run_and_commands_for_kind() -> std::io::Result<()> {
+        let hooks = hooks_from_toml(
+            r#"
+            [hooks]
+            dry_run = "simulate"
+            stop = ["notify-done"]
+
+            [[hooks.pre_tool_use]]
+            matcher = "shell"
+            command = ["check-shell"]
+
+            [[hooks.pre_tool_use]]
+            command = ["audit"]
+            "#,
+        )?;
+
+        assert_eq!(hooks.dry_run, Some(HookDryRun::Simulate));
+        assert!(!hooks.trace);
+        assert!(hooks.should_trace());
+        let argvs = |kind: HookKind, tool: Option<&str>| -> Vec<Vec<String>> {
+            hooks
+                .commands_for(kind, tool)
+                .into_iter()
+                .map(|hook| hook.argv)
+                .collect()
+        };
+        assert_eq!(
+            argvs(HookKind::PreToolUse, Some("apply_patch")),
+            vec![vec!["audit".to_string()]]
+        );
+        assert_eq!(argvs(HookKind::PreToolUse, None).len(), 2);
+        assert_eq!(
+            argvs(HookKind::Stop, None),
+            vec![vec!["notify-done".to_string()]]
+        );
+        assert!(argvs(HookKind::SessionStart, None).is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn hooks_payload_version_defaults_to_latest_and_is_validated() -> std::io::Result<()> {
+        assert_eq!(hooks_from_toml("")?.payload_version, HOOK_PAYLOAD_VERSION);
+        assert_eq!(
+            hooks_from_toml("[hooks]\npayload_version = 1")?.payload_version,
+            1
+        );
+
+        let err = hooks_from_toml("[hooks]\npayload_version = 99")
+            .expect_err("unknown payload version should fail");
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+        assert!(
+            err.to_string()
+                .contains("unsupported hooks.payload_version 99")
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn invalid_hook_matcher_regex_is_rejected() {
+        let err = hooks_from_toml(
+            r#"
+            [[hooks.pre_tool_use]]
+            matcher = "/mcp:(github/"
+            command = ["hook"]
+            "#,
+        )
+        .expect_err("invalid regex should fail");
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+        assert!(
+            err.to_string()
+                .contains("invalid hook matcher `/mcp:(github/`")
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn project_hooks_are_discovered_only_in_trusted_projects() -> std::io::Result<()> {
+        use std::os::unix::fs::PermissionsExt;
+
+        let project = TempDir::new()?;
+        let project_root = project.path().canonicalize()?;
+        let git_init = std::process::Command::new("git")
+            .args(["init", "--quiet"])
+            .current_dir(&project_root)
+            .status()?;
+        assert!(git_init.success());
+        let hooks_dir = project_root.join(PROJECT_HOOKS_DIR);
+        std::fs::create_dir_all(&hooks_dir)?;
+        for (name, mode) in [
+            ("pre-tool-use", 0o755),
+            ("stop", 0o755),
+            ("post-tool-use", 0o644),
+        ] {
+            let path = hooks_dir.join(name);
+            std::fs::write(&path, "#!/bin/sh\n")?;
+            std::fs::set_permissions(&path, std::fs::Permissions::from_mode(mode))?;
+        }
+        let cwd = project_root.join("src");
+        std::fs::create_dir(&cwd)?;
+
+        let mut cfg: ConfigToml = toml::from_str(
+            r#"
+[hooks]
+stop = ["global-stop"]
+pre_tool_use = ["global-pre"]
+"#,
+        )
+        .expect("TOML deserialization should succeed");
+        let codex_home = TempDir::new()?;
+        let load = |cfg: &ConfigToml| {
+            Config::load_from_base_config_with_overrides(
+                cfg.clone(),
+                ConfigOverrides {
+                    cwd: Some(cwd.clone()),
+                    ..Default::default()
+                },
+                codex_home.path().to_path_buf(),
+            )
+        };
+
+        let untrusted = load(&cfg)?;
+        assert_eq!(
+            untrusted.hooks.stop,
+            Some(HookCommand::from(vec!["global-stop".to_string()]))
+        );
+        assert_eq!(untrusted.hooks.pre_tool_use_rules.len(), 1);
+
+        cfg.projects = Some(HashMap::from([(
+            project_root.to_string_lossy().into_owned(),
+            ProjectConfig {
+                trust_level: Some("trusted".to_string()),
+                ..Default::default()
+            },
+        )]));
+        let trusted = load(&cfg)?;
+        let script = |name: &str| hooks_dir.join(name).to_string_lossy().into_owned();
+        assert_eq!(
+            trusted.hooks.stop,
+            Some(HookCommand::from(vec![script("stop")]))
+        );
+        assert_eq!(
+            trusted
+                .hooks
+                .pre_tool_use_rules
+                .iter()
+                .map(|rule| rule.argv.clone())
+                .collect::<Vec<_>>(),
+            vec![vec!["global-pre".to_string()], vec![script("pre-tool-use")]]
+        );
+        // Scripts that are not executable are ignored.
+        assert!(trusted.hooks.post_tool_use_rules.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn model_alias_expands_to_model_provider_and_effort() -> std::io::Result<()> {
+        let cfg: ConfigToml = toml::from_str(
+            r#"
+model = "smart"
+model_reasoning_effort = "medium"
+
+[model_aliases.smart]
+model = "o3"
+model_reasoning_effort = "high"
+
+[model_aliases.cheap]
+model = "gpt-3.5-turbo"
+model_provider = "openai-chat-completions"
+
+[model_providers.openai-chat-completions]
+name = "OpenAI using Chat Completions"
+base_url = "https://api.openai.com/v1"
+wire_api = "chat"
+"#,
+        )
+        .expect("TOML deserialization should succeed");
+        let codex_home = TempDir::new()?;
+
+        let config = Config::load_from_base_config_with_overrides(
+            cfg.clone(),
+            ConfigOverrides::default(),
+            codex_home.path().to_path_buf(),
+        )?;
+        assert_eq!(config.model, "o3");
+        assert_eq!(
+            config.model_family,
+            find_family_for_model("o3").expect("known model slug")
+        );
+        assert_eq!(config.model_provider_id, "openai");
+        assert_eq!(config.model_reasoning_effort, Some(ReasoningEffort::High));
+
+        // An alias passed as `--model` resolves the same way and keeps the
+        // configured effort when the alias does not set one.
+        let config = Config::load_from_base_config_with_overrides(
+            cfg,
+            ConfigOverrides {
+                model: Some("cheap".to_string()),
+                ..Default::default()
+            },
+            codex_home.path().to_path_buf(),
+        )?;
+        assert_eq!(config.model, "gpt-3.5-turbo");
+        assert_eq!(config.model_provider_id, "openai-chat-completions");
+        assert_eq!(config.model_reasoning_effort, Some(ReasoningEffort::Medium));
+        assert_eq!(config.model_aliases.len(), 2);
+        Ok(())
+    }
+
+    #[test]
+    fn sandbox_preset_overrides_sandbox_approval_and_env() -> std::io::Result<()> {
+        let cfg: ConfigToml = toml::from_str(
+            r#"
+sandbox_mode = "read-only"
+approval_policy = "untrusted"
+
+[sandbox_presets.ci]
+description = "Writable build dir, network on"
+writable_roots = ["/tmp/build"]
+network_access = true
+approval_policy = "never"
+
+[sandbox_presets.ci.shell_environment_policy]
+inherit = "core"
+
+[sandbox_presets.locked]
+sandbox_mode = "read-only"
+"#,
+        )
+        .expect("TOML deserialization should succeed");
+        let codex_home = TempDir::new()?;
+
+        let config = Config::load_from_base_config_with_overrides(
+            cfg.clone(),
+            ConfigOverrides {
+                sandbox_preset: Some("ci".to_string()),
+                ..Default::default()
+            },
+            codex_home.path().to_path_buf(),
+        )?;
+        assert_eq!(
+            config.sandbox_policy,
+            SandboxPolicy::WorkspaceWrite {
+                writable_roots: vec![PathBuf::from("/tmp/build")],
+                network_access: true,
+                allowed_domains: vec![],
+                exclude_tmpdir_env_var: false,
+                exclude_slash_tmp: false,
+            }
+        );
+        assert_eq!(config.approval_policy, AskForApproval::Never);
+        assert_eq!(
+            config.shell_environment_policy.inherit,
+            ShellEnvironmentPolicyInherit::Core
+        );
+        assert_eq!(
+            config.sandbox_presets.keys().collect::<Vec<_>>(),
+            vec!["ci", "locked"]
+        );
+
+        // An explicit approval policy still wins over the preset's.
+        let config = Config::load_from_base_config_with_overrides(
+            cfg.clone(),
+            ConfigOverrides {
+                sandbox_preset: Some("ci".to_string()),
+                approval_policy: Some(AskForApproval::OnRequest),
+                ..Default::default()
+            },
+            codex_home.path().to_path_buf(),
+        )?;
+        assert_eq!(config.approval_policy, AskForApproval::OnRequest);
+
+        let err = Config::load_from_base_config_with_overrides(
+            cfg,
+            ConfigOverrides {
+                sandbox_preset: Some("missing".to_string()),
+                ..Default::default()
+            },
+            codex_home.path().to_path_buf(),
+        )
+        .expect_err("unknown preset should fail");
+        assert_eq!(err.to_string(), "sandbox preset `missing` not found");
+        Ok(())
+    }
 }
 
 #[cfg(test)]
 mod notifications_tests {
     use crate::config_types::Notifications;
+    use crate::config_types::QuietHours;
+    use crate::config_types::Tui;
+    use chrono::NaiveTime;
+    use pretty_assertions::assert_eq;
     use serde::Deserialize;
 
     #[derive(Deserialize, Debug, PartialEq)]
@@ -2179,6 +3911,42 @@ mod notifications_tests {
         ));
     }
 
+    #[test]
+    fn tui_bell_sound_command_and_quiet_hours() {
+        let tui: Tui = toml::from_str(
+            r#"
+                bell = ["approval-requested"]
+                sound_command = ["afplay", "/System/Library/Sounds/Glass.aiff"]
+                quiet_hours = "22:00-07:30"
+            "#,
+        )
+        .expect("deserialize tui bell settings");
+
+        assert_eq!(
+            tui.bell,
+            Notifications::Custom(vec!["approval-requested".to_string()])
+        );
+        assert_eq!(
+            tui.sound_command,
+            Some(vec![
+                "afplay".to_string(),
+                "/System/Library/Sounds/Glass.aiff".to_string()
+            ])
+        );
+        let quiet_hours = tui.quiet_hours.expect("quiet hours");
+        let at = |h, m| NaiveTime::from_hms_opt(h, m, 0).expect("valid time");
+        assert!(quiet_hours.contains(at(23, 0)));
+        assert!(quiet_hours.contains(at(7, 29)));
+        assert!(!quiet_hours.contains(at(7, 30)));
+        assert!(!quiet_hours.contains(at(12, 0)));
+
+        let daytime: QuietHours = "12:00-13:00".parse().expect("parse daytime window");
+        assert!(daytime.contains(at(12, 30)));
+        assert!(!daytime.contains(at(13, 0)));
+
+        assert!(toml::from_str::<Tui>(r#"quiet_hours = "late""#).is_err());
+    }
+
     #[test]
     fn test_tui_notifications_custom_array() {
         let toml = r#"
@@ -3,11 +3,20 @@
 // Note this file should generally be restricted to simple struct/enum
 // definitions that do not contain business logic.
 
+use std::collections::BTreeMap;
 use std::collections::HashMap;
+use std::num::NonZeroUsize;
 use std::path::PathBuf;
+use std::str::FromStr;
 use std::time::Duration;
 use wildmatch::WildMatchPattern;
 
+use chrono::NaiveTime;
+use codex_protocol::config_types::ReasoningEffort;
+use codex_protocol::config_types::SandboxMode;
+use codex_protocol::protocol::AskForApproval;
+use codex_protocol::protocol::SandboxPolicy;
+use codex_protocol::protocol::TokenUsage;
 use serde::Deserialize;
 use serde::Deserializer;
 use serde::Serialize;
@@ -15,6 +24,9 @@ use serde::de::Error as SerdeError;
 
 #[derive(Serialize, Debug, Clone, PartialEq)]
 pub struct McpServerConfig {
+    /// Program to launch for a server reached over stdio. Empty when the
+    /// server is reached over HTTP via `url`.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
     pub command: String,
 
     #[serde(default)]
@@ -23,6 +35,34 @@ pub struct McpServerConfig {
     #[serde(default)]
     pub env: Option<HashMap<String, String>>,
 
+    /// Endpoint of a server that speaks the streamable HTTP transport.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+
+    /// Extra HTTP headers sent with every request to `url`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub headers: Option<HashMap<String, String>>,
+
+    /// Name of the environment variable holding the bearer token sent in the
+    /// `Authorization` header of requests to `url`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bearer_token_env_var: Option<String>,
+
+    /// OAuth client settings for a server at `url` that requires the user
+    /// to authorize Codex. See `codex mcp login`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub oauth: Option<McpServerOAuthConfig>,
+
+    /// Globs naming the only tools from this server to expose to the model.
+    /// Empty means all tools.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub include_tools: Vec<String>,
+
+    /// Globs naming tools from this server to hide from the model, applied
+    /// after `include_tools`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub exclude_tools: Vec<String>,
+
     /// Startup timeout in seconds for initializing MCP server & initially listing tools.
     #[serde(
         default,
@@ -31,9 +71,34 @@ pub struct McpServerConfig {
     )]
     pub startup_timeout_sec: Option<Duration>,
 
-    /// Default timeout for MCP tool calls initiated via this server.
+    /// Default timeout for MCP tool calls initiated via this server. May
+    /// also be given in milliseconds as `tool_timeout_ms`.
     #[serde(default, with = "option_duration_secs")]
     pub tool_timeout_sec: Option<Duration>,
+
+    /// Per-tool timeouts in milliseconds, keyed by the tool's name on this
+    /// server. Overrides `tool_timeout_sec` for those tools.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub tool_timeouts_ms: BTreeMap<String, u64>,
+
+    /// Most tool calls to this server that may run at once. Further calls
+    /// wait for a running one to finish. Unset means no limit.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_concurrent_calls: Option<NonZeroUsize>,
+
+    /// Start the server on first use instead of at session start, describing
+    /// its tools to the model from the list cached the last time it ran.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub lazy: bool,
+
+    /// Stop the server after it has been idle this long; it is started again
+    /// on next use.
+    #[serde(
+        default,
+        with = "option_duration_secs",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub idle_timeout_sec: Option<Duration>,
 }
 
 impl<'de> Deserialize<'de> for McpServerConfig {
@@ -43,17 +108,40 @@ impl<'de> Deserialize<'de> for McpServerConfig {
     {
         #[derive(Deserialize)]
         struct RawMcpServerConfig {
-            command: String,
+            #[serde(default)]
+            command: Option<String>,
             #[serde(default)]
             args: Vec<String>,
             #[serde(default)]
             env: Option<HashMap<String, String>>,
             #[serde(default)]
+            url: Option<String>,
+            #[serde(default)]
+            headers: Option<HashMap<String, String>>,
+            #[serde(default)]
+            bearer_token_env_var: Option<String>,
+            #[serde(default)]
+            oauth: Option<McpServerOAuthConfig>,
+            #[serde(default)]
+            include_tools: Vec<String>,
+            #[serde(default)]
+            exclude_tools: Vec<String>,
+            #[serde(default)]
             startup_timeout_sec: Option<f64>,
             #[serde(default)]
             startup_timeout_ms: Option<u64>,
             #[serde(default, with = "option_duration_secs")]
             tool_timeout_sec: Option<Duration>,
+            #[serde(default)]
+            tool_timeout_ms: Option<u64>,
+            #[serde(default)]
+            tool_timeouts_ms: BTreeMap<String, u64>,
+            #[serde(default)]
+            max_concurrent_calls: Option<NonZeroUsize>,
+            #[serde(default)]
+            lazy: bool,
+            #[serde(default, with = "option_duration_secs")]
+            idle_timeout_sec: Option<Duration>,
         }
 
         let raw = RawMcpServerConfig::deserialize(deserializer)?;
@@ -67,16 +155,62 @@ impl<'de> Deserialize<'de> for McpServerConfig {
             (None, None) => None,
         };
 
+        let tool_timeout_sec = raw
+            .tool_timeout_sec
+            .or(raw.tool_timeout_ms.map(Duration::from_millis));
+
+        let command = match (raw.command, &raw.url) {
+            (Some(command), None) => command,
+            (None, Some(_)) => String::new(),
+            (Some(_), Some(_)) => {
+                return Err(SerdeError::custom(
+                    "MCP server config must set either `command` or `url`, not both",
+                ));
+            }
+            (None, None) => {
+                return Err(SerdeError::custom(
+                    "MCP server config must set either `command` or `url`",
+                ));
+            }
+        };
+
         Ok(Self {
-            command: raw.command,
+            command,
             args: raw.args,
             env: raw.env,
+            url: raw.url,
+            headers: raw.headers,
+            bearer_token_env_var: raw.bearer_token_env_var,
+            oauth: raw.oauth,
+            include_tools: raw.include_tools,
+            exclude_tools: raw.exclude_tools,
             startup_timeout_sec,
-            tool_timeout_sec: raw.tool_timeout_sec,
+            tool_timeout_sec,
+            tool_timeouts_ms: raw.tool_timeouts_ms,
+            max_concurrent_calls: raw.max_concurrent_calls,
+            lazy: raw.lazy,
+            idle_timeout_sec: raw.idle_timeout_sec,
         })
     }
 }
 
+/// OAuth client registration Codex uses to log in to an MCP server with the
+/// device authorization grant.
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+pub struct McpServerOAuthConfig {
+    /// Client id registered with the server's authorization server.
+    pub client_id: String,
+
+    /// Scopes to request.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub scopes: Vec<String>,
+
+    /// Issuer of the authorization server, when it is not hosted at the
+    /// origin of the server's `url`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub authorization_server: Option<String>,
+}
+
 mod option_duration_secs {
     use serde::Deserialize;
     use serde::Deserializer;
@@ -145,6 +279,194 @@ pub struct History {
     pub max_bytes: Option<usize>,
 }
 
+/// Thresholds below which session rollouts switch to a reduced-persistence
+/// mode that drops raw reasoning and truncates tool output.
+#[derive(Deserialize, Debug, Copy, Clone, PartialEq, Eq)]
+#[serde(default)]
+pub struct Storage {
+    /// Minimum free space, in MiB, on the disk that holds `CODEX_HOME`.
+    pub min_free_mb: u64,
+
+    /// Optional limit, in MiB, on the combined size of `CODEX_HOME/sessions`
+    /// and `CODEX_HOME/log`.
+    pub sessions_quota_mb: Option<u64>,
+}
+
+impl Default for Storage {
+    fn default() -> Self {
+        Self {
+            min_free_mb: 256,
+            sessions_quota_mb: None,
+        }
+    }
+}
+
+/// Per-command resource limits applied to every process the agent spawns.
+/// Unset fields leave the corresponding resource unlimited.
+#[derive(Deserialize, Debug, Copy, Clone, PartialEq, Eq, Default)]
+#[serde(default)]
+pub struct ResourceLimits {
+    /// Maximum memory, in MiB. On Unix this caps the address space
+    /// (`RLIMIT_AS`); on Windows it caps the committed memory of the job.
+    pub max_memory_mb: Option<u64>,
+
+    /// Maximum CPU time, in seconds, the command may consume.
+    pub max_cpu_seconds: Option<u64>,
+
+    /// Maximum number of output bytes retained from the command. Output past
+    /// this point is still drained but discarded.
+    pub max_output_bytes: Option<usize>,
+}
+
+/// Settings for the commands the model runs through the `shell` tool.
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq, Default)]
+#[serde(default)]
+pub struct ExecConfig {
+    /// Timeout, in milliseconds, for commands that match none of `timeouts`
+    /// and do not set `timeout_ms` themselves. Unset keeps the built-in
+    /// default of 10 seconds.
+    pub default_timeout_ms: Option<u64>,
+
+    /// Command pattern (as in `approved_command_patterns`, e.g.
+    /// `cargo test *`) to timeout in milliseconds.
+    pub timeouts: HashMap<String, u64>,
+
+    /// Reuse the output of read-only commands (`cat`, `rg`, `ls`, ...) when
+    /// the files they look at have not changed since they last ran.
+    pub cache_read_only_commands: bool,
+}
+
+/// Limits on the work of a single task, from `[task_budget]`. A task that
+/// reaches one is aborted with `TurnAbortReason::BudgetExceeded`; unset
+/// limits do not apply. `Op::UserTurn` can override each of them.
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(default)]
+pub struct TaskBudget {
+    /// Most model requests the task may make.
+    pub max_turns: Option<u32>,
+
+    /// Most tokens the task's model requests may use in total.
+    pub max_total_tokens: Option<u64>,
+
+    /// Most wall-clock time the task may run for.
+    #[serde(rename = "max_duration_sec", with = "option_duration_secs")]
+    pub max_duration: Option<Duration>,
+}
+
+impl TaskBudget {
+    /// This budget with the limits that are set in the arguments replaced.
+    pub fn with_overrides(
+        self,
+        max_turns: Option<u32>,
+        max_total_tokens: Option<u64>,
+        max_duration: Option<Duration>,
+    ) -> Self {
+        Self {
+            max_turns: max_turns.or(self.max_turns),
+            max_total_tokens: max_total_tokens.or(self.max_total_tokens),
+            max_duration: max_duration.or(self.max_duration),
+        }
+    }
+}
+
+/// Extra rules merged into the sandbox Codex generates, from `[sandbox]`.
+/// They let a tool reach something outside the workspace, such as
+/// `/var/run/docker.sock` or a shared cache directory, without switching to
+/// `danger-full-access`. Neither applies under `danger-full-access`.
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq, Default)]
+#[serde(default)]
+pub struct SandboxExtras {
+    /// Seatbelt (SBPL) rules appended to the macOS profile, e.g.
+    /// `(allow network-outbound (literal "/private/var/run/docker.sock"))`.
+    pub extra_seatbelt_rules: Vec<String>,
+
+    /// Paths the Linux sandbox makes writable in addition to the writable
+    /// roots of the sandbox policy.
+    pub extra_landlock_paths: Vec<PathBuf>,
+}
+
+/// How live command output is batched into `ExecCommandOutputDelta` events.
+/// Coalescing keeps chatty commands (`cargo build -vv`) from flooding the
+/// event channel faster than a client can render it.
+#[derive(Deserialize, Debug, Copy, Clone, PartialEq, Eq)]
+#[serde(default)]
+pub struct OutputStreaming {
+    /// Longest time, in milliseconds, output is held before it is sent.
+    /// `0` sends every read as soon as it completes.
+    pub flush_interval_ms: u64,
+
+    /// Largest chunk, in bytes, carried by a single event.
+    pub max_chunk_bytes: usize,
+}
+
+impl Default for OutputStreaming {
+    fn default() -> Self {
+        Self {
+            flush_interval_ms: 50,
+            max_chunk_bytes: 64 * 1024,
+        }
+    }
+}
+
+/// Model prices in USD per million tokens, used to estimate what a session
+/// cost. Reasoning tokens are billed as output.
+#[derive(Deserialize, Debug, Copy, Clone, PartialEq)]
+pub struct ModelPricing {
+    pub input: f64,
+
+    /// Price of input served from the prompt cache; `input` when unset.
+    #[serde(default)]
+    pub cached_input: Option<f64>,
+
+    pub output: f64,
+}
+
+impl ModelPricing {
+    /// Estimated cost of `usage` in USD.
+    pub fn cost_usd(&self, usage: &TokenUsage) -> f64 {
+        const TOKENS_PER_MILLION: f64 = 1_000_000.0;
+        let cached_input = self.cached_input.unwrap_or(self.input);
+        (usage.non_cached_input() as f64 * self.input
+            + usage.cached_input() as f64 * cached_input
+            + usage.output_tokens as f64 * self.output)
+            / TOKENS_PER_MILLION
+    }
+}
+
+/// Named shortcut for a model, e.g. `fast` or `smart`, usable anywhere a
+/// model name is accepted.
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct ModelAlias {
+    pub model: String,
+
+    /// Provider to use with this model; the configured provider otherwise.
+    #[serde(default)]
+    pub model_provider: Option<String>,
+
+    /// Reasoning effort to use with this model; the configured effort
+    /// otherwise.
+    #[serde(default)]
+    pub model_reasoning_effort: Option<ReasoningEffort>,
+}
+
+/// Remembered answer to a guided permission prompt, stored per project under
+/// `[projects."<path>".permissions]`.
+#[derive(Deserialize, Debug, Copy, Clone, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum PermissionDecision {
+    Allow,
+    Deny,
+}
+
+impl PermissionDecision {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            PermissionDecision::Allow => "allow",
+            PermissionDecision::Deny => "deny",
+        }
+    }
+}
+
 #[derive(Deserialize, Debug, Copy, Clone, PartialEq, Default)]
 #[serde(rename_all = "kebab-case")]
 pub enum HistoryPersistence {
@@ -155,6 +477,18 @@ pub enum HistoryPersistence {
     None,
 }
 
+/// What happens to `Op::UserInput` submitted while a task is running.
+#[derive(Deserialize, Debug, Copy, Clone, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum BusyInput {
+    /// Add the input to the running task; the model sees it on its next turn.
+    #[default]
+    Inject,
+    /// Queue the input as a task of its own, started once the tasks before
+    /// it have finished.
+    Queue,
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
 #[serde(untagged)]
 pub enum Notifications {
@@ -168,6 +502,54 @@ impl Default for Notifications {
     }
 }
 
+/// Daily local-time window such as `"22:00-07:00"`; the end is exclusive and
+/// may be earlier than the start to span midnight.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct QuietHours {
+    pub start: NaiveTime,
+    pub end: NaiveTime,
+}
+
+impl QuietHours {
+    pub fn contains(&self, time: NaiveTime) -> bool {
+        if self.start <= self.end {
+            self.start <= time && time < self.end
+        } else {
+            time >= self.start || time < self.end
+        }
+    }
+}
+
+impl FromStr for QuietHours {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let parse = |time: &str| {
+            NaiveTime::parse_from_str(time.trim(), "%H:%M")
+                .map_err(|_| format!("invalid time `{time}` in quiet_hours, expected HH:MM"))
+        };
+        let Some((start, end)) = value.split_once('-') else {
+            return Err(format!(
+                "invalid quiet_hours `{value}`, expected HH:MM-HH:MM"
+            ));
+        };
+        Ok(Self {
+            start: parse(start)?,
+            end: parse(end)?,
+        })
+    }
+}
+
+impl<'de> Deserialize<'de> for QuietHours {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let value = String::deserialize(deserializer)?;
+        value.parse().map_err(SerdeError::custom)
+    }
+}
+
 /// Collection of settings that are specific to the TUI.
 #[derive(Deserialize, Debug, Clone, PartialEq, Default)]
 pub struct Tui {
@@ -175,6 +557,23 @@ pub struct Tui {
     /// Defaults to `false`.
     #[serde(default)]
     pub notifications: Notifications,
+
+    /// Ring the terminal bell when the terminal is unfocused: once when a
+    /// turn completes, twice when an approval is requested. Accepts the same
+    /// values as `notifications`. Defaults to `false`.
+    #[serde(default)]
+    pub bell: Notifications,
+
+    /// Command to run instead of ringing the bell. The event type
+    /// (`agent-turn-complete` or `approval-requested`) is appended as the
+    /// last argument.
+    #[serde(default)]
+    pub sound_command: Option<Vec<String>>,
+
+    /// Local-time window during which the bell and `sound_command` stay
+    /// silent, e.g. `"22:00-07:00"`.
+    #[serde(default)]
+    pub quiet_hours: Option<QuietHours>,
 }
 
 #[derive(Deserialize, Debug, Clone, PartialEq, Default)]
@@ -184,6 +583,8 @@ pub struct SandboxWorkspaceWrite {
     #[serde(default)]
     pub network_access: bool,
     #[serde(default)]
+    pub allowed_domains: Vec<String>,
+    #[serde(default)]
     pub exclude_tmpdir_env_var: bool,
     #[serde(default)]
     pub exclude_slash_tmp: bool,
@@ -296,6 +697,67 @@ impl From<ShellEnvironmentPolicyToml> for ShellEnvironmentPolicy {
     }
 }
 
+/// A named sandbox preset from `[sandbox_presets.<name>]`, selectable with
+/// `--sandbox <name>`, `/approvals` or `Op::OverrideTurnContext`.
+#[derive(Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct SandboxPresetToml {
+    /// Shown next to the preset's name in `/approvals`.
+    pub description: Option<String>,
+
+    /// Base sandbox; `workspace-write` when unset.
+    pub sandbox_mode: Option<SandboxMode>,
+
+    /// Extra writable roots (`workspace-write` only).
+    #[serde(default)]
+    pub writable_roots: Vec<PathBuf>,
+
+    /// Whether commands may use the network (`workspace-write` only).
+    #[serde(default)]
+    pub network_access: bool,
+
+    /// Hosts reachable through the filtering proxy when `network_access` is
+    /// off (`workspace-write` only).
+    #[serde(default)]
+    pub allowed_domains: Vec<String>,
+
+    /// Approval policy applied along with the sandbox; unchanged when unset.
+    pub approval_policy: Option<AskForApproval>,
+
+    /// Replaces `[shell_environment_policy]` while the preset is active.
+    pub shell_environment_policy: Option<ShellEnvironmentPolicyToml>,
+}
+
+/// Resolved form of [`SandboxPresetToml`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct SandboxPreset {
+    pub description: Option<String>,
+    pub sandbox_policy: SandboxPolicy,
+    pub approval_policy: Option<AskForApproval>,
+    pub shell_environment_policy: Option<ShellEnvironmentPolicy>,
+}
+
+impl From<SandboxPresetToml> for SandboxPreset {
+    fn from(toml: SandboxPresetToml) -> Self {
+        let sandbox_policy = match toml.sandbox_mode.unwrap_or(SandboxMode::WorkspaceWrite) {
+            SandboxMode::ReadOnly => SandboxPolicy::new_read_only_policy(),
+            SandboxMode::WorkspaceWrite => SandboxPolicy::WorkspaceWrite {
+                writable_roots: toml.writable_roots,
+                network_access: toml.network_access,
+                allowed_domains: toml.allowed_domains,
+                exclude_tmpdir_env_var: false,
+                exclude_slash_tmp: false,
+            },
+            SandboxMode::DangerFullAccess => SandboxPolicy::DangerFullAccess,
+        };
+        Self {
+            description: toml.description,
+            sandbox_policy,
+            approval_policy: toml.approval_policy,
+            shell_environment_policy: toml.shell_environment_policy.map(Into::into),
+        }
+    }
+}
+
 #[derive(Deserialize, Debug, Clone, PartialEq, Eq, Default, Hash)]
 #[serde(rename_all = "kebab-case")]
 pub enum ReasoningSummaryFormat {
@@ -87,7 +87,13 @@ impl ConversationManager {
             }
         };
 
-        let conversation = Arc::new(CodexConversation::new(codex));
+        let conversation = Arc::new(CodexConversation::new(
+            codex,
+            SessionConfiguredEvent {
+                initial_messages: None,
+                ..session_configured.clone()
+            },
+        ));
         self.conversations
             .write()
             .await
@@ -111,6 +117,16 @@ impl ConversationManager {
             .ok_or_else(|| CodexErr::ConversationNotFound(conversation_id))
     }
 
+    /// All conversations currently held by the manager, in no particular order.
+    pub async fn loaded_conversations(&self) -> Vec<(ConversationId, Arc<CodexConversation>)> {
+        self.conversations
+            .read()
+            .await
+            .iter()
+            .map(|(id, conversation)| (*id, conversation.clone()))
+            .collect()
+    }
+
     pub async fn resume_conversation_from_rollout(
         &self,
         config: Config,
@@ -1,4 +1,9 @@
+use crate::mcp_connection_manager::McpConnectionManager;
 use codex_protocol::custom_prompts::CustomPrompt;
+use futures::future::join_all;
+use mcp_types::ContentBlock;
+use mcp_types::EmbeddedResourceResource;
+use mcp_types::GetPromptResult;
 use std::collections::HashSet;
 use std::path::Path;
 use std::path::PathBuf;
@@ -73,6 +78,78 @@ pub async fn discover_prompts_in_excluding(
     out
 }
 
+/// Separates the server name from the prompt name in the name of a prompt
+/// provided by an MCP server, e.g. `docs:summarize`.
+const MCP_PROMPT_NAME_DELIMITER: &str = ":";
+
+/// Render the prompts offered by connected MCP servers as custom prompts
+/// named `<server>:<prompt>`, sorted by name. Prompts with required
+/// arguments are skipped, since the slash popup has no way to fill them in.
+pub(crate) async fn discover_mcp_prompts(manager: &McpConnectionManager) -> Vec<CustomPrompt> {
+    let mut requests = Vec::new();
+    for (server, prompts) in manager.list_all_prompts().await {
+        for prompt in prompts {
+            let needs_arguments = prompt
+                .arguments
+                .iter()
+                .flatten()
+                .any(|argument| argument.required == Some(true));
+            if needs_arguments {
+                tracing::debug!(
+                    "skipping MCP prompt `{server}/{}`: it has required arguments",
+                    prompt.name
+                );
+                continue;
+            }
+            let server = server.clone();
+            requests.push(async move {
+                let rendered = manager.get_prompt(&server, &prompt.name, None).await;
+                (server, prompt.name, rendered)
+            });
+        }
+    }
+
+    let mut out: Vec<CustomPrompt> = Vec::new();
+    for (server, name, rendered) in join_all(requests).await {
+        let content = match rendered {
+            Ok(result) => prompt_result_to_text(&result),
+            Err(e) => {
+                tracing::warn!("{e:#}");
+                continue;
+            }
+        };
+        if content.is_empty() {
+            continue;
+        }
+        out.push(CustomPrompt {
+            name: format!("{server}{MCP_PROMPT_NAME_DELIMITER}{name}"),
+            // There is no file behind the prompt; record where it came from.
+            path: PathBuf::from(format!("mcp://{server}/{name}")),
+            content,
+        });
+    }
+    out.sort_by(|a, b| a.name.cmp(&b.name));
+    out
+}
+
+/// The text of a rendered MCP prompt. Non-text content such as images is
+/// dropped, since a custom prompt is plain text.
+fn prompt_result_to_text(result: &GetPromptResult) -> String {
+    let texts: Vec<&str> = result
+        .messages
+        .iter()
+        .filter_map(|message| match &message.content {
+            ContentBlock::TextContent(text) => Some(text.text.as_str()),
+            ContentBlock::EmbeddedResource(embedded) => match &embedded.resource {
+                EmbeddedResourceResource::TextResourceContents(text) => Some(text.text.as_str()),
+                EmbeddedResourceResource::BlobResourceContents(_) => None,
+            },
+            _ => None,
+        })
+        .collect();
+    texts.join("\n\n")
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -124,4 +201,26 @@ mod tests {
         let names: Vec<String> = found.into_iter().map(|e| e.name).collect();
         assert_eq!(names, vec!["good"]);
     }
+
+    #[test]
+    fn mcp_prompt_text_keeps_only_text_content() {
+        let result: GetPromptResult = serde_json::from_value(serde_json::json!({
+            "messages": [
+                { "role": "user", "content": { "type": "text", "text": "Summarize the docs." } },
+                { "role": "user", "content": { "type": "image", "data": "AA==", "mimeType": "image/png" } },
+                {
+                    "role": "user",
+                    "content": {
+                        "type": "resource",
+                        "resource": { "uri": "docs://index", "text": "Index page" }
+                    }
+                }
+            ]
+        }))
+        .unwrap();
+        assert_eq!(
+            prompt_result_to_text(&result),
+            "Summarize the docs.\n\nIndex page"
+        );
+    }
 }
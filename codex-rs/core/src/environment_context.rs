@@ -6,6 +6,7 @@ use crate::codex::TurnContext;
 use crate::protocol::AskForApproval;
 use crate::protocol::SandboxPolicy;
 use crate::shell::Shell;
+use codex_git_tooling::RepoStatus;
 use codex_protocol::config_types::SandboxMode;
 use codex_protocol::models::ContentItem;
 use codex_protocol::models::ResponseItem;
@@ -27,8 +28,60 @@ pub(crate) struct EnvironmentContext {
     pub approval_policy: Option<AskForApproval>,
     pub sandbox_mode: Option<SandboxMode>,
     pub network_access: Option<NetworkAccess>,
+    pub allowed_domains: Option<Vec<String>>,
     pub writable_roots: Option<Vec<PathBuf>>,
     pub shell: Option<Shell>,
+    pub scratch_dir: Option<PathBuf>,
+    pub locale: Option<String>,
+    pub timezone: Option<String>,
+    pub local_time: Option<String>,
+    pub git_status: Option<GitStatusContext>,
+}
+
+/// The user's locale, timezone and wall-clock time, sent at the start of each
+/// turn so date-sensitive answers are not computed in UTC.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct LocaleContext {
+    pub locale: Option<String>,
+    pub timezone: Option<String>,
+    /// Local time to the minute, with UTC offset and weekday.
+    pub local_time: String,
+}
+
+impl LocaleContext {
+    pub fn current() -> Self {
+        Self {
+            locale: sys_locale::get_locale(),
+            timezone: iana_time_zone::get_timezone().ok(),
+            local_time: chrono::Local::now()
+                .format("%Y-%m-%d %H:%M %:z (%A)")
+                .to_string(),
+        }
+    }
+}
+
+/// Branch, uncommitted changes and upstream distance of the repository
+/// containing the turn's `cwd`, so the model does not need `git status` to
+/// orient itself.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub(crate) struct GitStatusContext {
+    /// `None` on a detached `HEAD`.
+    pub branch: Option<String>,
+    pub dirty_files: usize,
+    /// Only set when the branch has an upstream.
+    pub ahead: Option<usize>,
+    pub behind: Option<usize>,
+}
+
+impl From<&RepoStatus> for GitStatusContext {
+    fn from(status: &RepoStatus) -> Self {
+        Self {
+            branch: status.branch().map(str::to_string),
+            dirty_files: status.dirty_files(),
+            ahead: status.ahead(),
+            behind: status.behind(),
+        }
+    }
 }
 
 impl EnvironmentContext {
@@ -37,6 +90,7 @@ impl EnvironmentContext {
         approval_policy: Option<AskForApproval>,
         sandbox_policy: Option<SandboxPolicy>,
         shell: Option<Shell>,
+        scratch_dir: Option<PathBuf>,
     ) -> Self {
         Self {
             cwd,
@@ -59,6 +113,10 @@ impl EnvironmentContext {
                 }
                 None => None,
             },
+            allowed_domains: sandbox_policy
+                .as_ref()
+                .map(|policy| policy.allowed_domains().to_vec())
+                .filter(|domains| !domains.is_empty()),
             writable_roots: match sandbox_policy {
                 Some(SandboxPolicy::WorkspaceWrite { writable_roots, .. }) => {
                     if writable_roots.is_empty() {
@@ -70,27 +128,41 @@ impl EnvironmentContext {
                 _ => None,
             },
             shell,
+            scratch_dir,
+            locale: None,
+            timezone: None,
+            local_time: None,
+            git_status: None,
         }
     }
 
-    /// Compares two environment contexts, ignoring the shell. Useful when
-    /// comparing turn to turn, since the initial environment_context will
-    /// include the shell, and then it is not configurable from turn to turn.
+    /// Compares two environment contexts, ignoring the shell, scratch dir,
+    /// locale and git status. Useful when comparing turn to turn, since the
+    /// initial environment_context will include the first two, and then they
+    /// are not configurable from turn to turn; the locale and git status are
+    /// refreshed separately.
     pub fn equals_except_shell(&self, other: &EnvironmentContext) -> bool {
         let EnvironmentContext {
             cwd,
             approval_policy,
             sandbox_mode,
             network_access,
+            allowed_domains,
             writable_roots,
-            // should compare all fields except shell
+            // should compare all fields except shell, scratch_dir, locale and git status
             shell: _,
+            scratch_dir: _,
+            locale: _,
+            timezone: _,
+            local_time: _,
+            git_status: _,
         } = other;
 
         self.cwd == *cwd
             && self.approval_policy == *approval_policy
             && self.sandbox_mode == *sandbox_mode
             && self.network_access == *network_access
+            && self.allowed_domains == *allowed_domains
             && self.writable_roots == *writable_roots
     }
 }
@@ -103,10 +175,36 @@ impl From<&TurnContext> for EnvironmentContext {
             Some(turn_context.sandbox_policy.clone()),
             // Shell is not configurable from turn to turn
             None,
+            None,
         )
     }
 }
 
+impl From<LocaleContext> for EnvironmentContext {
+    fn from(locale_context: LocaleContext) -> Self {
+        let LocaleContext {
+            locale,
+            timezone,
+            local_time,
+        } = locale_context;
+        Self {
+            locale,
+            timezone,
+            local_time: Some(local_time),
+            ..Self::new(None, None, None, None, None)
+        }
+    }
+}
+
+impl From<GitStatusContext> for EnvironmentContext {
+    fn from(git_status: GitStatusContext) -> Self {
+        Self {
+            git_status: Some(git_status),
+            ..Self::new(None, None, None, None, None)
+        }
+    }
+}
+
 impl EnvironmentContext {
     /// Serializes the environment context to XML. Libraries like `quick-xml`
     /// require custom macros to handle Enums with newtypes, so we just do it
@@ -119,7 +217,13 @@ impl EnvironmentContext {
     ///   <sandbox_mode>...</sandbox_mode>
     ///   <writable_roots>...</writable_roots>
     ///   <network_access>...</network_access>
+    ///   <allowed_domains>...</allowed_domains>
     ///   <shell>...</shell>
+    ///   <scratch_dir>...</scratch_dir>
+    ///   <locale>...</locale>
+    ///   <timezone>...</timezone>
+    ///   <local_time>...</local_time>
+    ///   <git_status>...</git_status>
     /// </environment_context>
     /// ```
     pub fn serialize_to_xml(self) -> String {
@@ -140,6 +244,13 @@ impl EnvironmentContext {
                 "  <network_access>{network_access}</network_access>"
             ));
         }
+        if let Some(allowed_domains) = self.allowed_domains {
+            lines.push("  <allowed_domains>".to_string());
+            for domain in allowed_domains {
+                lines.push(format!("    <domain>{domain}</domain>"));
+            }
+            lines.push("  </allowed_domains>".to_string());
+        }
         if let Some(writable_roots) = self.writable_roots {
             lines.push("  <writable_roots>".to_string());
             for writable_root in writable_roots {
@@ -155,6 +266,35 @@ impl EnvironmentContext {
         {
             lines.push(format!("  <shell>{shell_name}</shell>"));
         }
+        if let Some(scratch_dir) = self.scratch_dir {
+            lines.push(format!(
+                "  <scratch_dir>{}</scratch_dir>",
+                scratch_dir.to_string_lossy()
+            ));
+        }
+        if let Some(locale) = self.locale {
+            lines.push(format!("  <locale>{locale}</locale>"));
+        }
+        if let Some(timezone) = self.timezone {
+            lines.push(format!("  <timezone>{timezone}</timezone>"));
+        }
+        if let Some(local_time) = self.local_time {
+            lines.push(format!("  <local_time>{local_time}</local_time>"));
+        }
+        if let Some(git_status) = self.git_status {
+            lines.push("  <git_status>".to_string());
+            let branch = git_status.branch.as_deref().unwrap_or("(detached)");
+            lines.push(format!("    <branch>{branch}</branch>"));
+            lines.push(format!(
+                "    <dirty_files>{}</dirty_files>",
+                git_status.dirty_files
+            ));
+            if let (Some(ahead), Some(behind)) = (git_status.ahead, git_status.behind) {
+                lines.push(format!("    <ahead>{ahead}</ahead>"));
+                lines.push(format!("    <behind>{behind}</behind>"));
+            }
+            lines.push("  </git_status>".to_string());
+        }
         lines.push(ENVIRONMENT_CONTEXT_CLOSE_TAG.to_string());
         lines.join("\n")
     }
@@ -184,6 +324,7 @@ mod tests {
         SandboxPolicy::WorkspaceWrite {
             writable_roots: writable_roots.into_iter().map(PathBuf::from).collect(),
             network_access,
+            allowed_domains: vec![],
             exclude_tmpdir_env_var: false,
             exclude_slash_tmp: false,
         }
@@ -196,6 +337,7 @@ mod tests {
             Some(AskForApproval::OnRequest),
             Some(workspace_write_policy(vec!["/repo", "/tmp"], false)),
             None,
+            None,
         );
 
         let expected = r#"<environment_context>
@@ -212,6 +354,72 @@ mod tests {
         assert_eq!(context.serialize_to_xml(), expected);
     }
 
+    #[test]
+    fn serialize_allowed_domains_environment_context() {
+        let context = EnvironmentContext::new(
+            None,
+            None,
+            Some(SandboxPolicy::WorkspaceWrite {
+                writable_roots: vec![],
+                network_access: false,
+                allowed_domains: vec!["crates.io".to_string(), "github.com".to_string()],
+                exclude_tmpdir_env_var: false,
+                exclude_slash_tmp: false,
+            }),
+            None,
+            None,
+        );
+
+        let expected = r#"<environment_context>
+  <sandbox_mode>workspace-write</sandbox_mode>
+  <network_access>restricted</network_access>
+  <allowed_domains>
+    <domain>crates.io</domain>
+    <domain>github.com</domain>
+  </allowed_domains>
+</environment_context>"#;
+
+        assert_eq!(context.serialize_to_xml(), expected);
+    }
+
+    #[test]
+    fn serialize_locale_environment_context() {
+        let context = EnvironmentContext::from(LocaleContext {
+            locale: Some("de-DE".to_string()),
+            timezone: Some("Europe/Berlin".to_string()),
+            local_time: "2025-03-14 09:30 +01:00 (Friday)".to_string(),
+        });
+
+        let expected = r#"<environment_context>
+  <locale>de-DE</locale>
+  <timezone>Europe/Berlin</timezone>
+  <local_time>2025-03-14 09:30 +01:00 (Friday)</local_time>
+</environment_context>"#;
+
+        assert_eq!(context.serialize_to_xml(), expected);
+    }
+
+    #[test]
+    fn serialize_git_status_environment_context() {
+        let context = EnvironmentContext::from(GitStatusContext {
+            branch: Some("main".to_string()),
+            dirty_files: 3,
+            ahead: Some(1),
+            behind: Some(0),
+        });
+
+        let expected = r#"<environment_context>
+  <git_status>
+    <branch>main</branch>
+    <dirty_files>3</dirty_files>
+    <ahead>1</ahead>
+    <behind>0</behind>
+  </git_status>
+</environment_context>"#;
+
+        assert_eq!(context.serialize_to_xml(), expected);
+    }
+
     #[test]
     fn serialize_read_only_environment_context() {
         let context = EnvironmentContext::new(
@@ -219,6 +427,7 @@ mod tests {
             Some(AskForApproval::Never),
             Some(SandboxPolicy::ReadOnly),
             None,
+            None,
         );
 
         let expected = r#"<environment_context>
@@ -237,6 +446,7 @@ mod tests {
             Some(AskForApproval::OnFailure),
             Some(SandboxPolicy::DangerFullAccess),
             None,
+            None,
         );
 
         let expected = r#"<environment_context>
@@ -248,6 +458,26 @@ mod tests {
         assert_eq!(context.serialize_to_xml(), expected);
     }
 
+    #[test]
+    fn serialize_environment_context_with_scratch_dir() {
+        let context = EnvironmentContext::new(
+            None,
+            Some(AskForApproval::Never),
+            Some(SandboxPolicy::ReadOnly),
+            None,
+            Some(PathBuf::from("/tmp/codex-scratch-abc")),
+        );
+
+        let expected = r#"<environment_context>
+  <approval_policy>never</approval_policy>
+  <sandbox_mode>read-only</sandbox_mode>
+  <network_access>restricted</network_access>
+  <scratch_dir>/tmp/codex-scratch-abc</scratch_dir>
+</environment_context>"#;
+
+        assert_eq!(context.serialize_to_xml(), expected);
+    }
+
     #[test]
     fn equals_except_shell_compares_approval_policy() {
         // Approval policy
@@ -256,12 +486,14 @@ mod tests {
             Some(AskForApproval::OnRequest),
             Some(workspace_write_policy(vec!["/repo"], false)),
             None,
+            None,
         );
         let context2 = EnvironmentContext::new(
             Some(PathBuf::from("/repo")),
             Some(AskForApproval::Never),
             Some(workspace_write_policy(vec!["/repo"], true)),
             None,
+            None,
         );
         assert!(!context1.equals_except_shell(&context2));
     }
@@ -273,12 +505,14 @@ mod tests {
             Some(AskForApproval::OnRequest),
             Some(SandboxPolicy::new_read_only_policy()),
             None,
+            None,
         );
         let context2 = EnvironmentContext::new(
             Some(PathBuf::from("/repo")),
             Some(AskForApproval::OnRequest),
             Some(SandboxPolicy::new_workspace_write_policy()),
             None,
+            None,
         );
 
         assert!(!context1.equals_except_shell(&context2));
@@ -291,12 +525,14 @@ mod tests {
             Some(AskForApproval::OnRequest),
             Some(workspace_write_policy(vec!["/repo", "/tmp", "/var"], false)),
             None,
+            None,
         );
         let context2 = EnvironmentContext::new(
             Some(PathBuf::from("/repo")),
             Some(AskForApproval::OnRequest),
             Some(workspace_write_policy(vec!["/repo", "/tmp"], true)),
             None,
+            None,
         );
 
         assert!(!context1.equals_except_shell(&context2));
@@ -312,6 +548,7 @@ mod tests {
                 shell_path: "/bin/bash".into(),
                 bashrc_path: "/home/user/.bashrc".into(),
             })),
+            None,
         );
         let context2 = EnvironmentContext::new(
             Some(PathBuf::from("/repo")),
@@ -321,6 +558,7 @@ mod tests {
                 shell_path: "/bin/zsh".into(),
                 zshrc_path: "/home/user/.zshrc".into(),
             })),
+            None,
         );
 
         assert!(context1.equals_except_shell(&context2));
@@ -108,6 +108,10 @@ pub enum CodexErr {
     #[error("unsupported operation: {0}")]
     UnsupportedOperation(String),
 
+    /// A hook refused to let the turn continue; the message says why.
+    #[error("{0}")]
+    HookBlocked(String),
+
     // -----------------------------------------------------------------
     // Automatic conversions for common external error types
     // -----------------------------------------------------------------
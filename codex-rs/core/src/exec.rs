@@ -10,23 +10,44 @@ use std::time::Duration;
 use std::time::Instant;
 
 use async_channel::Sender;
+use portable_pty::CommandBuilder;
+use portable_pty::PtySize;
+use portable_pty::native_pty_system;
 use tokio::io::AsyncRead;
 use tokio::io::AsyncReadExt;
 use tokio::io::BufReader;
 use tokio::process::Child;
+use tokio_util::sync::CancellationToken;
 
+use crate::config_types::OutputStreaming;
+use crate::config_types::ResourceLimits;
+use crate::config_types::SandboxExtras;
 use crate::error::CodexErr;
 use crate::error::Result;
 use crate::error::SandboxErr;
+use crate::exec_cache::ExecOutputCache;
+use crate::landlock::CODEX_LINUX_SANDBOX_ARG1;
+use crate::landlock::create_linux_sandbox_command_args;
 use crate::landlock::spawn_command_under_linux_sandbox;
+use crate::network_proxy::prepare_network_proxy;
+use crate::path_utils::simplify_verbatim;
 use crate::protocol::Event;
 use crate::protocol::EventMsg;
 use crate::protocol::ExecCommandOutputDeltaEvent;
 use crate::protocol::ExecOutputStream;
 use crate::protocol::SandboxPolicy;
+use crate::resource_limits;
+use crate::resource_limits::OUTPUT_TRUNCATED_MARKER;
+use crate::resource_limits::append_within_limit;
+use crate::seatbelt::MACOS_PATH_TO_SEATBELT_EXECUTABLE;
+use crate::seatbelt::create_seatbelt_command_args;
 use crate::seatbelt::spawn_command_under_seatbelt;
+use crate::spawn::CODEX_SANDBOX_ENV_VAR;
+use crate::spawn::CODEX_SANDBOX_NETWORK_DISABLED_ENV_VAR;
 use crate::spawn::StdioPolicy;
 use crate::spawn::spawn_child_async;
+use crate::windows_sandbox::spawn_command_under_windows_sandbox;
+use crate::windows_sandbox::windows_sandbox_command;
 
 const DEFAULT_TIMEOUT_MS: u64 = 10_000;
 
@@ -36,15 +57,28 @@ const SIGKILL_CODE: i32 = 9;
 const TIMEOUT_CODE: i32 = 64;
 const EXIT_CODE_SIGNAL_BASE: i32 = 128; // conventional shell: 128 + signal
 const EXEC_TIMEOUT_EXIT_CODE: i32 = 124; // conventional timeout exit code
+const EXEC_CANCELLED_EXIT_CODE: i32 = 130; // conventional shell: interrupted by Ctrl-C
 
 // I/O buffer sizing
 const READ_CHUNK_SIZE: usize = 8192; // bytes per read
 const AGGREGATE_BUFFER_INITIAL_CAPACITY: usize = 8 * 1024; // 8 KiB
 
+// Terminal used for `tty` calls unless the call's env sets LINES/COLUMNS.
+const DEFAULT_PTY_ROWS: u16 = 24;
+const DEFAULT_PTY_COLS: u16 = 80;
+const DEFAULT_PTY_TERM: &str = "xterm-256color";
+
 /// Limit the number of ExecCommandOutputDelta events emitted per exec call.
 /// Aggregation still collects full output; only the live event stream is capped.
 pub(crate) const MAX_EXEC_OUTPUT_DELTAS_PER_CALL: usize = 10_000;
 
+/// Output deltas are held back while this many events are waiting to be
+/// consumed, so a slow client throttles the command instead of queueing
+/// unbounded output. The wait is capped so a stalled client cannot hang it.
+const MAX_PENDING_EVENTS: usize = 256;
+const BACKPRESSURE_POLL_INTERVAL: Duration = Duration::from_millis(10);
+const MAX_BACKPRESSURE_WAIT: Duration = Duration::from_secs(1);
+
 #[derive(Clone, Debug)]
 pub struct ExecParams {
     pub command: Vec<String>,
@@ -53,6 +87,13 @@ pub struct ExecParams {
     pub env: HashMap<String, String>,
     pub with_escalated_permissions: Option<bool>,
     pub justification: Option<String>,
+    /// Run the command attached to a pseudo-terminal instead of pipes, for
+    /// programs that change behavior when they do not see a TTY.
+    pub tty: bool,
+    /// Memory, CPU and output caps for the command.
+    pub resource_limits: ResourceLimits,
+    /// Extra rules merged into the seatbelt or Landlock sandbox.
+    pub sandbox_extras: SandboxExtras,
 }
 
 impl ExecParams {
@@ -70,6 +111,9 @@ pub enum SandboxType {
 
     /// Only available on Linux.
     LinuxSeccomp,
+
+    /// Only available on Windows.
+    WindowsRestrictedToken,
 }
 
 #[derive(Clone)]
@@ -77,8 +121,15 @@ pub struct StdoutStream {
     pub sub_id: String,
     pub call_id: String,
     pub tx_event: Sender<Event>,
+    pub streaming: OutputStreaming,
 }
 
+/// Runs `params` under `sandbox_type`. Cancelling `cancellation_token` kills
+/// the command and everything it started; the output produced so far is
+/// returned with exit code 130. With an `output_cache`, read-only commands
+/// whose inputs have not changed since their last run return the cached
+/// output instead of running again.
+#[allow(clippy::too_many_arguments)]
 pub async fn process_exec_tool_call(
     params: ExecParams,
     sandbox_type: SandboxType,
@@ -86,19 +137,79 @@ pub async fn process_exec_tool_call(
     sandbox_cwd: &Path,
     codex_linux_sandbox_exe: &Option<PathBuf>,
     stdout_stream: Option<StdoutStream>,
+    cancellation_token: Option<CancellationToken>,
+    output_cache: Option<&ExecOutputCache>,
 ) -> Result<ExecToolCallOutput> {
+    let cache_slot = match output_cache {
+        Some(_) => ExecOutputCache::slot_for(&params).await,
+        None => None,
+    };
+    if let (Some(cache), Some(slot)) = (output_cache, &cache_slot)
+        && let Some(output) = cache.get(slot)
+    {
+        tracing::debug!("reusing cached output for {:?}", params.command);
+        return Ok(output);
+    }
+
     let start = Instant::now();
+    let cancellation_token = cancellation_token.unwrap_or_default();
 
     let timeout_duration = params.timeout_duration();
 
     let raw_output_result: std::result::Result<RawExecToolCallOutput, CodexErr> = match sandbox_type
     {
-        SandboxType::None => exec(params, sandbox_policy, stdout_stream.clone()).await,
+        SandboxType::None => {
+            exec(
+                params,
+                sandbox_policy,
+                stdout_stream.clone(),
+                &cancellation_token,
+            )
+            .await
+        }
+        SandboxType::MacosSeatbelt
+        | SandboxType::LinuxSeccomp
+        | SandboxType::WindowsRestrictedToken
+            if params.tty =>
+        {
+            let ExecParams {
+                command,
+                cwd: command_cwd,
+                env,
+                resource_limits,
+                sandbox_extras,
+                ..
+            } = params;
+            let (program, args, env) = sandboxed_pty_command(
+                command,
+                env,
+                sandbox_type,
+                sandbox_policy,
+                sandbox_cwd,
+                codex_linux_sandbox_exe,
+                &sandbox_extras,
+            )
+            .await?;
+            exec_in_pty(
+                program,
+                args,
+                command_cwd,
+                env,
+                sandbox_policy,
+                timeout_duration,
+                stdout_stream.clone(),
+                resource_limits,
+                &cancellation_token,
+            )
+            .await
+        }
         SandboxType::MacosSeatbelt => {
             let ExecParams {
                 command,
                 cwd: command_cwd,
                 env,
+                resource_limits,
+                sandbox_extras,
                 ..
             } = params;
             let child = spawn_command_under_seatbelt(
@@ -108,18 +219,28 @@ pub async fn process_exec_tool_call(
                 sandbox_cwd,
                 StdioPolicy::RedirectForShellTool,
                 env,
+                resource_limits,
+                &sandbox_extras,
             )
             .await?;
-            consume_truncated_output(child, timeout_duration, stdout_stream.clone()).await
+            consume_truncated_output(
+                child,
+                timeout_duration,
+                stdout_stream.clone(),
+                resource_limits.max_output_bytes,
+                &cancellation_token,
+            )
+            .await
         }
         SandboxType::LinuxSeccomp => {
             let ExecParams {
                 command,
                 cwd: command_cwd,
                 env,
+                resource_limits,
+                sandbox_extras,
                 ..
             } = params;
-
             let codex_linux_sandbox_exe = codex_linux_sandbox_exe
                 .as_ref()
                 .ok_or(CodexErr::LandlockSandboxExecutableNotProvided)?;
@@ -131,10 +252,47 @@ pub async fn process_exec_tool_call(
                 sandbox_cwd,
                 StdioPolicy::RedirectForShellTool,
                 env,
+                resource_limits,
+                &sandbox_extras,
             )
             .await?;
 
-            consume_truncated_output(child, timeout_duration, stdout_stream).await
+            consume_truncated_output(
+                child,
+                timeout_duration,
+                stdout_stream,
+                resource_limits.max_output_bytes,
+                &cancellation_token,
+            )
+            .await
+        }
+        SandboxType::WindowsRestrictedToken => {
+            let ExecParams {
+                command,
+                cwd: command_cwd,
+                env,
+                resource_limits,
+                ..
+            } = params;
+            let child = spawn_command_under_windows_sandbox(
+                command,
+                command_cwd,
+                sandbox_policy,
+                sandbox_cwd,
+                StdioPolicy::RedirectForShellTool,
+                env,
+                resource_limits,
+            )
+            .await?;
+
+            consume_truncated_output(
+                child,
+                timeout_duration,
+                stdout_stream,
+                resource_limits.max_output_bytes,
+                &cancellation_token,
+            )
+            .await
         }
     };
     let duration = start.elapsed();
@@ -144,19 +302,21 @@ pub async fn process_exec_tool_call(
             let mut timed_out = raw_output.timed_out;
 
             #[cfg(target_family = "unix")]
+            if !raw_output.cancelled
+                && let Some(signal) = raw_output.exit_status.signal()
             {
-                if let Some(signal) = raw_output.exit_status.signal() {
-                    if signal == TIMEOUT_CODE {
-                        timed_out = true;
-                    } else {
-                        return Err(CodexErr::Sandbox(SandboxErr::Signal(signal)));
-                    }
+                if signal == TIMEOUT_CODE {
+                    timed_out = true;
+                } else {
+                    return Err(CodexErr::Sandbox(SandboxErr::Signal(signal)));
                 }
             }
 
             let mut exit_code = raw_output.exit_status.code().unwrap_or(-1);
             if timed_out {
                 exit_code = EXEC_TIMEOUT_EXIT_CODE;
+            } else if raw_output.cancelled {
+                exit_code = EXEC_CANCELLED_EXIT_CODE;
             }
 
             let stdout = raw_output.stdout.from_utf8_lossy();
@@ -171,6 +331,11 @@ pub async fn process_exec_tool_call(
                 timed_out,
             };
 
+            // Keep whatever the command printed before it was interrupted.
+            if raw_output.cancelled {
+                return Ok(exec_output);
+            }
+
             if timed_out {
                 return Err(CodexErr::Sandbox(SandboxErr::Timeout {
                     output: Box::new(exec_output),
@@ -183,6 +348,9 @@ pub async fn process_exec_tool_call(
                 }));
             }
 
+            if let (Some(cache), Some(slot)) = (output_cache, cache_slot) {
+                cache.insert(slot, &exec_output);
+            }
             Ok(exec_output)
         }
         Err(err) => {
@@ -212,7 +380,7 @@ fn is_likely_sandbox_denied(sandbox_type: SandboxType, exit_code: i32) -> bool {
     true
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct StreamOutput<T> {
     pub text: T,
     pub truncated_after_lines: Option<u32>,
@@ -224,6 +392,7 @@ struct RawExecToolCallOutput {
     pub stderr: StreamOutput<Vec<u8>>,
     pub aggregated_output: StreamOutput<Vec<u8>>,
     pub timed_out: bool,
+    pub cancelled: bool,
 }
 
 impl StreamOutput<String> {
@@ -249,7 +418,7 @@ fn append_all(dst: &mut Vec<u8>, src: &[u8]) {
     dst.extend_from_slice(src);
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct ExecToolCallOutput {
     pub exit_code: i32,
     pub stdout: StreamOutput<String>,
@@ -263,11 +432,19 @@ async fn exec(
     params: ExecParams,
     sandbox_policy: &SandboxPolicy,
     stdout_stream: Option<StdoutStream>,
+    cancellation_token: &CancellationToken,
 ) -> Result<RawExecToolCallOutput> {
     let timeout = params.timeout_duration();
     let ExecParams {
-        command, cwd, env, ..
+        command,
+        cwd,
+        env,
+        tty,
+        resource_limits,
+        ..
     } = params;
+    // `cmd.exe` rejects verbatim (`\\?\`) working directories.
+    let cwd = simplify_verbatim(&cwd);
 
     let (program, args) = command.split_first().ok_or_else(|| {
         CodexErr::Io(io::Error::new(
@@ -275,6 +452,20 @@ async fn exec(
             "command args are empty",
         ))
     })?;
+    if tty {
+        return exec_in_pty(
+            PathBuf::from(program),
+            args.into(),
+            cwd,
+            env,
+            sandbox_policy,
+            timeout,
+            stdout_stream,
+            resource_limits,
+            cancellation_token,
+        )
+        .await;
+    }
     let arg0 = None;
     let child = spawn_child_async(
         PathBuf::from(program),
@@ -284,17 +475,269 @@ async fn exec(
         sandbox_policy,
         StdioPolicy::RedirectForShellTool,
         env,
+        resource_limits,
     )
     .await?;
-    consume_truncated_output(child, timeout, stdout_stream).await
+    consume_truncated_output(
+        child,
+        timeout,
+        stdout_stream,
+        resource_limits.max_output_bytes,
+        cancellation_token,
+    )
+    .await
+}
+
+/// Resolves the program, arguments and environment that run `command` under
+/// `sandbox_type` in a PTY. The PTY spawner cannot set `argv[0]`, so the Linux
+/// sandbox is selected with [`CODEX_LINUX_SANDBOX_ARG1`] instead.
+pub(crate) async fn sandboxed_pty_command(
+    command: Vec<String>,
+    mut env: HashMap<String, String>,
+    sandbox_type: SandboxType,
+    sandbox_policy: &SandboxPolicy,
+    sandbox_cwd: &Path,
+    codex_linux_sandbox_exe: &Option<PathBuf>,
+    sandbox_extras: &SandboxExtras,
+) -> Result<(PathBuf, Vec<String>, HashMap<String, String>)> {
+    match sandbox_type {
+        SandboxType::None => {
+            let (program, args) = command.split_first().ok_or_else(|| {
+                CodexErr::Io(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    "command args are empty",
+                ))
+            })?;
+            Ok((PathBuf::from(program), args.to_vec(), env))
+        }
+        SandboxType::MacosSeatbelt => {
+            let proxy_port = prepare_network_proxy(sandbox_policy, &mut env).await?;
+            let args = create_seatbelt_command_args(
+                command,
+                sandbox_policy,
+                sandbox_cwd,
+                proxy_port,
+                sandbox_extras,
+            );
+            env.insert(CODEX_SANDBOX_ENV_VAR.to_string(), "seatbelt".to_string());
+            Ok((PathBuf::from(MACOS_PATH_TO_SEATBELT_EXECUTABLE), args, env))
+        }
+        SandboxType::LinuxSeccomp => {
+            let codex_linux_sandbox_exe = codex_linux_sandbox_exe
+                .as_ref()
+                .ok_or(CodexErr::LandlockSandboxExecutableNotProvided)?;
+            let proxy_port = prepare_network_proxy(sandbox_policy, &mut env).await?;
+            let mut args = vec![CODEX_LINUX_SANDBOX_ARG1.to_string()];
+            args.extend(create_linux_sandbox_command_args(
+                command,
+                sandbox_policy,
+                sandbox_cwd,
+                proxy_port,
+                sandbox_extras,
+            ));
+            Ok((codex_linux_sandbox_exe.clone(), args, env))
+        }
+        SandboxType::WindowsRestrictedToken => {
+            let (program, args) =
+                windows_sandbox_command(command, sandbox_policy, sandbox_cwd, &mut env)?;
+            Ok((program, args, env))
+        }
+    }
+}
+
+/// Builds the PTY command with exactly `env` as its environment.
+pub(crate) fn pty_command_builder(
+    program: PathBuf,
+    args: Vec<String>,
+    cwd: PathBuf,
+    env: HashMap<String, String>,
+    sandbox_policy: &SandboxPolicy,
+) -> CommandBuilder {
+    let mut builder = CommandBuilder::new(program);
+    builder.args(args);
+    builder.cwd(cwd);
+    builder.env_clear();
+    if !env.contains_key("TERM") {
+        builder.env("TERM", DEFAULT_PTY_TERM);
+    }
+    for (key, value) in env {
+        builder.env(key, value);
+    }
+    if !sandbox_policy.has_full_network_access() {
+        builder.env(CODEX_SANDBOX_NETWORK_DISABLED_ENV_VAR, "1");
+    }
+    builder
+}
+
+/// Runs `program` attached to a pseudo-terminal so programs that check for a
+/// TTY (pagers, progress bars, interactive installers) behave as they would
+/// for a user. The terminal merges stdout and stderr, so all output is
+/// reported as stdout and streamed to the client as raw bytes, escape
+/// sequences included.
+async fn exec_in_pty(
+    program: PathBuf,
+    args: Vec<String>,
+    cwd: PathBuf,
+    env: HashMap<String, String>,
+    sandbox_policy: &SandboxPolicy,
+    timeout: Duration,
+    stdout_stream: Option<StdoutStream>,
+    resource_limits: ResourceLimits,
+    cancellation_token: &CancellationToken,
+) -> Result<RawExecToolCallOutput> {
+    let pair = native_pty_system()
+        .openpty(pty_size_for_env(&env))
+        .map_err(pty_err)?;
+
+    let builder = pty_command_builder(program, args, cwd, env, sandbox_policy);
+    let mut child = pair.slave.spawn_command(builder).map_err(pty_err)?;
+    // Only the child should hold the slave side, so the reader sees EOF once
+    // it exits.
+    drop(pair.slave);
+    let mut killer = child.clone_killer();
+    // The pty child leads its own session, so killing its process group also
+    // stops anything it started.
+    let pid = child.process_id();
+    let mut kill = move || -> io::Result<()> {
+        #[cfg(unix)]
+        if let Some(pid) = pid {
+            crate::spawn::kill_process_group(pid);
+        }
+        killer.kill()
+    };
+    if let Some(pid) = pid
+        && let Err(err) = resource_limits::apply_to_running_process(pid, &resource_limits)
+    {
+        tracing::warn!("failed to apply resource limits: {err}");
+    }
+
+    let mut reader = pair.master.try_clone_reader().map_err(pty_err)?;
+    let (chunk_tx, chunk_rx) = async_channel::unbounded::<Vec<u8>>();
+    let reader_handle = tokio::task::spawn_blocking(move || {
+        let mut buf = [0u8; READ_CHUNK_SIZE];
+        loop {
+            match io::Read::read(&mut reader, &mut buf) {
+                Ok(0) => break,
+                Ok(n) => {
+                    if chunk_tx.send_blocking(buf[..n].to_vec()).is_err() {
+                        break;
+                    }
+                }
+                Err(ref e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                // Linux reports EIO on the master once the child side closes.
+                Err(_) => break,
+            }
+        }
+    });
+    let max_output_bytes = resource_limits.max_output_bytes;
+    let collect_handle = tokio::spawn(async move {
+        let mut buf = Vec::with_capacity(AGGREGATE_BUFFER_INITIAL_CAPACITY);
+        let mut deltas = stdout_stream.map(|stream| OutputDeltaBuffer::new(stream, false));
+        let mut complete = true;
+        loop {
+            let Some(received) = next_or_flush(chunk_rx.recv(), deltas.as_mut()).await else {
+                continue;
+            };
+            let Ok(chunk) = received else {
+                break;
+            };
+            if !complete {
+                continue;
+            }
+            if let Some(deltas) = deltas.as_mut() {
+                deltas.push(&chunk).await;
+            }
+            complete = append_within_limit(&mut buf, &chunk, max_output_bytes);
+        }
+        if let Some(deltas) = deltas.as_mut() {
+            deltas.flush().await;
+        }
+        if !complete {
+            append_all(&mut buf, OUTPUT_TRUNCATED_MARKER.as_bytes());
+        }
+        buf
+    });
+
+    let wait_handle = tokio::task::spawn_blocking(move || child.wait());
+    let (exit_status, timed_out, cancelled) = tokio::select! {
+        result = tokio::time::timeout(timeout, wait_handle) => {
+            match result {
+                Ok(joined) => {
+                    let status = joined.map_err(io::Error::other)??;
+                    (exited_status(status.exit_code() as i32), false, false)
+                }
+                Err(_) => {
+                    kill()?;
+                    (synthetic_exit_status(EXIT_CODE_SIGNAL_BASE + TIMEOUT_CODE), true, false)
+                }
+            }
+        }
+        _ = cancellation_token.cancelled() => {
+            kill()?;
+            (synthetic_exit_status(EXIT_CODE_SIGNAL_BASE + SIGKILL_CODE), false, true)
+        }
+        _ = tokio::signal::ctrl_c() => {
+            kill()?;
+            (synthetic_exit_status(EXIT_CODE_SIGNAL_BASE + SIGKILL_CODE), false, false)
+        }
+    };
+
+    reader_handle.await?;
+    let output = collect_handle.await?;
+    // Keep the master open until the reader is done; closing it hangs up the
+    // terminal.
+    drop(pair.master);
+
+    Ok(RawExecToolCallOutput {
+        exit_status,
+        stdout: StreamOutput {
+            text: output.clone(),
+            truncated_after_lines: None,
+        },
+        stderr: StreamOutput {
+            text: Vec::new(),
+            truncated_after_lines: None,
+        },
+        aggregated_output: StreamOutput {
+            text: output,
+            truncated_after_lines: None,
+        },
+        timed_out,
+        cancelled,
+    })
+}
+
+/// Sizes the terminal from `LINES`/`COLUMNS` in the call's environment so
+/// callers can match the width the output will be shown at.
+fn pty_size_for_env(env: &HashMap<String, String>) -> PtySize {
+    let dimension = |key: &str, default: u16| {
+        env.get(key)
+            .and_then(|value| value.parse::<u16>().ok())
+            .filter(|value| *value > 0)
+            .unwrap_or(default)
+    };
+    PtySize {
+        rows: dimension("LINES", DEFAULT_PTY_ROWS),
+        cols: dimension("COLUMNS", DEFAULT_PTY_COLS),
+        pixel_width: 0,
+        pixel_height: 0,
+    }
+}
+
+fn pty_err(err: anyhow::Error) -> CodexErr {
+    CodexErr::Io(io::Error::other(format!("failed to start pty: {err}")))
 }
 
 /// Consumes the output of a child process, truncating it so it is suitable for
 /// use as the output of a `shell` tool call. Also enforces specified timeout.
+/// Each of stdout, stderr and the aggregated output keeps at most
+/// `max_output_bytes`; the rest is drained and dropped.
 async fn consume_truncated_output(
     mut child: Child,
     timeout: Duration,
     stdout_stream: Option<StdoutStream>,
+    max_output_bytes: Option<usize>,
+    cancellation_token: &CancellationToken,
 ) -> Result<RawExecToolCallOutput> {
     // Both stdout and stderr were configured with `Stdio::piped()`
     // above, therefore `take()` should normally return `Some`.  If it doesn't
@@ -318,32 +761,38 @@ async fn consume_truncated_output(
         stdout_stream.clone(),
         false,
         Some(agg_tx.clone()),
+        max_output_bytes,
     ));
     let stderr_handle = tokio::spawn(read_capped(
         BufReader::new(stderr_reader),
         stdout_stream.clone(),
         true,
         Some(agg_tx.clone()),
+        max_output_bytes,
     ));
 
-    let (exit_status, timed_out) = tokio::select! {
+    let (exit_status, timed_out, cancelled) = tokio::select! {
         result = tokio::time::timeout(timeout, child.wait()) => {
             match result {
                 Ok(status_result) => {
                     let exit_status = status_result?;
-                    (exit_status, false)
+                    (exit_status, false, false)
                 }
                 Err(_) => {
                     // timeout
-                    child.start_kill()?;
+                    kill_child_and_descendants(&mut child)?;
                     // Debatable whether `child.wait().await` should be called here.
-                    (synthetic_exit_status(EXIT_CODE_SIGNAL_BASE + TIMEOUT_CODE), true)
+                    (synthetic_exit_status(EXIT_CODE_SIGNAL_BASE + TIMEOUT_CODE), true, false)
                 }
             }
         }
+        _ = cancellation_token.cancelled() => {
+            kill_child_and_descendants(&mut child)?;
+            (synthetic_exit_status(EXIT_CODE_SIGNAL_BASE + SIGKILL_CODE), false, true)
+        }
         _ = tokio::signal::ctrl_c() => {
-            child.start_kill()?;
-            (synthetic_exit_status(EXIT_CODE_SIGNAL_BASE + SIGKILL_CODE), false)
+            kill_child_and_descendants(&mut child)?;
+            (synthetic_exit_status(EXIT_CODE_SIGNAL_BASE + SIGKILL_CODE), false, false)
         }
     };
 
@@ -353,8 +802,12 @@ async fn consume_truncated_output(
     drop(agg_tx);
 
     let mut combined_buf = Vec::with_capacity(AGGREGATE_BUFFER_INITIAL_CAPACITY);
+    let mut complete = true;
     while let Ok(chunk) = agg_rx.recv().await {
-        append_all(&mut combined_buf, &chunk);
+        complete &= append_within_limit(&mut combined_buf, &chunk, max_output_bytes);
+    }
+    if !complete {
+        append_all(&mut combined_buf, OUTPUT_TRUNCATED_MARKER.as_bytes());
     }
     let aggregated_output = StreamOutput {
         text: combined_buf,
@@ -367,56 +820,62 @@ async fn consume_truncated_output(
         stderr,
         aggregated_output,
         timed_out,
+        cancelled,
     })
 }
 
+/// Kills `child` and, on unix, the process group it leads, so that background
+/// jobs do not outlive the command and keep its output pipes open.
+fn kill_child_and_descendants(child: &mut Child) -> io::Result<()> {
+    #[cfg(unix)]
+    if let Some(pid) = child.id() {
+        crate::spawn::kill_process_group(pid);
+    }
+    child.start_kill()
+}
+
 async fn read_capped<R: AsyncRead + Unpin + Send + 'static>(
     mut reader: R,
     stream: Option<StdoutStream>,
     is_stderr: bool,
     aggregate_tx: Option<Sender<Vec<u8>>>,
+    max_bytes: Option<usize>,
 ) -> io::Result<StreamOutput<Vec<u8>>> {
     let mut buf = Vec::with_capacity(AGGREGATE_BUFFER_INITIAL_CAPACITY);
     let mut tmp = [0u8; READ_CHUNK_SIZE];
-    let mut emitted_deltas: usize = 0;
-
-    // No caps: append all bytes
+    let mut deltas = stream.map(|stream| OutputDeltaBuffer::new(stream, is_stderr));
+    let mut complete = true;
 
     loop {
-        let n = reader.read(&mut tmp).await?;
+        let Some(read) = next_or_flush(reader.read(&mut tmp), deltas.as_mut()).await else {
+            continue;
+        };
+        let n = read?;
         if n == 0 {
             break;
         }
+        if !complete {
+            // Keep draining so the child does not block on a full pipe.
+            continue;
+        }
 
-        if let Some(stream) = &stream
-            && emitted_deltas < MAX_EXEC_OUTPUT_DELTAS_PER_CALL
-        {
-            let chunk = tmp[..n].to_vec();
-            let msg = EventMsg::ExecCommandOutputDelta(ExecCommandOutputDeltaEvent {
-                call_id: stream.call_id.clone(),
-                stream: if is_stderr {
-                    ExecOutputStream::Stderr
-                } else {
-                    ExecOutputStream::Stdout
-                },
-                chunk,
-            });
-            let event = Event {
-                id: stream.sub_id.clone(),
-                msg,
-            };
-            #[allow(clippy::let_unit_value)]
-            let _ = stream.tx_event.send(event).await;
-            emitted_deltas += 1;
+        if let Some(deltas) = deltas.as_mut() {
+            deltas.push(&tmp[..n]).await;
         }
 
         if let Some(tx) = &aggregate_tx {
             let _ = tx.send(tmp[..n].to_vec()).await;
         }
 
-        append_all(&mut buf, &tmp[..n]);
+        complete = append_within_limit(&mut buf, &tmp[..n], max_bytes);
         // Continue reading to EOF to avoid back-pressure
     }
+    if let Some(deltas) = deltas.as_mut() {
+        deltas.flush().await;
+    }
+    if !complete {
+        append_all(&mut buf, OUTPUT_TRUNCATED_MARKER.as_bytes());
+    }
 
     Ok(StreamOutput {
         text: buf,
@@ -424,6 +883,133 @@ async fn read_capped<R: AsyncRead + Unpin + Send + 'static>(
     })
 }
 
+/// Coalesces a command's live output into `ExecCommandOutputDelta` events:
+/// output is held until `flush_interval_ms` has passed since the first
+/// pending byte or `max_chunk_bytes` have accumulated, whichever comes first.
+struct OutputDeltaBuffer {
+    stream: StdoutStream,
+    is_stderr: bool,
+    pending: Vec<u8>,
+    pending_since: Option<tokio::time::Instant>,
+    emitted_deltas: usize,
+}
+
+impl OutputDeltaBuffer {
+    fn new(stream: StdoutStream, is_stderr: bool) -> Self {
+        Self {
+            stream,
+            is_stderr,
+            pending: Vec::new(),
+            pending_since: None,
+            emitted_deltas: 0,
+        }
+    }
+
+    fn flush_interval(&self) -> Duration {
+        Duration::from_millis(self.stream.streaming.flush_interval_ms)
+    }
+
+    /// When the pending output is due, or `None` if nothing is pending.
+    fn flush_deadline(&self) -> Option<tokio::time::Instant> {
+        self.pending_since
+            .map(|since| since + self.flush_interval())
+    }
+
+    async fn push(&mut self, chunk: &[u8]) {
+        if self.emitted_deltas >= MAX_EXEC_OUTPUT_DELTAS_PER_CALL {
+            return;
+        }
+        if self.pending.is_empty() {
+            self.pending_since = Some(tokio::time::Instant::now());
+        }
+        self.pending.extend_from_slice(chunk);
+        let due = self
+            .pending_since
+            .is_some_and(|since| since.elapsed() >= self.flush_interval());
+        if due || self.pending.len() >= self.stream.streaming.max_chunk_bytes {
+            self.flush().await;
+        }
+    }
+
+    async fn flush(&mut self) {
+        self.pending_since = None;
+        let pending = std::mem::take(&mut self.pending);
+        let max_chunk_bytes = self.stream.streaming.max_chunk_bytes.max(1);
+        for chunk in pending.chunks(max_chunk_bytes) {
+            if self.emitted_deltas >= MAX_EXEC_OUTPUT_DELTAS_PER_CALL {
+                break;
+            }
+            wait_for_event_backlog(&self.stream.tx_event).await;
+            send_output_delta(&self.stream, self.is_stderr, chunk.to_vec()).await;
+            self.emitted_deltas += 1;
+        }
+    }
+}
+
+/// Awaits `next`, unless pending output falls due first, in which case it is
+/// flushed and `None` is returned so the caller can poll again. `next` must be
+/// cancel safe.
+async fn next_or_flush<T>(
+    next: impl Future<Output = T>,
+    deltas: Option<&mut OutputDeltaBuffer>,
+) -> Option<T> {
+    let Some(deltas) = deltas else {
+        return Some(next.await);
+    };
+    let Some(deadline) = deltas.flush_deadline() else {
+        return Some(next.await);
+    };
+    tokio::select! {
+        value = next => Some(value),
+        _ = tokio::time::sleep_until(deadline) => {
+            deltas.flush().await;
+            None
+        }
+    }
+}
+
+/// Holds the next delta while the client is behind. Not reading leaves the
+/// command blocked on a full pipe, which throttles it to the client's pace.
+async fn wait_for_event_backlog(tx_event: &Sender<Event>) {
+    let started = Instant::now();
+    while tx_event.len() >= MAX_PENDING_EVENTS
+        && !tx_event.is_closed()
+        && started.elapsed() < MAX_BACKPRESSURE_WAIT
+    {
+        tokio::time::sleep(BACKPRESSURE_POLL_INTERVAL).await;
+    }
+}
+
+async fn send_output_delta(stream: &StdoutStream, is_stderr: bool, chunk: Vec<u8>) {
+    let msg = EventMsg::ExecCommandOutputDelta(ExecCommandOutputDeltaEvent {
+        call_id: stream.call_id.clone(),
+        stream: if is_stderr {
+            ExecOutputStream::Stderr
+        } else {
+            ExecOutputStream::Stdout
+        },
+        chunk,
+    });
+    let event = Event {
+        id: stream.sub_id.clone(),
+        msg,
+    };
+    #[allow(clippy::let_unit_value)]
+    let _ = stream.tx_event.send(event).await;
+}
+
+/// Builds the status of a process that exited normally with `code`.
+#[cfg(unix)]
+fn exited_status(code: i32) -> ExitStatus {
+    synthetic_exit_status((code & 0xff) << 8)
+}
+
+#[cfg(windows)]
+fn exited_status(code: i32) -> ExitStatus {
+    use std::os::windows::process::ExitStatusExt;
+    ExitStatus::from_raw(code as u32)
+}
+
 #[cfg(unix)]
 fn synthetic_exit_status(code: i32) -> ExitStatus {
     use std::os::unix::process::ExitStatusExt;
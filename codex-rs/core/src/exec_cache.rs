@@ -0,0 +1,302 @@
+//! Opt-in cache for the output of read-only commands such as `cat`, `rg` and
+//! `ls`, enabled with `exec.cache_read_only_commands`.
+//!
+//! Models exploring a large repository tend to rerun the same searches turn
+//! after turn. A command is only cached when it is known to be safe and
+//! `parse_command` classifies every part of it as a read, a listing or a
+//! search. Entries are keyed by argv and working directory and remember a
+//! fingerprint of the modification times of the paths the command looks at,
+//! so touching any of them runs the command again.
+
+use std::collections::HashMap;
+use std::hash::DefaultHasher;
+use std::hash::Hash;
+use std::hash::Hasher;
+use std::path::Path;
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+use crate::command_safety::command_pattern::bash_lc_commands;
+use crate::exec::ExecParams;
+use crate::exec::ExecToolCallOutput;
+use crate::is_safe_command::is_known_safe_command;
+use crate::parse_command::ParsedCommand;
+use crate::parse_command::parse_command;
+use crate::path_utils::resolve_path;
+
+/// Entries kept before the least recently used one is dropped.
+const MAX_ENTRIES: usize = 64;
+
+/// Files and directories a fingerprint may stat. Commands that look at
+/// larger trees are not cached: checking them would cost about as much as
+/// running them.
+const MAX_FINGERPRINT_ENTRIES: usize = 20_000;
+
+#[derive(Default)]
+pub struct ExecOutputCache {
+    state: Mutex<CacheState>,
+}
+
+#[derive(Default)]
+struct CacheState {
+    entries: HashMap<CacheKey, CacheEntry>,
+    clock: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct CacheKey {
+    command: Vec<String>,
+    cwd: PathBuf,
+}
+
+struct CacheEntry {
+    fingerprint: u64,
+    output: ExecToolCallOutput,
+    last_used: u64,
+}
+
+/// Where the output of a cacheable command is looked up and stored.
+#[derive(Debug)]
+pub(crate) struct CacheSlot {
+    key: CacheKey,
+    fingerprint: u64,
+}
+
+impl ExecOutputCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The slot for `params`, or `None` when the command is not cacheable.
+    /// Stats the files the command looks at, so it runs on a blocking thread.
+    pub(crate) async fn slot_for(params: &ExecParams) -> Option<CacheSlot> {
+        if params.tty || !is_cacheable(&params.command) {
+            return None;
+        }
+        let key = CacheKey {
+            command: params.command.clone(),
+            cwd: params.cwd.clone(),
+        };
+        tokio::task::spawn_blocking(move || {
+            let fingerprint = fingerprint(&key.command, &key.cwd)?;
+            Some(CacheSlot { key, fingerprint })
+        })
+        .await
+        .ok()
+        .flatten()
+    }
+
+    /// The output stored in `slot`, unless the files it depends on changed.
+    pub(crate) fn get(&self, slot: &CacheSlot) -> Option<ExecToolCallOutput> {
+        let mut state = self.lock();
+        state.clock += 1;
+        let clock = state.clock;
+        let entry = state.entries.get_mut(&slot.key)?;
+        if entry.fingerprint != slot.fingerprint {
+            return None;
+        }
+        entry.last_used = clock;
+        Some(entry.output.clone())
+    }
+
+    pub(crate) fn insert(&self, slot: CacheSlot, output: &ExecToolCallOutput) {
+        let mut state = self.lock();
+        state.clock += 1;
+        let last_used = state.clock;
+        state.entries.insert(
+            slot.key,
+            CacheEntry {
+                fingerprint: slot.fingerprint,
+                output: output.clone(),
+                last_used,
+            },
+        );
+        if state.entries.len() > MAX_ENTRIES
+            && let Some(oldest) = state
+                .entries
+                .iter()
+                .min_by_key(|(_, entry)| entry.last_used)
+                .map(|(key, _)| key.clone())
+        {
+            state.entries.remove(&oldest);
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, CacheState> {
+        match self.state.lock() {
+            Ok(guard) => guard,
+            Err(poisoned) => poisoned.into_inner(),
+        }
+    }
+}
+
+fn is_cacheable(command: &[String]) -> bool {
+    is_known_safe_command(command)
+        && parse_command(command)
+            .iter()
+            .all(|parsed| !matches!(parsed, ParsedCommand::Unknown { .. }))
+}
+
+/// Hashes the modification time and size of every operand of `command` that
+/// exists, recursing into directories, and records the ones that do not
+/// exist so that creating them invalidates the entry. Operands are every
+/// argument that is not a flag; patterns such as `rg`'s simply never exist.
+/// When no operand exists the whole of `cwd` is covered instead, as for a
+/// bare `rg foo`. Returns `None` when the tree is too large.
+fn fingerprint(command: &[String], cwd: &Path) -> Option<u64> {
+    let commands = bash_lc_commands(command).unwrap_or_else(|| vec![command.to_vec()]);
+    let operands: Vec<PathBuf> = commands
+        .iter()
+        .flat_map(|cmd| cmd.iter().skip(1))
+        .filter(|arg| !arg.starts_with('-'))
+        .map(|arg| resolve_path(cwd, Path::new(arg)))
+        .collect();
+
+    let mut hasher = DefaultHasher::new();
+    let mut budget = MAX_FINGERPRINT_ENTRIES;
+    let mut any_exists = false;
+    for operand in &operands {
+        any_exists |= hash_tree(operand, &mut hasher, &mut budget)?;
+    }
+    if !any_exists {
+        hash_tree(cwd, &mut hasher, &mut budget)?;
+    }
+    Some(hasher.finish())
+}
+
+/// Hashes `path` and, for a directory, everything below it except `.git`.
+/// Returns whether `path` exists, or `None` once `budget` runs out.
+fn hash_tree(path: &Path, hasher: &mut DefaultHasher, budget: &mut usize) -> Option<bool> {
+    *budget = budget.checked_sub(1)?;
+    path.hash(hasher);
+    // Do not follow symlinks so that a link cycle cannot recurse forever.
+    let Ok(metadata) = std::fs::symlink_metadata(path) else {
+        false.hash(hasher);
+        return Some(false);
+    };
+    true.hash(hasher);
+    metadata.len().hash(hasher);
+    metadata.modified().ok().hash(hasher);
+    if metadata.is_dir() {
+        let Ok(entries) = std::fs::read_dir(path) else {
+            return Some(true);
+        };
+        let mut children: Vec<PathBuf> = entries
+            .flatten()
+            .filter(|entry| entry.file_name() != ".git")
+            .map(|entry| entry.path())
+            .collect();
+        children.sort();
+        for child in &children {
+            hash_tree(child, hasher, budget)?;
+        }
+    }
+    Some(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config_types::ResourceLimits;
+    use crate::config_types::SandboxExtras;
+    use crate::exec::StreamOutput;
+    use pretty_assertions::assert_eq;
+    use std::time::Duration;
+    use tempfile::TempDir;
+
+    fn params(command: &[&str], cwd: &Path) -> ExecParams {
+        ExecParams {
+            command: command.iter().map(|s| (*s).to_string()).collect(),
+            cwd: cwd.to_path_buf(),
+            timeout_ms: None,
+            env: HashMap::new(),
+            with_escalated_permissions: None,
+            justification: None,
+            tty: false,
+            resource_limits: ResourceLimits::default(),
+            sandbox_extras: SandboxExtras::default(),
+        }
+    }
+
+    fn output(text: &str) -> ExecToolCallOutput {
+        ExecToolCallOutput {
+            exit_code: 0,
+            stdout: StreamOutput::new(text.to_string()),
+            stderr: StreamOutput::new(String::new()),
+            aggregated_output: StreamOutput::new(text.to_string()),
+            duration: Duration::from_millis(5),
+            timed_out: false,
+        }
+    }
+
+    #[test]
+    fn only_read_only_commands_are_cacheable() {
+        let vec = |words: &[&str]| words.iter().map(|s| (*s).to_string()).collect::<Vec<_>>();
+        assert!(is_cacheable(&vec(&["cat", "README.md"])));
+        assert!(is_cacheable(&vec(&["bash", "-lc", "ls -la src"])));
+        assert!(!is_cacheable(&vec(&["cargo", "test"])));
+        assert!(!is_cacheable(&vec(&["find", ".", "-delete"])));
+        assert!(!is_cacheable(&vec(&["bash", "-lc", "cat a.txt > b.txt"])));
+    }
+
+    #[tokio::test]
+    async fn modifying_a_file_invalidates_its_entry() {
+        let dir = TempDir::new().expect("tempdir");
+        let file = dir.path().join("notes.txt");
+        std::fs::write(&file, "one").expect("write file");
+        let cache = ExecOutputCache::new();
+        let params = params(&["cat", "notes.txt"], dir.path());
+
+        let slot = ExecOutputCache::slot_for(&params).await.expect("cacheable");
+        assert!(cache.get(&slot).is_none());
+        cache.insert(slot, &output("one"));
+
+        let slot = ExecOutputCache::slot_for(&params).await.expect("cacheable");
+        assert_eq!(
+            cache.get(&slot).map(|out| out.stdout.text),
+            Some("one".to_string())
+        );
+
+        // A different size changes the fingerprint even within the same
+        // mtime tick.
+        std::fs::write(&file, "two!").expect("rewrite file");
+        let slot = ExecOutputCache::slot_for(&params).await.expect("cacheable");
+        assert!(cache.get(&slot).is_none());
+    }
+
+    #[tokio::test]
+    async fn creating_a_missing_operand_invalidates_the_entry() {
+        let dir = TempDir::new().expect("tempdir");
+        let cache = ExecOutputCache::new();
+        let params = params(&["ls", "out"], dir.path());
+
+        let slot = ExecOutputCache::slot_for(&params).await.expect("cacheable");
+        cache.insert(slot, &output(""));
+
+        std::fs::create_dir(dir.path().join("out")).expect("create dir");
+        let slot = ExecOutputCache::slot_for(&params).await.expect("cacheable");
+        assert!(cache.get(&slot).is_none());
+    }
+
+    #[tokio::test]
+    async fn least_recently_used_entries_are_evicted() {
+        let dir = TempDir::new().expect("tempdir");
+        let cache = ExecOutputCache::new();
+        for i in 0..=MAX_ENTRIES {
+            let name = format!("{i}.txt");
+            let slot = ExecOutputCache::slot_for(&params(&["cat", &name], dir.path()))
+                .await
+                .expect("cacheable");
+            cache.insert(slot, &output(&name));
+        }
+        let first = ExecOutputCache::slot_for(&params(&["cat", "0.txt"], dir.path()))
+            .await
+            .expect("cacheable");
+        assert!(cache.get(&first).is_none());
+        let last_name = format!("{MAX_ENTRIES}.txt");
+        let last = ExecOutputCache::slot_for(&params(&["cat", &last_name], dir.path()))
+            .await
+            .expect("cacheable");
+        assert!(cache.get(&last).is_some());
+    }
+}
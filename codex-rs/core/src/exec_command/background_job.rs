@@ -0,0 +1,236 @@
+use std::sync::Arc;
+use std::sync::Mutex as StdMutex;
+
+use tokio::sync::broadcast;
+use tokio::sync::oneshot;
+use tokio::time::Duration;
+use tokio::time::Instant;
+use tokio::time::timeout;
+
+use crate::exec_command::session_id::SessionId;
+use crate::truncate::truncate_middle;
+
+/// Most output kept per job; older output is dropped once a job has printed
+/// more than this without being read.
+const MAX_JOB_LOG_BYTES: usize = 1024 * 1024;
+
+/// An `exec_command` session started with `run_in_background`. Its output is
+/// buffered so it can be read with `job_output` after the turn moves on.
+#[derive(Debug)]
+pub(crate) struct BackgroundJob {
+    cmd: String,
+    started: Instant,
+    log: Arc<StdMutex<JobLog>>,
+}
+
+#[derive(Debug, Default)]
+struct JobLog {
+    /// Buffered output, the tail of everything the job printed.
+    output: Vec<u8>,
+    /// Total bytes printed; `output` holds the last `output.len()` of them.
+    total: usize,
+    /// How many of `total` bytes have been returned by `job_output`.
+    read: usize,
+    exit_code: Option<i32>,
+    finished_at: Option<Instant>,
+    killed: bool,
+}
+
+impl JobLog {
+    fn push(&mut self, chunk: &[u8]) {
+        self.output.extend_from_slice(chunk);
+        self.total += chunk.len();
+        if self.output.len() > MAX_JOB_LOG_BYTES {
+            let excess = self.output.len() - MAX_JOB_LOG_BYTES;
+            self.output.drain(..excess);
+        }
+    }
+
+    /// Output not yet returned by `job_output`, marking it read.
+    fn take_unread(&mut self) -> Vec<u8> {
+        let buffered_from = self.total - self.output.len();
+        let start = self.read.max(buffered_from) - buffered_from;
+        self.read = self.total;
+        self.output[start..].to_vec()
+    }
+
+    fn finish(&mut self, exit_code: Option<i32>) {
+        self.exit_code = exit_code;
+        self.finished_at = Some(Instant::now());
+    }
+}
+
+/// State of a background job as reported to the model.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum JobState {
+    Running,
+    Exited(i32),
+    Killed,
+}
+
+impl BackgroundJob {
+    /// Starts buffering the session's output and watching for its exit.
+    pub(crate) fn spawn(
+        cmd: String,
+        mut output_rx: broadcast::Receiver<Vec<u8>>,
+        mut exit_rx: oneshot::Receiver<i32>,
+    ) -> Self {
+        let log = Arc::new(StdMutex::new(JobLog::default()));
+        let collector_log = log.clone();
+        tokio::spawn(async move {
+            let push = |chunk: &[u8]| {
+                if let Ok(mut log) = collector_log.lock() {
+                    log.push(chunk);
+                }
+            };
+            let exit_code = loop {
+                tokio::select! {
+                    biased;
+                    exit = &mut exit_rx => {
+                        // Pull output still buffered in the channel.
+                        while let Ok(Ok(chunk)) =
+                            timeout(Duration::from_millis(25), output_rx.recv()).await
+                        {
+                            push(&chunk);
+                        }
+                        break exit.ok();
+                    }
+                    chunk = output_rx.recv() => match chunk {
+                        Ok(chunk) => push(&chunk),
+                        Err(broadcast::error::RecvError::Lagged(_)) => {}
+                        Err(broadcast::error::RecvError::Closed) => break exit_rx.await.ok(),
+                    },
+                }
+            };
+            if let Ok(mut log) = collector_log.lock() {
+                log.finish(exit_code);
+            }
+        });
+        Self {
+            cmd,
+            started: Instant::now(),
+            log,
+        }
+    }
+
+    pub(crate) fn cmd(&self) -> &str {
+        &self.cmd
+    }
+
+    pub(crate) fn state(&self) -> JobState {
+        let Ok(log) = self.log.lock() else {
+            return JobState::Running;
+        };
+        if log.killed {
+            JobState::Killed
+        } else if log.finished_at.is_some() {
+            JobState::Exited(log.exit_code.unwrap_or(-1))
+        } else {
+            JobState::Running
+        }
+    }
+
+    /// Time the job has been running, or ran for if it has finished.
+    pub(crate) fn elapsed(&self) -> Duration {
+        let finished_at = self.log.lock().ok().and_then(|log| log.finished_at);
+        finished_at
+            .unwrap_or_else(Instant::now)
+            .duration_since(self.started)
+    }
+
+    pub(crate) fn mark_killed(&self) {
+        if let Ok(mut log) = self.log.lock() {
+            log.killed = true;
+            if log.finished_at.is_none() {
+                log.finished_at = Some(Instant::now());
+            }
+        }
+    }
+
+    /// Output printed since the previous call, truncated in the middle to
+    /// `max_output_tokens`.
+    pub(crate) fn read_output(&self, session_id: SessionId, max_output_tokens: u64) -> JobOutput {
+        let unread = self
+            .log
+            .lock()
+            .map(|mut log| log.take_unread())
+            .unwrap_or_default();
+        let cap_bytes = max_output_tokens.saturating_mul(4).min(usize::MAX as u64) as usize;
+        let (output, original_token_count) =
+            truncate_middle(&String::from_utf8_lossy(&unread), cap_bytes);
+        JobOutput {
+            session_id,
+            state: self.state(),
+            elapsed: self.elapsed(),
+            original_token_count,
+            output,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct JobOutput {
+    session_id: SessionId,
+    state: JobState,
+    elapsed: Duration,
+    original_token_count: Option<u64>,
+    output: String,
+}
+
+impl JobOutput {
+    pub(crate) fn to_text_output(&self) -> String {
+        let status = job_status_text(self.session_id, self.state, self.elapsed);
+        let truncation_status = match self.original_token_count {
+            Some(tokens) => {
+                format!("\nWarning: truncated output (original token count: {tokens})")
+            }
+            None => "".to_string(),
+        };
+        format!(
+            "{status}{truncation_status}\nOutput since last read:\n{output}",
+            output = self.output
+        )
+    }
+}
+
+/// One-line summary such as `Job 3 running for 12.000 seconds`.
+pub(crate) fn job_status_text(session_id: SessionId, state: JobState, elapsed: Duration) -> String {
+    let id = session_id.0;
+    let secs = elapsed.as_secs_f32();
+    match state {
+        JobState::Running => format!("Job {id} running for {secs:.3} seconds"),
+        JobState::Exited(code) => {
+            format!("Job {id} exited with code {code} after {secs:.3} seconds")
+        }
+        JobState::Killed => format!("Job {id} killed after {secs:.3} seconds"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn job_log_returns_only_unread_output() {
+        let mut log = JobLog::default();
+        log.push(b"first\n");
+        assert_eq!(log.take_unread(), b"first\n");
+        assert_eq!(log.take_unread(), b"");
+
+        log.push(b"second\n");
+        log.push(b"third\n");
+        assert_eq!(log.take_unread(), b"second\nthird\n");
+    }
+
+    #[test]
+    fn job_log_keeps_the_tail_of_large_output() {
+        let mut log = JobLog::default();
+        log.push(&vec![b'a'; MAX_JOB_LOG_BYTES]);
+        log.push(b"tail");
+
+        let unread = log.take_unread();
+        assert_eq!(unread.len(), MAX_JOB_LOG_BYTES);
+        assert!(unread.ends_with(b"atail"));
+    }
+}
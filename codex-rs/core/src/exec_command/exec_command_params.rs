@@ -18,6 +18,19 @@ pub struct ExecCommandParams {
 
     #[serde(default = "default_login")]
     pub(crate) login: bool,
+
+    /// Return immediately and keep the process running as a background job
+    /// whose output is buffered for `job_output`.
+    #[serde(default)]
+    pub(crate) run_in_background: bool,
+}
+
+impl ExecCommandParams {
+    /// The argv that runs `cmd` in `shell`, as seen by approval and sandboxing.
+    pub(crate) fn command(&self) -> Vec<String> {
+        let shell_mode = if self.login { "-lc" } else { "-c" };
+        vec![self.shell.clone(), shell_mode.to_string(), self.cmd.clone()]
+    }
 }
 
 fn default_yield_time() -> u64 {
@@ -46,6 +59,14 @@ pub struct WriteStdinParams {
 
     #[serde(default = "write_stdin_default_max_output_tokens")]
     pub(crate) max_output_tokens: u64,
+
+    /// Resize the session's terminal before writing. A missing dimension
+    /// keeps its current value.
+    #[serde(default)]
+    pub(crate) rows: Option<u16>,
+
+    #[serde(default)]
+    pub(crate) cols: Option<u16>,
 }
 
 fn write_stdin_default_yield_time_ms() -> u64 {
@@ -55,3 +76,20 @@ fn write_stdin_default_yield_time_ms() -> u64 {
 fn write_stdin_default_max_output_tokens() -> u64 {
     10_000
 }
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct JobOutputParams {
+    pub(crate) job_id: SessionId,
+
+    #[serde(default = "job_output_default_max_output_tokens")]
+    pub(crate) max_output_tokens: u64,
+}
+
+fn job_output_default_max_output_tokens() -> u64 {
+    10_000
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct KillJobParams {
+    pub(crate) job_id: SessionId,
+}
@@ -1,5 +1,7 @@
 use std::sync::Mutex as StdMutex;
 
+use portable_pty::MasterPty;
+use portable_pty::PtySize;
 use tokio::sync::broadcast;
 use tokio::sync::mpsc;
 use tokio::task::JoinHandle;
@@ -27,9 +29,23 @@ pub(crate) struct ExecCommandSession {
 
     /// Tracks whether the underlying process has exited.
     exit_status: std::sync::Arc<std::sync::atomic::AtomicBool>,
+
+    /// PTY master, kept open for the life of the session so the terminal can
+    /// be resized.
+    master: StdMutex<PtyMaster>,
+}
+
+/// `MasterPty` is not `Debug`, so wrap it to keep the session derivable.
+struct PtyMaster(Box<dyn MasterPty + Send>);
+
+impl std::fmt::Debug for PtyMaster {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("PtyMaster")
+    }
 }
 
 impl ExecCommandSession {
+    #[allow(clippy::too_many_arguments)]
     pub(crate) fn new(
         writer_tx: mpsc::Sender<Vec<u8>>,
         output_tx: broadcast::Sender<Vec<u8>>,
@@ -38,6 +54,7 @@ impl ExecCommandSession {
         writer_handle: JoinHandle<()>,
         wait_handle: JoinHandle<()>,
         exit_status: std::sync::Arc<std::sync::atomic::AtomicBool>,
+        master: Box<dyn MasterPty + Send>,
     ) -> (Self, broadcast::Receiver<Vec<u8>>) {
         let initial_output_rx = output_tx.subscribe();
         (
@@ -49,6 +66,7 @@ impl ExecCommandSession {
                 writer_handle: StdMutex::new(Some(writer_handle)),
                 wait_handle: StdMutex::new(Some(wait_handle)),
                 exit_status,
+                master: StdMutex::new(PtyMaster(master)),
             },
             initial_output_rx,
         )
@@ -65,6 +83,21 @@ impl ExecCommandSession {
     pub(crate) fn has_exited(&self) -> bool {
         self.exit_status.load(std::sync::atomic::Ordering::SeqCst)
     }
+
+    /// Resizes the terminal; a `None` dimension keeps its current value. The
+    /// child receives SIGWINCH and redraws at the new size.
+    pub(crate) fn resize(&self, rows: Option<u16>, cols: Option<u16>) -> anyhow::Result<()> {
+        let master = self
+            .master
+            .lock()
+            .map_err(|_| anyhow::anyhow!("pty master lock poisoned"))?;
+        let current = master.0.get_size()?;
+        master.0.resize(PtySize {
+            rows: rows.unwrap_or(current.rows),
+            cols: cols.unwrap_or(current.cols),
+            ..current
+        })
+    }
 }
 
 impl Drop for ExecCommandSession {
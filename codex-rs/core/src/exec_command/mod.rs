@@ -1,3 +1,4 @@
+mod background_job;
 mod exec_command_params;
 mod exec_command_session;
 mod responses_api;
@@ -5,10 +6,19 @@ mod session_id;
 mod session_manager;
 
 pub use exec_command_params::ExecCommandParams;
+pub use exec_command_params::JobOutputParams;
+pub use exec_command_params::KillJobParams;
 pub use exec_command_params::WriteStdinParams;
 pub(crate) use exec_command_session::ExecCommandSession;
 pub use responses_api::EXEC_COMMAND_TOOL_NAME;
+pub use responses_api::JOB_OUTPUT_TOOL_NAME;
+pub use responses_api::KILL_JOB_TOOL_NAME;
+pub use responses_api::LIST_JOBS_TOOL_NAME;
 pub use responses_api::WRITE_STDIN_TOOL_NAME;
 pub use responses_api::create_exec_command_tool_for_responses_api;
+pub use responses_api::create_job_output_tool_for_responses_api;
+pub use responses_api::create_kill_job_tool_for_responses_api;
+pub use responses_api::create_list_jobs_tool_for_responses_api;
 pub use responses_api::create_write_stdin_tool_for_responses_api;
+pub(crate) use session_manager::ExecCommandSpawn;
 pub use session_manager::SessionManager as ExecSessionManager;
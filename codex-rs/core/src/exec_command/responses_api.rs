@@ -5,6 +5,9 @@ use crate::openai_tools::ResponsesApiTool;
 
 pub const EXEC_COMMAND_TOOL_NAME: &str = "exec_command";
 pub const WRITE_STDIN_TOOL_NAME: &str = "write_stdin";
+pub const LIST_JOBS_TOOL_NAME: &str = "list_jobs";
+pub const JOB_OUTPUT_TOOL_NAME: &str = "job_output";
+pub const KILL_JOB_TOOL_NAME: &str = "kill_job";
 
 pub fn create_exec_command_tool_for_responses_api() -> ResponsesApiTool {
     let mut properties = BTreeMap::<String, JsonSchema>::new();
@@ -40,6 +43,15 @@ pub fn create_exec_command_tool_for_responses_api() -> ResponsesApiTool {
             ),
         },
     );
+    properties.insert(
+        "run_in_background".to_string(),
+        JsonSchema::Boolean {
+            description: Some(
+                "Return immediately and keep the process running as a background job, e.g. for a dev server. Read its output later with job_output. Defaults to false."
+                    .to_string(),
+            ),
+        },
+    );
 
     ResponsesApiTool {
         name: EXEC_COMMAND_TOOL_NAME.to_owned(),
@@ -49,7 +61,7 @@ pub fn create_exec_command_tool_for_responses_api() -> ResponsesApiTool {
         parameters: JsonSchema::Object {
             properties,
             required: Some(vec!["cmd".to_string()]),
-            additional_properties: Some(false),
+            additional_properties: Some(false.into()),
         },
     }
 }
@@ -82,6 +94,22 @@ pub fn create_write_stdin_tool_for_responses_api() -> ResponsesApiTool {
             description: Some("The maximum number of tokens to output.".to_string()),
         },
     );
+    properties.insert(
+        "rows".to_string(),
+        JsonSchema::Number {
+            description: Some(
+                "Resize the session's terminal to this many rows before writing.".to_string(),
+            ),
+        },
+    );
+    properties.insert(
+        "cols".to_string(),
+        JsonSchema::Number {
+            description: Some(
+                "Resize the session's terminal to this many columns before writing.".to_string(),
+            ),
+        },
+    );
 
     ResponsesApiTool {
         name: WRITE_STDIN_TOOL_NAME.to_owned(),
@@ -92,7 +120,70 @@ Can write control characters (\u0003 for Ctrl-C), or an empty string to just pol
         parameters: JsonSchema::Object {
             properties,
             required: Some(vec!["session_id".to_string(), "chars".to_string()]),
-            additional_properties: Some(false),
+            additional_properties: Some(false.into()),
+        },
+    }
+}
+
+pub fn create_list_jobs_tool_for_responses_api() -> ResponsesApiTool {
+    ResponsesApiTool {
+        name: LIST_JOBS_TOOL_NAME.to_owned(),
+        description: "List background jobs started with exec_command(run_in_background=true), with their state and command."
+            .to_string(),
+        strict: false,
+        parameters: JsonSchema::Object {
+            properties: BTreeMap::new(),
+            required: None,
+            additional_properties: Some(false.into()),
+        },
+    }
+}
+
+pub fn create_job_output_tool_for_responses_api() -> ResponsesApiTool {
+    let mut properties = BTreeMap::<String, JsonSchema>::new();
+    properties.insert(
+        "job_id".to_string(),
+        JsonSchema::Number {
+            description: Some("The ID of the background job.".to_string()),
+        },
+    );
+    properties.insert(
+        "max_output_tokens".to_string(),
+        JsonSchema::Number {
+            description: Some("The maximum number of tokens to output.".to_string()),
+        },
+    );
+
+    ResponsesApiTool {
+        name: JOB_OUTPUT_TOOL_NAME.to_owned(),
+        description: "Return the state of a background job and the output it printed since the last job_output call. Does not wait."
+            .to_string(),
+        strict: false,
+        parameters: JsonSchema::Object {
+            properties,
+            required: Some(vec!["job_id".to_string()]),
+            additional_properties: Some(false.into()),
+        },
+    }
+}
+
+pub fn create_kill_job_tool_for_responses_api() -> ResponsesApiTool {
+    let mut properties = BTreeMap::<String, JsonSchema>::new();
+    properties.insert(
+        "job_id".to_string(),
+        JsonSchema::Number {
+            description: Some("The ID of the background job.".to_string()),
+        },
+    );
+
+    ResponsesApiTool {
+        name: KILL_JOB_TOOL_NAME.to_owned(),
+        description: "Kill a running background job and return its remaining output.".to_string(),
+        strict: false,
+        parameters: JsonSchema::Object {
+            properties,
+            required: Some(vec!["job_id".to_string()]),
+            additional_properties: Some(false.into()),
         },
     }
 }
@@ -1,6 +1,7 @@
 use std::collections::HashMap;
 use std::io::ErrorKind;
 use std::io::Read;
+use std::path::PathBuf;
 use std::sync::Arc;
 use std::sync::Mutex as StdMutex;
 use std::sync::atomic::AtomicBool;
@@ -16,16 +17,45 @@ use tokio::time::Duration;
 use tokio::time::Instant;
 use tokio::time::timeout;
 
+use crate::config_types::ResourceLimits;
+use crate::config_types::SandboxExtras;
+use crate::exec::SandboxType;
+use crate::exec::pty_command_builder;
+use crate::exec::sandboxed_pty_command;
+use crate::exec_command::background_job::BackgroundJob;
+use crate::exec_command::background_job::JobOutput;
+use crate::exec_command::background_job::JobState;
+use crate::exec_command::background_job::job_status_text;
 use crate::exec_command::exec_command_params::ExecCommandParams;
+use crate::exec_command::exec_command_params::JobOutputParams;
+use crate::exec_command::exec_command_params::KillJobParams;
 use crate::exec_command::exec_command_params::WriteStdinParams;
 use crate::exec_command::exec_command_session::ExecCommandSession;
 use crate::exec_command::session_id::SessionId;
+use crate::protocol::BackgroundProcess;
+use crate::protocol::SandboxPolicy;
+use crate::resource_limits;
 use crate::truncate::truncate_middle;
 
 #[derive(Debug, Default)]
 pub struct SessionManager {
     next_session_id: AtomicU32,
     sessions: Mutex<HashMap<SessionId, ExecCommandSession>>,
+    jobs: Mutex<HashMap<SessionId, BackgroundJob>>,
+}
+
+/// Where and how a session's process runs, resolved from the turn that
+/// started it.
+#[derive(Debug, Clone)]
+pub(crate) struct ExecCommandSpawn {
+    pub cwd: PathBuf,
+    pub env: HashMap<String, String>,
+    pub sandbox_type: SandboxType,
+    pub sandbox_policy: SandboxPolicy,
+    pub sandbox_cwd: PathBuf,
+    pub codex_linux_sandbox_exe: Option<PathBuf>,
+    pub resource_limits: ResourceLimits,
+    pub sandbox_extras: SandboxExtras,
 }
 
 #[derive(Debug)]
@@ -44,6 +74,10 @@ impl ExecCommandOutput {
             ExitStatus::Ongoing(session_id) => {
                 format!("Process running with session ID {}", session_id.0)
             }
+            ExitStatus::Background(session_id) => format!(
+                "Process running in the background as job {id}; read its output with job_output(job_id={id})",
+                id = session_id.0
+            ),
         };
         let truncation_status = match self.original_token_count {
             Some(tokens) => {
@@ -65,13 +99,15 @@ Output:
 pub enum ExitStatus {
     Exited(i32),
     Ongoing(SessionId),
+    Background(SessionId),
 }
 
 impl SessionManager {
     /// Processes the request and is required to send a response via `outgoing`.
-    pub async fn handle_exec_command_request(
+    pub(crate) async fn handle_exec_command_request(
         &self,
         params: ExecCommandParams,
+        spawn: ExecCommandSpawn,
     ) -> Result<ExecCommandOutput, String> {
         // Allocate a session id.
         let session_id = SessionId(
@@ -79,18 +115,30 @@ impl SessionManager {
                 .fetch_add(1, std::sync::atomic::Ordering::SeqCst),
         );
 
-        let (session, mut output_rx, mut exit_rx) = create_exec_command_session(params.clone())
-            .await
-            .map_err(|err| {
-                format!(
-                    "failed to create exec command session for session id {}: {err}",
-                    session_id.0
-                )
-            })?;
+        let (session, mut output_rx, mut exit_rx) =
+            create_exec_command_session(params.clone(), spawn)
+                .await
+                .map_err(|err| {
+                    format!(
+                        "failed to create exec command session for session id {}: {err}",
+                        session_id.0
+                    )
+                })?;
 
         // Insert into session map.
         self.sessions.lock().await.insert(session_id, session);
 
+        if params.run_in_background {
+            let job = BackgroundJob::spawn(params.cmd, output_rx, exit_rx);
+            self.jobs.lock().await.insert(session_id, job);
+            return Ok(ExecCommandOutput {
+                wall_time: Duration::ZERO,
+                exit_status: ExitStatus::Background(session_id),
+                original_token_count: None,
+                output: String::new(),
+            });
+        }
+
         // Collect output until either timeout expires or process exits.
         // Do not cap during collection; truncate at the end if needed.
         // Use a modest initial capacity to avoid large preallocation.
@@ -171,13 +219,22 @@ impl SessionManager {
             chars,
             yield_time_ms,
             max_output_tokens,
+            rows,
+            cols,
         } = params;
 
         // Grab handles without holding the sessions lock across await points.
         let (writer_tx, mut output_rx) = {
             let sessions = self.sessions.lock().await;
             match sessions.get(&session_id) {
-                Some(session) => (session.writer_sender(), session.output_receiver()),
+                Some(session) => {
+                    if (rows.is_some() || cols.is_some())
+                        && let Err(err) = session.resize(rows, cols)
+                    {
+                        return Err(format!("failed to resize terminal: {err}"));
+                    }
+                    (session.writer_sender(), session.output_receiver())
+                }
                 None => {
                     return Err(format!("unknown session id {}", session_id.0));
                 }
@@ -224,23 +281,127 @@ impl SessionManager {
             output,
         })
     }
+
+    /// One line per background job with its state and command.
+    pub async fn handle_list_jobs_request(&self) -> String {
+        let jobs = self.jobs.lock().await;
+        if jobs.is_empty() {
+            return "No background jobs.".to_string();
+        }
+        let mut ids: Vec<&SessionId> = jobs.keys().collect();
+        ids.sort_by_key(|id| id.0);
+        ids.into_iter()
+            .map(|id| {
+                let job = &jobs[id];
+                format!(
+                    "{}: {}",
+                    job_status_text(*id, job.state(), job.elapsed()),
+                    job.cmd()
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    /// Background jobs that are still running, by session id.
+    pub(crate) async fn running_jobs(&self) -> Vec<BackgroundProcess> {
+        let jobs = self.jobs.lock().await;
+        let mut running: Vec<BackgroundProcess> = jobs
+            .iter()
+            .filter(|(_, job)| job.state() == JobState::Running)
+            .map(|(id, job)| BackgroundProcess {
+                session_id: id.0,
+                command: job.cmd().to_string(),
+            })
+            .collect();
+        running.sort_by_key(|process| process.session_id);
+        running
+    }
+
+    /// Output a background job printed since it was last read.
+    pub async fn handle_job_output_request(
+        &self,
+        params: JobOutputParams,
+    ) -> Result<JobOutput, String> {
+        let JobOutputParams {
+            job_id,
+            max_output_tokens,
+        } = params;
+        let jobs = self.jobs.lock().await;
+        let job = jobs
+            .get(&job_id)
+            .ok_or_else(|| format!("unknown job id {}", job_id.0))?;
+        Ok(job.read_output(job_id, max_output_tokens))
+    }
+
+    /// Kills a running background job and returns its remaining output.
+    pub async fn handle_kill_job_request(
+        &self,
+        params: KillJobParams,
+    ) -> Result<JobOutput, String> {
+        let KillJobParams { job_id } = params;
+        let jobs = self.jobs.lock().await;
+        let job = jobs
+            .get(&job_id)
+            .ok_or_else(|| format!("unknown job id {}", job_id.0))?;
+        if job.state() == JobState::Running {
+            // Dropping the session kills the process.
+            self.sessions.lock().await.remove(&job_id);
+            job.mark_killed();
+        }
+        Ok(job.read_output(job_id, 10_000))
+    }
+
+    /// Kills session `session_id` on behalf of the user, whether it is a
+    /// command the model is still waiting on or a background job. A pending
+    /// `exec_command` call returns as soon as the process is gone. Returns
+    /// `false` when no running session has that id.
+    pub async fn kill_session(&self, session_id: u32) -> bool {
+        let session_id = SessionId(session_id);
+        let Some(session) = self.sessions.lock().await.remove(&session_id) else {
+            return false;
+        };
+        let was_running = !session.has_exited();
+        // Dropping the session kills the process.
+        drop(session);
+        if let Some(job) = self.jobs.lock().await.get(&session_id)
+            && job.state() == JobState::Running
+        {
+            job.mark_killed();
+        }
+        was_running
+    }
 }
 
 /// Spawn PTY and child process per spawn_exec_command_session logic.
 async fn create_exec_command_session(
     params: ExecCommandParams,
+    spawn: ExecCommandSpawn,
 ) -> anyhow::Result<(
     ExecCommandSession,
     tokio::sync::broadcast::Receiver<Vec<u8>>,
     oneshot::Receiver<i32>,
 )> {
-    let ExecCommandParams {
-        cmd,
-        yield_time_ms: _,
-        max_output_tokens: _,
-        shell,
-        login,
-    } = params;
+    let ExecCommandSpawn {
+        cwd,
+        env,
+        sandbox_type,
+        sandbox_policy,
+        sandbox_cwd,
+        codex_linux_sandbox_exe,
+        resource_limits,
+        sandbox_extras,
+    } = spawn;
+    let (program, args, env) = sandboxed_pty_command(
+        params.command(),
+        env,
+        sandbox_type,
+        &sandbox_policy,
+        &sandbox_cwd,
+        &codex_linux_sandbox_exe,
+        &sandbox_extras,
+    )
+    .await?;
 
     // Use the native pty implementation for the system
     let pty_system = native_pty_system();
@@ -253,13 +414,14 @@ async fn create_exec_command_session(
         pixel_height: 0,
     })?;
 
-    // Spawn a shell into the pty
-    let mut command_builder = CommandBuilder::new(shell);
-    let shell_mode_opt = if login { "-lc" } else { "-c" };
-    command_builder.arg(shell_mode_opt);
-    command_builder.arg(cmd);
-
+    // Spawn the shell into the pty, wrapped in the sandbox if any.
+    let command_builder = pty_command_builder(program, args, cwd, env, &sandbox_policy);
     let mut child = pair.slave.spawn_command(command_builder)?;
+    if let Some(pid) = child.process_id()
+        && let Err(err) = resource_limits::apply_to_running_process(pid, &resource_limits)
+    {
+        tracing::warn!("failed to apply resource limits: {err}");
+    }
     // Obtain a killer that can signal the process independently of `.wait()`.
     let killer = child.clone_killer();
 
@@ -336,6 +498,7 @@ async fn create_exec_command_session(
         writer_handle,
         wait_handle,
         exit_status,
+        pair.master,
     );
     Ok((session, initial_output_rx, exit_rx))
 }
@@ -345,6 +508,21 @@ mod tests {
     use super::*;
     use crate::exec_command::session_id::SessionId;
 
+    /// Runs the session without a sandbox, in the test's cwd and env.
+    fn unsandboxed_spawn() -> ExecCommandSpawn {
+        let cwd = std::env::current_dir().expect("current dir");
+        ExecCommandSpawn {
+            cwd: cwd.clone(),
+            env: std::env::vars().collect(),
+            sandbox_type: SandboxType::None,
+            sandbox_policy: SandboxPolicy::DangerFullAccess,
+            sandbox_cwd: cwd,
+            codex_linux_sandbox_exe: None,
+            resource_limits: ResourceLimits::default(),
+            sandbox_extras: SandboxExtras::default(),
+        }
+    }
+
     /// Test that verifies that [`SessionManager::handle_exec_command_request()`]
     /// and [`SessionManager::handle_write_stdin_request()`] work as expected
     /// in the presence of a process that never terminates (but produces
@@ -378,9 +556,10 @@ PY"#
             max_output_tokens: 1_000, // large enough to avoid truncation here
             shell: "/bin/bash".to_string(),
             login: false,
+            run_in_background: false,
         };
         let initial_output = match session_manager
-            .handle_exec_command_request(params.clone())
+            .handle_exec_command_request(params.clone(), unsandboxed_spawn())
             .await
         {
             Ok(v) => v,
@@ -419,6 +598,8 @@ PY"#
             chars: String::new(),
             yield_time_ms: 3_000,
             max_output_tokens: 16, // 16 tokens ~= 64 bytes -> likely truncation
+            rows: None,
+            cols: None,
         };
         let second = session_manager
             .handle_write_stdin_request(write_params)
@@ -453,6 +634,171 @@ PY"#
         );
     }
 
+    /// Background jobs return immediately, buffer output until it is read,
+    /// and can be killed.
+    #[cfg(unix)]
+    #[allow(clippy::print_stderr)]
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn background_job_buffers_output_until_killed() {
+        use crate::exec_command::exec_command_params::ExecCommandParams;
+        use crate::exec_command::exec_command_params::JobOutputParams;
+        use crate::exec_command::exec_command_params::KillJobParams;
+        use tokio::time::sleep;
+
+        let session_manager = SessionManager::default();
+        let params = ExecCommandParams {
+            cmd: "echo server ready; sleep 30".to_string(),
+            yield_time_ms: 10_000,
+            max_output_tokens: 1_000,
+            shell: "/bin/bash".to_string(),
+            login: false,
+            run_in_background: true,
+        };
+        let started = match session_manager
+            .handle_exec_command_request(params, unsandboxed_spawn())
+            .await
+        {
+            Ok(v) => v,
+            Err(e) => {
+                if e.contains("openpty") || e.contains("Operation not permitted") {
+                    eprintln!("skipping test due to restricted PTY: {e}");
+                    return;
+                }
+                panic!("exec request failed unexpectedly: {e}");
+            }
+        };
+        let ExitStatus::Background(job_id) = started.exit_status else {
+            panic!("expected a background job, got {started:?}");
+        };
+        assert!(started.wall_time < Duration::from_secs(5));
+
+        let mut output = String::new();
+        for _ in 0..50 {
+            let read = session_manager
+                .handle_job_output_request(JobOutputParams {
+                    job_id,
+                    max_output_tokens: 1_000,
+                })
+                .await
+                .expect("job output");
+            output.push_str(&read.to_text_output());
+            if output.contains("server ready") {
+                break;
+            }
+            sleep(Duration::from_millis(100)).await;
+        }
+        assert!(output.contains("server ready"), "output: {output}");
+        assert!(
+            session_manager
+                .handle_list_jobs_request()
+                .await
+                .starts_with(&format!("Job {} running", job_id.0))
+        );
+
+        let killed = session_manager
+            .handle_kill_job_request(KillJobParams { job_id })
+            .await
+            .expect("kill job");
+        assert!(
+            killed
+                .to_text_output()
+                .starts_with(&format!("Job {} killed", job_id.0))
+        );
+        assert!(
+            session_manager
+                .handle_job_output_request(JobOutputParams {
+                    job_id: SessionId(job_id.0 + 1),
+                    max_output_tokens: 1_000,
+                })
+                .await
+                .is_err()
+        );
+    }
+
+    /// Killing a session ends the `exec_command` call waiting on it.
+    #[cfg(unix)]
+    #[allow(clippy::print_stderr)]
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn killing_a_session_ends_the_pending_call() {
+        use crate::exec_command::exec_command_params::ExecCommandParams;
+        use tokio::time::sleep;
+
+        let session_manager = SessionManager::default();
+        let params = ExecCommandParams {
+            cmd: "sleep 30".to_string(),
+            yield_time_ms: 20_000,
+            max_output_tokens: 1_000,
+            shell: "/bin/bash".to_string(),
+            login: false,
+            run_in_background: false,
+        };
+        let kill = async {
+            for _ in 0..50 {
+                sleep(Duration::from_millis(100)).await;
+                if session_manager.kill_session(0).await {
+                    return true;
+                }
+            }
+            false
+        };
+        let (result, killed) = tokio::join!(
+            session_manager.handle_exec_command_request(params, unsandboxed_spawn()),
+            kill
+        );
+        let output = match result {
+            Ok(v) => v,
+            Err(e) => {
+                if e.contains("openpty") || e.contains("Operation not permitted") {
+                    eprintln!("skipping test due to restricted PTY: {e}");
+                    return;
+                }
+                panic!("exec request failed unexpectedly: {e}");
+            }
+        };
+        assert!(killed);
+        assert!(matches!(output.exit_status, ExitStatus::Exited(_)));
+        assert!(output.wall_time < Duration::from_secs(10));
+        assert!(!session_manager.kill_session(0).await);
+    }
+
+    /// Sessions run in the turn's cwd with exactly the env they are given.
+    #[cfg(unix)]
+    #[allow(clippy::print_stderr)]
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn session_uses_spawn_cwd_and_env() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let cwd = dir.path().canonicalize().expect("canonicalize");
+        let mut spawn = unsandboxed_spawn();
+        spawn.cwd = cwd.clone();
+        spawn.env = HashMap::from([("CODEX_TEST_VALUE".to_string(), "42".to_string())]);
+
+        let params = ExecCommandParams {
+            cmd: "/bin/pwd; echo \"value=$CODEX_TEST_VALUE\"".to_string(),
+            yield_time_ms: 5_000,
+            max_output_tokens: 1_000,
+            shell: "/bin/sh".to_string(),
+            login: false,
+            run_in_background: false,
+        };
+        let output = match SessionManager::default()
+            .handle_exec_command_request(params, spawn)
+            .await
+        {
+            Ok(v) => v,
+            Err(e) => {
+                if e.contains("openpty") || e.contains("Operation not permitted") {
+                    eprintln!("skipping test due to restricted PTY: {e}");
+                    return;
+                }
+                panic!("exec request failed unexpectedly: {e}");
+            }
+        };
+
+        assert!(matches!(output.exit_status, ExitStatus::Exited(0)));
+        let expected = format!("{}\r\nvalue=42\r\n", cwd.display());
+        assert_eq!(output.output, expected);
+    }
+
     #[cfg(unix)]
     fn extract_monotonic_numbers(s: &str) -> Vec<i64> {
         s.lines()
@@ -1,6 +1,7 @@
 use crate::config_types::EnvironmentVariablePattern;
 use crate::config_types::ShellEnvironmentPolicy;
 use crate::config_types::ShellEnvironmentPolicyInherit;
+use std::collections::BTreeMap;
 use std::collections::HashMap;
 use std::collections::HashSet;
 
@@ -12,10 +13,54 @@ use std::collections::HashSet;
 /// The derivation follows the algorithm documented in the struct-level comment
 /// for [`ShellEnvironmentPolicy`].
 pub fn create_env(policy: &ShellEnvironmentPolicy) -> HashMap<String, String> {
-    populate_env(std::env::vars(), policy)
+    populate_env(std::env::vars(), policy, &HashMap::new())
 }
 
-fn populate_env<I>(vars: I, policy: &ShellEnvironmentPolicy) -> HashMap<String, String>
+/// Like [`create_env`], but additionally merges per-call `overrides` (e.g. the
+/// `env` argument of a shell tool call). Overrides replace inherited values but
+/// are filtered by the same exclude and `include_only` patterns, and entries in
+/// the policy's `set` table still take precedence.
+pub fn create_env_with_overrides(
+    policy: &ShellEnvironmentPolicy,
+    overrides: &HashMap<String, String>,
+) -> HashMap<String, String> {
+    populate_env(std::env::vars(), policy, overrides)
+}
+
+/// Returns `true` for variable names whose session-level override requires
+/// explicit user approval: likely credentials (the same patterns as the
+/// default excludes) and variables that change which executables or shared
+/// libraries get loaded.
+pub fn is_sensitive_env_var(name: &str) -> bool {
+    const SENSITIVE: &[&str] = &["*KEY*", "*SECRET*", "*TOKEN*", "PATH", "LD_*", "DYLD_*"];
+    SENSITIVE
+        .iter()
+        .any(|pattern| EnvironmentVariablePattern::new_case_insensitive(pattern).matches(name))
+}
+
+/// Applies session-level overrides on top of an environment produced by
+/// [`create_env`]. `Some(value)` sets the variable and `None` removes it.
+pub fn apply_session_env(
+    env: &mut HashMap<String, String>,
+    overrides: &BTreeMap<String, Option<String>>,
+) {
+    for (key, value) in overrides {
+        match value {
+            Some(value) => {
+                env.insert(key.clone(), value.clone());
+            }
+            None => {
+                env.remove(key);
+            }
+        }
+    }
+}
+
+fn populate_env<I>(
+    vars: I,
+    policy: &ShellEnvironmentPolicy,
+    overrides: &HashMap<String, String>,
+) -> HashMap<String, String>
 where
     I: IntoIterator<Item = (String, String)>,
 {
@@ -35,6 +80,12 @@ where
         }
     };
 
+    // Per-call overrides sit on top of the inherited set so that they go
+    // through the same filtering as everything else.
+    for (key, val) in overrides {
+        env_map.insert(key.clone(), val.clone());
+    }
+
     // Internal helper – does `name` match **any** pattern in `patterns`?
     let matches_any = |name: &str, patterns: &[EnvironmentVariablePattern]| -> bool {
         patterns.iter().any(|pattern| pattern.matches(name))
@@ -91,7 +142,7 @@ mod tests {
         ]);
 
         let policy = ShellEnvironmentPolicy::default(); // inherit Core, default excludes on
-        let result = populate_env(vars, &policy);
+        let result = populate_env(vars, &policy, &HashMap::new());
 
         let expected: HashMap<String, String> = hashmap! {
             "PATH".to_string() => "/usr/bin".to_string(),
@@ -112,7 +163,7 @@ mod tests {
             ..Default::default()
         };
 
-        let result = populate_env(vars, &policy);
+        let result = populate_env(vars, &policy, &HashMap::new());
 
         let expected: HashMap<String, String> = hashmap! {
             "PATH".to_string() => "/usr/bin".to_string(),
@@ -131,7 +182,7 @@ mod tests {
         };
         policy.r#set.insert("NEW_VAR".to_string(), "42".to_string());
 
-        let result = populate_env(vars, &policy);
+        let result = populate_env(vars, &policy, &HashMap::new());
 
         let expected: HashMap<String, String> = hashmap! {
             "PATH".to_string() => "/usr/bin".to_string(),
@@ -151,7 +202,7 @@ mod tests {
             ..Default::default()
         };
 
-        let result = populate_env(vars.clone(), &policy);
+        let result = populate_env(vars.clone(), &policy, &HashMap::new());
         let expected: HashMap<String, String> = vars.into_iter().collect();
         assert_eq!(result, expected);
     }
@@ -165,7 +216,7 @@ mod tests {
             ..Default::default()
         };
 
-        let result = populate_env(vars, &policy);
+        let result = populate_env(vars, &policy, &HashMap::new());
         let expected: HashMap<String, String> = hashmap! {
             "PATH".to_string() => "/usr/bin".to_string(),
         };
@@ -185,10 +236,69 @@ mod tests {
             .r#set
             .insert("ONLY_VAR".to_string(), "yes".to_string());
 
-        let result = populate_env(vars, &policy);
+        let result = populate_env(vars, &policy, &HashMap::new());
         let expected: HashMap<String, String> = hashmap! {
             "ONLY_VAR".to_string() => "yes".to_string(),
         };
         assert_eq!(result, expected);
     }
+
+    #[test]
+    fn test_overrides_are_filtered_by_policy() {
+        let vars = make_vars(&[("PATH", "/usr/bin"), ("RUST_LOG", "info")]);
+
+        let mut policy = ShellEnvironmentPolicy {
+            inherit: ShellEnvironmentPolicyInherit::All,
+            exclude: vec![EnvironmentVariablePattern::new_case_insensitive("AWS_*")],
+            ..Default::default()
+        };
+        policy.r#set.insert("CI".to_string(), "true".to_string());
+
+        let overrides = hashmap! {
+            "RUST_LOG".to_string() => "debug".to_string(),
+            "CI".to_string() => "1".to_string(),
+            "GITHUB_TOKEN".to_string() => "t".to_string(),
+            "AWS_REGION".to_string() => "us-east-1".to_string(),
+        };
+
+        let result = populate_env(vars, &policy, &overrides);
+        let expected: HashMap<String, String> = hashmap! {
+            "PATH".to_string() => "/usr/bin".to_string(),
+            "RUST_LOG".to_string() => "debug".to_string(),
+            "CI".to_string() => "true".to_string(),
+        };
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn test_sensitive_env_vars() {
+        assert!(is_sensitive_env_var("OPENAI_API_KEY"));
+        assert!(is_sensitive_env_var("github_token"));
+        assert!(is_sensitive_env_var("PATH"));
+        assert!(is_sensitive_env_var("LD_PRELOAD"));
+        assert!(is_sensitive_env_var("DYLD_INSERT_LIBRARIES"));
+        assert!(!is_sensitive_env_var("RUST_LOG"));
+        assert!(!is_sensitive_env_var("CI"));
+        assert!(!is_sensitive_env_var("MANPATH"));
+    }
+
+    #[test]
+    fn test_apply_session_env() {
+        let mut env = hashmap! {
+            "PATH".to_string() => "/usr/bin".to_string(),
+            "PAGER".to_string() => "less".to_string(),
+        };
+        let overrides = BTreeMap::from([
+            ("PAGER".to_string(), None),
+            ("RUST_LOG".to_string(), Some("debug".to_string())),
+        ]);
+
+        apply_session_env(&mut env, &overrides);
+
+        let expected: HashMap<String, String> = hashmap! {
+            "PATH".to_string() => "/usr/bin".to_string(),
+            "RUST_LOG".to_string() => "debug".to_string(),
+        };
+        assert_eq!(env, expected);
+    }
 }
@@ -1,3 +1,6 @@
+use crate::config_types::ResourceLimits;
+use crate::config_types::SandboxExtras;
+use crate::network_proxy::prepare_network_proxy;
 use crate::protocol::SandboxPolicy;
 use crate::spawn::StdioPolicy;
 use crate::spawn::spawn_child_async;
@@ -6,6 +9,10 @@ use std::path::Path;
 use std::path::PathBuf;
 use tokio::process::Child;
 
+/// Passed as the first argument to the Codex executable to run it as
+/// `codex-linux-sandbox`, for spawners that cannot set `argv[0]` (the PTY).
+pub const CODEX_LINUX_SANDBOX_ARG1: &str = "--codex-run-as-linux-sandbox";
+
 /// Spawn a shell tool command under the Linux Landlock+seccomp sandbox helper
 /// (codex-linux-sandbox).
 ///
@@ -20,12 +27,21 @@ pub async fn spawn_command_under_linux_sandbox<P>(
     sandbox_policy: &SandboxPolicy,
     sandbox_policy_cwd: &Path,
     stdio_policy: StdioPolicy,
-    env: HashMap<String, String>,
+    mut env: HashMap<String, String>,
+    resource_limits: ResourceLimits,
+    sandbox_extras: &SandboxExtras,
 ) -> std::io::Result<Child>
 where
     P: AsRef<Path>,
 {
-    let args = create_linux_sandbox_command_args(command, sandbox_policy, sandbox_policy_cwd);
+    let proxy_port = prepare_network_proxy(sandbox_policy, &mut env).await?;
+    let args = create_linux_sandbox_command_args(
+        command,
+        sandbox_policy,
+        sandbox_policy_cwd,
+        proxy_port,
+        sandbox_extras,
+    );
     let arg0 = Some("codex-linux-sandbox");
     spawn_child_async(
         codex_linux_sandbox_exe.as_ref().to_path_buf(),
@@ -35,15 +51,19 @@ where
         sandbox_policy,
         stdio_policy,
         env,
+        resource_limits,
     )
     .await
 }
 
 /// Converts the sandbox policy into the CLI invocation for `codex-linux-sandbox`.
-fn create_linux_sandbox_command_args(
+/// `sandbox.extra_landlock_paths` become `--extra-writable-path` flags.
+pub(crate) fn create_linux_sandbox_command_args(
     command: Vec<String>,
     sandbox_policy: &SandboxPolicy,
     sandbox_policy_cwd: &Path,
+    proxy_port: Option<u16>,
+    sandbox_extras: &SandboxExtras,
 ) -> Vec<String> {
     #[expect(clippy::expect_used)]
     let sandbox_policy_cwd = sandbox_policy_cwd
@@ -55,13 +75,23 @@ fn create_linux_sandbox_command_args(
     let sandbox_policy_json =
         serde_json::to_string(sandbox_policy).expect("Failed to serialize SandboxPolicy to JSON");
 
-    let mut linux_cmd: Vec<String> = vec![
+    let mut linux_cmd: Vec<String> = Vec::new();
+    if let Some(port) = proxy_port {
+        linux_cmd.extend(["--proxy-port".to_string(), port.to_string()]);
+    }
+    for path in &sandbox_extras.extra_landlock_paths {
+        linux_cmd.extend([
+            "--extra-writable-path".to_string(),
+            path.to_string_lossy().to_string(),
+        ]);
+    }
+    linux_cmd.extend([
         sandbox_policy_cwd,
         sandbox_policy_json,
         // Separator so that command arguments starting with `-` are not parsed as
         // options of the helper itself.
         "--".to_string(),
-    ];
+    ]);
 
     // Append the original tool command.
     linux_cmd.extend(command);
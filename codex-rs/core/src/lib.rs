@@ -7,14 +7,17 @@
 
 mod apply_patch;
 pub mod auth;
+pub mod automations;
 pub mod bash;
 mod chat_completions;
 mod client;
 mod client_common;
 pub mod codex;
 mod codex_conversation;
+pub mod codex_ignore;
 pub mod token_data;
 pub use codex_conversation::CodexConversation;
+pub mod command_log;
 mod command_safety;
 pub mod config;
 pub mod config_edit;
@@ -25,20 +28,29 @@ pub mod custom_prompts;
 mod environment_context;
 pub mod error;
 pub mod exec;
+pub mod exec_cache;
 mod exec_command;
 pub mod exec_env;
 mod flags;
 pub mod git_info;
 pub mod internal_storage;
 pub mod landlock;
+pub mod live_sessions;
+mod loop_detector;
 mod mcp_connection_manager;
+pub mod mcp_oauth;
+mod mcp_tool_cache;
 mod mcp_tool_call;
 mod message_history;
 mod model_provider_info;
+mod network_proxy;
 pub mod parse_command;
+pub mod path_utils;
+mod permission_gate;
 mod truncate;
 mod unified_exec;
 mod user_instructions;
+pub mod wasm_hooks;
 pub use model_provider_info::BUILT_IN_OSS_MODEL_PROVIDER_ID;
 pub use model_provider_info::ModelProviderInfo;
 pub use model_provider_info::WireApi;
@@ -59,6 +71,8 @@ mod openai_model_info;
 mod openai_tools;
 pub mod plan_tool;
 pub mod project_doc;
+mod prompt_preview;
+mod resource_limits;
 mod rollout;
 pub(crate) mod safety;
 pub mod seatbelt;
@@ -71,18 +85,31 @@ pub use rollout::ARCHIVED_SESSIONS_SUBDIR;
 pub use rollout::RolloutRecorder;
 pub use rollout::SESSIONS_SUBDIR;
 pub use rollout::SessionMeta;
+pub use rollout::TRASHED_SESSIONS_SUBDIR;
+pub use rollout::TrashedSession;
 pub use rollout::find_conversation_path_by_id_str;
+pub use rollout::format::parse_rollout_line;
 pub use rollout::list::ConversationItem;
 pub use rollout::list::ConversationsPage;
 pub use rollout::list::Cursor;
+pub use rollout::list_trashed_sessions;
+pub use rollout::restore_trashed_session;
 mod function_tool;
+mod scratch_dir;
 mod state;
+mod storage_guard;
 mod user_notification;
 pub mod util;
+mod vendored_tools;
+pub mod windows_sandbox;
 
 pub use apply_patch::CODEX_APPLY_PATCH_ARG1;
+pub use apply_patch::apply_patch_standalone;
+pub use command_safety::command_pattern;
 pub use command_safety::is_safe_command;
+pub use landlock::CODEX_LINUX_SANDBOX_ARG1;
 pub use safety::get_platform_sandbox;
+pub use windows_sandbox::CODEX_WINDOWS_SANDBOX_ARG1;
 // Re-export the protocol types from the standalone `codex-protocol` crate so existing
 // `codex_core::protocol::...` references continue to work across the workspace.
 pub use codex_protocol::protocol;
@@ -0,0 +1,304 @@
+//! Status files for running sessions, read by `codex status`.
+//!
+//! Every session keeps `$CODEX_HOME/live/<conversation id>.json` up to date
+//! with its working directory and whether it is running a turn, waiting for
+//! the user to approve something, or idle. The file is removed when the
+//! session shuts down; files left behind by a process that died are skipped
+//! and cleaned up by [`list_live_sessions`].
+
+use std::io::ErrorKind;
+use std::path::Path;
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+use chrono::SecondsFormat;
+use chrono::Utc;
+use serde::Deserialize;
+use serde::Serialize;
+use strum_macros::Display;
+use tracing::warn;
+
+use crate::protocol::EventMsg;
+use codex_protocol::mcp_protocol::ConversationId;
+
+/// Directory under `$CODEX_HOME` holding one status file per live session.
+pub const LIVE_SESSIONS_SUBDIR: &str = "live";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Display)]
+#[serde(rename_all = "kebab-case")]
+#[strum(serialize_all = "kebab-case")]
+pub enum LiveSessionState {
+    Running,
+    WaitingApproval,
+    Idle,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LiveSession {
+    pub conversation_id: ConversationId,
+    pub pid: u32,
+    pub cwd: PathBuf,
+    pub state: LiveSessionState,
+    /// RFC 3339 time of the last state change.
+    pub updated_at: String,
+}
+
+/// The status file of the session in this process.
+pub(crate) struct LiveSessionFile {
+    path: PathBuf,
+    session: Mutex<LiveSession>,
+}
+
+impl LiveSessionFile {
+    /// Writes the initial, idle status. Failures are logged rather than
+    /// returned since the session works fine without a status file.
+    pub(crate) fn create(codex_home: &Path, conversation_id: ConversationId, cwd: PathBuf) -> Self {
+        let path = codex_home
+            .join(LIVE_SESSIONS_SUBDIR)
+            .join(format!("{conversation_id}.json"));
+        let file = Self {
+            path,
+            session: Mutex::new(LiveSession {
+                conversation_id,
+                pid: std::process::id(),
+                cwd,
+                state: LiveSessionState::Idle,
+                updated_at: now(),
+            }),
+        };
+        file.update(|_| true);
+        file
+    }
+
+    pub(crate) fn set_state(&self, state: LiveSessionState) {
+        self.update(|session| {
+            let changed = session.state != state;
+            session.state = state;
+            changed
+        });
+    }
+
+    pub(crate) fn set_cwd(&self, cwd: &Path) {
+        self.update(|session| {
+            let changed = session.cwd != cwd;
+            session.cwd = cwd.to_path_buf();
+            changed
+        });
+    }
+
+    /// Updates the state for an event the session is about to emit.
+    pub(crate) fn observe(&self, msg: &EventMsg) {
+        let state = match msg {
+            EventMsg::TaskStarted(_) => LiveSessionState::Running,
+            EventMsg::ExecApprovalRequest(_)
+            | EventMsg::ApplyPatchApprovalRequest(_)
+            | EventMsg::EscalationRequest(_)
+            | EventMsg::PermissionConsentRequest(_) => LiveSessionState::WaitingApproval,
+            EventMsg::TaskComplete(_) | EventMsg::TurnAborted(_) => LiveSessionState::Idle,
+            _ => return,
+        };
+        self.set_state(state);
+    }
+
+    /// Marks the session running again once an approval was answered, unless
+    /// the turn already ended in the meantime.
+    pub(crate) fn approval_answered(&self) {
+        self.update(|session| {
+            let changed = session.state == LiveSessionState::WaitingApproval;
+            if changed {
+                session.state = LiveSessionState::Running;
+            }
+            changed
+        });
+    }
+
+    pub(crate) fn remove(&self) {
+        if let Err(err) = std::fs::remove_file(&self.path)
+            && err.kind() != ErrorKind::NotFound
+        {
+            warn!("failed to remove {}: {err}", self.path.display());
+        }
+    }
+
+    /// Applies `change` and rewrites the file when it reports a change.
+    fn update(&self, change: impl FnOnce(&mut LiveSession) -> bool) {
+        let mut session = match self.session.lock() {
+            Ok(guard) => guard,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+        if !change(&mut session) {
+            return;
+        }
+        session.updated_at = now();
+        if let Err(err) = write_atomically(&self.path, &session) {
+            warn!("failed to write {}: {err}", self.path.display());
+        }
+    }
+}
+
+impl Drop for LiveSessionFile {
+    fn drop(&mut self) {
+        self.remove();
+    }
+}
+
+/// Sessions whose process is still alive, sorted by working directory.
+/// Status files of processes that are gone are deleted.
+pub fn list_live_sessions(codex_home: &Path) -> std::io::Result<Vec<LiveSession>> {
+    let dir = codex_home.join(LIVE_SESSIONS_SUBDIR);
+    let entries = match std::fs::read_dir(&dir) {
+        Ok(entries) => entries,
+        Err(err) if err.kind() == ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(err) => return Err(err),
+    };
+    let mut sessions = Vec::new();
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.extension().is_none_or(|ext| ext != "json") {
+            continue;
+        }
+        // A file may vanish or be mid-rename while we read; skip it.
+        let Ok(contents) = std::fs::read_to_string(&path) else {
+            continue;
+        };
+        let Ok(session) = serde_json::from_str::<LiveSession>(&contents) else {
+            continue;
+        };
+        if process_is_alive(session.pid) {
+            sessions.push(session);
+        } else {
+            let _ = std::fs::remove_file(&path);
+        }
+    }
+    sessions.sort_by(|a, b| a.cwd.cmp(&b.cwd).then(a.updated_at.cmp(&b.updated_at)));
+    Ok(sessions)
+}
+
+fn now() -> String {
+    Utc::now().to_rfc3339_opts(SecondsFormat::Secs, true)
+}
+
+fn write_atomically(path: &Path, session: &LiveSession) -> std::io::Result<()> {
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    let tmp = path.with_extension("json.tmp");
+    std::fs::write(&tmp, serde_json::to_vec(session)?)?;
+    std::fs::rename(&tmp, path)
+}
+
+#[cfg(unix)]
+fn process_is_alive(pid: u32) -> bool {
+    let Ok(pid) = libc::pid_t::try_from(pid) else {
+        return false;
+    };
+    // Signal 0 only checks that the process exists. EPERM means it exists
+    // but belongs to another user.
+    if unsafe { libc::kill(pid, 0) } == 0 {
+        return true;
+    }
+    std::io::Error::last_os_error().raw_os_error() == Some(libc::EPERM)
+}
+
+#[cfg(windows)]
+fn process_is_alive(pid: u32) -> bool {
+    use windows_sys::Win32::Foundation::CloseHandle;
+    use windows_sys::Win32::Foundation::STILL_ACTIVE;
+    use windows_sys::Win32::System::Threading::GetExitCodeProcess;
+    use windows_sys::Win32::System::Threading::OpenProcess;
+    use windows_sys::Win32::System::Threading::PROCESS_QUERY_LIMITED_INFORMATION;
+
+    unsafe {
+        let handle = OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, 0, pid);
+        if handle.is_null() {
+            return false;
+        }
+        let mut code = 0u32;
+        let ok = GetExitCodeProcess(handle, &mut code) != 0;
+        CloseHandle(handle);
+        ok && code == STILL_ACTIVE as u32
+    }
+}
+
+#[cfg(not(any(unix, windows)))]
+fn process_is_alive(_pid: u32) -> bool {
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::protocol::TaskCompleteEvent;
+    use crate::protocol::TaskStartedEvent;
+    use pretty_assertions::assert_eq;
+    use tempfile::TempDir;
+
+    fn read_state(codex_home: &Path) -> Vec<LiveSessionState> {
+        list_live_sessions(codex_home)
+            .expect("list live sessions")
+            .into_iter()
+            .map(|session| session.state)
+            .collect()
+    }
+
+    #[test]
+    fn status_file_follows_the_session() {
+        let codex_home = TempDir::new().expect("tempdir");
+        let file = LiveSessionFile::create(
+            codex_home.path(),
+            ConversationId::new(),
+            PathBuf::from("/repo"),
+        );
+        assert_eq!(read_state(codex_home.path()), vec![LiveSessionState::Idle]);
+
+        file.observe(&EventMsg::TaskStarted(TaskStartedEvent {
+            model_context_window: None,
+            branch: None,
+        }));
+        assert_eq!(
+            read_state(codex_home.path()),
+            vec![LiveSessionState::Running]
+        );
+
+        file.set_state(LiveSessionState::WaitingApproval);
+        file.approval_answered();
+        assert_eq!(
+            read_state(codex_home.path()),
+            vec![LiveSessionState::Running]
+        );
+
+        file.observe(&EventMsg::TaskComplete(TaskCompleteEvent {
+            last_agent_message: None,
+            assessment: None,
+            estimated_cost_usd: None,
+            output_schema_error: None,
+        }));
+        file.approval_answered();
+        assert_eq!(read_state(codex_home.path()), vec![LiveSessionState::Idle]);
+
+        drop(file);
+        assert_eq!(read_state(codex_home.path()), Vec::new());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn stale_status_files_are_removed() {
+        let codex_home = TempDir::new().expect("tempdir");
+        let dir = codex_home.path().join(LIVE_SESSIONS_SUBDIR);
+        std::fs::create_dir_all(&dir).expect("create live dir");
+        let stale = LiveSession {
+            conversation_id: ConversationId::new(),
+            // Larger than any real pid_max.
+            pid: i32::MAX as u32,
+            cwd: PathBuf::from("/repo"),
+            state: LiveSessionState::Running,
+            updated_at: now(),
+        };
+        let path = dir.join(format!("{}.json", stale.conversation_id));
+        std::fs::write(&path, serde_json::to_vec(&stale).expect("serialize"))
+            .expect("write status file");
+
+        assert_eq!(read_state(codex_home.path()), Vec::new());
+        assert!(!path.exists());
+    }
+}
@@ -0,0 +1,300 @@
+//! Detects tasks that keep issuing the same tool calls and getting the same
+//! results back, or retrying a tool call that fails every time, without
+//! changing any files.
+//!
+//! A shell command that `repeated_command_failure_limit` answered without
+//! running it is reported as failed, so retrying it keeps counting here.
+
+use std::collections::HashMap;
+use std::collections::VecDeque;
+
+use codex_protocol::models::ResponseInputItem;
+use codex_protocol::models::ResponseItem;
+
+/// Number of previous turns a new turn is compared against. Two is enough to
+/// catch both a single repeated turn and two turns alternating.
+const HISTORY_LEN: usize = 2;
+
+/// Sent to the model when a stalled task is configured to recover on its own.
+pub(crate) const STALL_RECOVERY_PROMPT: &str = "You have repeated the same tool calls several times and they keep returning the same results without changing any files. Stop retrying them. Step back, reconsider the problem, and try a different approach; if you are blocked, explain what is blocking you and end your turn.";
+
+/// Sent to the model when it keeps retrying a failing tool call and the task
+/// is configured to recover on its own.
+fn failed_call_recovery_prompt(tool_name: &str) -> String {
+    format!(
+        "You have called `{tool_name}` with the same arguments several times in a row and it failed every time. Do not call it that way again. Read the error, reconsider the problem, and try a different approach; if you are blocked, explain what is blocking you and end your turn."
+    )
+}
+
+/// A tool call made during a turn, as compared with the calls of earlier
+/// turns.
+pub(crate) struct TurnToolCall {
+    /// The call and its result; see [`tool_call_fingerprint`].
+    fingerprint: String,
+    /// The call without its result, when it failed.
+    failed_call: Option<String>,
+    tool_name: String,
+}
+
+impl TurnToolCall {
+    pub(crate) fn new(item: &ResponseItem, response: &ResponseInputItem) -> Self {
+        Self {
+            fingerprint: tool_call_fingerprint(item, response),
+            failed_call: tool_call_failed(response).then(|| tool_call_signature(item)),
+            tool_name: tool_call_name(item),
+        }
+    }
+}
+
+/// Why a task was found to be stalled.
+#[derive(Debug, PartialEq)]
+pub(crate) struct Stall {
+    /// Consecutive turns that repeated an earlier one.
+    pub(crate) repeated_turns: u32,
+    /// The tool whose call failed with the same arguments in each of those
+    /// turns, when that is what repeated.
+    pub(crate) failing_tool: Option<String>,
+}
+
+impl Stall {
+    /// Sent to the model when the task is configured to recover on its own.
+    pub(crate) fn recovery_prompt(&self) -> String {
+        match &self.failing_tool {
+            Some(tool_name) => failed_call_recovery_prompt(tool_name),
+            None => STALL_RECOVERY_PROMPT.to_string(),
+        }
+    }
+}
+
+pub(crate) struct LoopDetector {
+    /// Consecutive repeated turns after which the task is considered stalled.
+    /// `0` disables detection.
+    threshold: u32,
+    recent: VecDeque<Vec<String>>,
+    repeated_turns: u32,
+    /// Calls that failed in the previous turn, with the tool they went to and
+    /// the number of consecutive turns before it in which they also failed.
+    failing_calls: HashMap<String, (String, u32)>,
+}
+
+impl LoopDetector {
+    pub(crate) fn new(threshold: u32) -> Self {
+        Self {
+            threshold,
+            recent: VecDeque::with_capacity(HISTORY_LEN),
+            repeated_turns: 0,
+            failing_calls: HashMap::new(),
+        }
+    }
+
+    /// Record the tool calls of a completed turn. Returns the stall once the
+    /// turn is the `threshold`-th in a row to repeat the calls and results of
+    /// an earlier one, or to retry a call that keeps failing, whatever the
+    /// error says.
+    pub(crate) fn record_turn(
+        &mut self,
+        tool_calls: Vec<TurnToolCall>,
+        changed_files: bool,
+    ) -> Option<Stall> {
+        if self.threshold == 0 {
+            return None;
+        }
+        let mut failing_calls = HashMap::new();
+        let mut fingerprints = Vec::with_capacity(tool_calls.len());
+        for call in tool_calls {
+            if let Some(failed_call) = call.failed_call {
+                failing_calls.insert(failed_call, (call.tool_name, 0));
+            }
+            fingerprints.push(call.fingerprint);
+        }
+
+        if fingerprints.is_empty() || changed_files {
+            self.reset();
+        } else {
+            if self.recent.contains(&fingerprints) {
+                self.repeated_turns += 1;
+            } else {
+                self.repeated_turns = 0;
+            }
+            for (failed_call, (_, repeats)) in &mut failing_calls {
+                if let Some((_, previous)) = self.failing_calls.get(failed_call) {
+                    *repeats = previous + 1;
+                }
+            }
+        }
+        self.failing_calls = failing_calls;
+
+        if !fingerprints.is_empty() {
+            if self.recent.len() == HISTORY_LEN {
+                self.recent.pop_front();
+            }
+            self.recent.push_back(fingerprints);
+        }
+
+        if let Some((tool_name, repeats)) = self
+            .failing_calls
+            .values()
+            .filter(|(_, repeats)| *repeats >= self.threshold)
+            .max_by_key(|(_, repeats)| *repeats)
+        {
+            return Some(Stall {
+                repeated_turns: *repeats,
+                failing_tool: Some(tool_name.clone()),
+            });
+        }
+        (self.repeated_turns >= self.threshold).then_some(Stall {
+            repeated_turns: self.repeated_turns,
+            failing_tool: None,
+        })
+    }
+
+    pub(crate) fn reset(&mut self) {
+        self.recent.clear();
+        self.repeated_turns = 0;
+        self.failing_calls.clear();
+    }
+}
+
+/// The tool a call was made to, as shown to the user.
+fn tool_call_name(item: &ResponseItem) -> String {
+    match item {
+        ResponseItem::FunctionCall { name, .. } | ResponseItem::CustomToolCall { name, .. } => {
+            name.clone()
+        }
+        ResponseItem::LocalShellCall { .. } => "local_shell".to_string(),
+        _ => "unknown".to_string(),
+    }
+}
+
+/// Summarize a tool call, ignoring the call id, so that calls to the same
+/// tool with the same arguments compare equal.
+fn tool_call_signature(item: &ResponseItem) -> String {
+    match item {
+        ResponseItem::FunctionCall {
+            name, arguments, ..
+        } => format!("{name}({arguments})"),
+        ResponseItem::CustomToolCall { name, input, .. } => format!("{name}({input})"),
+        ResponseItem::LocalShellCall { action, .. } => format!("local_shell({action:?})"),
+        other => format!("{other:?}"),
+    }
+}
+
+/// Whether the output of a tool call reports a failure.
+fn tool_call_failed(response: &ResponseInputItem) -> bool {
+    match response {
+        ResponseInputItem::FunctionCallOutput { output, .. } => output.success == Some(false),
+        ResponseInputItem::McpToolCallOutput { result, .. } => match result {
+            Ok(result) => result.is_error == Some(true),
+            Err(_) => true,
+        },
+        ResponseInputItem::CustomToolCallOutput { .. } | ResponseInputItem::Message { .. } => false,
+    }
+}
+
+/// Summarize a tool call and its result, ignoring the call id, so that
+/// identical calls with identical results compare equal across turns.
+fn tool_call_fingerprint(item: &ResponseItem, response: &ResponseInputItem) -> String {
+    let call = tool_call_signature(item);
+    let result = match response {
+        ResponseInputItem::FunctionCallOutput { output, .. } => output.content.clone(),
+        ResponseInputItem::CustomToolCallOutput { output, .. } => output.clone(),
+        ResponseInputItem::McpToolCallOutput { result, .. } => format!("{result:?}"),
+        ResponseInputItem::Message { content, .. } => format!("{content:?}"),
+    };
+    format!("{call} -> {result}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn turn(calls: &[&str]) -> Vec<TurnToolCall> {
+        calls
+            .iter()
+            .map(|call| TurnToolCall {
+                fingerprint: call.to_string(),
+                failed_call: None,
+                tool_name: "shell".to_string(),
+            })
+            .collect()
+    }
+
+    /// A turn with one call to `tool` that failed with `output`.
+    fn failed(tool: &str, output: &str) -> Vec<TurnToolCall> {
+        vec![TurnToolCall {
+            fingerprint: format!("{tool}() -> {output}"),
+            failed_call: Some(format!("{tool}()")),
+            tool_name: tool.to_string(),
+        }]
+    }
+
+    fn repeated(repeated_turns: u32) -> Option<Stall> {
+        Some(Stall {
+            repeated_turns,
+            failing_tool: None,
+        })
+    }
+
+    #[test]
+    fn identical_turns_stall_after_threshold() {
+        let mut detector = LoopDetector::new(2);
+        assert_eq!(detector.record_turn(turn(&["a"]), false), None);
+        assert_eq!(detector.record_turn(turn(&["a"]), false), None);
+        assert_eq!(detector.record_turn(turn(&["a"]), false), repeated(2));
+    }
+
+    #[test]
+    fn alternating_turns_stall() {
+        let mut detector = LoopDetector::new(2);
+        assert_eq!(detector.record_turn(turn(&["a"]), false), None);
+        assert_eq!(detector.record_turn(turn(&["b"]), false), None);
+        assert_eq!(detector.record_turn(turn(&["a"]), false), None);
+        assert_eq!(detector.record_turn(turn(&["b"]), false), repeated(2));
+    }
+
+    #[test]
+    fn new_information_or_file_changes_reset() {
+        let mut detector = LoopDetector::new(2);
+        detector.record_turn(turn(&["a"]), false);
+        detector.record_turn(turn(&["a"]), false);
+        assert_eq!(detector.record_turn(turn(&["c"]), false), None);
+        assert_eq!(detector.record_turn(turn(&["c"]), true), None);
+        assert_eq!(detector.record_turn(turn(&["c"]), false), None);
+        assert_eq!(detector.record_turn(turn(&["c"]), false), repeated(2));
+    }
+
+    #[test]
+    fn zero_threshold_disables_detection() {
+        let mut detector = LoopDetector::new(0);
+        for _ in 0..5 {
+            assert_eq!(detector.record_turn(turn(&["a"]), false), None);
+            assert_eq!(detector.record_turn(failed("shell", "a"), false), None);
+        }
+    }
+
+    #[test]
+    fn a_call_that_keeps_failing_stalls_whatever_the_error() {
+        let mut detector = LoopDetector::new(2);
+        assert_eq!(detector.record_turn(failed("shell", "a"), false), None);
+        assert_eq!(detector.record_turn(failed("shell", "b"), false), None);
+        assert_eq!(
+            detector.record_turn(failed("shell", "c"), false),
+            Some(Stall {
+                repeated_turns: 2,
+                failing_tool: Some("shell".to_string()),
+            })
+        );
+    }
+
+    #[test]
+    fn a_success_or_file_change_resets_failures() {
+        let mut detector = LoopDetector::new(2);
+        detector.record_turn(failed("shell", "a"), false);
+        detector.record_turn(failed("shell", "b"), false);
+        assert_eq!(detector.record_turn(turn(&["shell() -> ok"]), false), None);
+        detector.record_turn(failed("shell", "c"), false);
+        assert_eq!(detector.record_turn(failed("shell", "d"), true), None);
+        assert_eq!(detector.record_turn(failed("shell", "e"), false), None);
+    }
+}
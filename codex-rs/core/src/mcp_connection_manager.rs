@@ -4,30 +4,66 @@
 //! configured server (keyed by the *server name*). It offers convenience
 //! helpers to query the available tools across *all* servers and returns them
 //! in a single aggregated map using the fully-qualified tool name
-//! `"<server><MCP_TOOL_NAME_DELIMITER><tool>"` as the key.
+//! `"<server><MCP_TOOL_NAME_DELIMITER><tool>"` as the key. Resources and
+//! prompts are listed and read on demand from the servers that advertise them.
+//! Servers can be added, replaced or removed while the session runs.
 
 use std::collections::HashMap;
 use std::collections::HashSet;
 use std::ffi::OsString;
+use std::path::Path;
+use std::path::PathBuf;
 use std::sync::Arc;
+use std::sync::RwLock;
+use std::sync::RwLockReadGuard;
+use std::sync::RwLockWriteGuard;
+use std::sync::Weak;
+use std::sync::atomic::AtomicU32;
+use std::sync::atomic::Ordering;
 use std::time::Duration;
+use std::time::Instant;
 
 use anyhow::Context;
 use anyhow::Result;
 use anyhow::anyhow;
+use async_channel::Sender;
 use codex_mcp_client::McpClient;
+use futures::future::join_all;
 use mcp_types::ClientCapabilities;
+use mcp_types::GetPromptResult;
 use mcp_types::Implementation;
+use mcp_types::ListPromptsRequestParams;
+use mcp_types::ListResourcesRequestParams;
+use mcp_types::ProgressToken;
+use mcp_types::Prompt;
+use mcp_types::ReadResourceResult;
+use mcp_types::Resource;
+use mcp_types::ServerCapabilities;
+use mcp_types::ServerNotification;
 use mcp_types::Tool;
 
 use serde_json::json;
 use sha1::Digest;
 use sha1::Sha1;
+use tokio::sync::Mutex;
+use tokio::sync::OwnedSemaphorePermit;
+use tokio::sync::Semaphore;
+use tokio::sync::broadcast::error::RecvError;
 use tokio::task::JoinSet;
 use tracing::info;
 use tracing::warn;
+use wildmatch::WildMatchPattern;
 
+use crate::codex::INITIAL_SUBMIT_ID;
 use crate::config_types::McpServerConfig;
+use crate::mcp_oauth::mcp_oauth_access_token;
+use crate::mcp_tool_cache;
+use crate::mcp_tool_cache::CachedServer;
+use crate::protocol::BackgroundEventEvent;
+use crate::protocol::Event;
+use crate::protocol::EventMsg;
+use crate::protocol::McpNotification;
+use crate::protocol::McpNotificationEvent;
 
 /// Delimiter used to separate the server name from the tool name in a fully
 /// qualified tool name.
@@ -43,6 +79,21 @@ const DEFAULT_STARTUP_TIMEOUT: Duration = Duration::from_secs(10);
 /// Default timeout for individual tool calls.
 const DEFAULT_TOOL_TIMEOUT: Duration = Duration::from_secs(60);
 
+/// A server that exits after staying up at least this long has its restart
+/// backoff reset.
+const STABLE_UPTIME: Duration = Duration::from_secs(60);
+
+/// Consecutive unexpected exits after which a server is no longer restarted
+/// automatically (it is still started again on its next use).
+const MAX_AUTOMATIC_RESTARTS: u32 = 5;
+
+const MAX_RESTART_BACKOFF: Duration = Duration::from_secs(30);
+
+/// Pages of `resources/list` or `prompts/list` fetched per server before
+/// giving up, so a server that keeps returning a cursor cannot stall the
+/// caller.
+const MAX_LIST_PAGES: usize = 20;
+
 /// Map that holds a startup error for every MCP server that could **not** be
 /// spawned successfully.
 pub type ClientStartErrors = HashMap<String, anyhow::Error>;
@@ -79,51 +130,523 @@ fn qualify_tools(tools: Vec<ToolInfo>) -> HashMap<String, ToolInfo> {
     qualified_tools
 }
 
+#[derive(Clone)]
 struct ToolInfo {
     server_name: String,
     tool_name: String,
     tool: Tool,
 }
 
-struct ManagedClient {
-    client: Arc<McpClient>,
+/// Which of a server's tools are exposed to the model, from the
+/// `include_tools` / `exclude_tools` globs in its config.
+#[derive(Default)]
+struct ToolFilter {
+    include: Vec<WildMatchPattern<'*', '?'>>,
+    exclude: Vec<WildMatchPattern<'*', '?'>>,
+}
+
+impl ToolFilter {
+    fn new(include: &[String], exclude: &[String]) -> Self {
+        let patterns = |globs: &[String]| -> Vec<WildMatchPattern<'*', '?'>> {
+            globs
+                .iter()
+                .map(|glob| WildMatchPattern::new(glob))
+                .collect()
+        };
+        Self {
+            include: patterns(include),
+            exclude: patterns(exclude),
+        }
+    }
+
+    /// A tool is allowed if it matches an `include` glob (or none are set)
+    /// and matches no `exclude` glob.
+    fn allows(&self, tool_name: &str) -> bool {
+        (self.include.is_empty() || self.include.iter().any(|p| p.matches(tool_name)))
+            && !self.exclude.iter().any(|p| p.matches(tool_name))
+    }
+}
+
+/// Everything needed to (re)start a server, kept so that a server that is
+/// stopped (or was never started) can be started on demand.
+struct ServerLauncher {
+    server_name: String,
+    config: McpServerConfig,
+    codex_home: PathBuf,
     startup_timeout: Duration,
+}
+
+impl ServerLauncher {
+    /// Spawn or connect to the server and run the `initialize` handshake.
+    async fn start(&self) -> Result<(McpClient, ServerCapabilities)> {
+        let McpServerConfig {
+            command,
+            args,
+            env,
+            url,
+            headers,
+            bearer_token_env_var,
+            oauth,
+            ..
+        } = self.config.clone();
+        let client = match url {
+            Some(url) => {
+                let bearer_token = bearer_token(
+                    &self.codex_home,
+                    &self.server_name,
+                    bearer_token_env_var.as_deref(),
+                    oauth.is_some(),
+                )
+                .await?;
+                McpClient::new_streamable_http_client(
+                    url,
+                    headers.unwrap_or_default(),
+                    bearer_token,
+                )?
+            }
+            None => {
+                McpClient::new_stdio_client(
+                    command.into(),
+                    args.into_iter().map(OsString::from).collect(),
+                    env,
+                )
+                .await?
+            }
+        };
+
+        // Initialize the client.
+        let params = mcp_types::InitializeRequestParams {
+            capabilities: ClientCapabilities {
+                experimental: None,
+                roots: None,
+                sampling: None,
+                // https://modelcontextprotocol.io/specification/2025-06-18/client/elicitation#capabilities
+                // indicates this should be an empty object.
+                elicitation: Some(json!({})),
+            },
+            client_info: Implementation {
+                name: "codex-mcp-client".to_owned(),
+                version: env!("CARGO_PKG_VERSION").to_owned(),
+                title: Some("Codex".into()),
+                // This field is used by Codex when it is an MCP
+                // server: it should not be used when Codex is
+                // an MCP client.
+                user_agent: None,
+            },
+            protocol_version: mcp_types::MCP_SCHEMA_VERSION.to_owned(),
+        };
+        let initialize_notification_params = None;
+        let result = client
+            .initialize(
+                params,
+                initialize_notification_params,
+                Some(self.startup_timeout),
+            )
+            .await?;
+        Ok((client, result.capabilities))
+    }
+}
+
+/// A started server and when it was last used.
+struct RunningClient {
+    client: Arc<McpClient>,
+    started_at: Instant,
+    last_used: Instant,
+}
+
+/// The lifecycle of one server: started at session start or on first use,
+/// stopped after `idle_timeout`, and restarted (with backoff) if it exits
+/// on its own.
+struct ServerRuntime {
+    launcher: ServerLauncher,
+    /// `None` while the server is stopped: it is `lazy` and has not been
+    /// used yet, it was shut down after `idle_timeout`, or it exited and has
+    /// not been restarted.
+    running: Mutex<Option<RunningClient>>,
+    idle_timeout: Option<Duration>,
+    /// Unexpected exits since the server last stayed up for
+    /// `STABLE_UPTIME`; drives the restart backoff.
+    recent_exits: AtomicU32,
+    tx_event: Sender<Event>,
+}
+
+impl ServerRuntime {
+    /// Returns the running client, starting the server first if it is
+    /// stopped or has exited.
+    async fn client(self: &Arc<Self>) -> Result<Arc<McpClient>> {
+        let mut running = self.running.lock().await;
+        if let Some(current) = running.as_mut()
+            && !current.client.is_closed()
+        {
+            current.last_used = Instant::now();
+            return Ok(current.client.clone());
+        }
+
+        let server_name = &self.launcher.server_name;
+        info!("starting MCP server '{server_name}' on demand");
+        let (client, _) = self
+            .launcher
+            .start()
+            .await
+            .with_context(|| format!("failed to start MCP server '{server_name}'"))?;
+        Ok(self.install(&mut running, client))
+    }
+
+    /// Make `client` the running client and start watching it.
+    fn install(
+        self: &Arc<Self>,
+        running: &mut Option<RunningClient>,
+        client: McpClient,
+    ) -> Arc<McpClient> {
+        let client = Arc::new(client);
+        let now = Instant::now();
+        *running = Some(RunningClient {
+            client: client.clone(),
+            started_at: now,
+            last_used: now,
+        });
+        self.watch_for_exit(&client);
+        self.forward_notifications(&client);
+        self.schedule_idle_shutdown();
+        client
+    }
+
+    /// Surface the progress and log notifications `client` receives as
+    /// `EventMsg::McpNotification` until the client is dropped.
+    fn forward_notifications(&self, client: &McpClient) {
+        let mut notifications = client.subscribe_notifications();
+        let server = self.launcher.server_name.clone();
+        let tx_event = self.tx_event.clone();
+        tokio::spawn(async move {
+            loop {
+                let notification = match notifications.recv().await {
+                    Ok(notification) => notification,
+                    Err(RecvError::Lagged(skipped)) => {
+                        warn!("dropped {skipped} notifications from MCP server '{server}'");
+                        continue;
+                    }
+                    Err(RecvError::Closed) => return,
+                };
+                let Some(notification) = to_mcp_notification(notification) else {
+                    continue;
+                };
+                let event = Event {
+                    id: INITIAL_SUBMIT_ID.to_owned(),
+                    msg: EventMsg::McpNotification(McpNotificationEvent {
+                        server: server.clone(),
+                        notification,
+                    }),
+                };
+                if tx_event.send(event).await.is_err() {
+                    return;
+                }
+            }
+        });
+    }
+
+    /// Restart the server if `client` exits while it is still the running
+    /// client. Clients that are dropped on purpose (idle shutdown, end of
+    /// session) are not restarted.
+    fn watch_for_exit(self: &Arc<Self>, client: &Arc<McpClient>) {
+        let closed = client.closed();
+        let exited = Arc::downgrade(client);
+        let runtime = Arc::downgrade(self);
+        tokio::spawn(async move {
+            closed.await;
+            if exited.strong_count() == 0 {
+                return;
+            }
+            if let Some(runtime) = runtime.upgrade() {
+                runtime.restart_after_exit(exited).await;
+            }
+        });
+    }
+
+    async fn restart_after_exit(self: Arc<Self>, exited: Weak<McpClient>) {
+        let server_name = &self.launcher.server_name;
+        {
+            let mut running = self.running.lock().await;
+            let uptime = match running.as_ref() {
+                Some(current) if Weak::ptr_eq(&Arc::downgrade(&current.client), &exited) => {
+                    current.started_at.elapsed()
+                }
+                _ => return,
+            };
+            *running = None;
+            if uptime >= STABLE_UPTIME {
+                self.recent_exits.store(0, Ordering::Relaxed);
+            }
+        }
+
+        let exits = self.recent_exits.fetch_add(1, Ordering::Relaxed) + 1;
+        if exits > MAX_AUTOMATIC_RESTARTS {
+            self.notify(format!(
+                "MCP server `{server_name}` keeps exiting; it will be started again the next time it is used"
+            ));
+            return;
+        }
+        let delay = restart_backoff(exits);
+        self.notify(format!(
+            "MCP server `{server_name}` exited unexpectedly; restarting in {}s",
+            delay.as_secs()
+        ));
+        tokio::time::sleep(delay).await;
+
+        let mut running = self.running.lock().await;
+        if running.is_some() {
+            // Already started again on demand.
+            return;
+        }
+        match self.launcher.start().await {
+            Ok((client, _)) => {
+                self.install(&mut running, client);
+                self.notify(format!("MCP server `{server_name}` restarted"));
+            }
+            Err(e) => self.notify(format!(
+                "Failed to restart MCP server `{server_name}`: {e:#}; it will be started again the next time it is used"
+            )),
+        }
+    }
+
+    /// Stop the server once it has gone `idle_timeout` without being used.
+    /// A call that is still in flight counts as use.
+    fn schedule_idle_shutdown(self: &Arc<Self>) {
+        let Some(idle_timeout) = self.idle_timeout else {
+            return;
+        };
+        let runtime = Arc::downgrade(self);
+        tokio::spawn(async move {
+            let mut wait = idle_timeout;
+            loop {
+                tokio::time::sleep(wait).await;
+                let Some(runtime) = runtime.upgrade() else {
+                    return;
+                };
+                let mut guard = runtime.running.lock().await;
+                let Some(current) = guard.as_mut() else {
+                    return;
+                };
+                if Arc::strong_count(&current.client) > 1 {
+                    current.last_used = Instant::now();
+                }
+                let idle_for = current.last_used.elapsed();
+                if idle_for >= idle_timeout {
+                    let server_name = &runtime.launcher.server_name;
+                    info!("stopping MCP server '{server_name}' after {idle_for:?} idle");
+                    *guard = None;
+                    return;
+                }
+                wait = idle_timeout - idle_for;
+            }
+        });
+    }
+
+    fn notify(&self, message: String) {
+        warn!("{message}");
+        let event = Event {
+            id: INITIAL_SUBMIT_ID.to_owned(),
+            msg: EventMsg::BackgroundEvent(BackgroundEventEvent { message }),
+        };
+        let _ = self.tx_event.try_send(event);
+    }
+}
+
+/// The notifications worth showing to the user. Progress tokens are the
+/// `call_id` passed to [`McpConnectionManager::call_tool`].
+fn to_mcp_notification(notification: ServerNotification) -> Option<McpNotification> {
+    match notification {
+        ServerNotification::ProgressNotification(params) => Some(McpNotification::Progress {
+            call_id: match params.progress_token {
+                ProgressToken::String(call_id) => call_id,
+                ProgressToken::Integer(token) => token.to_string(),
+            },
+            progress: params.progress,
+            total: params.total,
+            message: params.message,
+        }),
+        ServerNotification::LoggingMessageNotification(params) => Some(McpNotification::Log {
+            level: params.level,
+            logger: params.logger,
+            data: params.data,
+        }),
+        _ => None,
+    }
+}
+
+/// Delay before the `exits`-th automatic restart in a row: 1s, 2s, 4s, ...
+fn restart_backoff(exits: u32) -> Duration {
+    let secs = 1u64 << exits.saturating_sub(1).min(5);
+    Duration::from_secs(secs).min(MAX_RESTART_BACKOFF)
+}
+
+struct ManagedClient {
+    runtime: Arc<ServerRuntime>,
     tool_timeout: Option<Duration>,
+    /// Overrides of `tool_timeout` for individual tools.
+    tool_timeouts: HashMap<String, Duration>,
+    tool_filter: ToolFilter,
+    /// Slots for concurrent tool calls, from `max_concurrent_calls`. `None`
+    /// means calls are not limited.
+    call_slots: Option<Arc<Semaphore>>,
+    /// Whether the server advertised the `resources` capability.
+    supports_resources: bool,
+    /// Whether the server advertised the `prompts` capability.
+    supports_prompts: bool,
 }
 
-/// A thin wrapper around a set of running [`McpClient`] instances.
-#[derive(Default)]
+impl ManagedClient {
+    fn new(
+        server_name: &str,
+        cfg: &McpServerConfig,
+        codex_home: &Path,
+        tx_event: &Sender<Event>,
+    ) -> Self {
+        let runtime = Arc::new(ServerRuntime {
+            launcher: ServerLauncher {
+                server_name: server_name.to_string(),
+                startup_timeout: cfg.startup_timeout_sec.unwrap_or(DEFAULT_STARTUP_TIMEOUT),
+                codex_home: codex_home.to_path_buf(),
+                config: cfg.clone(),
+            },
+            running: Mutex::new(None),
+            idle_timeout: cfg.idle_timeout_sec,
+            recent_exits: AtomicU32::new(0),
+            tx_event: tx_event.clone(),
+        });
+        Self {
+            runtime,
+            tool_timeout: Some(cfg.tool_timeout_sec.unwrap_or(DEFAULT_TOOL_TIMEOUT)),
+            tool_timeouts: cfg
+                .tool_timeouts_ms
+                .iter()
+                .map(|(tool, ms)| (tool.clone(), Duration::from_millis(*ms)))
+                .collect(),
+            tool_filter: ToolFilter::new(&cfg.include_tools, &cfg.exclude_tools),
+            call_slots: cfg
+                .max_concurrent_calls
+                .map(|limit| Arc::new(Semaphore::new(limit.get()))),
+            supports_resources: false,
+            supports_prompts: false,
+        }
+    }
+
+    async fn client(&self) -> Result<Arc<McpClient>> {
+        self.runtime.client().await
+    }
+
+    /// How long a call to `tool` may run.
+    fn timeout_for_tool(&self, tool: &str) -> Option<Duration> {
+        self.tool_timeouts.get(tool).copied().or(self.tool_timeout)
+    }
+
+    /// Wait for a free call slot when the server has `max_concurrent_calls`
+    /// set. A call that has to wait is reported as queued through a progress
+    /// event for `call_id`. The call may run while the returned permit is held.
+    async fn acquire_call_slot(
+        &self,
+        server: &str,
+        call_id: &str,
+    ) -> Result<Option<OwnedSemaphorePermit>> {
+        let Some(slots) = &self.call_slots else {
+            return Ok(None);
+        };
+        if let Ok(permit) = slots.clone().try_acquire_owned() {
+            return Ok(Some(permit));
+        }
+
+        let event = Event {
+            id: INITIAL_SUBMIT_ID.to_owned(),
+            msg: EventMsg::McpNotification(McpNotificationEvent {
+                server: server.to_string(),
+                notification: McpNotification::Progress {
+                    call_id: call_id.to_string(),
+                    progress: 0.0,
+                    total: None,
+                    message: Some(format!(
+                        "Queued: waiting for an earlier call to `{server}` to finish"
+                    )),
+                },
+            }),
+        };
+        let _ = self.runtime.tx_event.send(event).await;
+
+        let permit = slots
+            .clone()
+            .acquire_owned()
+            .await
+            .map_err(|_| anyhow!("call slots for MCP server '{server}' were closed"))?;
+        Ok(Some(permit))
+    }
+}
+
+/// A thin wrapper around a set of [`McpClient`] instances, started at session
+/// start or on first use.
 pub(crate) struct McpConnectionManager {
+    state: RwLock<ManagerState>,
+    codex_home: PathBuf,
+    tx_event: Sender<Event>,
+}
+
+/// The servers currently configured and the tools they offer. Updated in
+/// place when a server is added or removed mid-session.
+#[derive(Default)]
+struct ManagerState {
     /// Server-name -> client instance.
     ///
     /// The server name originates from the keys of the `mcp_servers` map in
-    /// the user configuration.
-    clients: HashMap<String, ManagedClient>,
+    /// the user configuration, or from `Op::AddMcpServer`.
+    clients: HashMap<String, Arc<ManagedClient>>,
 
     /// Fully qualified tool name -> tool instance.
     tools: HashMap<String, ToolInfo>,
 }
 
+impl Default for McpConnectionManager {
+    fn default() -> Self {
+        Self {
+            state: RwLock::default(),
+            codex_home: PathBuf::new(),
+            tx_event: async_channel::unbounded().0,
+        }
+    }
+}
+
 impl McpConnectionManager {
-    /// Spawn a [`McpClient`] for each configured server.
+    /// Spawn a [`McpClient`] for each configured server, except `lazy` ones
+    /// whose tools are already cached: those start on first use.
     ///
     /// * `mcp_servers` – Map loaded from the user configuration where *keys*
     ///   are human-readable server identifiers and *values* are the spawn
     ///   instructions.
     ///
+    /// * `codex_home` – Where OAuth tokens for HTTP servers and cached tool
+    ///   lists are stored.
+    ///
+    /// * `tx_event` – Receives a `BackgroundEvent` when a server exits
+    ///   unexpectedly and is restarted.
+    ///
     /// Servers that fail to start are reported in `ClientStartErrors`: the
     /// user should be informed about these errors.
     pub async fn new(
         mcp_servers: HashMap<String, McpServerConfig>,
+        codex_home: PathBuf,
+        tx_event: Sender<Event>,
     ) -> Result<(Self, ClientStartErrors)> {
         // Early exit if no servers are configured.
         if mcp_servers.is_empty() {
-            return Ok((Self::default(), ClientStartErrors::default()));
+            let manager = Self {
+                state: RwLock::default(),
+                codex_home,
+                tx_event,
+            };
+            return Ok((manager, ClientStartErrors::default()));
         }
 
         // Launch all configured servers concurrently.
         let mut join_set = JoinSet::new();
         let mut errors = ClientStartErrors::new();
+        let mut clients: HashMap<String, ManagedClient> = HashMap::with_capacity(mcp_servers.len());
+        let mut cached_tools: HashMap<String, Vec<Tool>> = HashMap::new();
 
         for (server_name, cfg) in mcp_servers {
             // Validate server name before spawning
@@ -136,65 +659,31 @@ impl McpConnectionManager {
                 continue;
             }
 
-            let startup_timeout = cfg.startup_timeout_sec.unwrap_or(DEFAULT_STARTUP_TIMEOUT);
+            let cached = if cfg.lazy {
+                mcp_tool_cache::load(&codex_home, &server_name, &cfg)
+            } else {
+                None
+            };
 
-            let tool_timeout = cfg.tool_timeout_sec.unwrap_or(DEFAULT_TOOL_TIMEOUT);
+            let mut managed = ManagedClient::new(&server_name, &cfg, &codex_home, &tx_event);
+            let runtime = managed.runtime.clone();
 
-            join_set.spawn(async move {
-                let McpServerConfig {
-                    command, args, env, ..
-                } = cfg;
-                let client_res = McpClient::new_stdio_client(
-                    command.into(),
-                    args.into_iter().map(OsString::from).collect(),
-                    env,
-                )
-                .await;
-                match client_res {
-                    Ok(client) => {
-                        // Initialize the client.
-                        let params = mcp_types::InitializeRequestParams {
-                            capabilities: ClientCapabilities {
-                                experimental: None,
-                                roots: None,
-                                sampling: None,
-                                // https://modelcontextprotocol.io/specification/2025-06-18/client/elicitation#capabilities
-                                // indicates this should be an empty object.
-                                elicitation: Some(json!({})),
-                            },
-                            client_info: Implementation {
-                                name: "codex-mcp-client".to_owned(),
-                                version: env!("CARGO_PKG_VERSION").to_owned(),
-                                title: Some("Codex".into()),
-                                // This field is used by Codex when it is an MCP
-                                // server: it should not be used when Codex is
-                                // an MCP client.
-                                user_agent: None,
-                            },
-                            protocol_version: mcp_types::MCP_SCHEMA_VERSION.to_owned(),
-                        };
-                        let initialize_notification_params = None;
-                        let init_result = client
-                            .initialize(
-                                params,
-                                initialize_notification_params,
-                                Some(startup_timeout),
-                            )
-                            .await;
-                        (
-                            (server_name, tool_timeout),
-                            init_result.map(|_| (client, startup_timeout)),
-                        )
-                    }
-                    Err(e) => ((server_name, tool_timeout), Err(e.into())),
+            match cached {
+                Some(cached) => {
+                    managed.supports_resources = cached.supports_resources;
+                    managed.supports_prompts = cached.supports_prompts;
+                    cached_tools.insert(server_name.clone(), cached.tools);
                 }
-            });
+                None => {
+                    let server_name = server_name.clone();
+                    join_set.spawn(async move { (server_name, runtime.launcher.start().await) });
+                }
+            }
+            clients.insert(server_name, managed);
         }
 
-        let mut clients: HashMap<String, ManagedClient> = HashMap::with_capacity(join_set.len());
-
         while let Some(res) = join_set.join_next().await {
-            let ((server_name, tool_timeout), client_res) = match res {
+            let (server_name, client_res) = match res {
                 Ok(result) => result,
                 Err(e) => {
                     warn!("Task panic when starting MCP server: {e:#}");
@@ -203,23 +692,26 @@ impl McpConnectionManager {
             };
 
             match client_res {
-                Ok((client, startup_timeout)) => {
-                    clients.insert(
-                        server_name,
-                        ManagedClient {
-                            client: Arc::new(client),
-                            startup_timeout,
-                            tool_timeout: Some(tool_timeout),
-                        },
-                    );
+                Ok((client, capabilities)) => {
+                    if let Some(managed) = clients.get_mut(&server_name) {
+                        managed.supports_resources = capabilities.resources.is_some();
+                        managed.supports_prompts = capabilities.prompts.is_some();
+                        let runtime = &managed.runtime;
+                        runtime.install(&mut *runtime.running.lock().await, client);
+                    }
                 }
                 Err(e) => {
+                    clients.remove(&server_name);
                     errors.insert(server_name, e);
                 }
             }
         }
 
-        let all_tools = match list_all_tools(&clients).await {
+        let clients: HashMap<String, Arc<ManagedClient>> = clients
+            .into_iter()
+            .map(|(server_name, managed)| (server_name, Arc::new(managed)))
+            .collect();
+        let all_tools = match list_all_tools(&clients, cached_tools).await {
             Ok(tools) => tools,
             Err(e) => {
                 warn!("Failed to list tools from some MCP servers: {e:#}");
@@ -229,65 +721,369 @@ impl McpConnectionManager {
 
         let tools = qualify_tools(all_tools);
 
-        Ok((Self { clients, tools }, errors))
+        let manager = Self {
+            state: RwLock::new(ManagerState { clients, tools }),
+            codex_home,
+            tx_event,
+        };
+        Ok((manager, errors))
+    }
+
+    /// Start the server `server_name` and make its tools available, replacing
+    /// any server already configured under that name. Like at session start,
+    /// a `lazy` server whose tools are cached is not started until first use.
+    pub async fn add_server(&self, server_name: String, cfg: McpServerConfig) -> Result<()> {
+        if !is_valid_mcp_server_name(&server_name) {
+            return Err(anyhow!(
+                "invalid server name '{server_name}': must match pattern ^[a-zA-Z0-9_-]+$"
+            ));
+        }
+
+        let mut managed = ManagedClient::new(&server_name, &cfg, &self.codex_home, &self.tx_event);
+        let mut cached_tools = HashMap::new();
+        let cached = if cfg.lazy {
+            mcp_tool_cache::load(&self.codex_home, &server_name, &cfg)
+        } else {
+            None
+        };
+        match cached {
+            Some(cached) => {
+                managed.supports_resources = cached.supports_resources;
+                managed.supports_prompts = cached.supports_prompts;
+                cached_tools.insert(server_name.clone(), cached.tools);
+            }
+            None => {
+                let (client, capabilities) = managed
+                    .runtime
+                    .launcher
+                    .start()
+                    .await
+                    .with_context(|| format!("failed to start MCP server '{server_name}'"))?;
+                managed.supports_resources = capabilities.resources.is_some();
+                managed.supports_prompts = capabilities.prompts.is_some();
+                let runtime = &managed.runtime;
+                runtime.install(&mut *runtime.running.lock().await, client);
+            }
+        }
+
+        let new_clients = HashMap::from([(server_name.clone(), Arc::new(managed))]);
+        let new_tools = list_all_tools(&new_clients, cached_tools).await?;
+
+        let mut state = self.write_state();
+        let mut all_tools: Vec<ToolInfo> = state
+            .tools
+            .values()
+            .filter(|tool| tool.server_name != server_name)
+            .cloned()
+            .collect();
+        all_tools.extend(new_tools);
+        state.tools = qualify_tools(all_tools);
+        state.clients.extend(new_clients);
+        info!("added MCP server '{server_name}'");
+        Ok(())
+    }
+
+    /// Stop the server `server_name` and withdraw its tools. Returns `false`
+    /// if no such server is configured.
+    pub fn remove_server(&self, server_name: &str) -> bool {
+        let mut state = self.write_state();
+        if state.clients.remove(server_name).is_none() {
+            return false;
+        }
+        state
+            .tools
+            .retain(|_, tool| tool.server_name != server_name);
+        info!("removed MCP server '{server_name}'");
+        true
+    }
+
+    fn read_state(&self) -> RwLockReadGuard<'_, ManagerState> {
+        match self.state.read() {
+            Ok(guard) => guard,
+            Err(poisoned) => poisoned.into_inner(),
+        }
+    }
+
+    fn write_state(&self) -> RwLockWriteGuard<'_, ManagerState> {
+        match self.state.write() {
+            Ok(guard) => guard,
+            Err(poisoned) => poisoned.into_inner(),
+        }
+    }
+
+    /// The client for `server`, cloned out so no lock is held across awaits.
+    fn managed_client(&self, server: &str) -> Result<Arc<ManagedClient>> {
+        self.read_state()
+            .clients
+            .get(server)
+            .cloned()
+            .ok_or_else(|| anyhow!("unknown MCP server '{server}'"))
     }
 
     /// Returns a single map that contains **all** tools. Each key is the
     /// fully-qualified name for the tool.
     pub fn list_all_tools(&self) -> HashMap<String, Tool> {
-        self.tools
+        self.read_state()
+            .tools
             .iter()
             .map(|(name, tool)| (name.clone(), tool.tool.clone()))
             .collect()
     }
 
-    /// Invoke the tool indicated by the (server, tool) pair.
+    /// Invoke the tool indicated by the (server, tool) pair. Progress the
+    /// server reports for the call is emitted as `McpNotification` events
+    /// carrying `call_id`.
     pub async fn call_tool(
         &self,
         server: &str,
         tool: &str,
         arguments: Option<serde_json::Value>,
+        call_id: &str,
     ) -> Result<mcp_types::CallToolResult> {
-        let managed = self
-            .clients
-            .get(server)
-            .ok_or_else(|| anyhow!("unknown MCP server '{server}'"))?;
-        let client = managed.client.clone();
-        let timeout = managed.tool_timeout;
+        let managed = self.managed_client(server)?;
+        let _slot = managed.acquire_call_slot(server, call_id).await?;
+        let client = managed.client().await?;
+        let timeout = managed.timeout_for_tool(tool);
 
         client
-            .call_tool(tool.to_string(), arguments, timeout)
+            .call_tool(
+                tool.to_string(),
+                arguments,
+                Some(ProgressToken::String(call_id.to_string())),
+                timeout,
+            )
             .await
             .with_context(|| format!("tool call failed for `{server}/{tool}`"))
     }
 
+    /// The `outputSchema` the (server, tool) pair declared, if any.
+    pub fn tool_output_schema(&self, server: &str, tool: &str) -> Option<serde_json::Value> {
+        self.read_state()
+            .tools
+            .values()
+            .find(|info| info.server_name == server && info.tool_name == tool)
+            .and_then(|info| info.tool.output_schema.as_ref())
+            .and_then(|schema| serde_json::to_value(schema).ok())
+    }
+
+    /// Whether the server marked the fully qualified tool `tool_name` as
+    /// read-only with the `readOnlyHint` annotation.
+    pub fn is_read_only_tool(&self, tool_name: &str) -> bool {
+        self.read_state()
+            .tools
+            .get(tool_name)
+            .and_then(|info| info.tool.annotations.as_ref())
+            .and_then(|annotations| annotations.read_only_hint)
+            .unwrap_or(false)
+    }
+
+    /// A manager without servers that offers `tools`, given as server name
+    /// and tool pairs.
+    #[cfg(test)]
+    pub(crate) fn with_tools_for_test(tools: Vec<(&str, Tool)>) -> Self {
+        let manager = Self::default();
+        manager.write_state().tools = qualify_tools(
+            tools
+                .into_iter()
+                .map(|(server_name, tool)| ToolInfo {
+                    server_name: server_name.to_string(),
+                    tool_name: tool.name.clone(),
+                    tool,
+                })
+                .collect(),
+        );
+        manager
+    }
+
     pub fn parse_tool_name(&self, tool_name: &str) -> Option<(String, String)> {
-        self.tools
+        self.read_state()
+            .tools
             .get(tool_name)
             .map(|tool| (tool.server_name.clone(), tool.tool_name.clone()))
     }
+
+    /// Whether any connected server offers resources.
+    pub fn has_resources(&self) -> bool {
+        self.read_state()
+            .clients
+            .values()
+            .any(|managed| managed.supports_resources)
+    }
+
+    /// Server name -> resources, for every server that offers them. Servers
+    /// that fail to answer are logged and left out.
+    pub async fn list_all_resources(&self) -> HashMap<String, Vec<Resource>> {
+        let servers: Vec<(String, Arc<ManagedClient>)> = self
+            .read_state()
+            .clients
+            .iter()
+            .filter(|(_, managed)| managed.supports_resources)
+            .map(|(server_name, managed)| (server_name.clone(), managed.clone()))
+            .collect();
+        let requests = servers
+            .into_iter()
+            .map(|(server_name, managed)| async move {
+                let res = match managed.client().await {
+                    Ok(client) => list_server_resources(&client, managed.tool_timeout).await,
+                    Err(e) => Err(e),
+                };
+                (server_name, res)
+            });
+
+        let mut resources = HashMap::new();
+        for (server_name, res) in join_all(requests).await {
+            match res {
+                Ok(list) => {
+                    resources.insert(server_name, list);
+                }
+                Err(e) => {
+                    warn!("Failed to list resources for MCP server '{server_name}': {e:#}");
+                }
+            }
+        }
+        resources
+    }
+
+    /// Read the resource at `uri` from `server`.
+    pub async fn read_resource(&self, server: &str, uri: &str) -> Result<ReadResourceResult> {
+        let managed = self.managed_client(server)?;
+        if !managed.supports_resources {
+            return Err(anyhow!("MCP server '{server}' does not offer resources"));
+        }
+
+        managed
+            .client()
+            .await?
+            .read_resource(uri.to_string(), managed.tool_timeout)
+            .await
+            .with_context(|| format!("resource read failed for `{server}` {uri}"))
+    }
+
+    /// Server name -> prompts, for every server that offers them. Servers
+    /// that fail to answer are logged and left out.
+    pub async fn list_all_prompts(&self) -> HashMap<String, Vec<Prompt>> {
+        let servers: Vec<(String, Arc<ManagedClient>)> = self
+            .read_state()
+            .clients
+            .iter()
+            .filter(|(_, managed)| managed.supports_prompts)
+            .map(|(server_name, managed)| (server_name.clone(), managed.clone()))
+            .collect();
+        let requests = servers
+            .into_iter()
+            .map(|(server_name, managed)| async move {
+                let res = match managed.client().await {
+                    Ok(client) => list_server_prompts(&client, managed.tool_timeout).await,
+                    Err(e) => Err(e),
+                };
+                (server_name, res)
+            });
+
+        let mut prompts = HashMap::new();
+        for (server_name, res) in join_all(requests).await {
+            match res {
+                Ok(list) => {
+                    prompts.insert(server_name, list);
+                }
+                Err(e) => {
+                    warn!("Failed to list prompts for MCP server '{server_name}': {e:#}");
+                }
+            }
+        }
+        prompts
+    }
+
+    /// Render the prompt `name` from `server` with the given arguments.
+    pub async fn get_prompt(
+        &self,
+        server: &str,
+        name: &str,
+        arguments: Option<serde_json::Value>,
+    ) -> Result<GetPromptResult> {
+        let managed = self.managed_client(server)?;
+
+        managed
+            .client()
+            .await?
+            .get_prompt(name.to_string(), arguments, managed.tool_timeout)
+            .await
+            .with_context(|| format!("prompt request failed for `{server}/{name}`"))
+    }
+}
+
+/// Every prompt `client` lists, following pagination cursors.
+async fn list_server_prompts(client: &McpClient, timeout: Option<Duration>) -> Result<Vec<Prompt>> {
+    let mut prompts = Vec::new();
+    let mut cursor = None;
+    for _ in 0..MAX_LIST_PAGES {
+        let params = cursor.map(|cursor| ListPromptsRequestParams {
+            cursor: Some(cursor),
+        });
+        let page = client.list_prompts(params, timeout).await?;
+        prompts.extend(page.prompts);
+        match page.next_cursor {
+            Some(next) => cursor = Some(next),
+            None => return Ok(prompts),
+        }
+    }
+    warn!("stopped listing MCP prompts after {MAX_LIST_PAGES} pages");
+    Ok(prompts)
 }
 
-/// Query every server for its available tools and return a single map that
-/// contains **all** tools. Each key is the fully-qualified name for the tool.
-async fn list_all_tools(clients: &HashMap<String, ManagedClient>) -> Result<Vec<ToolInfo>> {
+/// Every resource `client` lists, following pagination cursors.
+async fn list_server_resources(
+    client: &McpClient,
+    timeout: Option<Duration>,
+) -> Result<Vec<Resource>> {
+    let mut resources = Vec::new();
+    let mut cursor = None;
+    for _ in 0..MAX_LIST_PAGES {
+        let params = cursor.map(|cursor| ListResourcesRequestParams {
+            cursor: Some(cursor),
+        });
+        let page = client.list_resources(params, timeout).await?;
+        resources.extend(page.resources);
+        match page.next_cursor {
+            Some(next) => cursor = Some(next),
+            None => return Ok(resources),
+        }
+    }
+    warn!("stopped listing MCP resources after {MAX_LIST_PAGES} pages");
+    Ok(resources)
+}
+
+/// Query every running server for its available tools (using `cached_tools`
+/// for servers that have not been started) and return a single list of
+/// **all** tools allowed by each server's tool filter. The tools of servers
+/// that were queried are cached for later `lazy` starts.
+async fn list_all_tools(
+    clients: &HashMap<String, Arc<ManagedClient>>,
+    mut cached_tools: HashMap<String, Vec<Tool>>,
+) -> Result<Vec<ToolInfo>> {
     let mut join_set = JoinSet::new();
 
     // Spawn one task per server so we can query them concurrently. This
     // keeps the overall latency roughly at the slowest server instead of
     // the cumulative latency.
     for (server_name, managed_client) in clients {
+        let Some(client_clone) = managed_client
+            .runtime
+            .running
+            .lock()
+            .await
+            .as_ref()
+            .map(|running| running.client.clone())
+        else {
+            continue;
+        };
         let server_name_cloned = server_name.clone();
-        let client_clone = managed_client.client.clone();
-        let startup_timeout = managed_client.startup_timeout;
+        let startup_timeout = managed_client.runtime.launcher.startup_timeout;
         join_set.spawn(async move {
             let res = client_clone.list_tools(None, Some(startup_timeout)).await;
             (server_name_cloned, res)
         });
     }
 
-    let mut aggregated: Vec<ToolInfo> = Vec::with_capacity(join_set.len());
-
     while let Some(join_res) = join_set.join_next().await {
         let (server_name, list_result) = if let Ok(result) = join_res {
             result
@@ -303,7 +1099,31 @@ async fn list_all_tools(clients: &HashMap<String, ManagedClient>) -> Result<Vec<
             continue;
         };
 
-        for tool in list_result.tools {
+        if let Some(managed) = clients.get(&server_name) {
+            let launcher = &managed.runtime.launcher;
+            mcp_tool_cache::store(
+                &launcher.codex_home,
+                &server_name,
+                &CachedServer::new(
+                    &launcher.config,
+                    list_result.tools.clone(),
+                    managed.supports_resources,
+                    managed.supports_prompts,
+                ),
+            );
+        }
+        cached_tools.insert(server_name, list_result.tools);
+    }
+
+    let mut aggregated: Vec<ToolInfo> = Vec::new();
+    for (server_name, tools) in cached_tools {
+        let tool_filter = clients
+            .get(&server_name)
+            .map(|managed| &managed.tool_filter);
+        for tool in tools {
+            if tool_filter.is_some_and(|filter| !filter.allows(&tool.name)) {
+                continue;
+            }
             let tool_info = ToolInfo {
                 server_name: server_name.clone(),
                 tool_name: tool.name.clone(),
@@ -329,6 +1149,32 @@ fn is_valid_mcp_server_name(server_name: &str) -> bool {
             .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
 }
 
+/// Picks the bearer token for an HTTP server: the environment variable named
+/// in its config if there is one, otherwise the OAuth token stored by
+/// `codex mcp login` (refreshed first if it is about to expire).
+async fn bearer_token(
+    codex_home: &Path,
+    server_name: &str,
+    env_var: Option<&str>,
+    uses_oauth: bool,
+) -> Result<Option<String>> {
+    if let Some(var) = env_var {
+        return std::env::var(var).map(Some).map_err(|e| {
+            anyhow!("failed to read bearer token from environment variable {var}: {e}")
+        });
+    }
+    if !uses_oauth {
+        return Ok(None);
+    }
+    let client = crate::default_client::create_client();
+    match mcp_oauth_access_token(codex_home, server_name, &client).await? {
+        Some(token) => Ok(Some(token)),
+        None => Err(anyhow!(
+            "not logged in; run `codex mcp login {server_name}` to authorize Codex"
+        )),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -353,6 +1199,117 @@ mod tests {
         }
     }
 
+    #[test]
+    fn progress_and_log_notifications_are_surfaced() {
+        let progress: ServerNotification = serde_json::from_value(json!({
+            "method": "notifications/progress",
+            "params": { "progressToken": "call-7", "progress": 2, "message": "Indexing" },
+        }))
+        .unwrap();
+        assert_eq!(
+            to_mcp_notification(progress),
+            Some(McpNotification::Progress {
+                call_id: "call-7".to_string(),
+                progress: 2.0,
+                total: None,
+                message: Some("Indexing".to_string()),
+            })
+        );
+
+        let log: ServerNotification = serde_json::from_value(json!({
+            "method": "notifications/message",
+            "params": { "level": "warning", "data": "rate limited" },
+        }))
+        .unwrap();
+        assert_eq!(
+            to_mcp_notification(log),
+            Some(McpNotification::Log {
+                level: mcp_types::LoggingLevel::Warning,
+                logger: None,
+                data: json!("rate limited"),
+            })
+        );
+
+        let list_changed: ServerNotification = serde_json::from_value(json!({
+            "method": "notifications/tools/list_changed",
+            "params": {},
+        }))
+        .unwrap();
+        assert_eq!(to_mcp_notification(list_changed), None);
+    }
+
+    #[tokio::test]
+    async fn calls_beyond_max_concurrent_calls_are_queued() {
+        let cfg: McpServerConfig =
+            toml::from_str("command = \"echo\"\nmax_concurrent_calls = 1").unwrap();
+        let (tx_event, rx_event) = async_channel::unbounded();
+        let managed = ManagedClient::new("docs", &cfg, Path::new("/tmp"), &tx_event);
+
+        let first = managed.acquire_call_slot("docs", "call-1").await.unwrap();
+        assert!(first.is_some());
+        assert!(rx_event.is_empty());
+
+        let second = managed.acquire_call_slot("docs", "call-2");
+        tokio::pin!(second);
+        assert!(
+            tokio::time::timeout(Duration::from_millis(50), &mut second)
+                .await
+                .is_err(),
+            "second call should wait for the first"
+        );
+        let event = rx_event.try_recv().unwrap();
+        match event.msg {
+            EventMsg::McpNotification(McpNotificationEvent {
+                server,
+                notification: McpNotification::Progress { call_id, .. },
+            }) => {
+                assert_eq!(server, "docs");
+                assert_eq!(call_id, "call-2");
+            }
+            other => panic!("unexpected event: {other:?}"),
+        }
+
+        drop(first);
+        assert!(second.await.unwrap().is_some());
+    }
+
+    #[tokio::test]
+    async fn calls_are_not_limited_by_default() {
+        let cfg: McpServerConfig = toml::from_str("command = \"echo\"").unwrap();
+        let (tx_event, _rx_event) = async_channel::unbounded();
+        let managed = ManagedClient::new("docs", &cfg, Path::new("/tmp"), &tx_event);
+        assert!(
+            managed
+                .acquire_call_slot("docs", "call-1")
+                .await
+                .unwrap()
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn restart_backoff_doubles_up_to_the_cap() {
+        let delays: Vec<u64> = (1..=7).map(|n| restart_backoff(n).as_secs()).collect();
+        assert_eq!(delays, vec![1, 2, 4, 8, 16, 30, 30]);
+    }
+
+    #[test]
+    fn tool_filter_applies_include_then_exclude_globs() {
+        let filter = ToolFilter::new(
+            &["search_*".to_string(), "get_issue".to_string()],
+            &["search_admin*".to_string()],
+        );
+        assert!(filter.allows("search_issues"));
+        assert!(filter.allows("get_issue"));
+        assert!(!filter.allows("search_admin_logs"));
+        assert!(!filter.allows("delete_issue"));
+
+        let exclude_only = ToolFilter::new(&[], &["delete_*".to_string()]);
+        assert!(exclude_only.allows("get_issue"));
+        assert!(!exclude_only.allows("delete_issue"));
+        assert!(ToolFilter::default().allows("anything"));
+    }
+
     #[test]
     fn test_qualify_tools_short_non_duplicated_names() {
         let tools = vec![
@@ -0,0 +1,262 @@
+//! OAuth tokens for MCP servers reached over HTTP.
+//!
+//! Tokens are obtained with `codex mcp login <server>` and stored per server
+//! under `mcp_servers` in `$CODEX_HOME/auth.json`. The token endpoint and
+//! client id used to obtain them are stored alongside so an expired access
+//! token can be refreshed without repeating discovery.
+
+use std::path::Path;
+use std::time::Duration;
+
+use chrono::DateTime;
+use chrono::Utc;
+use serde::Deserialize;
+use serde::Serialize;
+
+use crate::auth::AuthDotJson;
+use crate::auth::get_auth_file;
+use crate::auth::try_read_auth_json;
+use crate::auth::write_auth_json;
+
+/// Access tokens this close to expiring are refreshed before use.
+const REFRESH_MARGIN: chrono::Duration = chrono::Duration::seconds(60);
+
+const REFRESH_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Tokens issued to Codex for one MCP server.
+#[derive(Deserialize, Serialize, Clone, Debug, PartialEq)]
+pub struct McpOAuthTokens {
+    pub access_token: String,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub refresh_token: Option<String>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expires_at: Option<DateTime<Utc>>,
+
+    /// Token endpoint of the authorization server that issued the tokens.
+    pub token_endpoint: String,
+
+    pub client_id: String,
+}
+
+impl McpOAuthTokens {
+    /// Build the stored tokens from a successful token endpoint response.
+    /// Authorization servers may omit the refresh token when refreshing, in
+    /// which case `previous_refresh_token` is kept.
+    pub fn from_response(
+        response: OAuthTokenResponse,
+        token_endpoint: String,
+        client_id: String,
+        previous_refresh_token: Option<String>,
+    ) -> Self {
+        Self {
+            access_token: response.access_token,
+            refresh_token: response.refresh_token.or(previous_refresh_token),
+            expires_at: response
+                .expires_in
+                .and_then(|secs| i64::try_from(secs).ok())
+                .map(|secs| Utc::now() + chrono::Duration::seconds(secs)),
+            token_endpoint,
+            client_id,
+        }
+    }
+
+    fn needs_refresh(&self, now: DateTime<Utc>) -> bool {
+        self.refresh_token.is_some()
+            && self
+                .expires_at
+                .is_some_and(|expires_at| expires_at - REFRESH_MARGIN <= now)
+    }
+}
+
+/// Successful response from an OAuth token endpoint (RFC 6749 §5.1).
+#[derive(Deserialize, Debug)]
+pub struct OAuthTokenResponse {
+    pub access_token: String,
+    #[serde(default)]
+    pub refresh_token: Option<String>,
+    #[serde(default)]
+    pub expires_in: Option<u64>,
+}
+
+/// Error response from an OAuth token endpoint (RFC 6749 §5.2).
+#[derive(Deserialize, Debug)]
+pub struct OAuthErrorResponse {
+    pub error: String,
+    #[serde(default)]
+    pub error_description: Option<String>,
+}
+
+impl std::fmt::Display for OAuthErrorResponse {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.error_description {
+            Some(description) => write!(f, "{}: {description}", self.error),
+            None => write!(f, "{}", self.error),
+        }
+    }
+}
+
+/// Returns the stored tokens for `server`, if any.
+pub fn read_mcp_oauth_tokens(
+    codex_home: &Path,
+    server: &str,
+) -> std::io::Result<Option<McpOAuthTokens>> {
+    match try_read_auth_json(&get_auth_file(codex_home)) {
+        Ok(mut auth) => Ok(auth.mcp_servers.remove(server)),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(e),
+    }
+}
+
+/// Stores `tokens` for `server`, keeping everything else in `auth.json`.
+pub fn save_mcp_oauth_tokens(
+    codex_home: &Path,
+    server: &str,
+    tokens: McpOAuthTokens,
+) -> std::io::Result<()> {
+    let auth_file = get_auth_file(codex_home);
+    let mut auth = match try_read_auth_json(&auth_file) {
+        Ok(auth) => auth,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => AuthDotJson {
+            openai_api_key: None,
+            tokens: None,
+            last_refresh: None,
+            mcp_servers: Default::default(),
+        },
+        Err(e) => return Err(e),
+    };
+    auth.mcp_servers.insert(server.to_string(), tokens);
+    write_auth_json(&auth_file, &auth)
+}
+
+/// Forgets the tokens for `server`. Returns `Ok(true)` if any were stored.
+pub fn remove_mcp_oauth_tokens(codex_home: &Path, server: &str) -> std::io::Result<bool> {
+    let auth_file = get_auth_file(codex_home);
+    let mut auth = match try_read_auth_json(&auth_file) {
+        Ok(auth) => auth,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(false),
+        Err(e) => return Err(e),
+    };
+    if auth.mcp_servers.remove(server).is_none() {
+        return Ok(false);
+    }
+    write_auth_json(&auth_file, &auth)?;
+    Ok(true)
+}
+
+/// Returns a usable access token for `server`, refreshing and storing a new
+/// one first if the stored token is about to expire. Returns `Ok(None)` if
+/// the user has not logged in to `server`.
+pub async fn mcp_oauth_access_token(
+    codex_home: &Path,
+    server: &str,
+    client: &reqwest::Client,
+) -> std::io::Result<Option<String>> {
+    let Some(tokens) = read_mcp_oauth_tokens(codex_home, server)? else {
+        return Ok(None);
+    };
+    if !tokens.needs_refresh(Utc::now()) {
+        return Ok(Some(tokens.access_token));
+    }
+
+    let refreshed = tokio::time::timeout(REFRESH_TIMEOUT, refresh(&tokens, client))
+        .await
+        .map_err(|_| {
+            std::io::Error::other(format!(
+                "timed out while refreshing OAuth token for MCP server {server}"
+            ))
+        })??;
+    let access_token = refreshed.access_token.clone();
+    save_mcp_oauth_tokens(codex_home, server, refreshed)?;
+    Ok(Some(access_token))
+}
+
+async fn refresh(
+    tokens: &McpOAuthTokens,
+    client: &reqwest::Client,
+) -> std::io::Result<McpOAuthTokens> {
+    let refresh_token = tokens.refresh_token.clone().unwrap_or_default();
+    let response = client
+        .post(&tokens.token_endpoint)
+        .form(&[
+            ("grant_type", "refresh_token"),
+            ("refresh_token", refresh_token.as_str()),
+            ("client_id", tokens.client_id.as_str()),
+        ])
+        .send()
+        .await
+        .map_err(std::io::Error::other)?;
+
+    if !response.status().is_success() {
+        let status = response.status();
+        let detail = match response.json::<OAuthErrorResponse>().await {
+            Ok(error) => error.to_string(),
+            Err(_) => status.to_string(),
+        };
+        return Err(std::io::Error::other(format!(
+            "failed to refresh MCP OAuth token: {detail}; run `codex mcp login` again"
+        )));
+    }
+
+    let body = response
+        .json::<OAuthTokenResponse>()
+        .await
+        .map_err(std::io::Error::other)?;
+    Ok(McpOAuthTokens::from_response(
+        body,
+        tokens.token_endpoint.clone(),
+        tokens.client_id.clone(),
+        Some(refresh_token),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::auth::login_with_api_key;
+    use pretty_assertions::assert_eq;
+    use tempfile::tempdir;
+
+    fn tokens(expires_at: Option<DateTime<Utc>>) -> McpOAuthTokens {
+        McpOAuthTokens {
+            access_token: "access".to_string(),
+            refresh_token: Some("refresh".to_string()),
+            expires_at,
+            token_endpoint: "https://auth.example.com/token".to_string(),
+            client_id: "codex".to_string(),
+        }
+    }
+
+    #[test]
+    fn tokens_are_stored_per_server_alongside_api_key() -> std::io::Result<()> {
+        let dir = tempdir()?;
+        login_with_api_key(dir.path(), "sk-test-key")?;
+
+        save_mcp_oauth_tokens(dir.path(), "docs", tokens(None))?;
+        assert_eq!(
+            read_mcp_oauth_tokens(dir.path(), "docs")?,
+            Some(tokens(None))
+        );
+        assert_eq!(read_mcp_oauth_tokens(dir.path(), "other")?, None);
+        let auth = try_read_auth_json(&get_auth_file(dir.path()))?;
+        assert_eq!(auth.openai_api_key.as_deref(), Some("sk-test-key"));
+
+        assert!(remove_mcp_oauth_tokens(dir.path(), "docs")?);
+        assert!(!remove_mcp_oauth_tokens(dir.path(), "docs")?);
+        assert_eq!(read_mcp_oauth_tokens(dir.path(), "docs")?, None);
+        Ok(())
+    }
+
+    #[test]
+    fn only_expiring_tokens_with_a_refresh_token_need_refresh() {
+        let now = Utc::now();
+        assert!(!tokens(None).needs_refresh(now));
+        assert!(!tokens(Some(now + chrono::Duration::hours(1))).needs_refresh(now));
+        assert!(tokens(Some(now + chrono::Duration::seconds(30))).needs_refresh(now));
+
+        let mut without_refresh = tokens(Some(now));
+        without_refresh.refresh_token = None;
+        assert!(!without_refresh.needs_refresh(now));
+    }
+}
@@ -0,0 +1,140 @@
+//! On-disk cache of the tools each MCP server offers, so servers configured
+//! with `lazy = true` can be described to the model without starting them.
+//!
+//! Entries live in `$CODEX_HOME/mcp_cache/<server>.json` and are keyed by a
+//! fingerprint of how the server is launched: changing its command, args,
+//! env, url or headers invalidates the entry, and the server is started once
+//! to refresh it.
+
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::path::PathBuf;
+
+use mcp_types::Tool;
+use serde::Deserialize;
+use serde::Serialize;
+use sha1::Digest;
+use sha1::Sha1;
+use tracing::warn;
+
+use crate::config_types::McpServerConfig;
+
+const MCP_CACHE_SUBDIR: &str = "mcp_cache";
+
+/// What Codex learned about a server the last time it was started.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub(crate) struct CachedServer {
+    fingerprint: String,
+    pub tools: Vec<Tool>,
+    pub supports_resources: bool,
+    pub supports_prompts: bool,
+}
+
+impl CachedServer {
+    pub fn new(
+        config: &McpServerConfig,
+        tools: Vec<Tool>,
+        supports_resources: bool,
+        supports_prompts: bool,
+    ) -> Self {
+        Self {
+            fingerprint: fingerprint(config),
+            tools,
+            supports_resources,
+            supports_prompts,
+        }
+    }
+}
+
+/// Returns the cached description of `server` if it was recorded for the
+/// same launch configuration.
+pub(crate) fn load(
+    codex_home: &Path,
+    server: &str,
+    config: &McpServerConfig,
+) -> Option<CachedServer> {
+    let contents = std::fs::read_to_string(cache_path(codex_home, server)).ok()?;
+    let cached: CachedServer = serde_json::from_str(&contents).ok()?;
+    (cached.fingerprint == fingerprint(config)).then_some(cached)
+}
+
+/// Records `cached` for `server`. Failures are logged: the cache only saves
+/// a server start next session.
+pub(crate) fn store(codex_home: &Path, server: &str, cached: &CachedServer) {
+    let path = cache_path(codex_home, server);
+    let result = path
+        .parent()
+        .map_or(Ok(()), std::fs::create_dir_all)
+        .and_then(|()| {
+            let json = serde_json::to_string(cached)?;
+            std::fs::write(&path, json)
+        });
+    if let Err(e) = result {
+        warn!("failed to cache tools for MCP server '{server}': {e}");
+    }
+}
+
+fn cache_path(codex_home: &Path, server: &str) -> PathBuf {
+    codex_home
+        .join(MCP_CACHE_SUBDIR)
+        .join(format!("{server}.json"))
+}
+
+fn fingerprint(config: &McpServerConfig) -> String {
+    let sorted = |map: &Option<std::collections::HashMap<String, String>>| {
+        map.as_ref()
+            .map(|map| map.iter().collect::<BTreeMap<_, _>>())
+    };
+    let launch = serde_json::json!({
+        "command": config.command,
+        "args": config.args,
+        "env": sorted(&config.env),
+        "url": config.url,
+        "headers": sorted(&config.headers),
+    });
+    let mut hasher = Sha1::new();
+    hasher.update(launch.to_string().as_bytes());
+    format!("{:x}", hasher.finalize())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mcp_types::ToolInputSchema;
+    use pretty_assertions::assert_eq;
+    use tempfile::tempdir;
+
+    fn config(command: &str) -> McpServerConfig {
+        toml::from_str(&format!("command = \"{command}\"\nlazy = true")).unwrap()
+    }
+
+    fn tool(name: &str) -> Tool {
+        Tool {
+            annotations: None,
+            description: None,
+            input_schema: ToolInputSchema {
+                properties: None,
+                required: None,
+                r#type: "object".to_string(),
+            },
+            name: name.to_string(),
+            output_schema: None,
+            title: None,
+        }
+    }
+
+    #[test]
+    fn cache_is_reused_until_the_launch_config_changes() {
+        let codex_home = tempdir().unwrap();
+        let docs = config("docs-server");
+        let cached = CachedServer::new(&docs, vec![tool("search")], true, false);
+        store(codex_home.path(), "docs", &cached);
+
+        assert_eq!(load(codex_home.path(), "docs", &docs), Some(cached));
+        assert_eq!(load(codex_home.path(), "other", &docs), None);
+        assert_eq!(
+            load(codex_home.path(), "docs", &config("docs-server-v2")),
+            None
+        );
+    }
+}
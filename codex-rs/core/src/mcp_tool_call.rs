@@ -1,5 +1,6 @@
 use std::time::Instant;
 
+use tokio_util::sync::CancellationToken;
 use tracing::error;
 
 use crate::codex::Session;
@@ -13,6 +14,9 @@ use codex_protocol::models::ResponseInputItem;
 
 /// Handles the specified tool call dispatches the appropriate
 /// `McpToolCallBegin` and `McpToolCallEnd` events to the `Session`.
+///
+/// Cancelling `cancellation_token` abandons the call, which tells the server
+/// to stop working on it.
 pub(crate) async fn handle_mcp_tool_call(
     sess: &Session,
     sub_id: &str,
@@ -20,6 +24,7 @@ pub(crate) async fn handle_mcp_tool_call(
     server: String,
     tool_name: String,
     arguments: String,
+    cancellation_token: &CancellationToken,
 ) -> ResponseInputItem {
     // Parse the `arguments` as JSON. An empty string is OK, but invalid JSON
     // is not.
@@ -55,15 +60,18 @@ pub(crate) async fn handle_mcp_tool_call(
 
     let start = Instant::now();
     // Perform the tool call.
-    let result = sess
-        .call_tool(&server, &tool_name, arguments_value.clone())
-        .await
-        .map_err(|e| format!("tool call error: {e}"));
+    let result = tokio::select! {
+        result = sess.call_tool(&server, &tool_name, arguments_value.clone(), &call_id) => {
+            result.map_err(|e| format!("tool call error: {e}"))
+        }
+        _ = cancellation_token.cancelled() => Err("tool call cancelled".to_string()),
+    };
     let tool_call_end_event = EventMsg::McpToolCallEnd(McpToolCallEndEvent {
         call_id: call_id.clone(),
         invocation,
         duration: start.elapsed(),
         result: result.clone(),
+        output_schema: sess.mcp_tool_output_schema(&server, &tool_name),
     });
 
     notify_mcp_tool_call_event(sess, sub_id, tool_call_end_event.clone()).await;
@@ -0,0 +1,364 @@
+//! Local HTTP proxy for sandboxes that allow only some domains.
+//!
+//! When a `workspace-write` policy has `allowed_domains`, sandboxed commands
+//! are denied all outbound network access except to this proxy on loopback,
+//! and `HTTP(S)_PROXY` points them at it. The proxy serves `CONNECT` tunnels
+//! and plain `http://` requests whose host is on the allowlist and answers
+//! everything else with `403 Forbidden`.
+//!
+//! One proxy is started per distinct allowlist and runs for the life of the
+//! process.
+
+use std::collections::HashMap;
+use std::io;
+use std::net::Ipv4Addr;
+use std::sync::Arc;
+use std::sync::LazyLock;
+
+use tokio::io::AsyncReadExt;
+use tokio::io::AsyncWriteExt;
+use tokio::net::TcpListener;
+use tokio::net::TcpStream;
+use tokio::sync::Mutex;
+
+use crate::protocol::SandboxPolicy;
+
+/// Largest request head the proxy reads before giving up on a client.
+const MAX_REQUEST_HEAD_BYTES: usize = 16 * 1024;
+
+const PROXY_ENV_VARS: [&str; 6] = [
+    "HTTP_PROXY",
+    "HTTPS_PROXY",
+    "ALL_PROXY",
+    "http_proxy",
+    "https_proxy",
+    "all_proxy",
+];
+
+/// Ports of the running proxies, keyed by their allowlist.
+static PROXIES: LazyLock<Mutex<HashMap<Vec<String>, u16>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+/// Starts (or reuses) the proxy for the policy's allowlist and points the
+/// command's environment at it. Returns the port sandboxes must let commands
+/// connect to, or `None` when the policy has no allowlist.
+pub(crate) async fn prepare_network_proxy(
+    sandbox_policy: &SandboxPolicy,
+    env: &mut HashMap<String, String>,
+) -> io::Result<Option<u16>> {
+    let allowed_domains = sandbox_policy.allowed_domains();
+    if allowed_domains.is_empty() {
+        return Ok(None);
+    }
+
+    let port = ensure_proxy(allowed_domains).await?;
+    let proxy_url = format!("http://{}:{port}", Ipv4Addr::LOCALHOST);
+    for key in PROXY_ENV_VARS {
+        env.insert(key.to_string(), proxy_url.clone());
+    }
+    // Hosts listed here would be contacted directly and fail in the sandbox.
+    env.remove("NO_PROXY");
+    env.remove("no_proxy");
+    Ok(Some(port))
+}
+
+async fn ensure_proxy(allowed_domains: &[String]) -> io::Result<u16> {
+    let mut allowed_domains: Vec<String> = allowed_domains
+        .iter()
+        .map(|domain| normalize_domain(domain))
+        .collect();
+    allowed_domains.sort();
+    allowed_domains.dedup();
+
+    let mut proxies = PROXIES.lock().await;
+    if let Some(port) = proxies.get(&allowed_domains) {
+        return Ok(*port);
+    }
+
+    let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, 0)).await?;
+    let port = listener.local_addr()?.port();
+    tokio::spawn(serve(listener, Arc::new(allowed_domains.clone())));
+    proxies.insert(allowed_domains, port);
+    Ok(port)
+}
+
+async fn serve(listener: TcpListener, allowed_domains: Arc<Vec<String>>) {
+    loop {
+        let (client, _) = match listener.accept().await {
+            Ok(accepted) => accepted,
+            Err(err) => {
+                tracing::warn!("network proxy accept failed: {err}");
+                continue;
+            }
+        };
+        let allowed_domains = Arc::clone(&allowed_domains);
+        tokio::spawn(async move {
+            if let Err(err) = handle_client(client, &allowed_domains).await {
+                tracing::debug!("network proxy connection failed: {err}");
+            }
+        });
+    }
+}
+
+async fn handle_client(mut client: TcpStream, allowed_domains: &[String]) -> io::Result<()> {
+    let (head, body_start) = read_request_head(&mut client).await?;
+    let Some(request) = ProxyRequest::parse(&head) else {
+        return respond(&mut client, "400 Bad Request", "malformed proxy request\n").await;
+    };
+
+    if !is_domain_allowed(&request.host, allowed_domains) {
+        tracing::info!("network proxy denied {}", request.host);
+        let body = format!(
+            "codex sandbox: {} is not in allowed_domains\n",
+            request.host
+        );
+        return respond(&mut client, "403 Forbidden", &body).await;
+    }
+
+    let mut upstream = match TcpStream::connect((request.host.as_str(), request.port)).await {
+        Ok(upstream) => upstream,
+        Err(err) => {
+            let body = format!("could not connect to {}: {err}\n", request.host);
+            return respond(&mut client, "502 Bad Gateway", &body).await;
+        }
+    };
+
+    match request.forward_head {
+        None => {
+            client
+                .write_all(b"HTTP/1.1 200 Connection Established\r\n\r\n")
+                .await?;
+        }
+        Some(forward_head) => upstream.write_all(&forward_head).await?,
+    }
+    upstream.write_all(&body_start).await?;
+    tokio::io::copy_bidirectional(&mut client, &mut upstream).await?;
+    Ok(())
+}
+
+/// Reads up to and including the blank line that ends the request head.
+/// Returns the head and any bytes the client sent after it.
+async fn read_request_head(client: &mut TcpStream) -> io::Result<(Vec<u8>, Vec<u8>)> {
+    let mut buf = Vec::new();
+    let mut chunk = [0u8; 4096];
+    loop {
+        let n = client.read(&mut chunk).await?;
+        if n == 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                "client closed before sending a request",
+            ));
+        }
+        buf.extend_from_slice(&chunk[..n]);
+        if let Some(end) = buf.windows(4).position(|w| w == b"\r\n\r\n") {
+            let rest = buf.split_off(end + 4);
+            return Ok((buf, rest));
+        }
+        if buf.len() > MAX_REQUEST_HEAD_BYTES {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "request head too large",
+            ));
+        }
+    }
+}
+
+async fn respond(client: &mut TcpStream, status: &str, body: &str) -> io::Result<()> {
+    let response = format!(
+        "HTTP/1.1 {status}\r\nContent-Type: text/plain\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+        body.len()
+    );
+    client.write_all(response.as_bytes()).await?;
+    client.shutdown().await
+}
+
+#[derive(Debug, PartialEq)]
+struct ProxyRequest {
+    host: String,
+    port: u16,
+    /// Head to send upstream for plain HTTP requests, rewritten to origin
+    /// form. `None` for `CONNECT`, where the client talks to the host
+    /// directly once the tunnel is up.
+    forward_head: Option<Vec<u8>>,
+}
+
+impl ProxyRequest {
+    fn parse(head: &[u8]) -> Option<Self> {
+        let head = std::str::from_utf8(head).ok()?;
+        let (request_line, rest) = head.split_once("\r\n")?;
+        let mut parts = request_line.split(' ');
+        let method = parts.next()?;
+        let target = parts.next()?;
+        let version = parts.next()?;
+
+        if method.eq_ignore_ascii_case("CONNECT") {
+            let (host, port) = split_host_port(target, None)?;
+            return Some(Self {
+                host,
+                port,
+                forward_head: None,
+            });
+        }
+
+        let after_scheme = target.strip_prefix("http://")?;
+        let (authority, path) = match after_scheme.find('/') {
+            Some(index) => after_scheme.split_at(index),
+            None => (after_scheme, "/"),
+        };
+        let (host, port) = split_host_port(authority, Some(80))?;
+        let forward_head = format!("{method} {path} {version}\r\n{rest}").into_bytes();
+        Some(Self {
+            host,
+            port,
+            forward_head: Some(forward_head),
+        })
+    }
+}
+
+/// Splits `host:port`, accepting bracketed IPv6 literals. The port may only
+/// be omitted when `default_port` is given.
+fn split_host_port(authority: &str, default_port: Option<u16>) -> Option<(String, u16)> {
+    let (host, port) = if let Some(bracketed) = authority.strip_prefix('[') {
+        let (host, rest) = bracketed.split_once(']')?;
+        (host, rest.strip_prefix(':'))
+    } else {
+        match authority.rsplit_once(':') {
+            Some((host, port)) => (host, Some(port)),
+            None => (authority, None),
+        }
+    };
+    let port = match port {
+        Some(port) => port.parse().ok()?,
+        None => default_port?,
+    };
+    if host.is_empty() {
+        return None;
+    }
+    Some((host.to_string(), port))
+}
+
+fn normalize_domain(domain: &str) -> String {
+    let domain = domain.trim();
+    let domain = domain.strip_prefix("*.").unwrap_or(domain);
+    domain.trim_end_matches('.').to_ascii_lowercase()
+}
+
+/// Whether `host` is an allowed domain or one of its subdomains.
+fn is_domain_allowed(host: &str, allowed_domains: &[String]) -> bool {
+    let host = normalize_domain(host);
+    allowed_domains.iter().any(|domain| {
+        host == *domain
+            || host
+                .strip_suffix(domain.as_str())
+                .is_some_and(|prefix| prefix.ends_with('.'))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn allows_listed_domains_and_their_subdomains() {
+        let allowed = vec!["crates.io".to_string(), "github.com".to_string()];
+
+        assert!(is_domain_allowed("crates.io", &allowed));
+        assert!(is_domain_allowed("static.crates.io", &allowed));
+        assert!(is_domain_allowed("GitHub.com.", &allowed));
+        assert!(!is_domain_allowed("evilcrates.io", &allowed));
+        assert!(!is_domain_allowed("crates.io.evil.com", &allowed));
+        assert!(!is_domain_allowed("example.com", &allowed));
+    }
+
+    #[test]
+    fn parses_connect_and_absolute_form_requests() {
+        let connect = ProxyRequest::parse(
+            b"CONNECT index.crates.io:443 HTTP/1.1\r\nHost: index.crates.io:443\r\n\r\n",
+        );
+        assert_eq!(
+            connect,
+            Some(ProxyRequest {
+                host: "index.crates.io".to_string(),
+                port: 443,
+                forward_head: None,
+            })
+        );
+
+        let get = ProxyRequest::parse(
+            b"GET http://example.com/a?b=1 HTTP/1.1\r\nHost: example.com\r\n\r\n",
+        );
+        assert_eq!(
+            get,
+            Some(ProxyRequest {
+                host: "example.com".to_string(),
+                port: 80,
+                forward_head: Some(b"GET /a?b=1 HTTP/1.1\r\nHost: example.com\r\n\r\n".to_vec()),
+            })
+        );
+
+        assert_eq!(ProxyRequest::parse(b"GET /relative HTTP/1.1\r\n\r\n"), None);
+    }
+
+    #[tokio::test]
+    async fn tunnels_allowed_hosts_and_rejects_others() {
+        let upstream = TcpListener::bind((Ipv4Addr::LOCALHOST, 0))
+            .await
+            .expect("bind upstream");
+        let upstream_port = upstream.local_addr().expect("upstream addr").port();
+        tokio::spawn(async move {
+            if let Ok((mut socket, _)) = upstream.accept().await {
+                let _ = socket.write_all(b"hello from upstream").await;
+            }
+        });
+
+        let policy = SandboxPolicy::WorkspaceWrite {
+            writable_roots: vec![],
+            network_access: false,
+            allowed_domains: vec!["localhost".to_string()],
+            exclude_tmpdir_env_var: false,
+            exclude_slash_tmp: false,
+        };
+        let mut env = HashMap::from([("NO_PROXY".to_string(), "*".to_string())]);
+        let port = prepare_network_proxy(&policy, &mut env)
+            .await
+            .expect("start proxy")
+            .expect("policy has an allowlist");
+        assert_eq!(
+            env.get("HTTPS_PROXY"),
+            Some(&format!("http://127.0.0.1:{port}"))
+        );
+        assert!(!env.contains_key("NO_PROXY"));
+
+        let mut allowed = TcpStream::connect((Ipv4Addr::LOCALHOST, port))
+            .await
+            .expect("connect to proxy");
+        allowed
+            .write_all(format!("CONNECT localhost:{upstream_port} HTTP/1.1\r\n\r\n").as_bytes())
+            .await
+            .expect("send CONNECT");
+        let mut response = Vec::new();
+        allowed
+            .read_to_end(&mut response)
+            .await
+            .expect("read tunnel");
+        assert_eq!(
+            String::from_utf8_lossy(&response),
+            "HTTP/1.1 200 Connection Established\r\n\r\nhello from upstream"
+        );
+
+        let mut denied = TcpStream::connect((Ipv4Addr::LOCALHOST, port))
+            .await
+            .expect("connect to proxy");
+        denied
+            .write_all(b"CONNECT example.com:443 HTTP/1.1\r\n\r\n")
+            .await
+            .expect("send CONNECT");
+        let mut response = Vec::new();
+        denied
+            .read_to_end(&mut response)
+            .await
+            .expect("read response");
+        assert!(String::from_utf8_lossy(&response).starts_with("HTTP/1.1 403 Forbidden\r\n"));
+    }
+}
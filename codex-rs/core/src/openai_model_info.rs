@@ -1,10 +1,7 @@
+use crate::config_types::ModelPricing;
 use crate::model_family::ModelFamily;
 
 /// Metadata about a model, particularly OpenAI models.
-/// We may want to consider including details like the pricing for
-/// input tokens, output tokens, etc., though users will need to be able to
-/// override this in config.toml, as this information can get out of date.
-/// Though this would help present more accurate pricing information in the UI.
 #[derive(Debug)]
 pub(crate) struct ModelInfo {
     /// Size of the context window in tokens. This is the maximum size of the input context.
@@ -16,6 +13,11 @@ pub(crate) struct ModelInfo {
     /// Token threshold where we should automatically compact conversation history. This considers
     /// input tokens + output tokens of this turn.
     pub(crate) auto_compact_token_limit: Option<i64>,
+
+    /// List prices, used to estimate session cost. These go out of date, so
+    /// they can be overridden with `model_pricing` in config.toml.
+    /// https://platform.openai.com/docs/pricing
+    pub(crate) pricing: Option<ModelPricing>,
 }
 
 impl ModelInfo {
@@ -24,8 +26,18 @@ impl ModelInfo {
             context_window,
             max_output_tokens,
             auto_compact_token_limit: None,
+            pricing: None,
         }
     }
+
+    const fn with_pricing(mut self, input: f64, cached_input: f64, output: f64) -> Self {
+        self.pricing = Some(ModelPricing {
+            input,
+            cached_input: Some(cached_input),
+            output,
+        });
+        self
+    }
 }
 
 pub(crate) fn get_model_info(model_family: &ModelFamily) -> Option<ModelInfo> {
@@ -37,38 +49,61 @@ pub(crate) fn get_model_info(model_family: &ModelFamily) -> Option<ModelInfo> {
         "gpt-oss-20b" => Some(ModelInfo::new(96_000, 32_000)),
         "gpt-oss-120b" => Some(ModelInfo::new(96_000, 32_000)),
         // https://platform.openai.com/docs/models/o3
-        "o3" => Some(ModelInfo::new(200_000, 100_000)),
+        "o3" => Some(ModelInfo::new(200_000, 100_000).with_pricing(2.00, 0.50, 8.00)),
 
         // https://platform.openai.com/docs/models/o4-mini
-        "o4-mini" => Some(ModelInfo::new(200_000, 100_000)),
+        "o4-mini" => Some(ModelInfo::new(200_000, 100_000).with_pricing(1.10, 0.275, 4.40)),
 
         // https://platform.openai.com/docs/models/codex-mini-latest
-        "codex-mini-latest" => Some(ModelInfo::new(200_000, 100_000)),
+        "codex-mini-latest" => {
+            Some(ModelInfo::new(200_000, 100_000).with_pricing(1.50, 0.375, 6.00))
+        }
 
         // As of Jun 25, 2025, gpt-4.1 defaults to gpt-4.1-2025-04-14.
         // https://platform.openai.com/docs/models/gpt-4.1
-        "gpt-4.1" | "gpt-4.1-2025-04-14" => Some(ModelInfo::new(1_047_576, 32_768)),
+        "gpt-4.1" | "gpt-4.1-2025-04-14" => {
+            Some(ModelInfo::new(1_047_576, 32_768).with_pricing(2.00, 0.50, 8.00))
+        }
 
         // As of Jun 25, 2025, gpt-4o defaults to gpt-4o-2024-08-06.
         // https://platform.openai.com/docs/models/gpt-4o
-        "gpt-4o" | "gpt-4o-2024-08-06" => Some(ModelInfo::new(128_000, 16_384)),
+        "gpt-4o" | "gpt-4o-2024-08-06" => {
+            Some(ModelInfo::new(128_000, 16_384).with_pricing(2.50, 1.25, 10.00))
+        }
 
         // https://platform.openai.com/docs/models/gpt-4o?snapshot=gpt-4o-2024-05-13
-        "gpt-4o-2024-05-13" => Some(ModelInfo::new(128_000, 4_096)),
+        "gpt-4o-2024-05-13" => Some(ModelInfo::new(128_000, 4_096).with_pricing(5.00, 5.00, 15.00)),
 
         // https://platform.openai.com/docs/models/gpt-4o?snapshot=gpt-4o-2024-11-20
-        "gpt-4o-2024-11-20" => Some(ModelInfo::new(128_000, 16_384)),
+        "gpt-4o-2024-11-20" => {
+            Some(ModelInfo::new(128_000, 16_384).with_pricing(2.50, 1.25, 10.00))
+        }
 
         // https://platform.openai.com/docs/models/gpt-3.5-turbo
-        "gpt-3.5-turbo" => Some(ModelInfo::new(16_385, 4_096)),
+        "gpt-3.5-turbo" => Some(ModelInfo::new(16_385, 4_096).with_pricing(0.50, 0.50, 1.50)),
 
         _ if slug.starts_with("gpt-5-codex") => Some(ModelInfo {
             context_window: 272_000,
             max_output_tokens: 128_000,
             auto_compact_token_limit: Some(350_000),
+            pricing: Some(ModelPricing {
+                input: 1.25,
+                cached_input: Some(0.125),
+                output: 10.00,
+            }),
         }),
 
-        _ if slug.starts_with("gpt-5") => Some(ModelInfo::new(272_000, 128_000)),
+        _ if slug.starts_with("gpt-5-mini") => {
+            Some(ModelInfo::new(272_000, 128_000).with_pricing(0.25, 0.025, 2.00))
+        }
+
+        _ if slug.starts_with("gpt-5-nano") => {
+            Some(ModelInfo::new(272_000, 128_000).with_pricing(0.05, 0.005, 0.40))
+        }
+
+        _ if slug.starts_with("gpt-5") => {
+            Some(ModelInfo::new(272_000, 128_000).with_pricing(1.25, 0.125, 10.00))
+        }
 
         _ if slug.starts_with("codex-") => Some(ModelInfo::new(272_000, 128_000)),
 
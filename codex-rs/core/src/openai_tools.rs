@@ -53,6 +53,18 @@ pub(crate) enum OpenAiTool {
     Freeform(FreeformTool),
 }
 
+impl OpenAiTool {
+    /// The name the model calls this tool by.
+    pub(crate) fn name(&self) -> &str {
+        match self {
+            OpenAiTool::Function(ResponsesApiTool { name, .. }) => name,
+            OpenAiTool::LocalShell {} => "local_shell",
+            OpenAiTool::WebSearch {} => "web_search",
+            OpenAiTool::Freeform(FreeformTool { name, .. }) => name,
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub enum ConfigShellToolType {
     Default,
@@ -67,7 +79,12 @@ pub(crate) struct ToolsConfig {
     pub apply_patch_tool_type: Option<ApplyPatchToolType>,
     pub web_search_request: bool,
     pub include_view_image_tool: bool,
+    pub include_scratch_path_tool: bool,
+    pub include_delegate_tool: bool,
     pub experimental_unified_exec_tool: bool,
+    /// When set, only the tools with these names are offered to the model.
+    /// Used to scope the tools of a `delegate` child.
+    pub allowed_tools: Option<Vec<String>>,
 }
 
 pub(crate) struct ToolsConfigParams<'a> {
@@ -77,6 +94,8 @@ pub(crate) struct ToolsConfigParams<'a> {
     pub(crate) include_web_search_request: bool,
     pub(crate) use_streamable_shell_tool: bool,
     pub(crate) include_view_image_tool: bool,
+    pub(crate) include_scratch_path_tool: bool,
+    pub(crate) include_delegate_tool: bool,
     pub(crate) experimental_unified_exec_tool: bool,
 }
 
@@ -89,6 +108,8 @@ impl ToolsConfig {
             include_web_search_request,
             use_streamable_shell_tool,
             include_view_image_tool,
+            include_scratch_path_tool,
+            include_delegate_tool,
             experimental_unified_exec_tool,
         } = params;
         let shell_type = if *use_streamable_shell_tool {
@@ -117,7 +138,10 @@ impl ToolsConfig {
             apply_patch_tool_type,
             web_search_request: *include_web_search_request,
             include_view_image_tool: *include_view_image_tool,
+            include_scratch_path_tool: *include_scratch_path_tool,
+            include_delegate_tool: *include_delegate_tool,
             experimental_unified_exec_tool: *experimental_unified_exec_tool,
+            allowed_tools: None,
         }
     }
 }
@@ -154,10 +178,31 @@ pub(crate) enum JsonSchema {
             rename = "additionalProperties",
             skip_serializing_if = "Option::is_none"
         )]
-        additional_properties: Option<bool>,
+        additional_properties: Option<AdditionalProperties>,
     },
 }
 
+/// Value of `additionalProperties` on an object schema: either a boolean or a
+/// schema that every additional property must satisfy.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(untagged)]
+pub(crate) enum AdditionalProperties {
+    Boolean(bool),
+    Schema(Box<JsonSchema>),
+}
+
+impl From<bool> for AdditionalProperties {
+    fn from(b: bool) -> Self {
+        AdditionalProperties::Boolean(b)
+    }
+}
+
+impl From<JsonSchema> for AdditionalProperties {
+    fn from(schema: JsonSchema) -> Self {
+        AdditionalProperties::Schema(Box::new(schema))
+    }
+}
+
 fn create_unified_exec_tool() -> OpenAiTool {
     let mut properties = BTreeMap::new();
     properties.insert(
@@ -200,7 +245,7 @@ fn create_unified_exec_tool() -> OpenAiTool {
         parameters: JsonSchema::Object {
             properties,
             required: Some(vec!["input".to_string()]),
-            additional_properties: Some(false),
+            additional_properties: Some(false.into()),
         },
     })
 }
@@ -236,18 +281,33 @@ fn create_shell_tool() -> OpenAiTool {
     properties.insert(
         "justification".to_string(),
         JsonSchema::String {
-            description: Some("Only set if with_escalated_permissions is true. 1-sentence explanation of why we want to run this command.".to_string()),
+            description: Some("Required if with_escalated_permissions is true; only set in that case. 1-sentence explanation, shown to the user, of why this command cannot run in the sandbox.".to_string()),
+        },
+    );
+
+    properties.insert(
+        "env".to_string(),
+        JsonSchema::Object {
+            properties: BTreeMap::new(),
+            required: None,
+            additional_properties: Some(JsonSchema::String { description: None }.into()),
+        },
+    );
+    properties.insert(
+        "tty".to_string(),
+        JsonSchema::Boolean {
+            description: Some("Run the command in a pseudo-terminal. Set to true for programs that behave differently without a TTY (pagers, progress bars, interactive prompts); output then includes raw terminal escape sequences.".to_string()),
         },
     );
 
     OpenAiTool::Function(ResponsesApiTool {
         name: "shell".to_string(),
-        description: "Runs a shell command and returns its output.".to_string(),
+        description: "Runs a shell command and returns its output. Use `env` to set extra environment variables for this invocation only.".to_string(),
         strict: false,
         parameters: JsonSchema::Object {
             properties,
             required: Some(vec!["command".to_string()]),
-            additional_properties: Some(false),
+            additional_properties: Some(false.into()),
         },
     })
 }
@@ -271,10 +331,113 @@ fn create_view_image_tool() -> OpenAiTool {
         parameters: JsonSchema::Object {
             properties,
             required: Some(vec!["path".to_string()]),
-            additional_properties: Some(false),
+            additional_properties: Some(false.into()),
+        },
+    })
+}
+/// Tools for reading the resources offered by MCP servers, included when at
+/// least one connected server offers them.
+pub(crate) fn create_mcp_resource_tools() -> Vec<OpenAiTool> {
+    let mut list_properties = BTreeMap::new();
+    list_properties.insert(
+        "server".to_string(),
+        JsonSchema::String {
+            description: Some("Only list the resources of this MCP server".to_string()),
+        },
+    );
+    let list_tool = OpenAiTool::Function(ResponsesApiTool {
+        name: "list_mcp_resources".to_string(),
+        description: "List the resources (documents, schemas, files and the like) offered by the configured MCP servers, with their URIs.".to_string(),
+        strict: false,
+        parameters: JsonSchema::Object {
+            properties: list_properties,
+            required: None,
+            additional_properties: Some(false.into()),
+        },
+    });
+
+    let mut read_properties = BTreeMap::new();
+    read_properties.insert(
+        "server".to_string(),
+        JsonSchema::String {
+            description: Some("Name of the MCP server offering the resource".to_string()),
+        },
+    );
+    read_properties.insert(
+        "uri".to_string(),
+        JsonSchema::String {
+            description: Some("URI of the resource, as listed by list_mcp_resources".to_string()),
+        },
+    );
+    let read_tool = OpenAiTool::Function(ResponsesApiTool {
+        name: "read_mcp_resource".to_string(),
+        description: "Read an MCP resource into the conversation context. Text is returned directly; images are attached like view_image.".to_string(),
+        strict: false,
+        parameters: JsonSchema::Object {
+            properties: read_properties,
+            required: Some(vec!["server".to_string(), "uri".to_string()]),
+            additional_properties: Some(false.into()),
+        },
+    });
+
+    vec![list_tool, read_tool]
+}
+
+fn create_scratch_path_tool() -> OpenAiTool {
+    OpenAiTool::Function(ResponsesApiTool {
+        name: "scratch_path".to_string(),
+        description: "Return the path of this session's scratch directory. It is always writable, even in a read-only sandbox, and is deleted when the session ends; use it for temporary scripts and artifacts instead of the workspace.".to_string(),
+        strict: false,
+        parameters: JsonSchema::Object {
+            properties: BTreeMap::new(),
+            required: None,
+            additional_properties: Some(false.into()),
+        },
+    })
+}
+
+fn create_delegate_tool() -> OpenAiTool {
+    let mut properties = BTreeMap::new();
+    properties.insert(
+        "prompt".to_string(),
+        JsonSchema::String {
+            description: Some(
+                "The subtask for the sub-agent, with all the context it needs; it does not see this conversation".to_string(),
+            ),
+        },
+    );
+    properties.insert(
+        "tools".to_string(),
+        JsonSchema::Array {
+            items: Box::new(JsonSchema::String { description: None }),
+            description: Some(
+                "Names of the tools the sub-agent may use. Defaults to all of your tools except delegate".to_string(),
+            ),
+        },
+    );
+    properties.insert(
+        "max_turns".to_string(),
+        JsonSchema::Number {
+            description: Some(format!(
+                "Most model turns the sub-agent may take (default {}, at most {})",
+                crate::codex::sub_agent::DEFAULT_MAX_TURNS,
+                crate::codex::sub_agent::MAX_TURNS_LIMIT
+            )),
+        },
+    );
+
+    OpenAiTool::Function(ResponsesApiTool {
+        name: "delegate".to_string(),
+        description: "Hand a self-contained subtask to a sub-agent that works in a fresh conversation and returns its final message. Use it for research or exploration whose intermediate steps you do not need to see.".to_string(),
+        strict: false,
+        parameters: JsonSchema::Object {
+            properties,
+            required: Some(vec!["prompt".to_string()]),
+            additional_properties: Some(false.into()),
         },
     })
 }
+
 /// TODO(dylan): deprecate once we get rid of json tool
 #[derive(Serialize, Deserialize)]
 pub(crate) struct ApplyPatchToolArgs {
@@ -499,6 +662,15 @@ pub(crate) fn get_openai_tools(
                 tools.push(OpenAiTool::Function(
                     crate::exec_command::create_write_stdin_tool_for_responses_api(),
                 ));
+                tools.push(OpenAiTool::Function(
+                    crate::exec_command::create_list_jobs_tool_for_responses_api(),
+                ));
+                tools.push(OpenAiTool::Function(
+                    crate::exec_command::create_job_output_tool_for_responses_api(),
+                ));
+                tools.push(OpenAiTool::Function(
+                    crate::exec_command::create_kill_job_tool_for_responses_api(),
+                ));
             }
         }
     }
@@ -526,6 +698,12 @@ pub(crate) fn get_openai_tools(
     if config.include_view_image_tool {
         tools.push(create_view_image_tool());
     }
+    if config.include_scratch_path_tool {
+        tools.push(create_scratch_path_tool());
+    }
+    if config.include_delegate_tool {
+        tools.push(create_delegate_tool());
+    }
     if let Some(mcp_tools) = mcp_tools {
         // Ensure deterministic ordering to maximize prompt cache hits.
         let mut entries: Vec<(String, mcp_types::Tool)> = mcp_tools.into_iter().collect();
@@ -553,15 +731,7 @@ mod tests {
     use super::*;
 
     fn assert_eq_tool_names(tools: &[OpenAiTool], expected_names: &[&str]) {
-        let tool_names = tools
-            .iter()
-            .map(|tool| match tool {
-                OpenAiTool::Function(ResponsesApiTool { name, .. }) => name,
-                OpenAiTool::LocalShell {} => "local_shell",
-                OpenAiTool::WebSearch {} => "web_search",
-                OpenAiTool::Freeform(FreeformTool { name, .. }) => name,
-            })
-            .collect::<Vec<_>>();
+        let tool_names = tools.iter().map(OpenAiTool::name).collect::<Vec<_>>();
 
         assert_eq!(
             tool_names.len(),
@@ -576,6 +746,23 @@ mod tests {
         }
     }
 
+    #[test]
+    fn mcp_resource_tools_require_server_and_uri_to_read() {
+        let tools = create_mcp_resource_tools();
+        assert_eq_tool_names(&tools, &["list_mcp_resources", "read_mcp_resource"]);
+        let OpenAiTool::Function(ResponsesApiTool {
+            parameters: JsonSchema::Object { required, .. },
+            ..
+        }) = &tools[1]
+        else {
+            panic!("read_mcp_resource should be a function tool");
+        };
+        assert_eq!(
+            required.as_deref(),
+            Some(&["server".to_string(), "uri".to_string()][..])
+        );
+    }
+
     #[test]
     fn test_get_openai_tools() {
         let model_family = find_family_for_model("codex-mini-latest")
@@ -587,6 +774,8 @@ mod tests {
             include_web_search_request: true,
             use_streamable_shell_tool: false,
             include_view_image_tool: true,
+            include_scratch_path_tool: false,
+            include_delegate_tool: false,
             experimental_unified_exec_tool: true,
         });
         let tools = get_openai_tools(&config, Some(HashMap::new()));
@@ -597,6 +786,33 @@ mod tests {
         );
     }
 
+    #[test]
+    fn delegate_tool_is_offered_when_enabled() {
+        let model_family = find_family_for_model("o3").expect("o3 should be a valid model family");
+        let config = ToolsConfig::new(&ToolsConfigParams {
+            model_family: &model_family,
+            include_plan_tool: false,
+            include_apply_patch_tool: false,
+            include_web_search_request: false,
+            use_streamable_shell_tool: false,
+            include_view_image_tool: false,
+            include_scratch_path_tool: false,
+            include_delegate_tool: true,
+            experimental_unified_exec_tool: false,
+        });
+        let tools = get_openai_tools(&config, None);
+
+        assert_eq_tool_names(&tools, &["shell", "delegate"]);
+        let OpenAiTool::Function(ResponsesApiTool {
+            parameters: JsonSchema::Object { required, .. },
+            ..
+        }) = &tools[1]
+        else {
+            panic!("delegate should be a function tool");
+        };
+        assert_eq!(required.as_deref(), Some(&["prompt".to_string()][..]));
+    }
+
     #[test]
     fn test_get_openai_tools_default_shell() {
         let model_family = find_family_for_model("o3").expect("o3 should be a valid model family");
@@ -607,6 +823,8 @@ mod tests {
             include_web_search_request: true,
             use_streamable_shell_tool: false,
             include_view_image_tool: true,
+            include_scratch_path_tool: false,
+            include_delegate_tool: false,
             experimental_unified_exec_tool: true,
         });
         let tools = get_openai_tools(&config, Some(HashMap::new()));
@@ -627,6 +845,8 @@ mod tests {
             include_web_search_request: true,
             use_streamable_shell_tool: false,
             include_view_image_tool: true,
+            include_scratch_path_tool: false,
+            include_delegate_tool: false,
             experimental_unified_exec_tool: true,
         });
         let tools = get_openai_tools(
@@ -708,7 +928,7 @@ mod tests {
                                     "string_property".to_string(),
                                     "number_property".to_string(),
                                 ]),
-                                additional_properties: Some(false),
+                                additional_properties: Some(false.into()),
                             },
                         ),
                     ]),
@@ -731,6 +951,8 @@ mod tests {
             include_web_search_request: false,
             use_streamable_shell_tool: false,
             include_view_image_tool: true,
+            include_scratch_path_tool: false,
+            include_delegate_tool: false,
             experimental_unified_exec_tool: true,
         });
 
@@ -807,6 +1029,8 @@ mod tests {
             include_web_search_request: true,
             use_streamable_shell_tool: false,
             include_view_image_tool: true,
+            include_scratch_path_tool: false,
+            include_delegate_tool: false,
             experimental_unified_exec_tool: true,
         });
 
@@ -868,6 +1092,8 @@ mod tests {
             include_web_search_request: true,
             use_streamable_shell_tool: false,
             include_view_image_tool: true,
+            include_scratch_path_tool: false,
+            include_delegate_tool: false,
             experimental_unified_exec_tool: true,
         });
 
@@ -924,6 +1150,8 @@ mod tests {
             include_web_search_request: true,
             use_streamable_shell_tool: false,
             include_view_image_tool: true,
+            include_scratch_path_tool: false,
+            include_delegate_tool: false,
             experimental_unified_exec_tool: true,
         });
 
@@ -983,6 +1211,8 @@ mod tests {
             include_web_search_request: true,
             use_streamable_shell_tool: false,
             include_view_image_tool: true,
+            include_scratch_path_tool: false,
+            include_delegate_tool: false,
             experimental_unified_exec_tool: true,
         });
 
@@ -1040,7 +1270,7 @@ mod tests {
         };
         assert_eq!(name, "shell");
 
-        let expected = "Runs a shell command and returns its output.";
+        let expected = "Runs a shell command and returns its output. Use `env` to set extra environment variables for this invocation only.";
         assert_eq!(description, expected);
     }
 }
@@ -0,0 +1,349 @@
+//! Platform-aware path helpers shared by exec, `apply_patch`, the sandbox
+//! safety checks and the turn diff tracker.
+//!
+//! Windows needs extra care in a few places that `std::path` leaves to the
+//! caller:
+//!
+//! - canonicalized paths come back in verbatim form (`\\?\C:\repo`,
+//!   `\\?\UNC\server\share`), which does not compare equal to the form the
+//!   user typed and which `cmd.exe` refuses as a working directory;
+//! - drive-relative paths such as `C:src` are not absolute, yet
+//!   `Path::join` replaces the base with them instead of resolving them;
+//! - path comparisons must ignore ASCII case.
+//!
+//! On other platforms these helpers reduce to the plain `std::path`
+//! operations.
+
+use std::path::Component;
+use std::path::Path;
+use std::path::PathBuf;
+
+const VERBATIM_PREFIX: &str = r"\\?\";
+const VERBATIM_UNC_PREFIX: &str = r"\\?\UNC\";
+/// Same limit as Linux's `MAXSYMLINKS`, so symlink loops terminate.
+const MAX_SYMLINKS_FOLLOWED: usize = 40;
+
+/// Rewrite a verbatim path (`\\?\C:\x`, `\\?\UNC\server\share\x`) into its
+/// ordinary form (`C:\x`, `\\server\share\x`). Other paths, including
+/// verbatim paths without a drive letter such as volume GUIDs, are returned
+/// unchanged. This is a no-op outside Windows.
+pub fn simplify_verbatim(path: &Path) -> PathBuf {
+    if !cfg!(windows) {
+        return path.to_path_buf();
+    }
+    match path.to_str().and_then(simplify_verbatim_str) {
+        Some(simplified) => PathBuf::from(simplified),
+        None => path.to_path_buf(),
+    }
+}
+
+/// Lexically normalize `path`: simplify verbatim prefixes, drop `.` and
+/// resolve `..` without touching the filesystem, so it works for paths that
+/// do not exist yet.
+pub fn normalize_path(path: &Path) -> PathBuf {
+    let mut out = PathBuf::new();
+    for comp in simplify_verbatim(path).components() {
+        match comp {
+            Component::ParentDir => {
+                out.pop();
+            }
+            Component::CurDir => {}
+            other => out.push(other.as_os_str()),
+        }
+    }
+    out
+}
+
+/// Resolve every symlink in `path`, including dangling ones, and every `..`
+/// in the order the OS would, so the result names the file that writing to
+/// `path` would actually create or modify. Unlike `std::fs::canonicalize`,
+/// `path` need not exist.
+pub fn resolve_symlinks(path: &Path) -> PathBuf {
+    let mut remaining = simplify_verbatim(path);
+    let mut links_followed = 0;
+    'restart: loop {
+        let mut resolved = PathBuf::new();
+        let mut components = remaining.components();
+        while let Some(comp) = components.next() {
+            match comp {
+                Component::ParentDir => {
+                    resolved.pop();
+                }
+                Component::CurDir => {}
+                Component::Normal(name) => {
+                    let candidate = resolved.join(name);
+                    if links_followed < MAX_SYMLINKS_FOLLOWED
+                        && let Ok(target) = std::fs::read_link(&candidate)
+                    {
+                        links_followed += 1;
+                        let next = resolved.join(target).join(components.as_path());
+                        remaining = simplify_verbatim(&next);
+                        continue 'restart;
+                    }
+                    resolved = candidate;
+                }
+                other => resolved.push(other.as_os_str()),
+            }
+        }
+        return resolved;
+    }
+}
+
+/// Resolve `path` against `cwd` the way the shell would. Unlike
+/// `cwd.join(path)`, a Windows drive-relative path (`C:src`) is resolved
+/// against `cwd` when it is on the same drive and against the drive root
+/// otherwise.
+pub fn resolve_path(cwd: &Path, path: &Path) -> PathBuf {
+    if cfg!(windows)
+        && let (Some(cwd_str), Some(path_str)) = (cwd.to_str(), path.to_str())
+        && let Some(resolved) = resolve_drive_relative_str(cwd_str, path_str)
+    {
+        return simplify_verbatim(Path::new(&resolved));
+    }
+    simplify_verbatim(&cwd.join(path))
+}
+
+/// `path.starts_with(base)`, ignoring ASCII case and verbatim prefixes on
+/// Windows.
+pub fn path_starts_with(path: &Path, base: &Path) -> bool {
+    strip_path_prefix(path, base).is_some()
+}
+
+/// Whether `a` and `b` name the same path, ignoring ASCII case and verbatim
+/// prefixes on Windows.
+pub fn paths_equal(a: &Path, b: &Path) -> bool {
+    strip_path_prefix(a, b).is_some_and(|rest| rest.as_os_str().is_empty())
+}
+
+/// `path.strip_prefix(base)`, ignoring ASCII case and verbatim prefixes on
+/// Windows. The returned remainder keeps the casing of `path`.
+pub fn strip_path_prefix(path: &Path, base: &Path) -> Option<PathBuf> {
+    if !cfg!(windows) {
+        return path.strip_prefix(base).ok().map(Path::to_path_buf);
+    }
+    strip_prefix_ignore_case(&simplify_verbatim(path), &simplify_verbatim(base))
+}
+
+/// Render `path` for display: verbatim prefixes are dropped, and relative
+/// paths always use `/` so they read the same as in a git diff.
+pub fn display_path(path: &Path) -> String {
+    let path = simplify_verbatim(path);
+    let shown = path.display().to_string();
+    if cfg!(windows) && !path.is_absolute() {
+        shown.replace('\\', "/")
+    } else {
+        shown
+    }
+}
+
+fn simplify_verbatim_str(path: &str) -> Option<String> {
+    if let Some(rest) = path.strip_prefix(VERBATIM_UNC_PREFIX) {
+        return Some(format!(r"\\{rest}"));
+    }
+    let rest = path.strip_prefix(VERBATIM_PREFIX)?;
+    let bytes = rest.as_bytes();
+    let has_drive = bytes.len() >= 2 && bytes[0].is_ascii_alphabetic() && bytes[1] == b':';
+    has_drive.then(|| rest.to_string())
+}
+
+/// Split `C:rest` into its drive letter and `rest`, unless `rest` starts at
+/// the drive root (`C:\rest`).
+fn split_drive_relative(path: &str) -> Option<(u8, &str)> {
+    let bytes = path.as_bytes();
+    if bytes.len() < 2 || !bytes[0].is_ascii_alphabetic() || bytes[1] != b':' {
+        return None;
+    }
+    let rest = &path[2..];
+    if rest.starts_with(['\\', '/']) {
+        return None;
+    }
+    Some((bytes[0], rest))
+}
+
+fn resolve_drive_relative_str(cwd: &str, path: &str) -> Option<String> {
+    let (drive, rest) = split_drive_relative(path)?;
+    let cwd = simplify_verbatim_str(cwd).unwrap_or_else(|| cwd.to_string());
+    let cwd_bytes = cwd.as_bytes();
+    let same_drive =
+        cwd_bytes.len() >= 2 && cwd_bytes[1] == b':' && cwd_bytes[0].eq_ignore_ascii_case(&drive);
+    let base = if same_drive {
+        cwd.trim_end_matches(['\\', '/']).to_string()
+    } else {
+        format!("{}:", drive as char)
+    };
+    if rest.is_empty() {
+        return Some(if same_drive { cwd } else { format!(r"{base}\") });
+    }
+    Some(format!(r"{base}\{rest}"))
+}
+
+fn strip_prefix_ignore_case(path: &Path, base: &Path) -> Option<PathBuf> {
+    let mut path_comps = path.components();
+    for base_comp in base.components() {
+        let path_comp = path_comps.next()?;
+        let (a, b) = (path_comp.as_os_str(), base_comp.as_os_str());
+        if a == b {
+            continue;
+        }
+        match (a.to_str(), b.to_str()) {
+            (Some(a), Some(b)) if a.eq_ignore_ascii_case(b) => {}
+            _ => return None,
+        }
+    }
+    Some(path_comps.as_path().to_path_buf())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn verbatim_prefixes_are_simplified() {
+        assert_eq!(
+            simplify_verbatim_str(r"\\?\C:\repo\src").as_deref(),
+            Some(r"C:\repo\src")
+        );
+        assert_eq!(
+            simplify_verbatim_str(r"\\?\UNC\server\share\repo").as_deref(),
+            Some(r"\\server\share\repo")
+        );
+        assert_eq!(
+            simplify_verbatim_str(r"\\?\Volume{0b1c}\repo").as_deref(),
+            None
+        );
+        assert_eq!(simplify_verbatim_str(r"C:\repo").as_deref(), None);
+    }
+
+    #[test]
+    fn drive_relative_paths_resolve_against_cwd_on_the_same_drive() {
+        assert_eq!(
+            resolve_drive_relative_str(r"C:\work", r"C:src\lib.rs").as_deref(),
+            Some(r"C:\work\src\lib.rs")
+        );
+        assert_eq!(
+            resolve_drive_relative_str(r"\\?\c:\work\", r"C:src").as_deref(),
+            Some(r"c:\work\src")
+        );
+        assert_eq!(
+            resolve_drive_relative_str(r"C:\work", "C:").as_deref(),
+            Some(r"C:\work")
+        );
+    }
+
+    #[test]
+    fn drive_relative_paths_on_another_drive_resolve_against_its_root() {
+        assert_eq!(
+            resolve_drive_relative_str(r"C:\work", r"D:data\x.txt").as_deref(),
+            Some(r"D:\data\x.txt")
+        );
+        assert_eq!(
+            resolve_drive_relative_str(r"C:\work", "D:").as_deref(),
+            Some(r"D:\")
+        );
+    }
+
+    #[test]
+    fn absolute_and_plain_relative_paths_are_not_drive_relative() {
+        assert_eq!(resolve_drive_relative_str(r"C:\work", r"D:\data"), None);
+        assert_eq!(resolve_drive_relative_str(r"C:\work", "D:/data"), None);
+        assert_eq!(resolve_drive_relative_str(r"C:\work", "src"), None);
+    }
+
+    #[test]
+    fn prefix_comparison_ignores_case() {
+        assert_eq!(
+            strip_prefix_ignore_case(Path::new("/Repo/Src/Main.rs"), Path::new("/repo/src")),
+            Some(PathBuf::from("Main.rs"))
+        );
+        assert_eq!(
+            strip_prefix_ignore_case(Path::new("/repo2/src"), Path::new("/repo")),
+            None
+        );
+    }
+
+    #[test]
+    fn normalize_path_resolves_dot_segments() {
+        assert_eq!(
+            normalize_path(Path::new("/repo/./src/../Cargo.toml")),
+            PathBuf::from("/repo/Cargo.toml")
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn resolve_symlinks_follows_links_before_parent_dirs() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let root = tmp.path().canonicalize().unwrap();
+        std::fs::create_dir_all(root.join("outside/nested")).unwrap();
+        std::fs::create_dir(root.join("repo")).unwrap();
+        std::os::unix::fs::symlink(root.join("outside/nested"), root.join("repo/link")).unwrap();
+        std::os::unix::fs::symlink(root.join("outside/missing"), root.join("repo/dangling"))
+            .unwrap();
+
+        assert_eq!(
+            resolve_symlinks(&root.join("repo/link/../new.txt")),
+            root.join("outside/new.txt")
+        );
+        assert_eq!(
+            resolve_symlinks(&root.join("repo/dangling")),
+            root.join("outside/missing")
+        );
+        assert_eq!(
+            resolve_symlinks(&root.join("repo/./src/new.txt")),
+            root.join("repo/src/new.txt")
+        );
+    }
+
+    #[cfg(not(windows))]
+    #[test]
+    fn comparisons_are_case_sensitive_outside_windows() {
+        assert!(path_starts_with(Path::new("/repo/src"), Path::new("/repo")));
+        assert!(!path_starts_with(
+            Path::new("/Repo/src"),
+            Path::new("/repo")
+        ));
+        assert!(!paths_equal(Path::new("/Repo"), Path::new("/repo")));
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn windows_comparisons_ignore_case_and_verbatim_prefixes() {
+        assert!(path_starts_with(
+            Path::new(r"\\?\C:\Repo\src\main.rs"),
+            Path::new(r"c:\repo")
+        ));
+        assert!(paths_equal(
+            Path::new(r"\\?\UNC\Server\Share\repo"),
+            Path::new(r"\\server\share\Repo")
+        ));
+        assert!(!path_starts_with(
+            Path::new(r"D:\repo\src"),
+            Path::new(r"C:\repo")
+        ));
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn windows_resolve_path_handles_drive_relative_and_unc() {
+        assert_eq!(
+            resolve_path(Path::new(r"C:\work"), Path::new(r"C:src")),
+            PathBuf::from(r"C:\work\src")
+        );
+        assert_eq!(
+            resolve_path(Path::new(r"\\?\UNC\server\share\work"), Path::new("src")),
+            PathBuf::from(r"\\server\share\work\src")
+        );
+        assert_eq!(
+            resolve_path(Path::new(r"C:\work"), Path::new(r"\tools")),
+            PathBuf::from(r"C:\tools")
+        );
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn windows_display_path_uses_forward_slashes_for_relative_paths() {
+        assert_eq!(display_path(Path::new(r"src\main.rs")), "src/main.rs");
+        assert_eq!(display_path(Path::new(r"\\?\C:\repo")), r"C:\repo");
+    }
+}
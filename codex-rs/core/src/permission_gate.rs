@@ -0,0 +1,226 @@
+//! Guided permissions: a one-time, per-project consent prompt the first time
+//! a session uses the network, a particular MCP tool, or writes outside the
+//! directory it started in.
+//!
+//! The gate sits above the regular safety checks. It does not replace
+//! approvals or the sandbox; it only makes sure the user has been told about
+//! a kind of capability once before the agent starts using it. Answers are
+//! stored under `[projects."<path>".permissions]` in `config.toml`.
+
+use std::collections::HashMap;
+use std::path::Path;
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+use codex_apply_patch::ApplyPatchAction;
+use codex_apply_patch::ApplyPatchFileChange;
+use tracing::warn;
+
+use crate::bash::try_parse_bash;
+use crate::bash::try_parse_word_only_commands_sequence;
+use crate::config::Config;
+use crate::config::permissions_project_root;
+use crate::config::set_project_permission;
+use crate::config_types::PermissionDecision;
+use crate::path_utils::normalize_path;
+use crate::path_utils::path_starts_with;
+
+pub(crate) struct PermissionGate {
+    enabled: bool,
+    codex_home: PathBuf,
+    project_root: PathBuf,
+    session_cwd: PathBuf,
+    decisions: Mutex<HashMap<String, PermissionDecision>>,
+}
+
+impl PermissionGate {
+    pub(crate) fn new(config: &Config) -> Self {
+        Self {
+            enabled: config.permission_prompts,
+            codex_home: config.codex_home.clone(),
+            project_root: permissions_project_root(&config.cwd),
+            session_cwd: config.cwd.clone(),
+            decisions: Mutex::new(config.project_permissions.clone()),
+        }
+    }
+
+    pub(crate) fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    /// The directory the session started in; later `cwd` overrides do not
+    /// move it.
+    pub(crate) fn session_cwd(&self) -> &Path {
+        &self.session_cwd
+    }
+
+    pub(crate) fn decision(&self, key: &str) -> Option<PermissionDecision> {
+        self.decisions
+            .lock()
+            .ok()
+            .and_then(|decisions| decisions.get(key).copied())
+    }
+
+    /// Remember `decision` for the rest of the session and persist it for the
+    /// project. Failing to persist only means the user is asked again next
+    /// session.
+    pub(crate) fn remember(&self, key: &str, decision: PermissionDecision) {
+        if let Ok(mut decisions) = self.decisions.lock() {
+            decisions.insert(key.to_string(), decision);
+        }
+        if let Err(e) = set_project_permission(&self.codex_home, &self.project_root, key, decision)
+        {
+            warn!("failed to save permission `{key}` for the project: {e}");
+        }
+    }
+}
+
+/// Whether `command` looks like it talks to the network: a network client
+/// such as `curl`, or a package manager or `git` subcommand that downloads or
+/// uploads.
+pub(crate) fn is_network_command(command: &[String]) -> bool {
+    if let Some(script) = shell_script(command) {
+        if let Some(tree) = try_parse_bash(script)
+            && let Some(commands) = try_parse_word_only_commands_sequence(&tree, script)
+        {
+            return commands.iter().any(|argv| is_network_argv(argv));
+        }
+        // The parser only handles plain commands; for anything else look at
+        // every word that could start a command.
+        let words: Vec<String> = script
+            .split(|c: char| c.is_whitespace() || matches!(c, ';' | '|' | '&' | '(' | ')'))
+            .filter(|word| !word.is_empty())
+            .map(str::to_string)
+            .collect();
+        return (0..words.len()).any(|start| is_network_argv(&words[start..]));
+    }
+    is_network_argv(command)
+}
+
+/// The script of a `bash -lc <script>`-style command.
+fn shell_script(command: &[String]) -> Option<&String> {
+    let [shell, flag, script] = command else {
+        return None;
+    };
+    let shell = Path::new(shell).file_name().and_then(|name| name.to_str());
+    (matches!(shell, Some("bash" | "sh" | "zsh")) && matches!(flag.as_str(), "-c" | "-lc"))
+        .then_some(script)
+}
+
+/// `command` as shown in the consent prompt: the script for `bash -lc`,
+/// otherwise the shell-quoted argv.
+pub(crate) fn command_detail(command: &[String]) -> String {
+    match shell_script(command) {
+        Some(script) => script.clone(),
+        None => shlex::try_join(command.iter().map(String::as_str))
+            .unwrap_or_else(|_| command.join(" ")),
+    }
+}
+
+fn is_network_argv(argv: &[String]) -> bool {
+    let Some(program) = argv.first() else {
+        return false;
+    };
+    let program = Path::new(program)
+        .file_name()
+        .and_then(|name| name.to_str())
+        .unwrap_or(program);
+    let subcommand = argv.get(1).map(String::as_str).unwrap_or_default();
+    match program {
+        "curl" | "wget" | "ssh" | "scp" | "sftp" | "rsync" | "nc" | "ncat" | "telnet" | "ftp"
+        | "gh" => true,
+        "git" => matches!(
+            subcommand,
+            "clone" | "fetch" | "pull" | "push" | "ls-remote" | "submodule"
+        ),
+        "npm" | "pnpm" | "yarn" | "bun" => matches!(
+            subcommand,
+            "install" | "i" | "ci" | "add" | "update" | "upgrade" | "publish"
+        ),
+        "pip" | "pip3" | "uv" => matches!(subcommand, "install" | "download" | "sync" | "add"),
+        "cargo" => matches!(
+            subcommand,
+            "install" | "fetch" | "update" | "add" | "publish" | "search"
+        ),
+        "go" => matches!(subcommand, "get" | "install"),
+        "docker" | "podman" => matches!(subcommand, "pull" | "push" | "login"),
+        _ => false,
+    }
+}
+
+/// Paths `action` would create, change, delete or move to that are not
+/// inside `cwd`, sorted.
+pub(crate) fn paths_outside(action: &ApplyPatchAction, cwd: &Path) -> Vec<PathBuf> {
+    let cwd = normalize_path(cwd);
+    let mut paths: Vec<PathBuf> = action
+        .changes()
+        .iter()
+        .flat_map(|(path, change)| {
+            let moved = match change {
+                ApplyPatchFileChange::Update {
+                    move_path: Some(dest),
+                    ..
+                } => Some(dest.clone()),
+                _ => None,
+            };
+            std::iter::once(path.clone()).chain(moved)
+        })
+        .filter(|path| !path_starts_with(&normalize_path(path), &cwd))
+        .collect();
+    paths.sort();
+    paths
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn argv(args: &[&str]) -> Vec<String> {
+        args.iter().map(|arg| (*arg).to_string()).collect()
+    }
+
+    #[test]
+    fn detects_network_commands() {
+        assert!(is_network_command(&argv(&["curl", "https://example.com"])));
+        assert!(is_network_command(&argv(&["/usr/bin/git", "fetch"])));
+        assert!(is_network_command(&argv(&[
+            "bash",
+            "-lc",
+            "cd web && npm install"
+        ])));
+        assert!(is_network_command(&argv(&[
+            "bash",
+            "-lc",
+            "for p in a b; do pip install $p; done"
+        ])));
+        assert!(is_network_command(&argv(&[
+            "/bin/sh",
+            "-c",
+            "curl -fsSL https://example.com | tar x"
+        ])));
+
+        assert!(!is_network_command(&argv(&["git", "status"])));
+        assert!(!is_network_command(&argv(&["cargo", "test"])));
+        assert!(!is_network_command(&argv(&["bash", "-lc", "ls -la"])));
+    }
+
+    #[test]
+    fn finds_patch_paths_outside_cwd() {
+        let cwd = PathBuf::from("/work/project");
+        let inside = ApplyPatchAction::new_add_for_test(
+            &cwd.join("src/../README.md"),
+            "hello\n".to_string(),
+        );
+        assert!(paths_outside(&inside, &cwd).is_empty());
+
+        let outside = ApplyPatchAction::new_add_for_test(
+            &cwd.join("../other/notes.md"),
+            "hello\n".to_string(),
+        );
+        assert_eq!(
+            paths_outside(&outside, &cwd),
+            vec![cwd.join("../other/notes.md")]
+        );
+    }
+}
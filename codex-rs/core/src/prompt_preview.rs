@@ -0,0 +1,218 @@
+//! Splits the prompt for the next turn into sections for `/context`.
+//!
+//! The preview is built from the same [`Prompt`] a turn would send, so what
+//! users see is what the model gets; only the conversation history is
+//! summarized, one line per item.
+
+use codex_protocol::models::ResponseItem;
+use codex_protocol::protocol::InputMessageKind;
+use codex_protocol::protocol::NextPromptPreviewEvent;
+use codex_protocol::protocol::PromptPreviewSection;
+use codex_protocol::protocol::PromptSectionKind;
+
+use crate::client_common::Prompt;
+use crate::codex::compact::content_items_to_text;
+use crate::model_family::ModelFamily;
+use crate::openai_tools::OpenAiTool;
+use crate::openai_tools::create_tools_json_for_responses_api;
+
+/// Longest text shown for a single history item.
+const HISTORY_LINE_MAX_CHARS: usize = 160;
+
+pub(crate) fn build_prompt_preview(
+    model: String,
+    model_family: &ModelFamily,
+    prompt: &Prompt,
+) -> NextPromptPreviewEvent {
+    let instructions = prompt.get_full_instructions(model_family).into_owned();
+    let mut sections = vec![PromptPreviewSection {
+        kind: PromptSectionKind::Instructions,
+        estimated_tokens: estimate_tokens(instructions.len()),
+        content: instructions,
+    }];
+
+    let mut user_instructions = Vec::new();
+    let mut environment_context = Vec::new();
+    let mut history_lines = Vec::new();
+    let mut history_bytes = 0;
+    for item in &prompt.input {
+        if let ResponseItem::Message { role, content, .. } = item
+            && let Some(text) = content_items_to_text(content)
+        {
+            match InputMessageKind::from((role, &text)) {
+                InputMessageKind::UserInstructions => {
+                    user_instructions.push(text);
+                    continue;
+                }
+                InputMessageKind::EnvironmentContext => {
+                    environment_context.push(text);
+                    continue;
+                }
+                InputMessageKind::Plain => {}
+            }
+        }
+        history_bytes += serde_json::to_string(item).map_or(0, |json| json.len());
+        history_lines.push(summarize_item(item));
+    }
+    for (kind, texts) in [
+        (PromptSectionKind::UserInstructions, user_instructions),
+        (PromptSectionKind::EnvironmentContext, environment_context),
+    ] {
+        if texts.is_empty() {
+            continue;
+        }
+        let content = texts.join("\n\n");
+        sections.push(PromptPreviewSection {
+            kind,
+            estimated_tokens: estimate_tokens(content.len()),
+            content,
+        });
+    }
+    sections.push(PromptPreviewSection {
+        kind: PromptSectionKind::History,
+        estimated_tokens: estimate_tokens(history_bytes),
+        content: history_lines.join("\n"),
+    });
+
+    let tools_bytes = create_tools_json_for_responses_api(&prompt.tools)
+        .map(|tools| tools.iter().map(|tool| tool.to_string().len()).sum())
+        .unwrap_or(0);
+    sections.push(PromptPreviewSection {
+        kind: PromptSectionKind::Tools,
+        estimated_tokens: estimate_tokens(tools_bytes),
+        content: prompt
+            .tools
+            .iter()
+            .map(tool_line)
+            .collect::<Vec<_>>()
+            .join("\n"),
+    });
+
+    NextPromptPreviewEvent {
+        model,
+        estimated_tokens: sections
+            .iter()
+            .map(|section| section.estimated_tokens)
+            .sum(),
+        sections,
+    }
+}
+
+fn estimate_tokens(bytes: usize) -> u64 {
+    (bytes as u64).div_ceil(4)
+}
+
+fn summarize_item(item: &ResponseItem) -> String {
+    let (label, text) = match item {
+        ResponseItem::Message { role, content, .. } => (
+            role.clone(),
+            content_items_to_text(content).unwrap_or_else(|| "(image)".to_string()),
+        ),
+        ResponseItem::Reasoning { summary, .. } => (
+            "reasoning".to_string(),
+            format!("{} summary part(s)", summary.len()),
+        ),
+        ResponseItem::LocalShellCall { action, .. } => (
+            "local_shell".to_string(),
+            serde_json::to_string(action).unwrap_or_default(),
+        ),
+        ResponseItem::FunctionCall {
+            name, arguments, ..
+        } => (format!("call {name}"), arguments.clone()),
+        ResponseItem::CustomToolCall { name, input, .. } => (format!("call {name}"), input.clone()),
+        ResponseItem::FunctionCallOutput { output, .. } => {
+            ("output".to_string(), output.content.clone())
+        }
+        ResponseItem::CustomToolCallOutput { output, .. } => ("output".to_string(), output.clone()),
+        ResponseItem::WebSearchCall { .. } => ("web_search".to_string(), String::new()),
+        ResponseItem::Other => ("other".to_string(), String::new()),
+    };
+    let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    if text.chars().count() > HISTORY_LINE_MAX_CHARS {
+        let shortened: String = text.chars().take(HISTORY_LINE_MAX_CHARS).collect();
+        format!("{label}: {shortened}…")
+    } else {
+        format!("{label}: {text}")
+    }
+}
+
+fn tool_line(tool: &OpenAiTool) -> String {
+    match tool {
+        OpenAiTool::Function(tool) => format!("{}: {}", tool.name, first_line(&tool.description)),
+        OpenAiTool::Freeform(tool) => format!("{}: {}", tool.name, first_line(&tool.description)),
+        OpenAiTool::LocalShell {} => "local_shell".to_string(),
+        OpenAiTool::WebSearch {} => "web_search".to_string(),
+    }
+}
+
+fn first_line(text: &str) -> &str {
+    text.lines().next().unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::environment_context::EnvironmentContext;
+    use crate::model_family::find_family_for_model;
+    use crate::user_instructions::UserInstructions;
+    use codex_protocol::models::ContentItem;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn preview_splits_prompt_into_sections() {
+        let prompt = Prompt {
+            input: vec![
+                UserInstructions::new("Always run the tests.".to_string()).into(),
+                ResponseItem::from(EnvironmentContext::new(None, None, None, None, None)),
+                ResponseItem::Message {
+                    id: None,
+                    role: "user".to_string(),
+                    content: vec![ContentItem::InputText {
+                        text: "fix the\nbuild".to_string(),
+                    }],
+                },
+                ResponseItem::FunctionCall {
+                    id: None,
+                    name: "shell".to_string(),
+                    arguments: r#"{"command":["cargo","build"]}"#.to_string(),
+                    call_id: "call-1".to_string(),
+                },
+            ],
+            tools: vec![OpenAiTool::LocalShell {}],
+            ..Default::default()
+        };
+        let family = find_family_for_model("gpt-5").expect("known model");
+
+        let preview = build_prompt_preview("gpt-5".to_string(), &family, &prompt);
+
+        let kinds: Vec<_> = preview.sections.iter().map(|s| s.kind).collect();
+        assert_eq!(
+            kinds,
+            vec![
+                PromptSectionKind::Instructions,
+                PromptSectionKind::UserInstructions,
+                PromptSectionKind::EnvironmentContext,
+                PromptSectionKind::History,
+                PromptSectionKind::Tools,
+            ]
+        );
+        assert!(
+            preview.sections[1]
+                .content
+                .contains("Always run the tests.")
+        );
+        assert_eq!(
+            preview.sections[3].content,
+            "user: fix the build\ncall shell: {\"command\":[\"cargo\",\"build\"]}"
+        );
+        assert_eq!(preview.sections[4].content, "local_shell");
+        assert_eq!(
+            preview.estimated_tokens,
+            preview
+                .sections
+                .iter()
+                .map(|s| s.estimated_tokens)
+                .sum::<u64>()
+        );
+    }
+}
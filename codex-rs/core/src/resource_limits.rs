@@ -0,0 +1,190 @@
+//! Enforcement of the per-command [`ResourceLimits`] from the config.
+//!
+//! On Unix the memory and CPU limits are rlimits installed in the child
+//! between `fork` and `exec`, so they also bind anything the command execs or
+//! forks. On Windows the child is placed in a job object after it starts.
+
+use std::io;
+
+use crate::config_types::ResourceLimits;
+
+const BYTES_PER_MB: u64 = 1024 * 1024;
+
+/// Appended to captured output that was cut off at `max_output_bytes`.
+pub(crate) const OUTPUT_TRUNCATED_MARKER: &str =
+    "\n[output truncated: max_output_bytes exceeded]\n";
+
+/// Installs `RLIMIT_AS` and `RLIMIT_CPU` on the calling process. Runs inside
+/// a `pre_exec` hook, so it only calls async-signal-safe functions.
+#[cfg(unix)]
+pub(crate) fn set_rlimits_for_current_process(limits: &ResourceLimits) -> io::Result<()> {
+    let apply = |resource, value: u64| {
+        let mut current = libc::rlimit {
+            rlim_cur: 0,
+            rlim_max: 0,
+        };
+        // SAFETY: both calls only read or write the `rlimit` passed in.
+        unsafe {
+            if libc::getrlimit(resource, &mut current) == -1 {
+                return Err(io::Error::last_os_error());
+            }
+            // An unprivileged process cannot raise its hard limit, so never
+            // ask for more than it already has.
+            let value = (value as libc::rlim_t).min(current.rlim_max);
+            let limit = libc::rlimit {
+                rlim_cur: value,
+                rlim_max: value,
+            };
+            if libc::setrlimit(resource, &limit) == -1 {
+                return Err(io::Error::last_os_error());
+            }
+        }
+        Ok(())
+    };
+
+    if let Some(mb) = limits.max_memory_mb {
+        apply(libc::RLIMIT_AS, mb.saturating_mul(BYTES_PER_MB))?;
+    }
+    if let Some(seconds) = limits.max_cpu_seconds {
+        apply(libc::RLIMIT_CPU, seconds)?;
+    }
+    Ok(())
+}
+
+/// Applies the memory and CPU limits to a child that has already started,
+/// for spawners (such as the PTY path) that offer no `pre_exec` hook.
+#[cfg(target_os = "linux")]
+pub(crate) fn apply_to_running_process(pid: u32, limits: &ResourceLimits) -> io::Result<()> {
+    let apply = |resource, value: u64| {
+        let limit = libc::rlimit {
+            rlim_cur: value as libc::rlim_t,
+            rlim_max: value as libc::rlim_t,
+        };
+        // SAFETY: `limit` outlives the call and the old value is not requested.
+        if unsafe { libc::prlimit(pid as libc::pid_t, resource, &limit, std::ptr::null_mut()) }
+            == -1
+        {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(())
+    };
+
+    if let Some(mb) = limits.max_memory_mb {
+        apply(libc::RLIMIT_AS, mb.saturating_mul(BYTES_PER_MB))?;
+    }
+    if let Some(seconds) = limits.max_cpu_seconds {
+        apply(libc::RLIMIT_CPU, seconds)?;
+    }
+    Ok(())
+}
+
+/// macOS has no way to set another process's rlimits, so PTY commands run
+/// without memory and CPU limits there.
+#[cfg(all(unix, not(target_os = "linux")))]
+pub(crate) fn apply_to_running_process(_pid: u32, limits: &ResourceLimits) -> io::Result<()> {
+    if limits.max_memory_mb.is_some() || limits.max_cpu_seconds.is_some() {
+        tracing::debug!("resource limits are not enforced for tty commands on this platform");
+    }
+    Ok(())
+}
+
+/// Places the process in a new job object capped at the configured committed
+/// memory and user-mode CPU time. The job is not kill-on-close, so our handle
+/// is closed straight away and the job lives as long as its processes.
+#[cfg(windows)]
+pub(crate) fn apply_to_running_process(pid: u32, limits: &ResourceLimits) -> io::Result<()> {
+    use windows_sys::Win32::Foundation::CloseHandle;
+    use windows_sys::Win32::System::JobObjects::AssignProcessToJobObject;
+    use windows_sys::Win32::System::JobObjects::CreateJobObjectW;
+    use windows_sys::Win32::System::JobObjects::JOB_OBJECT_LIMIT_JOB_MEMORY;
+    use windows_sys::Win32::System::JobObjects::JOB_OBJECT_LIMIT_JOB_TIME;
+    use windows_sys::Win32::System::JobObjects::JOBOBJECT_EXTENDED_LIMIT_INFORMATION;
+    use windows_sys::Win32::System::JobObjects::JobObjectExtendedLimitInformation;
+    use windows_sys::Win32::System::JobObjects::SetInformationJobObject;
+    use windows_sys::Win32::System::Threading::OpenProcess;
+    use windows_sys::Win32::System::Threading::PROCESS_SET_QUOTA;
+    use windows_sys::Win32::System::Threading::PROCESS_TERMINATE;
+
+    // Job CPU time is measured in 100-nanosecond ticks.
+    const TICKS_PER_SECOND: u64 = 10_000_000;
+
+    if limits.max_memory_mb.is_none() && limits.max_cpu_seconds.is_none() {
+        return Ok(());
+    }
+
+    // SAFETY: the info struct is plain data, and every handle opened here is
+    // closed before returning.
+    unsafe {
+        let mut info: JOBOBJECT_EXTENDED_LIMIT_INFORMATION = std::mem::zeroed();
+        if let Some(mb) = limits.max_memory_mb {
+            info.BasicLimitInformation.LimitFlags |= JOB_OBJECT_LIMIT_JOB_MEMORY;
+            info.JobMemoryLimit =
+                usize::try_from(mb.saturating_mul(BYTES_PER_MB)).unwrap_or(usize::MAX);
+        }
+        if let Some(seconds) = limits.max_cpu_seconds {
+            info.BasicLimitInformation.LimitFlags |= JOB_OBJECT_LIMIT_JOB_TIME;
+            info.BasicLimitInformation.PerJobUserTimeLimit =
+                i64::try_from(seconds.saturating_mul(TICKS_PER_SECOND)).unwrap_or(i64::MAX);
+        }
+
+        let job = CreateJobObjectW(std::ptr::null(), std::ptr::null());
+        if job.is_null() {
+            return Err(io::Error::last_os_error());
+        }
+        let process = OpenProcess(PROCESS_SET_QUOTA | PROCESS_TERMINATE, 0, pid);
+        let result = if process.is_null() {
+            Err(io::Error::last_os_error())
+        } else if SetInformationJobObject(
+            job,
+            JobObjectExtendedLimitInformation,
+            (&raw const info).cast(),
+            std::mem::size_of::<JOBOBJECT_EXTENDED_LIMIT_INFORMATION>() as u32,
+        ) == 0
+            || AssignProcessToJobObject(job, process) == 0
+        {
+            Err(io::Error::last_os_error())
+        } else {
+            Ok(())
+        };
+        if !process.is_null() {
+            CloseHandle(process);
+        }
+        CloseHandle(job);
+        result
+    }
+}
+
+/// Appends `chunk` to `dst` without letting `dst` grow past `max_bytes`.
+/// Returns `false` once bytes had to be dropped.
+pub(crate) fn append_within_limit(
+    dst: &mut Vec<u8>,
+    chunk: &[u8],
+    max_bytes: Option<usize>,
+) -> bool {
+    let Some(max_bytes) = max_bytes else {
+        dst.extend_from_slice(chunk);
+        return true;
+    };
+    let room = max_bytes.saturating_sub(dst.len());
+    dst.extend_from_slice(&chunk[..chunk.len().min(room)]);
+    chunk.len() <= room
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn append_within_limit_stops_at_the_cap() {
+        let mut buf = Vec::new();
+        assert!(append_within_limit(&mut buf, b"abc", Some(5)));
+        assert!(!append_within_limit(&mut buf, b"defg", Some(5)));
+        assert!(!append_within_limit(&mut buf, b"h", Some(5)));
+        assert_eq!(buf, b"abcde");
+
+        let mut unlimited = Vec::new();
+        assert!(append_within_limit(&mut unlimited, b"abcdefgh", None));
+        assert_eq!(unlimited, b"abcdefgh");
+    }
+}
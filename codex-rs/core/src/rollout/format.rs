@@ -0,0 +1,118 @@
+//! Reading rollout lines across envelope versions.
+//!
+//! Every line is a JSON object with a `timestamp`, the record `type` and its
+//! `payload`. Version 2 adds `schema_version` and an optional `turn_id`.
+//! Readers go through [`parse_rollout_line`] so that files written by older
+//! builds are upgraded on the fly and record types added by newer builds are
+//! skipped instead of failing the whole file.
+
+use codex_protocol::protocol::ROLLOUT_SCHEMA_VERSION;
+use codex_protocol::protocol::RolloutLine;
+use serde_json::Value;
+use tracing::debug;
+
+/// Record types understood by this build (the `type` tag of [`RolloutItem`]).
+///
+/// [`RolloutItem`]: codex_protocol::protocol::RolloutItem
+const KNOWN_RECORD_TYPES: &[&str] = &[
+    "session_meta",
+    "response_item",
+    "compacted",
+    "turn_context",
+    "event_msg",
+];
+
+/// Parse one line of a rollout file. Returns `Ok(None)` for versioned records
+/// of a type this build does not know about, which newer builds may write.
+pub fn parse_rollout_line(line: &str) -> serde_json::Result<Option<RolloutLine>> {
+    let value: Value = serde_json::from_str(line)?;
+    let record_type = value.get("type").and_then(Value::as_str).unwrap_or("");
+    if !KNOWN_RECORD_TYPES.contains(&record_type) && value.get("schema_version").is_some() {
+        debug!("skipping unsupported rollout record type {record_type:?}");
+        return Ok(None);
+    }
+    upgrade_rollout_line(value).map(Some)
+}
+
+/// Convert a rollout line written with any earlier envelope version to the
+/// current one.
+pub fn upgrade_rollout_line(value: Value) -> serde_json::Result<RolloutLine> {
+    let mut line: RolloutLine = serde_json::from_value(value)?;
+    // Version 1 lines hold the same `type`/`payload` record and only lack the
+    // envelope fields, which deserialize to their defaults. Their turn is
+    // unknown, so `turn_id` stays empty.
+    line.schema_version = ROLLOUT_SCHEMA_VERSION;
+    Ok(line)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use codex_protocol::protocol::CompactedItem;
+    use codex_protocol::protocol::RolloutItem;
+    use pretty_assertions::assert_eq;
+    use serde_json::json;
+
+    #[test]
+    fn version_one_line_is_upgraded() {
+        let line = json!({
+            "timestamp": "2025-01-01T00:00:00.000Z",
+            "type": "compacted",
+            "payload": { "message": "summary" },
+        })
+        .to_string();
+
+        let parsed = parse_rollout_line(&line)
+            .expect("parse")
+            .expect("known record");
+        assert_eq!(parsed.schema_version, ROLLOUT_SCHEMA_VERSION);
+        assert_eq!(parsed.turn_id, None);
+        let RolloutItem::Compacted(item) = parsed.item else {
+            panic!("expected compacted record");
+        };
+        assert_eq!(item.message, "summary");
+    }
+
+    #[test]
+    fn current_line_round_trips() {
+        let line = RolloutLine {
+            timestamp: "2025-01-01T00:00:00.000Z".to_string(),
+            schema_version: ROLLOUT_SCHEMA_VERSION,
+            turn_id: Some("7".to_string()),
+            item: RolloutItem::Compacted(CompactedItem {
+                message: "summary".to_string(),
+                preserved_messages: 0,
+            }),
+        };
+        let text = serde_json::to_string(&line).expect("serialize");
+        let value: Value = serde_json::from_str(&text).expect("json");
+        assert_eq!(value["schema_version"], json!(ROLLOUT_SCHEMA_VERSION));
+        assert_eq!(value["turn_id"], json!("7"));
+        assert_eq!(value["type"], json!("compacted"));
+
+        let parsed = parse_rollout_line(&text)
+            .expect("parse")
+            .expect("known record");
+        assert_eq!(parsed.turn_id.as_deref(), Some("7"));
+    }
+
+    #[test]
+    fn unknown_versioned_record_is_skipped() {
+        let line = json!({
+            "timestamp": "2025-01-01T00:00:00.000Z",
+            "schema_version": 3,
+            "type": "checkpoint",
+            "payload": { "id": "c1" },
+        })
+        .to_string();
+        assert!(parse_rollout_line(&line).expect("parse").is_none());
+
+        let unversioned = json!({
+            "timestamp": "2025-01-01T00:00:00.000Z",
+            "type": "checkpoint",
+            "payload": {},
+        })
+        .to_string();
+        assert!(parse_rollout_line(&unversioned).is_err());
+    }
+}
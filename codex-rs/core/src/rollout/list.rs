@@ -14,9 +14,9 @@ use time::macros::format_description;
 use uuid::Uuid;
 
 use super::SESSIONS_SUBDIR;
+use super::format::parse_rollout_line;
 use crate::protocol::EventMsg;
 use codex_protocol::protocol::RolloutItem;
-use codex_protocol::protocol::RolloutLine;
 
 /// Returned page of conversation summaries.
 #[derive(Debug, Default, PartialEq)]
@@ -269,7 +269,7 @@ where
     Ok(collected)
 }
 
-fn parse_timestamp_uuid_from_filename(name: &str) -> Option<(OffsetDateTime, Uuid)> {
+pub(super) fn parse_timestamp_uuid_from_filename(name: &str) -> Option<(OffsetDateTime, Uuid)> {
     // Expected: rollout-YYYY-MM-DDThh-mm-ss-<uuid>.jsonl
     let core = name.strip_prefix("rollout-")?.strip_suffix(".jsonl")?;
 
@@ -307,8 +307,9 @@ async fn read_head_and_flags(
             continue;
         }
 
-        let parsed: Result<RolloutLine, _> = serde_json::from_str(trimmed);
-        let Ok(rollout_line) = parsed else { continue };
+        let Ok(Some(rollout_line)) = parse_rollout_line(trimmed) else {
+            continue;
+        };
 
         match rollout_line.item {
             RolloutItem::SessionMeta(session_meta_line) => {
@@ -325,7 +326,7 @@ async fn read_head_and_flags(
             RolloutItem::TurnContext(_) => {
                 // Not included in `head`; skip.
             }
-            RolloutItem::Compacted(_) => {
+            RolloutItem::Compacted(_) | RolloutItem::SessionState(_) | RolloutItem::Rewound(_) => {
                 // Not included in `head`; skip.
             }
             RolloutItem::EventMsg(ev) => {
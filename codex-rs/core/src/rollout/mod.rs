@@ -2,15 +2,21 @@
 
 pub const SESSIONS_SUBDIR: &str = "sessions";
 pub const ARCHIVED_SESSIONS_SUBDIR: &str = "archived_sessions";
+pub const TRASHED_SESSIONS_SUBDIR: &str = "trashed_sessions";
 
+pub mod format;
 pub mod list;
 pub(crate) mod policy;
 pub mod recorder;
+pub mod trash;
 
 pub use codex_protocol::protocol::SessionMeta;
 pub use list::find_conversation_path_by_id_str;
 pub use recorder::RolloutRecorder;
 pub use recorder::RolloutRecorderParams;
+pub use trash::TrashedSession;
+pub use trash::list_trashed_sessions;
+pub use trash::restore_trashed_session;
 
 #[cfg(test)]
 pub mod tests;
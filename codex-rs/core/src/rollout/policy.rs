@@ -9,9 +9,11 @@ pub(crate) fn is_persisted_response_item(item: &RolloutItem) -> bool {
         RolloutItem::ResponseItem(item) => should_persist_response_item(item),
         RolloutItem::EventMsg(ev) => should_persist_event_msg(ev),
         // Persist Codex executive markers so we can analyze flows (e.g., compaction, API turns).
-        RolloutItem::Compacted(_) | RolloutItem::TurnContext(_) | RolloutItem::SessionMeta(_) => {
-            true
-        }
+        RolloutItem::Compacted(_)
+        | RolloutItem::TurnContext(_)
+        | RolloutItem::SessionMeta(_)
+        | RolloutItem::SessionState(_)
+        | RolloutItem::Rewound(_) => true,
     }
 }
 
@@ -42,8 +44,12 @@ pub(crate) fn should_persist_event_msg(ev: &EventMsg) -> bool {
         | EventMsg::TokenCount(_)
         | EventMsg::EnteredReviewMode(_)
         | EventMsg::ExitedReviewMode(_)
-        | EventMsg::TurnAborted(_) => true,
+        | EventMsg::TurnAborted(_)
+        | EventMsg::TaskStalled(_)
+        | EventMsg::AutoCompact(_)
+        | EventMsg::ExecCommandDryRun(_) => true,
         EventMsg::Error(_)
+        | EventMsg::Warning(_)
         | EventMsg::TaskStarted(_)
         | EventMsg::TaskComplete(_)
         | EventMsg::AgentMessageDelta(_)
@@ -53,6 +59,7 @@ pub(crate) fn should_persist_event_msg(ev: &EventMsg) -> bool {
         | EventMsg::SessionConfigured(_)
         | EventMsg::McpToolCallBegin(_)
         | EventMsg::McpToolCallEnd(_)
+        | EventMsg::McpNotification(_)
         | EventMsg::WebSearchBegin(_)
         | EventMsg::WebSearchEnd(_)
         | EventMsg::ExecCommandBegin(_)
@@ -60,16 +67,35 @@ pub(crate) fn should_persist_event_msg(ev: &EventMsg) -> bool {
         | EventMsg::ExecCommandEnd(_)
         | EventMsg::ExecApprovalRequest(_)
         | EventMsg::ApplyPatchApprovalRequest(_)
+        | EventMsg::EscalationRequest(_)
+        | EventMsg::PermissionConsentRequest(_)
+        | EventMsg::SpendLimitApprovalRequest(_)
         | EventMsg::BackgroundEvent(_)
         | EventMsg::StreamError(_)
         | EventMsg::PatchApplyBegin(_)
         | EventMsg::PatchApplyEnd(_)
+        | EventMsg::HookBegin(_)
+        | EventMsg::HookEnd(_)
+        | EventMsg::SubAgentBegin(_)
+        | EventMsg::SubAgentEnd(_)
         | EventMsg::TurnDiff(_)
         | EventMsg::GetHistoryEntryResponse(_)
         | EventMsg::McpListToolsResponse(_)
+        | EventMsg::McpListResourcesResponse(_)
         | EventMsg::ListCustomPromptsResponse(_)
+        | EventMsg::SessionEnvResponse(_)
+        | EventMsg::NextPromptPreview(_)
+        | EventMsg::UndoCompleted(_)
+        | EventMsg::ListSnapshotsResponse(_)
+        | EventMsg::SnapshotDiff(_)
+        | EventMsg::CheckpointCreated(_)
+        | EventMsg::CheckpointRewound(_)
+        | EventMsg::TaskQueueUpdated(_)
         | EventMsg::PlanUpdate(_)
         | EventMsg::ShutdownComplete
-        | EventMsg::ConversationPath(_) => false,
+        | EventMsg::ConversationPath(_)
+        | EventMsg::ExecSessionKilled(_)
+        | EventMsg::ToolCallCancelled(_)
+        | EventMsg::SessionRemoved(_) => false,
     }
 }
@@ -7,7 +7,6 @@ use std::path::Path;
 use std::path::PathBuf;
 
 use codex_protocol::mcp_protocol::ConversationId;
-use serde_json::Value;
 use time::OffsetDateTime;
 use time::format_description::FormatItem;
 use time::macros::format_description;
@@ -19,14 +18,17 @@ use tracing::info;
 use tracing::warn;
 
 use super::SESSIONS_SUBDIR;
+use super::format::parse_rollout_line;
 use super::list::ConversationsPage;
 use super::list::Cursor;
 use super::list::get_conversations;
 use super::policy::is_persisted_response_item;
+use super::trash::purge_trash;
 use crate::config::Config;
 use crate::default_client::ORIGINATOR;
 use crate::git_info::collect_git_info;
 use codex_protocol::protocol::InitialHistory;
+use codex_protocol::protocol::ROLLOUT_SCHEMA_VERSION;
 use codex_protocol::protocol::ResumedHistory;
 use codex_protocol::protocol::RolloutItem;
 use codex_protocol::protocol::RolloutLine;
@@ -61,6 +63,8 @@ pub enum RolloutRecorderParams {
 
 enum RolloutCmd {
     AddItems(Vec<RolloutItem>),
+    /// Tag items written from now on with this turn id.
+    SetTurnId(Option<String>),
     /// Ensure all prior writes are processed; respond when flushed.
     Flush {
         ack: oneshot::Sender<()>,
@@ -140,6 +144,8 @@ impl RolloutRecorder {
             ),
         };
 
+        let is_new_session = meta.is_some();
+
         // Clone the cwd for the spawned task to collect git info asynchronously
         let cwd = config.cwd.clone();
 
@@ -153,6 +159,13 @@ impl RolloutRecorder {
         // driver instead of blocking the runtime.
         tokio::task::spawn(rollout_writer(file, rx, meta, cwd));
 
+        // New sessions are a convenient time to expire old trash.
+        if is_new_session {
+            let codex_home = config.codex_home.clone();
+            let retention_days = config.session_trash_retention_days;
+            tokio::task::spawn(async move { purge_trash(&codex_home, retention_days).await });
+        }
+
         Ok(Self { tx, rollout_path })
     }
 
@@ -175,6 +188,15 @@ impl RolloutRecorder {
             .map_err(|e| IoError::other(format!("failed to queue rollout items: {e}")))
     }
 
+    /// Record the turn that subsequently recorded items belong to, or `None`
+    /// once it has ended.
+    pub(crate) async fn set_turn_id(&self, turn_id: Option<String>) -> std::io::Result<()> {
+        self.tx
+            .send(RolloutCmd::SetTurnId(turn_id))
+            .await
+            .map_err(|e| IoError::other(format!("failed to queue rollout turn id: {e}")))
+    }
+
     /// Flush all queued writes and wait until they are committed by the writer task.
     pub async fn flush(&self) -> std::io::Result<()> {
         let (tx, rx) = oneshot::channel();
@@ -199,17 +221,10 @@ impl RolloutRecorder {
             if line.trim().is_empty() {
                 continue;
             }
-            let v: Value = match serde_json::from_str(line) {
-                Ok(v) => v,
-                Err(e) => {
-                    warn!("failed to parse line as JSON: {line:?}, error: {e}");
-                    continue;
-                }
-            };
-
             // Parse the rollout line structure
-            match serde_json::from_value::<RolloutLine>(v.clone()) {
-                Ok(rollout_line) => match rollout_line.item {
+            match parse_rollout_line(line) {
+                Ok(None) => {}
+                Ok(Some(rollout_line)) => match rollout_line.item {
                     RolloutItem::SessionMeta(session_meta_line) => {
                         // Use the FIRST SessionMeta encountered in the file as the canonical
                         // conversation id and main session information. Keep all items intact.
@@ -230,9 +245,15 @@ impl RolloutRecorder {
                     RolloutItem::EventMsg(_ev) => {
                         items.push(RolloutItem::EventMsg(_ev));
                     }
+                    RolloutItem::SessionState(snapshot) => {
+                        items.push(RolloutItem::SessionState(snapshot));
+                    }
+                    RolloutItem::Rewound(item) => {
+                        items.push(RolloutItem::Rewound(item));
+                    }
                 },
                 Err(e) => {
-                    warn!("failed to parse rollout line: {v:?}, error: {e}");
+                    warn!("failed to parse rollout line: {line:?}, error: {e}");
                 }
             }
         }
@@ -335,7 +356,10 @@ async fn rollout_writer(
     mut meta: Option<SessionMeta>,
     cwd: std::path::PathBuf,
 ) -> std::io::Result<()> {
-    let mut writer = JsonlWriter { file };
+    let mut writer = JsonlWriter {
+        file,
+        turn_id: None,
+    };
 
     // If we have a meta, collect git info asynchronously and write meta first
     if let Some(session_meta) = meta.take() {
@@ -361,6 +385,9 @@ async fn rollout_writer(
                     }
                 }
             }
+            RolloutCmd::SetTurnId(turn_id) => {
+                writer.turn_id = turn_id;
+            }
             RolloutCmd::Flush { ack } => {
                 // Ensure underlying file is flushed and then ack.
                 if let Err(e) = writer.file.flush().await {
@@ -380,6 +407,7 @@ async fn rollout_writer(
 
 struct JsonlWriter {
     file: tokio::fs::File,
+    turn_id: Option<String>,
 }
 
 impl JsonlWriter {
@@ -393,6 +421,8 @@ impl JsonlWriter {
 
         let line = RolloutLine {
             timestamp,
+            schema_version: ROLLOUT_SCHEMA_VERSION,
+            turn_id: self.turn_id.clone(),
             item: rollout_item,
         };
         self.write_line(&line).await
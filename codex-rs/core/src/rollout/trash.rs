@@ -0,0 +1,260 @@
+//! Archiving, trashing and restoring session rollout files.
+//!
+//! Archived sessions are kept indefinitely under [`ARCHIVED_SESSIONS_SUBDIR`].
+//! Deleted sessions go to [`TRASHED_SESSIONS_SUBDIR`] instead, where they can
+//! be restored until [`purge_expired_trash`] removes them.
+
+use std::io;
+use std::path::Path;
+use std::path::PathBuf;
+use std::time::Duration;
+use std::time::SystemTime;
+
+use tracing::info;
+use tracing::warn;
+use uuid::Uuid;
+
+use super::ARCHIVED_SESSIONS_SUBDIR;
+use super::SESSIONS_SUBDIR;
+use super::TRASHED_SESSIONS_SUBDIR;
+use super::list::parse_timestamp_uuid_from_filename;
+
+/// Move a rollout file out of the sessions tree into `archived_sessions`.
+pub(crate) async fn archive_rollout(codex_home: &Path, rollout_path: &Path) -> io::Result<PathBuf> {
+    move_out_of_sessions(codex_home, rollout_path, ARCHIVED_SESSIONS_SUBDIR).await
+}
+
+/// Move a rollout file out of the sessions tree into the trash. The file's
+/// modification time is reset so that it expires relative to its deletion.
+pub(crate) async fn trash_rollout(codex_home: &Path, rollout_path: &Path) -> io::Result<PathBuf> {
+    let dest = move_out_of_sessions(codex_home, rollout_path, TRASHED_SESSIONS_SUBDIR).await?;
+    let file = std::fs::OpenOptions::new().append(true).open(&dest)?;
+    file.set_modified(SystemTime::now())?;
+    Ok(dest)
+}
+
+/// Move a trashed session identified by its conversation id back into the
+/// sessions tree so it can be resumed again. Returns the restored path, or
+/// `None` when the trash holds no such session.
+pub async fn restore_trashed_session(
+    codex_home: &Path,
+    id_str: &str,
+) -> io::Result<Option<PathBuf>> {
+    let Ok(id) = Uuid::parse_str(id_str) else {
+        return Ok(None);
+    };
+    let Some(trashed) = list_trashed_sessions(codex_home)
+        .await?
+        .into_iter()
+        .find(|session| session.id == id)
+    else {
+        return Ok(None);
+    };
+    let file_name = trashed
+        .path
+        .file_name()
+        .ok_or_else(|| io::Error::other("trashed session has no file name"))?;
+    let (timestamp, _) = parse_timestamp_uuid_from_filename(&file_name.to_string_lossy())
+        .ok_or_else(|| io::Error::other("trashed session has an unexpected file name"))?;
+
+    // Rollouts live under sessions/YYYY/MM/DD, matching the date in the name.
+    let dir = codex_home
+        .join(SESSIONS_SUBDIR)
+        .join(timestamp.year().to_string())
+        .join(format!("{:02}", u8::from(timestamp.month())))
+        .join(format!("{:02}", timestamp.day()));
+    tokio::fs::create_dir_all(&dir).await?;
+    let dest = dir.join(file_name);
+    tokio::fs::rename(&trashed.path, &dest).await?;
+    Ok(Some(dest))
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TrashedSession {
+    pub id: Uuid,
+    pub path: PathBuf,
+    /// When the session was moved to the trash.
+    pub trashed_at: SystemTime,
+}
+
+/// Sessions currently in the trash, most recently deleted first.
+pub async fn list_trashed_sessions(codex_home: &Path) -> io::Result<Vec<TrashedSession>> {
+    let trash = codex_home.join(TRASHED_SESSIONS_SUBDIR);
+    let mut entries = match tokio::fs::read_dir(&trash).await {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e),
+    };
+    let mut sessions = Vec::new();
+    while let Some(entry) = entries.next_entry().await? {
+        let name = entry.file_name();
+        let Some((_, id)) = parse_timestamp_uuid_from_filename(&name.to_string_lossy()) else {
+            continue;
+        };
+        let metadata = entry.metadata().await?;
+        if !metadata.is_file() {
+            continue;
+        }
+        sessions.push(TrashedSession {
+            id,
+            path: entry.path(),
+            trashed_at: metadata.modified()?,
+        });
+    }
+    sessions.sort_by(|a, b| b.trashed_at.cmp(&a.trashed_at));
+    Ok(sessions)
+}
+
+/// Permanently delete trashed sessions that have been in the trash for longer
+/// than `retention`. Returns how many were removed.
+pub(crate) async fn purge_expired_trash(
+    codex_home: &Path,
+    retention: Duration,
+) -> io::Result<usize> {
+    let now = SystemTime::now();
+    let mut purged = 0;
+    for session in list_trashed_sessions(codex_home).await? {
+        let age = now
+            .duration_since(session.trashed_at)
+            .unwrap_or(Duration::ZERO);
+        if age > retention {
+            tokio::fs::remove_file(&session.path).await?;
+            purged += 1;
+        }
+    }
+    Ok(purged)
+}
+
+/// Purge sessions trashed more than `retention_days` ago, logging the outcome
+/// instead of failing the caller.
+pub(crate) async fn purge_trash(codex_home: &Path, retention_days: u64) {
+    let retention = Duration::from_secs(retention_days.saturating_mul(24 * 60 * 60));
+    match purge_expired_trash(codex_home, retention).await {
+        Ok(0) => {}
+        Ok(purged) => info!("purged {purged} expired sessions from the trash"),
+        Err(e) => warn!("failed to purge session trash: {e}"),
+    }
+}
+
+async fn move_out_of_sessions(
+    codex_home: &Path,
+    rollout_path: &Path,
+    subdir: &str,
+) -> io::Result<PathBuf> {
+    let sessions = tokio::fs::canonicalize(codex_home.join(SESSIONS_SUBDIR)).await?;
+    let rollout_path = tokio::fs::canonicalize(rollout_path).await?;
+    if !rollout_path.starts_with(&sessions) {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!(
+                "rollout path `{}` must be in sessions directory",
+                rollout_path.display()
+            ),
+        ));
+    }
+    let file_name = rollout_path.file_name().ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!(
+                "rollout path `{}` missing file name",
+                rollout_path.display()
+            ),
+        )
+    })?;
+    let folder = codex_home.join(subdir);
+    tokio::fs::create_dir_all(&folder).await?;
+    let dest = folder.join(file_name);
+    tokio::fs::rename(&rollout_path, &dest).await?;
+    Ok(dest)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use tempfile::TempDir;
+
+    const ID: &str = "0199a5b2-6f3e-7a40-9b8e-3c1d2e4f5a6b";
+
+    fn write_session(home: &Path) -> PathBuf {
+        let dir = home
+            .join(SESSIONS_SUBDIR)
+            .join("2025")
+            .join("03")
+            .join("07");
+        std::fs::create_dir_all(&dir).expect("create sessions dir");
+        let path = dir.join(format!("rollout-2025-03-07T10-11-12-{ID}.jsonl"));
+        std::fs::write(&path, "{}\n").expect("write rollout");
+        path
+    }
+
+    #[tokio::test]
+    async fn trashed_session_can_be_restored() {
+        let home = TempDir::new().expect("tempdir");
+        let original = write_session(home.path());
+
+        let trashed = trash_rollout(home.path(), &original)
+            .await
+            .expect("trash rollout");
+        assert!(!original.exists());
+        assert!(trashed.starts_with(home.path().join(TRASHED_SESSIONS_SUBDIR)));
+        let listed = list_trashed_sessions(home.path())
+            .await
+            .expect("list trash");
+        assert_eq!(listed.len(), 1);
+        assert_eq!(listed[0].id.to_string(), ID);
+
+        let restored = restore_trashed_session(home.path(), ID)
+            .await
+            .expect("restore");
+        assert_eq!(restored, Some(original.clone()));
+        assert!(original.exists());
+        assert!(
+            list_trashed_sessions(home.path())
+                .await
+                .expect("list trash")
+                .is_empty()
+        );
+    }
+
+    #[tokio::test]
+    async fn purge_removes_only_expired_sessions() {
+        let home = TempDir::new().expect("tempdir");
+        let original = write_session(home.path());
+        let trashed = trash_rollout(home.path(), &original)
+            .await
+            .expect("trash rollout");
+
+        let purged = purge_expired_trash(home.path(), Duration::from_secs(3600))
+            .await
+            .expect("purge");
+        assert_eq!(purged, 0);
+        assert!(trashed.exists());
+
+        let file = std::fs::OpenOptions::new()
+            .append(true)
+            .open(&trashed)
+            .expect("open trashed");
+        file.set_modified(SystemTime::now() - Duration::from_secs(7200))
+            .expect("backdate");
+        let purged = purge_expired_trash(home.path(), Duration::from_secs(3600))
+            .await
+            .expect("purge");
+        assert_eq!(purged, 1);
+        assert!(!trashed.exists());
+    }
+
+    #[tokio::test]
+    async fn refuses_paths_outside_sessions() {
+        let home = TempDir::new().expect("tempdir");
+        std::fs::create_dir_all(home.path().join(SESSIONS_SUBDIR)).expect("sessions dir");
+        let outside = home.path().join("notes.jsonl");
+        std::fs::write(&outside, "").expect("write");
+
+        let err = archive_rollout(home.path(), &outside)
+            .await
+            .expect_err("outside path rejected");
+        assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+        assert!(outside.exists());
+    }
+}
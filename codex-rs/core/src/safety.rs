@@ -1,15 +1,22 @@
-use std::collections::HashSet;
-use std::path::Component;
 use std::path::Path;
 use std::path::PathBuf;
 
 use codex_apply_patch::ApplyPatchAction;
 use codex_apply_patch::ApplyPatchFileChange;
 
+use crate::command_pattern::ApprovedCommands;
 use crate::exec::SandboxType;
 use crate::is_safe_command::is_known_safe_command;
+use crate::parse_command::ParsedCommand;
+use crate::parse_command::parse_command;
+use crate::path_utils::normalize_path;
+use crate::path_utils::path_starts_with;
+use crate::path_utils::resolve_path;
+use crate::path_utils::resolve_symlinks;
 use crate::protocol::AskForApproval;
+use crate::protocol::PatchPathEscape;
 use crate::protocol::SandboxPolicy;
+use crate::protocol::WritableRoot;
 
 #[derive(Debug, PartialEq)]
 pub enum SafetyCheck {
@@ -41,6 +48,15 @@ pub fn assess_patch_safety(
         }
     }
 
+    // A path that escapes the writable roots while looking like it is inside
+    // them is never written, whatever the approval policy.
+    let escapes = find_patch_path_escapes(action, sandbox_policy, cwd);
+    if !escapes.is_empty() {
+        return SafetyCheck::Reject {
+            reason: describe_patch_path_escapes(&escapes),
+        };
+    }
+
     // Even though the patch *appears* to be constrained to writable paths, it
     // is possible that paths in the patch are hard links to files outside the
     // writable roots, so we should still run `apply_patch` in a sandbox in that
@@ -75,19 +91,20 @@ pub fn assess_patch_safety(
 /// For a command to be run _without_ a sandbox, one of the following must be
 /// true:
 ///
-/// - the user has explicitly approved the command
+/// - the user has explicitly approved the command, or a pattern matching it
 /// - the command is on the "known safe" list
 /// - `DangerFullAccess` was specified and `UnlessTrusted` was not
 pub fn assess_command_safety(
     command: &[String],
     approval_policy: AskForApproval,
     sandbox_policy: &SandboxPolicy,
-    approved: &HashSet<Vec<String>>,
+    approved: &ApprovedCommands,
     with_escalated_permissions: bool,
 ) -> SafetyCheck {
     // A command is "trusted" because either:
     // - it belongs to a set of commands we consider "safe" by default, or
-    // - the user has explicitly approved the command for this session
+    // - the user has explicitly approved the command for this session, or
+    //   approved a pattern that matches it
     //
     // Currently, whether a command is "trusted" is a simple boolean, but we
     // should include more metadata on this command test to indicate whether it
@@ -166,16 +183,118 @@ pub(crate) fn assess_safety_for_untrusted_command(
     }
 }
 
+/// For a command that `parse_command` classifies as only reading, listing or
+/// searching files, returns the platform sandbox with a read-only policy to
+/// run it under, even when `sandbox_policy` allows writes. This bounds the
+/// damage if the classification or the model is wrong. Returns `None` when
+/// the command should run as already decided.
+pub(crate) fn read_only_sandbox_for(
+    command: &[String],
+    sandbox_policy: &SandboxPolicy,
+) -> Option<(SandboxType, SandboxPolicy)> {
+    if matches!(sandbox_policy, SandboxPolicy::DangerFullAccess) {
+        return None;
+    }
+    let parsed = parse_command(command);
+    if parsed.is_empty()
+        || parsed
+            .iter()
+            .any(|cmd| matches!(cmd, ParsedCommand::Unknown { .. }))
+    {
+        return None;
+    }
+    let sandbox_type = get_platform_sandbox()?;
+    Some((sandbox_type, SandboxPolicy::new_read_only_policy()))
+}
+
 pub fn get_platform_sandbox() -> Option<SandboxType> {
     if cfg!(target_os = "macos") {
         Some(SandboxType::MacosSeatbelt)
     } else if cfg!(target_os = "linux") {
         Some(SandboxType::LinuxSeccomp)
+    } else if cfg!(windows) {
+        Some(SandboxType::WindowsRestrictedToken)
     } else {
         None
     }
 }
 
+/// The paths in `action` that appear to be inside the writable roots but
+/// resolve outside them, through a symlink or a `..` component, sorted by
+/// path.
+pub(crate) fn find_patch_path_escapes(
+    action: &ApplyPatchAction,
+    sandbox_policy: &SandboxPolicy,
+    cwd: &Path,
+) -> Vec<PatchPathEscape> {
+    let SandboxPolicy::WorkspaceWrite { .. } = sandbox_policy else {
+        return Vec::new();
+    };
+    let writable_roots = sandbox_policy.get_writable_roots_with_cwd(cwd);
+    let check = |p: &PathBuf| {
+        let abs = resolve_path(cwd, p);
+        let inside = |path: &Path| is_inside_writable_roots(path, &writable_roots);
+        if !writable_roots
+            .iter()
+            .any(|root| path_starts_with(&abs, &root.root))
+        {
+            // Explicitly outside the roots; the regular check asks about it.
+            return None;
+        }
+        let normalized = normalize_path(&abs);
+        if !inside(&normalized) {
+            return Some(PatchPathEscape::ParentDir {
+                path: p.clone(),
+                resolved: normalized,
+            });
+        }
+        let resolved = resolve_symlinks(&abs);
+        if !inside(&resolved) {
+            return Some(PatchPathEscape::Symlink {
+                path: p.clone(),
+                resolved,
+            });
+        }
+        None
+    };
+
+    let mut escapes: Vec<PatchPathEscape> = action
+        .changes()
+        .iter()
+        .filter_map(|(path, change)| {
+            let move_path = match change {
+                ApplyPatchFileChange::Update { move_path, .. } => move_path.as_ref(),
+                _ => None,
+            };
+            check(path).or_else(|| move_path.and_then(&check))
+        })
+        .collect();
+    escapes.sort_by(|a, b| a.path().cmp(b.path()));
+    escapes
+}
+
+/// Why the writes in `escapes` were rejected, for the model.
+pub(crate) fn describe_patch_path_escapes(escapes: &[PatchPathEscape]) -> String {
+    escapes
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>()
+        .join("; ")
+}
+
+/// Whether the already-resolved `path` is inside a writable root and not
+/// inside one of its read-only subpaths. Roots are resolved the same way, so
+/// e.g. `/tmp` and `/private/tmp` on macOS compare equal.
+fn is_inside_writable_roots(path: &Path, writable_roots: &[WritableRoot]) -> bool {
+    writable_roots.iter().any(|writable_root| {
+        path_starts_with(path, &resolve_symlinks(&writable_root.root))
+            && !writable_root
+                .read_only_subpaths
+                .iter()
+                .any(|subpath| path_starts_with(path, &resolve_symlinks(subpath)))
+    })
+}
+
 fn is_write_patch_constrained_to_writable_paths(
     action: &ApplyPatchAction,
     sandbox_policy: &SandboxPolicy,
@@ -192,39 +311,11 @@ fn is_write_patch_constrained_to_writable_paths(
         SandboxPolicy::WorkspaceWrite { .. } => sandbox_policy.get_writable_roots_with_cwd(cwd),
     };
 
-    // Normalize a path by removing `.` and resolving `..` without touching the
-    // filesystem (works even if the file does not exist).
-    fn normalize(path: &Path) -> Option<PathBuf> {
-        let mut out = PathBuf::new();
-        for comp in path.components() {
-            match comp {
-                Component::ParentDir => {
-                    out.pop();
-                }
-                Component::CurDir => { /* skip */ }
-                other => out.push(other.as_os_str()),
-            }
-        }
-        Some(out)
-    }
-
     // Determine whether `path` is inside **any** writable root. Both `path`
-    // and roots are converted to absolute, normalized forms before the
-    // prefix check.
+    // and roots are made absolute and have their symlinks and `..` resolved
+    // before the prefix check, which ignores case on Windows.
     let is_path_writable = |p: &PathBuf| {
-        let abs = if p.is_absolute() {
-            p.clone()
-        } else {
-            cwd.join(p)
-        };
-        let abs = match normalize(&abs) {
-            Some(v) => v,
-            None => return false,
-        };
-
-        writable_roots
-            .iter()
-            .any(|writable_root| writable_root.is_path_writable(&abs))
+        is_inside_writable_roots(&resolve_symlinks(&resolve_path(cwd, p)), &writable_roots)
     };
 
     for (path, change) in action.changes() {
@@ -274,6 +365,7 @@ mod tests {
         let policy_workspace_only = SandboxPolicy::WorkspaceWrite {
             writable_roots: vec![],
             network_access: false,
+            allowed_domains: vec![],
             exclude_tmpdir_env_var: true,
             exclude_slash_tmp: true,
         };
@@ -295,6 +387,7 @@ mod tests {
         let policy_with_parent = SandboxPolicy::WorkspaceWrite {
             writable_roots: vec![parent],
             network_access: false,
+            allowed_domains: vec![],
             exclude_tmpdir_env_var: true,
             exclude_slash_tmp: true,
         };
@@ -305,13 +398,87 @@ mod tests {
         ));
     }
 
+    #[cfg(windows)]
+    #[test]
+    fn test_writable_roots_constraint_ignores_case_on_windows() {
+        let cwd = PathBuf::from(r"C:\Work\Repo");
+        let add_inside =
+            ApplyPatchAction::new_add_for_test(Path::new(r"c:\work\repo\inner.txt"), "".into());
+        let add_outside =
+            ApplyPatchAction::new_add_for_test(Path::new(r"D:\work\repo\inner.txt"), "".into());
+        let policy = SandboxPolicy::WorkspaceWrite {
+            writable_roots: vec![PathBuf::from(r"\\?\C:\Work\Repo")],
+            network_access: false,
+            allowed_domains: vec![],
+            exclude_tmpdir_env_var: true,
+            exclude_slash_tmp: true,
+        };
+
+        assert!(is_write_patch_constrained_to_writable_paths(
+            &add_inside,
+            &policy,
+            &cwd,
+        ));
+        assert!(!is_write_patch_constrained_to_writable_paths(
+            &add_outside,
+            &policy,
+            &cwd,
+        ));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_symlink_and_parent_dir_escapes_are_rejected() {
+        let tmp = TempDir::new().unwrap();
+        let cwd = tmp.path().join("repo");
+        let outside = tmp.path().join("outside");
+        std::fs::create_dir(&cwd).unwrap();
+        std::fs::create_dir(&outside).unwrap();
+        std::os::unix::fs::symlink(&outside, cwd.join("link")).unwrap();
+        let policy = SandboxPolicy::WorkspaceWrite {
+            writable_roots: vec![],
+            network_access: false,
+            allowed_domains: vec![],
+            exclude_tmpdir_env_var: true,
+            exclude_slash_tmp: true,
+        };
+
+        let via_symlink =
+            ApplyPatchAction::new_add_for_test(&cwd.join("link/evil.txt"), "".to_string());
+        assert!(matches!(
+            find_patch_path_escapes(&via_symlink, &policy, &cwd).as_slice(),
+            [PatchPathEscape::Symlink { .. }]
+        ));
+        assert!(!is_write_patch_constrained_to_writable_paths(
+            &via_symlink,
+            &policy,
+            &cwd,
+        ));
+        for approval_policy in [AskForApproval::Never, AskForApproval::OnRequest] {
+            assert!(matches!(
+                assess_patch_safety(&via_symlink, approval_policy, &policy, &cwd),
+                SafetyCheck::Reject { reason } if reason.contains("symlink")
+            ));
+        }
+
+        let via_parent_dir =
+            ApplyPatchAction::new_add_for_test(&cwd.join("src/../../evil.txt"), "".to_string());
+        assert!(matches!(
+            find_patch_path_escapes(&via_parent_dir, &policy, &cwd).as_slice(),
+            [PatchPathEscape::ParentDir { .. }]
+        ));
+
+        let inside = ApplyPatchAction::new_add_for_test(&cwd.join("src/ok.txt"), "".to_string());
+        assert_eq!(find_patch_path_escapes(&inside, &policy, &cwd), Vec::new());
+    }
+
     #[test]
     fn test_request_escalated_privileges() {
         // Should not be a trusted command
         let command = vec!["git commit".to_string()];
         let approval_policy = AskForApproval::OnRequest;
         let sandbox_policy = SandboxPolicy::ReadOnly;
-        let approved: HashSet<Vec<String>> = HashSet::new();
+        let approved = ApprovedCommands::default();
         let request_escalated_privileges = true;
 
         let safety_check = assess_command_safety(
@@ -330,7 +497,7 @@ mod tests {
         let command = vec!["git".to_string(), "commit".to_string()];
         let approval_policy = AskForApproval::OnRequest;
         let sandbox_policy = SandboxPolicy::ReadOnly;
-        let approved: HashSet<Vec<String>> = HashSet::new();
+        let approved = ApprovedCommands::default();
         let request_escalated_privileges = false;
 
         let safety_check = assess_command_safety(
@@ -347,4 +514,39 @@ mod tests {
         };
         assert_eq!(safety_check, expected);
     }
+
+    #[test]
+    fn read_only_commands_get_a_read_only_sandbox() {
+        let workspace_write = SandboxPolicy::new_workspace_write_policy();
+        let rg = vec!["rg".to_string(), "TODO".to_string(), "src".to_string()];
+        let expected =
+            get_platform_sandbox().map(|sandbox_type| (sandbox_type, SandboxPolicy::ReadOnly));
+        assert_eq!(read_only_sandbox_for(&rg, &workspace_write), expected);
+
+        let cat_and_ls = vec![
+            "bash".to_string(),
+            "-lc".to_string(),
+            "cat README.md && ls -la".to_string(),
+        ];
+        assert_eq!(
+            read_only_sandbox_for(&cat_and_ls, &workspace_write),
+            expected
+        );
+
+        // Anything that is not purely read-only keeps the session's sandbox.
+        let build = vec!["cargo".to_string(), "build".to_string()];
+        assert_eq!(read_only_sandbox_for(&build, &workspace_write), None);
+        let mixed = vec![
+            "bash".to_string(),
+            "-lc".to_string(),
+            "cat README.md && rm -rf target".to_string(),
+        ];
+        assert_eq!(read_only_sandbox_for(&mixed, &workspace_write), None);
+
+        // Full access is an explicit opt out of sandboxing.
+        assert_eq!(
+            read_only_sandbox_for(&rg, &SandboxPolicy::DangerFullAccess),
+            None
+        );
+    }
 }
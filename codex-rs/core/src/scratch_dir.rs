@@ -0,0 +1,170 @@
+//! Per-session scratch directory where the model can keep temporary scripts
+//! and artifacts.
+//!
+//! The directory is writable under every sandbox policy (see
+//! [`ScratchDir::sandbox_policy`]) and is removed when the session shuts down
+//! unless `retain_scratch_dir` is set.
+
+use std::path::Component;
+use std::path::Path;
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+use tempfile::TempDir;
+use tracing::warn;
+
+use crate::protocol::SandboxPolicy;
+
+pub(crate) struct ScratchDir {
+    path: PathBuf,
+    /// Owns the directory until shutdown. `None` when the directory is
+    /// retained or has already been removed.
+    dir: Mutex<Option<TempDir>>,
+}
+
+impl ScratchDir {
+    /// Creates a fresh directory under the system temp dir.
+    pub(crate) fn create(retain: bool) -> std::io::Result<Self> {
+        let dir = tempfile::Builder::new()
+            .prefix("codex-scratch-")
+            .tempdir()?;
+        // Sandboxes match writable roots against resolved paths, so avoid
+        // handing out a path that goes through a symlink (e.g. /var on macOS).
+        let path = dir
+            .path()
+            .canonicalize()
+            .unwrap_or_else(|_| dir.path().to_path_buf());
+        let dir = if retain {
+            let _ = dir.keep();
+            None
+        } else {
+            Some(dir)
+        };
+        Ok(Self {
+            path,
+            dir: Mutex::new(dir),
+        })
+    }
+
+    pub(crate) fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Whether `path` (absolute) lies inside the scratch directory.
+    pub(crate) fn contains(&self, path: &Path) -> bool {
+        path.starts_with(&self.path)
+            && !path
+                .components()
+                .any(|component| component == Component::ParentDir)
+    }
+
+    /// Deletes the directory unless it is retained. Safe to call repeatedly.
+    pub(crate) fn remove(&self) {
+        let dir = match self.dir.lock() {
+            Ok(mut guard) => guard.take(),
+            Err(poisoned) => poisoned.into_inner().take(),
+        };
+        if let Some(dir) = dir
+            && let Err(err) = dir.close()
+        {
+            warn!(
+                "failed to remove scratch dir {}: {err}",
+                self.path.display()
+            );
+        }
+    }
+
+    /// Returns the policy to enforce when running a command under `policy`,
+    /// together with the cwd its writable roots are computed against, so that
+    /// the scratch directory is writable as well.
+    pub(crate) fn sandbox_policy(
+        &self,
+        policy: &SandboxPolicy,
+        cwd: &Path,
+    ) -> (SandboxPolicy, PathBuf) {
+        match policy {
+            SandboxPolicy::DangerFullAccess => (policy.clone(), cwd.to_path_buf()),
+            SandboxPolicy::WorkspaceWrite {
+                writable_roots,
+                network_access,
+                allowed_domains,
+                exclude_tmpdir_env_var,
+                exclude_slash_tmp,
+            } => {
+                let mut writable_roots = writable_roots.clone();
+                writable_roots.push(self.path.clone());
+                (
+                    SandboxPolicy::WorkspaceWrite {
+                        writable_roots,
+                        network_access: *network_access,
+                        allowed_domains: allowed_domains.clone(),
+                        exclude_tmpdir_env_var: *exclude_tmpdir_env_var,
+                        exclude_slash_tmp: *exclude_slash_tmp,
+                    },
+                    cwd.to_path_buf(),
+                )
+            }
+            // Workspace-write rooted at the scratch dir, with none of the
+            // default extra roots, leaves everything else read-only.
+            SandboxPolicy::ReadOnly => (
+                SandboxPolicy::WorkspaceWrite {
+                    writable_roots: Vec::new(),
+                    network_access: false,
+                    allowed_domains: Vec::new(),
+                    exclude_tmpdir_env_var: true,
+                    exclude_slash_tmp: true,
+                },
+                self.path.clone(),
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn read_only_policy_only_allows_writes_to_scratch_dir() {
+        let scratch = ScratchDir::create(false).expect("create scratch dir");
+        let (policy, policy_cwd) =
+            scratch.sandbox_policy(&SandboxPolicy::ReadOnly, Path::new("/repo"));
+
+        let roots: Vec<PathBuf> = policy
+            .get_writable_roots_with_cwd(&policy_cwd)
+            .into_iter()
+            .map(|root| root.root)
+            .collect();
+        assert_eq!(roots, vec![scratch.path().to_path_buf()]);
+        assert!(!policy.has_full_network_access());
+    }
+
+    #[test]
+    fn workspace_write_policy_gains_scratch_root() {
+        let scratch = ScratchDir::create(false).expect("create scratch dir");
+        let (policy, policy_cwd) = scratch.sandbox_policy(
+            &SandboxPolicy::new_workspace_write_policy(),
+            Path::new("/repo"),
+        );
+
+        assert_eq!(policy_cwd, PathBuf::from("/repo"));
+        let SandboxPolicy::WorkspaceWrite { writable_roots, .. } = policy else {
+            panic!("expected workspace-write policy");
+        };
+        assert_eq!(writable_roots, vec![scratch.path().to_path_buf()]);
+    }
+
+    #[test]
+    fn remove_deletes_directory_unless_retained() {
+        let scratch = ScratchDir::create(false).expect("create scratch dir");
+        assert!(scratch.path().is_dir());
+        scratch.remove();
+        assert!(!scratch.path().exists());
+
+        let retained = ScratchDir::create(true).expect("create scratch dir");
+        retained.remove();
+        assert!(retained.path().is_dir());
+        std::fs::remove_dir_all(retained.path()).expect("clean up retained dir");
+    }
+}
@@ -3,6 +3,9 @@ use std::path::Path;
 use std::path::PathBuf;
 use tokio::process::Child;
 
+use crate::config_types::ResourceLimits;
+use crate::config_types::SandboxExtras;
+use crate::network_proxy::prepare_network_proxy;
 use crate::protocol::SandboxPolicy;
 use crate::spawn::CODEX_SANDBOX_ENV_VAR;
 use crate::spawn::StdioPolicy;
@@ -14,7 +17,7 @@ const MACOS_SEATBELT_BASE_POLICY: &str = include_str!("seatbelt_base_policy.sbpl
 /// to defend against an attacker trying to inject a malicious version on the
 /// PATH. If /usr/bin/sandbox-exec has been tampered with, then the attacker
 /// already has root access.
-const MACOS_PATH_TO_SEATBELT_EXECUTABLE: &str = "/usr/bin/sandbox-exec";
+pub(crate) const MACOS_PATH_TO_SEATBELT_EXECUTABLE: &str = "/usr/bin/sandbox-exec";
 
 pub async fn spawn_command_under_seatbelt(
     command: Vec<String>,
@@ -23,8 +26,17 @@ pub async fn spawn_command_under_seatbelt(
     sandbox_policy_cwd: &Path,
     stdio_policy: StdioPolicy,
     mut env: HashMap<String, String>,
+    resource_limits: ResourceLimits,
+    sandbox_extras: &SandboxExtras,
 ) -> std::io::Result<Child> {
-    let args = create_seatbelt_command_args(command, sandbox_policy, sandbox_policy_cwd);
+    let proxy_port = prepare_network_proxy(sandbox_policy, &mut env).await?;
+    let args = create_seatbelt_command_args(
+        command,
+        sandbox_policy,
+        sandbox_policy_cwd,
+        proxy_port,
+        sandbox_extras,
+    );
     let arg0 = None;
     env.insert(CODEX_SANDBOX_ENV_VAR.to_string(), "seatbelt".to_string());
     spawn_child_async(
@@ -35,14 +47,17 @@ pub async fn spawn_command_under_seatbelt(
         sandbox_policy,
         stdio_policy,
         env,
+        resource_limits,
     )
     .await
 }
 
-fn create_seatbelt_command_args(
+pub(crate) fn create_seatbelt_command_args(
     command: Vec<String>,
     sandbox_policy: &SandboxPolicy,
     sandbox_policy_cwd: &Path,
+    proxy_port: Option<u16>,
+    sandbox_extras: &SandboxExtras,
 ) -> Vec<String> {
     let (file_write_policy, extra_cli_args) = {
         if sandbox_policy.has_full_disk_write_access() {
@@ -105,14 +120,21 @@ fn create_seatbelt_command_args(
 
     // TODO(mbolin): apply_patch calls must also honor the SandboxPolicy.
     let network_policy = if sandbox_policy.has_full_network_access() {
-        "(allow network-outbound)\n(allow network-inbound)\n(allow system-socket)"
+        "(allow network-outbound)\n(allow network-inbound)\n(allow system-socket)".to_string()
+    } else if let Some(port) = proxy_port {
+        // Only the filtering proxy is reachable; it enforces `allowed_domains`.
+        format!("(allow network-outbound (remote ip \"localhost:{port}\"))")
     } else {
-        ""
+        String::new()
     };
 
-    let full_policy = format!(
+    let mut full_policy = format!(
         "{MACOS_SEATBELT_BASE_POLICY}\n{file_read_policy}\n{file_write_policy}\n{network_policy}"
     );
+    if !sandbox_extras.extra_seatbelt_rules.is_empty() {
+        full_policy.push_str("\n; extra rules from `sandbox.extra_seatbelt_rules`\n");
+        full_policy.push_str(&sandbox_extras.extra_seatbelt_rules.join("\n"));
+    }
 
     let mut seatbelt_args: Vec<String> = vec!["-p".to_string(), full_policy];
     seatbelt_args.extend(extra_cli_args);
@@ -125,6 +147,7 @@ fn create_seatbelt_command_args(
 mod tests {
     use super::MACOS_SEATBELT_BASE_POLICY;
     use super::create_seatbelt_command_args;
+    use crate::config_types::SandboxExtras;
     use crate::protocol::SandboxPolicy;
     use pretty_assertions::assert_eq;
     use std::fs;
@@ -156,6 +179,7 @@ mod tests {
         let policy = SandboxPolicy::WorkspaceWrite {
             writable_roots: vec![root_with_git, root_without_git],
             network_access: false,
+            allowed_domains: vec![],
             exclude_tmpdir_env_var: true,
             exclude_slash_tmp: true,
         };
@@ -164,6 +188,8 @@ mod tests {
             vec!["/bin/echo".to_string(), "hello".to_string()],
             &policy,
             &cwd,
+            None,
+            &SandboxExtras::default(),
         );
 
         // Build the expected policy text using a raw string for readability.
@@ -231,6 +257,7 @@ mod tests {
         let policy = SandboxPolicy::WorkspaceWrite {
             writable_roots: vec![],
             network_access: false,
+            allowed_domains: vec![],
             exclude_tmpdir_env_var: false,
             exclude_slash_tmp: false,
         };
@@ -239,6 +266,8 @@ mod tests {
             vec!["/bin/echo".to_string(), "hello".to_string()],
             &policy,
             root_with_git.as_path(),
+            None,
+            &SandboxExtras::default(),
         );
 
         let tmpdir_env_var = std::env::var("TMPDIR")
@@ -301,6 +330,46 @@ mod tests {
         assert_eq!(expected_args, args);
     }
 
+    #[test]
+    fn create_seatbelt_args_appends_extra_rules() {
+        let extras = SandboxExtras {
+            extra_seatbelt_rules: vec![
+                r#"(allow network-outbound (literal "/private/var/run/docker.sock"))"#.to_string(),
+                r#"(allow file-write* (subpath "/Users/me/.cache/bazel"))"#.to_string(),
+            ],
+            extra_landlock_paths: vec![PathBuf::from("/ignored/on/macos")],
+        };
+
+        let args = create_seatbelt_command_args(
+            vec!["/bin/echo".to_string(), "hello".to_string()],
+            &SandboxPolicy::new_read_only_policy(),
+            Path::new("/repo"),
+            None,
+            &extras,
+        );
+
+        let expected_policy = format!(
+            r#"{MACOS_SEATBELT_BASE_POLICY}
+; allow read-only file operations
+(allow file-read*)
+
+
+; extra rules from `sandbox.extra_seatbelt_rules`
+(allow network-outbound (literal "/private/var/run/docker.sock"))
+(allow file-write* (subpath "/Users/me/.cache/bazel"))"#,
+        );
+        assert_eq!(
+            vec![
+                "-p".to_string(),
+                expected_policy,
+                "--".to_string(),
+                "/bin/echo".to_string(),
+                "hello".to_string(),
+            ],
+            args
+        );
+    }
+
     struct PopulatedTmp {
         root_with_git: PathBuf,
         root_without_git: PathBuf,
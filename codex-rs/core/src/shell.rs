@@ -305,6 +305,8 @@ mod tests {
         for (input, expected_cmd, expected_output) in cases {
             use std::collections::HashMap;
 
+            use crate::config_types::ResourceLimits;
+            use crate::config_types::SandboxExtras;
             use crate::exec::ExecParams;
             use crate::exec::SandboxType;
             use crate::exec::process_exec_tool_call;
@@ -347,12 +349,17 @@ mod tests {
                     )]),
                     with_escalated_permissions: None,
                     justification: None,
+                    tty: false,
+                    resource_limits: ResourceLimits::default(),
+                    sandbox_extras: SandboxExtras::default(),
                 },
                 SandboxType::None,
                 &SandboxPolicy::DangerFullAccess,
                 temp_home.path(),
                 &None,
                 None,
+                None,
+                None,
             )
             .await
             .unwrap();
@@ -412,6 +419,8 @@ mod macos_tests {
             use std::collections::HashMap;
             use std::path::PathBuf;
 
+            use crate::config_types::ResourceLimits;
+            use crate::config_types::SandboxExtras;
             use crate::exec::ExecParams;
             use crate::exec::SandboxType;
             use crate::exec::process_exec_tool_call;
@@ -455,12 +464,17 @@ mod macos_tests {
                     )]),
                     with_escalated_permissions: None,
                     justification: None,
+                    tty: false,
+                    resource_limits: ResourceLimits::default(),
+                    sandbox_extras: SandboxExtras::default(),
                 },
                 SandboxType::None,
                 &SandboxPolicy::DangerFullAccess,
                 temp_home.path(),
                 &None,
                 None,
+                None,
+                None,
             )
             .await
             .unwrap();
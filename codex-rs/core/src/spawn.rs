@@ -5,7 +5,9 @@ use tokio::process::Child;
 use tokio::process::Command;
 use tracing::trace;
 
+use crate::config_types::ResourceLimits;
 use crate::protocol::SandboxPolicy;
+use crate::resource_limits;
 
 /// Experimental environment variable that will be set to some non-empty value
 /// if both of the following are true:
@@ -35,6 +37,9 @@ pub enum StdioPolicy {
 /// For now, we take `SandboxPolicy` as a parameter to spawn_child() because
 /// we need to determine whether to set the
 /// `CODEX_SANDBOX_NETWORK_DISABLED_ENV_VAR` environment variable.
+///
+/// `resource_limits` caps the memory and CPU time of the child and everything
+/// it spawns.
 pub(crate) async fn spawn_child_async(
     program: PathBuf,
     args: Vec<String>,
@@ -43,9 +48,10 @@ pub(crate) async fn spawn_child_async(
     sandbox_policy: &SandboxPolicy,
     stdio_policy: StdioPolicy,
     env: HashMap<String, String>,
+    resource_limits: ResourceLimits,
 ) -> std::io::Result<Child> {
     trace!(
-        "spawn_child_async: {program:?} {args:?} {arg0:?} {cwd:?} {sandbox_policy:?} {stdio_policy:?} {env:?}"
+        "spawn_child_async: {program:?} {args:?} {arg0:?} {cwd:?} {sandbox_policy:?} {stdio_policy:?} {env:?} {resource_limits:?}"
     );
 
     let mut cmd = Command::new(&program);
@@ -85,6 +91,15 @@ pub(crate) async fn spawn_child_async(
         });
     }
 
+    #[cfg(unix)]
+    if resource_limits != ResourceLimits::default() {
+        unsafe {
+            cmd.pre_exec(move || {
+                resource_limits::set_rlimits_for_current_process(&resource_limits)
+            });
+        }
+    }
+
     match stdio_policy {
         StdioPolicy::RedirectForShellTool => {
             // Do not create a file descriptor for stdin because otherwise some
@@ -94,6 +109,11 @@ pub(crate) async fn spawn_child_async(
             cmd.stdin(Stdio::null());
 
             cmd.stdout(Stdio::piped()).stderr(Stdio::piped());
+
+            // Lead a new process group so that interrupting the tool call can
+            // kill everything the command started, not just the shell.
+            #[cfg(unix)]
+            cmd.process_group(0);
         }
         StdioPolicy::Inherit => {
             // Inherit stdin, stdout, and stderr from the parent process.
@@ -103,5 +123,24 @@ pub(crate) async fn spawn_child_async(
         }
     }
 
-    cmd.kill_on_drop(true).spawn()
+    let child = cmd.kill_on_drop(true).spawn()?;
+
+    #[cfg(windows)]
+    if let Some(pid) = child.id()
+        && let Err(err) = resource_limits::apply_to_running_process(pid, &resource_limits)
+    {
+        tracing::warn!("failed to apply resource limits: {err}");
+    }
+
+    Ok(child)
+}
+
+/// Sends SIGKILL to the process group led by `pid`. Children spawned with
+/// [`StdioPolicy::RedirectForShellTool`] lead their own group, so this also
+/// reaches background jobs and other descendants of the command.
+#[cfg(unix)]
+pub(crate) fn kill_process_group(pid: u32) {
+    unsafe {
+        libc::killpg(pid as libc::pid_t, libc::SIGKILL);
+    }
 }
@@ -3,6 +3,10 @@ mod session;
 mod turn;
 
 pub(crate) use service::SessionServices;
+pub(crate) use session::Checkpoint;
+pub(crate) use session::GhostSnapshot;
+pub(crate) use session::PendingTask;
 pub(crate) use session::SessionState;
+pub(crate) use session::TaskWorkBranch;
 pub(crate) use turn::ActiveTurn;
 pub(crate) use turn::TurnState;
@@ -1,10 +1,22 @@
 use crate::RolloutRecorder;
+use crate::automations::Automations;
+use crate::codex::sub_agent::SubAgentManager;
 use crate::config::HooksConfig;
+use crate::config_types::BusyInput;
+use crate::config_types::OutputStreaming;
+use crate::exec_cache::ExecOutputCache;
 use crate::exec_command::ExecSessionManager;
+use crate::live_sessions::LiveSessionFile;
 use crate::mcp_connection_manager::McpConnectionManager;
+use crate::permission_gate::PermissionGate;
+use crate::scratch_dir::ScratchDir;
+use crate::storage_guard::StorageGuard;
 use crate::unified_exec::UnifiedExecSessionManager;
 use crate::user_notification::UserNotifier;
+use codex_protocol::config_types::StallAction;
 use std::path::PathBuf;
+use std::sync::Arc;
+use std::sync::OnceLock;
 use tokio::sync::Mutex;
 
 pub(crate) struct SessionServices {
@@ -17,4 +29,29 @@ pub(crate) struct SessionServices {
     pub(crate) user_shell: crate::shell::Shell,
     pub(crate) show_raw_agent_reasoning: bool,
     pub(crate) hooks: HooksConfig,
+    pub(crate) repeated_command_failure_limit: u32,
+    pub(crate) max_parallel_tool_calls: usize,
+    /// Runs the children started by `delegate` calls.
+    pub(crate) sub_agents: SubAgentManager,
+    pub(crate) stall_detection_turns: u32,
+    pub(crate) stall_action: StallAction,
+    pub(crate) busy_input: BusyInput,
+    pub(crate) max_session_cost_usd: Option<f64>,
+    pub(crate) auto_compact_preserve_messages: u32,
+    pub(crate) task_completion_assessment: bool,
+    pub(crate) storage_guard: StorageGuard,
+    pub(crate) codex_home: PathBuf,
+    pub(crate) permission_gate: PermissionGate,
+    pub(crate) scratch_dir: Option<ScratchDir>,
+    pub(crate) locale_context: bool,
+    pub(crate) git_auto_branch: bool,
+    pub(crate) command_provenance: bool,
+    pub(crate) output_streaming: OutputStreaming,
+    pub(crate) automations: Automations,
+    /// Set once a vendored `rg` is available in `$CODEX_HOME/bin`.
+    pub(crate) vendored_bin_dir: Arc<OnceLock<PathBuf>>,
+    /// Status file read by `codex status`.
+    pub(crate) live_session: Option<LiveSessionFile>,
+    /// Set when `exec.cache_read_only_commands` is enabled.
+    pub(crate) exec_output_cache: Option<ExecOutputCache>,
 }
@@ -1,23 +1,127 @@
 //! Session-wide mutable state.
 
-use std::collections::HashSet;
+use std::collections::BTreeMap;
+use std::collections::HashMap;
+use std::collections::VecDeque;
+use std::path::Path;
+use std::path::PathBuf;
+use std::sync::Arc;
 
+use chrono::DateTime;
+use chrono::Utc;
+use codex_git_tooling::GhostCommit;
+use codex_git_tooling::WorkBranch;
 use codex_protocol::models::ResponseItem;
 
 use crate::codex::AgentTask;
+use crate::codex::TurnContext;
+use crate::command_pattern::ApprovedCommands;
+use crate::config_types::ModelPricing;
 use crate::conversation_history::ConversationHistory;
+use crate::environment_context::GitStatusContext;
+use crate::environment_context::LocaleContext;
+use crate::plan_tool::UpdatePlanArgs;
+use crate::protocol::BackgroundProcess;
+use crate::protocol::InputItem;
+use crate::protocol::QueuedTask;
 use crate::protocol::RateLimitSnapshot;
+use crate::protocol::SessionStateSnapshot;
 use crate::protocol::TokenUsage;
 use crate::protocol::TokenUsageInfo;
 
+/// Ghost commits older than this are dropped from the session's list.
+const MAX_TRACKED_GHOST_COMMITS: usize = 20;
+
+/// Checkpoints older than this are dropped from the session's list.
+const MAX_CHECKPOINTS: usize = 50;
+
 /// Persistent, session-scoped state previously stored directly on `Session`.
 #[derive(Default)]
 pub(crate) struct SessionState {
-    pub(crate) approved_commands: HashSet<Vec<String>>,
+    pub(crate) approved_commands: ApprovedCommands,
     pub(crate) current_task: Option<AgentTask>,
     pub(crate) history: ConversationHistory,
     pub(crate) token_info: Option<TokenUsageInfo>,
     pub(crate) latest_rate_limits: Option<RateLimitSnapshot>,
+    /// Environment overrides set via `Op::SetSessionEnv` / `Op::UnsetSessionEnv`.
+    pub(crate) session_env: BTreeMap<String, Option<String>>,
+    /// Commands that keep failing with identical output, keyed by argv + cwd.
+    pub(crate) failed_commands: HashMap<(Vec<String>, PathBuf), FailedCommand>,
+    /// Ghost commits recorded before patches and write-capable commands, oldest first.
+    pub(crate) ghost_snapshots: Vec<GhostSnapshot>,
+    /// Set once snapshotting fails (e.g. outside a git repository) so it is not retried.
+    pub(crate) ghost_snapshots_disabled: bool,
+    /// Locale context last sent to the model, so it is only resent once it changes.
+    pub(crate) last_locale_context: Option<LocaleContext>,
+    /// Git status last sent to the model, so it is only resent once it changes.
+    pub(crate) last_git_status: Option<GitStatusContext>,
+    /// Branch created for a task by `git.auto_branch`, checked out again when
+    /// that task is aborted.
+    pub(crate) work_branch: Option<TaskWorkBranch>,
+    /// The plan last set with `update_plan`.
+    pub(crate) plan: Option<UpdatePlanArgs>,
+    /// The snapshot last recorded in the rollout, so unchanged state is not
+    /// recorded again.
+    pub(crate) last_state_snapshot: Option<SessionStateSnapshot>,
+    /// Saved by `Op::Checkpoint`, oldest first.
+    pub(crate) checkpoints: Vec<Checkpoint>,
+    /// Number of checkpoints created so far, used to name the next one.
+    pub(crate) checkpoint_count: u64,
+    /// User input queued by `busy_input = "queue"`, oldest first.
+    pub(crate) queued_tasks: VecDeque<PendingTask>,
+    /// Spend, in USD, the user approved past `max_session_cost_usd`.
+    pub(crate) approved_extra_spend_usd: f64,
+    /// Set once the user approved any spend for the rest of the session.
+    pub(crate) spend_limit_lifted: bool,
+}
+
+/// User input queued to run as a task of its own, with the turn context in
+/// effect when it was submitted.
+#[derive(Clone)]
+pub(crate) struct PendingTask {
+    pub(crate) sub_id: String,
+    pub(crate) items: Vec<InputItem>,
+    pub(crate) turn_context: Arc<TurnContext>,
+}
+
+/// The conversation history, and optionally a ghost commit of the working
+/// tree, saved by `Op::Checkpoint`.
+#[derive(Debug, Clone)]
+pub(crate) struct Checkpoint {
+    pub(crate) id: String,
+    pub(crate) label: Option<String>,
+    pub(crate) history: Vec<ResponseItem>,
+    /// Ghost commit of the working tree and the directory it was taken from.
+    pub(crate) workspace: Option<(GhostCommit, PathBuf)>,
+}
+
+/// A ghost commit together with the directory it was taken from, which
+/// limits what restoring it touches.
+#[derive(Debug, Clone)]
+pub(crate) struct GhostSnapshot {
+    pub(crate) commit: GhostCommit,
+    pub(crate) cwd: PathBuf,
+    /// The task (submission id) that took the snapshot.
+    pub(crate) sub_id: String,
+    /// The tool call the snapshot was taken for.
+    pub(crate) call_id: String,
+    pub(crate) created_at: DateTime<Utc>,
+}
+
+/// A work branch together with the task that created it and the directory
+/// it was created from.
+#[derive(Debug, Clone)]
+pub(crate) struct TaskWorkBranch {
+    pub(crate) branch: WorkBranch,
+    pub(crate) cwd: PathBuf,
+    pub(crate) sub_id: String,
+}
+
+/// A command that has failed one or more times in a row with the same output.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct FailedCommand {
+    pub(crate) output: String,
+    pub(crate) count: u32,
 }
 
 impl SessionState {
@@ -51,21 +155,169 @@ impl SessionState {
         self.approved_commands.insert(cmd);
     }
 
-    pub(crate) fn approved_commands_ref(&self) -> &HashSet<Vec<String>> {
+    pub(crate) fn add_approved_command_pattern(&mut self, pattern: String) {
+        self.approved_commands.add_pattern(pattern);
+    }
+
+    pub(crate) fn approved_commands_ref(&self) -> &ApprovedCommands {
         &self.approved_commands
     }
 
+    // Session environment helpers
+    pub(crate) fn set_session_env(&mut self, key: String, value: Option<String>) {
+        self.session_env.insert(key, value);
+    }
+
+    pub(crate) fn session_env(&self) -> &BTreeMap<String, Option<String>> {
+        &self.session_env
+    }
+
+    // Work branch helpers
+    /// Remove and return the work branch if `sub_id` is the task that created it.
+    pub(crate) fn take_work_branch(&mut self, sub_id: &str) -> Option<TaskWorkBranch> {
+        if self
+            .work_branch
+            .as_ref()
+            .is_some_and(|work_branch| work_branch.sub_id == sub_id)
+        {
+            self.work_branch.take()
+        } else {
+            None
+        }
+    }
+
+    // Failed command helpers
+    pub(crate) fn record_command_failure(
+        &mut self,
+        command: Vec<String>,
+        cwd: PathBuf,
+        output: String,
+    ) -> u32 {
+        let entry = self
+            .failed_commands
+            .entry((command, cwd))
+            .or_insert_with(|| FailedCommand {
+                output: output.clone(),
+                count: 0,
+            });
+        if entry.output != output {
+            entry.output = output;
+            entry.count = 0;
+        }
+        entry.count += 1;
+        entry.count
+    }
+
+    pub(crate) fn record_command_success(&mut self, command: &[String], cwd: &Path) {
+        self.failed_commands
+            .remove(&(command.to_vec(), cwd.to_path_buf()));
+    }
+
+    pub(crate) fn failed_command(&self, command: &[String], cwd: &Path) -> Option<&FailedCommand> {
+        self.failed_commands
+            .get(&(command.to_vec(), cwd.to_path_buf()))
+    }
+
+    pub(crate) fn clear_failed_commands(&mut self) {
+        self.failed_commands.clear();
+    }
+
+    // Ghost snapshot helpers
+    pub(crate) fn record_ghost_snapshot(
+        &mut self,
+        commit: GhostCommit,
+        cwd: PathBuf,
+        sub_id: String,
+        call_id: String,
+    ) {
+        self.ghost_snapshots.push(GhostSnapshot {
+            commit,
+            cwd,
+            sub_id,
+            call_id,
+            created_at: Utc::now(),
+        });
+        if self.ghost_snapshots.len() > MAX_TRACKED_GHOST_COMMITS {
+            self.ghost_snapshots.remove(0);
+        }
+    }
+
+    pub(crate) fn take_last_ghost_snapshot(&mut self) -> Option<GhostSnapshot> {
+        self.ghost_snapshots.pop()
+    }
+
+    /// The queued tasks as reported to clients.
+    pub(crate) fn queued_task_infos(&self) -> Vec<QueuedTask> {
+        self.queued_tasks
+            .iter()
+            .map(|task| QueuedTask {
+                id: task.sub_id.clone(),
+                items: task.items.clone(),
+            })
+            .collect()
+    }
+
+    // Checkpoint helpers
+    pub(crate) fn add_checkpoint(
+        &mut self,
+        label: Option<String>,
+        workspace: Option<(GhostCommit, PathBuf)>,
+    ) -> String {
+        self.checkpoint_count += 1;
+        let id = format!("checkpoint-{}", self.checkpoint_count);
+        self.checkpoints.push(Checkpoint {
+            id: id.clone(),
+            label,
+            history: self.history.contents(),
+            workspace,
+        });
+        if self.checkpoints.len() > MAX_CHECKPOINTS {
+            self.checkpoints.remove(0);
+        }
+        id
+    }
+
+    /// Remove the checkpoint `id` and every later one, returning `id`.
+    pub(crate) fn take_checkpoint(&mut self, id: &str) -> Option<Checkpoint> {
+        let index = self
+            .checkpoints
+            .iter()
+            .position(|checkpoint| checkpoint.id == id)?;
+        self.checkpoints.drain(index..).next()
+    }
+
     // Token/rate limit helpers
     pub(crate) fn update_token_info_from_usage(
         &mut self,
         usage: &TokenUsage,
         model_context_window: Option<u64>,
+        pricing: Option<ModelPricing>,
     ) {
         self.token_info = TokenUsageInfo::new_or_append(
             &self.token_info,
             &Some(usage.clone()),
             model_context_window,
         );
+        if let (Some(info), Some(pricing)) = (self.token_info.as_mut(), pricing) {
+            info.add_cost(pricing.cost_usd(usage));
+        }
+    }
+
+    pub(crate) fn estimated_cost_usd(&self) -> Option<f64> {
+        self.token_info
+            .as_ref()
+            .and_then(|info| info.estimated_cost_usd)
+    }
+
+    /// The estimated cost of the session and the limit it reached, once it
+    /// reached `limit_usd` plus the spend already approved past it.
+    pub(crate) fn spend_limit_reached(&self, limit_usd: f64) -> Option<(f64, f64)> {
+        if self.spend_limit_lifted {
+            return None;
+        }
+        let cost = self.estimated_cost_usd()?;
+        let limit = limit_usd + self.approved_extra_spend_usd;
+        (cost >= limit).then_some((cost, limit))
     }
 
     pub(crate) fn set_rate_limits(&mut self, snapshot: RateLimitSnapshot) {
@@ -78,5 +330,161 @@ impl SessionState {
         (self.token_info.clone(), self.latest_rate_limits.clone())
     }
 
+    // State snapshot helpers
+    pub(crate) fn state_snapshot(
+        &self,
+        background_processes: Vec<BackgroundProcess>,
+    ) -> SessionStateSnapshot {
+        SessionStateSnapshot {
+            approved_commands: self.approved_commands.exact_commands(),
+            approved_command_patterns: self.approved_commands.patterns().to_vec(),
+            plan: self.plan.clone(),
+            token_usage: self.token_info.clone(),
+            background_processes,
+        }
+    }
+
+    /// The snapshot to record in the rollout, or `None` when it is the same
+    /// as the one recorded last.
+    pub(crate) fn changed_state_snapshot(
+        &mut self,
+        background_processes: Vec<BackgroundProcess>,
+    ) -> Option<SessionStateSnapshot> {
+        let snapshot = self.state_snapshot(background_processes);
+        if self.last_state_snapshot.as_ref() == Some(&snapshot) {
+            return None;
+        }
+        self.last_state_snapshot = Some(snapshot.clone());
+        Some(snapshot)
+    }
+
+    /// Restores the approvals, plan and token usage of a resumed session.
+    /// Background processes are not restored since they died with it.
+    pub(crate) fn restore_state_snapshot(&mut self, snapshot: SessionStateSnapshot) {
+        for command in snapshot.approved_commands {
+            self.approved_commands.insert(command);
+        }
+        for pattern in snapshot.approved_command_patterns {
+            self.approved_commands.add_pattern(pattern);
+        }
+        self.plan = snapshot.plan;
+        self.token_info = snapshot.token_usage;
+    }
+
     // Pending input/approval moved to TurnState.
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::plan_tool::PlanItemArg;
+    use crate::plan_tool::StepStatus;
+    use codex_protocol::models::ContentItem;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn state_snapshot_round_trips_and_skips_unchanged_state() {
+        let mut state = SessionState::new();
+        state.add_approved_command(vec!["make".to_string(), "deploy".to_string()]);
+        state.add_approved_command_pattern("cargo test *".to_string());
+        state.plan = Some(UpdatePlanArgs {
+            explanation: None,
+            plan: vec![PlanItemArg {
+                step: "Write tests".to_string(),
+                status: StepStatus::InProgress,
+            }],
+        });
+        let processes = vec![BackgroundProcess {
+            session_id: 3,
+            command: "npm run dev".to_string(),
+        }];
+
+        let snapshot = state
+            .changed_state_snapshot(processes.clone())
+            .expect("first snapshot is recorded");
+        assert_eq!(state.changed_state_snapshot(processes), None);
+
+        let mut restored = SessionState::new();
+        restored.restore_state_snapshot(snapshot.clone());
+        assert!(
+            restored
+                .approved_commands_ref()
+                .contains(&["make".to_string(), "deploy".to_string()])
+        );
+        assert!(
+            restored
+                .approved_commands_ref()
+                .contains(&["cargo".to_string(), "test".to_string()])
+        );
+        assert_eq!(
+            restored.state_snapshot(Vec::new()),
+            SessionStateSnapshot {
+                background_processes: Vec::new(),
+                ..snapshot
+            }
+        );
+    }
+
+    #[test]
+    fn spend_limit_is_reached_again_after_another_limit_is_spent() {
+        let pricing = ModelPricing {
+            input: 1.0,
+            cached_input: None,
+            output: 1.0,
+        };
+        let usage = TokenUsage {
+            input_tokens: 2_000_000,
+            total_tokens: 2_000_000,
+            ..Default::default()
+        };
+        let mut state = SessionState::new();
+        assert_eq!(state.spend_limit_reached(3.0), None);
+
+        state.update_token_info_from_usage(&usage, None, Some(pricing));
+        assert_eq!(state.spend_limit_reached(3.0), None);
+        state.update_token_info_from_usage(&usage, None, Some(pricing));
+        assert_eq!(state.spend_limit_reached(3.0), Some((4.0, 3.0)));
+
+        // Approving once allows another `limit_usd` from the current cost.
+        state.approved_extra_spend_usd = 4.0;
+        state.update_token_info_from_usage(&usage, None, Some(pricing));
+        assert_eq!(state.spend_limit_reached(3.0), None);
+        state.update_token_info_from_usage(&usage, None, Some(pricing));
+        assert_eq!(state.spend_limit_reached(3.0), Some((8.0, 7.0)));
+
+        state.spend_limit_lifted = true;
+        assert_eq!(state.spend_limit_reached(3.0), None);
+    }
+
+    #[test]
+    fn taking_a_checkpoint_discards_it_and_later_ones() {
+        let message = |text: &str| ResponseItem::Message {
+            id: None,
+            role: "user".to_string(),
+            content: vec![ContentItem::InputText {
+                text: text.to_string(),
+            }],
+        };
+        let mut state = SessionState::new();
+        let first = state.add_checkpoint(Some("first".to_string()), None);
+        state.record_items([&message("first")]);
+        let second = state.add_checkpoint(Some("second".to_string()), None);
+        state.record_items([&message("second")]);
+        state.add_checkpoint(None, None);
+
+        let checkpoint = state.take_checkpoint(&second).expect("checkpoint exists");
+        assert_eq!(checkpoint.label.as_deref(), Some("second"));
+        assert_eq!(checkpoint.history, vec![message("first")]);
+        assert_eq!(
+            state
+                .checkpoints
+                .iter()
+                .map(|checkpoint| checkpoint.id.as_str())
+                .collect::<Vec<_>>(),
+            vec![first.as_str()]
+        );
+        assert!(state.take_checkpoint(&second).is_none());
+        // Ids are not reused once checkpoints are discarded.
+        assert_eq!(state.add_checkpoint(None, None), "checkpoint-4");
+    }
+}
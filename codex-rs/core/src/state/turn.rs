@@ -1,11 +1,13 @@
 //! Turn-scoped state and active turn metadata scaffolding.
 
 use std::collections::HashMap;
+use std::path::PathBuf;
 use std::sync::Arc;
 use tokio::sync::Mutex;
 
 use codex_protocol::models::ResponseInputItem;
 use tokio::sync::oneshot;
+use tokio_util::sync::CancellationToken;
 
 use crate::protocol::ReviewDecision;
 
@@ -20,7 +22,13 @@ pub(crate) struct ActiveTurn {
 #[derive(Default)]
 pub(crate) struct TurnState {
     pending_approvals: HashMap<String, oneshot::Sender<ReviewDecision>>,
+    /// Files the user approved from a patch, when they approved only some.
+    approved_patch_files: HashMap<String, Vec<PathBuf>>,
     pending_input: Vec<ResponseInputItem>,
+    /// Messages from `Op::Steer`, delivered ahead of `pending_input`.
+    pending_steering: Vec<ResponseInputItem>,
+    /// Tokens of the tool calls that are running, by call id.
+    running_tool_calls: HashMap<String, CancellationToken>,
 }
 
 impl TurnState {
@@ -29,6 +37,7 @@ impl TurnState {
         key: String,
         tx: oneshot::Sender<ReviewDecision>,
     ) -> Option<oneshot::Sender<ReviewDecision>> {
+        self.approved_patch_files.remove(&key);
         self.pending_approvals.insert(key, tx)
     }
 
@@ -39,21 +48,65 @@ impl TurnState {
         self.pending_approvals.remove(key)
     }
 
-    pub(crate) fn clear_pending(&mut self) {
+    pub(crate) fn set_approved_patch_files(&mut self, key: String, files: Vec<PathBuf>) {
+        self.approved_patch_files.insert(key, files);
+    }
+
+    pub(crate) fn take_approved_patch_files(&mut self, key: &str) -> Option<Vec<PathBuf>> {
+        self.approved_patch_files.remove(key)
+    }
+
+    pub(crate) fn insert_running_tool_call(&mut self, call_id: String, token: CancellationToken) {
+        self.running_tool_calls.insert(call_id, token);
+    }
+
+    pub(crate) fn remove_running_tool_call(&mut self, call_id: &str) {
+        self.running_tool_calls.remove(call_id);
+    }
+
+    /// Cancels the running tool call `call_id`, returning whether there was one.
+    pub(crate) fn cancel_running_tool_call(&mut self, call_id: &str) -> bool {
+        match self.running_tool_calls.remove(call_id) {
+            Some(token) => {
+                token.cancel();
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Drops the pending approvals, so the calls waiting on them are denied.
+    pub(crate) fn clear_pending_approvals(&mut self) {
         self.pending_approvals.clear();
+        self.approved_patch_files.clear();
+    }
+
+    pub(crate) fn clear_pending(&mut self) {
+        self.clear_pending_approvals();
         self.pending_input.clear();
+        self.pending_steering.clear();
     }
 
     pub(crate) fn push_pending_input(&mut self, input: ResponseInputItem) {
         self.pending_input.push(input);
     }
 
+    pub(crate) fn push_pending_steering(&mut self, input: ResponseInputItem) {
+        self.pending_steering.push(input);
+    }
+
+    pub(crate) fn has_pending_steering(&self) -> bool {
+        !self.pending_steering.is_empty()
+    }
+
+    /// Takes the input waiting for the next model request, steering messages
+    /// first.
     pub(crate) fn take_pending_input(&mut self) -> Vec<ResponseInputItem> {
-        if self.pending_input.is_empty() {
+        if self.pending_input.is_empty() && self.pending_steering.is_empty() {
             Vec::with_capacity(0)
         } else {
-            let mut ret = Vec::new();
-            std::mem::swap(&mut ret, &mut self.pending_input);
+            let mut ret = std::mem::take(&mut self.pending_steering);
+            ret.append(&mut self.pending_input);
             ret
         }
     }
@@ -0,0 +1,290 @@
+//! Watches the disk that holds `CODEX_HOME` so session persistence degrades
+//! gracefully instead of failing writes once space runs out.
+//!
+//! While storage is low (less than `storage.min_free_mb` free, or the sessions
+//! and log directories together above `storage.sessions_quota_mb`), rollout
+//! items are passed through [`reduce_for_low_storage`] before they are written.
+//! The check itself stats the disk and walks those directories, so it runs on
+//! the blocking thread pool.
+
+use std::path::Path;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::Duration;
+use std::time::Instant;
+
+use codex_protocol::models::ResponseItem;
+use codex_protocol::protocol::EventMsg;
+use codex_protocol::protocol::RolloutItem;
+
+use crate::config_types::Storage;
+use crate::rollout::SESSIONS_SUBDIR;
+use crate::truncate::truncate_middle;
+
+/// How often the disk is re-checked; writes in between reuse the last result.
+const CHECK_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Tool output kept per call while storage is low.
+const REDUCED_TOOL_OUTPUT_BYTES: usize = 2 * 1024;
+
+const MIB: u64 = 1024 * 1024;
+
+/// Directory under `CODEX_HOME` that holds log files; see [`crate::config::log_dir`].
+const LOG_SUBDIR: &str = "log";
+
+/// Change in storage state observed by [`StorageGuard::poll`].
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) enum StorageTransition {
+    Unchanged,
+    /// Storage just dropped below a threshold, for the given reason.
+    BecameLow(String),
+    Recovered,
+}
+
+pub(crate) struct StorageGuard {
+    codex_home: PathBuf,
+    settings: Storage,
+    state: Mutex<GuardState>,
+}
+
+#[derive(Default)]
+struct GuardState {
+    last_check: Option<Instant>,
+    low: bool,
+    write_failed: bool,
+}
+
+impl StorageGuard {
+    pub(crate) fn new(codex_home: PathBuf, settings: Storage) -> Self {
+        Self {
+            codex_home,
+            settings,
+            state: Mutex::new(GuardState::default()),
+        }
+    }
+
+    /// Re-check storage if the last check is older than [`CHECK_INTERVAL`].
+    /// Callers that arrive while a check is running reuse the previous result.
+    pub(crate) async fn poll(&self) -> StorageTransition {
+        {
+            let Ok(mut state) = self.state.lock() else {
+                return StorageTransition::Unchanged;
+            };
+            if state
+                .last_check
+                .is_some_and(|last| last.elapsed() < CHECK_INTERVAL)
+            {
+                return StorageTransition::Unchanged;
+            }
+            state.last_check = Some(Instant::now());
+        }
+        let codex_home = self.codex_home.clone();
+        let settings = self.settings;
+        let Ok(reason) =
+            tokio::task::spawn_blocking(move || low_storage_reason(&codex_home, &settings)).await
+        else {
+            return StorageTransition::Unchanged;
+        };
+        let Ok(mut state) = self.state.lock() else {
+            return StorageTransition::Unchanged;
+        };
+        match (state.low, reason) {
+            (false, Some(reason)) => {
+                state.low = true;
+                StorageTransition::BecameLow(reason)
+            }
+            (true, None) => {
+                state.low = false;
+                StorageTransition::Recovered
+            }
+            _ => StorageTransition::Unchanged,
+        }
+    }
+
+    /// Whether rollouts should currently be written in reduced form.
+    pub(crate) fn is_low(&self) -> bool {
+        self.state.lock().map(|state| state.low).unwrap_or(false)
+    }
+
+    /// Record a failed rollout write. Returns `true` the first time so the
+    /// caller warns once rather than on every write.
+    pub(crate) fn mark_write_failed(&self) -> bool {
+        match self.state.lock() {
+            Ok(mut state) => !std::mem::replace(&mut state.write_failed, true),
+            Err(_) => false,
+        }
+    }
+}
+
+/// User-facing message for [`StorageTransition::BecameLow`].
+pub(crate) fn low_storage_warning(reason: &str) -> String {
+    format!(
+        "Low disk space: {reason}. Until space is freed, this session is saved without raw reasoning and with truncated tool output."
+    )
+}
+
+/// Why storage counts as low, or `None` when it is fine (or unknown).
+fn low_storage_reason(codex_home: &Path, settings: &Storage) -> Option<String> {
+    if let Some(available) = available_space(codex_home) {
+        let min_free = settings.min_free_mb.saturating_mul(MIB);
+        if available < min_free {
+            return Some(format!(
+                "only {} MiB free on the disk holding {} (minimum {} MiB)",
+                available / MIB,
+                codex_home.display(),
+                settings.min_free_mb
+            ));
+        }
+    }
+    if let Some(quota_mb) = settings.sessions_quota_mb {
+        let used = directory_size(&codex_home.join(SESSIONS_SUBDIR))
+            + directory_size(&codex_home.join(LOG_SUBDIR));
+        if used > quota_mb.saturating_mul(MIB) {
+            return Some(format!(
+                "sessions and logs use {} MiB, above the {quota_mb} MiB quota",
+                used / MIB
+            ));
+        }
+    }
+    None
+}
+
+#[cfg(unix)]
+fn available_space(path: &Path) -> Option<u64> {
+    use std::ffi::CString;
+    use std::os::unix::ffi::OsStrExt;
+
+    let c_path = CString::new(path.as_os_str().as_bytes()).ok()?;
+    let mut stat: libc::statvfs = unsafe { std::mem::zeroed() };
+    // SAFETY: `c_path` is a valid NUL-terminated string and `stat` is a
+    // properly sized out-parameter.
+    if unsafe { libc::statvfs(c_path.as_ptr(), &mut stat) } != 0 {
+        return None;
+    }
+    #[allow(clippy::unnecessary_cast)]
+    Some((stat.f_bavail as u64).saturating_mul(stat.f_frsize as u64))
+}
+
+#[cfg(not(unix))]
+fn available_space(_path: &Path) -> Option<u64> {
+    None
+}
+
+fn directory_size(path: &Path) -> u64 {
+    let Ok(entries) = std::fs::read_dir(path) else {
+        return 0;
+    };
+    entries
+        .flatten()
+        .map(|entry| match entry.file_type() {
+            Ok(file_type) if file_type.is_dir() => directory_size(&entry.path()),
+            Ok(_) => entry.metadata().map(|m| m.len()).unwrap_or(0),
+            Err(_) => 0,
+        })
+        .sum()
+}
+
+/// The form of `item` to persist while storage is low: raw reasoning is
+/// dropped and tool output is truncated. Returns `None` to skip the item.
+pub(crate) fn reduce_for_low_storage(item: &RolloutItem) -> Option<RolloutItem> {
+    match item {
+        RolloutItem::EventMsg(EventMsg::AgentReasoningRawContent(_)) => None,
+        RolloutItem::ResponseItem(ResponseItem::Reasoning {
+            id,
+            summary,
+            encrypted_content,
+            ..
+        }) => Some(RolloutItem::ResponseItem(ResponseItem::Reasoning {
+            id: id.clone(),
+            summary: summary.clone(),
+            content: None,
+            encrypted_content: encrypted_content.clone(),
+        })),
+        RolloutItem::ResponseItem(ResponseItem::FunctionCallOutput { call_id, output }) => {
+            let mut output = output.clone();
+            output.content = truncate_middle(&output.content, REDUCED_TOOL_OUTPUT_BYTES).0;
+            Some(RolloutItem::ResponseItem(
+                ResponseItem::FunctionCallOutput {
+                    call_id: call_id.clone(),
+                    output,
+                },
+            ))
+        }
+        RolloutItem::ResponseItem(ResponseItem::CustomToolCallOutput { call_id, output }) => Some(
+            RolloutItem::ResponseItem(ResponseItem::CustomToolCallOutput {
+                call_id: call_id.clone(),
+                output: truncate_middle(output, REDUCED_TOOL_OUTPUT_BYTES).0,
+            }),
+        ),
+        other => Some(other.clone()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use codex_protocol::models::FunctionCallOutputPayload;
+    use codex_protocol::protocol::AgentReasoningRawContentEvent;
+    use pretty_assertions::assert_eq;
+    use tempfile::TempDir;
+
+    #[test]
+    fn reduced_items_drop_raw_reasoning_and_truncate_output() {
+        let raw = RolloutItem::EventMsg(EventMsg::AgentReasoningRawContent(
+            AgentReasoningRawContentEvent {
+                text: "thinking".to_string(),
+            },
+        ));
+        assert!(reduce_for_low_storage(&raw).is_none());
+
+        let output = RolloutItem::ResponseItem(ResponseItem::FunctionCallOutput {
+            call_id: "call-1".to_string(),
+            output: FunctionCallOutputPayload {
+                content: "x".repeat(10 * REDUCED_TOOL_OUTPUT_BYTES),
+                success: Some(true),
+            },
+        });
+        let Some(RolloutItem::ResponseItem(ResponseItem::FunctionCallOutput { output, .. })) =
+            reduce_for_low_storage(&output)
+        else {
+            panic!("tool output should be kept");
+        };
+        assert!(output.content.len() < 2 * REDUCED_TOOL_OUTPUT_BYTES);
+        assert!(output.content.contains("tokens truncated"));
+        assert_eq!(output.success, Some(true));
+    }
+
+    #[tokio::test]
+    async fn sessions_quota_marks_storage_low_once() {
+        let home = TempDir::new().expect("tempdir");
+        let sessions = home.path().join(SESSIONS_SUBDIR).join("2025");
+        std::fs::create_dir_all(&sessions).expect("sessions dir");
+        std::fs::write(sessions.join("rollout.jsonl"), vec![b'x'; MIB as usize])
+            .expect("write rollout");
+        let logs = home.path().join(LOG_SUBDIR);
+        std::fs::create_dir_all(&logs).expect("log dir");
+        std::fs::write(logs.join("codex-tui.log"), vec![b'x'; MIB as usize]).expect("write log");
+
+        let guard = StorageGuard::new(
+            home.path().to_path_buf(),
+            Storage {
+                min_free_mb: 0,
+                sessions_quota_mb: Some(1),
+            },
+        );
+        let StorageTransition::BecameLow(reason) = guard.poll().await else {
+            panic!("quota should be exceeded");
+        };
+        assert_eq!(reason, "sessions and logs use 2 MiB, above the 1 MiB quota");
+        assert!(guard.is_low());
+        // Within the check interval the previous result is reused.
+        assert_eq!(guard.poll().await, StorageTransition::Unchanged);
+    }
+
+    #[test]
+    fn write_failure_is_reported_once() {
+        let guard = StorageGuard::new(PathBuf::from("."), Storage::default());
+        assert!(guard.mark_write_failed());
+        assert!(!guard.mark_write_failed());
+    }
+}
@@ -10,6 +10,11 @@ use anyhow::anyhow;
 use sha1::digest::Output;
 use uuid::Uuid;
 
+use crate::path_utils::display_path;
+use crate::path_utils::normalize_path;
+use crate::path_utils::path_starts_with;
+use crate::path_utils::paths_equal;
+use crate::path_utils::strip_path_prefix;
 use crate::protocol::FileChange;
 
 const ZERO_OID: &str = "0000000000000000000000000000000000000000";
@@ -53,6 +58,7 @@ impl TurnDiffTracker {
     /// - Also updates internal mappings for move/rename events.
     pub fn on_patch_begin(&mut self, changes: &HashMap<PathBuf, FileChange>) {
         for (path, change) in changes.iter() {
+            let path = &self.tracked_path(path);
             // Ensure a stable internal filename exists for this external path.
             if !self.external_to_temp_name.contains_key(path) {
                 let internal = Uuid::new_v4().to_string();
@@ -99,6 +105,7 @@ impl TurnDiffTracker {
                 ..
             } = change
             {
+                let dest = &self.tracked_path(dest);
                 let uuid_filename = match self.external_to_temp_name.get(path) {
                     Some(i) => i.clone(),
                     None => {
@@ -127,6 +134,21 @@ impl TurnDiffTracker {
         }
     }
 
+    /// Key under which `path` is tracked: an already-tracked path that names
+    /// the same file (on Windows, differing only in case or by a verbatim
+    /// prefix), or else `path` in normalized form.
+    fn tracked_path(&self, path: &Path) -> PathBuf {
+        let normalized = normalize_path(path);
+        if self.external_to_temp_name.contains_key(&normalized) {
+            return normalized;
+        }
+        self.external_to_temp_name
+            .keys()
+            .find(|known| paths_equal(known, &normalized))
+            .cloned()
+            .unwrap_or(normalized)
+    }
+
     fn get_path_for_internal(&self, internal: &str) -> Option<PathBuf> {
         self.temp_name_to_current_path
             .get(internal)
@@ -151,7 +173,7 @@ impl TurnDiffTracker {
         if let Some(root) = self
             .git_root_cache
             .iter()
-            .find(|r| dir.starts_with(r))
+            .find(|r| path_starts_with(dir, r))
             .cloned()
         {
             return Some(root);
@@ -187,13 +209,13 @@ impl TurnDiffTracker {
     /// Return a display string for `path` relative to its git root if found, else absolute.
     fn relative_to_git_root_str(&mut self, path: &Path) -> String {
         let s = if let Some(root) = self.find_git_root_cached(path) {
-            if let Ok(rel) = path.strip_prefix(&root) {
+            if let Some(rel) = strip_path_prefix(path, &root) {
                 rel.display().to_string()
             } else {
-                path.display().to_string()
+                display_path(path)
             }
         } else {
-            path.display().to_string()
+            display_path(path)
         };
         s.replace('\\', "/")
     }
@@ -203,7 +225,7 @@ impl TurnDiffTracker {
     fn git_blob_oid_for_path(&mut self, path: &Path) -> Option<String> {
         let root = self.find_git_root_cached(path)?;
         // Compute a path relative to the repo root for better portability across platforms.
-        let rel = path.strip_prefix(&root).unwrap_or(path);
+        let rel = strip_path_prefix(path, &root).unwrap_or_else(|| path.to_path_buf());
         let output = Command::new("git")
             .arg("-C")
             .arg(&root)
@@ -249,6 +271,24 @@ impl TurnDiffTracker {
         }
     }
 
+    /// Paths, as of the latest patch, of the files that appear in
+    /// [`Self::get_unified_diff`], sorted. A deleted file is listed under the
+    /// path it was deleted from.
+    pub fn changed_paths(&mut self) -> Vec<PathBuf> {
+        let internal_names: Vec<String> = self.baseline_file_info.keys().cloned().collect();
+        let mut paths = Vec::new();
+        for internal in internal_names {
+            if self.get_file_diff(&internal).is_empty() {
+                continue;
+            }
+            if let Some(path) = self.get_path_for_internal(&internal) {
+                paths.push(path);
+            }
+        }
+        paths.sort();
+        paths
+    }
+
     fn get_file_diff(&mut self, internal_file_name: &str) -> String {
         let mut aggregated = String::new();
 
@@ -571,6 +611,44 @@ index {ZERO_OID}..{right_oid}
         assert_eq!(combined, expected_combined);
     }
 
+    #[test]
+    fn changed_paths_lists_files_that_still_differ() {
+        let dir = tempdir().unwrap();
+        let edited = dir.path().join("edited.txt");
+        let reverted = dir.path().join("reverted.txt");
+        let added = dir.path().join("added.txt");
+        fs::write(&edited, "before\n").unwrap();
+        fs::write(&reverted, "same\n").unwrap();
+
+        let mut acc = TurnDiffTracker::new();
+        acc.on_patch_begin(&HashMap::from([
+            (
+                edited.clone(),
+                FileChange::Update {
+                    unified_diff: "".to_owned(),
+                    move_path: None,
+                },
+            ),
+            (
+                reverted.clone(),
+                FileChange::Update {
+                    unified_diff: "".to_owned(),
+                    move_path: None,
+                },
+            ),
+            (
+                added.clone(),
+                FileChange::Add {
+                    content: "new\n".to_string(),
+                },
+            ),
+        ]));
+        fs::write(&edited, "after\n").unwrap();
+        fs::write(&added, "new\n").unwrap();
+
+        assert_eq!(acc.changed_paths(), vec![added, edited]);
+    }
+
     #[test]
     fn accumulates_delete() {
         let dir = tempdir().unwrap();
@@ -57,6 +57,17 @@ pub(crate) enum UserNotification {
         /// The last message sent by the assistant in the turn.
         last_assistant_message: Option<String>,
     },
+
+    /// Sent by an `[[automations]]` rule with a `notify` action.
+    #[serde(rename_all = "kebab-case")]
+    Automation {
+        name: String,
+
+        /// The `type` of the event that matched, e.g. `stream_error`.
+        event: String,
+
+        message: String,
+    },
 }
 
 #[cfg(test)]
@@ -0,0 +1,259 @@
+//! Fallback copies of the search tools the agent relies on, for hosts such as
+//! bare containers and CI images that do not have them installed.
+//!
+//! When `rg` is missing from `PATH`, a prebuilt ripgrep is downloaded into
+//! `$CODEX_HOME/bin` and checked against a pinned size and BLAKE3 digest. That
+//! directory is then appended to the `PATH` of the agent's commands, so an
+//! `rg` installed on the host always wins. The pins mirror the DotSlash
+//! manifest in `codex-cli/bin/rg`, which the npm package bundles instead.
+//!
+//! Only ripgrep is covered. Every pin here is copied from a DotSlash
+//! manifest under `codex-cli/bin`, so each digest is reviewed once and shared
+//! with the npm package. There is no such manifest for `fd` yet, and a pin
+//! that nobody has checked against the real release would either fail every
+//! download or vouch for whatever was fetched when it was written. Until
+//! `codex-cli/bin/fd` exists, `fd` is not fetched; the agent's instructions
+//! and the read-only command list only rely on `rg`, which also lists files
+//! (`rg --files`).
+
+use std::collections::HashMap;
+use std::io;
+use std::path::Path;
+use std::path::PathBuf;
+
+const RIPGREP: &str = "rg";
+
+/// A release archive and the executable to take out of it.
+struct Artifact {
+    url: &'static str,
+    size: usize,
+    blake3: &'static str,
+    path_in_archive: &'static str,
+}
+
+#[cfg(all(target_os = "linux", target_arch = "x86_64"))]
+const RIPGREP_ARTIFACT: Option<Artifact> = Some(Artifact {
+    url: "https://github.com/BurntSushi/ripgrep/releases/download/14.1.1/ripgrep-14.1.1-x86_64-unknown-linux-musl.tar.gz",
+    size: 2566310,
+    blake3: "f73cca4e54d78c31f832c7f6e2c0b4db8b04fa3eaa747915727d570893dbee76",
+    path_in_archive: "ripgrep-14.1.1-x86_64-unknown-linux-musl/rg",
+});
+
+#[cfg(all(target_os = "linux", target_arch = "aarch64"))]
+const RIPGREP_ARTIFACT: Option<Artifact> = Some(Artifact {
+    url: "https://github.com/BurntSushi/ripgrep/releases/download/14.1.1/ripgrep-14.1.1-aarch64-unknown-linux-gnu.tar.gz",
+    size: 2047405,
+    blake3: "0b670b8fa0a3df2762af2fc82cc4932f684ca4c02dbd1260d4f3133fd4b2a515",
+    path_in_archive: "ripgrep-14.1.1-aarch64-unknown-linux-gnu/rg",
+});
+
+#[cfg(all(target_os = "macos", target_arch = "x86_64"))]
+const RIPGREP_ARTIFACT: Option<Artifact> = Some(Artifact {
+    url: "https://github.com/BurntSushi/ripgrep/releases/download/14.1.1/ripgrep-14.1.1-x86_64-apple-darwin.tar.gz",
+    size: 2082672,
+    blake3: "e9b862fc8da3127f92791f0ff6a799504154ca9d36c98bf3e60a81c6b1f7289e",
+    path_in_archive: "ripgrep-14.1.1-x86_64-apple-darwin/rg",
+});
+
+#[cfg(all(target_os = "macos", target_arch = "aarch64"))]
+const RIPGREP_ARTIFACT: Option<Artifact> = Some(Artifact {
+    url: "https://github.com/BurntSushi/ripgrep/releases/download/14.1.1/ripgrep-14.1.1-aarch64-apple-darwin.tar.gz",
+    size: 1787248,
+    blake3: "8d9942032585ea8ee805937634238d9aee7b210069f4703c88fbe568e26fb78a",
+    path_in_archive: "ripgrep-14.1.1-aarch64-apple-darwin/rg",
+});
+
+/// Windows releases are zip archives, which are not handled here.
+#[cfg(not(any(
+    all(
+        target_os = "linux",
+        any(target_arch = "x86_64", target_arch = "aarch64")
+    ),
+    all(
+        target_os = "macos",
+        any(target_arch = "x86_64", target_arch = "aarch64")
+    ),
+)))]
+const RIPGREP_ARTIFACT: Option<Artifact> = None;
+
+/// Directory holding the downloaded tools.
+pub(crate) fn vendored_bin_dir(codex_home: &Path) -> PathBuf {
+    codex_home.join("bin")
+}
+
+/// Makes sure `rg` can be run. Returns `None` when the host already has it
+/// (or no build is pinned for this platform), otherwise the directory holding
+/// the vendored copy, downloading it first if needed.
+pub(crate) async fn ensure_ripgrep(codex_home: &Path) -> io::Result<Option<PathBuf>> {
+    if which::which(RIPGREP).is_ok() {
+        return Ok(None);
+    }
+    let Some(artifact) = &RIPGREP_ARTIFACT else {
+        return Ok(None);
+    };
+    let bin_dir = vendored_bin_dir(codex_home);
+    if bin_dir.join(RIPGREP).is_file() {
+        return Ok(Some(bin_dir));
+    }
+
+    let response = crate::default_client::create_client()
+        .get(artifact.url)
+        .send()
+        .await
+        .and_then(reqwest::Response::error_for_status)
+        .map_err(io::Error::other)?;
+    let archive = response.bytes().await.map_err(io::Error::other)?;
+    verify(artifact, &archive)?;
+    install(&archive, artifact.path_in_archive, &bin_dir, RIPGREP).await?;
+    Ok(Some(bin_dir))
+}
+
+/// Rejects an archive whose size or BLAKE3 digest differs from the pin.
+fn verify(artifact: &Artifact, archive: &[u8]) -> io::Result<()> {
+    if archive.len() != artifact.size {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!(
+                "{} is {} bytes, expected {}",
+                artifact.url,
+                archive.len(),
+                artifact.size
+            ),
+        ));
+    }
+    let digest = blake3::hash(archive);
+    if digest.to_hex().as_str() != artifact.blake3 {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("checksum mismatch for {}", artifact.url),
+        ));
+    }
+    Ok(())
+}
+
+/// Extracts `path_in_archive` from a `.tar.gz` into `bin_dir/name`. The file
+/// is unpacked next to its destination and renamed into place, so a partial
+/// install is never picked up.
+async fn install(
+    archive: &[u8],
+    path_in_archive: &str,
+    bin_dir: &Path,
+    name: &str,
+) -> io::Result<()> {
+    tokio::fs::create_dir_all(bin_dir).await?;
+    let staging = tempfile::Builder::new()
+        .prefix(".download-")
+        .tempdir_in(bin_dir)?;
+    let archive_path = staging.path().join("archive.tar.gz");
+    tokio::fs::write(&archive_path, archive).await?;
+
+    let status = tokio::process::Command::new("tar")
+        .arg("-xzf")
+        .arg(&archive_path)
+        .arg("-C")
+        .arg(staging.path())
+        .arg(path_in_archive)
+        .status()
+        .await?;
+    if !status.success() {
+        return Err(io::Error::other(format!(
+            "failed to extract {path_in_archive}: tar exited with {status}"
+        )));
+    }
+
+    let extracted = staging.path().join(path_in_archive);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        tokio::fs::set_permissions(&extracted, std::fs::Permissions::from_mode(0o755)).await?;
+    }
+    tokio::fs::rename(&extracted, bin_dir.join(name)).await
+}
+
+/// Appends `dir` to the `PATH` in `env`, after everything already on it.
+pub(crate) fn append_to_path(env: &mut HashMap<String, String>, dir: &Path) {
+    let dir = dir.to_string_lossy();
+    match env.get_mut("PATH") {
+        Some(path) if !path.is_empty() => {
+            path.push(if cfg!(windows) { ';' } else { ':' });
+            path.push_str(&dir);
+        }
+        _ => {
+            env.insert("PATH".to_string(), dir.into_owned());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn artifact_for(archive: &[u8]) -> Artifact {
+        Artifact {
+            url: "https://example.com/tool.tar.gz",
+            size: archive.len(),
+            blake3: blake3::hash(archive).to_hex().to_string().leak(),
+            path_in_archive: "tool-1.0/tool",
+        }
+    }
+
+    #[test]
+    fn verify_checks_size_and_digest() {
+        let artifact = artifact_for(b"archive bytes");
+        assert!(verify(&artifact, b"archive bytes").is_ok());
+        assert!(verify(&artifact, b"archive bytez").is_err());
+        assert!(verify(&artifact, b"archive").is_err());
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn install_extracts_the_executable_into_bin_dir() {
+        let src = tempfile::tempdir().expect("tempdir");
+        std::fs::create_dir(src.path().join("tool-1.0")).expect("create dir");
+        std::fs::write(src.path().join("tool-1.0/tool"), "#!/bin/sh\necho ok\n").expect("write");
+        let archive_path = src.path().join("tool.tar.gz");
+        let status = std::process::Command::new("tar")
+            .arg("-czf")
+            .arg(&archive_path)
+            .arg("-C")
+            .arg(src.path())
+            .arg("tool-1.0")
+            .status()
+            .expect("run tar");
+        assert!(status.success());
+        let archive = std::fs::read(&archive_path).expect("read archive");
+
+        let codex_home = tempfile::tempdir().expect("tempdir");
+        let bin_dir = vendored_bin_dir(codex_home.path());
+        install(&archive, "tool-1.0/tool", &bin_dir, "tool")
+            .await
+            .expect("install");
+
+        let output = std::process::Command::new(bin_dir.join("tool"))
+            .output()
+            .expect("run installed tool");
+        assert_eq!(String::from_utf8_lossy(&output.stdout), "ok\n");
+        let leftovers: Vec<_> = std::fs::read_dir(&bin_dir)
+            .expect("read bin dir")
+            .map(|entry| entry.expect("entry").file_name())
+            .collect();
+        assert_eq!(leftovers, vec![std::ffi::OsString::from("tool")]);
+    }
+
+    #[test]
+    fn append_to_path_keeps_host_entries_first() {
+        let mut env = HashMap::from([("PATH".to_string(), "/usr/bin".to_string())]);
+        append_to_path(&mut env, Path::new("/codex/bin"));
+        let expected = if cfg!(windows) {
+            "/usr/bin;/codex/bin"
+        } else {
+            "/usr/bin:/codex/bin"
+        };
+        assert_eq!(env["PATH"], expected);
+
+        let mut empty = HashMap::new();
+        append_to_path(&mut empty, Path::new("/codex/bin"));
+        assert_eq!(empty["PATH"], "/codex/bin");
+    }
+}
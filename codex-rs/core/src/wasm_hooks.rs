@@ -0,0 +1,170 @@
+//! Runs hooks compiled to WebAssembly (WASI preview 1) inside the Codex
+//! process instead of spawning them.
+//!
+//! A hook whose program ends in `.wasm` is loaded with wasmtime and gets only
+//! what the hook contract needs: its arguments, the hook environment
+//! variables set on its command, and the directories listed under
+//! `[hooks.wasm]`. It cannot open sockets, start processes, or see the rest of
+//! the filesystem or environment, and the same module runs on every
+//! platform.
+//!
+//! The runtime is behind the `wasm-hooks` cargo feature; without it, `.wasm`
+//! hooks fail with an error instead of running.
+
+use std::ffi::OsStr;
+use std::io;
+use std::path::Path;
+use std::path::PathBuf;
+use std::process::Output;
+use std::time::Duration;
+
+use crate::config::WasmHooksConfig;
+
+#[cfg(feature = "wasm-hooks")]
+mod runtime;
+
+#[cfg(feature = "wasm-hooks")]
+use runtime::run_wasm_module;
+
+/// Whether `program` names a WASM hook rather than an executable.
+pub fn is_wasm_hook(program: &OsStr) -> bool {
+    Path::new(program)
+        .extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("wasm"))
+}
+
+/// Runs a hook command to completion, returning `None` if it did not finish
+/// within `timeout`. Commands whose program is a `.wasm` module run in the
+/// WASI sandbox described by `wasm`; everything else is spawned as usual.
+pub async fn run_hook_process(
+    cmd: &mut tokio::process::Command,
+    wasm: &WasmHooksConfig,
+    timeout: Duration,
+) -> Option<io::Result<Output>> {
+    let std_cmd = cmd.as_std();
+    if !is_wasm_hook(std_cmd.get_program()) {
+        return tokio::time::timeout(timeout, cmd.output()).await.ok();
+    }
+
+    let module_path = PathBuf::from(std_cmd.get_program());
+    let args: Vec<String> = std_cmd
+        .get_args()
+        .map(|arg| arg.to_string_lossy().into_owned())
+        .collect();
+    let env: Vec<(String, String)> = std_cmd
+        .get_envs()
+        .filter_map(|(key, value)| {
+            Some((
+                key.to_string_lossy().into_owned(),
+                value?.to_string_lossy().into_owned(),
+            ))
+        })
+        .collect();
+    let wasm = wasm.clone();
+    match tokio::task::spawn_blocking(move || {
+        run_wasm_module(&module_path, &args, &env, &wasm, timeout)
+    })
+    .await
+    {
+        Ok(result) => result.transpose(),
+        Err(e) => Some(Err(io::Error::other(format!("wasm hook task failed: {e}")))),
+    }
+}
+
+/// Stand-in for the wasmtime runner in builds without the `wasm-hooks`
+/// feature.
+#[cfg(not(feature = "wasm-hooks"))]
+fn run_wasm_module(
+    module_path: &Path,
+    _args: &[String],
+    _env: &[(String, String)],
+    _wasm: &WasmHooksConfig,
+    _timeout: Duration,
+) -> io::Result<Option<Output>> {
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        format!(
+            "{}: this build of Codex cannot run WASM hooks (enable the `wasm-hooks` feature)",
+            module_path.display()
+        ),
+    ))
+}
+
+#[cfg(all(test, feature = "wasm-hooks"))]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    /// A WASI command that greets on stdout, then exits with code 3 when it is
+    /// given more than one argument.
+    const ECHO_HOOK_WAT: &str = r#"
+        (module
+          (import "wasi_snapshot_preview1" "fd_write"
+            (func $fd_write (param i32 i32 i32 i32) (result i32)))
+          (import "wasi_snapshot_preview1" "proc_exit" (func $proc_exit (param i32)))
+          (import "wasi_snapshot_preview1" "args_sizes_get"
+            (func $args_sizes_get (param i32 i32) (result i32)))
+          (memory (export "memory") 1)
+          (data (i32.const 64) "hello from wasm\n")
+          (func (export "_start")
+            (i32.store (i32.const 0) (i32.const 64))
+            (i32.store (i32.const 4) (i32.const 16))
+            (drop (call $fd_write (i32.const 1) (i32.const 0) (i32.const 1) (i32.const 8)))
+            (drop (call $args_sizes_get (i32.const 16) (i32.const 20)))
+            (if (i32.gt_u (i32.load (i32.const 16)) (i32.const 2))
+              (then (call $proc_exit (i32.const 3))))))
+    "#;
+
+    /// Spins forever so the hook timeout has to interrupt it.
+    const LOOP_HOOK_WAT: &str = r#"
+        (module
+          (memory (export "memory") 1)
+          (func (export "_start") (loop (br 0))))
+    "#;
+
+    fn write_module(dir: &Path, name: &str, wat: &str) -> PathBuf {
+        let path = dir.join(name);
+        std::fs::write(&path, wat::parse_str(wat).expect("valid wat")).expect("write module");
+        path
+    }
+
+    #[tokio::test]
+    async fn wasm_hook_reports_output_and_exit_code() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let module = write_module(dir.path(), "echo.wasm", ECHO_HOOK_WAT);
+        let wasm = WasmHooksConfig::default();
+
+        let mut cmd = tokio::process::Command::new(&module);
+        cmd.arg("{}");
+        let output = run_hook_process(&mut cmd, &wasm, Duration::from_secs(5))
+            .await
+            .expect("finished")
+            .expect("ran");
+        assert_eq!(output.status.code(), Some(0));
+        assert_eq!(String::from_utf8_lossy(&output.stdout), "hello from wasm\n");
+
+        let mut cmd = tokio::process::Command::new(&module);
+        cmd.args(["--fail", "{}"]);
+        let output = run_hook_process(&mut cmd, &wasm, Duration::from_secs(5))
+            .await
+            .expect("finished")
+            .expect("ran");
+        assert_eq!(output.status.code(), Some(3));
+    }
+
+    #[tokio::test]
+    async fn wasm_hook_is_interrupted_at_its_timeout() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let module = write_module(dir.path(), "loop.wasm", LOOP_HOOK_WAT);
+
+        let mut cmd = tokio::process::Command::new(&module);
+        cmd.arg("{}");
+        let result = run_hook_process(
+            &mut cmd,
+            &WasmHooksConfig::default(),
+            Duration::from_millis(50),
+        )
+        .await;
+        assert!(result.is_none(), "expected a timeout, got {result:?}");
+    }
+}
@@ -0,0 +1,200 @@
+//! The wasmtime side of [`super`]: compiling, caching and running modules.
+//! Only built with the `wasm-hooks` feature.
+
+use std::collections::HashMap;
+use std::io;
+use std::path::Path;
+use std::path::PathBuf;
+use std::process::ExitStatus;
+use std::process::Output;
+use std::sync::LazyLock;
+use std::sync::Mutex;
+use std::time::Duration;
+use std::time::SystemTime;
+
+use wasmtime::Engine;
+use wasmtime::Linker;
+use wasmtime::Module;
+use wasmtime::Store;
+use wasmtime::StoreLimits;
+use wasmtime::StoreLimitsBuilder;
+use wasmtime::Trap;
+use wasmtime_wasi::DirPerms;
+use wasmtime_wasi::FilePerms;
+use wasmtime_wasi::I32Exit;
+use wasmtime_wasi::WasiCtxBuilder;
+use wasmtime_wasi::pipe::MemoryInputPipe;
+use wasmtime_wasi::pipe::MemoryOutputPipe;
+use wasmtime_wasi::preview1::WasiP1Ctx;
+
+use crate::config::WasmHooksConfig;
+
+/// How often the engine's epoch advances; hook timeouts are rounded up to it.
+const EPOCH_TICK: Duration = Duration::from_millis(10);
+
+/// Largest linear memory a hook may grow to.
+const MAX_MEMORY_BYTES: usize = 256 * 1024 * 1024;
+
+/// Output beyond this many bytes per stream makes the hook's writes fail.
+const MAX_OUTPUT_BYTES: usize = 1024 * 1024;
+
+/// Shared by every WASM hook so compiled modules can be cached. A background
+/// thread advances its epoch, which is what interrupts hooks that run past
+/// their timeout.
+static ENGINE: LazyLock<Result<Engine, String>> = LazyLock::new(|| {
+    let mut config = wasmtime::Config::new();
+    config.epoch_interruption(true);
+    let engine = Engine::new(&config).map_err(|e| format!("{e:#}"))?;
+    let ticker = engine.clone();
+    std::thread::Builder::new()
+        .name("codex-wasm-hook-epoch".to_string())
+        .spawn(move || {
+            loop {
+                std::thread::sleep(EPOCH_TICK);
+                ticker.increment_epoch();
+            }
+        })
+        .map_err(|e| e.to_string())?;
+    Ok(engine)
+});
+
+/// Compiled modules keyed by path, recompiled when the file changes.
+static MODULES: LazyLock<Mutex<HashMap<PathBuf, (SystemTime, Module)>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+struct HookState {
+    wasi: WasiP1Ctx,
+    limits: StoreLimits,
+}
+
+/// Instantiates the module and runs its `_start` export. `Ok(None)` means
+/// the hook hit its deadline.
+pub(super) fn run_wasm_module(
+    module_path: &Path,
+    args: &[String],
+    env: &[(String, String)],
+    wasm: &WasmHooksConfig,
+    timeout: Duration,
+) -> io::Result<Option<Output>> {
+    let engine = ENGINE.as_ref().map_err(|e| io::Error::other(e.clone()))?;
+    let module = load_module(engine, module_path)?;
+
+    let stdout = MemoryOutputPipe::new(MAX_OUTPUT_BYTES);
+    let stderr = MemoryOutputPipe::new(MAX_OUTPUT_BYTES);
+    let mut builder = WasiCtxBuilder::new();
+    builder
+        .arg(module_path.to_string_lossy())
+        .args(args)
+        .stdin(MemoryInputPipe::new(Vec::new()))
+        .stdout(stdout.clone())
+        .stderr(stderr.clone());
+    for (key, value) in env {
+        builder.env(key, value);
+    }
+    for dir in &wasm.write_dirs {
+        preopen(&mut builder, dir, DirPerms::all(), FilePerms::all())?;
+    }
+    for dir in wasm
+        .read_dirs
+        .iter()
+        .filter(|dir| !wasm.write_dirs.contains(dir))
+    {
+        preopen(&mut builder, dir, DirPerms::READ, FilePerms::READ)?;
+    }
+
+    let mut store = Store::new(
+        engine,
+        HookState {
+            wasi: builder.build_p1(),
+            limits: StoreLimitsBuilder::new()
+                .memory_size(MAX_MEMORY_BYTES)
+                .build(),
+        },
+    );
+    store.limiter(|state| &mut state.limits);
+    let ticks = timeout.as_millis().div_ceil(EPOCH_TICK.as_millis()) as u64;
+    store.set_epoch_deadline(ticks.max(1));
+
+    let exit_code = match call_start(engine, &mut store, &module) {
+        Ok(()) => 0,
+        Err(e) => {
+            if let Some(exit) = e.downcast_ref::<I32Exit>() {
+                exit.0
+            } else if e.downcast_ref::<Trap>() == Some(&Trap::Interrupt) {
+                return Ok(None);
+            } else {
+                return Err(wasm_error(e));
+            }
+        }
+    };
+
+    Ok(Some(Output {
+        status: exit_status(exit_code),
+        stdout: stdout.contents().to_vec(),
+        stderr: stderr.contents().to_vec(),
+    }))
+}
+
+/// Links WASI into the module and calls its default export (`_start`).
+fn call_start(
+    engine: &Engine,
+    store: &mut Store<HookState>,
+    module: &Module,
+) -> wasmtime::Result<()> {
+    let mut linker: Linker<HookState> = Linker::new(engine);
+    wasmtime_wasi::preview1::add_to_linker_sync(&mut linker, |state| &mut state.wasi)?;
+    linker.module(&mut *store, "", module)?;
+    let start = linker
+        .get_default(&mut *store, "")?
+        .typed::<(), ()>(&*store)?;
+    start.call(&mut *store, ())
+}
+
+fn load_module(engine: &Engine, path: &Path) -> io::Result<Module> {
+    let modified = std::fs::metadata(path)?.modified()?;
+    let mut modules = MODULES
+        .lock()
+        .map_err(|_| io::Error::other("wasm module cache poisoned"))?;
+    if let Some((cached_at, module)) = modules.get(path)
+        && *cached_at == modified
+    {
+        return Ok(module.clone());
+    }
+    let module = Module::from_file(engine, path).map_err(wasm_error)?;
+    modules.insert(path.to_path_buf(), (modified, module.clone()));
+    Ok(module)
+}
+
+/// Mounts `dir` at the same path inside the guest so the absolute paths in
+/// hook payloads (`cwd`, `git_root`, …) resolve unchanged. Missing
+/// directories are skipped.
+fn preopen(
+    builder: &mut WasiCtxBuilder,
+    dir: &Path,
+    dir_perms: DirPerms,
+    file_perms: FilePerms,
+) -> io::Result<()> {
+    if !dir.is_dir() {
+        return Ok(());
+    }
+    builder
+        .preopened_dir(dir, dir.to_string_lossy(), dir_perms, file_perms)
+        .map_err(wasm_error)?;
+    Ok(())
+}
+
+fn wasm_error(e: wasmtime::Error) -> io::Error {
+    io::Error::other(format!("wasm hook failed: {e:#}"))
+}
+
+#[cfg(unix)]
+fn exit_status(code: i32) -> ExitStatus {
+    use std::os::unix::process::ExitStatusExt;
+    ExitStatus::from_raw((code & 0xff) << 8)
+}
+
+#[cfg(windows)]
+fn exit_status(code: i32) -> ExitStatus {
+    use std::os::windows::process::ExitStatusExt;
+    ExitStatus::from_raw(code as u32)
+}
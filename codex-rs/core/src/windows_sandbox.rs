@@ -0,0 +1,106 @@
+use crate::config_types::ResourceLimits;
+use crate::exec::SandboxType;
+use crate::protocol::SandboxPolicy;
+use crate::safety::get_platform_sandbox;
+use crate::spawn::CODEX_SANDBOX_ENV_VAR;
+use crate::spawn::StdioPolicy;
+use crate::spawn::spawn_child_async;
+use std::collections::HashMap;
+use std::path::Path;
+use std::path::PathBuf;
+use tokio::process::Child;
+
+/// Passed as the first argument to the Codex executable to run it as the
+/// Windows sandbox helper (see the `codex-windows-sandbox` crate). Windows
+/// has no equivalent of the `argv[0]` alias used on Linux.
+pub const CODEX_WINDOWS_SANDBOX_ARG1: &str = "--codex-run-as-windows-sandbox";
+
+/// Spawn a shell tool command under the Windows sandbox helper, which runs it
+/// with a write-restricted token that can only write to the policy's
+/// writable roots.
+///
+/// The helper is the current executable, re-invoked with
+/// [`CODEX_WINDOWS_SANDBOX_ARG1`], so every binary that uses this must go
+/// through `codex_arg0::arg0_dispatch_or_else`.
+pub async fn spawn_command_under_windows_sandbox(
+    command: Vec<String>,
+    command_cwd: PathBuf,
+    sandbox_policy: &SandboxPolicy,
+    sandbox_policy_cwd: &Path,
+    stdio_policy: StdioPolicy,
+    mut env: HashMap<String, String>,
+    resource_limits: ResourceLimits,
+) -> std::io::Result<Child> {
+    let (program, args) =
+        windows_sandbox_command(command, sandbox_policy, sandbox_policy_cwd, &mut env)?;
+    spawn_child_async(
+        program,
+        args,
+        None,
+        command_cwd,
+        sandbox_policy,
+        stdio_policy,
+        env,
+        resource_limits,
+    )
+    .await
+}
+
+/// Warning for the user when `sandbox_policy` restricts the network but
+/// commands run under the Windows sandbox, whose token does not.
+pub(crate) fn unenforced_network_warning(sandbox_policy: &SandboxPolicy) -> Option<String> {
+    let windows_sandbox = get_platform_sandbox() == Some(SandboxType::WindowsRestrictedToken);
+    (windows_sandbox && !sandbox_policy.has_full_network_access()).then(|| {
+        "The Windows sandbox does not block network access: sandboxed commands can still \
+         reach the network even though the sandbox policy disables it."
+            .to_string()
+    })
+}
+
+/// Resolves the helper program and its arguments, and prepares `env` for
+/// it. The token does not restrict the network; the session warns about
+/// that through [`unenforced_network_warning`].
+pub(crate) fn windows_sandbox_command(
+    command: Vec<String>,
+    sandbox_policy: &SandboxPolicy,
+    sandbox_policy_cwd: &Path,
+    env: &mut HashMap<String, String>,
+) -> std::io::Result<(PathBuf, Vec<String>)> {
+    env.insert(CODEX_SANDBOX_ENV_VAR.to_string(), "windows".to_string());
+
+    let program = std::env::current_exe()?;
+    let mut args = vec![CODEX_WINDOWS_SANDBOX_ARG1.to_string()];
+    args.extend(create_windows_sandbox_command_args(
+        command,
+        sandbox_policy,
+        sandbox_policy_cwd,
+    ));
+    Ok((program, args))
+}
+
+/// Converts the sandbox policy into the CLI invocation for the helper.
+fn create_windows_sandbox_command_args(
+    command: Vec<String>,
+    sandbox_policy: &SandboxPolicy,
+    sandbox_policy_cwd: &Path,
+) -> Vec<String> {
+    #[expect(clippy::expect_used)]
+    let sandbox_policy_cwd = sandbox_policy_cwd
+        .to_str()
+        .expect("cwd must be valid UTF-8")
+        .to_string();
+
+    #[expect(clippy::expect_used)]
+    let sandbox_policy_json =
+        serde_json::to_string(sandbox_policy).expect("Failed to serialize SandboxPolicy to JSON");
+
+    let mut windows_cmd = vec![
+        sandbox_policy_cwd,
+        sandbox_policy_json,
+        // Separator so that command arguments starting with `-` are not parsed as
+        // options of the helper itself.
+        "--".to_string(),
+    ];
+    windows_cmd.extend(command);
+    windows_cmd
+}
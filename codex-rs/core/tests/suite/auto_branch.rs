@@ -0,0 +1,135 @@
+#![cfg(not(target_os = "windows"))]
+
+use std::path::Path;
+use std::process::Command;
+use std::time::Duration;
+
+use codex_core::protocol::AskForApproval;
+use codex_core::protocol::EventMsg;
+use codex_core::protocol::InputItem;
+use codex_core::protocol::Op;
+use codex_core::protocol::SandboxPolicy;
+use codex_protocol::config_types::ReasoningSummary;
+use core_test_support::non_sandbox_test;
+use core_test_support::responses;
+use core_test_support::test_codex::TestCodex;
+use core_test_support::test_codex::test_codex;
+use core_test_support::wait_for_event;
+use pretty_assertions::assert_eq;
+use responses::ev_assistant_message;
+use responses::ev_completed;
+use responses::sse;
+use responses::start_mock_server;
+use wiremock::Mock;
+use wiremock::matchers::method;
+use wiremock::matchers::path;
+
+fn git(cwd: &Path, args: &[&str]) -> anyhow::Result<String> {
+    let output = Command::new("git")
+        .args(["-c", "user.name=Test", "-c", "user.email=test@example.com"])
+        .args(args)
+        .current_dir(cwd)
+        .output()?;
+    anyhow::ensure!(output.status.success(), "git {args:?} failed: {output:?}");
+    Ok(String::from_utf8(output.stdout)?.trim().to_string())
+}
+
+fn init_clean_repo(cwd: &Path) -> anyhow::Result<()> {
+    git(cwd, &["init", "--quiet", "--initial-branch=main"])?;
+    std::fs::write(cwd.join("notes.txt"), "committed\n")?;
+    git(cwd, &["add", "notes.txt"])?;
+    git(cwd, &["commit", "--quiet", "-m", "initial"])?;
+    Ok(())
+}
+
+fn user_turn(cwd: &Path, text: &str) -> Op {
+    Op::UserTurn {
+        items: vec![InputItem::Text { text: text.into() }],
+        cwd: cwd.to_path_buf(),
+        approval_policy: AskForApproval::Never,
+        sandbox_policy: SandboxPolicy::DangerFullAccess,
+        model: "gpt-5".into(),
+        effort: None,
+        summary: ReasoningSummary::Auto,
+        final_output_json_schema: None,
+        max_turns: None,
+        max_total_tokens: None,
+        max_duration: None,
+    }
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn task_in_clean_repo_starts_on_work_branch() -> anyhow::Result<()> {
+    non_sandbox_test!(result);
+
+    let server = start_mock_server().await;
+    responses::mount_sse_once(
+        &server,
+        wiremock::matchers::any(),
+        sse(vec![ev_assistant_message("m1", "done"), ev_completed("r1")]),
+    )
+    .await;
+    let TestCodex { codex, cwd, .. } = test_codex()
+        .with_config(|config| config.git_auto_branch = true)
+        .build(&server)
+        .await?;
+    init_clean_repo(cwd.path())?;
+
+    codex
+        .submit(user_turn(cwd.path(), "Fix the login bug!"))
+        .await?;
+
+    let EventMsg::TaskStarted(started) =
+        wait_for_event(&codex, |ev| matches!(ev, EventMsg::TaskStarted(_))).await
+    else {
+        unreachable!()
+    };
+    wait_for_event(&codex, |ev| matches!(ev, EventMsg::TaskComplete(_))).await;
+
+    assert_eq!(started.branch.as_deref(), Some("codex/fix-the-login-bug"));
+    assert_eq!(
+        git(cwd.path(), &["branch", "--show-current"])?,
+        "codex/fix-the-login-bug"
+    );
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn aborted_task_switches_back_to_previous_branch() -> anyhow::Result<()> {
+    non_sandbox_test!(result);
+
+    let server = start_mock_server().await;
+    Mock::given(method("POST"))
+        .and(path("/v1/responses"))
+        .respond_with(
+            responses::sse_response(sse(vec![ev_completed("r1")]))
+                .set_delay(Duration::from_secs(60)),
+        )
+        .mount(&server)
+        .await;
+    let TestCodex { codex, cwd, .. } = test_codex()
+        .with_config(|config| config.git_auto_branch = true)
+        .build(&server)
+        .await?;
+    init_clean_repo(cwd.path())?;
+
+    codex.submit(user_turn(cwd.path(), "Add tests")).await?;
+    let EventMsg::TaskStarted(started) =
+        wait_for_event(&codex, |ev| matches!(ev, EventMsg::TaskStarted(_))).await
+    else {
+        unreachable!()
+    };
+    assert_eq!(started.branch.as_deref(), Some("codex/add-tests"));
+
+    codex.submit(Op::Interrupt).await?;
+    wait_for_event(&codex, |ev| matches!(ev, EventMsg::TurnAborted(_))).await;
+
+    assert_eq!(git(cwd.path(), &["branch", "--show-current"])?, "main");
+    assert_eq!(
+        git(cwd.path(), &["branch", "--list", "codex/add-tests"])?,
+        "codex/add-tests"
+    );
+
+    Ok(())
+}
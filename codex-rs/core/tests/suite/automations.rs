@@ -0,0 +1,136 @@
+#![cfg(not(target_os = "windows"))]
+
+use codex_core::automations::Automation;
+use codex_core::automations::AutomationAction;
+use codex_core::automations::FieldFilter;
+use codex_core::protocol::AskForApproval;
+use codex_core::protocol::EventMsg;
+use codex_core::protocol::InputItem;
+use codex_core::protocol::Op;
+use codex_core::protocol::SandboxPolicy;
+use codex_protocol::config_types::ReasoningSummary;
+use core_test_support::non_sandbox_test;
+use core_test_support::responses;
+use core_test_support::test_codex::TestCodex;
+use core_test_support::test_codex::test_codex;
+use core_test_support::wait_for_event;
+use responses::ev_assistant_message;
+use responses::ev_completed;
+use responses::ev_function_call;
+use responses::sse;
+use responses::start_mock_server;
+use tempfile::TempDir;
+use tokio::time::Duration;
+use tokio::time::sleep;
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn automations_approve_requests_and_run_commands() -> anyhow::Result<()> {
+    non_sandbox_test!(result);
+
+    let server = start_mock_server().await;
+    let out_dir = TempDir::new()?;
+    let approved_output = out_dir.path().join("approved.txt");
+    let event_log = out_dir.path().join("events.log");
+    let arguments = serde_json::to_string(&serde_json::json!({
+        "command": ["/bin/bash", "-c", format!("echo ran > {}", approved_output.display())],
+    }))?;
+    responses::mount_sse_once(
+        &server,
+        |req: &wiremock::Request| {
+            !String::from_utf8_lossy(&req.body).contains("function_call_output")
+        },
+        sse(vec![
+            ev_function_call("call-1", "shell", &arguments),
+            ev_completed("r1"),
+        ]),
+    )
+    .await;
+    responses::mount_sse_once(
+        &server,
+        |req: &wiremock::Request| {
+            String::from_utf8_lossy(&req.body).contains("function_call_output")
+        },
+        sse(vec![ev_assistant_message("m2", "done"), ev_completed("r2")]),
+    )
+    .await;
+
+    let automations = vec![
+        Automation {
+            name: "approve-echo".to_string(),
+            on: "exec_approval_request".to_string(),
+            when: vec![(
+                "command".to_string(),
+                FieldFilter::Exact(format!(
+                    "/bin/bash -c echo ran > {}",
+                    approved_output.display()
+                )),
+            )],
+            once: false,
+            action: AutomationAction::Approve,
+        },
+        Automation {
+            name: "log-completion".to_string(),
+            on: "task_complete".to_string(),
+            when: Vec::new(),
+            once: false,
+            action: AutomationAction::Run {
+                argv: vec![
+                    "/bin/bash".to_string(),
+                    "-c".to_string(),
+                    format!("echo \"$CODEX_EVENT\" >> {}", event_log.display()),
+                ],
+                timeout_ms: 5_000,
+            },
+        },
+    ];
+    let TestCodex { codex, cwd, .. } = test_codex()
+        .with_config(move |config| config.automations = automations)
+        .build(&server)
+        .await?;
+
+    codex
+        .submit(Op::UserTurn {
+            items: vec![InputItem::Text {
+                text: "please run".into(),
+            }],
+            cwd: cwd.path().to_path_buf(),
+            approval_policy: AskForApproval::UnlessTrusted,
+            sandbox_policy: SandboxPolicy::DangerFullAccess,
+            model: "gpt-5".into(),
+            effort: None,
+            summary: ReasoningSummary::Auto,
+            final_output_json_schema: None,
+            max_turns: None,
+            max_total_tokens: None,
+            max_duration: None,
+        })
+        .await?;
+
+    let EventMsg::BackgroundEvent(notice) = wait_for_event(&codex, |ev| {
+        matches!(
+            ev,
+            EventMsg::ExecApprovalRequest(_) | EventMsg::BackgroundEvent(_)
+        )
+    })
+    .await
+    else {
+        panic!("approval request reached the client instead of being approved");
+    };
+    assert!(
+        notice
+            .message
+            .starts_with("Automation `approve-echo` approved"),
+        "unexpected notice: {}",
+        notice.message
+    );
+    wait_for_event(&codex, |ev| matches!(ev, EventMsg::TaskComplete(_))).await;
+    assert!(approved_output.exists(), "approved command should run");
+
+    for _ in 0..50 {
+        if std::fs::read_to_string(&event_log).is_ok_and(|log| log.contains("task_complete")) {
+            return Ok(());
+        }
+        sleep(Duration::from_millis(100)).await;
+    }
+    panic!("run action did not record the task_complete event");
+}
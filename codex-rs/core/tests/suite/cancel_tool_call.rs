@@ -0,0 +1,122 @@
+#![cfg(not(target_os = "windows"))]
+
+use codex_core::protocol::AskForApproval;
+use codex_core::protocol::EventMsg;
+use codex_core::protocol::InputItem;
+use codex_core::protocol::Op;
+use codex_core::protocol::SandboxPolicy;
+use core_test_support::non_sandbox_test;
+use core_test_support::responses;
+use core_test_support::test_codex::TestCodex;
+use core_test_support::test_codex::test_codex;
+use core_test_support::wait_for_event;
+use pretty_assertions::assert_eq;
+use responses::ev_assistant_message;
+use responses::ev_completed;
+use responses::ev_function_call;
+use responses::sse;
+use responses::start_mock_server;
+use serde_json::Value;
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn cancelled_tool_call_is_reported_and_the_turn_continues() -> anyhow::Result<()> {
+    non_sandbox_test!(result);
+
+    let server = start_mock_server().await;
+
+    let args = serde_json::json!({
+        "command": ["/bin/bash", "-c", "sleep 30"],
+        "timeout_ms": 60_000,
+    })
+    .to_string();
+    responses::mount_sse_once(
+        &server,
+        |req: &wiremock::Request| {
+            !String::from_utf8_lossy(&req.body).contains("\"function_call_output\"")
+        },
+        sse(vec![
+            ev_function_call("call-sleep", "shell", &args),
+            ev_completed("r1"),
+        ]),
+    )
+    .await;
+    responses::mount_sse_once(
+        &server,
+        |req: &wiremock::Request| {
+            String::from_utf8_lossy(&req.body).contains("\"function_call_output\"")
+        },
+        sse(vec![
+            ev_assistant_message("m", "ok, skipping it"),
+            ev_completed("r2"),
+        ]),
+    )
+    .await;
+
+    let TestCodex { codex, .. } = test_codex()
+        .with_config(|config| {
+            config.approval_policy = AskForApproval::Never;
+            config.sandbox_policy = SandboxPolicy::DangerFullAccess;
+        })
+        .build(&server)
+        .await?;
+
+    codex
+        .submit(Op::UserInput {
+            items: vec![InputItem::Text {
+                text: "wait a while".into(),
+            }],
+        })
+        .await?;
+    wait_for_event(&codex, |ev| matches!(ev, EventMsg::ExecCommandBegin(_))).await;
+
+    codex
+        .submit(Op::CancelToolCall {
+            call_id: "call-sleep".into(),
+        })
+        .await?;
+    let EventMsg::ToolCallCancelled(cancelled) =
+        wait_for_event(&codex, |ev| matches!(ev, EventMsg::ToolCallCancelled(_))).await
+    else {
+        unreachable!()
+    };
+    assert!(cancelled.cancelled);
+
+    let mut aborted = false;
+    loop {
+        match wait_for_event(&codex, |_| true).await {
+            EventMsg::TurnAborted(_) => aborted = true,
+            EventMsg::TaskComplete(_) => break,
+            _ => {}
+        }
+    }
+    assert!(!aborted, "cancelling one call must not abort the turn");
+
+    let requests = server.received_requests().await.unwrap_or_default();
+    assert_eq!(requests.len(), 2);
+    let body = requests[1].body_json::<Value>()?;
+    let output = body["input"]
+        .as_array()
+        .and_then(|items| {
+            items.iter().find(|item| {
+                item["call_id"] == "call-sleep" && item["type"] == "function_call_output"
+            })
+        })
+        .and_then(|item| item["output"].as_str())
+        .unwrap_or_default();
+    assert_eq!(output, "aborted by user");
+
+    // The call is no longer running, so a second cancel finds nothing.
+    codex
+        .submit(Op::CancelToolCall {
+            call_id: "call-sleep".into(),
+        })
+        .await?;
+    let EventMsg::ToolCallCancelled(cancelled) =
+        wait_for_event(&codex, |ev| matches!(ev, EventMsg::ToolCallCancelled(_))).await
+    else {
+        unreachable!()
+    };
+    assert!(!cancelled.cancelled);
+
+    Ok(())
+}
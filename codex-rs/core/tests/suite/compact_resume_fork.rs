@@ -762,6 +762,10 @@ async fn start_test_conversation(
     let home = TempDir::new().expect("create temp dir");
     let mut config = load_default_config_for_test(&home);
     config.model_provider = model_provider;
+    // The expected histories are exact; leave out the random scratch dir
+    // path and the per-turn local time.
+    config.scratch_dir = false;
+    config.locale_context = false;
 
     let manager = ConversationManager::with_auth(CodexAuth::from_api_key("dummy"));
     let NewConversation { conversation, .. } = manager
@@ -0,0 +1,114 @@
+use codex_core::protocol::CompletionConfidence;
+use codex_core::protocol::EventMsg;
+use codex_core::protocol::InputItem;
+use codex_core::protocol::Op;
+use codex_core::protocol::TaskCompleteEvent;
+use codex_core::protocol::VerificationStatus;
+use core_test_support::responses;
+use core_test_support::test_codex::TestCodex;
+use core_test_support::test_codex::test_codex;
+use core_test_support::wait_for_event;
+use pretty_assertions::assert_eq;
+use responses::ev_assistant_message;
+use responses::ev_completed;
+use responses::sse;
+use responses::start_mock_server;
+use serde_json::Value;
+use wiremock::MockServer;
+
+fn is_assessment_request(req: &wiremock::Request) -> bool {
+    String::from_utf8_lossy(&req.body).contains("codex_output_schema")
+}
+
+async fn mount_turn(server: &MockServer) {
+    responses::mount_sse_once(
+        server,
+        |req: &wiremock::Request| !is_assessment_request(req),
+        sse(vec![
+            ev_assistant_message("m1", "Implemented the fix."),
+            ev_completed("r1"),
+        ]),
+    )
+    .await;
+}
+
+async fn run_turn(codex: &codex_core::CodexConversation) -> anyhow::Result<TaskCompleteEvent> {
+    codex
+        .submit(Op::UserInput {
+            items: vec![InputItem::Text {
+                text: "fix the bug".into(),
+            }],
+        })
+        .await?;
+    match wait_for_event(codex, |ev| matches!(ev, EventMsg::TaskComplete(_))).await {
+        EventMsg::TaskComplete(ev) => Ok(ev),
+        _ => unreachable!(),
+    }
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn task_complete_includes_assessment_when_enabled() -> anyhow::Result<()> {
+    let server = start_mock_server().await;
+    mount_turn(&server).await;
+    let assessment = serde_json::json!({
+        "confidence": "high",
+        "verification": "unverified",
+        "follow_ups": [
+            {"label": "Run the tests", "prompt": "Run the test suite."}
+        ],
+    })
+    .to_string();
+    responses::mount_sse_once(
+        &server,
+        is_assessment_request,
+        sse(vec![
+            ev_assistant_message("m2", &assessment),
+            ev_completed("r2"),
+        ]),
+    )
+    .await;
+
+    let TestCodex { codex, .. } = test_codex()
+        .with_config(|config| config.task_completion_assessment = true)
+        .build(&server)
+        .await?;
+
+    let complete = run_turn(&codex).await?;
+    assert_eq!(
+        complete.last_agent_message.as_deref(),
+        Some("Implemented the fix.")
+    );
+    let assessment = complete.assessment.expect("assessment");
+    assert_eq!(assessment.confidence, CompletionConfidence::High);
+    assert_eq!(assessment.verification, VerificationStatus::Unverified);
+    assert_eq!(assessment.follow_ups.len(), 1);
+    assert_eq!(assessment.follow_ups[0].prompt, "Run the test suite.");
+
+    let requests = server.received_requests().await.unwrap_or_default();
+    assert_eq!(requests.len(), 2);
+    let body: Value = requests[1].body_json()?;
+    assert_eq!(body["text"]["format"]["type"], "json_schema");
+    assert!(
+        body["input"].to_string().contains("Assess your own work"),
+        "assessment prompt missing from request"
+    );
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn task_complete_has_no_assessment_by_default() -> anyhow::Result<()> {
+    let server = start_mock_server().await;
+    mount_turn(&server).await;
+
+    let TestCodex { codex, .. } = test_codex().build(&server).await?;
+
+    let complete = run_turn(&codex).await?;
+    assert_eq!(complete.assessment, None);
+    assert_eq!(
+        server.received_requests().await.unwrap_or_default().len(),
+        1
+    );
+
+    Ok(())
+}
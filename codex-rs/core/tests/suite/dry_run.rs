@@ -0,0 +1,121 @@
+#![cfg(not(target_os = "windows"))]
+
+use codex_core::protocol::AskForApproval;
+use codex_core::protocol::EventMsg;
+use codex_core::protocol::InputItem;
+use codex_core::protocol::Op;
+use codex_core::protocol::SandboxPolicy;
+use core_test_support::non_sandbox_test;
+use core_test_support::responses;
+use core_test_support::test_codex::TestCodex;
+use core_test_support::test_codex::test_codex;
+use core_test_support::wait_for_event;
+use pretty_assertions::assert_eq;
+use responses::ev_assistant_message;
+use responses::ev_completed;
+use responses::ev_function_call;
+use responses::sse;
+use responses::start_mock_server;
+use serde_json::Value;
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn dry_run_reports_commands_without_running_them() -> anyhow::Result<()> {
+    non_sandbox_test!(result);
+
+    let server = start_mock_server().await;
+
+    let args = serde_json::json!({
+        "command": ["/bin/bash", "-c", "touch created-by-agent"],
+    })
+    .to_string();
+    responses::mount_sse_once(
+        &server,
+        |req: &wiremock::Request| {
+            !String::from_utf8_lossy(&req.body).contains("\"function_call_output\"")
+        },
+        sse(vec![
+            ev_function_call("call-1", "shell", &args),
+            ev_completed("r1"),
+        ]),
+    )
+    .await;
+    responses::mount_sse_once(
+        &server,
+        |req: &wiremock::Request| {
+            String::from_utf8_lossy(&req.body).contains("\"function_call_output\"")
+        },
+        sse(vec![ev_assistant_message("m", "done"), ev_completed("r2")]),
+    )
+    .await;
+
+    let TestCodex { codex, cwd, .. } = test_codex()
+        .with_config(|config| {
+            config.approval_policy = AskForApproval::Never;
+            config.sandbox_policy = SandboxPolicy::DangerFullAccess;
+        })
+        .build(&server)
+        .await?;
+
+    codex
+        .submit(Op::OverrideTurnContext {
+            cwd: None,
+            approval_policy: None,
+            sandbox_policy: None,
+            sandbox_preset: None,
+            model: None,
+            effort: None,
+            summary: None,
+            dry_run: Some(true),
+        })
+        .await?;
+    codex
+        .submit(Op::UserInput {
+            items: vec![InputItem::Text {
+                text: "create the file".into(),
+            }],
+        })
+        .await?;
+
+    let mut dry_runs = Vec::new();
+    let mut exec_begins = 0;
+    loop {
+        match wait_for_event(&codex, |_| true).await {
+            EventMsg::ExecCommandDryRun(ev) => dry_runs.push(ev.command),
+            EventMsg::ExecCommandBegin(_) => exec_begins += 1,
+            EventMsg::TaskComplete(_) => break,
+            _ => {}
+        }
+    }
+    assert_eq!(
+        dry_runs,
+        vec![vec![
+            "/bin/bash".to_string(),
+            "-c".to_string(),
+            "touch created-by-agent".to_string(),
+        ]]
+    );
+    assert_eq!(exec_begins, 0);
+    assert!(!cwd.path().join("created-by-agent").exists());
+
+    let requests = server.received_requests().await.unwrap_or_default();
+    let last = requests
+        .last()
+        .map(wiremock::Request::body_json::<Value>)
+        .transpose()?;
+    let output = last
+        .as_ref()
+        .and_then(|body| body["input"].as_array())
+        .and_then(|items| {
+            items
+                .iter()
+                .find(|item| item["call_id"] == "call-1" && item["type"] == "function_call_output")
+        })
+        .and_then(|item| item["output"].as_str())
+        .unwrap_or_default();
+    assert!(
+        output.starts_with("dry run: `touch created-by-agent` was not executed"),
+        "unexpected output: {output}"
+    );
+
+    Ok(())
+}
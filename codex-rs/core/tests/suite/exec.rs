@@ -3,6 +3,8 @@
 use std::collections::HashMap;
 use std::string::ToString;
 
+use codex_core::config_types::ResourceLimits;
+use codex_core::config_types::SandboxExtras;
 use codex_core::exec::ExecParams;
 use codex_core::exec::ExecToolCallOutput;
 use codex_core::exec::SandboxType;
@@ -36,11 +38,24 @@ async fn run_test_cmd(tmp: TempDir, cmd: Vec<&str>) -> Result<ExecToolCallOutput
         env: HashMap::new(),
         with_escalated_permissions: None,
         justification: None,
+        tty: false,
+        resource_limits: ResourceLimits::default(),
+        sandbox_extras: SandboxExtras::default(),
     };
 
     let policy = SandboxPolicy::new_read_only_policy();
 
-    process_exec_tool_call(params, sandbox_type, &policy, tmp.path(), &None, None).await
+    process_exec_tool_call(
+        params,
+        sandbox_type,
+        &policy,
+        tmp.path(),
+        &None,
+        None,
+        None,
+        None,
+    )
+    .await
 }
 
 /// Command succeeds with exit code 0 normally
@@ -5,6 +5,9 @@ use std::path::PathBuf;
 use std::time::Duration;
 
 use async_channel::Receiver;
+use codex_core::config_types::OutputStreaming;
+use codex_core::config_types::ResourceLimits;
+use codex_core::config_types::SandboxExtras;
 use codex_core::error::CodexErr;
 use codex_core::error::SandboxErr;
 use codex_core::exec::ExecParams;
@@ -16,6 +19,7 @@ use codex_core::protocol::EventMsg;
 use codex_core::protocol::ExecCommandOutputDeltaEvent;
 use codex_core::protocol::ExecOutputStream;
 use codex_core::protocol::SandboxPolicy;
+use tokio_util::sync::CancellationToken;
 
 fn collect_stdout_events(rx: Receiver<Event>) -> Vec<u8> {
     let mut out = Vec::new();
@@ -40,6 +44,7 @@ async fn test_exec_stdout_stream_events_echo() {
         sub_id: "test-sub".to_string(),
         call_id: "call-1".to_string(),
         tx_event: tx,
+        streaming: OutputStreaming::default(),
     };
 
     let cmd = vec![
@@ -57,6 +62,9 @@ async fn test_exec_stdout_stream_events_echo() {
         env: HashMap::new(),
         with_escalated_permissions: None,
         justification: None,
+        tty: false,
+        resource_limits: ResourceLimits::default(),
+        sandbox_extras: SandboxExtras::default(),
     };
 
     let policy = SandboxPolicy::new_read_only_policy();
@@ -68,6 +76,8 @@ async fn test_exec_stdout_stream_events_echo() {
         cwd.as_path(),
         &None,
         Some(stdout_stream),
+        None,
+        None,
     )
     .await;
 
@@ -92,6 +102,7 @@ async fn test_exec_stderr_stream_events_echo() {
         sub_id: "test-sub".to_string(),
         call_id: "call-2".to_string(),
         tx_event: tx,
+        streaming: OutputStreaming::default(),
     };
 
     let cmd = vec![
@@ -109,6 +120,9 @@ async fn test_exec_stderr_stream_events_echo() {
         env: HashMap::new(),
         with_escalated_permissions: None,
         justification: None,
+        tty: false,
+        resource_limits: ResourceLimits::default(),
+        sandbox_extras: SandboxExtras::default(),
     };
 
     let policy = SandboxPolicy::new_read_only_policy();
@@ -120,6 +134,8 @@ async fn test_exec_stderr_stream_events_echo() {
         cwd.as_path(),
         &None,
         Some(stdout_stream),
+        None,
+        None,
     )
     .await;
 
@@ -164,6 +180,9 @@ async fn test_aggregated_output_interleaves_in_order() {
         env: HashMap::new(),
         with_escalated_permissions: None,
         justification: None,
+        tty: false,
+        resource_limits: ResourceLimits::default(),
+        sandbox_extras: SandboxExtras::default(),
     };
 
     let policy = SandboxPolicy::new_read_only_policy();
@@ -175,6 +194,8 @@ async fn test_aggregated_output_interleaves_in_order() {
         cwd.as_path(),
         &None,
         None,
+        None,
+        None,
     )
     .await
     .expect("process_exec_tool_call");
@@ -202,6 +223,9 @@ async fn test_exec_timeout_returns_partial_output() {
         env: HashMap::new(),
         with_escalated_permissions: None,
         justification: None,
+        tty: false,
+        resource_limits: ResourceLimits::default(),
+        sandbox_extras: SandboxExtras::default(),
     };
 
     let policy = SandboxPolicy::new_read_only_policy();
@@ -213,6 +237,8 @@ async fn test_exec_timeout_returns_partial_output() {
         cwd.as_path(),
         &None,
         None,
+        None,
+        None,
     )
     .await;
 
@@ -227,3 +253,240 @@ async fn test_exec_timeout_returns_partial_output() {
     assert!(output.duration >= Duration::from_millis(200));
     assert!(output.timed_out);
 }
+
+#[tokio::test]
+async fn test_exec_tty_runs_in_sized_terminal() {
+    let (tx, rx) = async_channel::unbounded::<Event>();
+
+    let stdout_stream = StdoutStream {
+        sub_id: "test-sub".to_string(),
+        call_id: "call-tty".to_string(),
+        tx_event: tx,
+        streaming: OutputStreaming::default(),
+    };
+
+    // `stty` fails unless stdin is a terminal, and `-t 1` checks stdout.
+    let cmd = vec![
+        "/bin/sh".to_string(),
+        "-c".to_string(),
+        "[ -t 1 ] && stty size".to_string(),
+    ];
+
+    let cwd = std::env::current_dir().unwrap_or_else(|_| PathBuf::from("."));
+    let mut env = HashMap::from([
+        ("LINES".to_string(), "30".to_string()),
+        ("COLUMNS".to_string(), "100".to_string()),
+    ]);
+    if let Ok(path) = std::env::var("PATH") {
+        env.insert("PATH".to_string(), path);
+    }
+    let params = ExecParams {
+        command: cmd,
+        cwd: cwd.clone(),
+        timeout_ms: Some(5_000),
+        env,
+        with_escalated_permissions: None,
+        justification: None,
+        tty: true,
+        resource_limits: ResourceLimits::default(),
+        sandbox_extras: SandboxExtras::default(),
+    };
+
+    let policy = SandboxPolicy::new_read_only_policy();
+
+    let result = process_exec_tool_call(
+        params,
+        SandboxType::None,
+        &policy,
+        cwd.as_path(),
+        &None,
+        Some(stdout_stream),
+        None,
+        None,
+    )
+    .await;
+
+    let result = match result {
+        Ok(r) => r,
+        Err(e) => panic!("process_exec_tool_call failed: {e}"),
+    };
+
+    // The terminal translates "\n" to "\r\n".
+    assert_eq!(result.exit_code, 0);
+    assert_eq!(result.stdout.text, "30 100\r\n");
+    assert!(result.stderr.text.is_empty());
+    assert_eq!(result.aggregated_output.text, "30 100\r\n");
+
+    let streamed = collect_stdout_events(rx);
+    assert_eq!(String::from_utf8_lossy(&streamed), "30 100\r\n");
+}
+
+#[tokio::test]
+async fn test_exec_output_is_capped_at_max_output_bytes() {
+    let cmd = vec![
+        "/bin/sh".to_string(),
+        "-c".to_string(),
+        "printf 'abcdefghij'".to_string(),
+    ];
+
+    let cwd = std::env::current_dir().unwrap_or_else(|_| PathBuf::from("."));
+    let params = ExecParams {
+        command: cmd,
+        cwd: cwd.clone(),
+        timeout_ms: Some(5_000),
+        env: HashMap::new(),
+        with_escalated_permissions: None,
+        justification: None,
+        tty: false,
+        resource_limits: ResourceLimits {
+            max_output_bytes: Some(4),
+            ..Default::default()
+        },
+        sandbox_extras: SandboxExtras::default(),
+    };
+
+    let policy = SandboxPolicy::new_read_only_policy();
+
+    let result = process_exec_tool_call(
+        params,
+        SandboxType::None,
+        &policy,
+        cwd.as_path(),
+        &None,
+        None,
+        None,
+        None,
+    )
+    .await
+    .expect("process_exec_tool_call");
+
+    let expected = "abcd\n[output truncated: max_output_bytes exceeded]\n";
+    assert_eq!(result.exit_code, 0);
+    assert_eq!(result.stdout.text, expected);
+    assert_eq!(result.aggregated_output.text, expected);
+}
+
+#[tokio::test]
+async fn test_exec_stream_events_coalesce_chatty_output() {
+    let (tx, rx) = async_channel::unbounded::<Event>();
+
+    let stdout_stream = StdoutStream {
+        sub_id: "test-sub".to_string(),
+        call_id: "call-chatty".to_string(),
+        tx_event: tx,
+        streaming: OutputStreaming {
+            flush_interval_ms: 60_000,
+            max_chunk_bytes: 4096,
+        },
+    };
+
+    let cmd = vec![
+        "/bin/sh".to_string(),
+        "-c".to_string(),
+        "i=0; while [ $i -lt 2000 ]; do echo line-$i; i=$((i+1)); done".to_string(),
+    ];
+
+    let cwd = std::env::current_dir().unwrap_or_else(|_| PathBuf::from("."));
+    let params = ExecParams {
+        command: cmd,
+        cwd: cwd.clone(),
+        timeout_ms: Some(5_000),
+        env: HashMap::new(),
+        with_escalated_permissions: None,
+        justification: None,
+        tty: false,
+        resource_limits: ResourceLimits::default(),
+        sandbox_extras: SandboxExtras::default(),
+    };
+
+    let policy = SandboxPolicy::new_read_only_policy();
+
+    let result = process_exec_tool_call(
+        params,
+        SandboxType::None,
+        &policy,
+        cwd.as_path(),
+        &None,
+        Some(stdout_stream),
+        None,
+        None,
+    )
+    .await;
+
+    let result = match result {
+        Ok(r) => r,
+        Err(e) => panic!("process_exec_tool_call failed: {e}"),
+    };
+    assert_eq!(result.exit_code, 0);
+
+    let mut chunks = Vec::new();
+    while let Ok(ev) = rx.try_recv() {
+        if let EventMsg::ExecCommandOutputDelta(ExecCommandOutputDeltaEvent { chunk, .. }) = ev.msg
+        {
+            chunks.push(chunk);
+        }
+    }
+    // 2000 lines of output arrive as a handful of size-capped chunks.
+    assert!(chunks.iter().all(|chunk| chunk.len() <= 4096));
+    assert!(chunks.len() < 2000 / 10, "got {} chunks", chunks.len());
+    assert_eq!(
+        String::from_utf8_lossy(&chunks.concat()),
+        result.stdout.text
+    );
+}
+
+#[tokio::test]
+async fn test_exec_cancellation_kills_background_jobs_and_keeps_output() {
+    let cmd = vec![
+        "/bin/sh".to_string(),
+        "-c".to_string(),
+        // The background job holds stdout open; it must die with the shell.
+        "sleep 30 & printf 'partial\n'; sleep 30".to_string(),
+    ];
+
+    let cwd = std::env::current_dir().unwrap_or_else(|_| PathBuf::from("."));
+    let params = ExecParams {
+        command: cmd,
+        cwd: cwd.clone(),
+        timeout_ms: Some(60_000),
+        env: HashMap::new(),
+        with_escalated_permissions: None,
+        justification: None,
+        tty: false,
+        resource_limits: ResourceLimits::default(),
+        sandbox_extras: SandboxExtras::default(),
+    };
+
+    let policy = SandboxPolicy::new_read_only_policy();
+    let cancellation_token = CancellationToken::new();
+    let cancel = cancellation_token.clone();
+    tokio::spawn(async move {
+        tokio::time::sleep(Duration::from_millis(300)).await;
+        cancel.cancel();
+    });
+
+    let result = tokio::time::timeout(
+        Duration::from_secs(10),
+        process_exec_tool_call(
+            params,
+            SandboxType::None,
+            &policy,
+            cwd.as_path(),
+            &None,
+            None,
+            Some(cancellation_token),
+            None,
+        ),
+    )
+    .await
+    .expect("cancelled command should return promptly");
+
+    let result = match result {
+        Ok(r) => r,
+        Err(e) => panic!("process_exec_tool_call failed: {e}"),
+    };
+
+    assert_eq!(result.exit_code, 130);
+    assert!(!result.timed_out);
+    assert_eq!(result.aggregated_output.text, "partial\n");
+}
@@ -0,0 +1,232 @@
+#![cfg(not(target_os = "windows"))]
+
+use std::path::Path;
+use std::path::PathBuf;
+use std::process::Command;
+
+use codex_core::protocol::AskForApproval;
+use codex_core::protocol::EventMsg;
+use codex_core::protocol::InputItem;
+use codex_core::protocol::Op;
+use codex_core::protocol::SandboxPolicy;
+use codex_protocol::config_types::ReasoningSummary;
+use core_test_support::non_sandbox_test;
+use core_test_support::responses;
+use core_test_support::test_codex::TestCodex;
+use core_test_support::test_codex::test_codex;
+use core_test_support::wait_for_event;
+use pretty_assertions::assert_eq;
+use responses::ev_assistant_message;
+use responses::ev_completed;
+use responses::ev_function_call;
+use responses::sse;
+use responses::start_mock_server;
+
+fn git(cwd: &Path, args: &[&str]) -> anyhow::Result<String> {
+    let output = Command::new("git")
+        .args(["-c", "user.name=Test", "-c", "user.email=test@example.com"])
+        .args(args)
+        .current_dir(cwd)
+        .output()?;
+    anyhow::ensure!(output.status.success(), "git {args:?} failed: {output:?}");
+    Ok(String::from_utf8(output.stdout)?.trim().to_string())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn patch_apply_begin_reports_ghost_snapshot() -> anyhow::Result<()> {
+    non_sandbox_test!(result);
+
+    let server = start_mock_server().await;
+    let TestCodex { codex, cwd, .. } = test_codex().build(&server).await?;
+
+    git(cwd.path(), &["init", "--quiet"])?;
+    std::fs::write(cwd.path().join("notes.txt"), "committed\n")?;
+    git(cwd.path(), &["add", "notes.txt"])?;
+    git(cwd.path(), &["commit", "--quiet", "-m", "initial"])?;
+    std::fs::write(cwd.path().join("notes.txt"), "uncommitted\n")?;
+
+    let patch = "*** Begin Patch\n*** Add File: added.txt\n+hello\n*** End Patch";
+    let arguments = serde_json::to_string(&serde_json::json!({
+        "command": ["apply_patch", patch],
+    }))?;
+    responses::mount_sse_once(
+        &server,
+        |req: &wiremock::Request| {
+            !String::from_utf8_lossy(&req.body).contains("function_call_output")
+        },
+        sse(vec![
+            ev_function_call("call-1", "shell", &arguments),
+            ev_completed("r1"),
+        ]),
+    )
+    .await;
+    responses::mount_sse_once(
+        &server,
+        |req: &wiremock::Request| {
+            String::from_utf8_lossy(&req.body).contains("function_call_output")
+        },
+        sse(vec![ev_assistant_message("m2", "done"), ev_completed("r2")]),
+    )
+    .await;
+
+    codex
+        .submit(Op::UserTurn {
+            items: vec![InputItem::Text {
+                text: "add a file".into(),
+            }],
+            cwd: cwd.path().to_path_buf(),
+            approval_policy: AskForApproval::Never,
+            sandbox_policy: SandboxPolicy::DangerFullAccess,
+            model: "gpt-5".into(),
+            effort: None,
+            summary: ReasoningSummary::Auto,
+            final_output_json_schema: None,
+            max_turns: None,
+            max_total_tokens: None,
+            max_duration: None,
+        })
+        .await?;
+
+    let EventMsg::PatchApplyBegin(begin) =
+        wait_for_event(&codex, |ev| matches!(ev, EventMsg::PatchApplyBegin(_))).await
+    else {
+        unreachable!()
+    };
+    wait_for_event(&codex, |ev| matches!(ev, EventMsg::TaskComplete(_))).await;
+
+    let snapshot_id = begin
+        .snapshot_id
+        .expect("snapshot id for patch in git repo");
+    assert_eq!(
+        git(cwd.path(), &["cat-file", "-t", &snapshot_id])?,
+        "commit"
+    );
+    assert_eq!(
+        git(cwd.path(), &["show", &format!("{snapshot_id}:notes.txt")])?,
+        "uncommitted"
+    );
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn list_and_diff_snapshots_then_undo_last_change() -> anyhow::Result<()> {
+    non_sandbox_test!(result);
+
+    let server = start_mock_server().await;
+    let TestCodex { codex, cwd, .. } = test_codex().build(&server).await?;
+
+    git(cwd.path(), &["init", "--quiet"])?;
+    std::fs::write(cwd.path().join("notes.txt"), "committed\n")?;
+    git(cwd.path(), &["add", "notes.txt"])?;
+    git(cwd.path(), &["commit", "--quiet", "-m", "initial"])?;
+    std::fs::write(cwd.path().join("notes.txt"), "uncommitted\n")?;
+
+    let arguments = serde_json::to_string(&serde_json::json!({
+        "command": ["bash", "-c", "echo patched > notes.txt"],
+    }))?;
+    responses::mount_sse_once(
+        &server,
+        |req: &wiremock::Request| {
+            !String::from_utf8_lossy(&req.body).contains("function_call_output")
+        },
+        sse(vec![
+            ev_function_call("call-1", "shell", &arguments),
+            ev_completed("r1"),
+        ]),
+    )
+    .await;
+    responses::mount_sse_once(
+        &server,
+        |req: &wiremock::Request| {
+            String::from_utf8_lossy(&req.body).contains("function_call_output")
+        },
+        sse(vec![ev_assistant_message("m2", "done"), ev_completed("r2")]),
+    )
+    .await;
+
+    codex
+        .submit(Op::UserTurn {
+            items: vec![InputItem::Text {
+                text: "edit notes".into(),
+            }],
+            cwd: cwd.path().to_path_buf(),
+            approval_policy: AskForApproval::Never,
+            sandbox_policy: SandboxPolicy::DangerFullAccess,
+            model: "gpt-5".into(),
+            effort: None,
+            summary: ReasoningSummary::Auto,
+            final_output_json_schema: None,
+            max_turns: None,
+            max_total_tokens: None,
+            max_duration: None,
+        })
+        .await?;
+    wait_for_event(&codex, |ev| matches!(ev, EventMsg::TaskComplete(_))).await;
+    assert_eq!(
+        std::fs::read_to_string(cwd.path().join("notes.txt"))?,
+        "patched\n"
+    );
+
+    codex.submit(Op::ListSnapshots).await?;
+    let EventMsg::ListSnapshotsResponse(listing) = wait_for_event(&codex, |ev| {
+        matches!(ev, EventMsg::ListSnapshotsResponse(_))
+    })
+    .await
+    else {
+        unreachable!()
+    };
+    let [snapshot] = listing.snapshots.as_slice() else {
+        panic!("expected one snapshot, got {listing:?}");
+    };
+    assert_eq!(snapshot.call_id, "call-1");
+    assert_eq!(snapshot.files, vec![PathBuf::from("notes.txt")]);
+    assert!(
+        chrono::DateTime::parse_from_rfc3339(&snapshot.timestamp).is_ok(),
+        "{snapshot:?}"
+    );
+
+    // With no arguments the diff runs from the task's first snapshot to the
+    // current working tree.
+    codex
+        .submit(Op::SnapshotDiff {
+            from: None,
+            to: None,
+        })
+        .await?;
+    let EventMsg::SnapshotDiff(diff) =
+        wait_for_event(&codex, |ev| matches!(ev, EventMsg::SnapshotDiff(_))).await
+    else {
+        unreachable!()
+    };
+    assert_eq!(diff.from.as_deref(), Some(snapshot.id.as_str()));
+    assert_eq!(diff.to, None);
+    assert!(
+        diff.unified_diff.contains("-uncommitted\n+patched\n"),
+        "{diff:?}"
+    );
+
+    codex.submit(Op::UndoLastChange).await?;
+    let EventMsg::UndoCompleted(undo) =
+        wait_for_event(&codex, |ev| matches!(ev, EventMsg::UndoCompleted(_))).await
+    else {
+        unreachable!()
+    };
+    assert_eq!(undo.snapshot_id.as_deref(), Some(snapshot.id.as_str()));
+    assert_eq!(undo.reverted_files, vec![PathBuf::from("notes.txt")]);
+    assert_eq!(
+        std::fs::read_to_string(cwd.path().join("notes.txt"))?,
+        "uncommitted\n"
+    );
+
+    codex.submit(Op::UndoLastChange).await?;
+    let EventMsg::UndoCompleted(undo) =
+        wait_for_event(&codex, |ev| matches!(ev, EventMsg::UndoCompleted(_))).await
+    else {
+        unreachable!()
+    };
+    assert_eq!(undo.snapshot_id, None);
+    assert!(undo.message.is_some(), "{undo:?}");
+
+    Ok(())
+}
@@ -0,0 +1,102 @@
+#![cfg(not(target_os = "windows"))]
+
+use std::path::Path;
+use std::process::Command;
+
+use codex_core::protocol::EventMsg;
+use codex_core::protocol::InputItem;
+use codex_core::protocol::Op;
+use core_test_support::non_sandbox_test;
+use core_test_support::responses;
+use core_test_support::test_codex::TestCodex;
+use core_test_support::test_codex::test_codex;
+use core_test_support::wait_for_event;
+use pretty_assertions::assert_eq;
+use responses::ev_assistant_message;
+use responses::ev_completed;
+use responses::sse;
+use responses::start_mock_server;
+use serde_json::Value;
+
+fn git(cwd: &Path, args: &[&str]) -> anyhow::Result<()> {
+    let output = Command::new("git")
+        .args(["-c", "user.name=Test", "-c", "user.email=test@example.com"])
+        .args(args)
+        .current_dir(cwd)
+        .output()?;
+    anyhow::ensure!(output.status.success(), "git {args:?} failed: {output:?}");
+    Ok(())
+}
+
+/// `<git_status>` blocks among the user messages of a request body.
+fn git_status_texts(body: &Value) -> Vec<String> {
+    body["input"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter(|item| item["role"] == "user")
+        .filter_map(|item| item["content"][0]["text"].as_str())
+        .filter(|text| text.contains("<git_status>"))
+        .map(str::to_string)
+        .collect()
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn turn_reports_git_status_only_when_it_changes() -> anyhow::Result<()> {
+    non_sandbox_test!(result);
+
+    let server = start_mock_server().await;
+    responses::mount_sse_once(
+        &server,
+        wiremock::matchers::any(),
+        sse(vec![ev_assistant_message("m1", "done"), ev_completed("r1")]),
+    )
+    .await;
+    let TestCodex { codex, cwd, .. } = test_codex().build(&server).await?;
+    git(cwd.path(), &["init", "--quiet", "--initial-branch=main"])?;
+    std::fs::write(cwd.path().join("notes.txt"), "committed\n")?;
+    git(cwd.path(), &["add", "notes.txt"])?;
+    git(cwd.path(), &["commit", "--quiet", "-m", "initial"])?;
+    std::fs::write(cwd.path().join("draft.txt"), "untracked\n")?;
+
+    for text in ["first", "second", "third"] {
+        codex
+            .submit(Op::UserInput {
+                items: vec![InputItem::Text { text: text.into() }],
+            })
+            .await?;
+        wait_for_event(&codex, |ev| matches!(ev, EventMsg::TaskComplete(_))).await;
+        if text == "second" {
+            std::fs::write(cwd.path().join("more.txt"), "untracked\n")?;
+        }
+    }
+
+    let requests = server.received_requests().await.unwrap_or_default();
+    let bodies: Vec<Value> = requests
+        .iter()
+        .filter_map(|req| req.body_json::<Value>().ok())
+        .collect();
+    assert_eq!(bodies.len(), 3);
+
+    let first = git_status_texts(&bodies[0]);
+    assert_eq!(first.len(), 1, "{first:?}");
+    assert!(first[0].contains("<branch>main</branch>"), "{first:?}");
+    assert!(
+        first[0].contains("<dirty_files>1</dirty_files>"),
+        "{first:?}"
+    );
+    assert!(!first[0].contains("<ahead>"), "{first:?}");
+
+    // Unchanged status is not repeated on the next turn.
+    assert_eq!(git_status_texts(&bodies[1]), first);
+
+    // A changed status is sent again before the turn after the change.
+    let third = git_status_texts(&bodies[2]);
+    assert_eq!(third.len(), 2, "{third:?}");
+    assert!(
+        third[1].contains("<dirty_files>2</dirty_files>"),
+        "{third:?}"
+    );
+
+    Ok(())
+}
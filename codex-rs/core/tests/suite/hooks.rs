@@ -4,13 +4,16 @@ use std::os::unix::fs::PermissionsExt;
 use std::path::Path;
 
 use anyhow::Context;
+use codex_core::config::HOOK_PAYLOAD_VERSION;
 use codex_core::config::HookRule;
 use codex_core::config::HookToolMatcher;
 use codex_core::config::HooksConfig;
 use codex_core::protocol::AskForApproval;
 use codex_core::protocol::EventMsg;
+use codex_core::protocol::HookKind;
 use codex_core::protocol::InputItem;
 use codex_core::protocol::Op;
+use codex_core::protocol::ReviewDecision;
 use codex_core::protocol::SandboxPolicy;
 use codex_protocol::config_types::ReasoningSummary;
 use core_test_support::non_sandbox_test;
@@ -19,6 +22,7 @@ use core_test_support::test_codex::test_codex;
 use core_test_support::wait_for_event;
 use responses::ev_assistant_message;
 use responses::ev_completed;
+use responses::ev_completed_with_tokens;
 use responses::ev_function_call;
 use responses::sse;
 use responses::start_mock_server;
@@ -54,7 +58,7 @@ printf '%s\n' "${{@: -1}}" >> "{}"
     )?;
 
     let hook_cfg = HooksConfig {
-        user_prompt_submit: Some(vec![script_path.to_string_lossy().into_owned()]),
+        user_prompt_submit: Some(vec![script_path.to_string_lossy().into_owned()].into()),
         timeout_ms: 2_000,
         ..HooksConfig::default()
     };
@@ -88,6 +92,129 @@ printf '%s\n' "${{@: -1}}" >> "{}"
     Ok(())
 }
 
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn turn_complete_hook_receives_turn_summary() -> anyhow::Result<()> {
+    non_sandbox_test!(result);
+
+    let server = start_mock_server().await;
+
+    let args = shell_args("true");
+    let first = sse(vec![
+        ev_function_call("call-1", "container.exec", &args),
+        ev_completed_with_tokens("r1", 100),
+    ]);
+    responses::mount_sse_once(
+        &server,
+        |req: &wiremock::Request| {
+            !String::from_utf8_lossy(&req.body).contains("function_call_output")
+        },
+        first,
+    )
+    .await;
+    let second = sse(vec![
+        ev_assistant_message("m2", "all done"),
+        ev_completed_with_tokens("r2", 40),
+    ]);
+    responses::mount_sse_once(
+        &server,
+        |req: &wiremock::Request| {
+            String::from_utf8_lossy(&req.body).contains("function_call_output")
+        },
+        second,
+    )
+    .await;
+
+    let hook_tmp = TempDir::new()?;
+    let log_path = hook_tmp.path().join("turn_complete.log");
+    let script_path = write_hook_script(
+        hook_tmp.path(),
+        "turn_complete.sh",
+        &format!(
+            r#"#!/bin/bash
+set -euo pipefail
+printf '%s\n' "${{@: -1}}" >> "{}"
+"#,
+            log_path.display()
+        ),
+    )?;
+    let hook_cfg = HooksConfig {
+        turn_complete: Some(vec![script_path.to_string_lossy().into_owned()].into()),
+        timeout_ms: 2_000,
+        ..HooksConfig::default()
+    };
+    let TestCodexContext { codex, cwd, .. } = build_codex_with_hooks(&server, hook_cfg).await?;
+
+    submit_turn(&codex, &cwd).await?;
+    wait_for_event(&codex, |ev| matches!(ev, EventMsg::TaskComplete(_))).await;
+
+    // The hook runs before `TaskComplete`, so its log is already written.
+    let contents = std::fs::read_to_string(&log_path)?;
+    let entries: Vec<Value> = contents
+        .lines()
+        .map(serde_json::from_str)
+        .collect::<Result<_, _>>()?;
+    assert_eq!(entries.len(), 1, "expected a single hook invocation");
+    let payload = &entries[0];
+    assert_eq!(payload["type"], "turn-complete");
+    assert_eq!(payload["last_agent_message"], "all done");
+    assert_eq!(payload["turn_diff"], Value::Null);
+    assert_eq!(payload["token_usage"]["total_tokens"], 140);
+    assert_eq!(payload["total_token_usage"]["total_tokens"], 140);
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn hook_runs_are_bracketed_by_begin_and_end_events() -> anyhow::Result<()> {
+    non_sandbox_test!(result);
+
+    let server = start_mock_server().await;
+
+    let sse_body = sse(vec![ev_assistant_message("m1", "done"), ev_completed("r1")]);
+    responses::mount_sse_once(&server, any(), sse_body).await;
+
+    let hook_tmp = TempDir::new()?;
+    let script_path =
+        write_hook_script(hook_tmp.path(), "user_prompt.sh", "#!/bin/bash\nexit 0\n")?;
+
+    let hook_cfg = HooksConfig {
+        user_prompt_submit: Some(vec![script_path.to_string_lossy().into_owned()].into()),
+        timeout_ms: 2_000,
+        ..HooksConfig::default()
+    };
+
+    let TestCodexContext { codex, .. } = build_codex_with_hooks(&server, hook_cfg).await?;
+
+    codex
+        .submit(Op::UserInput {
+            items: vec![InputItem::Text {
+                text: "hello world".into(),
+            }],
+        })
+        .await?;
+
+    let EventMsg::HookBegin(begin) =
+        wait_for_event(&codex, |ev| matches!(ev, EventMsg::HookBegin(_))).await
+    else {
+        unreachable!();
+    };
+    assert_eq!(begin.hook, HookKind::UserPromptSubmit);
+    assert_eq!(begin.tool, None);
+
+    let EventMsg::HookEnd(end) =
+        wait_for_event(&codex, |ev| matches!(ev, EventMsg::HookEnd(_))).await
+    else {
+        unreachable!();
+    };
+    assert_eq!(end.hook, HookKind::UserPromptSubmit);
+    assert_eq!(end.exit_code, Some(0));
+    assert!(!end.timed_out);
+
+    wait_for_event(&codex, |ev| matches!(ev, EventMsg::TaskComplete(_))).await;
+
+    Ok(())
+}
+
 #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
 async fn pre_tool_hook_failure_blocks_execution() -> anyhow::Result<()> {
     non_sandbox_test!(result);
@@ -98,11 +225,7 @@ async fn pre_tool_hook_failure_blocks_execution() -> anyhow::Result<()> {
     let command_output = _tmp_dir.path().join("should_not_exist.txt");
     let command = format!("echo ran > {}", command_output.display());
     let args = shell_args(&command);
-    let sse_body = sse(vec![
-        ev_function_call("call-1", "container.exec", &args),
-        ev_completed("r1"),
-    ]);
-    responses::mount_sse_once(&server, any(), sse_body).await;
+    mount_tool_call_then_finish(&server, "call-1", "container.exec", &args).await;
 
     let hook_tmp = TempDir::new()?;
     let log_path = hook_tmp.path().join("pre_hook.log");
@@ -122,6 +245,9 @@ exit 42
     let hook_rule = HookRule {
         argv: vec![script_path.to_string_lossy().into_owned()],
         matcher: HookToolMatcher::default(),
+        blocking: true,
+        timeout_ms: None,
+        retries: 0,
     };
 
     let hook_cfg = HooksConfig {
@@ -144,6 +270,9 @@ exit 42
             effort: None,
             summary: ReasoningSummary::Auto,
             final_output_json_schema: None,
+            max_turns: None,
+            max_total_tokens: None,
+            max_duration: None,
         })
         .await?;
 
@@ -160,6 +289,145 @@ exit 42
     Ok(())
 }
 
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn pre_tool_hook_is_retried_after_nonzero_exit() -> anyhow::Result<()> {
+    non_sandbox_test!(result);
+
+    let server = start_mock_server().await;
+
+    let tmp_dir = TempDir::new()?;
+    let command_output = tmp_dir.path().join("ran.txt");
+    let args = shell_args(&format!("echo ran > {}", command_output.display()));
+    mount_tool_call_then_finish(&server, "call-1", "container.exec", &args).await;
+
+    // Fails on the first run and succeeds on the second.
+    let hook_tmp = TempDir::new()?;
+    let log_path = hook_tmp.path().join("flaky_hook.log");
+    let script_path = write_hook_script(
+        hook_tmp.path(),
+        "flaky.sh",
+        &format!(
+            r#"#!/bin/bash
+set -euo pipefail
+printf '%s\n' "${{@: -1}}" >> "{log}"
+[ "$(wc -l < "{log}")" -ge 2 ]
+"#,
+            log = log_path.display()
+        ),
+    )?;
+    let mut hooks = pre_tool_hooks(&script_path);
+    hooks.pre_tool_use_rules[0].retries = 1;
+    let TestCodexContext { codex, cwd, .. } = build_codex_with_hooks(&server, hooks).await?;
+
+    submit_turn(&codex, &cwd).await?;
+    wait_for_event(&codex, |ev| matches!(ev, EventMsg::TaskComplete(_))).await;
+
+    let entries = read_hook_entries(&log_path).await?;
+    assert_eq!(entries.len(), 2, "expected the hook to run twice");
+    assert!(
+        command_output.exists(),
+        "command should run after the retry"
+    );
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn pre_tool_hook_timeout_overrides_global_timeout() -> anyhow::Result<()> {
+    non_sandbox_test!(result);
+
+    let server = start_mock_server().await;
+
+    let tmp_dir = TempDir::new()?;
+    let command_output = tmp_dir.path().join("should_not_exist.txt");
+    let args = shell_args(&format!("echo ran > {}", command_output.display()));
+    mount_tool_call_then_finish(&server, "call-1", "container.exec", &args).await;
+
+    let hook_tmp = TempDir::new()?;
+    let script_path = write_hook_script(hook_tmp.path(), "slow.sh", "#!/bin/bash\nsleep 5\n")?;
+    let mut hooks = pre_tool_hooks(&script_path);
+    hooks.timeout_ms = 20_000;
+    hooks.pre_tool_use_rules[0].timeout_ms = Some(200);
+    let TestCodexContext { codex, cwd, .. } = build_codex_with_hooks(&server, hooks).await?;
+
+    submit_turn(&codex, &cwd).await?;
+    let EventMsg::HookEnd(end) =
+        wait_for_event(&codex, |ev| matches!(ev, EventMsg::HookEnd(_))).await
+    else {
+        unreachable!()
+    };
+    assert!(end.timed_out);
+    assert!(end.duration < Duration::from_secs(5));
+    wait_for_event(&codex, |ev| matches!(ev, EventMsg::TaskComplete(_))).await;
+
+    assert!(
+        function_call_output(&server, "call-1")
+            .await
+            .contains("timed out after 200 ms")
+    );
+    assert!(
+        !command_output.exists(),
+        "timed out hook should block the call"
+    );
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn non_blocking_pre_tool_hook_does_not_delay_or_block_execution() -> anyhow::Result<()> {
+    non_sandbox_test!(result);
+
+    let server = start_mock_server().await;
+
+    let _tmp_dir = TempDir::new()?;
+    let command_output = _tmp_dir.path().join("ran.txt");
+    let args = shell_args(&format!("echo ran > {}", command_output.display()));
+    mount_tool_call_then_finish(&server, "call-1", "container.exec", &args).await;
+
+    // A failing hook would block the call if it were blocking; this one also
+    // outlives the whole turn.
+    let hook_tmp = TempDir::new()?;
+    let marker = hook_tmp.path().join("hook_done");
+    let script_path = write_hook_script(
+        hook_tmp.path(),
+        "slow_telemetry.sh",
+        &format!(
+            "#!/bin/bash\nsleep 3\ntouch \"{}\"\nexit 42\n",
+            marker.display()
+        ),
+    )?;
+    let hook_cfg = HooksConfig {
+        pre_tool_use_rules: vec![HookRule {
+            argv: vec![script_path.to_string_lossy().into_owned()],
+            matcher: HookToolMatcher::default(),
+            blocking: false,
+            timeout_ms: None,
+            retries: 0,
+        }],
+        timeout_ms: 10_000,
+        ..HooksConfig::default()
+    };
+    let TestCodexContext { codex, cwd, .. } = build_codex_with_hooks(&server, hook_cfg).await?;
+
+    submit_turn(&codex, &cwd).await?;
+    wait_for_event(&codex, |ev| matches!(ev, EventMsg::TaskComplete(_))).await;
+
+    assert!(
+        command_output.exists(),
+        "command should run without waiting"
+    );
+    assert!(!marker.exists(), "turn should not wait for the hook");
+
+    // The detached hook still runs to completion in the background.
+    let deadline = std::time::Instant::now() + std::time::Duration::from_secs(10);
+    while !marker.exists() && std::time::Instant::now() < deadline {
+        tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+    }
+    assert!(marker.exists(), "detached hook should still run");
+
+    Ok(())
+}
+
 #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
 async fn post_tool_hook_captures_output_metadata() -> anyhow::Result<()> {
     non_sandbox_test!(result);
@@ -167,11 +435,7 @@ async fn post_tool_hook_captures_output_metadata() -> anyhow::Result<()> {
     let server = start_mock_server().await;
 
     let args = shell_args("echo hook-output");
-    let sse_body = sse(vec![
-        ev_function_call("call-1", "container.exec", &args),
-        ev_completed("r1"),
-    ]);
-    responses::mount_sse_once(&server, any(), sse_body).await;
+    mount_tool_call_then_finish(&server, "call-1", "container.exec", &args).await;
 
     let hook_tmp = TempDir::new()?;
     let post_log = hook_tmp.path().join("post_hook.log");
@@ -204,10 +468,16 @@ printf '%s\n' "${{@: -1}}" >> "{}"
         pre_tool_use_rules: vec![HookRule {
             argv: vec![pre_script.to_string_lossy().into_owned()],
             matcher: HookToolMatcher::default(),
+            blocking: true,
+            timeout_ms: None,
+            retries: 0,
         }],
         post_tool_use_rules: vec![HookRule {
             argv: vec![post_script.to_string_lossy().into_owned()],
             matcher: HookToolMatcher::default(),
+            blocking: true,
+            timeout_ms: None,
+            retries: 0,
         }],
         timeout_ms: 2_000,
         ..HooksConfig::default()
@@ -227,6 +497,9 @@ printf '%s\n' "${{@: -1}}" >> "{}"
             effort: None,
             summary: ReasoningSummary::Auto,
             final_output_json_schema: None,
+            max_turns: None,
+            max_total_tokens: None,
+            max_duration: None,
         })
         .await?;
 
@@ -243,6 +516,428 @@ printf '%s\n' "${{@: -1}}" >> "{}"
         .and_then(|v| v.as_str())
         .context("post hook payload missing output field")?;
     assert!(output.contains("hook-output"));
+    assert_eq!(payload["schema_version"], Value::from(HOOK_PAYLOAD_VERSION));
+    assert!(payload["duration_ms"].is_u64());
+
+    let pre_entries = read_hook_entries(&pre_log).await?;
+    assert_eq!(
+        pre_entries[0]["schema_version"],
+        Value::from(HOOK_PAYLOAD_VERSION)
+    );
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn post_turn_diff_hook_receives_diff_and_files() -> anyhow::Result<()> {
+    non_sandbox_test!(result);
+
+    let server = start_mock_server().await;
+
+    let patch = "*** Begin Patch\n*** Add File: added.txt\n+hello\n*** End Patch\n";
+    let args = serde_json::to_string(&serde_json::json!({
+        "command": ["apply_patch", patch],
+        "workdir": null,
+        "timeout_ms": null,
+    }))?;
+    mount_tool_call_then_finish(&server, "call-1", "container.exec", &args).await;
+
+    let hook_tmp = TempDir::new()?;
+    let log_path = hook_tmp.path().join("post_turn_diff.log");
+    let script_path = write_hook_script(
+        hook_tmp.path(),
+        "post_turn_diff.sh",
+        &format!(
+            r#"#!/bin/bash
+set -euo pipefail
+printf '%s\n' "${{@: -1}}" >> "{}"
+"#,
+            log_path.display()
+        ),
+    )?;
+
+    let hook_cfg = HooksConfig {
+        post_turn_diff: Some(vec![script_path.to_string_lossy().into_owned()].into()),
+        timeout_ms: 2_000,
+        ..HooksConfig::default()
+    };
+
+    let TestCodexContext { codex, cwd, .. } = build_codex_with_hooks(&server, hook_cfg).await?;
+    submit_turn(&codex, &cwd).await?;
+    wait_for_event(&codex, |ev| matches!(ev, EventMsg::TaskComplete(_))).await;
+
+    let entries = read_hook_entries(&log_path).await?;
+    let payload = entries.first().context("post_turn_diff hook did not run")?;
+    assert_eq!(payload["type"], Value::String("post-turn-diff".into()));
+    let added = cwd.path().join("added.txt");
+    assert_eq!(
+        payload["files"],
+        serde_json::json!([added.to_string_lossy()])
+    );
+    let diff = payload["unified_diff"]
+        .as_str()
+        .context("diff should be inline")?;
+    assert!(diff.contains("+hello"));
+    assert_eq!(payload["unified_diff_path"], Value::Null);
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn pinned_payload_version_omits_newer_fields() -> anyhow::Result<()> {
+    non_sandbox_test!(result);
+
+    let server = start_mock_server().await;
+
+    let args = shell_args("true");
+    mount_tool_call_then_finish(&server, "call-1", "container.exec", &args).await;
+
+    let hook_tmp = TempDir::new()?;
+    let post_log = hook_tmp.path().join("post_hook.log");
+    let post_script = write_hook_script(
+        hook_tmp.path(),
+        "post.sh",
+        &format!(
+            r#"#!/bin/bash
+set -euo pipefail
+printf '%s\n' "${{@: -1}}" >> "{}"
+"#,
+            post_log.display()
+        ),
+    )?;
+
+    let hook_cfg = HooksConfig {
+        post_tool_use_rules: vec![HookRule {
+            argv: vec![post_script.to_string_lossy().into_owned()],
+            matcher: HookToolMatcher::default(),
+            blocking: true,
+            timeout_ms: None,
+            retries: 0,
+        }],
+        timeout_ms: 2_000,
+        payload_version: 1,
+        ..HooksConfig::default()
+    };
+
+    let TestCodexContext { codex, cwd, .. } = build_codex_with_hooks(&server, hook_cfg).await?;
+    submit_turn(&codex, &cwd).await?;
+    wait_for_event(&codex, |ev| matches!(ev, EventMsg::TaskComplete(_))).await;
+
+    let entries = read_hook_entries(&post_log).await?;
+    assert_eq!(entries.len(), 1, "expected a single post-hook invocation");
+    let payload = &entries[0];
+    assert_eq!(payload["type"], Value::String("post-tool-use".into()));
+    assert_eq!(payload["schema_version"], Value::from(1));
+    assert_eq!(payload.get("duration_ms"), None);
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn post_tool_hook_additional_context_reaches_next_turn() -> anyhow::Result<()> {
+    non_sandbox_test!(result);
+
+    let server = start_mock_server().await;
+    mount_tool_call_then_finish(&server, "call-1", "container.exec", &shell_args("echo hi")).await;
+
+    let hook_tmp = TempDir::new()?;
+    let post_script = write_hook_script(
+        hook_tmp.path(),
+        "post_context.sh",
+        "#!/bin/bash\necho '{\"additional_context\": \"lint: 2 warnings in main.rs\"}'\n",
+    )?;
+    let hook_cfg = HooksConfig {
+        post_tool_use_rules: vec![HookRule {
+            argv: vec![post_script.to_string_lossy().into_owned()],
+            matcher: HookToolMatcher::default(),
+            blocking: true,
+            timeout_ms: None,
+            retries: 0,
+        }],
+        timeout_ms: 2_000,
+        ..HooksConfig::default()
+    };
+    let TestCodexContext { codex, cwd, .. } = build_codex_with_hooks(&server, hook_cfg).await?;
+
+    submit_turn(&codex, &cwd).await?;
+    wait_for_event(&codex, |ev| matches!(ev, EventMsg::TaskComplete(_))).await;
+
+    let requests = server.received_requests().await.unwrap_or_default();
+    assert_eq!(requests.len(), 2);
+    let body: Value = requests[1].body_json()?;
+    let input = body["input"].as_array().cloned().unwrap_or_default();
+    let output_idx = input
+        .iter()
+        .position(|item| item["type"] == "function_call_output")
+        .context("tool output missing from follow-up request")?;
+    let context_idx = input
+        .iter()
+        .position(|item| {
+            item["type"] == "message"
+                && item["role"] == "user"
+                && item["content"][0]["text"]
+                    == "Note from post_tool_use hooks on the `shell` call call-1:\nlint: 2 warnings in main.rs"
+        })
+        .context("hook context missing from follow-up request")?;
+    assert!(
+        context_idx > output_idx,
+        "hook context should follow the tool output"
+    );
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn pre_tool_hook_deny_returns_reason_to_model() -> anyhow::Result<()> {
+    non_sandbox_test!(result);
+
+    let server = start_mock_server().await;
+
+    let tmp_dir = TempDir::new()?;
+    let command_output = tmp_dir.path().join("should_not_exist.txt");
+    let args = shell_args(&format!("echo ran > {}", command_output.display()));
+    mount_tool_call_then_finish(&server, "call-1", "container.exec", &args).await;
+
+    let hook_tmp = TempDir::new()?;
+    let script_path = write_decision_hook(
+        hook_tmp.path(),
+        &serde_json::json!({ "decision": "deny", "reason": "writes are frozen" }),
+    )?;
+    let TestCodexContext { codex, cwd, .. } =
+        build_codex_with_hooks(&server, pre_tool_hooks(&script_path)).await?;
+
+    submit_turn(&codex, &cwd).await?;
+    wait_for_event(&codex, |ev| matches!(ev, EventMsg::TaskComplete(_))).await;
+
+    assert!(!command_output.exists(), "denied command should not run");
+    let output = function_call_output(&server, "call-1").await;
+    assert_eq!(
+        output,
+        "shell call denied by pre_tool_use hook: writes are frozen"
+    );
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn pre_tool_hook_modified_arguments_replace_command() -> anyhow::Result<()> {
+    non_sandbox_test!(result);
+
+    let server = start_mock_server().await;
+
+    let tmp_dir = TempDir::new()?;
+    let original_output = tmp_dir.path().join("original.txt");
+    let modified_output = tmp_dir.path().join("modified.txt");
+    let args = shell_args(&format!("echo ran > {}", original_output.display()));
+    mount_tool_call_then_finish(&server, "call-1", "container.exec", &args).await;
+
+    let hook_tmp = TempDir::new()?;
+    let script_path = write_decision_hook(
+        hook_tmp.path(),
+        &serde_json::json!({
+            "decision": "allow",
+            "modified_arguments": {
+                "argv": [
+                    "/bin/bash",
+                    "-c",
+                    format!("echo ran > {}", modified_output.display()),
+                ],
+            },
+        }),
+    )?;
+    let TestCodexContext { codex, cwd, .. } =
+        build_codex_with_hooks(&server, pre_tool_hooks(&script_path)).await?;
+
+    submit_turn(&codex, &cwd).await?;
+    wait_for_event(&codex, |ev| matches!(ev, EventMsg::TaskComplete(_))).await;
+
+    assert!(!original_output.exists(), "original command should not run");
+    assert!(modified_output.exists(), "modified command should run");
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn pre_tool_hook_ask_requests_user_approval() -> anyhow::Result<()> {
+    non_sandbox_test!(result);
+
+    let server = start_mock_server().await;
+
+    let tmp_dir = TempDir::new()?;
+    let command_output = tmp_dir.path().join("approved.txt");
+    let args = shell_args(&format!("echo ran > {}", command_output.display()));
+    mount_tool_call_then_finish(&server, "call-1", "container.exec", &args).await;
+
+    let hook_tmp = TempDir::new()?;
+    let script_path = write_decision_hook(
+        hook_tmp.path(),
+        &serde_json::json!({ "decision": "ask", "reason": "touches shared state" }),
+    )?;
+    let TestCodexContext { codex, cwd, .. } =
+        build_codex_with_hooks(&server, pre_tool_hooks(&script_path)).await?;
+
+    let turn_id = submit_turn(&codex, &cwd).await?;
+    let EventMsg::ExecApprovalRequest(request) =
+        wait_for_event(&codex, |ev| matches!(ev, EventMsg::ExecApprovalRequest(_))).await
+    else {
+        unreachable!()
+    };
+    assert_eq!(request.reason.as_deref(), Some("touches shared state"));
+    assert!(!command_output.exists(), "command should wait for approval");
+
+    codex
+        .submit(Op::ExecApproval {
+            id: turn_id,
+            decision: ReviewDecision::Approved,
+        })
+        .await?;
+    wait_for_event(&codex, |ev| matches!(ev, EventMsg::TaskComplete(_))).await;
+
+    assert!(command_output.exists(), "approved command should run");
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn session_start_hook_records_payload_and_injects_context() -> anyhow::Result<()> {
+    non_sandbox_test!(result);
+
+    let server = start_mock_server().await;
+
+    let sse_body = sse(vec![ev_assistant_message("m1", "done"), ev_completed("r1")]);
+    responses::mount_sse_once(&server, any(), sse_body).await;
+
+    let hook_tmp = TempDir::new()?;
+    let log_path = hook_tmp.path().join("session_start.log");
+    let script_path = write_hook_script(
+        hook_tmp.path(),
+        "session_start.sh",
+        &format!(
+            r#"#!/bin/bash
+set -euo pipefail
+printf '%s\n' "${{@: -1}}" >> "{}"
+echo '{{"additional_context": "scratch dir is /tmp/scratch-'"$SESSION_ID"'"}}'
+"#,
+            log_path.display()
+        ),
+    )?;
+
+    let hook_cfg = HooksConfig {
+        session_start: Some(vec![script_path.to_string_lossy().into_owned()].into()),
+        timeout_ms: 2_000,
+        ..HooksConfig::default()
+    };
+
+    let TestCodexContext { codex, cwd, .. } = build_codex_with_hooks(&server, hook_cfg).await?;
+
+    let entries = read_hook_entries(&log_path).await?;
+    assert_eq!(
+        entries.len(),
+        1,
+        "expected a single session_start invocation"
+    );
+    let payload = &entries[0];
+    assert_eq!(payload["type"], Value::String("session-start".into()));
+    assert_eq!(
+        payload["cwd"],
+        Value::String(cwd.path().to_string_lossy().into_owned())
+    );
+    assert!(payload["model"].is_string());
+    assert!(payload["sandbox_policy"].is_object());
+    let session_id = payload["session_id"]
+        .as_str()
+        .context("session_start payload missing session_id")?
+        .to_string();
+
+    codex
+        .submit(Op::UserInput {
+            items: vec![InputItem::Text {
+                text: "hello".into(),
+            }],
+        })
+        .await?;
+    wait_for_event(&codex, |ev| matches!(ev, EventMsg::TaskComplete(_))).await;
+
+    let requests = server.received_requests().await.unwrap_or_default();
+    let body = requests
+        .first()
+        .map(|req| String::from_utf8_lossy(&req.body).into_owned())
+        .unwrap_or_default();
+    assert!(body.contains(&format!("scratch dir is /tmp/scratch-{session_id}")));
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn pre_model_call_hook_can_block_the_request() -> anyhow::Result<()> {
+    non_sandbox_test!(result);
+
+    let server = start_mock_server().await;
+
+    let hook_tmp = TempDir::new()?;
+    let log_path = hook_tmp.path().join("pre_model_call.log");
+    let script_path = write_hook_script(
+        hook_tmp.path(),
+        "pre_model_call.sh",
+        &format!(
+            r#"#!/bin/bash
+set -euo pipefail
+printf '%s\n' "${{@: -1}}" >> "{}"
+echo '{{"decision": "block", "reason": "prompt mentions a key file"}}'
+"#,
+            log_path.display()
+        ),
+    )?;
+
+    let hook_cfg = HooksConfig {
+        pre_model_call: Some(vec![script_path.to_string_lossy().into_owned()].into()),
+        timeout_ms: 2_000,
+        ..HooksConfig::default()
+    };
+
+    let TestCodexContext { codex, .. } = build_codex_with_hooks(&server, hook_cfg).await?;
+
+    codex
+        .submit(Op::UserInput {
+            items: vec![InputItem::Text {
+                text: "print id.pem".into(),
+            }],
+        })
+        .await?;
+
+    let EventMsg::Error(error) =
+        wait_for_event(&codex, |ev| matches!(ev, EventMsg::Error(_))).await
+    else {
+        unreachable!();
+    };
+    assert_eq!(
+        error.message,
+        "model request blocked by pre_model_call hook: prompt mentions a key file"
+    );
+    wait_for_event(&codex, |ev| matches!(ev, EventMsg::TaskComplete(_))).await;
+
+    let requests = server.received_requests().await.unwrap_or_default();
+    assert!(
+        requests.is_empty(),
+        "blocked request must not reach the model"
+    );
+
+    let entries = read_hook_entries(&log_path).await?;
+    assert_eq!(entries.len(), 1, "a blocked turn is not retried");
+    let payload = &entries[0];
+    assert_eq!(payload["type"], Value::String("pre-model-call".into()));
+    assert!(payload["model"].is_string());
+    assert!(
+        payload["item_counts"]["message"]
+            .as_u64()
+            .is_some_and(|n| n > 0)
+    );
+    assert!(payload["estimated_tokens"].as_u64().is_some_and(|n| n > 0));
+    assert!(
+        payload["tools"]
+            .as_array()
+            .is_some_and(|tools| !tools.is_empty())
+    );
 
     Ok(())
 }
@@ -259,16 +954,15 @@ async fn build_codex_with_hooks(
 ) -> anyhow::Result<TestCodexContext> {
     use core_test_support::test_codex::TestCodex;
 
-    let hooks_cfg = hooks.clone();
     let mut builder = test_codex().with_config(move |cfg| {
-        cfg.hooks = hooks_cfg.clone();
+        cfg.hooks = hooks;
     });
 
     let TestCodex {
         codex, cwd, home, ..
     } = builder.build(server).await?;
-    // Drain the SessionConfigured event so tests can focus on their assertions.
-    let _ = wait_for_event(&codex, |ev| matches!(ev, EventMsg::SessionConfigured(_))).await;
+    // `SessionConfigured` is consumed by `ConversationManager::new_conversation`,
+    // so the event stream starts with whatever the test submits.
 
     Ok(TestCodexContext {
         codex,
@@ -277,6 +971,99 @@ async fn build_codex_with_hooks(
     })
 }
 
+/// Mounts a first response that issues a single tool call and a follow-up
+/// response (matched by the presence of the tool output) that ends the turn.
+async fn mount_tool_call_then_finish(
+    server: &wiremock::MockServer,
+    call_id: &str,
+    name: &str,
+    arguments: &str,
+) {
+    let first = sse(vec![
+        ev_function_call(call_id, name, arguments),
+        ev_completed("r1"),
+    ]);
+    responses::mount_sse_once(
+        server,
+        |req: &wiremock::Request| {
+            !String::from_utf8_lossy(&req.body).contains("function_call_output")
+        },
+        first,
+    )
+    .await;
+
+    let second = sse(vec![ev_assistant_message("m2", "done"), ev_completed("r2")]);
+    responses::mount_sse_once(
+        server,
+        |req: &wiremock::Request| {
+            String::from_utf8_lossy(&req.body).contains("function_call_output")
+        },
+        second,
+    )
+    .await;
+}
+
+fn pre_tool_hooks(script_path: &Path) -> HooksConfig {
+    HooksConfig {
+        pre_tool_use_rules: vec![HookRule {
+            argv: vec![script_path.to_string_lossy().into_owned()],
+            matcher: HookToolMatcher::default(),
+            blocking: true,
+            timeout_ms: None,
+            retries: 0,
+        }],
+        timeout_ms: 2_000,
+        ..HooksConfig::default()
+    }
+}
+
+async fn submit_turn(
+    codex: &codex_core::CodexConversation,
+    cwd: &TempDir,
+) -> anyhow::Result<String> {
+    let id = codex
+        .submit(Op::UserTurn {
+            items: vec![InputItem::Text {
+                text: "please run".into(),
+            }],
+            cwd: cwd.path().to_path_buf(),
+            approval_policy: AskForApproval::Never,
+            sandbox_policy: SandboxPolicy::DangerFullAccess,
+            model: MODEL_NAME.into(),
+            effort: None,
+            summary: ReasoningSummary::Auto,
+            final_output_json_schema: None,
+            max_turns: None,
+            max_total_tokens: None,
+            max_duration: None,
+        })
+        .await?;
+    Ok(id)
+}
+
+/// Returns the output the model received for `call_id`.
+async fn function_call_output(server: &wiremock::MockServer, call_id: &str) -> String {
+    let requests = server.received_requests().await.unwrap_or_default();
+    requests
+        .iter()
+        .filter_map(|req| req.body_json::<Value>().ok())
+        .filter_map(|body| body["input"].as_array().cloned())
+        .flatten()
+        .find(|item| item["type"] == "function_call_output" && item["call_id"] == call_id)
+        .and_then(|item| item["output"].as_str().map(str::to_string))
+        .unwrap_or_default()
+}
+
+/// Writes a pre-tool hook that prints `decision` as its JSON decision.
+fn write_decision_hook(dir: &Path, decision: &Value) -> anyhow::Result<std::path::PathBuf> {
+    write_hook_script(
+        dir,
+        "decision.sh",
+        &format!("#!/bin/bash\ncat <<'JSON'\n{decision}\nJSON\n"),
+    )
+}
+
+#[expect(clippy::expect_used)]
 fn shell_args(command: &str) -> String {
     serde_json::to_string(&serde_json::json!({
         "command": ["/bin/bash", "-c", command],
@@ -78,6 +78,9 @@ async fn codex_returns_json_result(model: String) -> anyhow::Result<()> {
                 text: "hello world".into(),
             }],
             final_output_json_schema: Some(serde_json::from_str(SCHEMA)?),
+            max_turns: None,
+            max_total_tokens: None,
+            max_duration: None,
             cwd: cwd.path().to_path_buf(),
             approval_policy: AskForApproval::Never,
             sandbox_policy: SandboxPolicy::DangerFullAccess,
@@ -104,3 +107,71 @@ async fn codex_returns_json_result(model: String) -> anyhow::Result<()> {
 
     Ok(())
 }
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn final_answer_that_does_not_match_the_schema_is_repaired() -> anyhow::Result<()> {
+    non_sandbox_test!(result);
+
+    let server = start_mock_server().await;
+
+    let is_repair_request = |req: &wiremock::Request| {
+        String::from_utf8_lossy(&req.body).contains("does not match the required JSON schema")
+    };
+    responses::mount_sse_once(
+        &server,
+        move |req: &wiremock::Request| !is_repair_request(req),
+        sse(vec![
+            ev_assistant_message("m1", r#"{"explanation": "explanation"}"#),
+            ev_completed("r1"),
+        ]),
+    )
+    .await;
+    responses::mount_sse_once(
+        &server,
+        is_repair_request,
+        sse(vec![
+            ev_assistant_message(
+                "m2",
+                r#"{"explanation": "explanation", "final_answer": "final_answer"}"#,
+            ),
+            ev_completed("r2"),
+        ]),
+    )
+    .await;
+
+    let TestCodex { codex, cwd, .. } = test_codex().build(&server).await?;
+
+    codex
+        .submit(Op::UserTurn {
+            items: vec![InputItem::Text {
+                text: "hello world".into(),
+            }],
+            final_output_json_schema: Some(serde_json::from_str(SCHEMA)?),
+            max_turns: None,
+            max_total_tokens: None,
+            max_duration: None,
+            cwd: cwd.path().to_path_buf(),
+            approval_policy: AskForApproval::Never,
+            sandbox_policy: SandboxPolicy::DangerFullAccess,
+            model: "gpt-5".to_string(),
+            effort: None,
+            summary: ReasoningSummary::Auto,
+        })
+        .await?;
+
+    let EventMsg::TaskComplete(complete) =
+        wait_for_event(&codex, |ev| matches!(ev, EventMsg::TaskComplete(_))).await
+    else {
+        unreachable!()
+    };
+    assert_eq!(
+        complete.last_agent_message.as_deref(),
+        Some(r#"{"explanation": "explanation", "final_answer": "final_answer"}"#)
+    );
+    assert_eq!(complete.output_schema_error, None);
+
+    let requests = server.received_requests().await.unwrap_or_default();
+    assert_eq!(requests.len(), 2);
+
+    Ok(())
+}
@@ -0,0 +1,85 @@
+#![cfg(not(target_os = "windows"))]
+
+use codex_core::protocol::EventMsg;
+use codex_core::protocol::InputItem;
+use codex_core::protocol::Op;
+use core_test_support::non_sandbox_test;
+use core_test_support::responses;
+use core_test_support::test_codex::TestCodex;
+use core_test_support::test_codex::test_codex;
+use core_test_support::wait_for_event;
+use responses::ev_assistant_message;
+use responses::ev_completed;
+use responses::sse;
+use responses::start_mock_server;
+use serde_json::Value;
+
+/// Texts of the user messages sent in the first request.
+async fn run_turn_and_collect_user_texts(locale_context: bool) -> anyhow::Result<Vec<String>> {
+    let server = start_mock_server().await;
+    responses::mount_sse_once(
+        &server,
+        |_req: &wiremock::Request| true,
+        sse(vec![ev_assistant_message("m1", "done"), ev_completed("r1")]),
+    )
+    .await;
+    let TestCodex { codex, .. } = test_codex()
+        .with_config(move |config| config.locale_context = locale_context)
+        .build(&server)
+        .await?;
+
+    codex
+        .submit(Op::UserInput {
+            items: vec![InputItem::Text {
+                text: "what day is it?".into(),
+            }],
+        })
+        .await?;
+    wait_for_event(&codex, |ev| matches!(ev, EventMsg::TaskComplete(_))).await;
+
+    let requests = server.received_requests().await.unwrap_or_default();
+    let body = requests
+        .first()
+        .and_then(|req| req.body_json::<Value>().ok())
+        .ok_or_else(|| anyhow::anyhow!("no request sent to the model"))?;
+    Ok(body["input"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter(|item| item["role"] == "user")
+        .filter_map(|item| item["content"][0]["text"].as_str())
+        .map(str::to_string)
+        .collect())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn turn_includes_local_time_before_user_message() -> anyhow::Result<()> {
+    non_sandbox_test!(result);
+
+    let texts = run_turn_and_collect_user_texts(true).await?;
+    let locale_index = texts
+        .iter()
+        .position(|text| text.contains("<local_time>"))
+        .expect("locale context in request");
+    let user_index = texts
+        .iter()
+        .position(|text| text == "what day is it?")
+        .expect("user message in request");
+    assert!(locale_index < user_index, "{texts:?}");
+    assert!(!texts[locale_index].contains("<cwd>"), "{texts:?}");
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn locale_context_can_be_disabled() -> anyhow::Result<()> {
+    non_sandbox_test!(result);
+
+    let texts = run_turn_and_collect_user_texts(false).await?;
+    assert!(
+        texts.iter().all(|text| !text.contains("<local_time>")),
+        "{texts:?}"
+    );
+
+    Ok(())
+}
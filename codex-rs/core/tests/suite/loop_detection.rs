@@ -0,0 +1,147 @@
+#![cfg(not(target_os = "windows"))]
+
+use codex_core::protocol::AskForApproval;
+use codex_core::protocol::EventMsg;
+use codex_core::protocol::InputItem;
+use codex_core::protocol::Op;
+use codex_core::protocol::SandboxPolicy;
+use codex_core::protocol::TaskStalledEvent;
+use codex_protocol::config_types::StallAction;
+use core_test_support::non_sandbox_test;
+use core_test_support::responses;
+use core_test_support::test_codex::TestCodex;
+use core_test_support::test_codex::test_codex;
+use core_test_support::wait_for_event;
+use pretty_assertions::assert_eq;
+use responses::ev_assistant_message;
+use responses::ev_completed;
+use responses::ev_function_call;
+use responses::sse;
+use responses::start_mock_server;
+use wiremock::MockServer;
+
+/// Mount responses that repeat the same shell call for the first `repeats`
+/// requests and finish with an assistant message afterwards.
+async fn mount_repeated_shell_call(server: &MockServer, script: &str, repeats: usize) {
+    let args = serde_json::json!({
+        "command": ["/bin/bash", "-c", script],
+    })
+    .to_string();
+    for turn in 0..=repeats {
+        let body = if turn < repeats {
+            sse(vec![
+                ev_function_call(&format!("call-{turn}"), "shell", &args),
+                ev_completed(&format!("r{turn}")),
+            ])
+        } else {
+            sse(vec![ev_assistant_message("m", "done"), ev_completed("rn")])
+        };
+        responses::mount_sse_once(
+            server,
+            move |req: &wiremock::Request| {
+                String::from_utf8_lossy(&req.body)
+                    .matches("\"function_call_output\"")
+                    .count()
+                    == turn
+            },
+            body,
+        )
+        .await;
+    }
+}
+
+async fn run_until_complete(
+    server: &MockServer,
+    action: StallAction,
+) -> anyhow::Result<(Vec<TaskStalledEvent>, usize)> {
+    let TestCodex {
+        codex, cwd: _cwd, ..
+    } = test_codex()
+        .with_config(move |config| {
+            config.approval_policy = AskForApproval::Never;
+            config.sandbox_policy = SandboxPolicy::DangerFullAccess;
+            config.stall_detection_turns = 2;
+            config.stall_action = action;
+        })
+        .build(server)
+        .await?;
+
+    codex
+        .submit(Op::UserInput {
+            items: vec![InputItem::Text {
+                text: "run it".into(),
+            }],
+        })
+        .await?;
+
+    let mut stalls = Vec::new();
+    let mut exec_begins = 0;
+    loop {
+        match wait_for_event(&codex, |_| true).await {
+            EventMsg::TaskStalled(ev) => stalls.push(ev),
+            EventMsg::ExecCommandBegin(_) => exec_begins += 1,
+            EventMsg::TaskComplete(_) => break,
+            _ => {}
+        }
+    }
+    Ok((stalls, exec_begins))
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn repeated_tool_calls_pause_the_task() -> anyhow::Result<()> {
+    non_sandbox_test!(result);
+
+    let server = start_mock_server().await;
+    mount_repeated_shell_call(&server, "echo same", 5).await;
+
+    let (stalls, exec_begins) = run_until_complete(&server, StallAction::Pause).await?;
+
+    assert_eq!(stalls.len(), 1);
+    assert_eq!(stalls[0].repeated_turns, 2);
+    assert_eq!(stalls[0].failing_tool, None);
+    assert_eq!(stalls[0].action, StallAction::Pause);
+    assert_eq!(exec_begins, 3);
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn repeated_tool_calls_inject_recovery_instruction() -> anyhow::Result<()> {
+    non_sandbox_test!(result);
+
+    let server = start_mock_server().await;
+    mount_repeated_shell_call(&server, "echo same", 3).await;
+
+    let (stalls, exec_begins) = run_until_complete(&server, StallAction::Recover).await?;
+
+    assert_eq!(stalls.len(), 1);
+    assert_eq!(stalls[0].action, StallAction::Recover);
+    assert_eq!(exec_begins, 3);
+
+    let requests = server.received_requests().await.unwrap_or_default();
+    let last_body = requests
+        .last()
+        .map(|req| String::from_utf8_lossy(&req.body).into_owned())
+        .unwrap_or_default();
+    assert!(last_body.contains("Stop retrying them"));
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn repeatedly_failing_tool_call_stalls_the_task() -> anyhow::Result<()> {
+    non_sandbox_test!(result);
+
+    let server = start_mock_server().await;
+    // The output differs every time, so only the failures repeat.
+    mount_repeated_shell_call(&server, "echo attempt $RANDOM; exit 1", 5).await;
+
+    let (stalls, exec_begins) = run_until_complete(&server, StallAction::Pause).await?;
+
+    assert_eq!(stalls.len(), 1);
+    assert_eq!(stalls[0].repeated_turns, 2);
+    assert_eq!(stalls[0].failing_tool.as_deref(), Some("shell"));
+    assert_eq!(exec_begins, 3);
+
+    Ok(())
+}
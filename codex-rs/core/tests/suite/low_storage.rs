@@ -0,0 +1,49 @@
+use codex_core::protocol::EventMsg;
+use codex_core::protocol::InputItem;
+use codex_core::protocol::Op;
+use codex_core::protocol::WarningEvent;
+use core_test_support::responses;
+use core_test_support::test_codex::TestCodex;
+use core_test_support::test_codex::test_codex;
+use core_test_support::wait_for_event;
+use responses::ev_assistant_message;
+use responses::ev_completed;
+use responses::sse;
+use responses::start_mock_server;
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn low_disk_space_warns_and_session_keeps_running() -> anyhow::Result<()> {
+    let server = start_mock_server().await;
+    responses::mount_sse_once(
+        &server,
+        |_req: &wiremock::Request| true,
+        sse(vec![ev_assistant_message("m1", "done"), ev_completed("r1")]),
+    )
+    .await;
+
+    // No disk has this much free space, so storage is always considered low.
+    let TestCodex {
+        codex, home: _home, ..
+    } = test_codex()
+        .with_config(|config| config.storage.min_free_mb = u64::MAX)
+        .build(&server)
+        .await?;
+
+    let EventMsg::Warning(WarningEvent { message }) =
+        wait_for_event(&codex, |ev| matches!(ev, EventMsg::Warning(_))).await
+    else {
+        unreachable!();
+    };
+    assert!(message.starts_with("Low disk space"), "{message}");
+
+    codex
+        .submit(Op::UserInput {
+            items: vec![InputItem::Text {
+                text: "hello".into(),
+            }],
+        })
+        .await?;
+    wait_for_event(&codex, |ev| matches!(ev, EventMsg::TaskComplete(_))).await;
+
+    Ok(())
+}
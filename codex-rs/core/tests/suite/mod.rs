@@ -1,20 +1,40 @@
 // Aggregates all former standalone integration tests as modules.
 
+mod auto_branch;
+mod automations;
+mod cancel_tool_call;
 mod cli_stream;
 mod client;
 mod compact;
 mod compact_resume_fork;
+mod completion_assessment;
+mod dry_run;
 mod exec;
 mod exec_stream_events;
 mod fork_conversation;
+mod ghost_snapshots;
+mod git_status_context;
 mod hooks;
 mod json_result;
 mod live_cli;
+mod locale_context;
+mod loop_detection;
+mod low_storage;
 mod model_overrides;
+mod permission_prompts;
 mod prompt_caching;
+mod prompt_preview;
+mod repeated_command_failure;
 mod review;
+mod rollout_format;
 mod rollout_list_find;
+mod scratch_dir;
 mod seatbelt;
+mod session_env;
+mod session_removal;
+mod steer;
 mod stream_error_allows_next_turn;
 mod stream_no_completed;
+mod task_budget;
+mod task_queue;
 mod user_notification;
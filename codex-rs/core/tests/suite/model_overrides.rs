@@ -1,11 +1,20 @@
 use codex_core::CodexAuth;
 use codex_core::ConversationManager;
+use codex_core::config_types::ModelAlias;
 use codex_core::protocol::EventMsg;
+use codex_core::protocol::InputItem;
 use codex_core::protocol::Op;
 use codex_core::protocol_config_types::ReasoningEffort;
 use core_test_support::load_default_config_for_test;
+use core_test_support::responses;
+use core_test_support::test_codex::TestCodex;
+use core_test_support::test_codex::test_codex;
 use core_test_support::wait_for_event;
 use pretty_assertions::assert_eq;
+use responses::ev_assistant_message;
+use responses::ev_completed;
+use responses::sse;
+use responses::start_mock_server;
 use tempfile::TempDir;
 
 const CONFIG_TOML: &str = "config.toml";
@@ -35,9 +44,11 @@ async fn override_turn_context_does_not_persist_when_config_exists() {
             cwd: None,
             approval_policy: None,
             sandbox_policy: None,
+            sandbox_preset: None,
             model: Some("o3".to_string()),
             effort: Some(Some(ReasoningEffort::High)),
             summary: None,
+            dry_run: None,
         })
         .await
         .expect("submit override");
@@ -75,9 +86,11 @@ async fn override_turn_context_does_not_create_config_file() {
             cwd: None,
             approval_policy: None,
             sandbox_policy: None,
+            sandbox_preset: None,
             model: Some("o3".to_string()),
             effort: Some(Some(ReasoningEffort::Medium)),
             summary: None,
+            dry_run: None,
         })
         .await
         .expect("submit override");
@@ -90,3 +103,59 @@ async fn override_turn_context_does_not_create_config_file() {
         "override should not create config.toml"
     );
 }
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn override_turn_context_resolves_model_alias() -> anyhow::Result<()> {
+    let server = start_mock_server().await;
+    responses::mount_sse_once(
+        &server,
+        |_req: &wiremock::Request| true,
+        sse(vec![ev_assistant_message("m1", "done"), ev_completed("r1")]),
+    )
+    .await;
+
+    let TestCodex {
+        codex, home: _home, ..
+    } = test_codex()
+        .with_config(|config| {
+            config.model_aliases.insert(
+                "fast".to_string(),
+                ModelAlias {
+                    model: "o3".to_string(),
+                    model_provider: None,
+                    model_reasoning_effort: Some(ReasoningEffort::Low),
+                },
+            );
+        })
+        .build(&server)
+        .await?;
+
+    codex
+        .submit(Op::OverrideTurnContext {
+            cwd: None,
+            approval_policy: None,
+            sandbox_policy: None,
+            sandbox_preset: None,
+            model: Some("fast".to_string()),
+            effort: None,
+            summary: None,
+            dry_run: None,
+        })
+        .await?;
+    codex
+        .submit(Op::UserInput {
+            items: vec![InputItem::Text {
+                text: "hello".into(),
+            }],
+        })
+        .await?;
+    wait_for_event(&codex, |ev| matches!(ev, EventMsg::TaskComplete(_))).await;
+
+    let requests = server.received_requests().await.unwrap_or_default();
+    assert_eq!(requests.len(), 1);
+    let body = requests[0].body_json::<serde_json::Value>()?;
+    assert_eq!(body["model"], "o3");
+    assert_eq!(body["reasoning"]["effort"], "low");
+
+    Ok(())
+}
@@ -0,0 +1,168 @@
+#![cfg(not(target_os = "windows"))]
+
+use std::path::Path;
+
+use codex_core::CodexConversation;
+use codex_core::protocol::AskForApproval;
+use codex_core::protocol::EventMsg;
+use codex_core::protocol::GuidedPermission;
+use codex_core::protocol::InputItem;
+use codex_core::protocol::Op;
+use codex_core::protocol::ReviewDecision;
+use codex_core::protocol::SandboxPolicy;
+use codex_protocol::config_types::ReasoningSummary;
+use core_test_support::non_sandbox_test;
+use core_test_support::responses;
+use core_test_support::test_codex::TestCodex;
+use core_test_support::test_codex::test_codex;
+use core_test_support::wait_for_event;
+use pretty_assertions::assert_eq;
+use responses::ev_assistant_message;
+use responses::ev_completed;
+use responses::ev_function_call;
+use responses::sse;
+use responses::start_mock_server;
+use serde_json::Value;
+use wiremock::MockServer;
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn denied_network_permission_is_saved_for_the_project() -> anyhow::Result<()> {
+    non_sandbox_test!(result);
+
+    let server = start_mock_server().await;
+    let TestCodex {
+        codex, cwd, home, ..
+    } = test_codex().build(&server).await?;
+
+    let marker = cwd.path().join("downloaded.txt");
+    let turn_id = start_download_turn(&codex, &server, cwd.path(), &marker).await?;
+
+    let EventMsg::PermissionConsentRequest(request) = wait_for_event(&codex, |ev| {
+        matches!(ev, EventMsg::PermissionConsentRequest(_))
+    })
+    .await
+    else {
+        unreachable!()
+    };
+    assert_eq!(request.call_id, "call-1");
+    assert_eq!(request.permission, GuidedPermission::Network);
+    assert!(request.detail.starts_with("curl -fsS"));
+
+    codex
+        .submit(Op::ExecApproval {
+            id: turn_id,
+            decision: ReviewDecision::Denied,
+        })
+        .await?;
+    wait_for_event(&codex, |ev| matches!(ev, EventMsg::TaskComplete(_))).await;
+
+    assert!(!marker.exists(), "denied command should not run");
+
+    let requests = server.received_requests().await.unwrap_or_default();
+    let output = requests
+        .iter()
+        .filter_map(|req| req.body_json::<Value>().ok())
+        .filter_map(|body| body["input"].as_array().cloned())
+        .flatten()
+        .find(|item| item["type"] == "function_call_output" && item["call_id"] == "call-1")
+        .and_then(|item| item["output"].as_str().map(str::to_string))
+        .unwrap_or_default();
+    assert!(
+        output.contains("has not allowed `network` for this project"),
+        "unexpected output: {output}"
+    );
+
+    let config: toml::Value =
+        toml::from_str(&std::fs::read_to_string(home.path().join("config.toml"))?)?;
+    let project = cwd.path().to_string_lossy();
+    assert_eq!(
+        config["projects"][project.as_ref()]["permissions"]["network"].as_str(),
+        Some("deny")
+    );
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn unanswered_permission_prompt_is_not_saved() -> anyhow::Result<()> {
+    non_sandbox_test!(result);
+
+    let server = start_mock_server().await;
+    let TestCodex {
+        codex, cwd, home, ..
+    } = test_codex().build(&server).await?;
+
+    let marker = cwd.path().join("downloaded.txt");
+    start_download_turn(&codex, &server, cwd.path(), &marker).await?;
+    wait_for_event(&codex, |ev| {
+        matches!(ev, EventMsg::PermissionConsentRequest(_))
+    })
+    .await;
+
+    codex.submit(Op::Interrupt).await?;
+    wait_for_event(&codex, |ev| matches!(ev, EventMsg::TurnAborted(_))).await;
+
+    assert!(!marker.exists(), "unanswered command should not run");
+    let config = std::fs::read_to_string(home.path().join("config.toml")).unwrap_or_default();
+    assert!(
+        !config.contains("permissions"),
+        "interrupted prompt was saved: {config}"
+    );
+
+    Ok(())
+}
+
+/// Starts a turn whose only tool call downloads to `marker` with curl, which
+/// prompts for the `network` permission.
+async fn start_download_turn(
+    codex: &CodexConversation,
+    server: &MockServer,
+    cwd: &Path,
+    marker: &Path,
+) -> anyhow::Result<String> {
+    let arguments = serde_json::to_string(&serde_json::json!({
+        "command": [
+            "/bin/bash",
+            "-c",
+            format!("curl -fsS http://127.0.0.1:9/ > {}", marker.display()),
+        ],
+    }))?;
+    responses::mount_sse_once(
+        server,
+        |req: &wiremock::Request| {
+            !String::from_utf8_lossy(&req.body).contains("function_call_output")
+        },
+        sse(vec![
+            ev_function_call("call-1", "shell", &arguments),
+            ev_completed("r1"),
+        ]),
+    )
+    .await;
+    responses::mount_sse_once(
+        server,
+        |req: &wiremock::Request| {
+            String::from_utf8_lossy(&req.body).contains("function_call_output")
+        },
+        sse(vec![ev_assistant_message("m2", "done"), ev_completed("r2")]),
+    )
+    .await;
+
+    let turn_id = codex
+        .submit(Op::UserTurn {
+            items: vec![InputItem::Text {
+                text: "download it".into(),
+            }],
+            cwd: cwd.to_path_buf(),
+            approval_policy: AskForApproval::OnRequest,
+            sandbox_policy: SandboxPolicy::DangerFullAccess,
+            model: "gpt-5".into(),
+            effort: None,
+            summary: ReasoningSummary::Auto,
+            final_output_json_schema: None,
+            max_turns: None,
+            max_total_tokens: None,
+            max_duration: None,
+        })
+        .await?;
+    Ok(turn_id)
+}
@@ -216,7 +216,13 @@ async fn prompt_tools_are_consistent_across_requests() {
 
     // our internal implementation is responsible for keeping tools in sync
     // with the OpenAI schema, so we just verify the tool presence here
-    let expected_tools_names: &[&str] = &["shell", "update_plan", "apply_patch", "view_image"];
+    let expected_tools_names: &[&str] = &[
+        "shell",
+        "update_plan",
+        "apply_patch",
+        "view_image",
+        "scratch_path",
+    ];
     let body0 = requests[0].body_json::<serde_json::Value>().unwrap();
     assert_eq!(
         body0["instructions"],
@@ -260,6 +266,10 @@ async fn prefixes_context_and_instructions_once_and_consistently_across_requests
     let codex_home = TempDir::new().unwrap();
     let mut config = load_default_config_for_test(&codex_home);
     config.cwd = cwd.path().to_path_buf();
+    // The scratch dir path and local time vary; keep them out of the
+    // expected context.
+    config.scratch_dir = false;
+    config.locale_context = false;
     config.model_provider = model_provider;
     config.user_instructions = Some("be consistent and helpful".to_string());
 
@@ -408,12 +418,15 @@ async fn overrides_turn_context_but_keeps_cached_prefix_and_key_constant() {
             sandbox_policy: Some(SandboxPolicy::WorkspaceWrite {
                 writable_roots: vec![writable.path().to_path_buf()],
                 network_access: true,
+                allowed_domains: vec![],
                 exclude_tmpdir_env_var: true,
                 exclude_slash_tmp: true,
             }),
+            sandbox_preset: None,
             model: Some("o3".to_string()),
             effort: Some(Some(ReasoningEffort::High)),
             summary: Some(ReasoningSummary::Detailed),
+            dry_run: None,
         })
         .await
         .unwrap();
@@ -540,6 +553,7 @@ async fn per_turn_overrides_keep_cached_prefix_and_key_constant() {
             sandbox_policy: SandboxPolicy::WorkspaceWrite {
                 writable_roots: vec![writable.path().to_path_buf()],
                 network_access: true,
+                allowed_domains: vec![],
                 exclude_tmpdir_env_var: true,
                 exclude_slash_tmp: true,
             },
@@ -547,6 +561,9 @@ async fn per_turn_overrides_keep_cached_prefix_and_key_constant() {
             effort: Some(ReasoningEffort::High),
             summary: ReasoningSummary::Detailed,
             final_output_json_schema: None,
+            max_turns: None,
+            max_total_tokens: None,
+            max_duration: None,
         })
         .await
         .unwrap();
@@ -627,6 +644,10 @@ async fn send_user_turn_with_no_changes_does_not_send_environment_context() {
     let codex_home = TempDir::new().unwrap();
     let mut config = load_default_config_for_test(&codex_home);
     config.cwd = cwd.path().to_path_buf();
+    // The scratch dir path and local time vary; keep them out of the
+    // expected context.
+    config.scratch_dir = false;
+    config.locale_context = false;
     config.model_provider = model_provider;
     config.user_instructions = Some("be consistent and helpful".to_string());
 
@@ -657,6 +678,9 @@ async fn send_user_turn_with_no_changes_does_not_send_environment_context() {
             effort: default_effort,
             summary: default_summary,
             final_output_json_schema: None,
+            max_turns: None,
+            max_total_tokens: None,
+            max_duration: None,
         })
         .await
         .unwrap();
@@ -674,6 +698,9 @@ async fn send_user_turn_with_no_changes_does_not_send_environment_context() {
             effort: default_effort,
             summary: default_summary,
             final_output_json_schema: None,
+            max_turns: None,
+            max_total_tokens: None,
+            max_duration: None,
         })
         .await
         .unwrap();
@@ -740,6 +767,10 @@ async fn send_user_turn_with_changes_sends_environment_context() {
     let codex_home = TempDir::new().unwrap();
     let mut config = load_default_config_for_test(&codex_home);
     config.cwd = cwd.path().to_path_buf();
+    // The scratch dir path and local time vary; keep them out of the
+    // expected context.
+    config.scratch_dir = false;
+    config.locale_context = false;
     config.model_provider = model_provider;
     config.user_instructions = Some("be consistent and helpful".to_string());
 
@@ -770,6 +801,9 @@ async fn send_user_turn_with_changes_sends_environment_context() {
             effort: default_effort,
             summary: default_summary,
             final_output_json_schema: None,
+            max_turns: None,
+            max_total_tokens: None,
+            max_duration: None,
         })
         .await
         .unwrap();
@@ -787,6 +821,9 @@ async fn send_user_turn_with_changes_sends_environment_context() {
             effort: Some(ReasoningEffort::High),
             summary: ReasoningSummary::Detailed,
             final_output_json_schema: None,
+            max_turns: None,
+            max_total_tokens: None,
+            max_duration: None,
         })
         .await
         .unwrap();
@@ -0,0 +1,91 @@
+use codex_core::protocol::EventMsg;
+use codex_core::protocol::InputItem;
+use codex_core::protocol::Op;
+use codex_core::protocol::PromptSectionKind;
+use core_test_support::responses::ev_assistant_message;
+use core_test_support::responses::ev_completed;
+use core_test_support::responses::mount_sse_once;
+use core_test_support::responses::sse;
+use core_test_support::responses::start_mock_server;
+use core_test_support::test_codex::TestCodex;
+use core_test_support::test_codex::test_codex;
+use core_test_support::wait_for_event;
+use pretty_assertions::assert_eq;
+use wiremock::matchers::any;
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn next_prompt_preview_matches_what_the_model_receives() -> anyhow::Result<()> {
+    let server = start_mock_server().await;
+    mount_sse_once(
+        &server,
+        any(),
+        sse(vec![ev_assistant_message("m1", "done"), ev_completed("r1")]),
+    )
+    .await;
+    let TestCodex {
+        codex,
+        cwd,
+        home: _home,
+        ..
+    } = test_codex().build(&server).await?;
+
+    codex
+        .submit(Op::UserInput {
+            items: vec![InputItem::Text {
+                text: "hello world".into(),
+            }],
+        })
+        .await?;
+    wait_for_event(&codex, |ev| matches!(ev, EventMsg::TaskComplete(_))).await;
+
+    codex.submit(Op::GetNextPromptPreview).await?;
+    let EventMsg::NextPromptPreview(preview) =
+        wait_for_event(&codex, |ev| matches!(ev, EventMsg::NextPromptPreview(_))).await
+    else {
+        unreachable!()
+    };
+
+    let requests = server.received_requests().await.unwrap_or_default();
+    let body = requests[0].body_json::<serde_json::Value>()?;
+    let section = |kind| {
+        preview
+            .sections
+            .iter()
+            .find(|section| section.kind == kind)
+            .map(|section| section.content.as_str())
+            .unwrap_or_default()
+    };
+
+    assert_eq!(
+        Some(section(PromptSectionKind::Instructions)),
+        body["instructions"].as_str()
+    );
+    assert!(
+        section(PromptSectionKind::EnvironmentContext).contains(&cwd.path().display().to_string())
+    );
+    assert_eq!(
+        section(PromptSectionKind::History),
+        "user: hello world\nassistant: done"
+    );
+    let tool_names: Vec<&str> = body["tools"]
+        .as_array()
+        .map(|tools| {
+            tools
+                .iter()
+                .map(|tool| {
+                    tool["name"]
+                        .as_str()
+                        .unwrap_or(tool["type"].as_str().unwrap_or_default())
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    let preview_tools: Vec<&str> = section(PromptSectionKind::Tools)
+        .lines()
+        .map(|line| line.split(':').next().unwrap_or_default())
+        .collect();
+    assert_eq!(preview_tools, tool_names);
+    assert!(preview.estimated_tokens > 0);
+
+    Ok(())
+}
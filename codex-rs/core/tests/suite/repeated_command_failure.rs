@@ -0,0 +1,109 @@
+#![cfg(not(target_os = "windows"))]
+
+use codex_core::protocol::AskForApproval;
+use codex_core::protocol::EventMsg;
+use codex_core::protocol::InputItem;
+use codex_core::protocol::Op;
+use codex_core::protocol::SandboxPolicy;
+use core_test_support::non_sandbox_test;
+use core_test_support::responses;
+use core_test_support::test_codex::TestCodex;
+use core_test_support::test_codex::test_codex;
+use core_test_support::wait_for_event;
+use pretty_assertions::assert_eq;
+use responses::ev_assistant_message;
+use responses::ev_completed;
+use responses::ev_function_call;
+use responses::sse;
+use responses::start_mock_server;
+use serde_json::Value;
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn repeated_identical_failure_is_not_rerun() -> anyhow::Result<()> {
+    non_sandbox_test!(result);
+
+    let server = start_mock_server().await;
+
+    // The model issues the same failing command three times; only the first
+    // two should actually run.
+    let args = serde_json::json!({
+        "command": ["/bin/bash", "-c", "echo still broken; exit 1"],
+    })
+    .to_string();
+    for turn in 0..4 {
+        let body = if turn < 3 {
+            sse(vec![
+                ev_function_call(&format!("call-{turn}"), "shell", &args),
+                ev_completed(&format!("r{turn}")),
+            ])
+        } else {
+            sse(vec![
+                ev_assistant_message("m", "giving up"),
+                ev_completed("r3"),
+            ])
+        };
+        responses::mount_sse_once(
+            &server,
+            move |req: &wiremock::Request| {
+                String::from_utf8_lossy(&req.body)
+                    .matches("\"function_call_output\"")
+                    .count()
+                    == turn
+            },
+            body,
+        )
+        .await;
+    }
+
+    let TestCodex {
+        codex, cwd: _cwd, ..
+    } = test_codex()
+        .with_config(|config| {
+            config.approval_policy = AskForApproval::Never;
+            config.sandbox_policy = SandboxPolicy::DangerFullAccess;
+            config.repeated_command_failure_limit = 2;
+        })
+        .build(&server)
+        .await?;
+
+    codex
+        .submit(Op::UserInput {
+            items: vec![InputItem::Text {
+                text: "run it".into(),
+            }],
+        })
+        .await?;
+
+    let mut exec_begins = 0;
+    loop {
+        match wait_for_event(&codex, |_| true).await {
+            EventMsg::ExecCommandBegin(_) => exec_begins += 1,
+            EventMsg::TaskComplete(_) => break,
+            _ => {}
+        }
+    }
+    assert_eq!(exec_begins, 2);
+
+    let requests = server.received_requests().await.unwrap_or_default();
+    let last = requests
+        .last()
+        .map(wiremock::Request::body_json::<Value>)
+        .transpose()?;
+    let output = last
+        .as_ref()
+        .and_then(|body| body["input"].as_array())
+        .and_then(|items| {
+            items
+                .iter()
+                .find(|item| item["call_id"] == "call-2" && item["type"] == "function_call_output")
+        })
+        .and_then(|item| item["output"].as_str())
+        .unwrap_or_default();
+    assert!(
+        output.starts_with("command not run: it already failed 2 times"),
+        "unexpected output: {output}"
+    );
+    assert!(output.contains("still broken"));
+
+    Ok(())
+}
@@ -0,0 +1,82 @@
+use codex_core::parse_rollout_line;
+use codex_core::protocol::ConversationPathResponseEvent;
+use codex_core::protocol::EventMsg;
+use codex_core::protocol::InputItem;
+use codex_core::protocol::Op;
+use codex_core::protocol::ROLLOUT_SCHEMA_VERSION;
+use codex_core::protocol::RolloutItem;
+use core_test_support::responses;
+use core_test_support::test_codex::TestCodex;
+use core_test_support::test_codex::test_codex;
+use core_test_support::wait_for_event;
+use pretty_assertions::assert_eq;
+use responses::ev_assistant_message;
+use responses::ev_completed;
+use responses::sse;
+use responses::start_mock_server;
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn rollout_lines_carry_schema_version_and_turn_id() -> anyhow::Result<()> {
+    let server = start_mock_server().await;
+    responses::mount_sse_once(
+        &server,
+        |_req: &wiremock::Request| true,
+        sse(vec![ev_assistant_message("m1", "done"), ev_completed("r1")]),
+    )
+    .await;
+
+    let TestCodex {
+        codex, home: _home, ..
+    } = test_codex().build(&server).await?;
+
+    let sub_id = codex
+        .submit(Op::UserInput {
+            items: vec![InputItem::Text {
+                text: "hello".into(),
+            }],
+        })
+        .await?;
+    wait_for_event(&codex, |ev| matches!(ev, EventMsg::TaskComplete(_))).await;
+
+    codex.submit(Op::GetPath).await?;
+    let EventMsg::ConversationPath(ConversationPathResponseEvent { path, .. }) =
+        wait_for_event(&codex, |ev| matches!(ev, EventMsg::ConversationPath(_))).await
+    else {
+        unreachable!();
+    };
+    let text = std::fs::read_to_string(&path)?;
+
+    let mut saw_meta = false;
+    let mut saw_user_message = false;
+    let mut saw_assistant_message = false;
+    for line in text.lines().filter(|l| !l.trim().is_empty()) {
+        let value: serde_json::Value = serde_json::from_str(line)?;
+        assert_eq!(
+            value["schema_version"],
+            serde_json::json!(ROLLOUT_SCHEMA_VERSION)
+        );
+        let Some(rollout_line) = parse_rollout_line(line)? else {
+            continue;
+        };
+        match rollout_line.item {
+            RolloutItem::SessionMeta(_) => {
+                assert_eq!(rollout_line.turn_id, None);
+                saw_meta = true;
+            }
+            RolloutItem::EventMsg(EventMsg::UserMessage(_)) => {
+                assert_eq!(rollout_line.turn_id.as_deref(), Some(sub_id.as_str()));
+                saw_user_message = true;
+            }
+            RolloutItem::EventMsg(EventMsg::AgentMessage(_)) => {
+                assert_eq!(rollout_line.turn_id.as_deref(), Some(sub_id.as_str()));
+                saw_assistant_message = true;
+            }
+            _ => {}
+        }
+    }
+    assert!(saw_meta, "session meta missing from rollout");
+    assert!(saw_user_message, "user message missing from rollout");
+    assert!(saw_assistant_message, "agent message missing from rollout");
+
+    Ok(())
+}
@@ -0,0 +1,87 @@
+#![cfg(not(target_os = "windows"))]
+
+use std::path::PathBuf;
+
+use codex_core::protocol::EventMsg;
+use codex_core::protocol::InputItem;
+use codex_core::protocol::Op;
+use core_test_support::non_sandbox_test;
+use core_test_support::responses;
+use core_test_support::test_codex::TestCodex;
+use core_test_support::test_codex::test_codex;
+use core_test_support::wait_for_event;
+use responses::ev_assistant_message;
+use responses::ev_completed;
+use responses::ev_function_call;
+use responses::sse;
+use responses::start_mock_server;
+use serde_json::Value;
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn scratch_path_is_advertised_and_removed_on_shutdown() -> anyhow::Result<()> {
+    non_sandbox_test!(result);
+
+    let server = start_mock_server().await;
+    let TestCodex { codex, .. } = test_codex().build(&server).await?;
+
+    responses::mount_sse_once(
+        &server,
+        |req: &wiremock::Request| {
+            !String::from_utf8_lossy(&req.body).contains("function_call_output")
+        },
+        sse(vec![
+            ev_function_call("call-1", "scratch_path", "{}"),
+            ev_completed("r1"),
+        ]),
+    )
+    .await;
+    responses::mount_sse_once(
+        &server,
+        |req: &wiremock::Request| {
+            String::from_utf8_lossy(&req.body).contains("function_call_output")
+        },
+        sse(vec![ev_assistant_message("m2", "done"), ev_completed("r2")]),
+    )
+    .await;
+
+    codex
+        .submit(Op::UserInput {
+            items: vec![InputItem::Text {
+                text: "where can I write?".into(),
+            }],
+        })
+        .await?;
+    wait_for_event(&codex, |ev| matches!(ev, EventMsg::TaskComplete(_))).await;
+
+    let requests = server.received_requests().await.unwrap_or_default();
+    let inputs: Vec<Value> = requests
+        .iter()
+        .filter_map(|req| req.body_json::<Value>().ok())
+        .filter_map(|body| body["input"].as_array().cloned())
+        .flatten()
+        .collect();
+    let scratch = inputs
+        .iter()
+        .find(|item| item["type"] == "function_call_output" && item["call_id"] == "call-1")
+        .and_then(|item| item["output"].as_str())
+        .map(PathBuf::from)
+        .expect("scratch_path output");
+    assert!(scratch.is_dir(), "scratch dir should exist: {scratch:?}");
+
+    let advertised = format!("<scratch_dir>{}</scratch_dir>", scratch.display());
+    assert!(
+        inputs.iter().any(|item| item["content"][0]["text"]
+            .as_str()
+            .is_some_and(|text| text.contains(&advertised))),
+        "environment context should mention {advertised}"
+    );
+
+    codex.submit(Op::Shutdown).await?;
+    wait_for_event(&codex, |ev| matches!(ev, EventMsg::ShutdownComplete)).await;
+    assert!(
+        !scratch.exists(),
+        "scratch dir should be removed on shutdown"
+    );
+
+    Ok(())
+}
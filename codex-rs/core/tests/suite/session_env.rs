@@ -0,0 +1,80 @@
+use codex_core::protocol::EventMsg;
+use codex_core::protocol::Op;
+use core_test_support::responses::start_mock_server;
+use core_test_support::test_codex::TestCodex;
+use core_test_support::test_codex::test_codex;
+use core_test_support::wait_for_event;
+use pretty_assertions::assert_eq;
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn session_env_overrides_are_reported() -> anyhow::Result<()> {
+    let server = start_mock_server().await;
+    let TestCodex { codex, .. } = test_codex().build(&server).await?;
+
+    codex
+        .submit(Op::SetSessionEnv {
+            key: "CODEX_TEST_SESSION_VAR".to_string(),
+            value: "1".to_string(),
+            approved: false,
+        })
+        .await?;
+    codex
+        .submit(Op::UnsetSessionEnv {
+            key: "CODEX_TEST_REMOVED_VAR".to_string(),
+        })
+        .await?;
+    codex.submit(Op::GetSessionEnv).await?;
+
+    let EventMsg::SessionEnvResponse(ev) =
+        wait_for_event(&codex, |ev| matches!(ev, EventMsg::SessionEnvResponse(_))).await
+    else {
+        unreachable!()
+    };
+    assert_eq!(
+        ev.env.get("CODEX_TEST_SESSION_VAR").map(String::as_str),
+        Some("1")
+    );
+    assert!(!ev.env.contains_key("CODEX_TEST_REMOVED_VAR"));
+    assert_eq!(ev.overrides.get("CODEX_TEST_REMOVED_VAR"), Some(&None));
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn sensitive_session_env_requires_approval() -> anyhow::Result<()> {
+    let server = start_mock_server().await;
+    let TestCodex { codex, .. } = test_codex().build(&server).await?;
+
+    codex
+        .submit(Op::SetSessionEnv {
+            key: "LD_PRELOAD".to_string(),
+            value: "/tmp/evil.so".to_string(),
+            approved: false,
+        })
+        .await?;
+    let EventMsg::Error(err) = wait_for_event(&codex, |ev| matches!(ev, EventMsg::Error(_))).await
+    else {
+        unreachable!()
+    };
+    assert!(err.message.contains("requires approval"));
+
+    codex
+        .submit(Op::SetSessionEnv {
+            key: "LD_PRELOAD".to_string(),
+            value: "/tmp/approved.so".to_string(),
+            approved: true,
+        })
+        .await?;
+    codex.submit(Op::GetSessionEnv).await?;
+    let EventMsg::SessionEnvResponse(ev) =
+        wait_for_event(&codex, |ev| matches!(ev, EventMsg::SessionEnvResponse(_))).await
+    else {
+        unreachable!()
+    };
+    assert_eq!(
+        ev.env.get("LD_PRELOAD").map(String::as_str),
+        Some("/tmp/approved.so")
+    );
+
+    Ok(())
+}
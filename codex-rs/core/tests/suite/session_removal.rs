@@ -0,0 +1,93 @@
+use codex_core::ARCHIVED_SESSIONS_SUBDIR;
+use codex_core::TRASHED_SESSIONS_SUBDIR;
+use codex_core::list_trashed_sessions;
+use codex_core::protocol::ConversationPathResponseEvent;
+use codex_core::protocol::EventMsg;
+use codex_core::protocol::Op;
+use codex_core::protocol::SessionDisposition;
+use codex_core::protocol::SessionRemovedEvent;
+use codex_core::restore_trashed_session;
+use core_test_support::responses::start_mock_server;
+use core_test_support::test_codex::TestCodex;
+use core_test_support::test_codex::test_codex;
+use core_test_support::wait_for_event;
+use pretty_assertions::assert_eq;
+
+async fn rollout_path(codex: &codex_core::CodexConversation) -> anyhow::Result<std::path::PathBuf> {
+    codex.submit(Op::GetPath).await?;
+    let EventMsg::ConversationPath(ConversationPathResponseEvent { path, .. }) =
+        wait_for_event(codex, |ev| matches!(ev, EventMsg::ConversationPath(_))).await
+    else {
+        unreachable!();
+    };
+    Ok(path)
+}
+
+async fn remove_session(
+    codex: &codex_core::CodexConversation,
+    op: Op,
+) -> anyhow::Result<SessionRemovedEvent> {
+    codex.submit(op).await?;
+    let EventMsg::SessionRemoved(removed) =
+        wait_for_event(codex, |ev| matches!(ev, EventMsg::SessionRemoved(_))).await
+    else {
+        unreachable!();
+    };
+    wait_for_event(codex, |ev| matches!(ev, EventMsg::ShutdownComplete)).await;
+    Ok(removed)
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn delete_session_moves_rollout_to_trash_and_can_be_restored() -> anyhow::Result<()> {
+    let server = start_mock_server().await;
+    let TestCodex {
+        codex,
+        home,
+        session_configured,
+        ..
+    } = test_codex().build(&server).await?;
+    let original = rollout_path(&codex).await?;
+
+    let removed = remove_session(&codex, Op::DeleteSession).await?;
+    assert_eq!(removed.disposition, SessionDisposition::Trashed);
+    assert_eq!(removed.conversation_id, session_configured.session_id);
+    assert!(
+        removed
+            .path
+            .starts_with(home.path().join(TRASHED_SESSIONS_SUBDIR))
+    );
+    assert!(removed.path.exists());
+    assert!(!original.exists());
+
+    let trashed = list_trashed_sessions(home.path()).await?;
+    assert_eq!(trashed.len(), 1);
+    assert_eq!(trashed[0].path, removed.path);
+
+    let id = session_configured.session_id.to_string();
+    let restored = restore_trashed_session(home.path(), &id).await?;
+    assert_eq!(restored, Some(original.clone()));
+    assert!(original.exists());
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn archive_session_moves_rollout_to_archive() -> anyhow::Result<()> {
+    let server = start_mock_server().await;
+    let TestCodex { codex, home, .. } = test_codex().build(&server).await?;
+    let original = rollout_path(&codex).await?;
+
+    let removed = remove_session(&codex, Op::ArchiveSession).await?;
+    assert_eq!(removed.disposition, SessionDisposition::Archived);
+    assert_eq!(
+        removed.path,
+        home.path()
+            .join(ARCHIVED_SESSIONS_SUBDIR)
+            .join(original.file_name().unwrap_or_default())
+    );
+    assert!(removed.path.exists());
+    assert!(!original.exists());
+    assert!(list_trashed_sessions(home.path()).await?.is_empty());
+
+    Ok(())
+}
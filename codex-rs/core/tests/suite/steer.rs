@@ -0,0 +1,158 @@
+#![cfg(not(target_os = "windows"))]
+
+use codex_core::protocol::AskForApproval;
+use codex_core::protocol::EventMsg;
+use codex_core::protocol::InputItem;
+use codex_core::protocol::Op;
+use codex_core::protocol::SandboxPolicy;
+use core_test_support::non_sandbox_test;
+use core_test_support::responses;
+use core_test_support::test_codex::TestCodex;
+use core_test_support::test_codex::test_codex;
+use core_test_support::wait_for_event;
+use pretty_assertions::assert_eq;
+use responses::ev_assistant_message;
+use responses::ev_completed;
+use responses::ev_function_call;
+use responses::sse;
+use responses::start_mock_server;
+use serde_json::Value;
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn steering_reaches_the_model_at_the_next_tool_call_boundary() -> anyhow::Result<()> {
+    non_sandbox_test!(result);
+
+    let server = start_mock_server().await;
+
+    let args = serde_json::json!({
+        "command": ["/bin/bash", "-c", "sleep 1"],
+    })
+    .to_string();
+    responses::mount_sse_once(
+        &server,
+        |req: &wiremock::Request| {
+            !String::from_utf8_lossy(&req.body).contains("\"function_call_output\"")
+        },
+        sse(vec![
+            ev_function_call("call-sleep", "shell", &args),
+            ev_completed("r1"),
+        ]),
+    )
+    .await;
+    responses::mount_sse_once(
+        &server,
+        |req: &wiremock::Request| {
+            String::from_utf8_lossy(&req.body).contains("\"function_call_output\"")
+        },
+        sse(vec![
+            ev_assistant_message("m", "switching to the retry helper"),
+            ev_completed("r2"),
+        ]),
+    )
+    .await;
+
+    let TestCodex { codex, .. } = test_codex()
+        .with_config(|config| {
+            config.approval_policy = AskForApproval::Never;
+            config.sandbox_policy = SandboxPolicy::DangerFullAccess;
+        })
+        .build(&server)
+        .await?;
+
+    codex
+        .submit(Op::UserInput {
+            items: vec![InputItem::Text {
+                text: "fix the flaky test".into(),
+            }],
+        })
+        .await?;
+    wait_for_event(&codex, |ev| matches!(ev, EventMsg::ExecCommandBegin(_))).await;
+
+    codex
+        .submit(Op::Steer {
+            items: vec![InputItem::Text {
+                text: "use the existing retry helper".into(),
+            }],
+        })
+        .await?;
+
+    wait_for_event(&codex, |ev| matches!(ev, EventMsg::TaskComplete(_))).await;
+
+    let requests = server.received_requests().await.unwrap_or_default();
+    assert_eq!(
+        requests.len(),
+        2,
+        "steering must not start a task of its own"
+    );
+    let body = requests[1].body_json::<Value>()?;
+    let input = body["input"].as_array().cloned().unwrap_or_default();
+    let output_index = input
+        .iter()
+        .position(|item| item["type"] == "function_call_output")
+        .expect("tool output in second request");
+    let steering = &input[output_index + 1];
+    assert_eq!(steering["role"], "developer");
+    assert_eq!(
+        steering["content"][0]["text"],
+        "use the existing retry helper"
+    );
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn steering_without_a_running_turn_starts_a_new_turn() -> anyhow::Result<()> {
+    non_sandbox_test!(result);
+
+    let server = start_mock_server().await;
+    responses::mount_sse_once(
+        &server,
+        |_: &wiremock::Request| true,
+        sse(vec![
+            ev_assistant_message("m", "using the retry helper"),
+            ev_completed("r1"),
+        ]),
+    )
+    .await;
+
+    let TestCodex { codex, .. } = test_codex().build(&server).await?;
+
+    codex
+        .submit(Op::Steer {
+            items: vec![InputItem::Text {
+                text: "use the existing retry helper".into(),
+            }],
+        })
+        .await?;
+
+    let EventMsg::BackgroundEvent(notice) = wait_for_event(
+        &codex,
+        |ev| matches!(ev, EventMsg::BackgroundEvent(notice) if notice.message.contains("steer")),
+    )
+    .await
+    else {
+        unreachable!("waited for a background event");
+    };
+    assert_eq!(
+        notice.message,
+        "No turn is running to steer; starting a new turn with the guidance."
+    );
+    wait_for_event(&codex, |ev| matches!(ev, EventMsg::TaskComplete(_))).await;
+
+    let requests = server.received_requests().await.unwrap_or_default();
+    assert_eq!(requests.len(), 1);
+    let body = requests[0].body_json::<Value>()?;
+    let input = body["input"].as_array().cloned().unwrap_or_default();
+    let guidance = input
+        .iter()
+        .rev()
+        .find(|item| item["type"] == "message")
+        .expect("guidance in the request");
+    assert_eq!(guidance["role"], "user");
+    assert_eq!(
+        guidance["content"][0]["text"],
+        "use the existing retry helper"
+    );
+
+    Ok(())
+}
@@ -0,0 +1,93 @@
+#![cfg(not(target_os = "windows"))]
+
+use std::time::Duration;
+use std::time::Instant;
+
+use codex_core::protocol::AskForApproval;
+use codex_core::protocol::EventMsg;
+use codex_core::protocol::InputItem;
+use codex_core::protocol::Op;
+use codex_core::protocol::SandboxPolicy;
+use codex_core::protocol::TaskBudgetLimit;
+use codex_core::protocol::TurnAbortReason;
+use codex_protocol::config_types::ReasoningSummary;
+use core_test_support::non_sandbox_test;
+use core_test_support::responses;
+use core_test_support::test_codex::TestCodex;
+use core_test_support::test_codex::test_codex;
+use core_test_support::wait_for_event;
+use pretty_assertions::assert_eq;
+use responses::ev_completed;
+use responses::ev_function_call;
+use responses::sse;
+use responses::start_mock_server;
+
+/// A turn whose tool call runs past `max_duration` is stopped at the
+/// deadline instead of when the call finishes.
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn turn_running_past_max_duration_is_aborted() -> anyhow::Result<()> {
+    non_sandbox_test!(result);
+
+    let server = start_mock_server().await;
+
+    let args = serde_json::json!({
+        "command": ["/bin/bash", "-c", "sleep 30"],
+        "timeout_ms": 60_000,
+    })
+    .to_string();
+    responses::mount_sse_once(
+        &server,
+        |req: &wiremock::Request| {
+            !String::from_utf8_lossy(&req.body).contains("\"function_call_output\"")
+        },
+        sse(vec![
+            ev_function_call("call-sleep", "shell", &args),
+            ev_completed("r1"),
+        ]),
+    )
+    .await;
+
+    let TestCodex { codex, cwd, .. } = test_codex().build(&server).await?;
+
+    let started = Instant::now();
+    codex
+        .submit(Op::UserTurn {
+            items: vec![InputItem::Text {
+                text: "wait a while".into(),
+            }],
+            cwd: cwd.path().to_path_buf(),
+            approval_policy: AskForApproval::Never,
+            sandbox_policy: SandboxPolicy::DangerFullAccess,
+            model: "gpt-5".into(),
+            effort: None,
+            summary: ReasoningSummary::Auto,
+            final_output_json_schema: None,
+            max_turns: None,
+            max_total_tokens: None,
+            max_duration: Some(Duration::from_secs(2)),
+        })
+        .await?;
+    wait_for_event(&codex, |ev| matches!(ev, EventMsg::ExecCommandBegin(_))).await;
+
+    let aborted = loop {
+        match wait_for_event(&codex, |_| true).await {
+            EventMsg::TurnAborted(aborted) => break aborted,
+            EventMsg::TaskComplete(_) => panic!("the turn should not complete"),
+            _ => {}
+        }
+    };
+    assert!(
+        started.elapsed() < Duration::from_secs(20),
+        "the turn should stop at the deadline, not when the command finishes"
+    );
+    assert_eq!(aborted.reason, TurnAbortReason::BudgetExceeded);
+    assert_eq!(aborted.exceeded_limit, Some(TaskBudgetLimit::MaxDuration));
+    assert_eq!(aborted.budget_allowed, Some(2.0));
+    assert!(aborted.budget_used.is_some_and(|used| used >= 2.0));
+
+    // The deadline ended the task before another model request.
+    let requests = server.received_requests().await.unwrap_or_default();
+    assert_eq!(requests.len(), 1);
+
+    Ok(())
+}
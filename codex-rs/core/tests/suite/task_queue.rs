@@ -0,0 +1,153 @@
+use std::time::Duration;
+
+use codex_core::CodexConversation;
+use codex_core::config_types::BusyInput;
+use codex_core::protocol::Event;
+use codex_core::protocol::EventMsg;
+use codex_core::protocol::InputItem;
+use codex_core::protocol::Op;
+use core_test_support::non_sandbox_test;
+use core_test_support::responses;
+use core_test_support::test_codex::TestCodex;
+use core_test_support::test_codex::test_codex;
+use core_test_support::wait_for_event;
+use pretty_assertions::assert_eq;
+use responses::ev_assistant_message;
+use responses::ev_completed;
+use responses::sse;
+use responses::start_mock_server;
+use wiremock::Mock;
+use wiremock::MockServer;
+use wiremock::matchers::method;
+use wiremock::matchers::path;
+
+fn user_input(text: &str) -> Op {
+    Op::UserInput {
+        items: vec![InputItem::Text { text: text.into() }],
+    }
+}
+
+/// Answers the request that contains `text` but not `later` after `delay_ms`.
+async fn mount_reply(
+    server: &MockServer,
+    text: &'static str,
+    later: Option<&'static str>,
+    delay_ms: u64,
+) {
+    Mock::given(method("POST"))
+        .and(path("/v1/responses"))
+        .and(move |req: &wiremock::Request| {
+            let body = std::str::from_utf8(&req.body).unwrap_or("");
+            body.contains(text) && !later.is_some_and(|later| body.contains(later))
+        })
+        .respond_with(
+            responses::sse_response(sse(vec![
+                ev_assistant_message("m1", &format!("done: {text}")),
+                ev_completed("r1"),
+            ]))
+            .set_delay(Duration::from_millis(delay_ms)),
+        )
+        .mount(server)
+        .await;
+}
+
+/// Events up to and including the `TaskComplete` of submission `id`.
+async fn events_until_complete(codex: &CodexConversation, id: &str) -> Vec<Event> {
+    let mut events = Vec::new();
+    loop {
+        let event = tokio::time::timeout(Duration::from_secs(10), codex.next_event())
+            .await
+            .expect("timeout waiting for event")
+            .expect("stream ended unexpectedly");
+        let done = event.id == id && matches!(event.msg, EventMsg::TaskComplete(_));
+        events.push(event);
+        if done {
+            return events;
+        }
+    }
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn input_while_busy_runs_as_its_own_task_afterwards() -> anyhow::Result<()> {
+    non_sandbox_test!(result);
+
+    let server = start_mock_server().await;
+    mount_reply(&server, "first task", Some("second task"), 500).await;
+    mount_reply(&server, "second task", None, 0).await;
+    let TestCodex { codex, .. } = test_codex()
+        .with_config(|config| config.busy_input = BusyInput::Queue)
+        .build(&server)
+        .await?;
+
+    let first = codex.submit(user_input("first task")).await?;
+    wait_for_event(&codex, |ev| matches!(ev, EventMsg::TaskStarted(_))).await;
+    let second = codex.submit(user_input("second task")).await?;
+
+    let EventMsg::TaskQueueUpdated(queued) =
+        wait_for_event(&codex, |ev| matches!(ev, EventMsg::TaskQueueUpdated(_))).await
+    else {
+        unreachable!()
+    };
+    assert_eq!(
+        queued
+            .tasks
+            .iter()
+            .map(|task| task.id.as_str())
+            .collect::<Vec<_>>(),
+        vec![second.as_str()]
+    );
+
+    events_until_complete(&codex, &first).await;
+    let events = events_until_complete(&codex, &second).await;
+    assert!(events.iter().any(|event| event.id == second
+        && matches!(&event.msg, EventMsg::TaskQueueUpdated(update) if update.tasks.is_empty())));
+    assert!(
+        events
+            .iter()
+            .any(|event| event.id == second && matches!(event.msg, EventMsg::TaskStarted(_)))
+    );
+
+    // The second input was not added to the first task's request.
+    let requests = server.received_requests().await.unwrap_or_default();
+    assert_eq!(requests.len(), 2);
+    assert!(!String::from_utf8_lossy(&requests[0].body).contains("second task"));
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn cancelled_queued_task_never_runs() -> anyhow::Result<()> {
+    non_sandbox_test!(result);
+
+    let server = start_mock_server().await;
+    mount_reply(&server, "first task", Some("third task"), 500).await;
+    mount_reply(&server, "third task", None, 0).await;
+    let TestCodex { codex, .. } = test_codex()
+        .with_config(|config| config.busy_input = BusyInput::Queue)
+        .build(&server)
+        .await?;
+
+    let first = codex.submit(user_input("first task")).await?;
+    wait_for_event(&codex, |ev| matches!(ev, EventMsg::TaskStarted(_))).await;
+    let second = codex.submit(user_input("second task")).await?;
+    let third = codex.submit(user_input("third task")).await?;
+    codex.submit(Op::CancelQueuedTask { id: second }).await?;
+
+    // The queue goes [second], [second, third], then [third].
+    wait_for_event(&codex, |ev| {
+        matches!(ev, EventMsg::TaskQueueUpdated(update)
+            if update.tasks.iter().map(|task| task.id.as_str()).eq([third.as_str()]))
+    })
+    .await;
+
+    events_until_complete(&codex, &first).await;
+    events_until_complete(&codex, &third).await;
+
+    let requests = server.received_requests().await.unwrap_or_default();
+    assert_eq!(requests.len(), 2);
+    assert!(
+        requests
+            .iter()
+            .all(|req| !String::from_utf8_lossy(&req.body).contains("second task"))
+    );
+    Ok(())
+}
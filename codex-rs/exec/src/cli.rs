@@ -22,9 +22,10 @@ pub struct Cli {
     pub oss: bool,
 
     /// Select the sandbox policy to use when executing model-generated shell
-    /// commands.
-    #[arg(long = "sandbox", short = 's', value_enum)]
-    pub sandbox_mode: Option<codex_common::SandboxModeCliArg>,
+    /// commands: read-only, workspace-write, danger-full-access, or the name
+    /// of a `[sandbox_presets]` entry in config.toml.
+    #[arg(long = "sandbox", short = 's')]
+    pub sandbox_mode: Option<codex_common::SandboxCliArg>,
 
     /// Configuration profile from config.toml to specify default options.
     #[arg(long = "profile", short = 'p')]
@@ -44,6 +45,11 @@ pub struct Cli {
     )]
     pub dangerously_bypass_approvals_and_sandbox: bool,
 
+    /// Report the shell commands the agent wants to run instead of running
+    /// them. The agent is told each command did not run.
+    #[arg(long = "dry-run", default_value_t = false)]
+    pub dry_run: bool,
+
     /// Tell the agent to use the specified directory as its working root.
     #[clap(long = "cd", short = 'C', value_name = "DIR")]
     pub cwd: Option<PathBuf>,
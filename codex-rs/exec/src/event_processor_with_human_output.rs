@@ -12,18 +12,27 @@ use codex_core::protocol::ErrorEvent;
 use codex_core::protocol::Event;
 use codex_core::protocol::EventMsg;
 use codex_core::protocol::ExecCommandBeginEvent;
+use codex_core::protocol::ExecCommandDryRunEvent;
 use codex_core::protocol::ExecCommandEndEvent;
 use codex_core::protocol::FileChange;
+use codex_core::protocol::HookEndEvent;
 use codex_core::protocol::McpInvocation;
+use codex_core::protocol::McpNotification;
+use codex_core::protocol::McpNotificationEvent;
 use codex_core::protocol::McpToolCallBeginEvent;
 use codex_core::protocol::McpToolCallEndEvent;
 use codex_core::protocol::PatchApplyBeginEvent;
 use codex_core::protocol::PatchApplyEndEvent;
 use codex_core::protocol::SessionConfiguredEvent;
+use codex_core::protocol::SessionDisposition;
 use codex_core::protocol::StreamErrorEvent;
+use codex_core::protocol::SubAgentBeginEvent;
+use codex_core::protocol::SubAgentEndEvent;
 use codex_core::protocol::TaskCompleteEvent;
+use codex_core::protocol::TaskStartedEvent;
 use codex_core::protocol::TurnAbortReason;
 use codex_core::protocol::TurnDiffEvent;
+use codex_core::protocol::WarningEvent;
 use codex_core::protocol::WebSearchBeginEvent;
 use codex_core::protocol::WebSearchEndEvent;
 use codex_protocol::num_format::format_with_separators;
@@ -175,26 +184,141 @@ impl EventProcessor for EventProcessorWithHumanOutput {
                 let prefix = "ERROR:".style(self.red);
                 ts_println!(self, "{prefix} {message}");
             }
+            EventMsg::Warning(WarningEvent { message }) => {
+                let prefix = "WARNING:".style(self.magenta);
+                ts_println!(self, "{prefix} {message}");
+            }
             EventMsg::BackgroundEvent(BackgroundEventEvent { message }) => {
                 ts_println!(self, "{}", message.style(self.dimmed));
             }
             EventMsg::StreamError(StreamErrorEvent { message }) => {
                 ts_println!(self, "{}", message.style(self.dimmed));
             }
-            EventMsg::TaskStarted(_) => {
-                // Ignore.
+            EventMsg::TaskStarted(TaskStartedEvent { branch, .. }) => {
+                if let Some(branch) = branch {
+                    ts_println!(
+                        self,
+                        "{}",
+                        format!("working on branch {branch}").style(self.dimmed)
+                    );
+                }
+            }
+            EventMsg::HookBegin(_) => {
+                // Reported once the hook finishes.
+            }
+            EventMsg::HookEnd(HookEndEvent {
+                hook,
+                tool,
+                duration,
+                exit_code,
+                timed_out,
+            }) => {
+                let label = match tool {
+                    Some(tool) => format!("{hook} hook ({tool})"),
+                    None => format!("{hook} hook"),
+                };
+                let outcome = match (timed_out, exit_code) {
+                    (true, _) => "timed out".to_string(),
+                    (false, Some(code)) => format!("exited {code}"),
+                    (false, None) => "failed".to_string(),
+                };
+                let line = format!("{label} {outcome} in {}", format_duration(duration));
+                ts_println!(self, "{}", line.style(self.dimmed));
+            }
+            EventMsg::SubAgentBegin(SubAgentBeginEvent {
+                agent_id, prompt, ..
+            }) => {
+                ts_println!(
+                    self,
+                    "{} {}",
+                    format!("delegated to {agent_id}:").style(self.magenta),
+                    prompt
+                );
             }
-            EventMsg::TaskComplete(TaskCompleteEvent { last_agent_message }) => {
+            EventMsg::SubAgentEnd(SubAgentEndEvent {
+                agent_id,
+                turns,
+                budget_exhausted,
+                ..
+            }) => {
+                let outcome = if budget_exhausted {
+                    "ran out of turns"
+                } else {
+                    "finished"
+                };
+                let line = format!("{agent_id} {outcome} after {turns} turn(s)");
+                ts_println!(self, "{}", line.style(self.dimmed));
+            }
+            EventMsg::TaskComplete(TaskCompleteEvent {
+                last_agent_message,
+                assessment,
+                output_schema_error,
+                ..
+            }) => {
+                if let Some(error) = output_schema_error {
+                    ts_println!(
+                        self,
+                        "{} {error}",
+                        "final answer does not match the output schema:".style(self.red)
+                    );
+                }
+                if let Some(assessment) = assessment {
+                    ts_println!(
+                        self,
+                        "{} {} confidence, {}",
+                        "assessment:".style(self.magenta),
+                        assessment.confidence,
+                        assessment.verification
+                    );
+                    for follow_up in assessment.follow_ups {
+                        ts_println!(
+                            self,
+                            "{} {}",
+                            format!("follow-up {}:", follow_up.label).style(self.bold),
+                            follow_up.prompt.style(self.dimmed)
+                        );
+                    }
+                }
                 if let Some(output_file) = self.last_message_path.as_deref() {
                     handle_last_message(last_agent_message.as_deref(), output_file);
                 }
                 return CodexStatus::InitiateShutdown;
             }
+            EventMsg::TaskStalled(ev) => match ev.failing_tool {
+                Some(tool_name) => ts_println!(
+                    self,
+                    "{} `{}` kept failing with the same arguments for {} turns ({})",
+                    "task stalled:".style(self.red),
+                    tool_name,
+                    ev.repeated_turns,
+                    ev.action
+                ),
+                None => ts_println!(
+                    self,
+                    "{} repeated the same tool calls for {} turns without making progress ({})",
+                    "task stalled:".style(self.red),
+                    ev.repeated_turns,
+                    ev.action
+                ),
+            },
+            EventMsg::AutoCompact(ev) => {
+                ts_println!(
+                    self,
+                    "{} {} tokens in context reached the limit of {}; summarizing the conversation",
+                    "auto-compact:".style(self.dimmed),
+                    format_with_separators(ev.tokens_in_context_window),
+                    format_with_separators(ev.token_limit)
+                );
+            }
             EventMsg::TokenCount(ev) => {
                 if let Some(usage_info) = ev.info {
+                    let cost = usage_info
+                        .estimated_cost_usd
+                        .map(|cost| format!(" (estimated cost ${cost:.2})"))
+                        .unwrap_or_default();
                     ts_println!(
                         self,
-                        "tokens used: {}",
+                        "tokens used: {}{cost}",
                         format_with_separators(usage_info.total_token_usage.blended_total())
                     );
                 }
@@ -278,6 +402,7 @@ impl EventProcessor for EventProcessorWithHumanOutput {
                 command,
                 cwd,
                 parsed_cmd: _,
+                sandbox_policy: _,
             }) => {
                 self.call_id_to_command.insert(
                     call_id,
@@ -294,6 +419,15 @@ impl EventProcessor for EventProcessorWithHumanOutput {
                 );
             }
             EventMsg::ExecCommandOutputDelta(_) => {}
+            EventMsg::ExecCommandDryRun(ExecCommandDryRunEvent { command, cwd, .. }) => {
+                ts_println!(
+                    self,
+                    "{} {} in {}",
+                    "dry run".style(self.magenta),
+                    escape_command(&command).style(self.bold),
+                    cwd.to_string_lossy(),
+                );
+            }
             EventMsg::ExecCommandEnd(ExecCommandEndEvent {
                 call_id,
                 aggregated_output,
@@ -347,6 +481,7 @@ impl EventProcessor for EventProcessorWithHumanOutput {
                     result,
                     invocation,
                     duration,
+                    output_schema: _,
                 } = tool_call_end_event;
 
                 let duration = format!(" in {}", format_duration(duration));
@@ -370,6 +505,40 @@ impl EventProcessor for EventProcessorWithHumanOutput {
                     }
                 }
             }
+            EventMsg::McpNotification(McpNotificationEvent {
+                server,
+                notification,
+            }) => match notification {
+                McpNotification::Progress {
+                    call_id: _,
+                    progress,
+                    total,
+                    message,
+                } => {
+                    let progress = match total {
+                        Some(total) => format!("{progress}/{total}"),
+                        None => progress.to_string(),
+                    };
+                    let message = message.map(|m| format!(" {m}")).unwrap_or_default();
+                    ts_println!(
+                        self,
+                        "{}",
+                        format!("{server} progress {progress}{message}").style(self.dimmed)
+                    );
+                }
+                McpNotification::Log { level, data, .. } => {
+                    let text = match data {
+                        serde_json::Value::String(text) => text,
+                        other => other.to_string(),
+                    };
+                    let level = format!("{level:?}").to_lowercase();
+                    ts_println!(
+                        self,
+                        "{}",
+                        format!("{server} {level}: {text}").style(self.dimmed)
+                    );
+                }
+            },
             EventMsg::WebSearchBegin(WebSearchBeginEvent { call_id: _ }) => {}
             EventMsg::WebSearchEnd(WebSearchEndEvent { call_id: _, query }) => {
                 ts_println!(self, "🌐 Searched: {query}");
@@ -378,6 +547,7 @@ impl EventProcessor for EventProcessorWithHumanOutput {
                 call_id,
                 auto_approved,
                 changes,
+                snapshot_id: _,
             }) => {
                 // Store metadata so we can calculate duration later when we
                 // receive the corresponding PatchApplyEnd event.
@@ -501,6 +671,15 @@ impl EventProcessor for EventProcessorWithHumanOutput {
             EventMsg::ApplyPatchApprovalRequest(_) => {
                 // Should we exit?
             }
+            EventMsg::EscalationRequest(_) => {
+                // Should we exit?
+            }
+            EventMsg::PermissionConsentRequest(_) => {
+                // Should we exit?
+            }
+            EventMsg::SpendLimitApprovalRequest(_) => {
+                // Should we exit?
+            }
             EventMsg::AgentReasoning(agent_reasoning_event) => {
                 if self.show_agent_reasoning {
                     if !self.reasoning_started {
@@ -577,9 +756,28 @@ impl EventProcessor for EventProcessorWithHumanOutput {
             EventMsg::McpListToolsResponse(_) => {
                 // Currently ignored in exec output.
             }
+            EventMsg::McpListResourcesResponse(_) => {
+                // Currently ignored in exec output.
+            }
             EventMsg::ListCustomPromptsResponse(_) => {
                 // Currently ignored in exec output.
             }
+            EventMsg::SessionEnvResponse(_) => {
+                // Currently ignored in exec output.
+            }
+            EventMsg::NextPromptPreview(_) => {
+                // Currently ignored in exec output.
+            }
+            EventMsg::UndoCompleted(_) => {
+                // Currently ignored in exec output.
+            }
+            EventMsg::ListSnapshotsResponse(_)
+            | EventMsg::SnapshotDiff(_)
+            | EventMsg::CheckpointCreated(_)
+            | EventMsg::CheckpointRewound(_)
+            | EventMsg::TaskQueueUpdated(_) => {
+                // Currently ignored in exec output.
+            }
             EventMsg::TurnAborted(abort_reason) => match abort_reason.reason {
                 TurnAbortReason::Interrupted => {
                     ts_println!(self, "task interrupted");
@@ -590,9 +788,34 @@ impl EventProcessor for EventProcessorWithHumanOutput {
                 TurnAbortReason::ReviewEnded => {
                     ts_println!(self, "task aborted: review ended");
                 }
+                TurnAbortReason::BudgetExceeded => {
+                    let summary = abort_reason
+                        .budget_summary()
+                        .unwrap_or_else(|| "budget exceeded".to_string());
+                    ts_println!(self, "task aborted: {summary}");
+                    return CodexStatus::InitiateShutdown;
+                }
             },
             EventMsg::ShutdownComplete => return CodexStatus::Shutdown,
             EventMsg::ConversationPath(_) => {}
+            EventMsg::ExecSessionKilled(ev) => {
+                if ev.killed {
+                    ts_println!(self, "killed exec session {}", ev.session_id);
+                }
+            }
+            EventMsg::ToolCallCancelled(ev) => {
+                if ev.cancelled {
+                    ts_println!(self, "cancelled tool call {}", ev.call_id);
+                }
+            }
+            EventMsg::SessionRemoved(ev) => match ev.disposition {
+                SessionDisposition::Archived => {
+                    ts_println!(self, "session archived to {}", ev.path.display());
+                }
+                SessionDisposition::Trashed => {
+                    ts_println!(self, "session moved to trash: {}", ev.path.display());
+                }
+            },
             EventMsg::UserMessage(_) => {}
             EventMsg::EnteredReviewMode(_) => {}
             EventMsg::ExitedReviewMode(_) => {}
@@ -31,6 +31,8 @@ use codex_core::protocol::PatchApplyBeginEvent;
 use codex_core::protocol::PatchApplyEndEvent;
 use codex_core::protocol::SessionConfiguredEvent;
 use codex_core::protocol::TaskCompleteEvent;
+use codex_core::protocol::TurnAbortReason;
+use codex_core::protocol::TurnAbortedEvent;
 use tracing::error;
 
 pub struct ExperimentalEventProcessorWithJsonOutput {
@@ -217,11 +219,22 @@ impl EventProcessor for ExperimentalEventProcessorWithJsonOutput {
 
         let Event { msg, .. } = event;
 
-        if let EventMsg::TaskComplete(TaskCompleteEvent { last_agent_message }) = msg {
+        if let EventMsg::TaskComplete(TaskCompleteEvent {
+            last_agent_message, ..
+        }) = msg
+        {
             if let Some(output_file) = self.last_message_path.as_deref() {
                 handle_last_message(last_agent_message.as_deref(), output_file);
             }
             CodexStatus::InitiateShutdown
+        } else if matches!(
+            msg,
+            EventMsg::TurnAborted(TurnAbortedEvent {
+                reason: TurnAbortReason::BudgetExceeded,
+                ..
+            })
+        ) {
+            CodexStatus::InitiateShutdown
         } else {
             CodexStatus::Running
         }
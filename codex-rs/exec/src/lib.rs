@@ -10,6 +10,7 @@ use std::io::Read;
 use std::path::PathBuf;
 
 pub use cli::Cli;
+use codex_common::SandboxCliArg;
 use codex_core::AuthManager;
 use codex_core::BUILT_IN_OSS_MODEL_PROVIDER_ID;
 use codex_core::ConversationManager;
@@ -23,6 +24,8 @@ use codex_core::protocol::EventMsg;
 use codex_core::protocol::InputItem;
 use codex_core::protocol::Op;
 use codex_core::protocol::TaskCompleteEvent;
+use codex_core::protocol::TurnAbortReason;
+use codex_core::protocol::TurnAbortedEvent;
 use codex_ollama::DEFAULT_OSS_MODEL;
 use codex_protocol::config_types::SandboxMode;
 use event_processor_with_human_output::EventProcessorWithHumanOutput;
@@ -38,6 +41,7 @@ use crate::event_processor::CodexStatus;
 use crate::event_processor::EventProcessor;
 use crate::event_processor_with_json_output::EventProcessorWithJsonOutput;
 use codex_core::find_conversation_path_by_id_str;
+use codex_core::restore_trashed_session;
 
 pub async fn run_main(cli: Cli, codex_linux_sandbox_exe: Option<PathBuf>) -> anyhow::Result<()> {
     let Cli {
@@ -48,6 +52,7 @@ pub async fn run_main(cli: Cli, codex_linux_sandbox_exe: Option<PathBuf>) -> any
         config_profile,
         full_auto,
         dangerously_bypass_approvals_and_sandbox,
+        dry_run,
         cwd,
         skip_git_repo_check,
         color,
@@ -128,12 +133,19 @@ pub async fn run_main(cli: Cli, codex_linux_sandbox_exe: Option<PathBuf>) -> any
         .with_writer(std::io::stderr)
         .try_init();
 
-    let sandbox_mode = if full_auto {
-        Some(SandboxMode::WorkspaceWrite)
+    let (sandbox_mode, sandbox_preset) = if full_auto {
+        (Some(SandboxMode::WorkspaceWrite), None)
     } else if dangerously_bypass_approvals_and_sandbox {
-        Some(SandboxMode::DangerFullAccess)
+        (Some(SandboxMode::DangerFullAccess), None)
     } else {
-        sandbox_mode_cli_arg.map(Into::<SandboxMode>::into)
+        (
+            sandbox_mode_cli_arg
+                .as_ref()
+                .and_then(SandboxCliArg::sandbox_mode),
+            sandbox_mode_cli_arg
+                .as_ref()
+                .and_then(SandboxCliArg::preset),
+        )
     };
 
     // When using `--oss`, let the bootstrapper pick the model (defaulting to
@@ -162,6 +174,7 @@ pub async fn run_main(cli: Cli, codex_linux_sandbox_exe: Option<PathBuf>) -> any
         // the user for approval.
         approval_policy: Some(AskForApproval::Never),
         sandbox_mode,
+        sandbox_preset,
         cwd: cwd.map(|p| p.canonicalize().unwrap_or(p)),
         model_provider,
         codex_linux_sandbox_exe,
@@ -292,6 +305,21 @@ pub async fn run_main(cli: Cli, codex_linux_sandbox_exe: Option<PathBuf>) -> any
         });
     }
 
+    if dry_run {
+        conversation
+            .submit(Op::OverrideTurnContext {
+                cwd: None,
+                approval_policy: None,
+                sandbox_policy: None,
+                sandbox_preset: None,
+                model: None,
+                effort: None,
+                summary: None,
+                dry_run: Some(true),
+            })
+            .await?;
+    }
+
     // Send images first, if any.
     if !images.is_empty() {
         let items: Vec<InputItem> = images
@@ -302,12 +330,7 @@ pub async fn run_main(cli: Cli, codex_linux_sandbox_exe: Option<PathBuf>) -> any
         info!("Sent images with event ID: {initial_images_event_id}");
         while let Ok(event) = conversation.next_event().await {
             if event.id == initial_images_event_id
-                && matches!(
-                    event.msg,
-                    EventMsg::TaskComplete(TaskCompleteEvent {
-                        last_agent_message: _,
-                    })
-                )
+                && matches!(event.msg, EventMsg::TaskComplete(TaskCompleteEvent { .. }))
             {
                 break;
             }
@@ -326,12 +349,31 @@ pub async fn run_main(cli: Cli, codex_linux_sandbox_exe: Option<PathBuf>) -> any
             effort: default_effort,
             summary: default_summary,
             final_output_json_schema: output_schema,
+            max_turns: None,
+            max_total_tokens: None,
+            max_duration: None,
         })
         .await?;
     info!("Sent prompt with event ID: {initial_prompt_task_id}");
 
     // Run the loop until the task is complete.
+    let mut budget_exceeded = false;
+    let mut output_schema_failed = false;
     while let Some(event) = rx.recv().await {
+        output_schema_failed |= matches!(
+            event.msg,
+            EventMsg::TaskComplete(TaskCompleteEvent {
+                output_schema_error: Some(_),
+                ..
+            })
+        );
+        budget_exceeded |= matches!(
+            event.msg,
+            EventMsg::TurnAborted(TurnAbortedEvent {
+                reason: TurnAbortReason::BudgetExceeded,
+                ..
+            })
+        );
         let shutdown: CodexStatus = event_processor.process_event(event);
         match shutdown {
             CodexStatus::Running => continue,
@@ -344,6 +386,12 @@ pub async fn run_main(cli: Cli, codex_linux_sandbox_exe: Option<PathBuf>) -> any
         }
     }
 
+    // Let scripts tell a run cut short by `task_budget`, or one whose answer
+    // does not match `--output-schema`, from a finished one.
+    if budget_exceeded || output_schema_failed {
+        std::process::exit(1);
+    }
+
     Ok(())
 }
 
@@ -360,8 +408,10 @@ async fn resolve_resume_path(
             }
         }
     } else if let Some(id_str) = args.session_id.as_deref() {
-        let path = find_conversation_path_by_id_str(&config.codex_home, id_str).await?;
-        Ok(path)
+        match find_conversation_path_by_id_str(&config.codex_home, id_str).await? {
+            Some(path) => Ok(Some(path)),
+            None => Ok(restore_trashed_session(&config.codex_home, id_str).await?),
+        }
     } else {
         Ok(None)
     }
@@ -153,6 +153,7 @@ fn exec_command_end_success_produces_completed_command_item() {
             command: vec!["bash".to_string(), "-lc".to_string(), "echo hi".to_string()],
             cwd: std::env::current_dir().unwrap(),
             parsed_cmd: Vec::new(),
+            sandbox_policy: None,
         }),
     );
     let out_begin = ep.collect_conversation_events(&begin);
@@ -200,6 +201,7 @@ fn exec_command_end_failure_produces_failed_command_item() {
             command: vec!["sh".to_string(), "-c".to_string(), "exit 1".to_string()],
             cwd: std::env::current_dir().unwrap(),
             parsed_cmd: Vec::new(),
+            sandbox_policy: None,
         }),
     );
     assert!(ep.collect_conversation_events(&begin).is_empty());
@@ -267,6 +269,7 @@ fn patch_apply_success_produces_item_completed_patchapply() {
             call_id: "call-1".to_string(),
             auto_approved: true,
             changes: changes.clone(),
+            snapshot_id: None,
         }),
     );
     let out_begin = ep.collect_conversation_events(&begin);
@@ -336,6 +339,7 @@ fn patch_apply_failure_produces_item_completed_patchapply_failed() {
             call_id: "call-2".to_string(),
             auto_approved: false,
             changes: changes.clone(),
+            snapshot_id: None,
         }),
     );
     assert!(ep.collect_conversation_events(&begin).is_empty());
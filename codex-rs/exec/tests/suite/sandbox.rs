@@ -1,4 +1,6 @@
 #![cfg(unix)]
+use codex_core::config_types::ResourceLimits;
+use codex_core::config_types::SandboxExtras;
 use codex_core::protocol::SandboxPolicy;
 use codex_core::spawn::StdioPolicy;
 use std::collections::HashMap;
@@ -27,6 +29,8 @@ async fn spawn_command_under_sandbox(
         sandbox_cwd,
         stdio_policy,
         env,
+        ResourceLimits::default(),
+        &SandboxExtras::default(),
     )
     .await
 }
@@ -50,6 +54,8 @@ async fn spawn_command_under_sandbox(
         sandbox_cwd,
         stdio_policy,
         env,
+        ResourceLimits::default(),
+        &SandboxExtras::default(),
     )
     .await
 }
@@ -69,6 +75,7 @@ async fn python_multiprocessing_lock_works_under_sandbox() {
     let policy = SandboxPolicy::WorkspaceWrite {
         writable_roots,
         network_access: false,
+        allowed_domains: vec![],
         exclude_tmpdir_env_var: false,
         exclude_slash_tmp: false,
     };
@@ -128,6 +135,7 @@ async fn sandbox_distinguishes_command_and_policy_cwds() {
     let policy = SandboxPolicy::WorkspaceWrite {
         writable_roots: vec![],
         network_access: false,
+        allowed_domains: vec![],
         exclude_tmpdir_env_var: true,
         exclude_slash_tmp: true,
     };
@@ -156,7 +156,9 @@ pub fn run(
         // Allow hidden entries.
         .hidden(false)
         // Don't require git to be present to apply to apply git-related ignore rules.
-        .require_git(false);
+        .require_git(false)
+        // Paths the user keeps out of the model's context.
+        .add_custom_ignore_filename(".codexignore");
 
     if !exclude.is_empty() {
         let mut override_builder = OverrideBuilder::new(search_directory);
@@ -0,0 +1,253 @@
+use std::ffi::OsString;
+use std::path::Path;
+
+use crate::GitToolingError;
+use crate::operations::ensure_git_repository;
+use crate::operations::resolve_head;
+use crate::operations::resolve_repository_root;
+use crate::operations::run_git_for_status;
+use crate::operations::run_git_for_stdout;
+
+/// Prefix of the branches created by [`start_work_branch`].
+pub const WORK_BRANCH_PREFIX: &str = "codex/";
+
+/// Longest slug [`work_branch_slug`] produces, so branch names stay readable.
+const MAX_SLUG_LEN: usize = 48;
+
+/// A branch created for a task, and the branch that was checked out before it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WorkBranch {
+    name: String,
+    previous: String,
+}
+
+impl WorkBranch {
+    /// Name of the created branch, e.g. `codex/fix-the-login-bug`.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Branch that was checked out when the work branch was created.
+    pub fn previous(&self) -> &str {
+        &self.previous
+    }
+}
+
+/// Turn free-form text such as a prompt into a branch name component:
+/// lowercase ASCII words joined by `-`, cut at a word boundary. Falls back to
+/// `task` when the text has no usable characters.
+pub fn work_branch_slug(text: &str) -> String {
+    let mut slug = String::new();
+    for word in text
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|word| !word.is_empty())
+    {
+        if !slug.is_empty() && slug.len() + 1 + word.len() > MAX_SLUG_LEN {
+            break;
+        }
+        if !slug.is_empty() {
+            slug.push('-');
+        }
+        slug.push_str(&word.to_ascii_lowercase());
+    }
+    slug.truncate(MAX_SLUG_LEN);
+    if slug.is_empty() {
+        slug.push_str("task");
+    }
+    slug
+}
+
+/// Create and check out a `codex/<slug>` branch for a task described by
+/// `description`, appending `-2`, `-3`, … when the name is taken.
+///
+/// Returns `None` without touching the repository unless the working tree is
+/// clean and a named branch other than a `codex/` branch is checked out, so
+/// follow-up tasks keep working on the branch of the first one.
+pub fn start_work_branch(
+    repo_path: &Path,
+    description: &str,
+) -> Result<Option<WorkBranch>, GitToolingError> {
+    ensure_git_repository(repo_path)?;
+
+    let repo_root = resolve_repository_root(repo_path)?;
+    let Some(previous) = current_branch(repo_root.as_path())? else {
+        return Ok(None);
+    };
+    if previous.starts_with(WORK_BRANCH_PREFIX) || resolve_head(repo_root.as_path())?.is_none() {
+        return Ok(None);
+    }
+    let status = run_git_for_stdout(
+        repo_root.as_path(),
+        vec![
+            OsString::from("status"),
+            OsString::from("--porcelain"),
+            OsString::from("--untracked-files=normal"),
+        ],
+        None,
+    )?;
+    if !status.is_empty() {
+        return Ok(None);
+    }
+
+    let base = format!("{WORK_BRANCH_PREFIX}{}", work_branch_slug(description));
+    let mut name = base.clone();
+    let mut suffix = 2;
+    while branch_exists(repo_root.as_path(), &name)? {
+        name = format!("{base}-{suffix}");
+        suffix += 1;
+    }
+
+    run_git_for_status(
+        repo_root.as_path(),
+        vec![
+            OsString::from("checkout"),
+            OsString::from("-b"),
+            OsString::from(&name),
+        ],
+        None,
+    )?;
+    Ok(Some(WorkBranch { name, previous }))
+}
+
+/// Check out the branch that was active before `branch` was created. The work
+/// branch itself is kept; uncommitted changes carry over as with any checkout.
+pub fn leave_work_branch(repo_path: &Path, branch: &WorkBranch) -> Result<(), GitToolingError> {
+    ensure_git_repository(repo_path)?;
+
+    let repo_root = resolve_repository_root(repo_path)?;
+    run_git_for_status(
+        repo_root.as_path(),
+        vec![
+            OsString::from("checkout"),
+            OsString::from(branch.previous()),
+        ],
+        None,
+    )
+}
+
+/// Short name of the checked out branch, or `None` on a detached `HEAD`.
+fn current_branch(repo_root: &Path) -> Result<Option<String>, GitToolingError> {
+    match run_git_for_stdout(
+        repo_root,
+        vec![
+            OsString::from("symbolic-ref"),
+            OsString::from("--quiet"),
+            OsString::from("--short"),
+            OsString::from("HEAD"),
+        ],
+        None,
+    ) {
+        Ok(name) => Ok(Some(name)),
+        Err(GitToolingError::GitCommand { status, .. }) if status.code() == Some(1) => Ok(None),
+        Err(other) => Err(other),
+    }
+}
+
+fn branch_exists(repo_root: &Path, name: &str) -> Result<bool, GitToolingError> {
+    match run_git_for_status(
+        repo_root,
+        vec![
+            OsString::from("show-ref"),
+            OsString::from("--verify"),
+            OsString::from("--quiet"),
+            OsString::from(format!("refs/heads/{name}")),
+        ],
+        None,
+    ) {
+        Ok(()) => Ok(true),
+        Err(GitToolingError::GitCommand { status, .. }) if status.code() == Some(1) => Ok(false),
+        Err(other) => Err(other),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use std::process::Command;
+
+    /// Runs a git command in the test repository and asserts success.
+    fn run_git_in(repo_path: &Path, args: &[&str]) {
+        let status = Command::new("git")
+            .current_dir(repo_path)
+            .args(args)
+            .status()
+            .expect("git command");
+        assert!(status.success(), "git command failed: {args:?}");
+    }
+
+    /// Initializes a repository with one commit on `main`.
+    fn init_committed_repo(repo: &Path) {
+        run_git_in(repo, &["init", "--initial-branch=main"]);
+        std::fs::write(repo.join("file.txt"), "hello\n").unwrap();
+        run_git_in(repo, &["add", "--all"]);
+        run_git_in(
+            repo,
+            &[
+                "-c",
+                "user.name=Tester",
+                "-c",
+                "user.email=test@example.com",
+                "commit",
+                "-m",
+                "init",
+            ],
+        );
+    }
+
+    fn checked_out(repo: &Path) -> Option<String> {
+        current_branch(repo).unwrap()
+    }
+
+    #[test]
+    /// Slugs keep lowercase words and stop at a word boundary.
+    fn work_branch_slug_normalizes_text() {
+        assert_eq!(work_branch_slug("Fix the login bug!"), "fix-the-login-bug");
+        assert_eq!(
+            work_branch_slug("Rename `foo_bar` to baz"),
+            "rename-foo-bar-to-baz"
+        );
+        assert_eq!(work_branch_slug("???"), "task");
+        let long = work_branch_slug(&"word ".repeat(40));
+        assert!(long.len() <= MAX_SLUG_LEN && !long.ends_with('-'));
+    }
+
+    #[test]
+    /// Creates a unique work branch in a clean repository and switches back.
+    fn start_and_leave_work_branch() -> Result<(), GitToolingError> {
+        let temp = tempfile::tempdir()?;
+        let repo = temp.path();
+        init_committed_repo(repo);
+        run_git_in(repo, &["branch", "codex/add-tests"]);
+
+        let branch = start_work_branch(repo, "Add tests")?.expect("work branch");
+        assert_eq!(branch.name(), "codex/add-tests-2");
+        assert_eq!(branch.previous(), "main");
+        assert_eq!(checked_out(repo).as_deref(), Some("codex/add-tests-2"));
+
+        // Follow-up tasks stay on the work branch.
+        assert_eq!(start_work_branch(repo, "More tests")?, None);
+
+        leave_work_branch(repo, &branch)?;
+        assert_eq!(checked_out(repo).as_deref(), Some("main"));
+        assert!(branch_exists(repo, "codex/add-tests-2")?);
+        Ok(())
+    }
+
+    #[test]
+    /// Leaves dirty working trees and detached heads alone.
+    fn start_work_branch_skips_dirty_or_detached_repositories() -> Result<(), GitToolingError> {
+        let temp = tempfile::tempdir()?;
+        let repo = temp.path();
+        init_committed_repo(repo);
+
+        std::fs::write(repo.join("untracked.txt"), "new\n")?;
+        assert_eq!(start_work_branch(repo, "task")?, None);
+        std::fs::remove_file(repo.join("untracked.txt"))?;
+
+        run_git_in(repo, &["checkout", "--detach"]);
+        assert_eq!(start_work_branch(repo, "task")?, None);
+        assert_eq!(checked_out(repo), None);
+        Ok(())
+    }
+}
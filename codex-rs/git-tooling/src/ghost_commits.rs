@@ -1,6 +1,8 @@
 use std::ffi::OsString;
 use std::path::Path;
 use std::path::PathBuf;
+use std::time::Duration;
+use std::time::SystemTime;
 
 use tempfile::Builder;
 
@@ -10,19 +12,30 @@ use crate::operations::apply_repo_prefix_to_force_include;
 use crate::operations::ensure_git_repository;
 use crate::operations::normalize_relative_path;
 use crate::operations::repo_subdir;
+use crate::operations::resolve_git_common_dir;
 use crate::operations::resolve_head;
 use crate::operations::resolve_repository_root;
+use crate::operations::run_git_for_raw_stdout;
 use crate::operations::run_git_for_status;
 use crate::operations::run_git_for_stdout;
 
 /// Default commit message used for ghost commits when none is provided.
 const DEFAULT_COMMIT_MESSAGE: &str = "codex snapshot";
 
+/// Ref namespace that keeps recorded ghost commits reachable until pruned.
+const GHOST_REFS_PREFIX: &str = "refs/codex/ghosts/";
+
+/// Loose objects modified more recently than this are never deleted, so a
+/// concurrent git command that just reused one does not lose it.
+const LOOSE_OBJECT_GRACE: Duration = Duration::from_secs(60 * 60);
+
 /// Options to control ghost commit creation.
 pub struct CreateGhostCommitOptions<'a> {
     pub repo_path: &'a Path,
     pub message: Option<&'a str>,
     pub force_include: Vec<PathBuf>,
+    pub include_untracked: bool,
+    pub include_ignored: bool,
 }
 
 impl<'a> CreateGhostCommitOptions<'a> {
@@ -32,6 +45,8 @@ impl<'a> CreateGhostCommitOptions<'a> {
             repo_path,
             message: None,
             force_include: Vec::new(),
+            include_untracked: true,
+            include_ignored: false,
         }
     }
 
@@ -50,6 +65,22 @@ impl<'a> CreateGhostCommitOptions<'a> {
         self
     }
 
+    /// Whether to capture untracked files that are not ignored. Defaults to
+    /// `true`; when `false` only files tracked in the repository's index are
+    /// captured.
+    pub fn include_untracked(mut self, include: bool) -> Self {
+        self.include_untracked = include;
+        self
+    }
+
+    /// Whether to capture ignored files too, such as build outputs or `.env`.
+    /// Defaults to `false`. Ignored files are untracked, so this has no effect
+    /// unless [`Self::include_untracked`] is set.
+    pub fn include_ignored(mut self, include: bool) -> Self {
+        self.include_ignored = include;
+        self
+    }
+
     /// Adds a single path to the force-include list.
     pub fn push_force_include<P>(mut self, path: P) -> Self
     where
@@ -84,7 +115,30 @@ pub fn create_ghost_commit(
         OsString::from(index_path.as_os_str()),
     )];
 
-    let mut add_args = vec![OsString::from("add"), OsString::from("--all")];
+    let mut add_args = vec![OsString::from("add")];
+    if options.include_untracked {
+        add_args.push(OsString::from("--all"));
+        if options.include_ignored {
+            add_args.push(OsString::from("--force"));
+        }
+    } else {
+        // Start from the real index so `--update` knows which files are
+        // tracked, including ones staged but not yet committed.
+        let index_source = run_git_for_stdout(
+            repo_root.as_path(),
+            vec![
+                OsString::from("rev-parse"),
+                OsString::from("--git-path"),
+                OsString::from("index"),
+            ],
+            None,
+        )?;
+        let index_source = repo_root.join(index_source);
+        if index_source.exists() {
+            std::fs::copy(&index_source, &index_path)?;
+        }
+        add_args.push(OsString::from("--update"));
+    }
     if let Some(prefix) = repo_prefix.as_deref() {
         add_args.extend([OsString::from("--"), prefix.as_os_str().to_os_string()]);
     }
@@ -160,6 +214,290 @@ pub fn restore_to_commit(repo_path: &Path, commit_id: &str) -> Result<(), GitToo
     Ok(())
 }
 
+/// List the paths, relative to the repository root, whose contents differ
+/// between two commits (e.g. two ghost commits).
+pub fn changed_paths(
+    repo_path: &Path,
+    from_commit: &str,
+    to_commit: &str,
+) -> Result<Vec<PathBuf>, GitToolingError> {
+    ensure_git_repository(repo_path)?;
+
+    let repo_root = resolve_repository_root(repo_path)?;
+    let output = run_git_for_stdout(
+        repo_root.as_path(),
+        vec![
+            OsString::from("diff"),
+            OsString::from("--name-only"),
+            OsString::from("-z"),
+            OsString::from(from_commit),
+            OsString::from(to_commit),
+        ],
+        None,
+    )?;
+    Ok(output
+        .split('\0')
+        .filter(|path| !path.is_empty())
+        .map(PathBuf::from)
+        .collect())
+}
+
+/// Unified diff of the changes between two commits (e.g. two ghost commits),
+/// with paths relative to the repository root. Empty when they match.
+pub fn diff_ghost_commits(
+    repo_path: &Path,
+    from_commit: &str,
+    to_commit: &str,
+) -> Result<String, GitToolingError> {
+    ensure_git_repository(repo_path)?;
+
+    let repo_root = resolve_repository_root(repo_path)?;
+    run_git_for_raw_stdout(
+        repo_root.as_path(),
+        vec![
+            OsString::from("diff"),
+            OsString::from("--no-color"),
+            OsString::from("--no-ext-diff"),
+            OsString::from(from_commit),
+            OsString::from(to_commit),
+        ],
+        None,
+    )
+}
+
+/// Read the contents of `path` as of a commit (e.g. a ghost commit). `path`
+/// is resolved against `repo_path` when relative. Returns `None` when the
+/// commit does not contain the file.
+pub fn read_file_at_commit(
+    repo_path: &Path,
+    commit_id: &str,
+    path: &Path,
+) -> Result<Option<String>, GitToolingError> {
+    ensure_git_repository(repo_path)?;
+
+    let repo_root = resolve_repository_root(repo_path)?;
+    let path = repo_path.join(path);
+    let path = std::fs::canonicalize(&path).unwrap_or(path);
+    let relative = path.strip_prefix(&repo_root)?;
+    let relative = relative.to_string_lossy().replace('\\', "/");
+    match run_git_for_raw_stdout(
+        repo_root.as_path(),
+        vec![
+            OsString::from("show"),
+            OsString::from(format!("{commit_id}:{relative}")),
+        ],
+        None,
+    ) {
+        Ok(contents) => Ok(Some(contents)),
+        Err(GitToolingError::GitCommand { status, .. }) if status.code() == Some(128) => Ok(None),
+        Err(other) => Err(other),
+    }
+}
+
+/// Prefix of the branches [`export_ghost_commit`] names on its own.
+const SNAPSHOT_BRANCH_PREFIX: &str = "codex/snapshot-";
+
+/// Where [`export_ghost_commit`] writes a ghost commit.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GhostCommitExport {
+    /// A `git format-patch` mailbox against the commit's parent, which
+    /// `git am` can apply. Relative paths are resolved against the repository
+    /// path passed to [`export_ghost_commit`].
+    PatchFile(PathBuf),
+    /// A new branch pointing at the commit. `None` names it
+    /// `codex/snapshot-<unix timestamp of the commit>`.
+    Branch(Option<String>),
+}
+
+/// Export a ghost commit so it can be reviewed or shared like any other
+/// change. Returns the path of the written patch file or the name of the
+/// created branch. An existing branch of the same name is never overwritten.
+pub fn export_ghost_commit(
+    repo_path: &Path,
+    commit: &GhostCommit,
+    export: GhostCommitExport,
+) -> Result<String, GitToolingError> {
+    ensure_git_repository(repo_path)?;
+
+    let repo_root = resolve_repository_root(repo_path)?;
+    match export {
+        GhostCommitExport::PatchFile(path) => {
+            let mut args = vec![
+                OsString::from("format-patch"),
+                OsString::from("--stdout"),
+                OsString::from("--binary"),
+                OsString::from("--no-signature"),
+            ];
+            match commit.parent() {
+                Some(parent) => args.push(OsString::from(format!("{parent}..{}", commit.id()))),
+                None => args.extend([OsString::from("--root"), OsString::from(commit.id())]),
+            }
+            let patch = run_git_for_raw_stdout(repo_root.as_path(), args, None)?;
+            let path = repo_path.join(path);
+            std::fs::write(&path, patch)?;
+            Ok(path.to_string_lossy().into_owned())
+        }
+        GhostCommitExport::Branch(name) => {
+            let name = match name {
+                Some(name) => name,
+                None => {
+                    let timestamp = run_git_for_stdout(
+                        repo_root.as_path(),
+                        vec![
+                            OsString::from("show"),
+                            OsString::from("-s"),
+                            OsString::from("--format=%ct"),
+                            OsString::from(commit.id()),
+                        ],
+                        None,
+                    )?;
+                    format!("{SNAPSHOT_BRANCH_PREFIX}{timestamp}")
+                }
+            };
+            run_git_for_status(
+                repo_root.as_path(),
+                vec![
+                    OsString::from("branch"),
+                    OsString::from("--no-track"),
+                    OsString::from(&name),
+                    OsString::from(commit.id()),
+                ],
+                None,
+            )?;
+            Ok(name)
+        }
+    }
+}
+
+/// How many recorded ghost commits to keep per repository, and for how long.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GhostCommitRetention {
+    /// Number of most recent ghost commits to keep.
+    pub max_count: usize,
+    /// Ghost commits older than this are pruned regardless of `max_count`.
+    pub max_age: Duration,
+}
+
+impl Default for GhostCommitRetention {
+    fn default() -> Self {
+        Self {
+            max_count: 50,
+            max_age: Duration::from_secs(7 * 24 * 60 * 60),
+        }
+    }
+}
+
+/// Record a ghost commit under `refs/codex/ghosts/` so it survives `git gc`
+/// until [`prune_ghost_commits`] drops it.
+pub fn record_ghost_commit(repo_path: &Path, commit: &GhostCommit) -> Result<(), GitToolingError> {
+    ensure_git_repository(repo_path)?;
+
+    let repo_root = resolve_repository_root(repo_path)?;
+    run_git_for_status(
+        repo_root.as_path(),
+        vec![
+            OsString::from("update-ref"),
+            OsString::from(format!("{GHOST_REFS_PREFIX}{}", commit.id())),
+            OsString::from(commit.id()),
+        ],
+        None,
+    )
+}
+
+/// Drop the recorded ghost commits that fall outside `retention`, deleting
+/// their refs and the loose objects nothing else references. Returns the ids
+/// of the pruned commits, newest first.
+pub fn prune_ghost_commits(
+    repo_path: &Path,
+    retention: &GhostCommitRetention,
+) -> Result<Vec<String>, GitToolingError> {
+    ensure_git_repository(repo_path)?;
+
+    let repo_root = resolve_repository_root(repo_path)?;
+    let listing = run_git_for_stdout(
+        repo_root.as_path(),
+        vec![
+            OsString::from("for-each-ref"),
+            OsString::from("--sort=-committerdate"),
+            OsString::from("--format=%(refname) %(objectname) %(committerdate:unix)"),
+            OsString::from(GHOST_REFS_PREFIX),
+        ],
+        None,
+    )?;
+    let cutoff = SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .unwrap_or_default()
+        .saturating_sub(retention.max_age)
+        .as_secs();
+
+    let mut pruned = Vec::new();
+    for (index, line) in listing.lines().enumerate() {
+        let mut fields = line.split(' ');
+        let (Some(refname), Some(id), Some(created)) =
+            (fields.next(), fields.next(), fields.next())
+        else {
+            continue;
+        };
+        let created = created.parse::<u64>().unwrap_or_default();
+        if index < retention.max_count && created >= cutoff {
+            continue;
+        }
+        run_git_for_status(
+            repo_root.as_path(),
+            vec![
+                OsString::from("update-ref"),
+                OsString::from("-d"),
+                OsString::from(refname),
+            ],
+            None,
+        )?;
+        pruned.push(id.to_string());
+    }
+
+    if !pruned.is_empty() {
+        remove_unreferenced_loose_objects(repo_root.as_path(), &pruned)?;
+    }
+    Ok(pruned)
+}
+
+/// Delete the loose objects reachable from `commits` but not from any ref,
+/// reflog or index. Packed objects are left for `git gc`.
+fn remove_unreferenced_loose_objects(
+    repo_root: &Path,
+    commits: &[String],
+) -> Result<(), GitToolingError> {
+    let mut args = vec![OsString::from("rev-list"), OsString::from("--objects")];
+    args.extend(commits.iter().map(OsString::from));
+    args.extend(
+        ["--not", "--all", "--reflog", "--indexed-objects"]
+            .into_iter()
+            .map(OsString::from),
+    );
+    let objects = run_git_for_stdout(repo_root, args, None)?;
+    let objects_dir = resolve_git_common_dir(repo_root)?.join("objects");
+
+    for line in objects.lines() {
+        let Some(id) = line.split(' ').next().filter(|id| id.len() > 2) else {
+            continue;
+        };
+        let path = objects_dir.join(&id[..2]).join(&id[2..]);
+        let recently_used = std::fs::metadata(&path)
+            .and_then(|metadata| metadata.modified())
+            .ok()
+            .and_then(|modified| modified.elapsed().ok())
+            .is_none_or(|age| age < LOOSE_OBJECT_GRACE);
+        if recently_used {
+            continue;
+        }
+        match std::fs::remove_file(&path) {
+            Ok(()) => {}
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => {}
+            Err(err) => return Err(err.into()),
+        }
+    }
+    Ok(())
+}
+
 /// Returns the default author and committer identity for ghost commits.
 fn default_commit_identity() -> Vec<(OsString, OsString)> {
     vec![
@@ -216,6 +554,131 @@ mod tests {
         run_git_in(repo, &["config", "core.autocrlf", "false"]);
     }
 
+    /// Commits every change in the repository as `Tester`.
+    fn commit_all(repo_path: &Path, message: &str) {
+        run_git_in(repo_path, &["add", "--all"]);
+        run_git_in(
+            repo_path,
+            &[
+                "-c",
+                "user.name=Tester",
+                "-c",
+                "user.email=test@example.com",
+                "commit",
+                "-m",
+                message,
+            ],
+        );
+    }
+
+    #[test]
+    /// Snapshots, records, restores and prunes from a linked worktree, whose
+    /// objects and refs live in the main repository's git dir.
+    fn ghost_commits_in_linked_worktree() -> Result<(), GitToolingError> {
+        let temp = tempfile::tempdir()?;
+        let main = temp.path().join("main");
+        let worktree = temp.path().join("worktree");
+        std::fs::create_dir(&main)?;
+        init_test_repo(&main);
+        std::fs::write(main.join("file.txt"), "main\n")?;
+        commit_all(&main, "init");
+        run_git_in(
+            &main,
+            &[
+                "worktree",
+                "add",
+                "-b",
+                "feature",
+                worktree.to_str().unwrap(),
+            ],
+        );
+
+        std::fs::write(worktree.join("file.txt"), "snapshot\n")?;
+        std::fs::write(worktree.join("new.txt"), "new\n")?;
+        let ghost = create_ghost_commit(&CreateGhostCommitOptions::new(&worktree))?;
+        record_ghost_commit(&worktree, &ghost)?;
+        assert_eq!(recorded_ghosts(&main), vec![ghost.id().to_string()]);
+
+        std::fs::write(worktree.join("file.txt"), "changed\n")?;
+        std::fs::remove_file(worktree.join("new.txt"))?;
+        restore_ghost_commit(&worktree, &ghost)?;
+        assert_eq!(
+            std::fs::read_to_string(worktree.join("file.txt"))?,
+            "snapshot\n"
+        );
+        assert_eq!(std::fs::read_to_string(worktree.join("new.txt"))?, "new\n");
+        assert_eq!(std::fs::read_to_string(main.join("file.txt"))?, "main\n");
+
+        // Age the ghost commit past the grace period so pruning deletes it
+        // from the main repository's object store.
+        let object = main
+            .join(".git/objects")
+            .join(&ghost.id()[..2])
+            .join(&ghost.id()[2..]);
+        std::fs::File::options()
+            .write(true)
+            .open(&object)?
+            .set_modified(SystemTime::now() - 2 * LOOSE_OBJECT_GRACE)?;
+        let retention = GhostCommitRetention {
+            max_count: 0,
+            ..GhostCommitRetention::default()
+        };
+        assert_eq!(
+            prune_ghost_commits(&worktree, &retention)?,
+            vec![ghost.id().to_string()]
+        );
+        assert!(recorded_ghosts(&main).is_empty());
+        assert_eq!(object.exists(), false);
+        Ok(())
+    }
+
+    #[test]
+    /// Snapshots and restores a submodule checkout on its own, without
+    /// touching the superproject.
+    fn ghost_commits_in_submodule_checkout() -> Result<(), GitToolingError> {
+        let temp = tempfile::tempdir()?;
+        let upstream = temp.path().join("upstream");
+        let superproject = temp.path().join("super");
+        std::fs::create_dir(&upstream)?;
+        std::fs::create_dir(&superproject)?;
+        init_test_repo(&upstream);
+        std::fs::write(upstream.join("lib.txt"), "v1\n")?;
+        commit_all(&upstream, "init");
+        init_test_repo(&superproject);
+        run_git_in(
+            &superproject,
+            &[
+                "-c",
+                "protocol.file.allow=always",
+                "submodule",
+                "add",
+                upstream.to_str().unwrap(),
+                "vendor/lib",
+            ],
+        );
+        commit_all(&superproject, "add submodule");
+
+        let submodule = superproject.join("vendor/lib");
+        std::fs::write(submodule.join("lib.txt"), "v2\n")?;
+        let ghost = create_ghost_commit(&CreateGhostCommitOptions::new(&submodule))?;
+        record_ghost_commit(&submodule, &ghost)?;
+        assert_eq!(recorded_ghosts(&submodule), vec![ghost.id().to_string()]);
+        assert!(recorded_ghosts(&superproject).is_empty());
+        assert_eq!(
+            changed_paths(&submodule, ghost.parent().unwrap(), ghost.id())?,
+            vec![PathBuf::from("lib.txt")]
+        );
+
+        std::fs::write(submodule.join("lib.txt"), "v3\n")?;
+        restore_ghost_commit(&submodule, &ghost)?;
+        assert_eq!(std::fs::read_to_string(submodule.join("lib.txt"))?, "v2\n");
+        assert_eq!(
+            run_git_stdout(&superproject, &["status", "--porcelain"]),
+            "M vendor/lib"
+        );
+        Ok(())
+    }
+
     #[test]
     /// Verifies a ghost commit can be created and restored end to end.
     fn create_and_restore_roundtrip() -> Result<(), GitToolingError> {
@@ -281,6 +744,297 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    /// Leaves untracked files out, or captures ignored ones too, on request.
+    fn create_ghost_commit_untracked_and_ignored_options() -> Result<(), GitToolingError> {
+        let temp = tempfile::tempdir()?;
+        let repo = temp.path();
+        init_test_repo(repo);
+        std::fs::write(repo.join(".gitignore"), ".env\n")?;
+        std::fs::write(repo.join("tracked.txt"), "initial\n")?;
+        commit_all(repo, "init");
+        std::fs::write(repo.join("staged.txt"), "staged\n")?;
+        run_git_in(repo, &["add", "staged.txt"]);
+        std::fs::write(repo.join("tracked.txt"), "modified\n")?;
+        std::fs::write(repo.join("untracked.txt"), "untracked\n")?;
+        std::fs::write(repo.join(".env"), "SECRET=1\n")?;
+
+        let files_in = |ghost: &GhostCommit| {
+            run_git_stdout(repo, &["ls-tree", "--name-only", ghost.id()])
+                .lines()
+                .map(str::to_string)
+                .collect::<Vec<_>>()
+        };
+
+        let default = create_ghost_commit(&CreateGhostCommitOptions::new(repo))?;
+        assert_eq!(
+            files_in(&default),
+            vec![".gitignore", "staged.txt", "tracked.txt", "untracked.txt"]
+        );
+
+        let tracked_only =
+            create_ghost_commit(&CreateGhostCommitOptions::new(repo).include_untracked(false))?;
+        assert_eq!(
+            files_in(&tracked_only),
+            vec![".gitignore", "staged.txt", "tracked.txt"]
+        );
+        assert_eq!(
+            run_git_stdout(
+                repo,
+                &["show", &format!("{}:tracked.txt", tracked_only.id())]
+            ),
+            "modified"
+        );
+
+        let everything =
+            create_ghost_commit(&CreateGhostCommitOptions::new(repo).include_ignored(true))?;
+        assert_eq!(
+            files_in(&everything),
+            vec![
+                ".env",
+                ".gitignore",
+                "staged.txt",
+                "tracked.txt",
+                "untracked.txt"
+            ]
+        );
+
+        // The repository's own index is left untouched.
+        assert_eq!(
+            run_git_stdout(repo, &["diff", "--cached", "--name-only"]),
+            "staged.txt"
+        );
+        Ok(())
+    }
+
+    #[test]
+    /// Lists the files that differ between two ghost commits.
+    fn changed_paths_between_snapshots() -> Result<(), GitToolingError> {
+        let temp = tempfile::tempdir()?;
+        let repo = temp.path();
+        init_test_repo(repo);
+        std::fs::write(repo.join("same.txt"), "unchanged\n")?;
+        std::fs::write(repo.join("edited.txt"), "before\n")?;
+        let before = create_ghost_commit(&CreateGhostCommitOptions::new(repo))?;
+
+        std::fs::write(repo.join("edited.txt"), "after\n")?;
+        std::fs::write(repo.join("added file.txt"), "new\n")?;
+        let after = create_ghost_commit(&CreateGhostCommitOptions::new(repo))?;
+
+        assert_eq!(
+            changed_paths(repo, before.id(), after.id())?,
+            vec![PathBuf::from("added file.txt"), PathBuf::from("edited.txt")]
+        );
+        assert_eq!(
+            changed_paths(repo, after.id(), after.id())?,
+            Vec::<PathBuf>::new()
+        );
+        Ok(())
+    }
+
+    #[test]
+    /// Exports a ghost commit as a patch that `git am` applies to a clean
+    /// checkout of its parent.
+    fn export_ghost_commit_as_patch_file() -> Result<(), GitToolingError> {
+        let temp = tempfile::tempdir()?;
+        let repo = temp.path().join("repo");
+        std::fs::create_dir(&repo)?;
+        init_test_repo(&repo);
+        std::fs::write(repo.join("file.txt"), "before\n")?;
+        commit_all(&repo, "init");
+
+        std::fs::write(repo.join("file.txt"), "after\n")?;
+        std::fs::write(repo.join("new.txt"), "new\n")?;
+        let ghost = create_ghost_commit(&CreateGhostCommitOptions::new(&repo))?;
+        let patch_path = temp.path().join("snapshot.patch");
+        let written = export_ghost_commit(
+            &repo,
+            &ghost,
+            GhostCommitExport::PatchFile(patch_path.clone()),
+        )?;
+        assert_eq!(PathBuf::from(written), patch_path);
+
+        run_git_in(&repo, &["checkout", "--", "file.txt"]);
+        std::fs::remove_file(repo.join("new.txt"))?;
+        run_git_in(
+            &repo,
+            &[
+                "-c",
+                "user.name=Tester",
+                "-c",
+                "user.email=test@example.com",
+                "am",
+                patch_path.to_str().unwrap(),
+            ],
+        );
+        assert_eq!(std::fs::read_to_string(repo.join("file.txt"))?, "after\n");
+        assert_eq!(std::fs::read_to_string(repo.join("new.txt"))?, "new\n");
+        Ok(())
+    }
+
+    #[test]
+    /// Exports a ghost commit as a branch, named after the commit time unless
+    /// a name is given, and refuses to overwrite an existing branch.
+    fn export_ghost_commit_as_branch() -> Result<(), GitToolingError> {
+        let temp = tempfile::tempdir()?;
+        let repo = temp.path();
+        init_test_repo(repo);
+        std::fs::write(repo.join("file.txt"), "before\n")?;
+        commit_all(repo, "init");
+        std::fs::write(repo.join("file.txt"), "after\n")?;
+        let ghost = create_ghost_commit(&CreateGhostCommitOptions::new(repo))?;
+
+        let branch = export_ghost_commit(repo, &ghost, GhostCommitExport::Branch(None))?;
+        let timestamp = run_git_stdout(repo, &["show", "-s", "--format=%ct", ghost.id()]);
+        assert_eq!(branch, format!("codex/snapshot-{timestamp}"));
+        assert_eq!(run_git_stdout(repo, &["rev-parse", &branch]), ghost.id());
+
+        let named = export_ghost_commit(
+            repo,
+            &ghost,
+            GhostCommitExport::Branch(Some("review/agent-run".to_string())),
+        )?;
+        assert_eq!(named, "review/agent-run");
+        assert_eq!(run_git_stdout(repo, &["rev-parse", &named]), ghost.id());
+
+        assert!(matches!(
+            export_ghost_commit(repo, &ghost, GhostCommitExport::Branch(Some(named))),
+            Err(GitToolingError::GitCommand { .. })
+        ));
+        assert_eq!(run_git_stdout(repo, &["branch", "--show-current"]), "main");
+        Ok(())
+    }
+
+    /// Records a ghost commit of the empty tree committed at `timestamp`.
+    fn record_ghost_at(repo_path: &Path, timestamp: u64) -> String {
+        let tree = run_git_stdout(repo_path, &["write-tree"]);
+        let output = Command::new("git")
+            .current_dir(repo_path)
+            .env("GIT_AUTHOR_DATE", format!("{timestamp} +0000"))
+            .env("GIT_COMMITTER_DATE", format!("{timestamp} +0000"))
+            .args([
+                "-c",
+                "user.name=Tester",
+                "-c",
+                "user.email=test@example.com",
+            ])
+            .args(["commit-tree", &tree, "-m", &timestamp.to_string()])
+            .output()
+            .expect("git commit-tree");
+        assert!(output.status.success(), "git commit-tree failed");
+        let id = String::from_utf8_lossy(&output.stdout).trim().to_string();
+        record_ghost_commit(repo_path, &GhostCommit::new(id.clone(), None)).expect("record");
+        id
+    }
+
+    /// Lists the commits recorded under the ghost ref namespace.
+    fn recorded_ghosts(repo_path: &Path) -> Vec<String> {
+        let listing = run_git_stdout(
+            repo_path,
+            &["for-each-ref", "--format=%(objectname)", GHOST_REFS_PREFIX],
+        );
+        let mut ids: Vec<String> = listing.lines().map(str::to_string).collect();
+        ids.sort();
+        ids
+    }
+
+    /// Seconds since the Unix epoch.
+    fn now_secs() -> u64 {
+        SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .expect("clock after epoch")
+            .as_secs()
+    }
+
+    #[test]
+    /// Keeps only the newest `max_count` recorded ghost commits.
+    fn prune_ghost_commits_keeps_newest() -> Result<(), GitToolingError> {
+        let temp = tempfile::tempdir()?;
+        let repo = temp.path();
+        init_test_repo(repo);
+
+        let now = now_secs();
+        let oldest = record_ghost_at(repo, now - 300);
+        let middle = record_ghost_at(repo, now - 200);
+        let newest = record_ghost_at(repo, now - 100);
+
+        let retention = GhostCommitRetention {
+            max_count: 1,
+            ..GhostCommitRetention::default()
+        };
+        assert_eq!(prune_ghost_commits(repo, &retention)?, vec![middle, oldest]);
+        assert_eq!(recorded_ghosts(repo), vec![newest]);
+        Ok(())
+    }
+
+    #[test]
+    /// Prunes recorded ghost commits older than `max_age`.
+    fn prune_ghost_commits_drops_expired() -> Result<(), GitToolingError> {
+        let temp = tempfile::tempdir()?;
+        let repo = temp.path();
+        init_test_repo(repo);
+
+        std::fs::write(repo.join("file.txt"), "contents\n")?;
+        let fresh = create_ghost_commit(&CreateGhostCommitOptions::new(repo))?;
+        record_ghost_commit(repo, &fresh)?;
+        let expired = record_ghost_at(repo, now_secs() - 30 * 24 * 60 * 60);
+
+        assert_eq!(
+            prune_ghost_commits(repo, &GhostCommitRetention::default())?,
+            vec![expired]
+        );
+        assert_eq!(recorded_ghosts(repo), vec![fresh.id().to_string()]);
+        Ok(())
+    }
+
+    #[test]
+    /// Produces a unified diff between two ghost commits.
+    fn diff_between_snapshots() -> Result<(), GitToolingError> {
+        let temp = tempfile::tempdir()?;
+        let repo = temp.path();
+        init_test_repo(repo);
+        std::fs::write(repo.join("edited.txt"), "before\n")?;
+        let before = create_ghost_commit(&CreateGhostCommitOptions::new(repo))?;
+
+        std::fs::write(repo.join("edited.txt"), "after\n")?;
+        let after = create_ghost_commit(&CreateGhostCommitOptions::new(repo))?;
+
+        let diff = diff_ghost_commits(repo, before.id(), after.id())?;
+        assert!(
+            diff.starts_with("diff --git a/edited.txt b/edited.txt\n"),
+            "{diff}"
+        );
+        assert!(diff.ends_with("-before\n+after\n"), "{diff}");
+        assert_eq!(diff_ghost_commits(repo, after.id(), after.id())?, "");
+        Ok(())
+    }
+
+    #[test]
+    /// Reads a file as it was in a ghost commit, by absolute or relative path.
+    fn read_file_from_snapshot() -> Result<(), GitToolingError> {
+        let temp = tempfile::tempdir()?;
+        let repo = temp.path();
+        init_test_repo(repo);
+        std::fs::create_dir(repo.join("src"))?;
+        std::fs::write(repo.join("src/lib.rs"), "before\n")?;
+        let ghost = create_ghost_commit(&CreateGhostCommitOptions::new(repo))?;
+        std::fs::write(repo.join("src/lib.rs"), "after\n")?;
+
+        assert_eq!(
+            read_file_at_commit(repo, ghost.id(), &repo.join("src/lib.rs"))?,
+            Some("before\n".to_string())
+        );
+        assert_eq!(
+            read_file_at_commit(repo, ghost.id(), Path::new("src/lib.rs"))?,
+            Some("before\n".to_string())
+        );
+        assert_eq!(
+            read_file_at_commit(repo, ghost.id(), Path::new("missing.rs"))?,
+            None
+        );
+        Ok(())
+    }
+
     #[test]
     /// Ensures ghost commits succeed in repositories without an existing HEAD.
     fn create_snapshot_without_existing_head() -> Result<(), GitToolingError> {
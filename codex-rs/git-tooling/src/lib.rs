@@ -1,16 +1,33 @@
 use std::fmt;
 
+mod branches;
 mod errors;
 mod ghost_commits;
 mod operations;
 mod platform;
+mod status;
 
+pub use branches::WORK_BRANCH_PREFIX;
+pub use branches::WorkBranch;
+pub use branches::leave_work_branch;
+pub use branches::start_work_branch;
+pub use branches::work_branch_slug;
 pub use errors::GitToolingError;
 pub use ghost_commits::CreateGhostCommitOptions;
+pub use ghost_commits::GhostCommitExport;
+pub use ghost_commits::GhostCommitRetention;
+pub use ghost_commits::changed_paths;
 pub use ghost_commits::create_ghost_commit;
+pub use ghost_commits::diff_ghost_commits;
+pub use ghost_commits::export_ghost_commit;
+pub use ghost_commits::prune_ghost_commits;
+pub use ghost_commits::read_file_at_commit;
+pub use ghost_commits::record_ghost_commit;
 pub use ghost_commits::restore_ghost_commit;
 pub use ghost_commits::restore_to_commit;
 pub use platform::create_symlink;
+pub use status::RepoStatus;
+pub use status::repo_status;
 
 /// Details of a ghost commit created from a repository state.
 #[derive(Debug, Clone, PartialEq, Eq)]
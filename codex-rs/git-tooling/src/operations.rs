@@ -89,6 +89,24 @@ pub(crate) fn resolve_repository_root(path: &Path) -> Result<PathBuf, GitTooling
     Ok(PathBuf::from(root))
 }
 
+/// The git dir shared by all worktrees of the repository containing `path`,
+/// i.e. where its objects and shared refs live. For a linked worktree this is
+/// the main repository's `.git`, not the worktree's own git dir; for a
+/// submodule checkout it is the submodule's dir under the superproject's
+/// `.git/modules`.
+pub(crate) fn resolve_git_common_dir(path: &Path) -> Result<PathBuf, GitToolingError> {
+    let common_dir = run_git_for_stdout(
+        path,
+        vec![
+            OsString::from("rev-parse"),
+            OsString::from("--git-common-dir"),
+        ],
+        None,
+    )?;
+    // Older git prints this relative to the working directory.
+    Ok(path.join(common_dir))
+}
+
 pub(crate) fn apply_repo_prefix_to_force_include(
     prefix: Option<&Path>,
     paths: &[PathBuf],
@@ -161,6 +179,35 @@ where
         })
 }
 
+/// Like [`run_git_for_stdout`], but keeps leading and trailing whitespace.
+pub(crate) fn run_git_for_raw_stdout<I, S>(
+    dir: &Path,
+    args: I,
+    env: Option<&[(OsString, OsString)]>,
+) -> Result<String, GitToolingError>
+where
+    I: IntoIterator<Item = S>,
+    S: AsRef<OsStr>,
+{
+    let run = run_git(dir, args, env)?;
+    String::from_utf8(run.output.stdout).map_err(|source| GitToolingError::GitOutputUtf8 {
+        command: run.command,
+        source,
+    })
+}
+
+/// Environment variables that override which repository, worktree or index
+/// a git command uses.
+const REPOSITORY_ENV_VARS: &[&str] = &[
+    "GIT_DIR",
+    "GIT_WORK_TREE",
+    "GIT_COMMON_DIR",
+    "GIT_INDEX_FILE",
+    "GIT_OBJECT_DIRECTORY",
+    "GIT_ALTERNATE_OBJECT_DIRECTORIES",
+    "GIT_PREFIX",
+];
+
 fn run_git<I, S>(
     dir: &Path,
     args: I,
@@ -179,6 +226,12 @@ where
     let command_string = build_command_string(&args_vec);
     let mut command = Command::new("git");
     command.current_dir(dir);
+    // Let `dir` alone decide the repository. Inherited variables such as
+    // those git sets for hooks would otherwise point a linked worktree or
+    // submodule checkout at another repository's git dir or index.
+    for key in REPOSITORY_ENV_VARS {
+        command.env_remove(key);
+    }
     if let Some(envs) = env {
         for (key, value) in envs {
             command.env(key, value);
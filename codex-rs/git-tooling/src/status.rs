@@ -0,0 +1,171 @@
+use std::ffi::OsString;
+use std::path::Path;
+
+use crate::GitToolingError;
+use crate::operations::ensure_git_repository;
+use crate::operations::resolve_repository_root;
+use crate::operations::run_git_for_stdout;
+
+/// Summary of a repository's state: the checked out branch, how many paths
+/// have uncommitted changes, and how far the branch is from its upstream.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RepoStatus {
+    branch: Option<String>,
+    dirty_files: usize,
+    ahead_behind: Option<(usize, usize)>,
+}
+
+impl RepoStatus {
+    /// Checked out branch, or `None` on a detached `HEAD`.
+    pub fn branch(&self) -> Option<&str> {
+        self.branch.as_deref()
+    }
+
+    /// Number of modified, staged, conflicted or untracked paths.
+    pub fn dirty_files(&self) -> usize {
+        self.dirty_files
+    }
+
+    /// Commits the branch is ahead of its upstream, if it has one.
+    pub fn ahead(&self) -> Option<usize> {
+        self.ahead_behind.map(|(ahead, _)| ahead)
+    }
+
+    /// Commits the branch is behind its upstream, if it has one.
+    pub fn behind(&self) -> Option<usize> {
+        self.ahead_behind.map(|(_, behind)| behind)
+    }
+}
+
+/// Read the status of the repository containing `repo_path` with a single
+/// `git status` call. Ignored files do not count as dirty.
+pub fn repo_status(repo_path: &Path) -> Result<RepoStatus, GitToolingError> {
+    ensure_git_repository(repo_path)?;
+
+    let repo_root = resolve_repository_root(repo_path)?;
+    let output = run_git_for_stdout(
+        repo_root.as_path(),
+        vec![
+            OsString::from("status"),
+            OsString::from("--porcelain=v2"),
+            OsString::from("--branch"),
+            OsString::from("--untracked-files=normal"),
+        ],
+        None,
+    )?;
+    Ok(parse_porcelain_v2(&output))
+}
+
+fn parse_porcelain_v2(output: &str) -> RepoStatus {
+    let mut status = RepoStatus {
+        branch: None,
+        dirty_files: 0,
+        ahead_behind: None,
+    };
+    for line in output.lines() {
+        let Some(header) = line.strip_prefix("# ") else {
+            if !line.is_empty() {
+                status.dirty_files += 1;
+            }
+            continue;
+        };
+        if let Some(head) = header.strip_prefix("branch.head ") {
+            if head != "(detached)" {
+                status.branch = Some(head.to_string());
+            }
+        } else if let Some(counts) = header.strip_prefix("branch.ab ") {
+            let mut counts = counts
+                .split_whitespace()
+                .map(|count| count.trim_start_matches(['+', '-']).parse::<usize>());
+            if let (Some(Ok(ahead)), Some(Ok(behind))) = (counts.next(), counts.next()) {
+                status.ahead_behind = Some((ahead, behind));
+            }
+        }
+    }
+    status
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use std::process::Command;
+
+    /// Runs a git command in the test repository and asserts success.
+    fn run_git_in(repo_path: &Path, args: &[&str]) {
+        let status = Command::new("git")
+            .current_dir(repo_path)
+            .args([
+                "-c",
+                "user.name=Tester",
+                "-c",
+                "user.email=test@example.com",
+            ])
+            .args(args)
+            .status()
+            .expect("git command");
+        assert!(status.success(), "git command failed: {args:?}");
+    }
+
+    #[test]
+    /// Parses branch, ahead/behind counts and changed entries.
+    fn parse_porcelain_v2_reads_headers_and_entries() {
+        let output = "# branch.oid 1234\n\
+                      # branch.head feature\n\
+                      # branch.upstream origin/feature\n\
+                      # branch.ab +2 -1\n\
+                      1 .M N... 100644 100644 100644 abc abc src/lib.rs\n\
+                      ? notes.txt";
+        assert_eq!(
+            parse_porcelain_v2(output),
+            RepoStatus {
+                branch: Some("feature".to_string()),
+                dirty_files: 2,
+                ahead_behind: Some((2, 1)),
+            }
+        );
+
+        let detached = parse_porcelain_v2("# branch.oid 1234\n# branch.head (detached)");
+        assert_eq!(detached.branch(), None);
+        assert_eq!(detached.ahead(), None);
+    }
+
+    #[test]
+    /// Reports dirty files and ahead/behind counts against an upstream.
+    fn repo_status_counts_changes_and_upstream_distance() -> Result<(), GitToolingError> {
+        let temp = tempfile::tempdir()?;
+        let upstream = temp.path().join("upstream");
+        let repo = temp.path().join("repo");
+        std::fs::create_dir(&upstream)?;
+        run_git_in(&upstream, &["init", "--initial-branch=main"]);
+        std::fs::write(upstream.join("file.txt"), "hello\n")?;
+        run_git_in(&upstream, &["add", "--all"]);
+        run_git_in(&upstream, &["commit", "-m", "init"]);
+        run_git_in(
+            temp.path(),
+            &["clone", upstream.to_str().expect("utf-8 path"), "repo"],
+        );
+
+        let clean = repo_status(&repo)?;
+        assert_eq!(clean.branch(), Some("main"));
+        assert_eq!(clean.dirty_files(), 0);
+        assert_eq!((clean.ahead(), clean.behind()), (Some(0), Some(0)));
+
+        std::fs::write(repo.join("file.txt"), "changed\n")?;
+        run_git_in(&repo, &["commit", "-am", "local"]);
+        std::fs::write(repo.join("file.txt"), "changed again\n")?;
+        std::fs::write(repo.join("new.txt"), "new\n")?;
+        std::fs::write(upstream.join("other.txt"), "other\n")?;
+        run_git_in(&upstream, &["add", "--all"]);
+        run_git_in(&upstream, &["commit", "-m", "remote"]);
+        run_git_in(&repo, &["fetch", "--quiet"]);
+
+        let status = repo_status(&repo)?;
+        assert_eq!(status.dirty_files(), 2);
+        assert_eq!((status.ahead(), status.behind()), (Some(1), Some(1)));
+
+        run_git_in(&repo, &["checkout", "--quiet", "-b", "local-only"]);
+        assert_eq!(repo_status(&repo)?.ahead(), None);
+        Ok(())
+    }
+}
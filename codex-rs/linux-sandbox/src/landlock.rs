@@ -7,11 +7,15 @@ use codex_core::error::Result;
 use codex_core::error::SandboxErr;
 use codex_core::protocol::SandboxPolicy;
 
+use crate::proxy_bridge::isolate_network_behind_proxy;
+
 use landlock::ABI;
 use landlock::Access;
 use landlock::AccessFs;
+use landlock::AccessNet;
 use landlock::CompatLevel;
 use landlock::Compatible;
+use landlock::NetPort;
 use landlock::Ruleset;
 use landlock::RulesetAttr;
 use landlock::RulesetCreatedAttr;
@@ -27,12 +31,26 @@ use seccompiler::apply_filter;
 
 /// Apply sandbox policies inside this thread so only the child inherits
 /// them, not the entire CLI process.
+///
+/// `proxy_port` is the loopback port of the domain-filtering proxy; when it is
+/// set, the command gets a private network namespace in which TCP connections
+/// to `127.0.0.1:<proxy_port>` reach the proxy and nothing else is routable.
+/// `extra_writable_paths` are writable in addition to the policy's roots.
 pub(crate) fn apply_sandbox_policy_to_current_thread(
     sandbox_policy: &SandboxPolicy,
     cwd: &Path,
+    proxy_port: Option<u16>,
+    extra_writable_paths: Vec<PathBuf>,
 ) -> Result<()> {
     if !sandbox_policy.has_full_network_access() {
-        install_network_seccomp_filter_on_current_thread()?;
+        match proxy_port {
+            Some(port) => {
+                isolate_network_behind_proxy(port)?;
+                install_network_seccomp_filter_on_current_thread(true)?;
+                install_network_landlock_rules_on_current_thread(port)?;
+            }
+            None => install_network_seccomp_filter_on_current_thread(false)?,
+        }
     }
 
     if !sandbox_policy.has_full_disk_write_access() {
@@ -40,6 +58,13 @@ pub(crate) fn apply_sandbox_policy_to_current_thread(
             .get_writable_roots_with_cwd(cwd)
             .into_iter()
             .map(|writable_root| writable_root.root)
+            // A configured path that does not exist (yet) would make the
+            // whole ruleset fail to apply, so it is skipped.
+            .chain(
+                extra_writable_paths
+                    .into_iter()
+                    .filter(|path| path.exists()),
+            )
             .collect();
         install_filesystem_landlock_rules_on_current_thread(writable_roots)?;
     }
@@ -83,8 +108,11 @@ fn install_filesystem_landlock_rules_on_current_thread(writable_roots: Vec<PathB
 }
 
 /// Installs a seccomp filter that blocks outbound network access except for
-/// AF_UNIX domain sockets.
-fn install_network_seccomp_filter_on_current_thread() -> std::result::Result<(), SandboxErr> {
+/// AF_UNIX domain sockets. With `allow_tcp_clients`, TCP client sockets are
+/// permitted too; callers must then restrict where they can connect.
+fn install_network_seccomp_filter_on_current_thread(
+    allow_tcp_clients: bool,
+) -> std::result::Result<(), SandboxErr> {
     // Build rule map.
     let mut rules: BTreeMap<i64, Vec<SeccompRule>> = BTreeMap::new();
 
@@ -93,25 +121,27 @@ fn install_network_seccomp_filter_on_current_thread() -> std::result::Result<(),
         rules.insert(nr, vec![]); // empty rule vec = unconditional match
     };
 
-    deny_syscall(libc::SYS_connect);
     deny_syscall(libc::SYS_accept);
     deny_syscall(libc::SYS_accept4);
     deny_syscall(libc::SYS_bind);
     deny_syscall(libc::SYS_listen);
-    deny_syscall(libc::SYS_getpeername);
-    deny_syscall(libc::SYS_getsockname);
-    deny_syscall(libc::SYS_shutdown);
-    deny_syscall(libc::SYS_sendto);
-    deny_syscall(libc::SYS_sendmsg);
     deny_syscall(libc::SYS_sendmmsg);
-    // NOTE: allowing recvfrom allows some tools like: `cargo clippy` to run
-    // with their socketpair + child processes for sub-proc management
-    // deny_syscall(libc::SYS_recvfrom);
-    deny_syscall(libc::SYS_recvmsg);
     deny_syscall(libc::SYS_recvmmsg);
-    deny_syscall(libc::SYS_getsockopt);
-    deny_syscall(libc::SYS_setsockopt);
     deny_syscall(libc::SYS_ptrace);
+    if !allow_tcp_clients {
+        deny_syscall(libc::SYS_connect);
+        deny_syscall(libc::SYS_getpeername);
+        deny_syscall(libc::SYS_getsockname);
+        deny_syscall(libc::SYS_shutdown);
+        deny_syscall(libc::SYS_sendto);
+        deny_syscall(libc::SYS_sendmsg);
+        // NOTE: allowing recvfrom allows some tools like: `cargo clippy` to run
+        // with their socketpair + child processes for sub-proc management
+        // deny_syscall(libc::SYS_recvfrom);
+        deny_syscall(libc::SYS_recvmsg);
+        deny_syscall(libc::SYS_getsockopt);
+        deny_syscall(libc::SYS_setsockopt);
+    }
 
     // For `socket` we allow AF_UNIX (arg0 == AF_UNIX) and deny everything else.
     let unix_only_rule = SeccompRule::new(vec![SeccompCondition::new(
@@ -121,7 +151,12 @@ fn install_network_seccomp_filter_on_current_thread() -> std::result::Result<(),
         libc::AF_UNIX as u64,
     )?])?;
 
-    rules.insert(libc::SYS_socket, vec![unix_only_rule.clone()]);
+    let socket_rules = if allow_tcp_clients {
+        tcp_client_socket_rules()?
+    } else {
+        vec![unix_only_rule.clone()]
+    };
+    rules.insert(libc::SYS_socket, socket_rules);
     rules.insert(libc::SYS_socketpair, vec![unix_only_rule]); // always deny (Unix can use socketpair but fine, keep open?)
 
     let filter = SeccompFilter::new(
@@ -143,3 +178,57 @@ fn install_network_seccomp_filter_on_current_thread() -> std::result::Result<(),
 
     Ok(())
 }
+
+/// `socket` rules that deny every domain other than AF_UNIX, AF_INET and
+/// AF_INET6, and any non-stream (UDP, raw) socket in the two IP domains.
+fn tcp_client_socket_rules() -> std::result::Result<Vec<SeccompRule>, SandboxErr> {
+    let domain_is = |domain: i32| {
+        SeccompCondition::new(0, SeccompCmpArgLen::Dword, SeccompCmpOp::Eq, domain as u64)
+    };
+    let domain_is_not = |domain: i32| {
+        SeccompCondition::new(0, SeccompCmpArgLen::Dword, SeccompCmpOp::Ne, domain as u64)
+    };
+    // The type argument may carry SOCK_NONBLOCK/SOCK_CLOEXEC in its high bits.
+    let type_is = |socket_type: i32| {
+        SeccompCondition::new(
+            1,
+            SeccompCmpArgLen::Dword,
+            SeccompCmpOp::MaskedEq(0xf),
+            socket_type as u64,
+        )
+    };
+
+    let mut rules = vec![SeccompRule::new(vec![
+        domain_is_not(libc::AF_UNIX)?,
+        domain_is_not(libc::AF_INET)?,
+        domain_is_not(libc::AF_INET6)?,
+    ])?];
+    for domain in [libc::AF_INET, libc::AF_INET6] {
+        for socket_type in [libc::SOCK_DGRAM, libc::SOCK_RAW, libc::SOCK_SEQPACKET] {
+            rules.push(SeccompRule::new(vec![
+                domain_is(domain)?,
+                type_is(socket_type)?,
+            ])?);
+        }
+    }
+    Ok(rules)
+}
+
+/// Installs a Landlock ruleset that only lets TCP connect to `proxy_port`, so
+/// within the private namespace only the proxy forwarder is reachable.
+/// Requires Landlock network support (Linux 6.7+); fails rather than leaving
+/// the network open on older kernels.
+fn install_network_landlock_rules_on_current_thread(proxy_port: u16) -> Result<()> {
+    let status = Ruleset::default()
+        .set_compatibility(CompatLevel::HardRequirement)
+        .handle_access(AccessNet::ConnectTcp)?
+        .create()?
+        .add_rule(NetPort::new(proxy_port, AccessNet::ConnectTcp))?
+        .restrict_self()?;
+
+    if status.ruleset != landlock::RulesetStatus::FullyEnforced {
+        return Err(CodexErr::Sandbox(SandboxErr::LandlockRestrict));
+    }
+
+    Ok(())
+}
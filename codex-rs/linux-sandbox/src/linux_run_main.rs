@@ -1,4 +1,5 @@
 use clap::Parser;
+use codex_core::CODEX_LINUX_SANDBOX_ARG1;
 use std::ffi::CString;
 use std::path::PathBuf;
 
@@ -6,6 +7,17 @@ use crate::landlock::apply_sandbox_policy_to_current_thread;
 
 #[derive(Debug, Parser)]
 pub struct LandlockCommand {
+    /// Port of Codex's filtering proxy on loopback. When set under a policy
+    /// without network access, TCP connections to this port are the only
+    /// outbound traffic allowed.
+    #[arg(long)]
+    pub proxy_port: Option<u16>,
+
+    /// Additional path to make writable, beyond the writable roots of the
+    /// sandbox policy. May be repeated.
+    #[arg(long = "extra-writable-path")]
+    pub extra_writable_paths: Vec<PathBuf>,
+
     /// It is possible that the cwd used in the context of the sandbox policy
     /// is different from the cwd of the process to spawn.
     pub sandbox_policy_cwd: PathBuf,
@@ -18,13 +30,26 @@ pub struct LandlockCommand {
 }
 
 pub fn run_main() -> ! {
+    // When invoked through the main Codex executable rather than the
+    // `codex-linux-sandbox` alias, skip the flag that selected this entrypoint.
+    let args = std::env::args_os()
+        .enumerate()
+        .filter(|(i, arg)| !(*i == 1 && arg == CODEX_LINUX_SANDBOX_ARG1))
+        .map(|(_, arg)| arg);
     let LandlockCommand {
+        proxy_port,
+        extra_writable_paths,
         sandbox_policy_cwd,
         sandbox_policy,
         command,
-    } = LandlockCommand::parse();
+    } = LandlockCommand::parse_from(args);
 
-    if let Err(e) = apply_sandbox_policy_to_current_thread(&sandbox_policy, &sandbox_policy_cwd) {
+    if let Err(e) = apply_sandbox_policy_to_current_thread(
+        &sandbox_policy,
+        &sandbox_policy_cwd,
+        proxy_port,
+        extra_writable_paths,
+    ) {
         panic!("error running landlock: {e:?}");
     }
 
@@ -0,0 +1,226 @@
+//! Confines a sandboxed command's network to Codex's filtering proxy.
+//!
+//! The command gets a private network namespace whose only interface is
+//! loopback, so no other host is routable from it. A forwarder process forked
+//! beforehand stays in the original namespace and relays connections made to
+//! `127.0.0.1:<proxy_port>` inside the sandbox to the proxy listening on the
+//! same port outside.
+
+use std::io;
+use std::io::Read;
+use std::io::Write;
+use std::net::Ipv4Addr;
+use std::net::Shutdown;
+use std::net::TcpListener;
+use std::net::TcpStream;
+use std::os::fd::AsRawFd;
+use std::os::fd::FromRawFd;
+use std::os::fd::OwnedFd;
+use std::os::fd::RawFd;
+use std::os::unix::net::UnixStream;
+
+/// Moves the current process into a new user and network namespace with only
+/// loopback up, and forwards `127.0.0.1:<proxy_port>` in it to the proxy.
+///
+/// Must be called while the process is single-threaded, before the command
+/// is exec'd. Fails, rather than leaving the network open, when unprivileged
+/// user namespaces are unavailable.
+pub(crate) fn isolate_network_behind_proxy(proxy_port: u16) -> io::Result<()> {
+    let (control, forwarder_control) = UnixStream::pair()?;
+    // SAFETY: no pointer arguments.
+    let helper_pid = unsafe { libc::getpid() };
+    // SAFETY: the sandbox helper is single-threaded at this point, so the
+    // child may keep running Rust code after `fork`.
+    match unsafe { libc::fork() } {
+        -1 => return Err(io::Error::last_os_error()),
+        0 => {
+            drop(control);
+            run_forwarder(forwarder_control, helper_pid, proxy_port);
+        }
+        _ => drop(forwarder_control),
+    }
+
+    enter_private_network_namespace()?;
+    let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, proxy_port))?;
+    send_fd(&control, listener.as_raw_fd())
+    // The listener and control socket are close-on-exec, so only the
+    // forwarder keeps them once the command starts.
+}
+
+fn enter_private_network_namespace() -> io::Result<()> {
+    // SAFETY: plain libc calls without pointer arguments.
+    let (uid, gid) = unsafe { (libc::geteuid(), libc::getegid()) };
+    // SAFETY: no pointer arguments.
+    if unsafe { libc::unshare(libc::CLONE_NEWUSER | libc::CLONE_NEWNET) } != 0 {
+        return Err(io::Error::last_os_error());
+    }
+    // Keep the same uid and gid inside the namespace so file ownership and
+    // permissions are unchanged for the command.
+    std::fs::write("/proc/self/setgroups", "deny")?;
+    std::fs::write("/proc/self/uid_map", format!("{uid} {uid} 1"))?;
+    std::fs::write("/proc/self/gid_map", format!("{gid} {gid} 1"))?;
+    bring_up_loopback()
+}
+
+fn bring_up_loopback() -> io::Result<()> {
+    // SAFETY: no pointer arguments; the result is checked below.
+    let fd = unsafe { libc::socket(libc::AF_INET, libc::SOCK_DGRAM | libc::SOCK_CLOEXEC, 0) };
+    if fd < 0 {
+        return Err(io::Error::last_os_error());
+    }
+    // SAFETY: `fd` is a freshly created socket owned by nobody else.
+    let socket = unsafe { OwnedFd::from_raw_fd(fd) };
+
+    // SAFETY: `ifreq` is plain old data, for which all zeroes is valid.
+    let mut request: libc::ifreq = unsafe { std::mem::zeroed() };
+    for (dst, src) in request.ifr_name.iter_mut().zip(b"lo") {
+        *dst = *src as libc::c_char;
+    }
+    // SAFETY: `request` is a valid `ifreq` naming the loopback interface.
+    if unsafe { libc::ioctl(socket.as_raw_fd(), libc::SIOCGIFFLAGS as _, &mut request) } < 0 {
+        return Err(io::Error::last_os_error());
+    }
+    // SAFETY: SIOCGIFFLAGS filled in the flags member of the union.
+    unsafe {
+        request.ifr_ifru.ifru_flags |= libc::IFF_UP as libc::c_short;
+    }
+    // SAFETY: as above.
+    if unsafe { libc::ioctl(socket.as_raw_fd(), libc::SIOCSIFFLAGS as _, &request) } < 0 {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(())
+}
+
+/// Body of the forwarder process: accepts connections on the listener the
+/// sandboxed side sends over `control` and pipes each to the proxy.
+fn run_forwarder(control: UnixStream, helper_pid: libc::pid_t, proxy_port: u16) -> ! {
+    // Exit together with the sandboxed command, which replaces the parent.
+    // SAFETY: no pointer arguments.
+    unsafe {
+        libc::prctl(libc::PR_SET_PDEATHSIG, libc::SIGKILL);
+        if libc::getppid() != helper_pid {
+            libc::_exit(0);
+        }
+    }
+    // Do not hold on to the command's stdio, or readers of its output would
+    // wait for the forwarder too.
+    if let Ok(dev_null) = std::fs::File::options()
+        .read(true)
+        .write(true)
+        .open("/dev/null")
+    {
+        for fd in 0..=2 {
+            // SAFETY: both fds are open; dup2 replaces the target atomically.
+            unsafe {
+                libc::dup2(dev_null.as_raw_fd(), fd);
+            }
+        }
+    }
+    let listener = match recv_fd(&control) {
+        // SAFETY: the fd was just received and is owned by this process.
+        Ok(fd) => unsafe { TcpListener::from_raw_fd(fd) },
+        Err(_) => std::process::exit(1),
+    };
+    drop(control);
+    for inbound in listener.incoming().flatten() {
+        std::thread::spawn(move || {
+            if let Ok(outbound) = TcpStream::connect((Ipv4Addr::LOCALHOST, proxy_port)) {
+                relay(inbound, outbound);
+            }
+        });
+    }
+    std::process::exit(0);
+}
+
+/// Copies bytes both ways until each side has closed its half.
+fn relay(inbound: TcpStream, outbound: TcpStream) {
+    let (Ok(inbound_reader), Ok(outbound_reader)) = (inbound.try_clone(), outbound.try_clone())
+    else {
+        return;
+    };
+    let upload = std::thread::spawn(move || copy_then_shutdown(inbound_reader, outbound));
+    copy_then_shutdown(outbound_reader, inbound);
+    let _ = upload.join();
+}
+
+fn copy_then_shutdown(mut from: TcpStream, mut to: TcpStream) {
+    let mut buf = [0u8; 16 * 1024];
+    loop {
+        match from.read(&mut buf) {
+            Ok(0) | Err(_) => break,
+            Ok(n) => {
+                if to.write_all(&buf[..n]).is_err() {
+                    break;
+                }
+            }
+        }
+    }
+    let _ = to.shutdown(Shutdown::Write);
+}
+
+/// Room for one `SCM_RIGHTS` message carrying a single fd, as `u64`s so the
+/// buffer is aligned for `cmsghdr`.
+const CONTROL_WORDS: usize = 4;
+
+fn send_fd(socket: &UnixStream, fd: RawFd) -> io::Result<()> {
+    let mut byte = [0u8; 1];
+    let mut iov = libc::iovec {
+        iov_base: byte.as_mut_ptr().cast(),
+        iov_len: byte.len(),
+    };
+    let mut control = [0u64; CONTROL_WORDS];
+    // SAFETY: `msghdr` is plain old data, for which all zeroes is valid.
+    let mut msg: libc::msghdr = unsafe { std::mem::zeroed() };
+    msg.msg_iov = &mut iov;
+    msg.msg_iovlen = 1;
+    msg.msg_control = control.as_mut_ptr().cast();
+    // SAFETY: CMSG_SPACE only does arithmetic.
+    msg.msg_controllen = unsafe { libc::CMSG_SPACE(size_of::<RawFd>() as u32) } as _;
+    // SAFETY: `msg_control` points to a buffer large enough for one header
+    // carrying one fd, so the header and its data are in bounds.
+    unsafe {
+        let cmsg = libc::CMSG_FIRSTHDR(&msg);
+        (*cmsg).cmsg_level = libc::SOL_SOCKET;
+        (*cmsg).cmsg_type = libc::SCM_RIGHTS;
+        (*cmsg).cmsg_len = libc::CMSG_LEN(size_of::<RawFd>() as u32) as _;
+        std::ptr::write_unaligned(libc::CMSG_DATA(cmsg).cast::<RawFd>(), fd);
+    }
+    // SAFETY: `msg` and everything it points to outlive the call.
+    if unsafe { libc::sendmsg(socket.as_raw_fd(), &msg, 0) } < 0 {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(())
+}
+
+fn recv_fd(socket: &UnixStream) -> io::Result<RawFd> {
+    let mut byte = [0u8; 1];
+    let mut iov = libc::iovec {
+        iov_base: byte.as_mut_ptr().cast(),
+        iov_len: byte.len(),
+    };
+    let mut control = [0u64; CONTROL_WORDS];
+    // SAFETY: `msghdr` is plain old data, for which all zeroes is valid.
+    let mut msg: libc::msghdr = unsafe { std::mem::zeroed() };
+    msg.msg_iov = &mut iov;
+    msg.msg_iovlen = 1;
+    msg.msg_control = control.as_mut_ptr().cast();
+    msg.msg_controllen = size_of_val(&control) as _;
+    // SAFETY: `msg` and everything it points to outlive the call.
+    if unsafe { libc::recvmsg(socket.as_raw_fd(), &mut msg, libc::MSG_CMSG_CLOEXEC) } < 0 {
+        return Err(io::Error::last_os_error());
+    }
+    // SAFETY: the kernel filled in `msg_control`; CMSG_FIRSTHDR checks that a
+    // header fits before returning it.
+    unsafe {
+        let cmsg = libc::CMSG_FIRSTHDR(&msg);
+        if cmsg.is_null()
+            || (*cmsg).cmsg_level != libc::SOL_SOCKET
+            || (*cmsg).cmsg_type != libc::SCM_RIGHTS
+        {
+            return Err(io::Error::other("expected a listener fd from the sandbox"));
+        }
+        Ok(std::ptr::read_unaligned(
+            libc::CMSG_DATA(cmsg).cast::<RawFd>(),
+        ))
+    }
+}
@@ -1,4 +1,6 @@
 #![cfg(target_os = "linux")]
+use codex_core::config_types::ResourceLimits;
+use codex_core::config_types::SandboxExtras;
 use codex_core::config_types::ShellEnvironmentPolicy;
 use codex_core::error::CodexErr;
 use codex_core::error::SandboxErr;
@@ -8,6 +10,9 @@ use codex_core::exec::process_exec_tool_call;
 use codex_core::exec_env::create_env;
 use codex_core::protocol::SandboxPolicy;
 use std::collections::HashMap;
+use std::net::Ipv4Addr;
+use std::net::TcpListener;
+use std::net::UdpSocket;
 use std::path::PathBuf;
 use tempfile::NamedTempFile;
 
@@ -33,8 +38,17 @@ fn create_env_from_core_vars() -> HashMap<String, String> {
     create_env(&policy)
 }
 
-#[expect(clippy::print_stdout, clippy::expect_used, clippy::unwrap_used)]
 async fn run_cmd(cmd: &[&str], writable_roots: &[PathBuf], timeout_ms: u64) {
+    run_cmd_with_extras(cmd, writable_roots, SandboxExtras::default(), timeout_ms).await;
+}
+
+#[expect(clippy::print_stdout, clippy::expect_used, clippy::unwrap_used)]
+async fn run_cmd_with_extras(
+    cmd: &[&str],
+    writable_roots: &[PathBuf],
+    sandbox_extras: SandboxExtras,
+    timeout_ms: u64,
+) {
     let cwd = std::env::current_dir().expect("cwd should exist");
     let sandbox_cwd = cwd.clone();
     let params = ExecParams {
@@ -44,11 +58,15 @@ async fn run_cmd(cmd: &[&str], writable_roots: &[PathBuf], timeout_ms: u64) {
         env: create_env_from_core_vars(),
         with_escalated_permissions: None,
         justification: None,
+        tty: false,
+        resource_limits: ResourceLimits::default(),
+        sandbox_extras,
     };
 
     let sandbox_policy = SandboxPolicy::WorkspaceWrite {
         writable_roots: writable_roots.to_vec(),
         network_access: false,
+        allowed_domains: vec![],
         // Exclude tmp-related folders from writable roots because we need a
         // folder that is writable by tests but that we intentionally disallow
         // writing to in the sandbox.
@@ -64,6 +82,8 @@ async fn run_cmd(cmd: &[&str], writable_roots: &[PathBuf], timeout_ms: u64) {
         sandbox_cwd.as_path(),
         &codex_linux_sandbox_exe,
         None,
+        None,
+        None,
     )
     .await
     .unwrap();
@@ -123,6 +143,26 @@ async fn test_writable_root() {
     .await;
 }
 
+#[tokio::test]
+async fn test_extra_landlock_path() {
+    let tmpdir = tempfile::tempdir().unwrap();
+    let file_path = tmpdir.path().join("test");
+    run_cmd_with_extras(
+        &[
+            "bash",
+            "-lc",
+            &format!("echo blah > {}", file_path.to_string_lossy()),
+        ],
+        &[],
+        SandboxExtras {
+            extra_landlock_paths: vec![tmpdir.path().to_path_buf()],
+            ..Default::default()
+        },
+        LONG_TIMEOUT_MS,
+    )
+    .await;
+}
+
 #[tokio::test]
 #[should_panic(expected = "Sandbox(Timeout")]
 async fn test_timeout() {
@@ -146,6 +186,9 @@ async fn assert_network_blocked(cmd: &[&str]) {
         env: create_env_from_core_vars(),
         with_escalated_permissions: None,
         justification: None,
+        tty: false,
+        resource_limits: ResourceLimits::default(),
+        sandbox_extras: SandboxExtras::default(),
     };
 
     let sandbox_policy = SandboxPolicy::new_read_only_policy();
@@ -158,6 +201,8 @@ async fn assert_network_blocked(cmd: &[&str]) {
         sandbox_cwd.as_path(),
         &codex_linux_sandbox_exe,
         None,
+        None,
+        None,
     )
     .await;
 
@@ -234,3 +279,44 @@ async fn sandbox_blocks_dev_tcp_redirection() {
     // all images ship bash, so we guard against 127 as well.
     assert_network_blocked(&["bash", "-c", "echo hi > /dev/tcp/127.0.0.1/80"]).await;
 }
+
+/// With a proxy port, the sandbox may only reach that port on loopback. A
+/// listener on every address of the host must not be reachable through the
+/// host's other addresses.
+#[test]
+#[expect(clippy::expect_used)]
+fn proxy_port_is_only_reachable_on_loopback() {
+    let listener = TcpListener::bind((Ipv4Addr::UNSPECIFIED, 0)).expect("bind listener");
+    let port = listener.local_addr().expect("listener addr").port();
+    // Any routed local address will do; without one there is nothing to test.
+    let Some(host_ip) = UdpSocket::bind((Ipv4Addr::UNSPECIFIED, 0))
+        .and_then(|socket| {
+            socket.connect((Ipv4Addr::new(192, 0, 2, 1), 9))?;
+            socket.local_addr()
+        })
+        .ok()
+        .map(|addr| addr.ip())
+        .filter(|ip| !ip.is_loopback())
+    else {
+        return;
+    };
+
+    let connect_from_sandbox = |ip: String| {
+        std::process::Command::new(env!("CARGO_BIN_EXE_codex-linux-sandbox"))
+            .args(["--proxy-port", &port.to_string()])
+            .arg(std::env::current_dir().expect("cwd should exist"))
+            .arg(r#"{"mode":"read-only"}"#)
+            .args(["--", "bash", "-c", &format!("exec 3<>/dev/tcp/{ip}/{port}")])
+            .status()
+            .expect("run codex-linux-sandbox")
+    };
+
+    assert!(
+        connect_from_sandbox(Ipv4Addr::LOCALHOST.to_string()).success(),
+        "the proxy port should be reachable on loopback"
+    );
+    assert!(
+        !connect_from_sandbox(host_ip.to_string()).success(),
+        "the proxy port should not be reachable on {host_ip}"
+    );
+}
@@ -1,6 +1,10 @@
+mod mcp_oauth;
 mod pkce;
 mod server;
 
+pub use mcp_oauth::McpDeviceLogin;
+pub use mcp_oauth::start_mcp_device_login;
+
 pub use server::LoginServer;
 pub use server::ServerOptions;
 pub use server::ShutdownHandle;
@@ -0,0 +1,201 @@
+//! OAuth 2.0 device authorization grant (RFC 8628) for MCP servers reached
+//! over HTTP. The user approves Codex in a browser on any device while the
+//! CLI polls the token endpoint; the resulting tokens are stored in
+//! `auth.json` by [`codex_core::mcp_oauth`] and refreshed from there.
+
+use std::io;
+use std::path::Path;
+use std::time::Duration;
+use std::time::Instant;
+
+use codex_core::config_types::McpServerOAuthConfig;
+use codex_core::default_client::create_client;
+use codex_core::mcp_oauth::McpOAuthTokens;
+use codex_core::mcp_oauth::OAuthErrorResponse;
+use codex_core::mcp_oauth::OAuthTokenResponse;
+use codex_core::mcp_oauth::save_mcp_oauth_tokens;
+use serde::Deserialize;
+
+const DEVICE_CODE_GRANT_TYPE: &str = "urn:ietf:params:oauth:grant-type:device_code";
+
+/// Polling interval used when the authorization server does not suggest one.
+const DEFAULT_POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Authorization server metadata (RFC 8414), reduced to what the device flow
+/// needs.
+#[derive(Deserialize, Debug)]
+struct AuthorizationServerMetadata {
+    device_authorization_endpoint: Option<String>,
+    token_endpoint: String,
+}
+
+#[derive(Deserialize, Debug)]
+struct DeviceAuthorizationResponse {
+    device_code: String,
+    user_code: String,
+    verification_uri: String,
+    #[serde(default)]
+    verification_uri_complete: Option<String>,
+    expires_in: u64,
+    #[serde(default)]
+    interval: Option<u64>,
+}
+
+/// A device login that is waiting for the user to approve it.
+#[derive(Debug)]
+pub struct McpDeviceLogin {
+    /// Code the user enters at `verification_uri`.
+    pub user_code: String,
+    /// Page where the user approves the login.
+    pub verification_uri: String,
+    /// `verification_uri` with the code already filled in, if offered.
+    pub verification_uri_complete: Option<String>,
+
+    client: reqwest::Client,
+    client_id: String,
+    token_endpoint: String,
+    device_code: String,
+    interval: Duration,
+    deadline: Instant,
+}
+
+/// Starts a device login for the MCP server at `server_url`: discovers its
+/// authorization server and requests a device code. Show the user
+/// [`McpDeviceLogin::user_code`] and [`McpDeviceLogin::verification_uri`],
+/// then call [`McpDeviceLogin::complete`].
+pub async fn start_mcp_device_login(
+    server_url: &str,
+    oauth: &McpServerOAuthConfig,
+) -> io::Result<McpDeviceLogin> {
+    let client = create_client();
+    let issuer = match &oauth.authorization_server {
+        Some(issuer) => issuer.clone(),
+        None => origin(server_url)?,
+    };
+    let metadata_url = format!(
+        "{}/.well-known/oauth-authorization-server",
+        issuer.trim_end_matches('/')
+    );
+    let metadata: AuthorizationServerMetadata = client
+        .get(&metadata_url)
+        .send()
+        .await
+        .and_then(reqwest::Response::error_for_status)
+        .map_err(|e| io::Error::other(format!("failed to fetch {metadata_url}: {e}")))?
+        .json()
+        .await
+        .map_err(io::Error::other)?;
+    let device_authorization_endpoint =
+        metadata.device_authorization_endpoint.ok_or_else(|| {
+            io::Error::other(format!(
+                "authorization server {issuer} does not support the device authorization grant"
+            ))
+        })?;
+
+    let scope = oauth.scopes.join(" ");
+    let mut form = vec![("client_id", oauth.client_id.as_str())];
+    if !scope.is_empty() {
+        form.push(("scope", scope.as_str()));
+    }
+    let response = client
+        .post(&device_authorization_endpoint)
+        .form(&form)
+        .send()
+        .await
+        .map_err(io::Error::other)?;
+    if !response.status().is_success() {
+        return Err(oauth_error(response, "device authorization request failed").await);
+    }
+    let device: DeviceAuthorizationResponse = response.json().await.map_err(io::Error::other)?;
+
+    Ok(McpDeviceLogin {
+        user_code: device.user_code,
+        verification_uri: device.verification_uri,
+        verification_uri_complete: device.verification_uri_complete,
+        client,
+        client_id: oauth.client_id.clone(),
+        token_endpoint: metadata.token_endpoint,
+        device_code: device.device_code,
+        interval: device
+            .interval
+            .map(Duration::from_secs)
+            .unwrap_or(DEFAULT_POLL_INTERVAL),
+        deadline: Instant::now() + Duration::from_secs(device.expires_in),
+    })
+}
+
+impl McpDeviceLogin {
+    /// Polls until the user approves or denies the login, or the device code
+    /// expires, and on approval stores the tokens for `server_name`.
+    pub async fn complete(mut self, codex_home: &Path, server_name: &str) -> io::Result<()> {
+        loop {
+            if Instant::now() >= self.deadline {
+                return Err(io::Error::new(
+                    io::ErrorKind::TimedOut,
+                    "the device code expired before the login was approved",
+                ));
+            }
+            tokio::time::sleep(self.interval).await;
+
+            let response = self
+                .client
+                .post(&self.token_endpoint)
+                .form(&[
+                    ("grant_type", DEVICE_CODE_GRANT_TYPE),
+                    ("device_code", self.device_code.as_str()),
+                    ("client_id", self.client_id.as_str()),
+                ])
+                .send()
+                .await
+                .map_err(io::Error::other)?;
+
+            if response.status().is_success() {
+                let body: OAuthTokenResponse = response.json().await.map_err(io::Error::other)?;
+                let tokens =
+                    McpOAuthTokens::from_response(body, self.token_endpoint, self.client_id, None);
+                return save_mcp_oauth_tokens(codex_home, server_name, tokens);
+            }
+
+            let status = response.status();
+            let error: OAuthErrorResponse = response
+                .json()
+                .await
+                .map_err(|_| io::Error::other(format!("token request failed: {status}")))?;
+            match error.error.as_str() {
+                "authorization_pending" => {}
+                "slow_down" => self.interval += Duration::from_secs(5),
+                _ => return Err(io::Error::other(format!("login failed: {error}"))),
+            }
+        }
+    }
+}
+
+/// `scheme://host[:port]` of `url`, where the authorization server metadata
+/// is looked up by default.
+fn origin(url: &str) -> io::Result<String> {
+    let parsed = url::Url::parse(url)
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, format!("{url}: {e}")))?;
+    Ok(parsed.origin().ascii_serialization())
+}
+
+async fn oauth_error(response: reqwest::Response, context: &str) -> io::Error {
+    let status = response.status();
+    match response.json::<OAuthErrorResponse>().await {
+        Ok(error) => io::Error::other(format!("{context}: {error}")),
+        Err(_) => io::Error::other(format!("{context}: {status}")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn metadata_is_looked_up_at_the_server_origin() {
+        assert_eq!(
+            origin("https://mcp.example.com:8443/v1/mcp").unwrap(),
+            "https://mcp.example.com:8443"
+        );
+        assert!(origin("not a url").is_err());
+    }
+}
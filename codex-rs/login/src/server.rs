@@ -476,6 +476,7 @@ async fn persist_tokens_async(
             openai_api_key: api_key,
             tokens: Some(tokens),
             last_refresh: Some(Utc::now()),
+            mcp_servers: codex_core::auth::existing_mcp_server_tokens(&auth_file),
         };
         codex_core::auth::write_auth_json(&auth_file, &auth)
     })
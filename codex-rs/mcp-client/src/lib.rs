@@ -1,3 +1,4 @@
 mod mcp_client;
+mod streamable_http;
 
 pub use mcp_client::McpClient;
@@ -2,7 +2,8 @@
 //!
 //! The client is intentionally lightweight – it is only capable of:
 //!   1. Spawning a subprocess that launches a conforming MCP server that
-//!      communicates over stdio.
+//!      communicates over stdio, or connecting to a hosted server over the
+//!      streamable HTTP transport.
 //!   2. Sending MCP requests and pairing them with their corresponding
 //!      responses.
 //!   3. Offering a convenience helper for the common `tools/list` request.
@@ -23,6 +24,11 @@ use anyhow::Result;
 use anyhow::anyhow;
 use mcp_types::CallToolRequest;
 use mcp_types::CallToolRequestParams;
+use mcp_types::CancelledNotification;
+use mcp_types::CancelledNotificationParams;
+use mcp_types::GetPromptRequest;
+use mcp_types::GetPromptRequestParams;
+use mcp_types::GetPromptResult;
 use mcp_types::InitializeRequest;
 use mcp_types::InitializeRequestParams;
 use mcp_types::InitializedNotification;
@@ -31,12 +37,23 @@ use mcp_types::JSONRPCMessage;
 use mcp_types::JSONRPCNotification;
 use mcp_types::JSONRPCRequest;
 use mcp_types::JSONRPCResponse;
+use mcp_types::ListPromptsRequest;
+use mcp_types::ListPromptsRequestParams;
+use mcp_types::ListPromptsResult;
+use mcp_types::ListResourcesRequest;
+use mcp_types::ListResourcesRequestParams;
+use mcp_types::ListResourcesResult;
 use mcp_types::ListToolsRequest;
 use mcp_types::ListToolsRequestParams;
 use mcp_types::ListToolsResult;
 use mcp_types::ModelContextProtocolNotification;
 use mcp_types::ModelContextProtocolRequest;
+use mcp_types::ProgressToken;
+use mcp_types::ReadResourceRequest;
+use mcp_types::ReadResourceRequestParams;
+use mcp_types::ReadResourceResult;
 use mcp_types::RequestId;
+use mcp_types::ServerNotification;
 use serde::Serialize;
 use serde::de::DeserializeOwned;
 use tokio::io::AsyncBufReadExt;
@@ -44,14 +61,18 @@ use tokio::io::AsyncWriteExt;
 use tokio::io::BufReader;
 use tokio::process::Command;
 use tokio::sync::Mutex;
+use tokio::sync::broadcast;
 use tokio::sync::mpsc;
 use tokio::sync::oneshot;
+use tokio::sync::watch;
 use tokio::time;
 use tracing::debug;
 use tracing::error;
 use tracing::info;
 use tracing::warn;
 
+use crate::streamable_http;
+
 /// Capacity of the bounded channels used for transporting messages between the
 /// client API and the IO tasks.
 const CHANNEL_CAPACITY: usize = 128;
@@ -64,8 +85,9 @@ pub struct McpClient {
     /// Retain this child process until the client is dropped. The Tokio runtime
     /// will make a "best effort" to reap the process after it exits, but it is
     /// not a guarantee. See the `kill_on_drop` documentation for details.
+    /// `None` for servers reached over HTTP.
     #[allow(dead_code)]
-    child: tokio::process::Child,
+    child: Option<tokio::process::Child>,
 
     /// Channel for sending JSON-RPC messages *to* the background writer task.
     outgoing_tx: mpsc::Sender<JSONRPCMessage>,
@@ -76,6 +98,17 @@ pub struct McpClient {
 
     /// Monotonically increasing counter used to generate request IDs.
     id_counter: AtomicI64,
+
+    /// Becomes `true` once the connection to the server is gone, e.g. because
+    /// a stdio server exited.
+    closed: watch::Receiver<bool>,
+
+    /// Sender for `closed` when no background task owns it, so that it lives
+    /// exactly as long as the client.
+    _closed_tx: Option<watch::Sender<bool>>,
+
+    /// Fan-out of server-initiated notifications.
+    notifications: broadcast::Sender<ServerNotification>,
 }
 
 impl McpClient {
@@ -138,35 +171,28 @@ impl McpClient {
         };
 
         // Spawn reader task. It reads line-delimited JSON from the child's
-        // STDOUT and dispatches responses to the pending map.
+        // STDOUT and dispatches responses to the pending map. When STDOUT
+        // closes the server is gone: outstanding requests fail right away
+        // instead of waiting for their timeout.
+        let (closed_tx, closed) = watch::channel(false);
+        let (notifications, _) = broadcast::channel(CHANNEL_CAPACITY);
         let reader_handle = {
             let pending = pending.clone();
+            let notifications = notifications.clone();
             let mut lines = BufReader::new(stdout).lines();
 
             tokio::spawn(async move {
                 while let Ok(Some(line)) = lines.next_line().await {
                     debug!("MCP message from server: {line}");
                     match serde_json::from_str::<JSONRPCMessage>(&line) {
-                        Ok(JSONRPCMessage::Response(resp)) => {
-                            Self::dispatch_response(resp, &pending).await;
-                        }
-                        Ok(JSONRPCMessage::Error(err)) => {
-                            Self::dispatch_error(err, &pending).await;
-                        }
-                        Ok(JSONRPCMessage::Notification(JSONRPCNotification { .. })) => {
-                            // For now we only log server-initiated notifications.
-                            info!("<- notification: {}", line);
-                        }
-                        Ok(other) => {
-                            // Batch responses and requests are currently not
-                            // expected from the server – log and ignore.
-                            info!("<- unhandled message: {:?}", other);
-                        }
+                        Ok(msg) => Self::dispatch_incoming(msg, &pending, &notifications).await,
                         Err(e) => {
                             error!("failed to deserialize JSONRPCMessage: {e}; line = {}", line)
                         }
                     }
                 }
+                pending.lock().await.clear();
+                let _ = closed_tx.send(true);
             })
         };
 
@@ -177,18 +203,90 @@ impl McpClient {
         let _ = (writer_handle, reader_handle);
 
         Ok(Self {
-            child,
+            child: Some(child),
+            outgoing_tx,
+            pending,
+            id_counter: AtomicI64::new(1),
+            closed,
+            _closed_tx: None,
+            notifications,
+        })
+    }
+
+    /// Connect to an MCP server that speaks the streamable HTTP transport at
+    /// `url`. `headers` are sent with every request, along with an
+    /// `Authorization: Bearer` header when `bearer_token` is set. As with
+    /// [`new_stdio_client`](Self::new_stdio_client), the caller is responsible
+    /// for sending the `initialize` request.
+    pub fn new_streamable_http_client(
+        url: String,
+        headers: HashMap<String, String>,
+        bearer_token: Option<String>,
+    ) -> Result<Self> {
+        let http_client = reqwest::Client::builder()
+            .default_headers(streamable_http::default_headers(headers, bearer_token)?)
+            .build()?;
+
+        let (outgoing_tx, outgoing_rx) = mpsc::channel::<JSONRPCMessage>(CHANNEL_CAPACITY);
+        let (incoming_tx, mut incoming_rx) = mpsc::channel::<JSONRPCMessage>(CHANNEL_CAPACITY);
+        let pending: Arc<Mutex<HashMap<i64, PendingSender>>> = Arc::new(Mutex::new(HashMap::new()));
+
+        tokio::spawn(streamable_http::run(
+            http_client,
+            url,
+            outgoing_rx,
+            incoming_tx,
+        ));
+        let (notifications, _) = broadcast::channel(CHANNEL_CAPACITY);
+        {
+            let pending = pending.clone();
+            let notifications = notifications.clone();
+            tokio::spawn(async move {
+                while let Some(msg) = incoming_rx.recv().await {
+                    Self::dispatch_incoming(msg, &pending, &notifications).await;
+                }
+            });
+        }
+
+        // Each request is a separate HTTP exchange, so there is no
+        // connection that can close.
+        let (closed_tx, closed) = watch::channel(false);
+
+        Ok(Self {
+            child: None,
             outgoing_tx,
             pending,
             id_counter: AtomicI64::new(1),
+            closed,
+            _closed_tx: Some(closed_tx),
+            notifications,
         })
     }
 
+    /// Whether the connection to the server is gone.
+    pub fn is_closed(&self) -> bool {
+        *self.closed.borrow()
+    }
+
+    /// Resolves once the connection to the server is gone or the client has
+    /// been dropped. The returned future does not borrow the client, so it
+    /// can be awaited from a task that does not keep the client alive.
+    pub fn closed(&self) -> impl Future<Output = ()> + Send + 'static {
+        let mut closed = self.closed.clone();
+        async move {
+            let _ = closed.wait_for(|closed| *closed).await;
+        }
+    }
+
     /// Send an arbitrary MCP request and await the typed result.
     ///
     /// If `timeout` is `None` the call waits indefinitely. If `Some(duration)`
     /// is supplied and no response is received within the given period, a
     /// timeout error is returned.
+    ///
+    /// Dropping the returned future before the response arrives (for example
+    /// because the caller was interrupted) sends `notifications/cancelled` so
+    /// the server can stop working on the request.
     pub async fn send_request<R>(
         &self,
         params: R::Params,
@@ -199,10 +297,6 @@ impl McpClient {
         R::Params: Serialize,
         R::Result: DeserializeOwned,
     {
-        // Create a new unique ID.
-        let id = self.id_counter.fetch_add(1, Ordering::SeqCst);
-        let request_id = RequestId::Integer(id);
-
         // Serialize params -> JSON. For many request types `Params` is
         // `Option<T>` and `None` should be encoded as *absence* of the field.
         let params_json = serde_json::to_value(&params)?;
@@ -211,6 +305,22 @@ impl McpClient {
         } else {
             Some(params_json)
         };
+        self.send_request_json::<R>(params_field, timeout).await
+    }
+
+    /// [`send_request`](Self::send_request) with already serialized params.
+    async fn send_request_json<R>(
+        &self,
+        params_field: Option<serde_json::Value>,
+        timeout: Option<Duration>,
+    ) -> Result<R::Result>
+    where
+        R: ModelContextProtocolRequest,
+        R::Result: DeserializeOwned,
+    {
+        // Create a new unique ID.
+        let id = self.id_counter.fetch_add(1, Ordering::SeqCst);
+        let request_id = RequestId::Integer(id);
 
         let jsonrpc_request = JSONRPCRequest {
             id: request_id.clone(),
@@ -237,6 +347,12 @@ impl McpClient {
                 "failed to send message to writer task - channel closed"
             ));
         }
+        let mut cancel_on_drop = CancelOnDrop {
+            id,
+            outgoing_tx: self.outgoing_tx.clone(),
+            pending: Arc::clone(&self.pending),
+            armed: true,
+        };
 
         // Await the response, optionally bounded by a timeout.
         let msg = match timeout {
@@ -263,6 +379,7 @@ impl McpClient {
                 .await
                 .map_err(|_| anyhow!("response channel closed before a reply was received"))?,
         };
+        cancel_on_drop.armed = false;
 
         match msg {
             JSONRPCMessage::Response(JSONRPCResponse { result, .. }) => {
@@ -335,16 +452,109 @@ impl McpClient {
         self.send_request::<ListToolsRequest>(params, timeout).await
     }
 
-    /// Convenience wrapper around `tools/call`.
+    /// Convenience wrapper around `resources/list`.
+    pub async fn list_resources(
+        &self,
+        params: Option<ListResourcesRequestParams>,
+        timeout: Option<Duration>,
+    ) -> Result<ListResourcesResult> {
+        self.send_request::<ListResourcesRequest>(params, timeout)
+            .await
+    }
+
+    /// Convenience wrapper around `resources/read`.
+    pub async fn read_resource(
+        &self,
+        uri: String,
+        timeout: Option<Duration>,
+    ) -> Result<ReadResourceResult> {
+        let params = ReadResourceRequestParams { uri };
+        debug!("MCP resource read: {params:?}");
+        self.send_request::<ReadResourceRequest>(params, timeout)
+            .await
+    }
+
+    /// Convenience wrapper around `prompts/list`.
+    pub async fn list_prompts(
+        &self,
+        params: Option<ListPromptsRequestParams>,
+        timeout: Option<Duration>,
+    ) -> Result<ListPromptsResult> {
+        self.send_request::<ListPromptsRequest>(params, timeout)
+            .await
+    }
+
+    /// Convenience wrapper around `prompts/get`.
+    pub async fn get_prompt(
+        &self,
+        name: String,
+        arguments: Option<serde_json::Value>,
+        timeout: Option<Duration>,
+    ) -> Result<GetPromptResult> {
+        let params = GetPromptRequestParams { arguments, name };
+        self.send_request::<GetPromptRequest>(params, timeout).await
+    }
+
+    /// Convenience wrapper around `tools/call`. When `progress_token` is set
+    /// the server may report progress on the call with
+    /// `notifications/progress` carrying that token; see
+    /// [`subscribe_notifications`](Self::subscribe_notifications).
     pub async fn call_tool(
         &self,
         name: String,
         arguments: Option<serde_json::Value>,
+        progress_token: Option<ProgressToken>,
         timeout: Option<Duration>,
     ) -> Result<mcp_types::CallToolResult> {
         let params = CallToolRequestParams { name, arguments };
         debug!("MCP tool call: {params:?}");
-        self.send_request::<CallToolRequest>(params, timeout).await
+        let mut params_json = serde_json::to_value(&params)?;
+        if let (Some(token), Some(object)) = (progress_token, params_json.as_object_mut()) {
+            object.insert(
+                "_meta".to_string(),
+                serde_json::json!({ "progressToken": token }),
+            );
+        }
+        self.send_request_json::<CallToolRequest>(Some(params_json), timeout)
+            .await
+    }
+
+    /// Receive the notifications the server sends from now on, such as
+    /// progress and log messages. Notifications that arrive while nobody is
+    /// subscribed are dropped.
+    pub fn subscribe_notifications(&self) -> broadcast::Receiver<ServerNotification> {
+        self.notifications.subscribe()
+    }
+
+    /// Internal helper: route a message received from the server.
+    async fn dispatch_incoming(
+        msg: JSONRPCMessage,
+        pending: &Arc<Mutex<HashMap<i64, PendingSender>>>,
+        notifications: &broadcast::Sender<ServerNotification>,
+    ) {
+        match msg {
+            JSONRPCMessage::Response(resp) => {
+                Self::dispatch_response(resp, pending).await;
+            }
+            JSONRPCMessage::Error(err) => {
+                Self::dispatch_error(err, pending).await;
+            }
+            JSONRPCMessage::Notification(notification) => {
+                debug!("<- notification: {notification:?}");
+                match ServerNotification::try_from(notification) {
+                    // An error only means nobody is subscribed.
+                    Ok(notification) => {
+                        let _ = notifications.send(notification);
+                    }
+                    Err(e) => warn!("failed to parse server notification: {e}"),
+                }
+            }
+            other => {
+                // Batch responses and requests are currently not
+                // expected from the server – log and ignore.
+                info!("<- unhandled message: {:?}", other);
+            }
+        }
     }
 
     /// Internal helper: route a JSON-RPC *response* object to the pending map.
@@ -400,7 +610,9 @@ impl Drop for McpClient {
         // `kill_on_drop(true)` above, this extra check has the benefit of
         // forcing the process to be reaped immediately if it has already exited
         // instead of waiting for the Tokio runtime to reap it later.
-        let _ = self.child.try_wait();
+        if let Some(child) = self.child.as_mut() {
+            let _ = child.try_wait();
+        }
     }
 }
 
@@ -460,10 +672,107 @@ fn create_env_for_mcp_server(
         .collect::<HashMap<_, _>>()
 }
 
+/// Sends `notifications/cancelled` for a request whose caller stopped
+/// waiting for it, unless disarmed once the response has been received.
+struct CancelOnDrop {
+    id: i64,
+    outgoing_tx: mpsc::Sender<JSONRPCMessage>,
+    pending: Arc<Mutex<HashMap<i64, PendingSender>>>,
+    armed: bool,
+}
+
+impl Drop for CancelOnDrop {
+    fn drop(&mut self) {
+        if !self.armed {
+            return;
+        }
+        // Best effort: if the map is busy the entry is removed when the
+        // response arrives.
+        if let Ok(mut pending) = self.pending.try_lock() {
+            pending.remove(&self.id);
+        }
+        let params = CancelledNotificationParams {
+            reason: Some("request cancelled by the client".to_string()),
+            request_id: RequestId::Integer(self.id),
+        };
+        let notification = JSONRPCMessage::Notification(JSONRPCNotification {
+            jsonrpc: JSONRPC_VERSION.to_string(),
+            method: CancelledNotification::METHOD.to_string(),
+            params: serde_json::to_value(params).ok(),
+        });
+        if self.outgoing_tx.try_send(notification).is_err() {
+            warn!(
+                id = self.id,
+                "failed to send cancellation for abandoned request"
+            );
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[tokio::test]
+    async fn dropped_request_sends_cancellation() {
+        let (outgoing_tx, mut outgoing_rx) = mpsc::channel(CHANNEL_CAPACITY);
+        let pending: Arc<Mutex<HashMap<i64, PendingSender>>> = Arc::default();
+        let (tx, _rx) = oneshot::channel();
+        pending.lock().await.insert(7, tx);
+
+        drop(CancelOnDrop {
+            id: 7,
+            outgoing_tx,
+            pending: Arc::clone(&pending),
+            armed: true,
+        });
+
+        assert!(pending.lock().await.is_empty());
+        let Some(JSONRPCMessage::Notification(notification)) = outgoing_rx.recv().await else {
+            panic!("expected a cancellation notification");
+        };
+        assert_eq!(notification.method, "notifications/cancelled");
+        assert_eq!(
+            notification.params,
+            Some(serde_json::json!({
+                "reason": "request cancelled by the client",
+                "requestId": 7,
+            }))
+        );
+    }
+
+    #[tokio::test]
+    async fn notifications_are_forwarded_to_subscribers() {
+        let pending: Arc<Mutex<HashMap<i64, PendingSender>>> = Arc::default();
+        let (notifications, mut rx) = broadcast::channel(CHANNEL_CAPACITY);
+
+        let progress = JSONRPCNotification {
+            jsonrpc: JSONRPC_VERSION.to_string(),
+            method: "notifications/progress".to_string(),
+            params: Some(serde_json::json!({
+                "progressToken": "call-1",
+                "progress": 3,
+                "total": 10,
+            })),
+        };
+        McpClient::dispatch_incoming(
+            JSONRPCMessage::Notification(progress),
+            &pending,
+            &notifications,
+        )
+        .await;
+
+        let Ok(ServerNotification::ProgressNotification(params)) = rx.recv().await else {
+            panic!("expected a progress notification");
+        };
+        assert_eq!(
+            params.progress_token,
+            ProgressToken::String("call-1".to_string())
+        );
+        assert_eq!(params.progress, 3.0);
+        assert_eq!(params.total, Some(10.0));
+    }
+
     #[test]
     fn test_create_env_for_mcp_server() {
         let env_var = "USER";
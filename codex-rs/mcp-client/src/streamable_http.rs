@@ -0,0 +1,204 @@
+//! Client side of the MCP "Streamable HTTP" transport.
+//!
+//! Every message the client sends is `POST`ed to the server's endpoint. The
+//! server answers a request either with a single JSON body or with a
+//! `text/event-stream` whose events carry the response (and possibly
+//! notifications sent while the request runs). Notifications are answered
+//! with `202 Accepted` and no body. The session id the server assigns in its
+//! reply to `initialize` is sent back on every later request.
+//!
+//! See <https://modelcontextprotocol.io/specification/2025-06-18/basic/transports#streamable-http>.
+
+use std::sync::Arc;
+use std::sync::RwLock;
+
+use anyhow::Result;
+use mcp_types::JSONRPC_VERSION;
+use mcp_types::JSONRPCError;
+use mcp_types::JSONRPCErrorError;
+use mcp_types::JSONRPCMessage;
+use reqwest::header::ACCEPT;
+use reqwest::header::AUTHORIZATION;
+use reqwest::header::CONTENT_TYPE;
+use reqwest::header::HeaderMap;
+use reqwest::header::HeaderName;
+use reqwest::header::HeaderValue;
+use tokio::sync::mpsc;
+use tracing::debug;
+use tracing::error;
+
+const SESSION_ID_HEADER: &str = "mcp-session-id";
+const PROTOCOL_VERSION_HEADER: &str = "mcp-protocol-version";
+
+/// JSON-RPC error code reported to the caller when the HTTP exchange itself
+/// fails, so it does not have to wait for its timeout.
+const TRANSPORT_ERROR_CODE: i64 = -32000;
+
+/// Build the headers sent with every request: the configured ones, plus
+/// `Authorization` when a bearer token is given.
+pub(crate) fn default_headers(
+    headers: std::collections::HashMap<String, String>,
+    bearer_token: Option<String>,
+) -> Result<HeaderMap> {
+    let mut map = HeaderMap::new();
+    for (name, value) in headers {
+        map.insert(
+            HeaderName::from_bytes(name.as_bytes())?,
+            HeaderValue::from_str(&value)?,
+        );
+    }
+    if let Some(token) = bearer_token {
+        let mut value = HeaderValue::from_str(&format!("Bearer {token}"))?;
+        value.set_sensitive(true);
+        map.insert(AUTHORIZATION, value);
+    }
+    map.insert(
+        PROTOCOL_VERSION_HEADER,
+        HeaderValue::from_static(mcp_types::MCP_SCHEMA_VERSION),
+    );
+    Ok(map)
+}
+
+/// Send every message from `outgoing_rx` to `url` and forward whatever the
+/// server sends back to `incoming_tx`. Each message is posted from its own
+/// task so a long-running request does not hold up the ones after it.
+pub(crate) async fn run(
+    client: reqwest::Client,
+    url: String,
+    mut outgoing_rx: mpsc::Receiver<JSONRPCMessage>,
+    incoming_tx: mpsc::Sender<JSONRPCMessage>,
+) {
+    let session_id: Arc<RwLock<Option<String>>> = Arc::default();
+    while let Some(message) = outgoing_rx.recv().await {
+        let client = client.clone();
+        let url = url.clone();
+        let incoming_tx = incoming_tx.clone();
+        let session_id = Arc::clone(&session_id);
+        tokio::spawn(async move {
+            let request_id = match &message {
+                JSONRPCMessage::Request(request) => Some(request.id.clone()),
+                _ => None,
+            };
+            if let Err(e) = post_message(&client, &url, &message, &session_id, &incoming_tx).await {
+                error!("MCP HTTP transport error: {e:#}");
+                if let Some(id) = request_id {
+                    let _ = incoming_tx
+                        .send(JSONRPCMessage::Error(JSONRPCError {
+                            error: JSONRPCErrorError {
+                                code: TRANSPORT_ERROR_CODE,
+                                data: None,
+                                message: format!("{e:#}"),
+                            },
+                            id,
+                            jsonrpc: JSONRPC_VERSION.to_string(),
+                        }))
+                        .await;
+                }
+            }
+        });
+    }
+}
+
+async fn post_message(
+    client: &reqwest::Client,
+    url: &str,
+    message: &JSONRPCMessage,
+    session_id: &RwLock<Option<String>>,
+    incoming_tx: &mpsc::Sender<JSONRPCMessage>,
+) -> Result<()> {
+    let body = serde_json::to_string(message)?;
+    debug!("MCP message to server: {body}");
+    let mut request = client
+        .post(url)
+        .header(CONTENT_TYPE, "application/json")
+        .header(ACCEPT, "application/json, text/event-stream")
+        .body(body);
+    let current_session = session_id.read().ok().and_then(|guard| guard.clone());
+    if let Some(id) = current_session {
+        request = request.header(SESSION_ID_HEADER, id);
+    }
+
+    let mut response = request.send().await?.error_for_status()?;
+    if let Some(id) = response
+        .headers()
+        .get(SESSION_ID_HEADER)
+        .and_then(|value| value.to_str().ok())
+        && let Ok(mut guard) = session_id.write()
+    {
+        *guard = Some(id.to_string());
+    }
+
+    let is_event_stream = response
+        .headers()
+        .get(CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|value| value.starts_with("text/event-stream"));
+    if is_event_stream {
+        let mut parser = SseParser::default();
+        while let Some(chunk) = response.chunk().await? {
+            for data in parser.feed(&chunk) {
+                forward(&data, incoming_tx).await;
+            }
+        }
+    } else {
+        let body = response.text().await?;
+        if !body.trim().is_empty() {
+            forward(&body, incoming_tx).await;
+        }
+    }
+    Ok(())
+}
+
+async fn forward(data: &str, incoming_tx: &mpsc::Sender<JSONRPCMessage>) {
+    debug!("MCP message from server: {data}");
+    match serde_json::from_str::<JSONRPCMessage>(data) {
+        Ok(message) => {
+            let _ = incoming_tx.send(message).await;
+        }
+        Err(e) => error!("failed to deserialize JSONRPCMessage: {e}; data = {data}"),
+    }
+}
+
+/// Incremental parser for a `text/event-stream` body that yields the `data`
+/// of each complete event. Event names, ids and comments are ignored.
+#[derive(Default)]
+struct SseParser {
+    buffer: String,
+    data: Vec<String>,
+}
+
+impl SseParser {
+    fn feed(&mut self, chunk: &[u8]) -> Vec<String> {
+        self.buffer.push_str(&String::from_utf8_lossy(chunk));
+        let mut events = Vec::new();
+        while let Some(newline) = self.buffer.find('\n') {
+            let line: String = self.buffer.drain(..=newline).collect();
+            let line = line.trim_end_matches(['\n', '\r']);
+            if line.is_empty() {
+                if !self.data.is_empty() {
+                    events.push(self.data.join("\n"));
+                    self.data.clear();
+                }
+            } else if let Some(value) = line.strip_prefix("data:") {
+                self.data
+                    .push(value.strip_prefix(' ').unwrap_or(value).to_string());
+            }
+        }
+        events
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sse_parser_joins_data_lines_across_chunks() {
+        let mut parser = SseParser::default();
+        assert!(parser.feed(b"event: message\r\ndata: {\"a\":").is_empty());
+        assert_eq!(
+            parser.feed(b"1}\r\n\r\n: keep-alive\n\ndata: x\ndata: y\n\n"),
+            vec!["{\"a\":1}".to_string(), "x\ny".to_string()]
+        );
+    }
+}
@@ -27,6 +27,7 @@ use codex_core::exec_env::create_env;
 use codex_core::get_platform_sandbox;
 use codex_core::git_info::git_diff_to_remote;
 use codex_core::protocol::ApplyPatchApprovalRequestEvent;
+use codex_core::protocol::EscalationRequestEvent;
 use codex_core::protocol::Event;
 use codex_core::protocol::EventMsg;
 use codex_core::protocol::ExecApprovalRequestEvent;
@@ -60,6 +61,8 @@ use codex_protocol::mcp_protocol::InterruptConversationParams;
 use codex_protocol::mcp_protocol::InterruptConversationResponse;
 use codex_protocol::mcp_protocol::ListConversationsParams;
 use codex_protocol::mcp_protocol::ListConversationsResponse;
+use codex_protocol::mcp_protocol::ListLoadedConversationsResponse;
+use codex_protocol::mcp_protocol::LoadedConversation;
 use codex_protocol::mcp_protocol::LoginApiKeyParams;
 use codex_protocol::mcp_protocol::LoginApiKeyResponse;
 use codex_protocol::mcp_protocol::LoginChatGptCompleteNotification;
@@ -76,6 +79,8 @@ use codex_protocol::mcp_protocol::SendUserTurnResponse;
 use codex_protocol::mcp_protocol::ServerNotification;
 use codex_protocol::mcp_protocol::SetDefaultModelParams;
 use codex_protocol::mcp_protocol::SetDefaultModelResponse;
+use codex_protocol::mcp_protocol::SwitchConversationParams;
+use codex_protocol::mcp_protocol::SwitchConversationResponse;
 use codex_protocol::mcp_protocol::UserInfoResponse;
 use codex_protocol::mcp_protocol::UserSavedConfig;
 use codex_protocol::models::ContentItem;
@@ -122,6 +127,8 @@ pub(crate) struct CodexMessageProcessor {
     active_login: Arc<Mutex<Option<ActiveLogin>>>,
     // Queue of pending interrupt requests per conversation. We reply when TurnAborted arrives.
     pending_interrupts: Arc<Mutex<HashMap<ConversationId, Vec<RequestId>>>>,
+    // The conversation most recently created, resumed or switched to.
+    active_conversation: Arc<Mutex<Option<ConversationId>>>,
 }
 
 impl CodexMessageProcessor {
@@ -141,6 +148,7 @@ impl CodexMessageProcessor {
             conversation_listeners: HashMap::new(),
             active_login: Arc::new(Mutex::new(None)),
             pending_interrupts: Arc::new(Mutex::new(HashMap::new())),
+            active_conversation: Arc::new(Mutex::new(None)),
         }
     }
 
@@ -161,6 +169,12 @@ impl CodexMessageProcessor {
             ClientRequest::ArchiveConversation { request_id, params } => {
                 self.archive_conversation(request_id, params).await;
             }
+            ClientRequest::ListLoadedConversations { request_id } => {
+                self.handle_list_loaded_conversations(request_id).await;
+            }
+            ClientRequest::SwitchConversation { request_id, params } => {
+                self.switch_conversation(request_id, params).await;
+            }
             ClientRequest::SendUserMessage { request_id, params } => {
                 self.send_user_message(request_id, params).await;
             }
@@ -573,6 +587,9 @@ impl CodexMessageProcessor {
             env,
             with_escalated_permissions: None,
             justification: None,
+            tty: false,
+            resource_limits: self.config.resource_limits,
+            sandbox_extras: self.config.sandbox_extras.clone(),
         };
 
         let effective_policy = params
@@ -599,6 +616,8 @@ impl CodexMessageProcessor {
                 sandbox_cwd.as_path(),
                 &codex_linux_sandbox_exe,
                 None,
+                None,
+                None,
             )
             .await
             {
@@ -650,6 +669,7 @@ impl CodexMessageProcessor {
                     rollout_path: session_configured.rollout_path,
                 };
                 self.outgoing.send_response(request_id, response).await;
+                self.on_conversation_loaded(conversation_id).await;
             }
             Err(err) => {
                 let error = JSONRPCErrorError {
@@ -777,6 +797,7 @@ impl CodexMessageProcessor {
                     initial_messages,
                 };
                 self.outgoing.send_response(request_id, response).await;
+                self.on_conversation_loaded(conversation_id).await;
             }
             Err(err) => {
                 let error = JSONRPCErrorError {
@@ -789,6 +810,80 @@ impl CodexMessageProcessor {
         }
     }
 
+    /// Conversations loaded in this process, along with the active one.
+    pub(crate) async fn loaded_conversations(
+        &self,
+    ) -> (Vec<LoadedConversation>, Option<ConversationId>) {
+        let mut items: Vec<LoadedConversation> = self
+            .conversation_manager
+            .loaded_conversations()
+            .await
+            .into_iter()
+            .map(|(conversation_id, conversation)| {
+                let session_configured = conversation.session_configured();
+                LoadedConversation {
+                    conversation_id,
+                    model: session_configured.model.clone(),
+                    rollout_path: session_configured.rollout_path.clone(),
+                }
+            })
+            .collect();
+        // Rollout file names start with a timestamp, so this is creation order.
+        items.sort_by(|a, b| a.rollout_path.cmp(&b.rollout_path));
+
+        let active = *self.active_conversation.lock().await;
+        let active = active.filter(|id| items.iter().any(|it| it.conversation_id == *id));
+        (items, active)
+    }
+
+    async fn handle_list_loaded_conversations(&self, request_id: RequestId) {
+        let (items, active_conversation_id) = self.loaded_conversations().await;
+        let response = ListLoadedConversationsResponse {
+            items,
+            active_conversation_id,
+        };
+        self.outgoing.send_response(request_id, response).await;
+    }
+
+    async fn switch_conversation(&self, request_id: RequestId, params: SwitchConversationParams) {
+        let SwitchConversationParams { conversation_id } = params;
+        if self
+            .conversation_manager
+            .get_conversation(conversation_id)
+            .await
+            .is_err()
+        {
+            let error = JSONRPCErrorError {
+                code: INVALID_REQUEST_ERROR_CODE,
+                message: format!("conversation not found: {conversation_id}"),
+                data: None,
+            };
+            self.outgoing.send_error(request_id, error).await;
+            return;
+        }
+
+        *self.active_conversation.lock().await = Some(conversation_id);
+        self.outgoing
+            .send_response(request_id, SwitchConversationResponse {})
+            .await;
+    }
+
+    async fn on_conversation_loaded(&self, conversation_id: ConversationId) {
+        *self.active_conversation.lock().await = Some(conversation_id);
+        self.notify_resource_list_changed().await;
+    }
+
+    /// Loaded conversations are exposed as MCP resources, so tell the client
+    /// to refetch `resources/list`.
+    async fn notify_resource_list_changed(&self) {
+        self.outgoing
+            .send_notification(OutgoingNotification {
+                method: "notifications/resources/list_changed".to_string(),
+                params: None,
+            })
+            .await;
+    }
+
     async fn archive_conversation(&self, request_id: RequestId, params: ArchiveConversationParams) {
         let ArchiveConversationParams {
             conversation_id,
@@ -852,6 +947,13 @@ impl CodexMessageProcessor {
             .await;
         if let Some(conversation) = removed_conversation {
             info!("conversation {conversation_id} was active; shutting down");
+            {
+                let mut active = self.active_conversation.lock().await;
+                if *active == Some(conversation_id) {
+                    *active = None;
+                }
+            }
+            self.notify_resource_list_changed().await;
             let conversation_clone = conversation.clone();
             let notify = Arc::new(tokio::sync::Notify::new());
             let notify_clone = notify.clone();
@@ -1010,6 +1112,9 @@ impl CodexMessageProcessor {
                 effort,
                 summary,
                 final_output_json_schema: None,
+                max_turns: None,
+                max_total_tokens: None,
+                max_duration: None,
             })
             .await;
 
@@ -1183,6 +1288,8 @@ async fn apply_bespoke_event_handling(
             changes,
             reason,
             grant_root,
+            stats,
+            rejected_paths,
         }) => {
             let params = ApplyPatchApprovalParams {
                 conversation_id,
@@ -1190,6 +1297,8 @@ async fn apply_bespoke_event_handling(
                 file_changes: changes,
                 reason,
                 grant_root,
+                stats,
+                rejected_paths,
             };
             let value = serde_json::to_value(&params).unwrap_or_default();
             let rx = outgoing
@@ -1212,6 +1321,7 @@ async fn apply_bespoke_event_handling(
                 command,
                 cwd,
                 reason,
+                requested_permissions: Vec::new(),
             };
             let value = serde_json::to_value(&params).unwrap_or_default();
             let rx = outgoing
@@ -1223,6 +1333,32 @@ async fn apply_bespoke_event_handling(
                 on_exec_approval_response(event_id, rx, conversation).await;
             });
         }
+        EventMsg::EscalationRequest(EscalationRequestEvent {
+            call_id,
+            command,
+            cwd,
+            justification,
+            requested_permissions,
+        }) => {
+            // Escalations are answered with the same `Op::ExecApproval` as
+            // ordinary exec approvals, so reuse that request for clients.
+            let params = ExecCommandApprovalParams {
+                conversation_id,
+                call_id,
+                command,
+                cwd,
+                reason: Some(justification),
+                requested_permissions,
+            };
+            let value = serde_json::to_value(&params).unwrap_or_default();
+            let rx = outgoing
+                .send_request(EXEC_COMMAND_APPROVAL_METHOD, Some(value))
+                .await;
+
+            tokio::spawn(async move {
+                on_exec_approval_response(event_id, rx, conversation).await;
+            });
+        }
         // If this is a TurnAborted, reply to any pending interrupt requests.
         EventMsg::TurnAborted(turn_aborted_event) => {
             let pending = {
@@ -1265,6 +1401,7 @@ fn derive_config_from_params(
         cwd: cwd.map(PathBuf::from),
         approval_policy,
         sandbox_mode,
+        sandbox_preset: None,
         model_provider: None,
         codex_linux_sandbox_exe,
         base_instructions,
@@ -1281,7 +1418,10 @@ fn derive_config_from_params(
         .map(|(k, v)| (k, json_to_toml(v)))
         .collect();
 
-    Config::load_with_cli_overrides(cli_overrides, overrides)
+    let mut config = Config::load_with_cli_overrides(cli_overrides, overrides)?;
+    // MCP clients have no way to answer guided permission prompts.
+    config.permission_prompts = false;
+    Ok(config)
 }
 
 async fn on_patch_approval_response(
@@ -1298,6 +1438,7 @@ async fn on_patch_approval_response(
                 .submit(Op::PatchApproval {
                     id: event_id.clone(),
                     decision: ReviewDecision::Denied,
+                    approved_files: None,
                 })
                 .await
             {
@@ -1312,6 +1453,7 @@ async fn on_patch_approval_response(
             error!("failed to deserialize ApplyPatchApprovalResponse: {err}");
             ApplyPatchApprovalResponse {
                 decision: ReviewDecision::Denied,
+                approved_files: None,
             }
         });
 
@@ -1319,6 +1461,7 @@ async fn on_patch_approval_response(
         .submit(Op::PatchApproval {
             id: event_id,
             decision: response.decision,
+            approved_files: response.approved_files,
         })
         .await
     {
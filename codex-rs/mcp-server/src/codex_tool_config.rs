@@ -4,6 +4,7 @@ use codex_core::protocol::AskForApproval;
 use codex_protocol::config_types::SandboxMode;
 use mcp_types::Tool;
 use mcp_types::ToolInputSchema;
+use mcp_types::ToolOutputSchema;
 use schemars::JsonSchema;
 use schemars::r#gen::SchemaSettings;
 use serde::Deserialize;
@@ -157,6 +158,7 @@ impl CodexToolCallParam {
             cwd: cwd.map(PathBuf::from),
             approval_policy: approval_policy.map(Into::into),
             sandbox_mode: sandbox.map(Into::into),
+            sandbox_preset: None,
             model_provider: None,
             codex_linux_sandbox_exe,
             base_instructions,
@@ -173,7 +175,10 @@ impl CodexToolCallParam {
             .map(|(k, v)| (k, json_to_toml(v)))
             .collect();
 
-        let cfg = codex_core::config::Config::load_with_cli_overrides(cli_overrides, overrides)?;
+        let mut cfg =
+            codex_core::config::Config::load_with_cli_overrides(cli_overrides, overrides)?;
+        // MCP clients have no way to answer guided permission prompts.
+        cfg.permission_prompts = false;
 
         Ok((prompt, cfg))
     }
@@ -221,6 +226,234 @@ pub(crate) fn create_tool_for_codex_tool_call_reply_param() -> Tool {
     }
 }
 
+/// Client-supplied parameters for a `runTask` tool-call: a one-shot,
+/// headless task like `codex exec`.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct RunTaskToolCallParam {
+    /// The task for Codex to complete.
+    pub prompt: String,
+
+    /// Working directory for the task. If relative, it is resolved against
+    /// the server process's current working directory.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cwd: Option<String>,
+
+    /// Sandbox mode: `read-only`, `workspace-write`, or `danger-full-access`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sandbox: Option<CodexToolCallSandboxMode>,
+
+    /// Optional override for the model name (e.g. "o3", "o4-mini").
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+}
+
+impl RunTaskToolCallParam {
+    /// Returns the prompt and the config for the task. Nobody is around to
+    /// answer approval requests, so commands are never escalated to the user.
+    pub fn into_config(
+        self,
+        codex_linux_sandbox_exe: Option<PathBuf>,
+    ) -> std::io::Result<(String, codex_core::config::Config)> {
+        let Self {
+            prompt,
+            cwd,
+            sandbox,
+            model,
+        } = self;
+        CodexToolCallParam {
+            prompt,
+            model,
+            cwd,
+            sandbox,
+            approval_policy: Some(CodexToolCallApprovalPolicy::Never),
+            ..Default::default()
+        }
+        .into_config(codex_linux_sandbox_exe)
+    }
+}
+
+/// `structuredContent` of a successful `runTask` result.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct RunTaskResult {
+    /// Id of the conversation that ran the task.
+    pub conversation_id: String,
+
+    /// The last message Codex sent, if any.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_agent_message: Option<String>,
+
+    /// Unified diff of the changes the task made, if any.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub diff: Option<String>,
+}
+
+/// Builds a `Tool` definition for the `runTask` tool-call.
+pub(crate) fn create_tool_for_run_task_param() -> Tool {
+    let settings = SchemaSettings::draft2019_09().with(|s| {
+        s.inline_subschemas = true;
+        s.option_add_null_type = false;
+    });
+    let input_schema = settings
+        .clone()
+        .into_generator()
+        .into_root_schema_for::<RunTaskToolCallParam>();
+    let output_schema = settings
+        .into_generator()
+        .into_root_schema_for::<RunTaskResult>();
+
+    #[expect(clippy::expect_used)]
+    let input_schema_value =
+        serde_json::to_value(&input_schema).expect("runTask input schema should serialise");
+    #[expect(clippy::expect_used)]
+    let output_schema_value =
+        serde_json::to_value(&output_schema).expect("runTask output schema should serialise");
+
+    let tool_input_schema = serde_json::from_value::<ToolInputSchema>(input_schema_value)
+        .unwrap_or_else(|e| {
+            panic!("failed to create Tool from schema: {e}");
+        });
+    let tool_output_schema = serde_json::from_value::<ToolOutputSchema>(output_schema_value)
+        .unwrap_or_else(|e| {
+            panic!("failed to create Tool output schema: {e}");
+        });
+
+    Tool {
+        name: "runTask".to_string(),
+        title: Some("Run Codex Task".to_string()),
+        input_schema: tool_input_schema,
+        output_schema: Some(tool_output_schema),
+        description: Some(
+            "Run a one-shot Codex task without approvals and return its final message and diff."
+                .to_string(),
+        ),
+        annotations: None,
+    }
+}
+
+/// Client-supplied parameters for a `codex_apply_patch` tool-call.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ApplyPatchToolCallParam {
+    /// The patch to apply, in the `*** Begin Patch` format Codex uses.
+    pub patch: String,
+
+    /// Directory that relative paths in the patch resolve against. If
+    /// relative, it is resolved against the server process's current working
+    /// directory.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cwd: Option<String>,
+
+    /// Sandbox mode: `read-only`, `workspace-write` (the default), or
+    /// `danger-full-access`. The patch may only write where the mode allows.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sandbox: Option<CodexToolCallSandboxMode>,
+}
+
+/// Client-supplied parameters for a `codex_snapshot` tool-call.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct SnapshotToolCallParam {
+    /// Directory inside the git repository to snapshot. If relative, it is
+    /// resolved against the server process's current working directory.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cwd: Option<String>,
+
+    /// Message recorded on the snapshot commit.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+}
+
+/// `structuredContent` of a successful `codex_snapshot` result.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct SnapshotResult {
+    /// Id of the snapshot, to pass to `codex_restore`.
+    pub snapshot_id: String,
+}
+
+/// Client-supplied parameters for a `codex_restore` tool-call.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct RestoreToolCallParam {
+    /// Id returned by `codex_snapshot`.
+    pub snapshot_id: String,
+
+    /// Directory inside the git repository to restore; only files under it
+    /// are changed. If relative, it is resolved against the server process's
+    /// current working directory.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cwd: Option<String>,
+}
+
+/// Builds a `Tool` definition for the `codex_apply_patch` tool-call.
+pub(crate) fn create_tool_for_apply_patch_param() -> Tool {
+    Tool {
+        name: "codex_apply_patch".to_string(),
+        title: Some("Apply Patch".to_string()),
+        input_schema: input_schema_for::<ApplyPatchToolCallParam>(),
+        output_schema: None,
+        description: Some(
+            "Apply a patch in Codex's apply_patch format inside the Codex sandbox.".to_string(),
+        ),
+        annotations: None,
+    }
+}
+
+/// Builds a `Tool` definition for the `codex_snapshot` tool-call.
+pub(crate) fn create_tool_for_snapshot_param() -> Tool {
+    let output_schema = schema_value_for::<SnapshotResult>();
+    let tool_output_schema = serde_json::from_value::<ToolOutputSchema>(output_schema)
+        .unwrap_or_else(|e| {
+            panic!("failed to create Tool output schema: {e}");
+        });
+    Tool {
+        name: "codex_snapshot".to_string(),
+        title: Some("Snapshot Working Tree".to_string()),
+        input_schema: input_schema_for::<SnapshotToolCallParam>(),
+        output_schema: Some(tool_output_schema),
+        description: Some(
+            "Record the git working tree, including untracked files, without touching branches or the index, so it can be restored with codex_restore."
+                .to_string(),
+        ),
+        annotations: None,
+    }
+}
+
+/// Builds a `Tool` definition for the `codex_restore` tool-call.
+pub(crate) fn create_tool_for_restore_param() -> Tool {
+    Tool {
+        name: "codex_restore".to_string(),
+        title: Some("Restore Snapshot".to_string()),
+        input_schema: input_schema_for::<RestoreToolCallParam>(),
+        output_schema: None,
+        description: Some(
+            "Restore the git working tree to a snapshot taken with codex_snapshot.".to_string(),
+        ),
+        annotations: None,
+    }
+}
+
+fn schema_value_for<T: JsonSchema>() -> serde_json::Value {
+    let schema = SchemaSettings::draft2019_09()
+        .with(|s| {
+            s.inline_subschemas = true;
+            s.option_add_null_type = false;
+        })
+        .into_generator()
+        .into_root_schema_for::<T>();
+
+    #[expect(clippy::expect_used)]
+    serde_json::to_value(&schema).expect("tool schema should serialise to JSON")
+}
+
+fn input_schema_for<T: JsonSchema>() -> ToolInputSchema {
+    serde_json::from_value::<ToolInputSchema>(schema_value_for::<T>()).unwrap_or_else(|e| {
+        panic!("failed to create Tool from schema: {e}");
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -305,6 +538,104 @@ mod tests {
         assert_eq!(expected_tool_json, tool_json);
     }
 
+    #[test]
+    fn verify_run_task_tool_json_schema() {
+        let tool = create_tool_for_run_task_param();
+        let tool_json = serde_json::to_value(&tool).expect("tool serializes");
+        let expected_tool_json = serde_json::json!({
+          "name": "runTask",
+          "title": "Run Codex Task",
+          "description": "Run a one-shot Codex task without approvals and return its final message and diff.",
+          "inputSchema": {
+            "type": "object",
+            "properties": {
+              "cwd": {
+                "description": "Working directory for the task. If relative, it is resolved against the server process's current working directory.",
+                "type": "string"
+              },
+              "model": {
+                "description": "Optional override for the model name (e.g. \"o3\", \"o4-mini\").",
+                "type": "string"
+              },
+              "prompt": {
+                "description": "The task for Codex to complete.",
+                "type": "string"
+              },
+              "sandbox": {
+                "description": "Sandbox mode: `read-only`, `workspace-write`, or `danger-full-access`.",
+                "enum": [
+                  "read-only",
+                  "workspace-write",
+                  "danger-full-access"
+                ],
+                "type": "string"
+              },
+            },
+            "required": [
+              "prompt"
+            ]
+          },
+          "outputSchema": {
+            "type": "object",
+            "properties": {
+              "conversationId": {
+                "description": "Id of the conversation that ran the task.",
+                "type": "string"
+              },
+              "diff": {
+                "description": "Unified diff of the changes the task made, if any.",
+                "type": "string"
+              },
+              "lastAgentMessage": {
+                "description": "The last message Codex sent, if any.",
+                "type": "string"
+              },
+            },
+            "required": [
+              "conversationId"
+            ]
+          }
+        });
+        assert_eq!(expected_tool_json, tool_json);
+    }
+
+    #[test]
+    fn verify_apply_patch_tool_json_schema() {
+        let tool = create_tool_for_apply_patch_param();
+        let tool_json = serde_json::to_value(&tool).expect("tool serializes");
+        let expected_tool_json = serde_json::json!({
+          "name": "codex_apply_patch",
+          "title": "Apply Patch",
+          "description": "Apply a patch in Codex's apply_patch format inside the Codex sandbox.",
+          "inputSchema": {
+            "type": "object",
+            "properties": {
+              "cwd": {
+                "description": "Directory that relative paths in the patch resolve against. If relative, it is resolved against the server process's current working directory.",
+                "type": "string"
+              },
+              "patch": {
+                "description": "The patch to apply, in the `*** Begin Patch` format Codex uses.",
+                "type": "string"
+              },
+              "sandbox": {
+                "description": "Sandbox mode: `read-only`, `workspace-write` (the default), or `danger-full-access`. The patch may only write where the mode allows.",
+                "enum": [
+                  "read-only",
+                  "workspace-write",
+                  "danger-full-access"
+                ],
+                "type": "string"
+              },
+            },
+            "required": [
+              "patch"
+            ]
+          }
+        });
+        assert_eq!(expected_tool_json, tool_json);
+    }
+
     #[test]
     fn verify_codex_tool_reply_json_schema() {
         let tool = create_tool_for_codex_tool_call_reply_param();
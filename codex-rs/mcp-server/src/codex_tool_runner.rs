@@ -5,6 +5,7 @@
 use std::collections::HashMap;
 use std::sync::Arc;
 
+use crate::codex_tool_config::RunTaskResult;
 use crate::exec_approval::handle_exec_approval_request;
 use crate::outgoing_message::OutgoingMessageSender;
 use crate::outgoing_message::OutgoingNotificationMeta;
@@ -15,6 +16,7 @@ use codex_core::NewConversation;
 use codex_core::config::Config as CodexConfig;
 use codex_core::protocol::AgentMessageEvent;
 use codex_core::protocol::ApplyPatchApprovalRequestEvent;
+use codex_core::protocol::EscalationRequestEvent;
 use codex_core::protocol::Event;
 use codex_core::protocol::EventMsg;
 use codex_core::protocol::ExecApprovalRequestEvent;
@@ -22,6 +24,7 @@ use codex_core::protocol::InputItem;
 use codex_core::protocol::Op;
 use codex_core::protocol::Submission;
 use codex_core::protocol::TaskCompleteEvent;
+use codex_core::protocol::TurnDiffEvent;
 use codex_protocol::mcp_protocol::ConversationId;
 use mcp_types::CallToolResult;
 use mcp_types::ContentBlock;
@@ -113,6 +116,128 @@ pub async fn run_codex_tool_session(
     .await;
 }
 
+/// Run a one-shot `runTask` tool-call: start a conversation, stream its
+/// events as notifications until the task ends, then respond with the final
+/// message and diff and shut the conversation down.
+pub async fn run_task_tool_session(
+    id: RequestId,
+    prompt: String,
+    config: CodexConfig,
+    outgoing: Arc<OutgoingMessageSender>,
+    conversation_manager: Arc<ConversationManager>,
+    running_requests_id_to_codex_uuid: Arc<Mutex<HashMap<RequestId, ConversationId>>>,
+) {
+    let NewConversation {
+        conversation_id,
+        conversation,
+        session_configured,
+    } = match conversation_manager.new_conversation(config).await {
+        Ok(res) => res,
+        Err(e) => {
+            outgoing
+                .send_response(id, error_result(format!("Failed to start Codex task: {e}")))
+                .await;
+            return;
+        }
+    };
+
+    let meta = || Some(OutgoingNotificationMeta::new(Some(id.clone())));
+    outgoing
+        .send_event_as_notification(
+            &Event {
+                id: "".to_string(),
+                msg: EventMsg::SessionConfigured(session_configured),
+            },
+            meta(),
+        )
+        .await;
+
+    running_requests_id_to_codex_uuid
+        .lock()
+        .await
+        .insert(id.clone(), conversation_id);
+    let submitted = conversation
+        .submit(Op::UserInput {
+            items: vec![InputItem::Text { text: prompt }],
+        })
+        .await;
+
+    let result = match submitted {
+        Ok(_) => {
+            let mut diff = None;
+            loop {
+                let event = match conversation.next_event().await {
+                    Ok(event) => event,
+                    Err(e) => break error_result(format!("Codex runtime error: {e}")),
+                };
+                outgoing.send_event_as_notification(&event, meta()).await;
+                match event.msg {
+                    EventMsg::TurnDiff(TurnDiffEvent { unified_diff }) => {
+                        diff = (!unified_diff.is_empty()).then_some(unified_diff);
+                    }
+                    EventMsg::TaskComplete(TaskCompleteEvent {
+                        last_agent_message, ..
+                    }) => {
+                        break task_result(conversation_id, last_agent_message, diff);
+                    }
+                    EventMsg::Error(err_event) => break error_result(err_event.message),
+                    EventMsg::TurnAborted(aborted) => {
+                        break error_result(match aborted.budget_summary() {
+                            Some(summary) => format!("Codex task aborted: {summary}"),
+                            None => format!("Codex task aborted: {:?}", aborted.reason),
+                        });
+                    }
+                    _ => {}
+                }
+            }
+        }
+        Err(e) => error_result(format!("Failed to submit Codex task: {e}")),
+    };
+
+    running_requests_id_to_codex_uuid.lock().await.remove(&id);
+    outgoing.send_response(id, result).await;
+
+    if let Err(e) = conversation.submit(Op::Shutdown).await {
+        tracing::warn!("failed to shut down runTask conversation: {e}");
+    }
+    conversation_manager
+        .remove_conversation(&conversation_id)
+        .await;
+}
+
+fn task_result(
+    conversation_id: ConversationId,
+    last_agent_message: Option<String>,
+    diff: Option<String>,
+) -> CallToolResult {
+    let structured = RunTaskResult {
+        conversation_id: conversation_id.to_string(),
+        last_agent_message,
+        diff,
+    };
+    CallToolResult {
+        content: vec![ContentBlock::TextContent(TextContent {
+            r#type: "text".to_string(),
+            text: structured.last_agent_message.clone().unwrap_or_default(),
+            annotations: None,
+        })],
+        is_error: None,
+        structured_content: serde_json::to_value(structured).ok(),
+    }
+}
+
+fn error_result(text: String) -> CallToolResult {
+    CallToolResult {
+        content: vec![ContentBlock::TextContent(TextContent {
+            r#type: "text".to_string(),
+            text,
+            annotations: None,
+        })],
+        is_error: Some(true),
+        structured_content: None,
+    }
+}
+
 pub async fn run_codex_tool_session_reply(
     conversation: Arc<CodexConversation>,
     outgoing: Arc<OutgoingMessageSender>,
@@ -188,6 +313,30 @@ async fn run_codex_tool_session_inner(
                             request_id_str.clone(),
                             event.id.clone(),
                             call_id,
+                            Vec::new(),
+                        )
+                        .await;
+                        continue;
+                    }
+                    EventMsg::EscalationRequest(EscalationRequestEvent {
+                        command,
+                        cwd,
+                        call_id,
+                        requested_permissions,
+                        ..
+                    }) => {
+                        // Escalations are answered with the same `Op::ExecApproval`
+                        // as ordinary exec approvals.
+                        handle_exec_approval_request(
+                            command,
+                            cwd,
+                            outgoing.clone(),
+                            codex.clone(),
+                            request_id.clone(),
+                            request_id_str.clone(),
+                            event.id.clone(),
+                            call_id,
+                            requested_permissions,
                         )
                         .await;
                         continue;
@@ -205,12 +354,16 @@ async fn run_codex_tool_session_inner(
                         reason,
                         grant_root,
                         changes,
+                        stats,
+                        rejected_paths,
                     }) => {
                         handle_patch_approval_request(
                             call_id,
                             reason,
                             grant_root,
                             changes,
+                            stats,
+                            rejected_paths,
                             outgoing.clone(),
                             codex.clone(),
                             request_id.clone(),
@@ -220,7 +373,9 @@ async fn run_codex_tool_session_inner(
                         .await;
                         continue;
                     }
-                    EventMsg::TaskComplete(TaskCompleteEvent { last_agent_message }) => {
+                    EventMsg::TaskComplete(TaskCompleteEvent {
+                        last_agent_message, ..
+                    }) => {
                         let text = match last_agent_message {
                             Some(msg) => msg,
                             None => "".to_string(),
@@ -257,20 +412,39 @@ async fn run_codex_tool_session_inner(
                     EventMsg::AgentReasoningRawContent(_)
                     | EventMsg::AgentReasoningRawContentDelta(_)
                     | EventMsg::TaskStarted(_)
+                    | EventMsg::TaskStalled(_)
+                    | EventMsg::AutoCompact(_)
                     | EventMsg::TokenCount(_)
                     | EventMsg::AgentReasoning(_)
                     | EventMsg::AgentReasoningSectionBreak(_)
                     | EventMsg::McpToolCallBegin(_)
                     | EventMsg::McpToolCallEnd(_)
+                    | EventMsg::McpNotification(_)
                     | EventMsg::McpListToolsResponse(_)
+                    | EventMsg::McpListResourcesResponse(_)
                     | EventMsg::ListCustomPromptsResponse(_)
+                    | EventMsg::SessionEnvResponse(_)
+                    | EventMsg::NextPromptPreview(_)
+                    | EventMsg::UndoCompleted(_)
+                    | EventMsg::ListSnapshotsResponse(_)
+                    | EventMsg::SnapshotDiff(_)
+                    | EventMsg::CheckpointCreated(_)
+                    | EventMsg::CheckpointRewound(_)
+                    | EventMsg::TaskQueueUpdated(_)
+                    | EventMsg::PermissionConsentRequest(_)
+                    | EventMsg::SpendLimitApprovalRequest(_)
                     | EventMsg::ExecCommandBegin(_)
                     | EventMsg::ExecCommandOutputDelta(_)
                     | EventMsg::ExecCommandEnd(_)
+                    | EventMsg::ExecCommandDryRun(_)
                     | EventMsg::BackgroundEvent(_)
                     | EventMsg::StreamError(_)
                     | EventMsg::PatchApplyBegin(_)
                     | EventMsg::PatchApplyEnd(_)
+                    | EventMsg::HookBegin(_)
+                    | EventMsg::HookEnd(_)
+                    | EventMsg::SubAgentBegin(_)
+                    | EventMsg::SubAgentEnd(_)
                     | EventMsg::TurnDiff(_)
                     | EventMsg::WebSearchBegin(_)
                     | EventMsg::WebSearchEnd(_)
@@ -278,6 +452,10 @@ async fn run_codex_tool_session_inner(
                     | EventMsg::PlanUpdate(_)
                     | EventMsg::TurnAborted(_)
                     | EventMsg::ConversationPath(_)
+                    | EventMsg::ExecSessionKilled(_)
+                    | EventMsg::ToolCallCancelled(_)
+                    | EventMsg::SessionRemoved(_)
+                    | EventMsg::Warning(_)
                     | EventMsg::UserMessage(_)
                     | EventMsg::ShutdownComplete
                     | EventMsg::EnteredReviewMode(_)
@@ -2,6 +2,7 @@ use std::path::PathBuf;
 use std::sync::Arc;
 
 use codex_core::CodexConversation;
+use codex_core::protocol::EscalatedPermission;
 use codex_core::protocol::Op;
 use codex_core::protocol::ReviewDecision;
 use mcp_types::ElicitRequest;
@@ -35,6 +36,8 @@ pub struct ExecApprovalElicitRequestParams {
     pub codex_call_id: String,
     pub codex_command: Vec<String>,
     pub codex_cwd: PathBuf,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub codex_requested_permissions: Vec<EscalatedPermission>,
 }
 
 // TODO(mbolin): ExecApprovalResponse does not conform to ElicitResult. See:
@@ -56,13 +59,22 @@ pub(crate) async fn handle_exec_approval_request(
     tool_call_id: String,
     event_id: String,
     call_id: String,
+    requested_permissions: Vec<EscalatedPermission>,
 ) {
     let escaped_command =
         shlex::try_join(command.iter().map(String::as_str)).unwrap_or_else(|_| command.join(" "));
-    let message = format!(
+    let mut message = format!(
         "Allow Codex to run `{escaped_command}` in `{cwd}`?",
         cwd = cwd.to_string_lossy()
     );
+    if !requested_permissions.is_empty() {
+        let permissions = requested_permissions
+            .iter()
+            .map(|permission| permission.description())
+            .collect::<Vec<_>>()
+            .join(", ");
+        message.push_str(&format!(" It would gain {permissions}."));
+    }
 
     let params = ExecApprovalElicitRequestParams {
         message,
@@ -77,6 +89,7 @@ pub(crate) async fn handle_exec_approval_request(
         codex_call_id: call_id,
         codex_command: command,
         codex_cwd: cwd,
+        codex_requested_permissions: requested_permissions,
     };
     let params_json = match serde_json::to_value(&params) {
         Ok(value) => value,
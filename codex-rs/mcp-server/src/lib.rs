@@ -29,13 +29,20 @@ mod json_to_toml;
 pub(crate) mod message_processor;
 mod outgoing_message;
 mod patch_approval;
+mod workspace_tools;
 
 use crate::message_processor::MessageProcessor;
 use crate::outgoing_message::OutgoingMessage;
 use crate::outgoing_message::OutgoingMessageSender;
 
+pub use crate::codex_tool_config::ApplyPatchToolCallParam;
 pub use crate::codex_tool_config::CodexToolCallParam;
 pub use crate::codex_tool_config::CodexToolCallReplyParam;
+pub use crate::codex_tool_config::RestoreToolCallParam;
+pub use crate::codex_tool_config::RunTaskResult;
+pub use crate::codex_tool_config::RunTaskToolCallParam;
+pub use crate::codex_tool_config::SnapshotResult;
+pub use crate::codex_tool_config::SnapshotToolCallParam;
 pub use crate::exec_approval::ExecApprovalElicitRequestParams;
 pub use crate::exec_approval::ExecApprovalResponse;
 pub use crate::patch_approval::PatchApprovalElicitRequestParams;
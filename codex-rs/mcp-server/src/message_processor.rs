@@ -2,10 +2,18 @@ use std::collections::HashMap;
 use std::path::PathBuf;
 
 use crate::codex_message_processor::CodexMessageProcessor;
+use crate::codex_tool_config::ApplyPatchToolCallParam;
 use crate::codex_tool_config::CodexToolCallParam;
 use crate::codex_tool_config::CodexToolCallReplyParam;
+use crate::codex_tool_config::RestoreToolCallParam;
+use crate::codex_tool_config::SnapshotToolCallParam;
+use crate::codex_tool_config::create_tool_for_apply_patch_param;
 use crate::codex_tool_config::create_tool_for_codex_tool_call_param;
 use crate::codex_tool_config::create_tool_for_codex_tool_call_reply_param;
+use crate::codex_tool_config::create_tool_for_restore_param;
+use crate::codex_tool_config::create_tool_for_run_task_param;
+use crate::codex_tool_config::create_tool_for_snapshot_param;
+use crate::error_code::INTERNAL_ERROR_CODE;
 use crate::error_code::INVALID_REQUEST_ERROR_CODE;
 use crate::outgoing_message::OutgoingMessageSender;
 use codex_protocol::mcp_protocol::ClientRequest;
@@ -26,17 +34,26 @@ use mcp_types::JSONRPCErrorError;
 use mcp_types::JSONRPCNotification;
 use mcp_types::JSONRPCRequest;
 use mcp_types::JSONRPCResponse;
+use mcp_types::ListResourcesResult;
 use mcp_types::ListToolsResult;
 use mcp_types::ModelContextProtocolRequest;
+use mcp_types::ReadResourceResult;
+use mcp_types::ReadResourceResultContents;
 use mcp_types::RequestId;
+use mcp_types::Resource;
+use mcp_types::ServerCapabilitiesResources;
 use mcp_types::ServerCapabilitiesTools;
 use mcp_types::ServerNotification;
 use mcp_types::TextContent;
+use mcp_types::TextResourceContents;
 use serde_json::json;
 use std::sync::Arc;
 use tokio::sync::Mutex;
 use tokio::task;
 
+/// Loaded conversations are exposed as resources under this URI prefix.
+const CONVERSATION_RESOURCE_URI_PREFIX: &str = "codex://conversations/";
+
 pub(crate) struct MessageProcessor {
     codex_message_processor: CodexMessageProcessor,
     outgoing: Arc<OutgoingMessageSender>,
@@ -106,13 +123,13 @@ impl MessageProcessor {
                 self.handle_ping(request_id, params).await;
             }
             McpClientRequest::ListResourcesRequest(params) => {
-                self.handle_list_resources(params);
+                self.handle_list_resources(request_id, params).await;
             }
             McpClientRequest::ListResourceTemplatesRequest(params) => {
                 self.handle_list_resource_templates(params);
             }
             McpClientRequest::ReadResourceRequest(params) => {
-                self.handle_read_resource(params);
+                self.handle_read_resource(request_id, params).await;
             }
             McpClientRequest::SubscribeRequest(params) => {
                 self.handle_subscribe(params);
@@ -225,7 +242,10 @@ impl MessageProcessor {
                 experimental: None,
                 logging: None,
                 prompts: None,
-                resources: None,
+                resources: Some(ServerCapabilitiesResources {
+                    list_changed: Some(true),
+                    subscribe: None,
+                }),
                 tools: Some(ServerCapabilitiesTools {
                     list_changed: Some(true),
                 }),
@@ -262,11 +282,40 @@ impl MessageProcessor {
             .await;
     }
 
-    fn handle_list_resources(
+    async fn handle_list_resources(
         &self,
+        id: RequestId,
         params: <mcp_types::ListResourcesRequest as mcp_types::ModelContextProtocolRequest>::Params,
     ) {
         tracing::info!("resources/list -> params: {:?}", params);
+        let (conversations, active) = self.codex_message_processor.loaded_conversations().await;
+        let resources = conversations
+            .into_iter()
+            .map(|conversation| {
+                let conversation_id = conversation.conversation_id;
+                let description = if active == Some(conversation_id) {
+                    format!("{} (active)", conversation.model)
+                } else {
+                    conversation.model
+                };
+                Resource {
+                    annotations: None,
+                    description: Some(description),
+                    mime_type: Some("application/json".to_string()),
+                    name: conversation_id.to_string(),
+                    size: None,
+                    title: Some(format!("Conversation {conversation_id}")),
+                    uri: format!("{CONVERSATION_RESOURCE_URI_PREFIX}{conversation_id}"),
+                }
+            })
+            .collect();
+
+        let result = ListResourcesResult {
+            next_cursor: None,
+            resources,
+        };
+        self.send_response::<mcp_types::ListResourcesRequest>(id, result)
+            .await;
     }
 
     fn handle_list_resource_templates(
@@ -277,11 +326,59 @@ impl MessageProcessor {
         tracing::info!("resources/templates/list -> params: {:?}", params);
     }
 
-    fn handle_read_resource(
+    async fn handle_read_resource(
         &self,
+        id: RequestId,
         params: <mcp_types::ReadResourceRequest as mcp_types::ModelContextProtocolRequest>::Params,
     ) {
         tracing::info!("resources/read -> params: {:?}", params);
+        let uri = params.uri;
+        let (conversations, active) = self.codex_message_processor.loaded_conversations().await;
+        let conversation = uri
+            .strip_prefix(CONVERSATION_RESOURCE_URI_PREFIX)
+            .and_then(|id| ConversationId::from_string(id).ok())
+            .and_then(|conversation_id| {
+                conversations
+                    .into_iter()
+                    .find(|it| it.conversation_id == conversation_id)
+            });
+        let Some(conversation) = conversation else {
+            let error = JSONRPCErrorError {
+                code: INVALID_REQUEST_ERROR_CODE,
+                message: format!("resource not found: {uri}"),
+                data: None,
+            };
+            self.outgoing.send_error(id, error).await;
+            return;
+        };
+
+        let active = active == Some(conversation.conversation_id);
+        let text = match serde_json::to_value(&conversation) {
+            Ok(mut value) => {
+                value["active"] = json!(active);
+                value.to_string()
+            }
+            Err(err) => {
+                let error = JSONRPCErrorError {
+                    code: INTERNAL_ERROR_CODE,
+                    message: format!("failed to serialize conversation: {err}"),
+                    data: None,
+                };
+                self.outgoing.send_error(id, error).await;
+                return;
+            }
+        };
+        let result = ReadResourceResult {
+            contents: vec![ReadResourceResultContents::TextResourceContents(
+                TextResourceContents {
+                    mime_type: Some("application/json".to_string()),
+                    text,
+                    uri,
+                },
+            )],
+        };
+        self.send_response::<mcp_types::ReadResourceRequest>(id, result)
+            .await;
     }
 
     fn handle_subscribe(
@@ -322,6 +419,10 @@ impl MessageProcessor {
             tools: vec![
                 create_tool_for_codex_tool_call_param(),
                 create_tool_for_codex_tool_call_reply_param(),
+                create_tool_for_run_task_param(),
+                create_tool_for_apply_patch_param(),
+                create_tool_for_snapshot_param(),
+                create_tool_for_restore_param(),
             ],
             next_cursor: None,
         };
@@ -344,6 +445,10 @@ impl MessageProcessor {
                 self.handle_tool_call_codex_session_reply(id, arguments)
                     .await
             }
+            "runTask" => self.handle_tool_call_run_task(id, arguments).await,
+            "codex_apply_patch" => self.handle_tool_call_apply_patch(id, arguments).await,
+            "codex_snapshot" => self.handle_tool_call_snapshot(id, arguments).await,
+            "codex_restore" => self.handle_tool_call_restore(id, arguments).await,
             _ => {
                 let result = CallToolResult {
                     content: vec![ContentBlock::TextContent(TextContent {
@@ -435,6 +540,151 @@ impl MessageProcessor {
         });
     }
 
+    async fn handle_tool_call_run_task(&self, id: RequestId, arguments: Option<serde_json::Value>) {
+        let Some(arguments) = arguments else {
+            self.send_tool_call_error(
+                id,
+                "Missing arguments for runTask tool-call; the `prompt` field is required."
+                    .to_string(),
+            )
+            .await;
+            return;
+        };
+        let params = match serde_json::from_value::<RunTaskToolCallParam>(arguments) {
+            Ok(params) => params,
+            Err(e) => {
+                self.send_tool_call_error(id, format!("Failed to parse runTask arguments: {e}"))
+                    .await;
+                return;
+            }
+        };
+        let (prompt, config) = match params.into_config(self.codex_linux_sandbox_exe.clone()) {
+            Ok(res) => res,
+            Err(e) => {
+                self.send_tool_call_error(
+                    id,
+                    format!("Failed to load Codex configuration from overrides: {e}"),
+                )
+                .await;
+                return;
+            }
+        };
+
+        let outgoing = self.outgoing.clone();
+        let conversation_manager = self.conversation_manager.clone();
+        let running_requests_id_to_codex_uuid = self.running_requests_id_to_codex_uuid.clone();
+        task::spawn(async move {
+            crate::codex_tool_runner::run_task_tool_session(
+                id,
+                prompt,
+                config,
+                outgoing,
+                conversation_manager,
+                running_requests_id_to_codex_uuid,
+            )
+            .await;
+        });
+    }
+
+    async fn handle_tool_call_apply_patch(
+        &self,
+        id: RequestId,
+        arguments: Option<serde_json::Value>,
+    ) {
+        let Some(arguments) = arguments else {
+            self.send_tool_call_error(
+                id,
+                "Missing arguments for codex_apply_patch tool-call; the `patch` field is required."
+                    .to_string(),
+            )
+            .await;
+            return;
+        };
+        let params = match serde_json::from_value::<ApplyPatchToolCallParam>(arguments) {
+            Ok(params) => params,
+            Err(e) => {
+                self.send_tool_call_error(
+                    id,
+                    format!("Failed to parse codex_apply_patch arguments: {e}"),
+                )
+                .await;
+                return;
+            }
+        };
+
+        let outgoing = self.outgoing.clone();
+        let codex_linux_sandbox_exe = self.codex_linux_sandbox_exe.clone();
+        task::spawn(async move {
+            let result =
+                crate::workspace_tools::run_apply_patch(params, codex_linux_sandbox_exe).await;
+            outgoing.send_response(id, result).await;
+        });
+    }
+
+    async fn handle_tool_call_snapshot(&self, id: RequestId, arguments: Option<serde_json::Value>) {
+        let params = match serde_json::from_value::<SnapshotToolCallParam>(
+            arguments.unwrap_or_else(|| json!({})),
+        ) {
+            Ok(params) => params,
+            Err(e) => {
+                self.send_tool_call_error(
+                    id,
+                    format!("Failed to parse codex_snapshot arguments: {e}"),
+                )
+                .await;
+                return;
+            }
+        };
+
+        let outgoing = self.outgoing.clone();
+        task::spawn(async move {
+            let result = crate::workspace_tools::run_snapshot(params).await;
+            outgoing.send_response(id, result).await;
+        });
+    }
+
+    async fn handle_tool_call_restore(&self, id: RequestId, arguments: Option<serde_json::Value>) {
+        let Some(arguments) = arguments else {
+            self.send_tool_call_error(
+                id,
+                "Missing arguments for codex_restore tool-call; the `snapshotId` field is required.".to_string(),
+            )
+            .await;
+            return;
+        };
+        let params = match serde_json::from_value::<RestoreToolCallParam>(arguments) {
+            Ok(params) => params,
+            Err(e) => {
+                self.send_tool_call_error(
+                    id,
+                    format!("Failed to parse codex_restore arguments: {e}"),
+                )
+                .await;
+                return;
+            }
+        };
+
+        let outgoing = self.outgoing.clone();
+        task::spawn(async move {
+            let result = crate::workspace_tools::run_restore(params).await;
+            outgoing.send_response(id, result).await;
+        });
+    }
+
+    async fn send_tool_call_error(&self, id: RequestId, text: String) {
+        let result = CallToolResult {
+            content: vec![ContentBlock::TextContent(TextContent {
+                r#type: "text".to_owned(),
+                text,
+                annotations: None,
+            })],
+            is_error: Some(true),
+            structured_content: None,
+        };
+        self.send_response::<mcp_types::CallToolRequest>(id, result)
+            .await;
+    }
+
     async fn handle_tool_call_codex_session_reply(
         &self,
         request_id: RequestId,
@@ -5,6 +5,8 @@ use std::sync::Arc;
 use codex_core::CodexConversation;
 use codex_core::protocol::FileChange;
 use codex_core::protocol::Op;
+use codex_core::protocol::PatchPathEscape;
+use codex_core::protocol::PatchStats;
 use codex_core::protocol::ReviewDecision;
 use mcp_types::ElicitRequest;
 use mcp_types::ElicitRequestParamsRequestedSchema;
@@ -33,11 +35,17 @@ pub struct PatchApprovalElicitRequestParams {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub codex_grant_root: Option<PathBuf>,
     pub codex_changes: HashMap<PathBuf, FileChange>,
+    pub codex_stats: PatchStats,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub codex_rejected_paths: Vec<PatchPathEscape>,
 }
 
 #[derive(Debug, Deserialize, Serialize)]
 pub struct PatchApprovalResponse {
     pub decision: ReviewDecision,
+    /// Apply only the changes to these files; see `Op::PatchApproval`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub approved_files: Option<Vec<PathBuf>>,
 }
 
 #[allow(clippy::too_many_arguments)]
@@ -46,6 +54,8 @@ pub(crate) async fn handle_patch_approval_request(
     reason: Option<String>,
     grant_root: Option<PathBuf>,
     changes: HashMap<PathBuf, FileChange>,
+    stats: PatchStats,
+    rejected_paths: Vec<PatchPathEscape>,
     outgoing: Arc<OutgoingMessageSender>,
     codex: Arc<CodexConversation>,
     request_id: RequestId,
@@ -56,7 +66,12 @@ pub(crate) async fn handle_patch_approval_request(
     if let Some(r) = &reason {
         message_lines.push(r.clone());
     }
-    message_lines.push("Allow Codex to apply proposed code changes?".to_string());
+    for escape in &rejected_paths {
+        message_lines.push(format!("Rejected: {escape}"));
+    }
+    message_lines.push(format!(
+        "Allow Codex to apply proposed code changes ({stats})?"
+    ));
 
     let params = PatchApprovalElicitRequestParams {
         message: message_lines.join("\n"),
@@ -72,6 +87,8 @@ pub(crate) async fn handle_patch_approval_request(
         codex_reason: reason,
         codex_grant_root: grant_root,
         codex_changes: changes,
+        codex_stats: stats,
+        codex_rejected_paths: rejected_paths,
     };
     let params_json = match serde_json::to_value(&params) {
         Ok(value) => value,
@@ -122,6 +139,7 @@ pub(crate) async fn on_patch_approval_response(
                 .submit(Op::PatchApproval {
                     id: event_id.clone(),
                     decision: ReviewDecision::Denied,
+                    approved_files: None,
                 })
                 .await
             {
@@ -135,6 +153,7 @@ pub(crate) async fn on_patch_approval_response(
         error!("failed to deserialize PatchApprovalResponse: {err}");
         PatchApprovalResponse {
             decision: ReviewDecision::Denied,
+            approved_files: None,
         }
     });
 
@@ -142,6 +161,7 @@ pub(crate) async fn on_patch_approval_response(
         .submit(Op::PatchApproval {
             id: event_id,
             decision: response.decision,
+            approved_files: response.approved_files,
         })
         .await
     {
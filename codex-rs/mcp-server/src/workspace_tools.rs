@@ -0,0 +1,134 @@
+//! `codex_apply_patch`, `codex_snapshot` and `codex_restore`: direct access to
+//! Codex's sandboxed patch engine and ghost-commit undo machinery, for
+//! agents that make their own edits but want Codex's guarantees.
+
+use std::path::PathBuf;
+
+use codex_core::apply_patch_standalone;
+use codex_core::protocol::SandboxPolicy;
+use codex_git_tooling::CreateGhostCommitOptions;
+use codex_git_tooling::create_ghost_commit;
+use codex_git_tooling::record_ghost_commit;
+use codex_git_tooling::restore_to_commit;
+use mcp_types::CallToolResult;
+use mcp_types::ContentBlock;
+use mcp_types::TextContent;
+
+use crate::codex_tool_config::ApplyPatchToolCallParam;
+use crate::codex_tool_config::CodexToolCallSandboxMode;
+use crate::codex_tool_config::RestoreToolCallParam;
+use crate::codex_tool_config::SnapshotResult;
+use crate::codex_tool_config::SnapshotToolCallParam;
+
+pub(crate) async fn run_apply_patch(
+    params: ApplyPatchToolCallParam,
+    codex_linux_sandbox_exe: Option<PathBuf>,
+) -> CallToolResult {
+    let ApplyPatchToolCallParam {
+        patch,
+        cwd,
+        sandbox,
+    } = params;
+    let cwd = match resolve_cwd(cwd) {
+        Ok(cwd) => cwd,
+        Err(e) => return text_result(e, true),
+    };
+    let sandbox_policy = match sandbox.unwrap_or(CodexToolCallSandboxMode::WorkspaceWrite) {
+        CodexToolCallSandboxMode::ReadOnly => SandboxPolicy::new_read_only_policy(),
+        CodexToolCallSandboxMode::WorkspaceWrite => SandboxPolicy::new_workspace_write_policy(),
+        CodexToolCallSandboxMode::DangerFullAccess => SandboxPolicy::DangerFullAccess,
+    };
+
+    match apply_patch_standalone(patch, &cwd, &sandbox_policy, &codex_linux_sandbox_exe).await {
+        Ok(output) => text_result(output, false),
+        Err(e) => text_result(e, true),
+    }
+}
+
+pub(crate) async fn run_snapshot(params: SnapshotToolCallParam) -> CallToolResult {
+    let SnapshotToolCallParam { cwd, message } = params;
+    let cwd = match resolve_cwd(cwd) {
+        Ok(cwd) => cwd,
+        Err(e) => return text_result(e, true),
+    };
+
+    // Snapshots shell out to git, so keep them off the async runtime.
+    let snapshot = tokio::task::spawn_blocking(move || {
+        let mut options = CreateGhostCommitOptions::new(&cwd);
+        if let Some(message) = message.as_deref() {
+            options = options.message(message);
+        }
+        let commit = create_ghost_commit(&options)?;
+        // Keep the snapshot reachable until Codex prunes old ghost commits.
+        record_ghost_commit(&cwd, &commit)?;
+        Ok::<_, codex_git_tooling::GitToolingError>(commit)
+    })
+    .await;
+
+    match snapshot {
+        Ok(Ok(commit)) => {
+            let structured = SnapshotResult {
+                snapshot_id: commit.id().to_string(),
+            };
+            CallToolResult {
+                content: vec![ContentBlock::TextContent(TextContent {
+                    r#type: "text".to_string(),
+                    text: format!("Created snapshot {}", commit.id()),
+                    annotations: None,
+                })],
+                is_error: None,
+                structured_content: serde_json::to_value(structured).ok(),
+            }
+        }
+        Ok(Err(e)) => text_result(format!("failed to create snapshot: {e}"), true),
+        Err(e) => text_result(format!("failed to create snapshot: {e}"), true),
+    }
+}
+
+pub(crate) async fn run_restore(params: RestoreToolCallParam) -> CallToolResult {
+    let RestoreToolCallParam { snapshot_id, cwd } = params;
+    let cwd = match resolve_cwd(cwd) {
+        Ok(cwd) => cwd,
+        Err(e) => return text_result(e, true),
+    };
+
+    let restored = tokio::task::spawn_blocking({
+        let snapshot_id = snapshot_id.clone();
+        move || restore_to_commit(&cwd, &snapshot_id)
+    })
+    .await;
+
+    match restored {
+        Ok(Ok(())) => text_result(format!("Restored snapshot {snapshot_id}"), false),
+        Ok(Err(e)) => text_result(
+            format!("failed to restore snapshot {snapshot_id}: {e}"),
+            true,
+        ),
+        Err(e) => text_result(
+            format!("failed to restore snapshot {snapshot_id}: {e}"),
+            true,
+        ),
+    }
+}
+
+/// `cwd` resolved against the server process's working directory.
+fn resolve_cwd(cwd: Option<String>) -> Result<PathBuf, String> {
+    let base = std::env::current_dir()
+        .map_err(|e| format!("failed to determine current directory: {e}"))?;
+    Ok(match cwd {
+        Some(cwd) => base.join(cwd),
+        None => base,
+    })
+}
+
+fn text_result(text: String, is_error: bool) -> CallToolResult {
+    CallToolResult {
+        content: vec![ContentBlock::TextContent(TextContent {
+            r#type: "text".to_string(),
+            text,
+            annotations: None,
+        })],
+        is_error: is_error.then_some(true),
+        structured_content: None,
+    }
+}
@@ -12,6 +12,7 @@ use tokio::process::ChildStdout;
 use anyhow::Context;
 use assert_cmd::prelude::*;
 use codex_mcp_server::CodexToolCallParam;
+use codex_mcp_server::RunTaskToolCallParam;
 use codex_protocol::mcp_protocol::AddConversationListenerParams;
 use codex_protocol::mcp_protocol::ArchiveConversationParams;
 use codex_protocol::mcp_protocol::CancelLoginChatGptParams;
@@ -25,6 +26,7 @@ use codex_protocol::mcp_protocol::ResumeConversationParams;
 use codex_protocol::mcp_protocol::SendUserMessageParams;
 use codex_protocol::mcp_protocol::SendUserTurnParams;
 use codex_protocol::mcp_protocol::SetDefaultModelParams;
+use codex_protocol::mcp_protocol::SwitchConversationParams;
 
 use mcp_types::CallToolRequestParams;
 use mcp_types::ClientCapabilities;
@@ -37,6 +39,7 @@ use mcp_types::JSONRPCRequest;
 use mcp_types::JSONRPCResponse;
 use mcp_types::ModelContextProtocolNotification;
 use mcp_types::ModelContextProtocolRequest;
+use mcp_types::ReadResourceRequestParams;
 use mcp_types::RequestId;
 use pretty_assertions::assert_eq;
 use serde_json::json;
@@ -215,6 +218,39 @@ impl McpProcess {
         .await
     }
 
+    /// Send a `runTask` tool call.
+    pub async fn send_run_task_tool_call(
+        &mut self,
+        params: RunTaskToolCallParam,
+    ) -> anyhow::Result<i64> {
+        let run_task_params = CallToolRequestParams {
+            name: "runTask".to_string(),
+            arguments: Some(serde_json::to_value(params)?),
+        };
+        self.send_request(
+            mcp_types::CallToolRequest::METHOD,
+            Some(serde_json::to_value(run_task_params)?),
+        )
+        .await
+    }
+
+    /// Send a `tools/call` request for the tool `name`.
+    pub async fn send_tool_call(
+        &mut self,
+        name: &str,
+        arguments: serde_json::Value,
+    ) -> anyhow::Result<i64> {
+        let params = CallToolRequestParams {
+            name: name.to_string(),
+            arguments: Some(arguments),
+        };
+        self.send_request(
+            mcp_types::CallToolRequest::METHOD,
+            Some(serde_json::to_value(params)?),
+        )
+        .await
+    }
+
     /// Send a `newConversation` JSON-RPC request.
     pub async fn send_new_conversation_request(
         &mut self,
@@ -322,6 +358,38 @@ impl McpProcess {
         self.send_request("listConversations", params).await
     }
 
+    /// Send a `listLoadedConversations` JSON-RPC request.
+    pub async fn send_list_loaded_conversations_request(&mut self) -> anyhow::Result<i64> {
+        self.send_request("listLoadedConversations", None).await
+    }
+
+    /// Send a `switchConversation` JSON-RPC request.
+    pub async fn send_switch_conversation_request(
+        &mut self,
+        params: SwitchConversationParams,
+    ) -> anyhow::Result<i64> {
+        let params = Some(serde_json::to_value(params)?);
+        self.send_request("switchConversation", params).await
+    }
+
+    /// Send a `resources/list` JSON-RPC request.
+    pub async fn send_list_resources_request(&mut self) -> anyhow::Result<i64> {
+        self.send_request(mcp_types::ListResourcesRequest::METHOD, None)
+            .await
+    }
+
+    /// Send a `resources/read` JSON-RPC request.
+    pub async fn send_read_resource_request(&mut self, uri: &str) -> anyhow::Result<i64> {
+        let params = ReadResourceRequestParams {
+            uri: uri.to_string(),
+        };
+        self.send_request(
+            mcp_types::ReadResourceRequest::METHOD,
+            Some(serde_json::to_value(params)?),
+        )
+        .await
+    }
+
     /// Send a `resumeConversation` JSON-RPC request.
     pub async fn send_resume_conversation_request(
         &mut self,
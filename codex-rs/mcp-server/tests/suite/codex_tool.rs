@@ -4,6 +4,7 @@ use std::path::Path;
 use std::path::PathBuf;
 
 use codex_core::protocol::FileChange;
+use codex_core::protocol::PatchStats;
 use codex_core::protocol::ReviewDecision;
 use codex_core::spawn::CODEX_SANDBOX_NETWORK_DISABLED_ENV_VAR;
 use codex_mcp_server::CodexToolCallParam;
@@ -192,6 +193,7 @@ fn create_expected_elicitation_request(
             codex_event_id,
             codex_command: command,
             codex_cwd: workdir.to_path_buf(),
+            codex_requested_permissions: Vec::new(),
             codex_call_id: "call1234".to_string(),
         })?),
     })
@@ -274,6 +276,7 @@ async fn patch_approval_triggers_elicitation() -> anyhow::Result<()> {
             elicitation_request_id,
             serde_json::to_value(PatchApprovalResponse {
                 decision: ReviewDecision::Approved,
+                approved_files: None,
             })?,
         )
         .await?;
@@ -382,7 +385,10 @@ fn create_expected_patch_approval_elicitation_request(
     if let Some(r) = &reason {
         message_lines.push(r.clone());
     }
-    message_lines.push("Allow Codex to apply proposed code changes?".to_string());
+    let stats = PatchStats::from_changes(&changes);
+    message_lines.push(format!(
+        "Allow Codex to apply proposed code changes ({stats})?"
+    ));
 
     Ok(JSONRPCRequest {
         jsonrpc: JSONRPC_VERSION.into(),
@@ -401,6 +407,8 @@ fn create_expected_patch_approval_elicitation_request(
             codex_reason: reason,
             codex_grant_root: grant_root,
             codex_changes: changes,
+            codex_stats: stats,
+            codex_rejected_paths: Vec::new(),
             codex_call_id: "call1234".to_string(),
         })?),
     })
@@ -0,0 +1,159 @@
+use std::path::Path;
+
+use codex_protocol::mcp_protocol::ConversationId;
+use codex_protocol::mcp_protocol::ListLoadedConversationsResponse;
+use codex_protocol::mcp_protocol::NewConversationParams;
+use codex_protocol::mcp_protocol::NewConversationResponse;
+use codex_protocol::mcp_protocol::SwitchConversationParams;
+use codex_protocol::mcp_protocol::SwitchConversationResponse;
+use mcp_test_support::McpProcess;
+use mcp_test_support::to_response;
+use mcp_types::JSONRPCResponse;
+use mcp_types::ListResourcesResult;
+use mcp_types::ReadResourceResult;
+use mcp_types::ReadResourceResultContents;
+use mcp_types::RequestId;
+use pretty_assertions::assert_eq;
+use tempfile::TempDir;
+use tokio::time::timeout;
+
+const DEFAULT_READ_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(10);
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn list_switch_and_read_loaded_conversations() {
+    let codex_home = TempDir::new().expect("create temp dir");
+    create_config_toml(codex_home.path()).expect("write config.toml");
+
+    let mut mcp = McpProcess::new(codex_home.path())
+        .await
+        .expect("spawn mcp process");
+    timeout(DEFAULT_READ_TIMEOUT, mcp.initialize())
+        .await
+        .expect("initialize timeout")
+        .expect("initialize request");
+
+    let first = new_conversation(&mut mcp).await;
+    let second = new_conversation(&mut mcp).await;
+
+    // The most recently created conversation is active.
+    let loaded = list_loaded_conversations(&mut mcp).await;
+    let mut ids: Vec<ConversationId> = loaded.items.iter().map(|c| c.conversation_id).collect();
+    ids.sort_by_key(ToString::to_string);
+    let mut expected = vec![first, second];
+    expected.sort_by_key(ToString::to_string);
+    assert_eq!(ids, expected);
+    assert_eq!(loaded.active_conversation_id, Some(second));
+
+    let switch_id = mcp
+        .send_switch_conversation_request(SwitchConversationParams {
+            conversation_id: first,
+        })
+        .await
+        .expect("send switchConversation");
+    let switch_response: JSONRPCResponse = timeout(
+        DEFAULT_READ_TIMEOUT,
+        mcp.read_stream_until_response_message(RequestId::Integer(switch_id)),
+    )
+    .await
+    .expect("switchConversation timeout")
+    .expect("switchConversation response");
+    let _: SwitchConversationResponse =
+        to_response(switch_response).expect("deserialize switchConversation response");
+
+    let loaded = list_loaded_conversations(&mut mcp).await;
+    assert_eq!(loaded.active_conversation_id, Some(first));
+
+    // Each loaded conversation is also an MCP resource.
+    let list_id = mcp
+        .send_list_resources_request()
+        .await
+        .expect("send resources/list");
+    let list_response: JSONRPCResponse = timeout(
+        DEFAULT_READ_TIMEOUT,
+        mcp.read_stream_until_response_message(RequestId::Integer(list_id)),
+    )
+    .await
+    .expect("resources/list timeout")
+    .expect("resources/list response");
+    let ListResourcesResult { resources, .. } =
+        to_response(list_response).expect("deserialize resources/list response");
+    assert_eq!(resources.len(), 2);
+    let first_uri = format!("codex://conversations/{first}");
+    let first_resource = resources
+        .iter()
+        .find(|r| r.uri == first_uri)
+        .expect("first conversation listed as a resource");
+    assert_eq!(
+        first_resource.description.as_deref(),
+        Some("mock-model (active)")
+    );
+
+    let read_id = mcp
+        .send_read_resource_request(&first_uri)
+        .await
+        .expect("send resources/read");
+    let read_response: JSONRPCResponse = timeout(
+        DEFAULT_READ_TIMEOUT,
+        mcp.read_stream_until_response_message(RequestId::Integer(read_id)),
+    )
+    .await
+    .expect("resources/read timeout")
+    .expect("resources/read response");
+    let ReadResourceResult { contents } =
+        to_response(read_response).expect("deserialize resources/read response");
+    let [ReadResourceResultContents::TextResourceContents(contents)] = contents.as_slice() else {
+        panic!("expected a single text resource, got {contents:?}");
+    };
+    let value: serde_json::Value =
+        serde_json::from_str(&contents.text).expect("resource text is JSON");
+    assert_eq!(value["conversationId"], first.to_string());
+    assert_eq!(value["model"], "mock-model");
+    assert_eq!(value["active"], true);
+}
+
+async fn new_conversation(mcp: &mut McpProcess) -> ConversationId {
+    let request_id = mcp
+        .send_new_conversation_request(NewConversationParams {
+            model: Some("mock-model".to_string()),
+            ..Default::default()
+        })
+        .await
+        .expect("send newConversation");
+    let response: JSONRPCResponse = timeout(
+        DEFAULT_READ_TIMEOUT,
+        mcp.read_stream_until_response_message(RequestId::Integer(request_id)),
+    )
+    .await
+    .expect("newConversation timeout")
+    .expect("newConversation response");
+    let NewConversationResponse {
+        conversation_id, ..
+    } = to_response(response).expect("deserialize newConversation response");
+    conversation_id
+}
+
+async fn list_loaded_conversations(mcp: &mut McpProcess) -> ListLoadedConversationsResponse {
+    let request_id = mcp
+        .send_list_loaded_conversations_request()
+        .await
+        .expect("send listLoadedConversations");
+    let response: JSONRPCResponse = timeout(
+        DEFAULT_READ_TIMEOUT,
+        mcp.read_stream_until_response_message(RequestId::Integer(request_id)),
+    )
+    .await
+    .expect("listLoadedConversations timeout")
+    .expect("listLoadedConversations response");
+    to_response(response).expect("deserialize listLoadedConversations response")
+}
+
+fn create_config_toml(codex_home: &Path) -> std::io::Result<()> {
+    let config_toml = codex_home.join("config.toml");
+    std::fs::write(
+        config_toml,
+        r#"model = "mock-model"
+approval_policy = "never"
+sandbox_mode = "read-only"
+"#,
+    )
+}
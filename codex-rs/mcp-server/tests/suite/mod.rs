@@ -7,8 +7,11 @@ mod config;
 mod create_conversation;
 mod interrupt;
 mod list_resume;
+mod loaded_conversations;
 mod login;
+mod run_task;
 mod send_message;
 mod set_default_model;
 mod user_agent;
 mod user_info;
+mod workspace_tools;
@@ -0,0 +1,97 @@
+#![cfg(unix)]
+// Support code lives in the `mcp_test_support` crate under tests/common.
+
+use std::path::Path;
+
+use codex_mcp_server::RunTaskResult;
+use codex_mcp_server::RunTaskToolCallParam;
+use core_test_support::non_sandbox_test;
+use mcp_types::CallToolResult;
+use mcp_types::RequestId;
+use pretty_assertions::assert_eq;
+use tempfile::TempDir;
+use tokio::time::timeout;
+
+use mcp_test_support::McpProcess;
+use mcp_test_support::create_final_assistant_message_sse_response;
+use mcp_test_support::create_mock_chat_completions_server;
+use mcp_test_support::to_response;
+
+const DEFAULT_READ_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(10);
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_run_task_returns_final_message() {
+    non_sandbox_test!();
+
+    if let Err(err) = run_task_returns_final_message().await {
+        panic!("failure: {err}");
+    }
+}
+
+async fn run_task_returns_final_message() -> anyhow::Result<()> {
+    let server =
+        create_mock_chat_completions_server(vec![create_final_assistant_message_sse_response(
+            "All done.",
+        )?])
+        .await;
+    let codex_home = TempDir::new()?;
+    create_config_toml(codex_home.path(), &server.uri())?;
+    let workdir = TempDir::new()?;
+
+    let mut mcp = McpProcess::new(codex_home.path()).await?;
+    timeout(DEFAULT_READ_TIMEOUT, mcp.initialize()).await??;
+
+    let request_id = mcp
+        .send_run_task_tool_call(RunTaskToolCallParam {
+            prompt: "Say you are done".to_string(),
+            cwd: Some(workdir.path().to_string_lossy().into_owned()),
+            ..Default::default()
+        })
+        .await?;
+
+    // Lifecycle events are streamed before the response arrives.
+    timeout(
+        DEFAULT_READ_TIMEOUT,
+        mcp.read_stream_until_notification_message("codex/event"),
+    )
+    .await??;
+
+    let response = timeout(
+        DEFAULT_READ_TIMEOUT,
+        mcp.read_stream_until_response_message(RequestId::Integer(request_id)),
+    )
+    .await??;
+    let result = to_response::<CallToolResult>(response)?;
+    assert_eq!(result.is_error, None);
+    let structured = result
+        .structured_content
+        .ok_or_else(|| anyhow::anyhow!("runTask result has no structured content"))?;
+    let task: RunTaskResult = serde_json::from_value(structured)?;
+    assert_eq!(task.last_agent_message.as_deref(), Some("All done."));
+    assert_eq!(task.diff, None);
+
+    Ok(())
+}
+
+fn create_config_toml(codex_home: &Path, server_uri: &str) -> std::io::Result<()> {
+    let config_toml = codex_home.join("config.toml");
+    std::fs::write(
+        config_toml,
+        format!(
+            r#"
+model = "mock-model"
+approval_policy = "untrusted"
+sandbox_mode = "read-only"
+
+model_provider = "mock_provider"
+
+[model_providers.mock_provider]
+name = "Mock provider for test"
+base_url = "{server_uri}/v1"
+wire_api = "chat"
+request_max_retries = 0
+stream_max_retries = 0
+"#
+        ),
+    )
+}
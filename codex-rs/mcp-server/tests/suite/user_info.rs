@@ -38,6 +38,7 @@ async fn user_info_returns_email_from_auth_json() {
             account_id: None,
         }),
         last_refresh: None,
+        mcp_servers: Default::default(),
     };
     write_auth_json(&auth_path, &auth).expect("write auth.json");
 
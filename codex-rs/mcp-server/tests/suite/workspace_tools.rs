@@ -0,0 +1,92 @@
+#![cfg(unix)]
+// Support code lives in the `mcp_test_support` crate under tests/common.
+
+use std::process::Command;
+
+use codex_mcp_server::SnapshotResult;
+use mcp_types::CallToolResult;
+use mcp_types::RequestId;
+use pretty_assertions::assert_eq;
+use serde_json::json;
+use tempfile::TempDir;
+use tokio::time::timeout;
+
+use mcp_test_support::McpProcess;
+use mcp_test_support::to_response;
+
+const DEFAULT_READ_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(10);
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_snapshot_apply_patch_and_restore() {
+    if let Err(err) = snapshot_apply_patch_and_restore().await {
+        panic!("failure: {err}");
+    }
+}
+
+async fn snapshot_apply_patch_and_restore() -> anyhow::Result<()> {
+    let codex_home = TempDir::new()?;
+    let repo = TempDir::new()?;
+    let status = Command::new("git")
+        .args(["init", "--quiet"])
+        .current_dir(repo.path())
+        .status()?;
+    anyhow::ensure!(status.success(), "git init failed");
+    let hello = repo.path().join("hello.txt");
+    std::fs::write(&hello, "hello\n")?;
+    let cwd = repo.path().to_string_lossy().into_owned();
+
+    let mut mcp = McpProcess::new(codex_home.path()).await?;
+    timeout(DEFAULT_READ_TIMEOUT, mcp.initialize()).await??;
+
+    let snapshot = call_tool(&mut mcp, "codex_snapshot", json!({ "cwd": cwd })).await?;
+    assert_eq!(snapshot.is_error, None);
+    let structured = snapshot
+        .structured_content
+        .ok_or_else(|| anyhow::anyhow!("codex_snapshot result has no structured content"))?;
+    let SnapshotResult { snapshot_id } = serde_json::from_value(structured)?;
+
+    // Danger-full-access skips the platform sandbox, which tests cannot rely on.
+    let patch = "*** Begin Patch\n*** Update File: hello.txt\n@@\n-hello\n+goodbye\n*** End Patch";
+    let applied = call_tool(
+        &mut mcp,
+        "codex_apply_patch",
+        json!({ "patch": patch, "cwd": cwd, "sandbox": "danger-full-access" }),
+    )
+    .await?;
+    assert_eq!(applied.is_error, None);
+    assert_eq!(std::fs::read_to_string(&hello)?, "goodbye\n");
+
+    let rejected = call_tool(
+        &mut mcp,
+        "codex_apply_patch",
+        json!({ "patch": patch, "cwd": cwd, "sandbox": "read-only" }),
+    )
+    .await?;
+    assert_eq!(rejected.is_error, Some(true));
+    assert_eq!(std::fs::read_to_string(&hello)?, "goodbye\n");
+
+    let restored = call_tool(
+        &mut mcp,
+        "codex_restore",
+        json!({ "snapshotId": snapshot_id, "cwd": cwd }),
+    )
+    .await?;
+    assert_eq!(restored.is_error, None);
+    assert_eq!(std::fs::read_to_string(&hello)?, "hello\n");
+
+    Ok(())
+}
+
+async fn call_tool(
+    mcp: &mut McpProcess,
+    name: &str,
+    arguments: serde_json::Value,
+) -> anyhow::Result<CallToolResult> {
+    let request_id = mcp.send_tool_call(name, arguments).await?;
+    let response = timeout(
+        DEFAULT_READ_TIMEOUT,
+        mcp.read_stream_until_response_message(RequestId::Integer(request_id)),
+    )
+    .await??;
+    to_response::<CallToolResult>(response)
+}
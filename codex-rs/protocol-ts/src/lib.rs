@@ -1,6 +1,7 @@
 use anyhow::Context;
 use anyhow::Result;
 use anyhow::anyhow;
+use schemars::JsonSchema;
 use std::ffi::OsStr;
 use std::fs;
 use std::io::Read;
@@ -8,45 +9,25 @@ use std::io::Write;
 use std::path::Path;
 use std::path::PathBuf;
 use std::process::Command;
+use ts_rs::ExportError;
 use ts_rs::TS;
 
 const HEADER: &str = "// GENERATED CODE! DO NOT MODIFY BY HAND!\n\n";
 
+mod python;
+
+pub use python::generate_py;
+
 pub fn generate_ts(out_dir: &Path, prettier: Option<&Path>) -> Result<()> {
     ensure_dir(out_dir)?;
 
     // Generate TS bindings
-    mcp_types::InitializeResult::export_all_to(out_dir)?;
-    codex_protocol::mcp_protocol::ConversationId::export_all_to(out_dir)?;
-    codex_protocol::mcp_protocol::InputItem::export_all_to(out_dir)?;
-    codex_protocol::mcp_protocol::ClientRequest::export_all_to(out_dir)?;
-    codex_protocol::mcp_protocol::ServerRequest::export_all_to(out_dir)?;
-    codex_protocol::mcp_protocol::NewConversationResponse::export_all_to(out_dir)?;
-    codex_protocol::mcp_protocol::ListConversationsResponse::export_all_to(out_dir)?;
-    codex_protocol::mcp_protocol::ResumeConversationResponse::export_all_to(out_dir)?;
-    codex_protocol::mcp_protocol::ArchiveConversationResponse::export_all_to(out_dir)?;
-    codex_protocol::mcp_protocol::AddConversationSubscriptionResponse::export_all_to(out_dir)?;
-    codex_protocol::mcp_protocol::RemoveConversationSubscriptionResponse::export_all_to(out_dir)?;
-    codex_protocol::mcp_protocol::SendUserMessageResponse::export_all_to(out_dir)?;
-    codex_protocol::mcp_protocol::SendUserTurnResponse::export_all_to(out_dir)?;
-    codex_protocol::mcp_protocol::InterruptConversationResponse::export_all_to(out_dir)?;
-    codex_protocol::mcp_protocol::GitDiffToRemoteResponse::export_all_to(out_dir)?;
-    codex_protocol::mcp_protocol::LoginApiKeyParams::export_all_to(out_dir)?;
-    codex_protocol::mcp_protocol::LoginApiKeyResponse::export_all_to(out_dir)?;
-    codex_protocol::mcp_protocol::LoginChatGptResponse::export_all_to(out_dir)?;
-    codex_protocol::mcp_protocol::CancelLoginChatGptResponse::export_all_to(out_dir)?;
-    codex_protocol::mcp_protocol::LogoutChatGptResponse::export_all_to(out_dir)?;
-    codex_protocol::mcp_protocol::GetAuthStatusResponse::export_all_to(out_dir)?;
-    codex_protocol::mcp_protocol::ApplyPatchApprovalResponse::export_all_to(out_dir)?;
-    codex_protocol::mcp_protocol::ExecCommandApprovalResponse::export_all_to(out_dir)?;
-    codex_protocol::mcp_protocol::GetUserSavedConfigResponse::export_all_to(out_dir)?;
-    codex_protocol::mcp_protocol::SetDefaultModelResponse::export_all_to(out_dir)?;
-    codex_protocol::mcp_protocol::GetUserAgentResponse::export_all_to(out_dir)?;
-    codex_protocol::mcp_protocol::UserInfoResponse::export_all_to(out_dir)?;
-
-    // All notification types reachable from this enum will be generated by
-    // induction, so they do not need to be listed individually.
-    codex_protocol::mcp_protocol::ServerNotification::export_all_to(out_dir)?;
+    let mut exporter = TsExporter {
+        out_dir,
+        result: Ok(()),
+    };
+    visit_exported_types(&mut exporter);
+    exporter.result?;
 
     generate_index_ts(out_dir)?;
 
@@ -66,13 +47,137 @@ pub fn generate_ts(out_dir: &Path, prettier: Option<&Path>) -> Result<()> {
             .status()
             .with_context(|| format!("Failed to invoke Prettier at {}", prettier_bin.display()))?;
         if !status.success() {
-            return Err(anyhow!("Prettier failed with status {}", status));
+            return Err(anyhow!("Prettier failed with status {status}"));
         }
     }
 
     Ok(())
 }
 
+/// Receives each root type of the protocol from `visit_exported_types`.
+trait ExportedTypeVisitor {
+    fn visit<T: TS + JsonSchema + 'static>(&mut self);
+}
+
+/// Visits the root types of the protocol. Every type reachable from these is
+/// generated too, so the TypeScript and Python bindings always cover the same
+/// set of types.
+fn visit_exported_types(v: &mut impl ExportedTypeVisitor) {
+    v.visit::<mcp_types::InitializeResult>();
+    v.visit::<codex_protocol::mcp_protocol::ConversationId>();
+    v.visit::<codex_protocol::mcp_protocol::InputItem>();
+    v.visit::<codex_protocol::mcp_protocol::ClientRequest>();
+    v.visit::<codex_protocol::mcp_protocol::ServerRequest>();
+    v.visit::<codex_protocol::mcp_protocol::NewConversationResponse>();
+    v.visit::<codex_protocol::mcp_protocol::ListConversationsResponse>();
+    v.visit::<codex_protocol::mcp_protocol::ResumeConversationResponse>();
+    v.visit::<codex_protocol::mcp_protocol::ArchiveConversationResponse>();
+    v.visit::<codex_protocol::mcp_protocol::ListLoadedConversationsResponse>();
+    v.visit::<codex_protocol::mcp_protocol::SwitchConversationResponse>();
+    v.visit::<codex_protocol::mcp_protocol::AddConversationSubscriptionResponse>();
+    v.visit::<codex_protocol::mcp_protocol::RemoveConversationSubscriptionResponse>();
+    v.visit::<codex_protocol::mcp_protocol::SendUserMessageResponse>();
+    v.visit::<codex_protocol::mcp_protocol::SendUserTurnResponse>();
+    v.visit::<codex_protocol::mcp_protocol::InterruptConversationResponse>();
+    v.visit::<codex_protocol::mcp_protocol::GitDiffToRemoteResponse>();
+    v.visit::<codex_protocol::mcp_protocol::LoginApiKeyParams>();
+    v.visit::<codex_protocol::mcp_protocol::LoginApiKeyResponse>();
+    v.visit::<codex_protocol::mcp_protocol::LoginChatGptResponse>();
+    v.visit::<codex_protocol::mcp_protocol::CancelLoginChatGptResponse>();
+    v.visit::<codex_protocol::mcp_protocol::LogoutChatGptResponse>();
+    v.visit::<codex_protocol::mcp_protocol::GetAuthStatusResponse>();
+    v.visit::<codex_protocol::mcp_protocol::ApplyPatchApprovalResponse>();
+    v.visit::<codex_protocol::mcp_protocol::ExecCommandApprovalResponse>();
+    v.visit::<codex_protocol::mcp_protocol::GetUserSavedConfigResponse>();
+    v.visit::<codex_protocol::mcp_protocol::SetDefaultModelResponse>();
+    v.visit::<codex_protocol::mcp_protocol::GetUserAgentResponse>();
+    v.visit::<codex_protocol::mcp_protocol::UserInfoResponse>();
+
+    // All notification types reachable from this enum will be generated by
+    // induction, so they do not need to be listed individually.
+    v.visit::<codex_protocol::mcp_protocol::ServerNotification>();
+
+    // The submission and event queues used by SDKs that drive the agent
+    // directly.
+    v.visit::<codex_protocol::protocol::Submission>();
+    v.visit::<codex_protocol::protocol::Event>();
+
+    // The JSON Schema of an internally tagged enum inlines the payload of each
+    // newtype variant, so `EventMsg` payloads are listed to keep a definition
+    // of their own in the Python bindings.
+    v.visit::<codex_protocol::protocol::AgentMessageDeltaEvent>();
+    v.visit::<codex_protocol::protocol::AgentMessageEvent>();
+    v.visit::<codex_protocol::protocol::AgentReasoningDeltaEvent>();
+    v.visit::<codex_protocol::protocol::AgentReasoningEvent>();
+    v.visit::<codex_protocol::protocol::AgentReasoningRawContentDeltaEvent>();
+    v.visit::<codex_protocol::protocol::AgentReasoningRawContentEvent>();
+    v.visit::<codex_protocol::protocol::AgentReasoningSectionBreakEvent>();
+    v.visit::<codex_protocol::protocol::ApplyPatchApprovalRequestEvent>();
+    v.visit::<codex_protocol::protocol::AutoCompactEvent>();
+    v.visit::<codex_protocol::protocol::BackgroundEventEvent>();
+    v.visit::<codex_protocol::protocol::CheckpointCreatedEvent>();
+    v.visit::<codex_protocol::protocol::CheckpointRewoundEvent>();
+    v.visit::<codex_protocol::protocol::ConversationPathResponseEvent>();
+    v.visit::<codex_protocol::protocol::ErrorEvent>();
+    v.visit::<codex_protocol::protocol::EscalationRequestEvent>();
+    v.visit::<codex_protocol::protocol::ExecApprovalRequestEvent>();
+    v.visit::<codex_protocol::protocol::ExecCommandBeginEvent>();
+    v.visit::<codex_protocol::protocol::ExecCommandDryRunEvent>();
+    v.visit::<codex_protocol::protocol::ExecCommandEndEvent>();
+    v.visit::<codex_protocol::protocol::ExecCommandOutputDeltaEvent>();
+    v.visit::<codex_protocol::protocol::ExecSessionKilledEvent>();
+    v.visit::<codex_protocol::protocol::ExitedReviewModeEvent>();
+    v.visit::<codex_protocol::protocol::GetHistoryEntryResponseEvent>();
+    v.visit::<codex_protocol::protocol::HookBeginEvent>();
+    v.visit::<codex_protocol::protocol::HookEndEvent>();
+    v.visit::<codex_protocol::protocol::ListCustomPromptsResponseEvent>();
+    v.visit::<codex_protocol::protocol::ListSnapshotsResponseEvent>();
+    v.visit::<codex_protocol::protocol::McpListResourcesResponseEvent>();
+    v.visit::<codex_protocol::protocol::McpListToolsResponseEvent>();
+    v.visit::<codex_protocol::protocol::McpNotificationEvent>();
+    v.visit::<codex_protocol::protocol::McpToolCallBeginEvent>();
+    v.visit::<codex_protocol::protocol::McpToolCallEndEvent>();
+    v.visit::<codex_protocol::protocol::NextPromptPreviewEvent>();
+    v.visit::<codex_protocol::protocol::PatchApplyBeginEvent>();
+    v.visit::<codex_protocol::protocol::PatchApplyEndEvent>();
+    v.visit::<codex_protocol::protocol::PermissionConsentRequestEvent>();
+    v.visit::<codex_protocol::protocol::SessionConfiguredEvent>();
+    v.visit::<codex_protocol::protocol::SessionEnvResponseEvent>();
+    v.visit::<codex_protocol::protocol::SessionRemovedEvent>();
+    v.visit::<codex_protocol::protocol::SnapshotDiffEvent>();
+    v.visit::<codex_protocol::protocol::SpendLimitApprovalRequestEvent>();
+    v.visit::<codex_protocol::protocol::StreamErrorEvent>();
+    v.visit::<codex_protocol::protocol::SubAgentBeginEvent>();
+    v.visit::<codex_protocol::protocol::SubAgentEndEvent>();
+    v.visit::<codex_protocol::protocol::TaskCompleteEvent>();
+    v.visit::<codex_protocol::protocol::TaskQueueUpdatedEvent>();
+    v.visit::<codex_protocol::protocol::TaskStalledEvent>();
+    v.visit::<codex_protocol::protocol::TaskStartedEvent>();
+    v.visit::<codex_protocol::protocol::TokenCountEvent>();
+    v.visit::<codex_protocol::protocol::ToolCallCancelledEvent>();
+    v.visit::<codex_protocol::protocol::TurnAbortedEvent>();
+    v.visit::<codex_protocol::protocol::TurnDiffEvent>();
+    v.visit::<codex_protocol::protocol::UndoCompletedEvent>();
+    v.visit::<codex_protocol::plan_tool::UpdatePlanArgs>();
+    v.visit::<codex_protocol::protocol::UserMessageEvent>();
+    v.visit::<codex_protocol::protocol::WarningEvent>();
+    v.visit::<codex_protocol::protocol::WebSearchBeginEvent>();
+    v.visit::<codex_protocol::protocol::WebSearchEndEvent>();
+}
+
+struct TsExporter<'a> {
+    out_dir: &'a Path,
+    result: std::result::Result<(), ExportError>,
+}
+
+impl ExportedTypeVisitor for TsExporter<'_> {
+    fn visit<T: TS + JsonSchema + 'static>(&mut self) {
+        if self.result.is_ok() {
+            self.result = T::export_all_to(self.out_dir);
+        }
+    }
+}
+
 fn ensure_dir(dir: &Path) -> Result<()> {
     fs::create_dir_all(dir)
         .with_context(|| format!("Failed to create output directory {}", dir.display()))
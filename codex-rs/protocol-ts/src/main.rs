@@ -1,20 +1,35 @@
 use anyhow::Result;
 use clap::Parser;
+use clap::ValueEnum;
 use std::path::PathBuf;
 
 #[derive(Parser, Debug)]
-#[command(about = "Generate TypeScript bindings for the Codex protocol")]
+#[command(about = "Generate TypeScript or Python bindings for the Codex protocol")]
 struct Args {
-    /// Output directory where .ts files will be written
+    /// Output directory where the generated files will be written
     #[arg(short = 'o', long = "out", value_name = "DIR")]
     out_dir: PathBuf,
 
+    /// Language of the generated bindings
+    #[arg(long = "lang", value_enum, default_value_t = Lang::Ts)]
+    lang: Lang,
+
     /// Optional path to the Prettier executable to format generated files
+    /// (TypeScript only)
     #[arg(short = 'p', long = "prettier", value_name = "PRETTIER_BIN")]
     prettier: Option<PathBuf>,
 }
 
+#[derive(Clone, Copy, Debug, ValueEnum)]
+enum Lang {
+    Ts,
+    Py,
+}
+
 fn main() -> Result<()> {
     let args = Args::parse();
-    codex_protocol_ts::generate_ts(&args.out_dir, args.prettier.as_deref())
+    match args.lang {
+        Lang::Ts => codex_protocol_ts::generate_ts(&args.out_dir, args.prettier.as_deref()),
+        Lang::Py => codex_protocol_ts::generate_py(&args.out_dir),
+    }
 }
@@ -0,0 +1,819 @@
+//! Python bindings for the protocol.
+//!
+//! The Rust types derive `JsonSchema` next to `TS`, so the Python module is
+//! generated from the JSON Schema schemars produces for the same root types
+//! as the TypeScript bindings. Objects become `TypedDict`s, and enums and
+//! other unions become type aliases built from `Literal`s and `|`.
+
+use anyhow::Context;
+use anyhow::Result;
+use schemars::JsonSchema;
+use schemars::r#gen::SchemaGenerator;
+use schemars::r#gen::SchemaSettings;
+use schemars::schema::InstanceType;
+use schemars::schema::Schema;
+use schemars::schema::SchemaObject;
+use schemars::schema::SingleOrVec;
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::Path;
+use ts_rs::TS;
+
+use crate::ExportedTypeVisitor;
+use crate::ensure_dir;
+use crate::visit_exported_types;
+
+const PY_FILE_NAME: &str = "codex_protocol.py";
+
+const PY_HEADER: &str = r#"# GENERATED CODE! DO NOT MODIFY BY HAND!
+"""Types for the Codex protocol, generated from its Rust definitions.
+
+Requires Python 3.11 or newer.
+"""
+
+from __future__ import annotations
+
+from typing import Any, Literal, NotRequired, TypeAlias, TypedDict
+"#;
+
+const DEFINITIONS_PREFIX: &str = "#/definitions/";
+
+/// Writes `codex_protocol.py` with a `TypedDict` or type alias for every type
+/// the TypeScript bindings contain.
+pub fn generate_py(out_dir: &Path) -> Result<()> {
+    ensure_dir(out_dir)?;
+
+    let mut collector = SchemaCollector::new();
+    visit_exported_types(&mut collector);
+
+    let source = render_module(collector.generator.definitions())?;
+    let path = out_dir.join(PY_FILE_NAME);
+    fs::write(&path, source).with_context(|| format!("Failed to write {}", path.display()))
+}
+
+/// Collects the schema definitions of every type reachable from the roots.
+struct SchemaCollector {
+    generator: SchemaGenerator,
+}
+
+impl SchemaCollector {
+    fn new() -> Self {
+        Self {
+            generator: SchemaSettings::draft07().into_generator(),
+        }
+    }
+}
+
+impl ExportedTypeVisitor for SchemaCollector {
+    fn visit<T: TS + JsonSchema + 'static>(&mut self) {
+        self.generator.subschema_for::<T>();
+    }
+}
+
+fn render_module(definitions: &schemars::Map<String, Schema>) -> Result<String> {
+    let mut decls = BTreeMap::new();
+    let mut docs = BTreeMap::new();
+    for (name, schema) in definitions {
+        let shape =
+            convert(schema).with_context(|| format!("Failed to convert the schema of {name}"))?;
+        decls.insert(name.clone(), shape);
+        if let Some(description) = description(schema) {
+            docs.insert(name.clone(), description);
+        }
+    }
+
+    let mut emitter = Emitter {
+        decls: &decls,
+        taken: decls.keys().cloned().collect(),
+        items: Vec::new(),
+    };
+    for (name, shape) in &decls {
+        emitter.emit_decl(name, docs.get(name).cloned(), shape);
+    }
+
+    // Class annotations are evaluated lazily but alias values are not, so
+    // aliases come after the classes and after the aliases they use.
+    let (aliases, classes): (Vec<PyItem>, Vec<PyItem>) = emitter
+        .items
+        .into_iter()
+        .partition(|item| matches!(item, PyItem::Alias { .. }));
+    let mut out = String::from(PY_HEADER);
+    for item in classes.iter().chain(order_aliases(aliases).iter()) {
+        out.push_str("\n\n");
+        out.push_str(&item.render());
+    }
+    Ok(out)
+}
+
+/// Sorts aliases so each is defined before it is used. References that close
+/// a cycle (such as `JsonValue` containing itself) become string forward
+/// references.
+fn order_aliases(aliases: Vec<PyItem>) -> Vec<PyItem> {
+    let mut names = Vec::new();
+    let mut by_name = BTreeMap::new();
+    for item in aliases {
+        if let PyItem::Alias { name, docs, expr } = item {
+            names.push(name.clone());
+            by_name.insert(name, (docs, expr));
+        }
+    }
+
+    fn visit(
+        name: &str,
+        by_name: &BTreeMap<String, (Option<String>, String)>,
+        visiting: &mut BTreeSet<String>,
+        done: &mut BTreeSet<String>,
+        ordered: &mut Vec<PyItem>,
+    ) {
+        if done.contains(name) || !visiting.insert(name.to_string()) {
+            return;
+        }
+        let Some((docs, expr)) = by_name.get(name) else {
+            return;
+        };
+        map_identifiers(expr, |ident| {
+            if by_name.contains_key(ident) {
+                visit(ident, by_name, visiting, done, ordered);
+            }
+            ident.to_string()
+        });
+        let expr = map_identifiers(expr, |ident| {
+            if by_name.contains_key(ident) && !done.contains(ident) {
+                format!("\"{ident}\"")
+            } else {
+                ident.to_string()
+            }
+        });
+        done.insert(name.to_string());
+        ordered.push(PyItem::Alias {
+            name: name.to_string(),
+            docs: docs.clone(),
+            expr,
+        });
+    }
+
+    let mut visiting = BTreeSet::new();
+    let mut done = BTreeSet::new();
+    let mut ordered = Vec::new();
+    for name in &names {
+        visit(name, &by_name, &mut visiting, &mut done, &mut ordered);
+    }
+    ordered
+}
+
+/// Rewrites every identifier in a Python type expression, leaving string
+/// literals untouched.
+fn map_identifiers(expr: &str, mut f: impl FnMut(&str) -> String) -> String {
+    let mut out = String::with_capacity(expr.len());
+    let mut ident = String::new();
+    let mut in_literal = false;
+    let mut escaped = false;
+    for c in expr.chars() {
+        if in_literal {
+            out.push(c);
+            match c {
+                _ if escaped => escaped = false,
+                '\\' => escaped = true,
+                '\'' => in_literal = false,
+                _ => {}
+            }
+        } else if c.is_ascii_alphanumeric() || c == '_' {
+            ident.push(c);
+        } else {
+            if !ident.is_empty() {
+                out.push_str(&f(&ident));
+                ident.clear();
+            }
+            in_literal = c == '\'';
+            out.push(c);
+        }
+    }
+    if !ident.is_empty() {
+        out.push_str(&f(&ident));
+    }
+    out
+}
+
+/// The shapes a schema can describe, in terms Python's typing can express.
+#[derive(Debug, Clone, PartialEq)]
+enum Shape {
+    /// Another definition, by name.
+    Ref(String),
+    /// A builtin such as `str`, or `Any`.
+    Builtin(&'static str),
+    StrLit(String),
+    /// A non-string literal, already spelled the Python way.
+    Lit(String),
+    Null,
+    Array(Box<Shape>),
+    Tuple(Vec<Shape>),
+    Object(Vec<Field>),
+    /// An object with arbitrary keys.
+    Map(Box<Shape>),
+    Union(Vec<Shape>),
+    Intersection(Vec<Shape>),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Field {
+    name: String,
+    optional: bool,
+    shape: Shape,
+    docs: Option<String>,
+}
+
+fn description(schema: &Schema) -> Option<String> {
+    match schema {
+        Schema::Object(object) => object
+            .metadata
+            .as_ref()
+            .and_then(|metadata| metadata.description.clone()),
+        Schema::Bool(_) => None,
+    }
+}
+
+fn convert(schema: &Schema) -> Result<Shape> {
+    match schema {
+        Schema::Bool(true) => Ok(Shape::Builtin("Any")),
+        Schema::Bool(false) => Ok(Shape::Null),
+        Schema::Object(object) => convert_object(object),
+    }
+}
+
+fn convert_object(object: &SchemaObject) -> Result<Shape> {
+    if let Some(reference) = &object.reference {
+        let name = reference
+            .strip_prefix(DEFINITIONS_PREFIX)
+            .with_context(|| format!("unsupported reference {reference}"))?;
+        return Ok(Shape::Ref(name.to_string()));
+    }
+
+    let mut parts = Vec::new();
+    if let Some(value) = &object.const_value {
+        parts.push(literal(value));
+    } else if let Some(values) = &object.enum_values {
+        parts.push(union(values.iter().map(literal).collect()));
+    } else if let Some(types) = &object.instance_type {
+        let types = match types {
+            SingleOrVec::Single(ty) => vec![**ty],
+            SingleOrVec::Vec(types) => types.clone(),
+        };
+        let members = types
+            .into_iter()
+            .map(|ty| convert_instance_type(object, ty))
+            .collect::<Result<Vec<_>>>()?;
+        parts.push(union(members));
+    }
+
+    if let Some(subschemas) = &object.subschemas {
+        for schema in subschemas.all_of.iter().flatten() {
+            parts.push(convert(schema)?);
+        }
+        for alternatives in [&subschemas.one_of, &subschemas.any_of]
+            .into_iter()
+            .flatten()
+        {
+            let members = alternatives
+                .iter()
+                .map(convert)
+                .collect::<Result<Vec<_>>>()?;
+            parts.push(union(members));
+        }
+    }
+
+    Ok(match parts.len() {
+        0 => Shape::Builtin("Any"),
+        _ => intersection(parts),
+    })
+}
+
+fn convert_instance_type(object: &SchemaObject, ty: InstanceType) -> Result<Shape> {
+    Ok(match ty {
+        InstanceType::Null => Shape::Null,
+        InstanceType::Boolean => Shape::Builtin("bool"),
+        InstanceType::Integer => Shape::Builtin("int"),
+        InstanceType::Number => Shape::Builtin("float"),
+        InstanceType::String => Shape::Builtin("str"),
+        InstanceType::Array => match object.array.as_ref().and_then(|array| array.items.as_ref()) {
+            Some(SingleOrVec::Single(item)) => Shape::Array(Box::new(convert(item)?)),
+            Some(SingleOrVec::Vec(items)) => {
+                Shape::Tuple(items.iter().map(convert).collect::<Result<Vec<_>>>()?)
+            }
+            None => Shape::Array(Box::new(Shape::Builtin("Any"))),
+        },
+        InstanceType::Object => {
+            let Some(validation) = &object.object else {
+                return Ok(Shape::Map(Box::new(Shape::Builtin("Any"))));
+            };
+            if validation.properties.is_empty() {
+                match validation.additional_properties.as_deref() {
+                    None | Some(Schema::Bool(false)) => return Ok(Shape::Object(Vec::new())),
+                    Some(value) => return Ok(Shape::Map(Box::new(convert(value)?))),
+                }
+            }
+            let fields = validation
+                .properties
+                .iter()
+                .map(|(name, schema)| {
+                    Ok(Field {
+                        name: name.clone(),
+                        optional: !validation.required.contains(name),
+                        shape: convert(schema)?,
+                        docs: description(schema),
+                    })
+                })
+                .collect::<Result<Vec<_>>>()?;
+            Shape::Object(fields)
+        }
+    })
+}
+
+fn literal(value: &Value) -> Shape {
+    match value {
+        Value::Null => Shape::Null,
+        Value::Bool(true) => Shape::Lit("True".to_string()),
+        Value::Bool(false) => Shape::Lit("False".to_string()),
+        Value::Number(number) => Shape::Lit(number.to_string()),
+        Value::String(value) => Shape::StrLit(value.clone()),
+        Value::Array(_) | Value::Object(_) => Shape::Builtin("Any"),
+    }
+}
+
+/// A union of `members`, flattening nested unions.
+fn union(members: Vec<Shape>) -> Shape {
+    let mut flat = Vec::new();
+    for member in members {
+        match member {
+            Shape::Union(nested) => flat.extend(nested),
+            member => flat.push(member),
+        }
+    }
+    if flat.len() == 1 {
+        flat.remove(0)
+    } else {
+        Shape::Union(flat)
+    }
+}
+
+/// An intersection of `parts`. Intersections distribute over unions, so a
+/// struct with a flattened enum becomes one object per variant.
+fn intersection(parts: Vec<Shape>) -> Shape {
+    let mut parts: Vec<Shape> = parts
+        .into_iter()
+        .flat_map(|part| match part {
+            Shape::Intersection(nested) => nested,
+            part => vec![part],
+        })
+        .collect();
+    let first_union = parts
+        .iter()
+        .enumerate()
+        .find_map(|(index, part)| match part {
+            Shape::Union(members) => Some((index, members.clone())),
+            _ => None,
+        });
+    if let Some((index, members)) = first_union {
+        parts.remove(index);
+        return union(
+            members
+                .into_iter()
+                .map(|member| {
+                    let mut alternative = parts.clone();
+                    alternative.insert(index, member);
+                    intersection(alternative)
+                })
+                .collect(),
+        );
+    }
+    if parts.len() == 1 {
+        parts.remove(0)
+    } else {
+        Shape::Intersection(parts)
+    }
+}
+
+enum PyItem {
+    Class {
+        name: String,
+        docs: Option<String>,
+        fields: Vec<PyField>,
+    },
+    Alias {
+        name: String,
+        docs: Option<String>,
+        expr: String,
+    },
+}
+
+struct PyField {
+    name: String,
+    ty: String,
+    docs: Option<String>,
+}
+
+impl PyItem {
+    fn render(&self) -> String {
+        match self {
+            PyItem::Alias { name, docs, expr } => {
+                format!("{}{name}: TypeAlias = {expr}\n", comment_lines(docs, ""))
+            }
+            PyItem::Class { name, docs, fields }
+                if fields.iter().all(|field| is_py_identifier(&field.name)) =>
+            {
+                let mut out = format!("class {name}(TypedDict):\n");
+                if let Some(docs) = docs {
+                    out.push_str(&format!("    {}\n", docstring(docs)));
+                    if !fields.is_empty() {
+                        out.push('\n');
+                    }
+                } else if fields.is_empty() {
+                    out.push_str("    pass\n");
+                }
+                for field in fields {
+                    out.push_str(&comment_lines(&field.docs, "    "));
+                    out.push_str(&format!("    {}: {}\n", field.name, field.ty));
+                }
+                out
+            }
+            // Keys such as `from` are not valid Python identifiers, so the
+            // class has to use the functional syntax.
+            PyItem::Class { name, docs, fields } => {
+                let mut out = comment_lines(docs, "");
+                out.push_str(&format!("{name} = TypedDict(\n    \"{name}\",\n    {{\n"));
+                for field in fields {
+                    out.push_str(&comment_lines(&field.docs, "        "));
+                    out.push_str(&format!("        \"{}\": \"{}\",\n", field.name, field.ty));
+                }
+                out.push_str("    },\n)\n");
+                out
+            }
+        }
+    }
+}
+
+fn comment_lines(docs: &Option<String>, indent: &str) -> String {
+    docs.iter()
+        .flat_map(|docs| docs.lines())
+        .map(|line| {
+            if line.is_empty() {
+                format!("{indent}#\n")
+            } else {
+                format!("{indent}# {line}\n")
+            }
+        })
+        .collect()
+}
+
+fn docstring(docs: &str) -> String {
+    let escaped = docs.replace('\\', "\\\\").replace("\"\"\"", "\\\"\\\"\\\"");
+    if escaped.contains('\n') {
+        format!("\"\"\"{}\n    \"\"\"", escaped.replace('\n', "\n    "))
+    } else {
+        format!("\"\"\"{escaped}\"\"\"")
+    }
+}
+
+fn is_py_identifier(name: &str) -> bool {
+    const KEYWORDS: &[&str] = &[
+        "False", "None", "True", "and", "as", "assert", "async", "await", "break", "class",
+        "continue", "def", "del", "elif", "else", "except", "finally", "for", "from", "global",
+        "if", "import", "in", "is", "lambda", "nonlocal", "not", "or", "pass", "raise", "return",
+        "try", "while", "with", "yield",
+    ];
+    let mut chars = name.chars();
+    chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+        && !KEYWORDS.contains(&name)
+}
+
+struct Emitter<'a> {
+    decls: &'a BTreeMap<String, Shape>,
+    /// Names already used by a declaration or a generated class.
+    taken: BTreeSet<String>,
+    items: Vec<PyItem>,
+}
+
+impl Emitter<'_> {
+    fn emit_decl(&mut self, name: &str, docs: Option<String>, ty: &Shape) {
+        match ty {
+            Shape::Object(_) | Shape::Intersection(_) if self.object_fields(ty).is_some() => {
+                self.emit_class(name, docs, ty);
+            }
+            _ => {
+                let index = self.reserve_slot();
+                let expr = self.py_type(ty, name);
+                self.items[index] = PyItem::Alias {
+                    name: name.to_string(),
+                    docs,
+                    expr,
+                };
+            }
+        }
+    }
+
+    /// Emits a `TypedDict` for an object or an intersection of objects. The
+    /// caller has checked that `object_fields` succeeds.
+    fn emit_class(&mut self, name: &str, docs: Option<String>, ty: &Shape) {
+        let fields = self.object_fields(ty).unwrap_or_default();
+        let index = self.reserve_slot();
+        let fields = fields
+            .into_iter()
+            .map(|field| {
+                let hint = format!("{name}{}", pascal_case(&field.name));
+                let ty = self.py_type(&field.shape, &hint);
+                PyField {
+                    ty: if field.optional {
+                        format!("NotRequired[{ty}]")
+                    } else {
+                        ty
+                    },
+                    name: field.name,
+                    docs: field.docs,
+                }
+            })
+            .collect();
+        self.items[index] = PyItem::Class {
+            name: name.to_string(),
+            docs,
+            fields,
+        };
+    }
+
+    /// Fields of an object type, with intersections flattened. `None` when
+    /// part of the type is not an object (for example a union).
+    fn object_fields(&self, ty: &Shape) -> Option<Vec<Field>> {
+        match ty {
+            Shape::Object(fields) => Some(fields.clone()),
+            Shape::Intersection(parts) => {
+                let mut fields: Vec<Field> = Vec::new();
+                for part in parts {
+                    for field in self.object_fields(part)? {
+                        fields.retain(|existing| existing.name != field.name);
+                        fields.push(field);
+                    }
+                }
+                Some(fields)
+            }
+            Shape::Ref(name) => self
+                .decls
+                .get(name)
+                .and_then(|decl| self.object_fields(decl)),
+            _ => None,
+        }
+    }
+
+    /// Python type expression for `ty`. Anonymous objects become classes
+    /// named after `hint`.
+    fn py_type(&mut self, ty: &Shape, hint: &str) -> String {
+        match ty {
+            Shape::Ref(name) => name.clone(),
+            Shape::Builtin(name) => (*name).to_string(),
+            Shape::StrLit(value) => format!("Literal[{}]", py_string(value)),
+            Shape::Lit(value) => format!("Literal[{value}]"),
+            Shape::Null => "None".to_string(),
+            Shape::Array(item) => format!("list[{}]", self.py_type(item, hint)),
+            Shape::Tuple(items) if items.is_empty() => "tuple[()]".to_string(),
+            Shape::Tuple(items) => {
+                let items: Vec<String> = items
+                    .iter()
+                    .enumerate()
+                    .map(|(i, item)| self.py_type(item, &format!("{hint}{i}")))
+                    .collect();
+                format!("tuple[{}]", items.join(", "))
+            }
+            Shape::Map(value) => format!("dict[str, {}]", self.py_type(value, hint)),
+            Shape::Union(members) => {
+                let mut out: Vec<String> = Vec::new();
+                for (i, member) in members.iter().enumerate() {
+                    let member_hint = match tag_value(member) {
+                        Some(tag) => format!("{hint}{}", pascal_case(&tag)),
+                        None => format!("{hint}{}", i + 1),
+                    };
+                    let py = self.py_type(member, &member_hint);
+                    if !out.contains(&py) {
+                        out.push(py);
+                    }
+                }
+                out.join(" | ")
+            }
+            Shape::Object(_) | Shape::Intersection(_) => {
+                if self.object_fields(ty).is_none() {
+                    return "dict[str, Any]".to_string();
+                }
+                let name = self.fresh_name(hint);
+                self.emit_class(&name, None, ty);
+                name
+            }
+        }
+    }
+
+    /// Pushes a placeholder so an item lands before the classes generated
+    /// for its nested objects.
+    fn reserve_slot(&mut self) -> usize {
+        self.items.push(PyItem::Alias {
+            name: String::new(),
+            docs: None,
+            expr: String::new(),
+        });
+        self.items.len() - 1
+    }
+
+    fn fresh_name(&mut self, hint: &str) -> String {
+        let mut name = hint.to_string();
+        let mut n = 2;
+        while self.taken.contains(&name) {
+            name = format!("{hint}{n}");
+            n += 1;
+        }
+        self.taken.insert(name.clone());
+        name
+    }
+}
+
+/// The value of the first string-literal field, which serde uses as the tag
+/// of internally and adjacently tagged enums.
+fn tag_value(ty: &Shape) -> Option<String> {
+    let parts = match ty {
+        Shape::Intersection(parts) => parts.as_slice(),
+        ty => std::slice::from_ref(ty),
+    };
+    parts
+        .iter()
+        .filter_map(|part| match part {
+            Shape::Object(fields) => Some(fields),
+            _ => None,
+        })
+        .flatten()
+        .find_map(|field| match &field.shape {
+            Shape::StrLit(value) => Some(value.clone()),
+            _ => None,
+        })
+}
+
+fn pascal_case(value: &str) -> String {
+    value
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|part| !part.is_empty())
+        .map(|part| {
+            let mut chars = part.chars();
+            match chars.next() {
+                Some(first) => first.to_ascii_uppercase().to_string() + chars.as_str(),
+                None => String::new(),
+            }
+        })
+        .collect()
+}
+
+fn py_string(value: &str) -> String {
+    // Functional `TypedDict`s quote their field types with double quotes.
+    format!("'{}'", value.replace('\\', "\\\\").replace('\'', "\\'"))
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(dead_code)]
+
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use std::collections::HashMap;
+
+    /// Renders the definitions reachable from `T`, without the header.
+    fn render<T: JsonSchema>() -> String {
+        let mut generator = SchemaSettings::draft07().into_generator();
+        generator.subschema_for::<T>();
+        let module = render_module(generator.definitions()).expect("render module");
+        module[PY_HEADER.len()..].trim().to_string()
+    }
+
+    #[derive(JsonSchema)]
+    struct ExecCommandEndEvent {
+        /// Identifier of the call.
+        call_id: String,
+        exit_code: i32,
+        writable_roots: Option<Vec<String>>,
+    }
+
+    #[test]
+    fn object_becomes_typed_dict() {
+        assert_eq!(
+            render::<ExecCommandEndEvent>(),
+            "class ExecCommandEndEvent(TypedDict):\n    # Identifier of the call.\n    call_id: str\n    exit_code: int\n    writable_roots: NotRequired[list[str] | None]"
+        );
+    }
+
+    #[derive(JsonSchema)]
+    struct ErrorEvent {
+        message: String,
+    }
+
+    #[derive(JsonSchema)]
+    #[serde(tag = "type", rename_all = "snake_case")]
+    enum EventMsg {
+        Error(ErrorEvent),
+        ShutdownComplete,
+    }
+
+    #[test]
+    fn tagged_union_members_become_classes() {
+        assert_eq!(
+            render::<EventMsg>(),
+            [
+                "class EventMsgError(TypedDict):\n    type: Literal['error']\n    message: str",
+                "class EventMsgShutdownComplete(TypedDict):\n    type: Literal['shutdown_complete']",
+                "EventMsg: TypeAlias = EventMsgError | EventMsgShutdownComplete",
+            ]
+            .join("\n\n\n")
+        );
+    }
+
+    #[derive(JsonSchema)]
+    struct Outer(Option<Inner>);
+
+    #[derive(JsonSchema)]
+    #[serde(rename_all = "snake_case")]
+    enum Inner {
+        A,
+        B,
+    }
+
+    #[derive(JsonSchema)]
+    #[serde(untagged)]
+    enum JsonValue {
+        Number(f64),
+        Array(Vec<JsonValue>),
+        Object(HashMap<String, JsonValue>),
+    }
+
+    #[derive(JsonSchema)]
+    struct Roots {
+        outer: Outer,
+        value: JsonValue,
+    }
+
+    #[test]
+    fn aliases_follow_their_dependencies() {
+        assert_eq!(
+            render::<Roots>(),
+            [
+                "class Roots(TypedDict):\n    outer: Outer\n    value: JsonValue",
+                "Inner: TypeAlias = Literal['a'] | Literal['b']",
+                "JsonValue: TypeAlias = float | list[\"JsonValue\"] | dict[str, \"JsonValue\"]",
+                "Outer: TypeAlias = Inner | None",
+            ]
+            .join("\n\n\n")
+        );
+    }
+
+    #[derive(JsonSchema)]
+    struct SnapshotDiff {
+        from: String,
+        to: Option<String>,
+        map: HashMap<String, f64>,
+    }
+
+    #[test]
+    fn keyword_fields_use_functional_syntax() {
+        assert_eq!(
+            render::<SnapshotDiff>(),
+            "SnapshotDiff = TypedDict(\n    \"SnapshotDiff\",\n    {\n        \"from\": \"str\",\n        \"to\": \"NotRequired[str | None]\",\n        \"map\": \"dict[str, float]\",\n    },\n)"
+        );
+    }
+
+    /// Every type the TypeScript generator writes has a Python definition of
+    /// the same name, so the two bindings cover the same types.
+    #[test]
+    fn every_generated_ts_type_has_a_python_definition() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        crate::generate_ts(dir.path(), None).expect("generate TypeScript");
+        generate_py(dir.path()).expect("generate Python");
+        let python = fs::read_to_string(dir.path().join(PY_FILE_NAME)).expect("read Python");
+
+        let mut checked = 0;
+        for path in crate::ts_files_in(dir.path()).expect("list TypeScript files") {
+            let name = path
+                .file_stem()
+                .and_then(|stem| stem.to_str())
+                .expect("file name")
+                .to_string();
+            if name == "index" {
+                continue;
+            }
+            assert!(
+                [
+                    format!("\nclass {name}("),
+                    format!("\n{name}: TypeAlias = "),
+                    format!("\n{name} = TypedDict("),
+                ]
+                .iter()
+                .any(|definition| python.contains(definition)),
+                "{name} has no Python definition"
+            );
+            checked += 1;
+        }
+        assert!(checked > 0, "no TypeScript files were generated");
+    }
+}
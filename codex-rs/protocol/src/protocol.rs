@@ -2,7 +2,13 @@
 //!
 //! Uses a SQ (Submission Queue) / EQ (Event Queue) pattern to asynchronously communicate
 //! between user and agent.
+//!
+//! Every public item here is part of the wire format that clients depend on,
+//! so the module must stay fully documented.
+
+#![deny(missing_docs)]
 
+use std::collections::BTreeMap;
 use std::collections::HashMap;
 use std::fmt;
 use std::path::Path;
@@ -12,6 +18,7 @@ use std::time::Duration;
 
 use crate::config_types::ReasoningEffort as ReasoningEffortConfig;
 use crate::config_types::ReasoningSummary as ReasoningSummaryConfig;
+use crate::config_types::StallAction;
 use crate::custom_prompts::CustomPrompt;
 use crate::mcp_protocol::ConversationId;
 use crate::message_history::HistoryEntry;
@@ -21,24 +28,32 @@ use crate::num_format::format_with_separators;
 use crate::parse_command::ParsedCommand;
 use crate::plan_tool::UpdatePlanArgs;
 use mcp_types::CallToolResult;
+use mcp_types::LoggingLevel as McpLoggingLevel;
+use mcp_types::Resource as McpResource;
 use mcp_types::Tool as McpTool;
+use schemars::JsonSchema;
 use serde::Deserialize;
 use serde::Serialize;
 use serde_json::Value;
 use serde_with::serde_as;
 use strum_macros::Display;
+use strum_macros::VariantNames;
 use ts_rs::TS;
 
 /// Open/close tags for special user-input blocks. Used across crates to avoid
 /// duplicated hardcoded strings.
 pub const USER_INSTRUCTIONS_OPEN_TAG: &str = "<user_instructions>";
+/// Closing tag matching [`USER_INSTRUCTIONS_OPEN_TAG`].
 pub const USER_INSTRUCTIONS_CLOSE_TAG: &str = "</user_instructions>";
+/// Opening tag of the environment-context block sent to the model.
 pub const ENVIRONMENT_CONTEXT_OPEN_TAG: &str = "<environment_context>";
+/// Closing tag matching [`ENVIRONMENT_CONTEXT_OPEN_TAG`].
 pub const ENVIRONMENT_CONTEXT_CLOSE_TAG: &str = "</environment_context>";
+/// Heading that precedes the user's request in prompts built by Codex.
 pub const USER_MESSAGE_BEGIN: &str = "## My request for Codex:";
 
 /// Submission Queue Entry - requests from user
-#[derive(Debug, Clone, Deserialize, Serialize)]
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema, TS)]
 pub struct Submission {
     /// Unique id for this Submission to correlate with Events
     pub id: String,
@@ -47,21 +62,40 @@ pub struct Submission {
 }
 
 /// Submission operation
-#[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, VariantNames, JsonSchema, TS)]
 #[serde(tag = "type", rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
 #[allow(clippy::large_enum_variant)]
 #[non_exhaustive]
 pub enum Op {
     /// Abort current task.
     /// This server sends [`EventMsg::TurnAborted`] in response.
+    /// Tasks queued with `busy_input = "queue"` are discarded as well.
     Interrupt,
 
+    /// Remove a task queued with `busy_input = "queue"` before it starts.
+    /// The remaining queue is reported via `EventMsg::TaskQueueUpdated`.
+    CancelQueuedTask {
+        /// Id of the `Op::UserInput` submission that queued the task.
+        id: String,
+    },
+
     /// Input from the user
     UserInput {
         /// User input items, see `InputItem`
         items: Vec<InputItem>,
     },
 
+    /// Guidance for the running task. Unlike [`Op::UserInput`], it is never
+    /// queued as a task of its own: it reaches the model at the next tool
+    /// call boundary of the current turn, as a developer message ahead of any
+    /// other pending input. When no turn is running, a `BackgroundEvent` says
+    /// so and a turn is started with the guidance right away.
+    Steer {
+        /// User input items, see `InputItem`
+        items: Vec<InputItem>,
+    },
+
     /// Similar to [`Op::UserInput`], but contains additional context required
     /// for a turn of a [`crate::codex_conversation::CodexConversation`].
     UserTurn {
@@ -88,8 +122,25 @@ pub enum Op {
 
         /// Will only be honored if the model is configured to use reasoning.
         summary: ReasoningSummaryConfig,
-        // The JSON schema to use for the final assistant message
+
+        /// JSON schema the final assistant message must conform to.
         final_output_json_schema: Option<Value>,
+
+        /// Most model requests the task may make. Overrides the configured
+        /// `task_budget.max_turns`.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        max_turns: Option<u32>,
+
+        /// Most tokens the task's model requests may use in total. Overrides
+        /// the configured `task_budget.max_total_tokens`.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        max_total_tokens: Option<u64>,
+
+        /// Most wall-clock time the task may run for. Overrides the
+        /// configured `task_budget.max_duration_sec`.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        #[ts(type = "string | null")]
+        max_duration: Option<Duration>,
     },
 
     /// Override parts of the persistent turn context for subsequent turns.
@@ -110,8 +161,15 @@ pub enum Op {
         #[serde(skip_serializing_if = "Option::is_none")]
         sandbox_policy: Option<SandboxPolicy>,
 
+        /// Name of a configured sandbox preset to switch to. Its sandbox,
+        /// approval and shell environment policies apply unless
+        /// `sandbox_policy` or `approval_policy` is also set.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        sandbox_preset: Option<String>,
+
         /// Updated model slug. When set, the model family is derived
-        /// automatically.
+        /// automatically. May also name a configured model alias, which
+        /// supplies the provider and effort unless `effort` is set.
         #[serde(skip_serializing_if = "Option::is_none")]
         model: Option<String>,
 
@@ -125,6 +183,13 @@ pub enum Op {
         /// Updated reasoning summary preference (honored only for reasoning-capable models).
         #[serde(skip_serializing_if = "Option::is_none")]
         summary: Option<ReasoningSummaryConfig>,
+
+        /// Turn dry-run mode on or off. In dry-run mode shell commands are
+        /// not executed: each one is reported with
+        /// `EventMsg::ExecCommandDryRun` and the model is told it did not
+        /// run.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        dry_run: Option<bool>,
     },
 
     /// Approve a command execution
@@ -141,6 +206,11 @@ pub enum Op {
         id: String,
         /// The user's decision in response to the request.
         decision: ReviewDecision,
+        /// With an approving decision, apply only the changes to these files
+        /// (paths as listed in the request's `changes`). The rest of the patch
+        /// is skipped and the model is told which files were left out.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        approved_files: Option<Vec<PathBuf>>,
     },
 
     /// Append an entry to the persistent cross-session message history.
@@ -153,7 +223,13 @@ pub enum Op {
     },
 
     /// Request a single history entry identified by `log_id` + `offset`.
-    GetHistoryEntryRequest { offset: usize, log_id: u64 },
+    /// Reply is delivered via `EventMsg::GetHistoryEntryResponse`.
+    GetHistoryEntryRequest {
+        /// Index of the entry in the history log.
+        offset: usize,
+        /// Identifier of the history log, from `SessionConfiguredEvent`.
+        log_id: u64,
+    },
 
     /// Request the full in-memory conversation transcript for the current session.
     /// Reply is delivered via `EventMsg::ConversationHistory`.
@@ -163,24 +239,166 @@ pub enum Op {
     /// Reply is delivered via `EventMsg::McpListToolsResponse`.
     ListMcpTools,
 
+    /// Request the resources offered by the configured MCP servers.
+    /// Reply is delivered via `EventMsg::McpListResourcesResponse`.
+    ListMcpResources,
+
+    /// Start an MCP server for the rest of the session, replacing any server
+    /// already configured under `name`. Reply is delivered via
+    /// `EventMsg::McpListToolsResponse` with the refreshed tool list, or
+    /// `EventMsg::Error` if the server could not be started.
+    AddMcpServer {
+        /// Server name, as used for the `[mcp_servers.<name>]` table.
+        name: String,
+        /// Server definition with the same shape as an `[mcp_servers.<name>]`
+        /// table in `config.toml`.
+        config: Value,
+    },
+
+    /// Stop an MCP server and withdraw its tools for the rest of the session.
+    /// Reply is delivered via `EventMsg::McpListToolsResponse`.
+    RemoveMcpServer {
+        /// Name of the server to remove.
+        name: String,
+    },
+
     /// Request the list of available custom prompts.
     ListCustomPrompts,
 
+    /// Set an environment variable for all subsequent commands in this
+    /// session. Takes precedence over `shell_environment_policy`.
+    ///
+    /// Sensitive names (credentials, `PATH`, dynamic loader variables) are
+    /// rejected unless `approved` is set, which clients should only do after
+    /// the user has explicitly confirmed the change.
+    SetSessionEnv {
+        /// Variable name.
+        key: String,
+        /// Value to set.
+        value: String,
+        /// Set once the user has confirmed setting a sensitive variable.
+        #[serde(default)]
+        approved: bool,
+    },
+
+    /// Remove an environment variable from the environment of all subsequent
+    /// commands in this session, whether it was inherited or set earlier.
+    UnsetSessionEnv {
+        /// Variable name.
+        key: String,
+    },
+
+    /// Request the effective environment that commands run by the agent will
+    /// see. Reply is delivered via `EventMsg::SessionEnvResponse`.
+    GetSessionEnv,
+
+    /// Request a preview of what would be sent to the model if the next turn
+    /// started now, split into sections with estimated token counts. Reply
+    /// is delivered via `EventMsg::NextPromptPreview`.
+    #[cfg(feature = "unstable")]
+    GetNextPromptPreview,
+
+    /// Restore the working tree to the ghost commit recorded before the most
+    /// recent patch or write-capable command. Reply is delivered via
+    /// `EventMsg::UndoCompleted`.
+    UndoLastChange,
+
+    /// Request the ghost commits recorded during this session, oldest first.
+    /// Reply is delivered via `EventMsg::ListSnapshotsResponse`.
+    ListSnapshots,
+
+    /// Request a unified diff between two ghost commits. Reply is delivered
+    /// via `EventMsg::SnapshotDiff`.
+    SnapshotDiff {
+        /// Snapshot to diff from; defaults to the first snapshot taken by the
+        /// most recent task that took one.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        from: Option<String>,
+        /// Snapshot to diff to; defaults to the current working tree.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        to: Option<String>,
+    },
+
+    /// Save the conversation history, and optionally the working tree, so the
+    /// session can later go back to this point with `Op::RewindTo`. Reply is
+    /// delivered via `EventMsg::CheckpointCreated`.
+    Checkpoint {
+        /// Free-form text echoed back in the events, e.g. the user message
+        /// about to be sent.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        label: Option<String>,
+        /// Also record a ghost commit of the working tree, so rewinding
+        /// restores the files as well.
+        #[serde(default)]
+        include_workspace: bool,
+    },
+
+    /// Restore the conversation history (and the working tree, if it was
+    /// saved) recorded by an earlier `Op::Checkpoint`. That checkpoint and
+    /// every later one are discarded. Reply is delivered via
+    /// `EventMsg::CheckpointRewound`.
+    RewindTo {
+        /// The id reported by `EventMsg::CheckpointCreated`.
+        checkpoint_id: String,
+    },
+
+    /// Kill a process started with the `exec_command` tool, such as a hung
+    /// command or a background job, without aborting the turn. Reply is
+    /// delivered via `EventMsg::ExecSessionKilled`.
+    KillExecSession {
+        /// The session id reported to the model when the process started.
+        session_id: u32,
+    },
+
+    /// Cancel one running tool call (a shell command or MCP tool call)
+    /// without aborting the turn. The model is told the call was aborted by
+    /// the user and continues. Reply is delivered via
+    /// `EventMsg::ToolCallCancelled`.
+    CancelToolCall {
+        /// The `call_id` of the running tool call.
+        call_id: String,
+    },
+
     /// Request the agent to summarize the current conversation context.
     /// The agent will use its existing context (either conversation history or previous response id)
     /// to generate a summary which will be returned as an AgentMessage event.
     Compact,
 
     /// Request a code review from the agent.
-    Review { review_request: ReviewRequest },
+    Review {
+        /// What to review and how to present it.
+        review_request: ReviewRequest,
+    },
 
     /// Request to shut down codex instance.
     Shutdown,
+
+    /// Shut down and move this session's rollout file to `archived_sessions`.
+    /// Reply is `EventMsg::SessionRemoved` followed by `ShutdownComplete`.
+    ArchiveSession,
+
+    /// Shut down and move this session's rollout file to the trash, where it
+    /// can be restored until it is purged. Reply is `EventMsg::SessionRemoved`
+    /// followed by `ShutdownComplete`.
+    DeleteSession,
 }
 
 /// Determines the conditions under which the user is consulted to approve
 /// running the command proposed by Codex.
-#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize, Display, TS)]
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    Default,
+    PartialEq,
+    Eq,
+    Hash,
+    Serialize,
+    Deserialize,
+    Display,
+    JsonSchema,
+    TS,
+)]
 #[serde(rename_all = "kebab-case")]
 #[strum(serialize_all = "kebab-case")]
 pub enum AskForApproval {
@@ -207,7 +425,7 @@ pub enum AskForApproval {
 }
 
 /// Determines execution restrictions for model shell commands.
-#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Display, TS)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Display, JsonSchema, TS)]
 #[strum(serialize_all = "kebab-case")]
 #[serde(tag = "mode", rename_all = "kebab-case")]
 pub enum SandboxPolicy {
@@ -233,6 +451,12 @@ pub enum SandboxPolicy {
         #[serde(default)]
         network_access: bool,
 
+        /// When `network_access` is `false`, hosts that commands may still
+        /// reach through Codex's filtering proxy. An entry matches the domain
+        /// itself and all of its subdomains.
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        allowed_domains: Vec<String>,
+
         /// When set to `true`, will NOT include the per-user `TMPDIR`
         /// environment variable among the default writable roots. Defaults to
         /// `false`.
@@ -260,6 +484,7 @@ pub struct WritableRoot {
 }
 
 impl WritableRoot {
+    /// Whether `path` is under `root` and outside every read-only subpath.
     pub fn is_path_writable(&self, path: &Path) -> bool {
         // Check if the path is under the root.
         if !path.starts_with(&self.root) {
@@ -298,6 +523,7 @@ impl SandboxPolicy {
         SandboxPolicy::WorkspaceWrite {
             writable_roots: vec![],
             network_access: false,
+            allowed_domains: vec![],
             exclude_tmpdir_env_var: false,
             exclude_slash_tmp: false,
         }
@@ -308,6 +534,7 @@ impl SandboxPolicy {
         true
     }
 
+    /// Whether commands may write anywhere on disk.
     pub fn has_full_disk_write_access(&self) -> bool {
         match self {
             SandboxPolicy::DangerFullAccess => true,
@@ -316,6 +543,7 @@ impl SandboxPolicy {
         }
     }
 
+    /// Whether commands have unrestricted outbound network access.
     pub fn has_full_network_access(&self) -> bool {
         match self {
             SandboxPolicy::DangerFullAccess => true,
@@ -324,6 +552,32 @@ impl SandboxPolicy {
         }
     }
 
+    /// Domains reachable through the filtering proxy. Empty unless network
+    /// access is otherwise disabled.
+    pub fn allowed_domains(&self) -> &[String] {
+        match self {
+            SandboxPolicy::WorkspaceWrite {
+                network_access: false,
+                allowed_domains,
+                ..
+            } => allowed_domains,
+            _ => &[],
+        }
+    }
+
+    /// Returns the permissions a command would gain over this policy if it
+    /// were run without a sandbox.
+    pub fn escalated_permissions(&self) -> Vec<EscalatedPermission> {
+        let mut permissions = Vec::new();
+        if !self.has_full_disk_write_access() {
+            permissions.push(EscalatedPermission::FullDiskWrite);
+        }
+        if !self.has_full_network_access() {
+            permissions.push(EscalatedPermission::NetworkAccess);
+        }
+        permissions
+    }
+
     /// Returns the list of writable roots (tailored to the current working
     /// directory) together with subpaths that should remain read‑only under
     /// each writable root.
@@ -336,6 +590,7 @@ impl SandboxPolicy {
                 exclude_tmpdir_env_var,
                 exclude_slash_tmp,
                 network_access: _,
+                allowed_domains: _,
             } => {
                 // Start from explicitly configured writable roots.
                 let mut roots: Vec<PathBuf> = writable_roots.clone();
@@ -389,26 +644,32 @@ impl SandboxPolicy {
 
 /// User input
 #[non_exhaustive]
-#[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, JsonSchema, TS)]
 #[serde(tag = "type", rename_all = "snake_case")]
+#[schemars(rename = "OpInputItem")]
+#[ts(rename = "OpInputItem")]
 pub enum InputItem {
+    /// Text typed by the user.
     Text {
+        /// The text itself.
         text: String,
     },
     /// Pre‑encoded data: URI image.
     Image {
+        /// `data:` URL of the image.
         image_url: String,
     },
 
     /// Local image path provided by the user.  This will be converted to an
     /// `Image` variant (base64 data URL) during request serialization.
     LocalImage {
+        /// Path to the image file.
         path: std::path::PathBuf,
     },
 }
 
 /// Event Queue Entry - events from agent
-#[derive(Debug, Clone, Deserialize, Serialize)]
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema, TS)]
 pub struct Event {
     /// Submission `id` that this event is correlated with.
     pub id: String,
@@ -418,19 +679,34 @@ pub struct Event {
 
 /// Response event from the agent
 /// NOTE: Make sure none of these values have optional types, as it will mess up the extension code-gen.
-#[derive(Debug, Clone, Deserialize, Serialize, Display, TS)]
+#[derive(Debug, Clone, Deserialize, Serialize, Display, VariantNames, JsonSchema, TS)]
 #[serde(tag = "type", rename_all = "snake_case")]
 #[strum(serialize_all = "snake_case")]
 pub enum EventMsg {
     /// Error while executing a submission
     Error(ErrorEvent),
 
+    /// Something degraded that the user should know about, although the
+    /// session keeps working (e.g., low disk space for session storage).
+    Warning(WarningEvent),
+
     /// Agent has started a task
     TaskStarted(TaskStartedEvent),
 
     /// Agent has completed all actions
     TaskComplete(TaskCompleteEvent),
 
+    /// The task kept repeating the same tool calls without making progress.
+    TaskStalled(TaskStalledEvent),
+
+    /// The tasks waiting to run after the current one changed.
+    TaskQueueUpdated(TaskQueueUpdatedEvent),
+
+    /// The conversation used more of the context window than the
+    /// auto-compaction limit allows, so the history is being summarized
+    /// before the task continues.
+    AutoCompact(AutoCompactEvent),
+
     /// Usage update for the current session, including totals and last turn.
     /// Optional means unknown — UIs should not display when `None`.
     TokenCount(TokenCountEvent),
@@ -461,12 +737,19 @@ pub enum EventMsg {
     /// Ack the client's configure message.
     SessionConfigured(SessionConfiguredEvent),
 
+    /// Notification that an MCP tool call is about to start.
     McpToolCallBegin(McpToolCallBeginEvent),
 
+    /// Notification that an MCP tool call finished.
     McpToolCallEnd(McpToolCallEndEvent),
 
+    /// Progress or log message sent by an MCP server.
+    McpNotification(McpNotificationEvent),
+
+    /// Notification that the model started a web search.
     WebSearchBegin(WebSearchBeginEvent),
 
+    /// Notification that a web search finished.
     WebSearchEnd(WebSearchEndEvent),
 
     /// Notification that the server is about to execute a command.
@@ -475,12 +758,35 @@ pub enum EventMsg {
     /// Incremental chunk of output from a running command.
     ExecCommandOutputDelta(ExecCommandOutputDeltaEvent),
 
+    /// Notification that a command finished.
     ExecCommandEnd(ExecCommandEndEvent),
 
+    /// A command the model asked to run while the session is in dry-run
+    /// mode. It was not executed.
+    ExecCommandDryRun(ExecCommandDryRunEvent),
+
+    /// Ask the user to approve running a command. Answered with
+    /// `Op::ExecApproval`.
     ExecApprovalRequest(ExecApprovalRequestEvent),
 
+    /// Ask the user to approve applying a patch. Answered with
+    /// `Op::PatchApproval`.
     ApplyPatchApprovalRequest(ApplyPatchApprovalRequestEvent),
 
+    /// The model asked to run a command with escalated permissions (outside
+    /// the sandbox). Answered with `Op::ExecApproval`.
+    EscalationRequest(EscalationRequestEvent),
+
+    /// Ask the user, once per project, whether the agent may use a kind of
+    /// capability for the first time. Answered with `Op::ExecApproval`.
+    PermissionConsentRequest(PermissionConsentRequestEvent),
+
+    /// Ask the user whether to keep going once the estimated cost of the
+    /// session reached `max_session_cost_usd`. Answered with
+    /// `Op::ExecApproval`.
+    SpendLimitApprovalRequest(SpendLimitApprovalRequestEvent),
+
+    /// Informational message that is not part of the conversation.
     BackgroundEvent(BackgroundEventEvent),
 
     /// Notification that a model stream experienced an error or disconnect
@@ -494,6 +800,21 @@ pub enum EventMsg {
     /// Notification that a patch application has finished.
     PatchApplyEnd(PatchApplyEndEvent),
 
+    /// Notification that a user-configured hook started running. The turn
+    /// waits for it until the matching `HookEnd`.
+    HookBegin(HookBeginEvent),
+
+    /// Notification that a hook finished, failed to start or timed out.
+    HookEnd(HookEndEvent),
+
+    /// The model delegated a subtask to a child agent with the `delegate`
+    /// tool. Events from the child carry the `agent_id` as their `id`.
+    SubAgentBegin(SubAgentBeginEvent),
+
+    /// A child agent finished; its final message is the `delegate` output.
+    SubAgentEnd(SubAgentEndEvent),
+
+    /// Unified diff of all changes made during the turn so far.
     TurnDiff(TurnDiffEvent),
 
     /// Response to GetHistoryEntryRequest.
@@ -502,18 +823,55 @@ pub enum EventMsg {
     /// List of MCP tools available to the agent.
     McpListToolsResponse(McpListToolsResponseEvent),
 
+    /// List of MCP resources the agent can read.
+    McpListResourcesResponse(McpListResourcesResponseEvent),
+
     /// List of custom prompts available to the agent.
     ListCustomPromptsResponse(ListCustomPromptsResponseEvent),
 
+    /// Response to `Op::GetSessionEnv`.
+    SessionEnvResponse(SessionEnvResponseEvent),
+
+    /// Response to `Op::GetNextPromptPreview`.
+    #[cfg(feature = "unstable")]
+    NextPromptPreview(NextPromptPreviewEvent),
+
+    /// Response to `Op::UndoLastChange`.
+    UndoCompleted(UndoCompletedEvent),
+
+    /// Response to `Op::ListSnapshots`.
+    ListSnapshotsResponse(ListSnapshotsResponseEvent),
+
+    /// Response to `Op::SnapshotDiff`.
+    SnapshotDiff(SnapshotDiffEvent),
+
+    /// Response to `Op::Checkpoint`.
+    CheckpointCreated(CheckpointCreatedEvent),
+
+    /// Response to `Op::RewindTo`.
+    CheckpointRewound(CheckpointRewoundEvent),
+
+    /// The agent's updated plan for the task.
     PlanUpdate(UpdatePlanArgs),
 
+    /// The task ended before completing; see `reason`.
     TurnAborted(TurnAbortedEvent),
 
     /// Notification that the agent is shutting down.
     ShutdownComplete,
 
+    /// Response to `Op::GetPath`.
     ConversationPath(ConversationPathResponseEvent),
 
+    /// Response to `Op::KillExecSession`.
+    ExecSessionKilled(ExecSessionKilledEvent),
+
+    /// Response to `Op::CancelToolCall`.
+    ToolCallCancelled(ToolCallCancelledEvent),
+
+    /// Response to `Op::ArchiveSession` / `Op::DeleteSession`.
+    SessionRemoved(SessionRemovedEvent),
+
     /// Entered review mode.
     EnteredReviewMode(ReviewRequest),
 
@@ -521,45 +879,183 @@ pub enum EventMsg {
     ExitedReviewMode(ExitedReviewModeEvent),
 }
 
-#[derive(Debug, Clone, Deserialize, Serialize, TS)]
+/// Payload of `EventMsg::ExitedReviewMode`.
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema, TS)]
 pub struct ExitedReviewModeEvent {
+    /// Result of the review, or `None` if it was interrupted.
     pub review_output: Option<ReviewOutputEvent>,
 }
 
 // Individual event payload types matching each `EventMsg` variant.
 
-#[derive(Debug, Clone, Deserialize, Serialize, TS)]
+/// Payload of `EventMsg::Error`.
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema, TS)]
 pub struct ErrorEvent {
+    /// Human-readable description of the error.
+    pub message: String,
+}
+
+/// Payload of `EventMsg::Warning`.
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema, TS)]
+pub struct WarningEvent {
+    /// Human-readable description of the problem.
     pub message: String,
 }
 
-#[derive(Debug, Clone, Deserialize, Serialize, TS)]
+/// Payload of `EventMsg::TaskComplete`.
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema, TS)]
 pub struct TaskCompleteEvent {
+    /// Last message the agent sent in the task, if any.
     pub last_agent_message: Option<String>,
+    /// The model's own assessment of the finished task, when
+    /// `task_completion_assessment` is enabled.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub assessment: Option<CompletionAssessment>,
+    /// Estimated cost of the session so far in USD, when prices for the
+    /// model are known.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub estimated_cost_usd: Option<f64>,
+    /// Why `last_agent_message` does not match the turn's
+    /// `final_output_json_schema`, when it still does not after the model
+    /// was asked to repair it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub output_schema_error: Option<String>,
+}
+
+/// Self-assessment produced by a final structured-output request once a task
+/// has finished.
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, Eq, JsonSchema, TS)]
+pub struct CompletionAssessment {
+    /// How sure the model is that the task was done correctly.
+    pub confidence: CompletionConfidence,
+    /// Whether the result was checked.
+    pub verification: VerificationStatus,
+    /// Suggested next actions the user can start with a single keypress.
+    #[serde(default)]
+    pub follow_ups: Vec<FollowUpSuggestion>,
 }
 
-#[derive(Debug, Clone, Deserialize, Serialize, TS)]
+/// How confident the model is in its own result.
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, PartialEq, Eq, Display, JsonSchema, TS)]
+#[serde(rename_all = "snake_case")]
+#[strum(serialize_all = "lowercase")]
+pub enum CompletionConfidence {
+    /// Likely incomplete or wrong.
+    Low,
+    /// Plausible but unverified in places.
+    Medium,
+    /// Expected to be correct.
+    High,
+}
+
+/// Whether the result of the task was checked, e.g. by running tests.
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, PartialEq, Eq, Display, JsonSchema, TS)]
+#[serde(rename_all = "snake_case")]
+#[strum(serialize_all = "lowercase")]
+pub enum VerificationStatus {
+    /// Checked in full, e.g. the relevant tests pass.
+    Verified,
+    /// Some of the changes were checked.
+    #[strum(serialize = "partially verified")]
+    PartiallyVerified,
+    /// Nothing was checked.
+    Unverified,
+}
+
+/// A follow-up task offered after a task completes.
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, Eq, JsonSchema, TS)]
+pub struct FollowUpSuggestion {
+    /// Short description shown to the user.
+    pub label: String,
+    /// Prompt submitted as the next user message when the follow-up is chosen.
+    pub prompt: String,
+}
+
+/// Payload of `EventMsg::TaskStalled`.
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema, TS)]
+pub struct TaskStalledEvent {
+    /// Number of consecutive turns that repeated earlier tool calls and
+    /// produced the same results, or retried a call that failed again.
+    pub repeated_turns: u32,
+    /// The tool whose call failed with the same arguments in each of those
+    /// turns, whatever the error said, when that is what repeated.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub failing_tool: Option<String>,
+    /// How the session responded to the stall.
+    pub action: StallAction,
+}
+
+/// Payload of `EventMsg::TaskQueueUpdated`.
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema, TS)]
+pub struct TaskQueueUpdatedEvent {
+    /// Tasks waiting to run, in the order they will start.
+    pub tasks: Vec<QueuedTask>,
+}
+
+/// User input queued to run as a task of its own.
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, JsonSchema, TS)]
+pub struct QueuedTask {
+    /// Id of the `Op::UserInput` submission; the task's events carry it.
+    pub id: String,
+    /// The submitted input.
+    pub items: Vec<InputItem>,
+}
+
+/// Payload of `EventMsg::AutoCompact`.
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema, TS)]
+pub struct AutoCompactEvent {
+    /// Tokens of the context window used by the last model request.
+    pub tokens_in_context_window: u64,
+    /// Auto-compaction limit that was reached.
+    pub token_limit: u64,
+    /// Most recent user and assistant messages kept verbatim after the
+    /// summary.
+    pub preserved_messages: u32,
+}
+
+/// Payload of `EventMsg::TaskStarted`.
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema, TS)]
 pub struct TaskStartedEvent {
+    /// Context window of the model used for the task, when known.
     pub model_context_window: Option<u64>,
+    /// Work branch created for this task when `git.auto_branch` is enabled.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub branch: Option<String>,
 }
 
-#[derive(Debug, Clone, Deserialize, Serialize, Default, TS)]
+/// Token counts for one or more model requests.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize, Default, JsonSchema, TS)]
 pub struct TokenUsage {
+    /// Input tokens, including cached ones.
     pub input_tokens: u64,
+    /// Input tokens served from the prompt cache.
     pub cached_input_tokens: u64,
+    /// Output tokens, including reasoning.
     pub output_tokens: u64,
+    /// Output tokens spent on reasoning.
     pub reasoning_output_tokens: u64,
+    /// Total tokens billed.
     pub total_tokens: u64,
 }
 
-#[derive(Debug, Clone, Deserialize, Serialize, TS)]
+/// Cumulative and most recent token usage of a session.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize, JsonSchema, TS)]
 pub struct TokenUsageInfo {
+    /// Usage summed over the whole session.
     pub total_token_usage: TokenUsage,
+    /// Usage of the most recent model request.
     pub last_token_usage: TokenUsage,
+    /// Context window of the model, when known.
     pub model_context_window: Option<u64>,
+    /// Estimated cost of the session in USD, summed over the model requests
+    /// whose prices were known. `None` when none were.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub estimated_cost_usd: Option<f64>,
 }
 
 impl TokenUsageInfo {
+    /// Adds `last` to `info`, starting from zero when there is no `info` yet.
+    /// Returns `None` when both are `None`.
     pub fn new_or_append(
         info: &Option<TokenUsageInfo>,
         last: &Option<TokenUsage>,
@@ -575,6 +1071,7 @@ impl TokenUsageInfo {
                 total_token_usage: TokenUsage::default(),
                 last_token_usage: TokenUsage::default(),
                 model_context_window,
+                estimated_cost_usd: None,
             },
         };
         if let Some(last) = last {
@@ -583,25 +1080,38 @@ impl TokenUsageInfo {
         Some(info)
     }
 
+    /// Adds `last` to the totals and records it as the most recent usage.
     pub fn append_last_usage(&mut self, last: &TokenUsage) {
         self.total_token_usage.add_assign(last);
         self.last_token_usage = last.clone();
     }
+
+    /// Adds `cost_usd` to the estimated cost of the session.
+    pub fn add_cost(&mut self, cost_usd: f64) {
+        self.estimated_cost_usd = Some(self.estimated_cost_usd.unwrap_or_default() + cost_usd);
+    }
 }
 
-#[derive(Debug, Clone, Deserialize, Serialize, TS)]
+/// Payload of `EventMsg::TokenCount`.
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema, TS)]
 pub struct TokenCountEvent {
+    /// Token usage so far; `None` when unknown.
     pub info: Option<TokenUsageInfo>,
+    /// Rate limit usage reported by the provider, when available.
     pub rate_limits: Option<RateLimitSnapshot>,
 }
 
-#[derive(Debug, Clone, Deserialize, Serialize, TS)]
+/// Rate limit usage for the primary and secondary windows.
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema, TS)]
 pub struct RateLimitSnapshot {
+    /// Shorter of the two windows.
     pub primary: Option<RateLimitWindow>,
+    /// Longer of the two windows.
     pub secondary: Option<RateLimitWindow>,
 }
 
-#[derive(Debug, Clone, Deserialize, Serialize, TS)]
+/// Usage of one rate limit window.
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema, TS)]
 pub struct RateLimitWindow {
     /// Percentage (0-100) of the window that has been consumed.
     pub used_percent: f64,
@@ -615,14 +1125,17 @@ pub struct RateLimitWindow {
 const BASELINE_TOKENS: u64 = 12000;
 
 impl TokenUsage {
+    /// Whether no tokens were used.
     pub fn is_zero(&self) -> bool {
         self.total_tokens == 0
     }
 
+    /// Input tokens served from the prompt cache.
     pub fn cached_input(&self) -> u64 {
         self.cached_input_tokens
     }
 
+    /// Input tokens that were not served from the prompt cache.
     pub fn non_cached_input(&self) -> u64 {
         self.input_tokens.saturating_sub(self.cached_input())
     }
@@ -674,8 +1187,10 @@ impl TokenUsage {
     }
 }
 
+/// Token usage printed when a session ends.
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct FinalOutput {
+    /// Usage summed over the session.
     pub token_usage: TokenUsage,
 }
 
@@ -715,12 +1230,15 @@ impl fmt::Display for FinalOutput {
     }
 }
 
-#[derive(Debug, Clone, Deserialize, Serialize, TS)]
+/// Payload of `EventMsg::AgentMessage`.
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema, TS)]
 pub struct AgentMessageEvent {
+    /// The complete message text.
     pub message: String,
 }
 
-#[derive(Debug, Clone, Deserialize, Serialize, TS)]
+/// Kind of a user message, based on the tags it is wrapped in.
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema, TS)]
 #[serde(rename_all = "snake_case")]
 pub enum InputMessageKind {
     /// Plain user text (default)
@@ -731,11 +1249,15 @@ pub enum InputMessageKind {
     EnvironmentContext,
 }
 
-#[derive(Debug, Clone, Deserialize, Serialize, TS)]
+/// Payload of `EventMsg::UserMessage`.
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema, TS)]
 pub struct UserMessageEvent {
+    /// The message text.
     pub message: String,
+    /// What kind of message this is; `None` for plain text.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub kind: Option<InputMessageKind>,
+    /// Images attached to the message, as paths or data URLs.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub images: Option<Vec<String>>,
 }
@@ -783,35 +1305,47 @@ fn ends_with_ignore_ascii_case(text: &str, suffix: &str) -> bool {
             .all(|(a, b)| a.eq_ignore_ascii_case(b))
 }
 
-#[derive(Debug, Clone, Deserialize, Serialize, TS)]
+/// Payload of `EventMsg::AgentMessageDelta`.
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema, TS)]
 pub struct AgentMessageDeltaEvent {
+    /// Text to append to the message being streamed.
     pub delta: String,
 }
 
-#[derive(Debug, Clone, Deserialize, Serialize, TS)]
+/// Payload of `EventMsg::AgentReasoning`.
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema, TS)]
 pub struct AgentReasoningEvent {
+    /// The reasoning summary text.
     pub text: String,
 }
 
-#[derive(Debug, Clone, Deserialize, Serialize, TS)]
+/// Payload of `EventMsg::AgentReasoningRawContent`.
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema, TS)]
 pub struct AgentReasoningRawContentEvent {
+    /// The raw reasoning text.
     pub text: String,
 }
 
-#[derive(Debug, Clone, Deserialize, Serialize, TS)]
+/// Payload of `EventMsg::AgentReasoningRawContentDelta`.
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema, TS)]
 pub struct AgentReasoningRawContentDeltaEvent {
+    /// Text to append to the raw reasoning being streamed.
     pub delta: String,
 }
 
-#[derive(Debug, Clone, Deserialize, Serialize, TS)]
+/// Payload of `EventMsg::AgentReasoningSectionBreak`.
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema, TS)]
 pub struct AgentReasoningSectionBreakEvent {}
 
-#[derive(Debug, Clone, Deserialize, Serialize, TS)]
+/// Payload of `EventMsg::AgentReasoningDelta`.
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema, TS)]
 pub struct AgentReasoningDeltaEvent {
+    /// Text to append to the reasoning summary being streamed.
     pub delta: String,
 }
 
-#[derive(Debug, Clone, Deserialize, Serialize, TS)]
+/// An MCP tool call as requested by the model.
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema, TS)]
 pub struct McpInvocation {
     /// Name of the MCP server as defined in the config.
     pub server: String,
@@ -821,67 +1355,257 @@ pub struct McpInvocation {
     pub arguments: Option<serde_json::Value>,
 }
 
-#[derive(Debug, Clone, Deserialize, Serialize, TS)]
+/// Payload of `EventMsg::McpToolCallBegin`.
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema, TS)]
 pub struct McpToolCallBeginEvent {
     /// Identifier so this can be paired with the McpToolCallEnd event.
     pub call_id: String,
+    /// The tool and arguments being called.
     pub invocation: McpInvocation,
 }
 
-#[derive(Debug, Clone, Deserialize, Serialize, TS)]
+/// Payload of `EventMsg::McpToolCallEnd`.
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema, TS)]
 pub struct McpToolCallEndEvent {
     /// Identifier for the corresponding McpToolCallBegin that finished.
     pub call_id: String,
+    /// The tool and arguments that were called.
     pub invocation: McpInvocation,
+    /// How long the call took.
     #[ts(type = "string")]
     pub duration: Duration,
     /// Result of the tool call. Note this could be an error.
     pub result: Result<CallToolResult, String>,
+    /// The `outputSchema` the tool declared, if any. When set,
+    /// `structured_content` in a successful result is expected to match it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub output_schema: Option<serde_json::Value>,
 }
 
 impl McpToolCallEndEvent {
+    /// Whether the call returned a result that is not flagged as an error.
     pub fn is_success(&self) -> bool {
         match &self.result {
             Ok(result) => !result.is_error.unwrap_or(false),
             Err(_) => false,
         }
     }
+
+    /// The structured (JSON) output of a successful call, if the tool
+    /// returned any.
+    pub fn structured_content(&self) -> Option<&serde_json::Value> {
+        match &self.result {
+            Ok(result) => result
+                .structured_content
+                .as_ref()
+                .filter(|value| !value.is_null()),
+            Err(_) => None,
+        }
+    }
+}
+
+/// Payload of `EventMsg::McpNotification`.
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema, TS)]
+pub struct McpNotificationEvent {
+    /// Name of the MCP server as defined in the config.
+    pub server: String,
+    /// What the server sent.
+    pub notification: McpNotification,
 }
 
-#[derive(Debug, Clone, Deserialize, Serialize, TS)]
+/// A server-initiated MCP notification that is surfaced to the user.
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, JsonSchema, TS)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum McpNotification {
+    /// `notifications/progress` for a running tool call.
+    Progress {
+        /// The `call_id` of the `McpToolCallBegin` this reports on.
+        call_id: String,
+        /// Progress so far; increases with every notification.
+        progress: f64,
+        /// Value `progress` will reach when the call is done, if known.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        total: Option<f64>,
+        /// Human-readable description of the current step.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        message: Option<String>,
+    },
+    /// `notifications/message`: a log message from the server.
+    Log {
+        /// Severity of the message.
+        level: McpLoggingLevel,
+        /// Name of the server component that logged the message.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        logger: Option<String>,
+        /// The message: usually a string, but any JSON value is allowed.
+        data: serde_json::Value,
+    },
+}
+
+/// Payload of `EventMsg::WebSearchBegin`.
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema, TS)]
 pub struct WebSearchBeginEvent {
+    /// Identifier so this can be paired with the WebSearchEnd event.
     pub call_id: String,
 }
 
-#[derive(Debug, Clone, Deserialize, Serialize, TS)]
+/// Payload of `EventMsg::WebSearchEnd`.
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema, TS)]
 pub struct WebSearchEndEvent {
+    /// Identifier for the WebSearchBegin that finished.
     pub call_id: String,
+    /// The query that was searched for.
     pub query: String,
 }
 
 /// Response payload for `Op::GetHistory` containing the current session's
 /// in-memory transcript.
-#[derive(Debug, Clone, Deserialize, Serialize, TS)]
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema, TS)]
 pub struct ConversationPathResponseEvent {
+    /// The conversation the transcript belongs to.
+    pub conversation_id: ConversationId,
+    /// Path of the rollout file holding the transcript.
+    pub path: PathBuf,
+}
+
+/// Payload of `EventMsg::ExecSessionKilled`.
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema, TS)]
+pub struct ExecSessionKilledEvent {
+    /// The session named in `Op::KillExecSession`.
+    pub session_id: u32,
+    /// Whether a running process was killed; `false` when the session had
+    /// already exited or never existed.
+    pub killed: bool,
+}
+
+/// Payload of `EventMsg::ToolCallCancelled`.
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema, TS)]
+pub struct ToolCallCancelledEvent {
+    /// The call named in `Op::CancelToolCall`.
+    pub call_id: String,
+    /// Whether a running call was cancelled; `false` when the call had
+    /// already finished or never existed.
+    pub cancelled: bool,
+}
+
+/// Payload of `EventMsg::UndoCompleted`.
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema, TS)]
+pub struct UndoCompletedEvent {
+    /// Snapshot the working tree was restored to; `None` if nothing was undone.
+    pub snapshot_id: Option<String>,
+    /// Files changed by the undo, relative to the repository root.
+    pub reverted_files: Vec<PathBuf>,
+    /// Why nothing was undone, when `snapshot_id` is `None`.
+    pub message: Option<String>,
+}
+
+/// Payload of `EventMsg::ListSnapshotsResponse`.
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema, TS)]
+pub struct ListSnapshotsResponseEvent {
+    /// Snapshots that can still be restored, oldest first.
+    pub snapshots: Vec<SnapshotInfo>,
+}
+
+/// Payload of `EventMsg::SnapshotDiff`.
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema, TS)]
+pub struct SnapshotDiffEvent {
+    /// Snapshot the diff starts from; `None` if there was none to diff.
+    pub from: Option<String>,
+    /// Snapshot the diff ends at; `None` for the working tree.
+    pub to: Option<String>,
+    /// Unified diff, with paths relative to the repository root.
+    pub unified_diff: String,
+    /// Why no diff was produced, when it could not be.
+    pub message: Option<String>,
+}
+
+/// Payload of `EventMsg::CheckpointCreated`.
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema, TS)]
+pub struct CheckpointCreatedEvent {
+    /// Id to pass to `Op::RewindTo`.
+    pub checkpoint_id: String,
+    /// The label given to `Op::Checkpoint`.
+    pub label: Option<String>,
+    /// Ghost commit of the working tree; `None` when the workspace was not
+    /// included or could not be snapshotted (e.g. outside a git repository).
+    pub snapshot_id: Option<String>,
+}
+
+/// Payload of `EventMsg::CheckpointRewound`.
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema, TS)]
+pub struct CheckpointRewoundEvent {
+    /// The checkpoint named in `Op::RewindTo`.
+    pub checkpoint_id: String,
+    /// Whether the session went back to the checkpoint.
+    pub rewound: bool,
+    /// The label given to `Op::Checkpoint`.
+    pub label: Option<String>,
+    /// Files the rewind restored, relative to the repository root.
+    pub restored_files: Vec<PathBuf>,
+    /// Why the session did not rewind, when `rewound` is `false`.
+    pub message: Option<String>,
+}
+
+/// A ghost commit of the working tree, taken before a patch or
+/// write-capable command ran.
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, JsonSchema, TS)]
+pub struct SnapshotInfo {
+    /// Commit id of the snapshot.
+    pub id: String,
+    /// When the snapshot was taken, as an RFC 3339 timestamp.
+    pub timestamp: String,
+    /// The tool call that triggered the snapshot.
+    pub call_id: String,
+    /// Files changed between this snapshot and the next one (or the current
+    /// working tree for the latest), relative to the repository root.
+    pub files: Vec<PathBuf>,
+}
+
+/// Payload of `EventMsg::SessionRemoved`.
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema, TS)]
+pub struct SessionRemovedEvent {
+    /// The conversation that was removed.
     pub conversation_id: ConversationId,
+    /// Where the rollout file now lives.
     pub path: PathBuf,
+    /// Whether the session was archived or moved to the trash.
+    pub disposition: SessionDisposition,
+}
+
+/// Where a removed session's rollout file went.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize, JsonSchema, TS)]
+#[serde(rename_all = "snake_case")]
+pub enum SessionDisposition {
+    /// Moved to `archived_sessions`.
+    Archived,
+    /// Moved to the trash; restorable until purged.
+    Trashed,
 }
 
-#[derive(Debug, Clone, Deserialize, Serialize, TS)]
+/// A session read back from its rollout file.
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema, TS)]
 pub struct ResumedHistory {
+    /// The conversation being resumed.
     pub conversation_id: ConversationId,
+    /// Items recorded in the rollout file, in order.
     pub history: Vec<RolloutItem>,
+    /// Path of the rollout file.
     pub rollout_path: PathBuf,
 }
 
-#[derive(Debug, Clone, Deserialize, Serialize, TS)]
+/// History a conversation starts from.
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema, TS)]
 pub enum InitialHistory {
+    /// A fresh conversation.
     New,
+    /// A conversation resumed from its rollout file.
     Resumed(ResumedHistory),
+    /// A new conversation seeded with items from another one.
     Forked(Vec<RolloutItem>),
 }
 
 impl InitialHistory {
+    /// All rollout items of the history; empty for a new conversation.
     pub fn get_rollout_items(&self) -> Vec<RolloutItem> {
         match self {
             InitialHistory::New => Vec::new(),
@@ -890,6 +1614,7 @@ impl InitialHistory {
         }
     }
 
+    /// The events recorded in the history, or `None` for a new conversation.
     pub fn get_event_msgs(&self) -> Option<Vec<EventMsg>> {
         match self {
             InitialHistory::New => None,
@@ -916,37 +1641,72 @@ impl InitialHistory {
     }
 }
 
-#[derive(Serialize, Deserialize, Clone, Default, Debug, TS)]
+/// Metadata written at the start of a rollout file.
+#[derive(Serialize, Deserialize, Clone, Default, Debug, JsonSchema, TS)]
 pub struct SessionMeta {
+    /// The conversation the rollout belongs to.
     pub id: ConversationId,
+    /// When the session started, as an RFC 3339 timestamp.
     pub timestamp: String,
+    /// Working directory the session started in.
     pub cwd: PathBuf,
+    /// Client that started the session.
     pub originator: String,
+    /// Version of Codex that wrote the rollout.
     pub cli_version: String,
+    /// User instructions in effect when the session started.
     pub instructions: Option<String>,
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone, TS)]
+/// [`SessionMeta`] together with the state of the git repository.
+#[derive(Serialize, Deserialize, Debug, Clone, JsonSchema, TS)]
 pub struct SessionMetaLine {
+    /// Session metadata.
     #[serde(flatten)]
     pub meta: SessionMeta,
+    /// Repository state when the session started, if `cwd` is in a git repository.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub git: Option<GitInfo>,
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone, TS)]
+/// A record in a rollout file.
+#[derive(Serialize, Deserialize, Debug, Clone, JsonSchema, TS)]
 #[serde(tag = "type", content = "payload", rename_all = "snake_case")]
 pub enum RolloutItem {
+    /// Session metadata; the first record of every rollout.
     SessionMeta(SessionMetaLine),
+    /// An item of the conversation sent to or received from the model.
     ResponseItem(ResponseItem),
+    /// A summary that replaced earlier history during compaction.
     Compacted(CompactedItem),
+    /// The turn context in effect for the following items.
     TurnContext(TurnContextItem),
+    /// An event emitted to clients.
     EventMsg(EventMsg),
+    /// Session-scoped state as of this point; the last one is restored on resume.
+    SessionState(SessionStateSnapshot),
+    /// The history was rewound to a checkpoint and replaced by its contents.
+    Rewound(RewoundItem),
 }
 
-#[derive(Serialize, Deserialize, Clone, Debug, TS)]
+/// Summary produced by compacting the conversation.
+#[derive(Serialize, Deserialize, Clone, Debug, JsonSchema, TS)]
 pub struct CompactedItem {
+    /// The summary text.
     pub message: String,
+    /// Number of most recent user and assistant messages kept verbatim after
+    /// the summary.
+    #[serde(default)]
+    pub preserved_messages: u32,
+}
+
+/// Conversation history restored by `Op::RewindTo`.
+#[derive(Serialize, Deserialize, Clone, Debug, JsonSchema, TS)]
+pub struct RewoundItem {
+    /// The checkpoint the history was rewound to.
+    pub checkpoint_id: String,
+    /// The history as of the checkpoint, which replaces the current one.
+    pub history: Vec<ResponseItem>,
 }
 
 impl From<CompactedItem> for ResponseItem {
@@ -961,25 +1721,82 @@ impl From<CompactedItem> for ResponseItem {
     }
 }
 
-#[derive(Serialize, Deserialize, Clone, Debug, TS)]
+/// Session-scoped state that is not part of the conversation and would
+/// otherwise only live in memory. It is recorded whenever it changes so that
+/// resuming a session after a crash does not lose it.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, JsonSchema, TS)]
+pub struct SessionStateSnapshot {
+    /// Commands approved for the rest of the session, sorted.
+    #[serde(default)]
+    pub approved_commands: Vec<Vec<String>>,
+    /// Command patterns (e.g. `cargo test *`) approved for the session.
+    #[serde(default)]
+    pub approved_command_patterns: Vec<String>,
+    /// The plan last set with `update_plan`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub plan: Option<UpdatePlanArgs>,
+    /// Token usage of the session so far.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub token_usage: Option<TokenUsageInfo>,
+    /// Background `exec_command` sessions that were running. Processes do not
+    /// survive their session, so these are only reported after a resume.
+    #[serde(default)]
+    pub background_processes: Vec<BackgroundProcess>,
+}
+
+/// A background `exec_command` session in a [`SessionStateSnapshot`].
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema, TS)]
+pub struct BackgroundProcess {
+    /// The `exec_command` session id.
+    pub session_id: u32,
+    /// The command the session was started with.
+    pub command: String,
+}
+
+/// Per-turn settings recorded in a rollout file.
+#[derive(Serialize, Deserialize, Clone, Debug, JsonSchema, TS)]
 pub struct TurnContextItem {
+    /// Working directory of the turn.
     pub cwd: PathBuf,
+    /// Approval policy of the turn.
     pub approval_policy: AskForApproval,
+    /// Sandbox policy of the turn.
     pub sandbox_policy: SandboxPolicy,
+    /// Model used for the turn.
     pub model: String,
+    /// Reasoning effort, when the model supports it.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub effort: Option<ReasoningEffortConfig>,
+    /// Reasoning summary preference.
     pub summary: ReasoningSummaryConfig,
 }
 
+/// Version of the rollout line envelope written by this build. Lines without
+/// a `schema_version` predate the envelope and are read as version 1.
+pub const ROLLOUT_SCHEMA_VERSION: u32 = 2;
+
+/// One line of a rollout file: a timestamped [`RolloutItem`].
 #[derive(Serialize, Deserialize, Clone)]
 pub struct RolloutLine {
+    /// When the record was written, as an RFC 3339 timestamp.
     pub timestamp: String,
+    /// Version of the line envelope; see [`ROLLOUT_SCHEMA_VERSION`].
+    #[serde(default = "legacy_rollout_schema_version")]
+    pub schema_version: u32,
+    /// Submission id of the turn that was running when the record was written.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub turn_id: Option<String>,
+    /// The record itself.
     #[serde(flatten)]
     pub item: RolloutItem,
 }
 
-#[derive(Serialize, Deserialize, Clone, Debug, TS)]
+fn legacy_rollout_schema_version() -> u32 {
+    1
+}
+
+/// State of the git repository a session started in.
+#[derive(Serialize, Deserialize, Clone, Debug, JsonSchema, TS)]
 pub struct GitInfo {
     /// Current commit hash (SHA)
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -993,18 +1810,24 @@ pub struct GitInfo {
 }
 
 /// Review request sent to the review session.
-#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, TS)]
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, JsonSchema, TS)]
 pub struct ReviewRequest {
+    /// Instructions for the review model.
     pub prompt: String,
+    /// Short description of the review shown to the user.
     pub user_facing_hint: String,
 }
 
 /// Structured review result produced by a child review session.
-#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, TS)]
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, JsonSchema, TS)]
 pub struct ReviewOutputEvent {
+    /// Issues found by the review.
     pub findings: Vec<ReviewFinding>,
+    /// Overall verdict on whether the change is correct.
     pub overall_correctness: String,
+    /// Explanation of the verdict.
     pub overall_explanation: String,
+    /// Confidence in the verdict, from 0 to 1.
     pub overall_confidence_score: f32,
 }
 
@@ -1020,30 +1843,40 @@ impl Default for ReviewOutputEvent {
 }
 
 /// A single review finding describing an observed issue or recommendation.
-#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, TS)]
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, JsonSchema, TS)]
 pub struct ReviewFinding {
+    /// One-line summary of the finding.
     pub title: String,
+    /// Explanation of the finding.
     pub body: String,
+    /// Confidence in the finding, from 0 to 1.
     pub confidence_score: f32,
+    /// Priority of the finding; lower is more urgent.
     pub priority: i32,
+    /// Code the finding refers to.
     pub code_location: ReviewCodeLocation,
 }
 
 /// Location of the code related to a review finding.
-#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, TS)]
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, JsonSchema, TS)]
 pub struct ReviewCodeLocation {
+    /// File containing the code.
     pub absolute_file_path: PathBuf,
+    /// Lines the finding refers to.
     pub line_range: ReviewLineRange,
 }
 
 /// Inclusive line range in a file associated with the finding.
-#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, TS)]
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, JsonSchema, TS)]
 pub struct ReviewLineRange {
+    /// First line, 1-based.
     pub start: u32,
+    /// Last line, 1-based and inclusive.
     pub end: u32,
 }
 
-#[derive(Debug, Clone, Deserialize, Serialize, TS)]
+/// Payload of `EventMsg::ExecCommandBegin`.
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema, TS)]
 pub struct ExecCommandBeginEvent {
     /// Identifier so this can be paired with the ExecCommandEnd event.
     pub call_id: String,
@@ -1051,10 +1884,18 @@ pub struct ExecCommandBeginEvent {
     pub command: Vec<String>,
     /// The command's working directory if not the default cwd for the agent.
     pub cwd: PathBuf,
+    /// The command split into recognized operations (reads, searches, …) for
+    /// display.
     pub parsed_cmd: Vec<ParsedCommand>,
+    /// Sandbox the command runs under, which is read-only for commands that
+    /// only read, list or search files even when the session allows writes.
+    /// `None` when the command runs without a sandbox.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sandbox_policy: Option<SandboxPolicy>,
 }
 
-#[derive(Debug, Clone, Deserialize, Serialize, TS)]
+/// Payload of `EventMsg::ExecCommandEnd`.
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema, TS)]
 pub struct ExecCommandEndEvent {
     /// Identifier for the ExecCommandBegin that finished.
     pub call_id: String,
@@ -1074,15 +1915,30 @@ pub struct ExecCommandEndEvent {
     pub formatted_output: String,
 }
 
-#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, TS)]
+/// Payload of `EventMsg::ExecCommandDryRun`.
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema, TS)]
+pub struct ExecCommandDryRunEvent {
+    /// Identifier of the tool call that asked for the command.
+    pub call_id: String,
+    /// The command that would have been executed.
+    pub command: Vec<String>,
+    /// The working directory it would have run in.
+    pub cwd: PathBuf,
+}
+
+/// Output stream of a command.
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, JsonSchema, TS)]
 #[serde(rename_all = "snake_case")]
 pub enum ExecOutputStream {
+    /// Standard output.
     Stdout,
+    /// Standard error.
     Stderr,
 }
 
+/// Payload of `EventMsg::ExecCommandOutputDelta`.
 #[serde_as]
-#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, TS)]
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, JsonSchema, TS)]
 pub struct ExecCommandOutputDeltaEvent {
     /// Identifier for the ExecCommandBegin that produced this chunk.
     pub call_id: String,
@@ -1090,11 +1946,13 @@ pub struct ExecCommandOutputDeltaEvent {
     pub stream: ExecOutputStream,
     /// Raw bytes from the stream (may not be valid UTF-8).
     #[serde_as(as = "serde_with::base64::Base64")]
+    #[schemars(with = "String")]
     #[ts(type = "string")]
     pub chunk: Vec<u8>,
 }
 
-#[derive(Debug, Clone, Deserialize, Serialize, TS)]
+/// Payload of `EventMsg::ExecApprovalRequest`.
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema, TS)]
 pub struct ExecApprovalRequestEvent {
     /// Identifier for the associated exec call, if available.
     pub call_id: String,
@@ -1107,10 +1965,115 @@ pub struct ExecApprovalRequestEvent {
     pub reason: Option<String>,
 }
 
-#[derive(Debug, Clone, Deserialize, Serialize, TS)]
+/// Payload of `EventMsg::EscalationRequest`.
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema, TS)]
+pub struct EscalationRequestEvent {
+    /// Identifier for the associated exec call.
+    pub call_id: String,
+    /// The command to be executed without a sandbox.
+    pub command: Vec<String>,
+    /// The command's working directory.
+    pub cwd: PathBuf,
+    /// The model's explanation of why the command needs escalated permissions.
+    pub justification: String,
+    /// Permissions the command would gain beyond the active sandbox policy.
+    pub requested_permissions: Vec<EscalatedPermission>,
+}
+
+/// Payload of `EventMsg::SpendLimitApprovalRequest`.
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema, TS)]
+pub struct SpendLimitApprovalRequestEvent {
+    /// Estimated cost of the session so far, in USD.
+    pub estimated_cost_usd: f64,
+    /// The limit that was reached, in USD.
+    pub limit_usd: f64,
+}
+
+/// Payload of `EventMsg::PermissionConsentRequest`.
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema, TS)]
+pub struct PermissionConsentRequestEvent {
+    /// Identifier for the tool call that triggered the prompt.
+    pub call_id: String,
+    /// The capability being asked about.
+    pub permission: GuidedPermission,
+    /// What the agent is about to do, e.g. the command or the paths it would
+    /// write.
+    pub detail: String,
+}
+
+/// A kind of capability the user is asked about the first time a project
+/// uses it. The answer is remembered for the project.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize, JsonSchema, TS)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum GuidedPermission {
+    /// A command that talks to the network.
+    Network,
+    /// A tool provided by an MCP server.
+    McpTool {
+        /// Name of the MCP server as defined in the config.
+        server: String,
+        /// Name of the tool as given by the MCP server.
+        tool: String,
+    },
+    /// Writing files outside the directory the session started in.
+    WriteOutsideCwd {
+        /// The directory the session started in.
+        cwd: PathBuf,
+    },
+}
+
+impl GuidedPermission {
+    /// Key the answer is stored under in `[projects."<path>".permissions]`.
+    pub fn key(&self) -> String {
+        match self {
+            GuidedPermission::Network => "network".to_string(),
+            GuidedPermission::McpTool { server, tool } => format!("mcp:{server}.{tool}"),
+            GuidedPermission::WriteOutsideCwd { .. } => "write_outside_cwd".to_string(),
+        }
+    }
+
+    /// Explanation shown the first time the permission is requested.
+    pub fn explanation(&self) -> String {
+        match self {
+            GuidedPermission::Network => "Codex wants to run a command that uses the network. Network access can download code, send data to other machines, or change remote state.".to_string(),
+            GuidedPermission::McpTool { server, tool } => format!(
+                "Codex wants to call `{tool}` from the MCP server `{server}`. MCP tools run outside the sandbox with whatever access the server has."
+            ),
+            GuidedPermission::WriteOutsideCwd { cwd } => format!(
+                "Codex wants to change files outside {}, the directory this session started in.",
+                cwd.display()
+            ),
+        }
+    }
+}
+
+/// A capability that is withheld by the active sandbox policy and would be
+/// granted to an escalated command.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize, JsonSchema, TS)]
+#[serde(rename_all = "snake_case")]
+pub enum EscalatedPermission {
+    /// Write access to the entire file system.
+    FullDiskWrite,
+    /// Unrestricted outbound network access.
+    NetworkAccess,
+}
+
+impl EscalatedPermission {
+    /// Short human-readable description suitable for approval prompts.
+    pub fn description(self) -> &'static str {
+        match self {
+            EscalatedPermission::FullDiskWrite => "write access to the entire file system",
+            EscalatedPermission::NetworkAccess => "unrestricted network access",
+        }
+    }
+}
+
+/// Payload of `EventMsg::ApplyPatchApprovalRequest`.
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema, TS)]
 pub struct ApplyPatchApprovalRequestEvent {
     /// Responses API call id for the associated patch apply call, if available.
     pub call_id: String,
+    /// The changes to be applied, keyed by path.
     pub changes: HashMap<PathBuf, FileChange>,
     /// Optional explanatory reason (e.g. request for extra write access).
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -1118,19 +2081,193 @@ pub struct ApplyPatchApprovalRequestEvent {
     /// When set, the agent is asking the user to allow writes under this root for the remainder of the session.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub grant_root: Option<PathBuf>,
+    /// Lines added and removed by `changes`, per file and in total.
+    #[serde(default)]
+    pub stats: PatchStats,
+    /// Writes from the patch that resolve outside the writable roots. They
+    /// were removed from `changes` and are not applied whatever the answer.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub rejected_paths: Vec<PatchPathEscape>,
+}
+
+/// A path in a patch that names a file inside the writable roots but would
+/// actually write outside them.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize, JsonSchema, TS)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum PatchPathEscape {
+    /// A symlink along the path points outside the writable roots.
+    Symlink {
+        /// The path as written in the patch.
+        path: PathBuf,
+        /// Where the path lands once the symlink is followed.
+        resolved: PathBuf,
+    },
+    /// A `..` component leads out of the writable roots.
+    ParentDir {
+        /// The path as written in the patch.
+        path: PathBuf,
+        /// Where the path lands once `..` is applied.
+        resolved: PathBuf,
+    },
+}
+
+impl PatchPathEscape {
+    /// The path as written in the patch.
+    pub fn path(&self) -> &Path {
+        match self {
+            PatchPathEscape::Symlink { path, .. } | PatchPathEscape::ParentDir { path, .. } => path,
+        }
+    }
 }
 
-#[derive(Debug, Clone, Deserialize, Serialize, TS)]
+impl fmt::Display for PatchPathEscape {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PatchPathEscape::Symlink { path, resolved } => write!(
+                f,
+                "{} resolves through a symlink to {}, outside the writable roots",
+                path.display(),
+                resolved.display()
+            ),
+            PatchPathEscape::ParentDir { path, resolved } => write!(
+                f,
+                "{} uses `..` to leave the writable roots and resolves to {}",
+                path.display(),
+                resolved.display()
+            ),
+        }
+    }
+}
+
+/// Payload of `EventMsg::BackgroundEvent`.
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema, TS)]
 pub struct BackgroundEventEvent {
+    /// The message to show.
     pub message: String,
 }
 
-#[derive(Debug, Clone, Deserialize, Serialize, TS)]
+/// Payload of `EventMsg::StreamError`.
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema, TS)]
 pub struct StreamErrorEvent {
+    /// Description of the error and how it is being handled.
     pub message: String,
 }
 
-#[derive(Debug, Clone, Deserialize, Serialize, TS)]
+/// Lifecycle point a hook is configured for.
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, PartialEq, Eq, JsonSchema, TS)]
+#[serde(rename_all = "snake_case")]
+pub enum HookKind {
+    /// Before a tool call runs; may block or modify it.
+    PreToolUse,
+    /// After a tool call finished.
+    PostToolUse,
+    /// When the user submits a prompt.
+    UserPromptSubmit,
+    /// When the agent is about to finish a task.
+    Stop,
+    /// When a session starts.
+    SessionStart,
+    /// Before each request to the model.
+    PreModelCall,
+    /// After each turn.
+    TurnComplete,
+    /// After a turn that changed files, with the turn's diff.
+    PostTurnDiff,
+}
+
+impl fmt::Display for HookKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            HookKind::PreToolUse => "pre_tool_use",
+            HookKind::PostToolUse => "post_tool_use",
+            HookKind::UserPromptSubmit => "user_prompt_submit",
+            HookKind::Stop => "stop",
+            HookKind::SessionStart => "session_start",
+            HookKind::PreModelCall => "pre_model_call",
+            HookKind::TurnComplete => "turn_complete",
+            HookKind::PostTurnDiff => "post_turn_diff",
+        })
+    }
+}
+
+/// Payload of `EventMsg::HookBegin`.
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema, TS)]
+pub struct HookBeginEvent {
+    /// Which hook is running.
+    pub hook: HookKind,
+    /// Tool the hook runs for; set for tool hooks only.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tool: Option<String>,
+}
+
+/// Payload of `EventMsg::HookEnd`.
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema, TS)]
+pub struct HookEndEvent {
+    /// Which hook finished.
+    pub hook: HookKind,
+    /// Tool the hook ran for; set for tool hooks only.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tool: Option<String>,
+    /// How long the hook ran.
+    #[ts(type = "string")]
+    pub duration: Duration,
+    /// The hook's exit code; `None` when it could not be started, timed out
+    /// or was killed by a signal.
+    pub exit_code: Option<i32>,
+    /// Whether the hook was stopped because it ran past its timeout.
+    pub timed_out: bool,
+}
+
+/// Separates the submission id from the child's name in the `Event::id` of
+/// events sent by a child agent, e.g. `3/delegate-1`.
+pub const SUB_AGENT_ID_SEPARATOR: char = '/';
+
+impl Event {
+    /// The id of the child agent that sent this event, if it came from one
+    /// rather than from the task itself.
+    pub fn sub_agent_id(&self) -> Option<&str> {
+        if self.id.contains(SUB_AGENT_ID_SEPARATOR) {
+            Some(&self.id)
+        } else {
+            None
+        }
+    }
+}
+
+/// Payload of `EventMsg::SubAgentBegin`.
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema, TS)]
+pub struct SubAgentBeginEvent {
+    /// The `delegate` call that started the child.
+    pub call_id: String,
+    /// `id` of the events the child sends, namespaced under the task's
+    /// submission id.
+    pub agent_id: String,
+    /// Prompt the child was started with.
+    pub prompt: String,
+    /// Tools the child may use; `None` when it gets the parent's tools.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tools: Option<Vec<String>>,
+    /// Most model turns the child may take.
+    pub max_turns: u32,
+}
+
+/// Payload of `EventMsg::SubAgentEnd`.
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema, TS)]
+pub struct SubAgentEndEvent {
+    /// The `delegate` call that started the child.
+    pub call_id: String,
+    /// Matches `SubAgentBeginEvent::agent_id`.
+    pub agent_id: String,
+    /// Model turns the child took.
+    pub turns: u32,
+    /// Whether the child was stopped for running out of turns.
+    pub budget_exhausted: bool,
+    /// The child's final message, returned to the parent as the tool output.
+    pub last_agent_message: Option<String>,
+}
+
+/// Payload of `EventMsg::PatchApplyBegin`.
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema, TS)]
 pub struct PatchApplyBeginEvent {
     /// Identifier so this can be paired with the PatchApplyEnd event.
     pub call_id: String,
@@ -1138,9 +2275,14 @@ pub struct PatchApplyBeginEvent {
     pub auto_approved: bool,
     /// The changes to be applied.
     pub changes: HashMap<PathBuf, FileChange>,
+    /// Ghost commit capturing the working tree before the patch was applied,
+    /// when the session runs inside a git repository.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub snapshot_id: Option<String>,
 }
 
-#[derive(Debug, Clone, Deserialize, Serialize, TS)]
+/// Payload of `EventMsg::PatchApplyEnd`.
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema, TS)]
 pub struct PatchApplyEndEvent {
     /// Identifier for the PatchApplyBegin that finished.
     pub call_id: String,
@@ -1152,14 +2294,19 @@ pub struct PatchApplyEndEvent {
     pub success: bool,
 }
 
-#[derive(Debug, Clone, Deserialize, Serialize, TS)]
+/// Payload of `EventMsg::TurnDiff`.
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema, TS)]
 pub struct TurnDiffEvent {
+    /// Unified diff of the turn's changes.
     pub unified_diff: String,
 }
 
-#[derive(Debug, Clone, Deserialize, Serialize, TS)]
+/// Payload of `EventMsg::GetHistoryEntryResponse`.
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema, TS)]
 pub struct GetHistoryEntryResponseEvent {
+    /// Offset that was requested.
     pub offset: usize,
+    /// History log that was requested.
     pub log_id: u64,
     /// The entry at the requested offset, if available and parseable.
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -1167,19 +2314,97 @@ pub struct GetHistoryEntryResponseEvent {
 }
 
 /// Response payload for `Op::ListMcpTools`.
-#[derive(Debug, Clone, Deserialize, Serialize, TS)]
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema, TS)]
 pub struct McpListToolsResponseEvent {
     /// Fully qualified tool name -> tool definition.
     pub tools: std::collections::HashMap<String, McpTool>,
 }
 
+/// Response payload for `Op::ListMcpResources`.
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema, TS)]
+pub struct McpListResourcesResponseEvent {
+    /// Server name -> resources it offers. Servers without resources, or
+    /// that failed to list them, are omitted.
+    pub resources: std::collections::HashMap<String, Vec<McpResource>>,
+}
+
+/// Effective environment for commands run by the agent.
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema, TS)]
+pub struct SessionEnvResponseEvent {
+    /// Variables passed to spawned commands after applying the
+    /// `shell_environment_policy` and session-level overrides.
+    pub env: BTreeMap<String, String>,
+    /// Session-level overrides: `Some(value)` for variables set via
+    /// `Op::SetSessionEnv`, `None` for variables removed via
+    /// `Op::UnsetSessionEnv`.
+    pub overrides: BTreeMap<String, Option<String>>,
+}
+
+/// What the next request to the model would contain, excluding the user's
+/// new message.
+#[cfg(feature = "unstable")]
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema, TS)]
+pub struct NextPromptPreviewEvent {
+    /// Model the request would be sent to.
+    pub model: String,
+    /// The parts of the request, in the order they are sent.
+    pub sections: Vec<PromptPreviewSection>,
+    /// Sum of the sections' estimates.
+    pub estimated_tokens: u64,
+}
+
+/// One part of a [`NextPromptPreviewEvent`].
+#[cfg(feature = "unstable")]
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema, TS)]
+pub struct PromptPreviewSection {
+    /// Which part of the request this is.
+    pub kind: PromptSectionKind,
+    /// Rough token count, assuming about 4 bytes per token.
+    pub estimated_tokens: u64,
+    /// The section as sent, except for conversation history, which is
+    /// summarized one line per item.
+    pub content: String,
+}
+
+/// Parts of a request to the model.
+#[cfg(feature = "unstable")]
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, PartialEq, Eq, JsonSchema, TS)]
+#[serde(rename_all = "snake_case")]
+pub enum PromptSectionKind {
+    /// Base instructions for the model.
+    Instructions,
+    /// Instructions from `AGENTS.md` and the user's config.
+    UserInstructions,
+    /// The environment-context message (cwd, sandbox, locale, …).
+    EnvironmentContext,
+    /// The conversation so far.
+    History,
+    /// Definitions of the tools offered to the model.
+    Tools,
+}
+
+#[cfg(feature = "unstable")]
+impl fmt::Display for PromptSectionKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            PromptSectionKind::Instructions => "Instructions",
+            PromptSectionKind::UserInstructions => "User instructions",
+            PromptSectionKind::EnvironmentContext => "Environment context",
+            PromptSectionKind::History => "Conversation history",
+            PromptSectionKind::Tools => "Tools",
+        })
+    }
+}
+
 /// Response payload for `Op::ListCustomPrompts`.
-#[derive(Debug, Clone, Deserialize, Serialize, TS)]
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema, TS)]
 pub struct ListCustomPromptsResponseEvent {
+    /// Prompts found in the prompts directory.
     pub custom_prompts: Vec<CustomPrompt>,
 }
 
-#[derive(Debug, Default, Clone, Deserialize, Serialize, TS)]
+/// Payload of `EventMsg::SessionConfigured`.
+#[derive(Debug, Default, Clone, Deserialize, Serialize, JsonSchema, TS)]
 pub struct SessionConfiguredEvent {
     /// Name left as session_id instead of conversation_id for backwards compatibility.
     pub session_id: ConversationId,
@@ -1202,11 +2427,12 @@ pub struct SessionConfiguredEvent {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub initial_messages: Option<Vec<EventMsg>>,
 
+    /// Path of the file the session is recorded to.
     pub rollout_path: PathBuf,
 }
 
 /// User's decision in response to an ExecApprovalRequest.
-#[derive(Debug, Default, Clone, Copy, Deserialize, Serialize, PartialEq, Eq, TS)]
+#[derive(Debug, Default, Clone, Copy, Deserialize, Serialize, PartialEq, Eq, JsonSchema, TS)]
 #[serde(rename_all = "snake_case")]
 pub enum ReviewDecision {
     /// User has approved this command and the agent should execute it.
@@ -1217,6 +2443,11 @@ pub enum ReviewDecision {
     /// remainder of the session.
     ApprovedForSession,
 
+    /// User has approved this command and wants every command matching its
+    /// pattern (e.g. `cargo test *`) approved automatically, in this and
+    /// future sessions. The pattern is saved to `approved_command_patterns`.
+    ApprovedAlways,
+
     /// User has denied this command and the agent should not execute it, but
     /// it should continue the session and try something else.
     #[default]
@@ -1227,40 +2458,214 @@ pub enum ReviewDecision {
     Abort,
 }
 
-#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, TS)]
+/// A change to one file made by a patch.
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, JsonSchema, TS)]
 #[serde(rename_all = "snake_case")]
 pub enum FileChange {
+    /// A new file.
     Add {
+        /// Content of the new file.
         content: String,
     },
+    /// A deleted file.
     Delete {
+        /// Content of the file before deletion.
         content: String,
     },
+    /// A modified, and possibly moved, file.
     Update {
+        /// Unified diff of the change.
         unified_diff: String,
+        /// New path when the file is also moved.
         move_path: Option<PathBuf>,
     },
 }
 
-#[derive(Debug, Clone, Deserialize, Serialize, TS)]
+impl FileChange {
+    /// Lines this change adds and removes.
+    pub fn line_counts(&self) -> LineCounts {
+        match self {
+            FileChange::Add { content } => LineCounts {
+                added: content.lines().count() as u64,
+                removed: 0,
+            },
+            FileChange::Delete { content } => LineCounts {
+                added: 0,
+                removed: content.lines().count() as u64,
+            },
+            FileChange::Update { unified_diff, .. } => count_unified_diff_lines(unified_diff),
+        }
+    }
+}
+
+/// Lines added and removed by a change.
+#[derive(Debug, Clone, Copy, Default, Deserialize, Serialize, PartialEq, Eq, JsonSchema, TS)]
+pub struct LineCounts {
+    /// Lines added.
+    pub added: u64,
+    /// Lines removed.
+    pub removed: u64,
+}
+
+/// Line counts for every file in a patch, and their sum.
+#[derive(Debug, Clone, Default, Deserialize, Serialize, PartialEq, JsonSchema, TS)]
+pub struct PatchStats {
+    /// Counts for each changed file, keyed like the patch's changes.
+    pub files: HashMap<PathBuf, LineCounts>,
+    /// Sum of the per-file counts.
+    pub total: LineCounts,
+}
+
+impl PatchStats {
+    /// Count the lines added and removed by `changes`.
+    pub fn from_changes(changes: &HashMap<PathBuf, FileChange>) -> Self {
+        let mut stats = PatchStats::default();
+        for (path, change) in changes {
+            stats.add_file(path.clone(), change.line_counts());
+        }
+        stats
+    }
+
+    /// Record the counts for one file and add them to the total.
+    pub fn add_file(&mut self, path: PathBuf, counts: LineCounts) {
+        self.total.added += counts.added;
+        self.total.removed += counts.removed;
+        self.files.insert(path, counts);
+    }
+}
+
+/// Renders the total as `+120 −43 across 6 files`.
+impl fmt::Display for PatchStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let files = self.files.len();
+        let noun = if files == 1 { "file" } else { "files" };
+        write!(
+            f,
+            "+{} \u{2212}{} across {files} {noun}",
+            self.total.added, self.total.removed
+        )
+    }
+}
+
+/// Count the `+` and `-` lines in the hunks of a unified diff, skipping any
+/// `---`/`+++` file headers.
+fn count_unified_diff_lines(diff: &str) -> LineCounts {
+    let mut counts = LineCounts::default();
+    let (mut old_left, mut new_left) = (0u64, 0u64);
+    for line in diff.lines() {
+        if old_left == 0 && new_left == 0 {
+            if let Some((old_len, new_len)) = parse_hunk_header(line) {
+                old_left = old_len;
+                new_left = new_len;
+            }
+            continue;
+        }
+        match line.as_bytes().first() {
+            Some(b'+') => {
+                counts.added += 1;
+                new_left = new_left.saturating_sub(1);
+            }
+            Some(b'-') => {
+                counts.removed += 1;
+                old_left = old_left.saturating_sub(1);
+            }
+            Some(b'\\') => {}
+            _ => {
+                old_left = old_left.saturating_sub(1);
+                new_left = new_left.saturating_sub(1);
+            }
+        }
+    }
+    counts
+}
+
+/// The old and new line counts of a `@@ -a,b +c,d @@` hunk header.
+fn parse_hunk_header(line: &str) -> Option<(u64, u64)> {
+    let (ranges, _) = line.strip_prefix("@@ -")?.split_once(" @@")?;
+    let (old, new) = ranges.split_once(" +")?;
+    let len = |range: &str| match range.split_once(',') {
+        Some((_, len)) => len.parse().ok(),
+        None => Some(1),
+    };
+    Some((len(old)?, len(new)?))
+}
+
+/// A hunk of a file change.
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema, TS)]
 pub struct Chunk {
     /// 1-based line index of the first line in the original file
     pub orig_index: u32,
+    /// Lines removed from the original file.
     pub deleted_lines: Vec<String>,
+    /// Lines inserted in their place.
     pub inserted_lines: Vec<String>,
 }
 
-#[derive(Debug, Clone, Deserialize, Serialize, TS)]
+/// Payload of `EventMsg::TurnAborted`.
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema, TS)]
 pub struct TurnAbortedEvent {
+    /// Why the turn ended early.
     pub reason: TurnAbortReason,
+    /// The limit the task ran into, when `reason` is `BudgetExceeded`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub exceeded_limit: Option<TaskBudgetLimit>,
+    /// How much of `exceeded_limit` the task used, in the limit's unit: model
+    /// requests, tokens, seconds or USD.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub budget_used: Option<f64>,
+    /// The value of `exceeded_limit`, in the same unit as `budget_used`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub budget_allowed: Option<f64>,
+}
+
+impl TurnAbortedEvent {
+    /// One line describing the budget the task exceeded, such as
+    /// `max_turns budget exceeded (40 of 40 model requests)`.
+    pub fn budget_summary(&self) -> Option<String> {
+        let limit = self.exceeded_limit?;
+        let (Some(used), Some(allowed)) = (self.budget_used, self.budget_allowed) else {
+            return Some(format!("{limit} budget exceeded"));
+        };
+        let usage = match limit {
+            TaskBudgetLimit::MaxTurns => format!("{used:.0} of {allowed:.0} model requests"),
+            TaskBudgetLimit::MaxTotalTokens => format!("{used:.0} of {allowed:.0} tokens"),
+            TaskBudgetLimit::MaxDuration => format!("{used:.0} of {allowed:.0} seconds"),
+            TaskBudgetLimit::MaxSessionCost => format!("${used:.2} of ${allowed:.2}"),
+        };
+        Some(format!("{limit} budget exceeded ({usage})"))
+    }
 }
 
-#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, TS)]
+/// Why a turn was aborted.
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, JsonSchema, TS)]
 #[serde(rename_all = "snake_case")]
 pub enum TurnAbortReason {
+    /// The user interrupted it.
     Interrupted,
+    /// A new turn replaced it.
     Replaced,
+    /// Review mode ended.
     ReviewEnded,
+    /// The task reached one of its budget limits; see
+    /// `TurnAbortedEvent::exceeded_limit`.
+    BudgetExceeded,
+}
+
+/// A limit that ended a task early: one set in `task_budget` or on
+/// `Op::UserTurn`, or the session's `max_session_cost_usd`.
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, PartialEq, Eq, Display, JsonSchema, TS)]
+#[serde(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
+pub enum TaskBudgetLimit {
+    /// The task made its allowed number of model requests.
+    MaxTurns,
+    /// The task's model requests used its allowed number of tokens.
+    MaxTotalTokens,
+    /// The task ran for its allowed wall-clock time.
+    MaxDuration,
+    /// The session's estimated cost reached `max_session_cost_usd` and the
+    /// user did not approve going on.
+    MaxSessionCost,
 }
 
 #[cfg(test)]
@@ -1322,4 +2727,61 @@ mod tests {
         assert_eq!(deserialized, event);
         Ok(())
     }
+
+    #[test]
+    fn patch_stats_count_diff_lines_but_not_headers() {
+        let changes = HashMap::from([
+            (
+                PathBuf::from("new.txt"),
+                FileChange::Add {
+                    content: "a\nb\n".to_string(),
+                },
+            ),
+            (
+                PathBuf::from("lib.rs"),
+                FileChange::Update {
+                    unified_diff: "--- a/lib.rs\n+++ b/lib.rs\n@@ -1,3 +1,3 @@\n keep\n--- old\n+++ new\n keep\n@@ -9 +9,2 @@\n-x\n+y\n+z\n\\ No newline at end of file\n"
+                        .to_string(),
+                    move_path: None,
+                },
+            ),
+        ]);
+        let stats = PatchStats::from_changes(&changes);
+        assert_eq!(
+            stats.files[Path::new("lib.rs")],
+            LineCounts {
+                added: 3,
+                removed: 2
+            }
+        );
+        assert_eq!(
+            stats.total,
+            LineCounts {
+                added: 5,
+                removed: 2
+            }
+        );
+        assert_eq!(stats.to_string(), "+5 \u{2212}2 across 2 files");
+    }
+    #[test]
+    fn budget_summary_shows_usage_in_the_limit_unit() {
+        let aborted = |limit, used, allowed| TurnAbortedEvent {
+            reason: TurnAbortReason::BudgetExceeded,
+            exceeded_limit: Some(limit),
+            budget_used: used,
+            budget_allowed: allowed,
+        };
+        assert_eq!(
+            aborted(TaskBudgetLimit::MaxTurns, Some(40.0), Some(40.0)).budget_summary(),
+            Some("max_turns budget exceeded (40 of 40 model requests)".to_string())
+        );
+        assert_eq!(
+            aborted(TaskBudgetLimit::MaxSessionCost, Some(5.014), Some(5.0)).budget_summary(),
+            Some("max_session_cost budget exceeded ($5.01 of $5.00)".to_string())
+        );
+        assert_eq!(
+            aborted(TaskBudgetLimit::MaxDuration, None, None).budget_summary(),
+            Some("max_duration budget exceeded".to_string())
+        );
+    }
 }
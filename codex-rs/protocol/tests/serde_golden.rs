@@ -0,0 +1,85 @@
+//! Golden round-trip tests for the wire format of `Op` and `EventMsg`.
+//!
+//! Every variant has a JSON file under `tests/golden/{op,event}` (or
+//! `tests/golden/unstable/{op,event}` when it sits behind the `unstable`
+//! feature). Each file must deserialize and serialize back to the same JSON,
+//! and a variant without a golden file fails the test, so changes to the
+//! protocol stay additive and visible in review.
+#![allow(clippy::expect_used)]
+
+use std::collections::BTreeSet;
+use std::path::Path;
+use std::path::PathBuf;
+
+use codex_protocol::protocol::Event;
+use codex_protocol::protocol::EventMsg;
+use codex_protocol::protocol::Op;
+use codex_protocol::protocol::Submission;
+use pretty_assertions::assert_eq;
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+use strum::VariantNames;
+
+fn golden_dirs(kind: &str) -> Vec<PathBuf> {
+    let root = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/golden");
+    let mut dirs = vec![root.join(kind)];
+    if cfg!(feature = "unstable") {
+        dirs.push(root.join("unstable").join(kind));
+    }
+    dirs
+}
+
+/// Round-trips every golden file for `kind` through `T` and returns the
+/// variant names they cover.
+fn round_trip_all<T>(kind: &str) -> BTreeSet<String>
+where
+    T: Serialize + DeserializeOwned,
+{
+    let mut covered = BTreeSet::new();
+    for dir in golden_dirs(kind) {
+        let entries = std::fs::read_dir(&dir)
+            .unwrap_or_else(|err| panic!("failed to read {}: {err}", dir.display()));
+        for entry in entries {
+            let path = entry.expect("golden dir entry").path();
+            if path.extension().is_none_or(|ext| ext != "json") {
+                continue;
+            }
+            let text = std::fs::read_to_string(&path)
+                .unwrap_or_else(|err| panic!("failed to read {}: {err}", path.display()));
+            let expected: Value = serde_json::from_str(&text)
+                .unwrap_or_else(|err| panic!("{} is not valid JSON: {err}", path.display()));
+            let parsed: T = serde_json::from_value(expected.clone())
+                .unwrap_or_else(|err| panic!("{} failed to deserialize: {err}", path.display()));
+            let actual = serde_json::to_value(&parsed).expect("serialize golden value");
+            assert_eq!(expected, actual, "{} did not round-trip", path.display());
+
+            let stem = path
+                .file_stem()
+                .and_then(|stem| stem.to_str())
+                .expect("golden file name")
+                .to_string();
+            assert!(
+                covered.insert(stem.clone()),
+                "duplicate golden file for `{stem}`"
+            );
+        }
+    }
+    covered
+}
+
+fn variant_names(names: &[&str]) -> BTreeSet<String> {
+    names.iter().map(|name| (*name).to_string()).collect()
+}
+
+#[test]
+fn op_golden_files_round_trip() {
+    let covered = round_trip_all::<Submission>("op");
+    assert_eq!(variant_names(Op::VARIANTS), covered);
+}
+
+#[test]
+fn event_golden_files_round_trip() {
+    let covered = round_trip_all::<Event>("event");
+    assert_eq!(variant_names(EventMsg::VARIANTS), covered);
+}
@@ -3,9 +3,11 @@ use crate::app_event::AppEvent;
 use crate::app_event_sender::AppEventSender;
 use crate::chatwidget::ChatWidget;
 use crate::file_search::FileSearchManager;
+use crate::history_cell;
 use crate::history_cell::HistoryCell;
 use crate::pager_overlay::Overlay;
 use crate::resume_picker::ResumeSelection;
+use crate::session_summary::SessionSummary;
 use crate::tui;
 use crate::tui::TuiEvent;
 use codex_ansi_escape::ansi_escape_line;
@@ -38,6 +40,7 @@ use tokio::sync::mpsc::unbounded_channel;
 pub struct AppExitInfo {
     pub token_usage: TokenUsage,
     pub conversation_id: Option<ConversationId>,
+    pub session_summary: SessionSummary,
 }
 
 pub(crate) struct App {
@@ -163,6 +166,7 @@ impl App {
         Ok(AppExitInfo {
             token_usage: app.token_usage(),
             conversation_id: app.chat_widget.conversation_id(),
+            session_summary: app.chat_widget.session_summary(),
         })
     }
 
@@ -363,6 +367,30 @@ impl App {
             AppEvent::OpenReviewCustomPrompt => {
                 self.chat_widget.show_review_custom_prompt();
             }
+            AppEvent::OpenRolloutTranscript(path) => match Overlay::new_rollout_transcript(&path) {
+                Ok(overlay) => {
+                    let _ = tui.enter_alt_screen();
+                    self.overlay = Some(overlay);
+                    tui.frame_requester().schedule_frame();
+                }
+                Err(e) => {
+                    self.chat_widget.add_error_message(format!(
+                        "Failed to open transcript {}: {e}",
+                        path.display()
+                    ));
+                }
+            },
+            AppEvent::OpenPromptPreview(preview) => {
+                let _ = tui.enter_alt_screen();
+                self.overlay = Some(Overlay::new_static_with_title(
+                    history_cell::prompt_preview_lines(preview),
+                    "C O N T E X T".to_string(),
+                ));
+                tui.frame_requester().schedule_frame();
+            }
+            AppEvent::SubmitFollowUp(prompt) => {
+                self.chat_widget.submit_text_message(prompt);
+            }
         }
         Ok(true)
     }
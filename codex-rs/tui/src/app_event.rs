@@ -2,6 +2,7 @@ use std::path::PathBuf;
 
 use codex_core::protocol::ConversationPathResponseEvent;
 use codex_core::protocol::Event;
+use codex_core::protocol::NextPromptPreviewEvent;
 use codex_file_search::FileMatch;
 
 use crate::history_cell::HistoryCell;
@@ -76,4 +77,13 @@ pub(crate) enum AppEvent {
 
     /// Open the custom prompt option from the review popup.
     OpenReviewCustomPrompt,
+
+    /// Open the transcript overlay rendered from the given rollout file.
+    OpenRolloutTranscript(PathBuf),
+
+    /// Open the `/context` overlay showing what the next turn would send.
+    OpenPromptPreview(NextPromptPreviewEvent),
+
+    /// Submit a suggested follow-up prompt as the next user message.
+    SubmitFollowUp(String),
 }
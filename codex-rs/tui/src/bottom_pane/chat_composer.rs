@@ -56,6 +56,9 @@ const LARGE_PASTE_CHAR_THRESHOLD: usize = 1000;
 pub enum InputResult {
     Submitted(String),
     Command(SlashCommand),
+    /// A command that [`SlashCommand::takes_args`], with the text typed
+    /// after it.
+    CommandWithArgs(SlashCommand, String),
     None,
 }
 
@@ -415,6 +418,7 @@ impl ChatComposer {
                 ..
             } => {
                 if let Some(sel) = popup.selected_item() {
+                    let args = command_args(self.textarea.text()).to_string();
                     // Clear textarea so no residual text remains.
                     self.textarea.set_text("");
                     // Capture any needed data from popup before clearing it.
@@ -428,6 +432,9 @@ impl ChatComposer {
                     self.active_popup = ActivePopup::None;
 
                     match sel {
+                        CommandItem::Builtin(cmd) if cmd.takes_args() && !args.is_empty() => {
+                            return (InputResult::CommandWithArgs(cmd, args), true);
+                        }
                         CommandItem::Builtin(cmd) => {
                             return (InputResult::Command(cmd), true);
                         }
@@ -1230,6 +1237,17 @@ impl ChatComposer {
     }
 }
 
+/// The text after the command token on the first line of `text`, e.g.
+/// `add docs npx` for `/mcp add docs npx`.
+fn command_args(text: &str) -> &str {
+    let first_line = text.lines().next().unwrap_or("");
+    let command = first_line.trim_start().trim_start_matches('/');
+    command
+        .split_once(char::is_whitespace)
+        .map(|(_, args)| args.trim())
+        .unwrap_or("")
+}
+
 impl WidgetRef for ChatComposer {
     fn render_ref(&self, area: Rect, buf: &mut Buffer) {
         let (popup_constraint, hint_spacing) = match &self.active_popup {
@@ -1862,7 +1880,9 @@ mod tests {
             InputResult::Submitted(text) => {
                 panic!("expected command dispatch, but composer submitted literal text: {text}")
             }
-            InputResult::None => panic!("expected Command result for '/init'"),
+            InputResult::CommandWithArgs(..) | InputResult::None => {
+                panic!("expected Command result for '/init'")
+            }
         }
         assert!(composer.textarea.is_empty(), "composer should be cleared");
     }
@@ -1920,13 +1940,46 @@ mod tests {
             InputResult::Submitted(text) => {
                 panic!("expected command dispatch, but composer submitted literal text: {text}")
             }
-            InputResult::None => panic!("expected Command result for '/mention'"),
+            InputResult::CommandWithArgs(..) | InputResult::None => {
+                panic!("expected Command result for '/mention'")
+            }
         }
         assert!(composer.textarea.is_empty(), "composer should be cleared");
         composer.insert_str("@");
         assert_eq!(composer.textarea.text(), "@");
     }
 
+    #[test]
+    fn slash_mcp_with_args_dispatches_command_with_args() {
+        use crossterm::event::KeyCode;
+        use crossterm::event::KeyEvent;
+        use crossterm::event::KeyModifiers;
+
+        let (tx, _rx) = unbounded_channel::<AppEvent>();
+        let sender = AppEventSender::new(tx);
+        let mut composer = ChatComposer::new(
+            true,
+            sender,
+            false,
+            "Ask Codex to do anything".to_string(),
+            false,
+        );
+
+        let typed: Vec<char> = "/mcp add docs npx -y docs-server".chars().collect();
+        type_chars_humanlike(&mut composer, &typed);
+        let (result, _needs_redraw) =
+            composer.handle_key_event(KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE));
+
+        assert_eq!(
+            result,
+            InputResult::CommandWithArgs(
+                SlashCommand::Mcp,
+                "add docs npx -y docs-server".to_string()
+            )
+        );
+        assert!(composer.textarea.is_empty(), "composer should be cleared");
+    }
+
     #[test]
     fn test_multiple_pastes_submission() {
         use crossterm::event::KeyCode;
@@ -209,6 +209,15 @@ impl ListSelectionView {
         }
     }
 
+    /// Select the item shown with number `n` and accept it.
+    fn accept_numbered(&mut self, n: usize) {
+        if n == 0 || n > self.visible_len() {
+            return;
+        }
+        self.state.selected_idx = Some(n - 1);
+        self.accept();
+    }
+
     #[cfg(test)]
     pub(crate) fn set_search_query(&mut self, query: String) {
         self.search_query = query;
@@ -249,6 +258,15 @@ impl BottomPaneView for ListSelectionView {
                 self.search_query.push(c);
                 self.apply_filter();
             }
+            KeyEvent {
+                code: KeyCode::Char(c),
+                modifiers: KeyModifiers::NONE,
+                ..
+            } if c.is_ascii_digit() => {
+                if let Some(n) = c.to_digit(10) {
+                    self.accept_numbered(n as usize);
+                }
+            }
             KeyEvent {
                 code: KeyCode::Enter,
                 modifiers: KeyModifiers::NONE,
@@ -497,4 +515,44 @@ mod tests {
         let lines = render_lines(&view);
         assert!(lines.contains("▌ filters"));
     }
+
+    #[test]
+    fn number_key_accepts_matching_item() {
+        let (tx_raw, mut rx) = unbounded_channel::<AppEvent>();
+        let tx = AppEventSender::new(tx_raw);
+        let items = ["first", "second"]
+            .into_iter()
+            .map(|name| {
+                let actions: Vec<SelectionAction> = vec![Box::new(move |tx| {
+                    tx.send(AppEvent::SubmitFollowUp(name.to_string()));
+                })];
+                SelectionItem {
+                    name: name.to_string(),
+                    description: None,
+                    is_current: false,
+                    actions,
+                    dismiss_on_select: true,
+                    search_value: None,
+                }
+            })
+            .collect();
+        let mut view = ListSelectionView::new(
+            SelectionViewParams {
+                title: "Suggested follow-ups".to_string(),
+                items,
+                ..Default::default()
+            },
+            tx,
+        );
+
+        view.handle_key_event(KeyEvent::new(KeyCode::Char('9'), KeyModifiers::NONE));
+        assert!(!view.is_complete());
+
+        view.handle_key_event(KeyEvent::new(KeyCode::Char('2'), KeyModifiers::NONE));
+        assert!(view.is_complete());
+        match rx.try_recv() {
+            Ok(AppEvent::SubmitFollowUp(name)) => assert_eq!(name, "second"),
+            other => panic!("expected follow-up event, got {other:?}"),
+        }
+    }
 }
@@ -4,6 +4,7 @@ use std::path::PathBuf;
 use std::sync::Arc;
 
 use codex_core::config::Config;
+use codex_core::config_types::McpServerConfig;
 use codex_core::config_types::Notifications;
 use codex_core::git_info::current_branch_name;
 use codex_core::git_info::local_git_branches;
@@ -14,39 +15,65 @@ use codex_core::protocol::AgentReasoningEvent;
 use codex_core::protocol::AgentReasoningRawContentDeltaEvent;
 use codex_core::protocol::AgentReasoningRawContentEvent;
 use codex_core::protocol::ApplyPatchApprovalRequestEvent;
+use codex_core::protocol::AutoCompactEvent;
 use codex_core::protocol::BackgroundEventEvent;
+use codex_core::protocol::CheckpointCreatedEvent;
+use codex_core::protocol::CheckpointRewoundEvent;
+use codex_core::protocol::CompletionAssessment;
 use codex_core::protocol::ErrorEvent;
+use codex_core::protocol::EscalationRequestEvent;
 use codex_core::protocol::Event;
 use codex_core::protocol::EventMsg;
 use codex_core::protocol::ExecApprovalRequestEvent;
 use codex_core::protocol::ExecCommandBeginEvent;
+use codex_core::protocol::ExecCommandDryRunEvent;
 use codex_core::protocol::ExecCommandEndEvent;
+use codex_core::protocol::ExecSessionKilledEvent;
 use codex_core::protocol::ExitedReviewModeEvent;
+use codex_core::protocol::FollowUpSuggestion;
+use codex_core::protocol::HookBeginEvent;
 use codex_core::protocol::InputItem;
 use codex_core::protocol::InputMessageKind;
 use codex_core::protocol::ListCustomPromptsResponseEvent;
+use codex_core::protocol::McpListResourcesResponseEvent;
 use codex_core::protocol::McpListToolsResponseEvent;
+use codex_core::protocol::McpNotification;
+use codex_core::protocol::McpNotificationEvent;
 use codex_core::protocol::McpToolCallBeginEvent;
 use codex_core::protocol::McpToolCallEndEvent;
 use codex_core::protocol::Op;
 use codex_core::protocol::PatchApplyBeginEvent;
+use codex_core::protocol::PermissionConsentRequestEvent;
 use codex_core::protocol::RateLimitSnapshot;
 use codex_core::protocol::ReviewRequest;
+use codex_core::protocol::SessionDisposition;
+use codex_core::protocol::SessionEnvResponseEvent;
+use codex_core::protocol::SessionRemovedEvent;
+use codex_core::protocol::SnapshotDiffEvent;
+use codex_core::protocol::SpendLimitApprovalRequestEvent;
 use codex_core::protocol::StreamErrorEvent;
+use codex_core::protocol::SubAgentBeginEvent;
+use codex_core::protocol::SubAgentEndEvent;
 use codex_core::protocol::TaskCompleteEvent;
+use codex_core::protocol::TaskStalledEvent;
 use codex_core::protocol::TokenUsage;
 use codex_core::protocol::TokenUsageInfo;
+use codex_core::protocol::ToolCallCancelledEvent;
 use codex_core::protocol::TurnAbortReason;
 use codex_core::protocol::TurnDiffEvent;
+use codex_core::protocol::UndoCompletedEvent;
 use codex_core::protocol::UserMessageEvent;
+use codex_core::protocol::WarningEvent;
 use codex_core::protocol::WebSearchBeginEvent;
 use codex_core::protocol::WebSearchEndEvent;
+use codex_protocol::config_types::StallAction;
 use codex_protocol::mcp_protocol::ConversationId;
 use codex_protocol::parse_command::ParsedCommand;
 use crossterm::event::KeyCode;
 use crossterm::event::KeyEvent;
 use crossterm::event::KeyEventKind;
 use crossterm::event::KeyModifiers;
+use mcp_types::LoggingLevel;
 use rand::Rng;
 use ratatui::buffer::Buffer;
 use ratatui::layout::Constraint;
@@ -80,6 +107,8 @@ use crate::history_cell::McpToolCallCell;
 use crate::history_cell::PatchEventType;
 use crate::history_cell::RateLimitSnapshotDisplay;
 use crate::markdown::append_markdown;
+use crate::session_summary::SessionStats;
+use crate::session_summary::SessionSummary;
 use crate::slash_command::SlashCommand;
 use crate::text_formatting::truncate_text;
 use crate::tui::FrameRequester;
@@ -100,19 +129,13 @@ use codex_common::approval_presets::ApprovalPreset;
 use codex_common::approval_presets::builtin_approval_presets;
 use codex_common::model_presets::ModelPreset;
 use codex_common::model_presets::builtin_model_presets;
+use codex_common::summarize_sandbox_policy;
 use codex_core::AuthManager;
 use codex_core::ConversationManager;
 use codex_core::protocol::AskForApproval;
 use codex_core::protocol::SandboxPolicy;
 use codex_core::protocol_config_types::ReasoningEffort as ReasoningEffortConfig;
 use codex_file_search::FileMatch;
-use codex_git_tooling::CreateGhostCommitOptions;
-use codex_git_tooling::GhostCommit;
-use codex_git_tooling::GitToolingError;
-use codex_git_tooling::create_ghost_commit;
-use codex_git_tooling::restore_ghost_commit;
-
-const MAX_TRACKED_GHOST_COMMITS: usize = 20;
 
 // Track information about an in-flight exec command.
 struct RunningCommand {
@@ -239,6 +262,8 @@ pub(crate) struct ChatWidget {
     // Accumulates full reasoning content for transcript-only recording
     full_reasoning_buffer: String,
     conversation_id: Option<ConversationId>,
+    // Rollout file of the current session, used by `/transcript`
+    rollout_path: Option<PathBuf>,
     frame_requester: FrameRequester,
     // Whether to include the initial welcome banner on session configured
     show_welcome_banner: bool,
@@ -247,13 +272,14 @@ pub(crate) struct ChatWidget {
     suppress_session_configured_redraw: bool,
     // User messages queued while a turn is in progress
     queued_user_messages: VecDeque<UserMessage>,
+    // Checkpoints taken before each submitted message, offered by `/rewind`
+    checkpoints: Vec<CheckpointCreatedEvent>,
     // Pending notification to show when unfocused on next Draw
     pending_notification: Option<Notification>,
     // Simple review mode flag; used to adjust layout and banners.
     is_review_mode: bool,
-    // List of ghost commits corresponding to each turn.
-    ghost_snapshots: Vec<GhostCommit>,
-    ghost_snapshots_disabled: bool,
+    // Totals for the summary printed on exit
+    session_stats: SessionStats,
 }
 
 struct UserMessage {
@@ -292,6 +318,7 @@ impl ChatWidget {
         self.bottom_pane
             .set_history_metadata(event.history_log_id, event.history_entry_count);
         self.conversation_id = Some(event.session_id);
+        self.rollout_path = Some(event.rollout_path.clone());
         let initial_messages = event.initial_messages.clone();
         let model_for_header = event.model.clone();
         self.session_header.set_model(&model_for_header);
@@ -367,7 +394,13 @@ impl ChatWidget {
 
     // Raw reasoning uses the same flow as summarized reasoning
 
-    fn on_task_started(&mut self) {
+    fn on_task_started(&mut self, branch: Option<String>) {
+        if let Some(branch) = branch {
+            self.add_to_history(history_cell::new_info_event(
+                format!("Working on branch {branch}"),
+                None,
+            ));
+        }
         self.bottom_pane.clear_ctrl_c_quit_hint();
         self.bottom_pane.set_task_running(true);
         self.full_reasoning_buffer.clear();
@@ -375,16 +408,30 @@ impl ChatWidget {
         self.request_redraw();
     }
 
-    fn on_task_complete(&mut self, last_agent_message: Option<String>) {
+    fn on_task_complete(
+        &mut self,
+        last_agent_message: Option<String>,
+        assessment: Option<CompletionAssessment>,
+    ) {
         // If a stream is currently active, finalize it.
         self.flush_answer_stream_with_separator();
         // Mark task stopped and request redraw now that all content is in history.
         self.bottom_pane.set_task_running(false);
         self.running_commands.clear();
+        if let Some(assessment) = &assessment {
+            self.add_to_history(history_cell::new_completion_assessment(assessment));
+        }
         self.request_redraw();
 
         // If there is a queued user message, send exactly one now to begin the next turn.
-        self.maybe_send_next_queued_input();
+        // Otherwise offer the suggested follow-ups, if any.
+        if self.queued_user_messages.is_empty() {
+            if let Some(assessment) = assessment {
+                self.open_follow_ups_popup(assessment.follow_ups);
+            }
+        } else {
+            self.maybe_send_next_queued_input();
+        }
         // Emit a notification when the turn completes (suppressed if focused).
         self.notify(Notification::AgentTurnComplete {
             response: last_agent_message.unwrap_or_default(),
@@ -499,6 +546,33 @@ impl ChatWidget {
         );
     }
 
+    fn on_escalation_request(&mut self, id: String, ev: EscalationRequestEvent) {
+        let id2 = id.clone();
+        let ev2 = ev.clone();
+        self.defer_or_handle(
+            |q| q.push_escalation(id, ev),
+            |s| s.handle_escalation_now(id2, ev2),
+        );
+    }
+
+    fn on_permission_consent_request(&mut self, id: String, ev: PermissionConsentRequestEvent) {
+        let id2 = id.clone();
+        let ev2 = ev.clone();
+        self.defer_or_handle(
+            |q| q.push_permission_consent(id, ev),
+            |s| s.handle_permission_consent_now(id2, ev2),
+        );
+    }
+
+    fn on_spend_limit_approval_request(&mut self, id: String, ev: SpendLimitApprovalRequestEvent) {
+        let id2 = id.clone();
+        let ev2 = ev.clone();
+        self.defer_or_handle(
+            |q| q.push_spend_limit_approval(id, ev),
+            |s| s.handle_spend_limit_approval_now(id2, ev2),
+        );
+    }
+
     fn on_apply_patch_approval_request(&mut self, id: String, ev: ApplyPatchApprovalRequestEvent) {
         let id2 = id.clone();
         let ev2 = ev.clone();
@@ -509,6 +583,7 @@ impl ChatWidget {
     }
 
     fn on_exec_command_begin(&mut self, ev: ExecCommandBeginEvent) {
+        self.session_stats.record_command();
         self.flush_answer_stream_with_separator();
         let ev2 = ev.clone();
         self.defer_or_handle(|q| q.push_exec_begin(ev), |s| s.handle_exec_begin_now(ev2));
@@ -554,6 +629,57 @@ impl ChatWidget {
         self.defer_or_handle(|q| q.push_mcp_end(ev), |s| s.handle_mcp_end_now(ev2));
     }
 
+    fn on_mcp_notification(&mut self, ev: McpNotificationEvent) {
+        let McpNotificationEvent {
+            server,
+            notification,
+        } = ev;
+        match notification {
+            McpNotification::Progress {
+                call_id,
+                progress,
+                total,
+                message,
+            } => {
+                if let Some(cell) = self
+                    .active_cell
+                    .as_mut()
+                    .and_then(|cell| cell.as_any_mut().downcast_mut::<McpToolCallCell>())
+                    && cell.call_id() == call_id
+                {
+                    cell.update_progress(progress, total, message);
+                    self.request_redraw();
+                }
+            }
+            McpNotification::Log {
+                level,
+                logger,
+                data,
+            } => {
+                let text = match data {
+                    serde_json::Value::String(text) => text,
+                    other => other.to_string(),
+                };
+                let source = match logger {
+                    Some(logger) => format!("{server}/{logger}"),
+                    None => server,
+                };
+                match level {
+                    LoggingLevel::Debug | LoggingLevel::Info | LoggingLevel::Notice => {
+                        debug!("MCP server {source}: {text}");
+                    }
+                    LoggingLevel::Warning
+                    | LoggingLevel::Error
+                    | LoggingLevel::Critical
+                    | LoggingLevel::Alert
+                    | LoggingLevel::Emergency => {
+                        self.on_warning(format!("MCP server `{source}`: {text}"));
+                    }
+                }
+            }
+        }
+    }
+
     fn on_web_search_begin(&mut self, _ev: WebSearchBeginEvent) {
         self.flush_answer_stream_with_separator();
     }
@@ -566,6 +692,12 @@ impl ChatWidget {
         )));
     }
 
+    fn on_exec_command_dry_run(&mut self, ev: ExecCommandDryRunEvent) {
+        self.flush_answer_stream_with_separator();
+        self.add_to_history(history_cell::new_dry_run_command(&ev.command));
+        self.request_redraw();
+    }
+
     fn on_get_history_entry_response(
         &mut self,
         event: codex_core::protocol::GetHistoryEntryResponseEvent,
@@ -579,18 +711,104 @@ impl ChatWidget {
             .on_history_entry_response(log_id, offset, entry.map(|e| e.text));
     }
 
+    fn on_session_removed(&mut self, ev: SessionRemovedEvent) {
+        let (message, hint) = match ev.disposition {
+            SessionDisposition::Archived => {
+                (format!("Session archived to {}", ev.path.display()), None)
+            }
+            SessionDisposition::Trashed => (
+                "Session moved to the trash".to_string(),
+                Some(format!(
+                    "restore it with `codex resume {}`",
+                    ev.conversation_id
+                )),
+            ),
+        };
+        self.add_info_message(message, hint);
+    }
+
     fn on_shutdown_complete(&mut self) {
         self.app_event_tx.send(AppEvent::ExitRequest);
     }
 
     fn on_turn_diff(&mut self, unified_diff: String) {
         debug!("TurnDiffEvent: {unified_diff}");
+        self.session_stats.record_turn_diff(&unified_diff);
+    }
+
+    fn on_warning(&mut self, message: String) {
+        self.add_to_history(history_cell::new_warning_event(message));
+        self.request_redraw();
+    }
+
+    fn on_hook_begin(&mut self, ev: HookBeginEvent) {
+        let header = match ev.tool {
+            Some(tool) => format!("Running {} hook for {tool}", ev.hook),
+            None => format!("Running {} hook", ev.hook),
+        };
+        self.bottom_pane.update_status_header(header);
+    }
+
+    fn on_hook_end(&mut self) {
+        self.bottom_pane
+            .update_status_header(String::from("Working"));
+    }
+
+    fn on_sub_agent_begin(&mut self, ev: SubAgentBeginEvent) {
+        self.flush_answer_stream_with_separator();
+        self.add_to_history(history_cell::new_sub_agent_begin(&ev));
+        self.bottom_pane
+            .update_status_header(format!("Waiting for {}", ev.agent_id));
+        self.request_redraw();
+    }
+
+    fn on_sub_agent_end(&mut self, ev: SubAgentEndEvent) {
+        self.add_to_history(history_cell::new_sub_agent_end(&ev));
+        self.bottom_pane
+            .update_status_header(String::from("Working"));
+        self.request_redraw();
     }
 
     fn on_background_event(&mut self, message: String) {
         debug!("BackgroundEvent: {message}");
     }
 
+    fn on_task_stalled(&mut self, ev: TaskStalledEvent) {
+        let what = match &ev.failing_tool {
+            Some(tool_name) => format!(
+                "`{tool_name}` kept failing with the same arguments for {} turns",
+                ev.repeated_turns
+            ),
+            None => format!(
+                "Codex repeated the same tool calls for {} turns without making progress",
+                ev.repeated_turns
+            ),
+        };
+        let message = match ev.action {
+            StallAction::Pause => {
+                format!("{what}, so Codex paused. Send a message to steer it.")
+            }
+            StallAction::Recover => {
+                format!("{what}; asking Codex to try a different approach.")
+            }
+        };
+        self.add_to_history(history_cell::new_warning_event(message));
+        self.request_redraw();
+    }
+
+    fn on_auto_compact(&mut self, ev: AutoCompactEvent) {
+        let hint = (ev.preserved_messages > 0)
+            .then(|| format!("keeping the last {} messages", ev.preserved_messages));
+        self.add_to_history(history_cell::new_info_event(
+            format!(
+                "Context is nearly full ({} of {} tokens); summarizing the conversation",
+                ev.tokens_in_context_window, ev.token_limit
+            ),
+            hint,
+        ));
+        self.request_redraw();
+    }
+
     fn on_stream_error(&mut self, message: String) {
         // Show stream errors in the transcript so users see retry/backoff info.
         self.add_to_history(history_cell::new_stream_error_event(message));
@@ -729,6 +947,53 @@ impl ChatWidget {
         self.request_redraw();
     }
 
+    pub(crate) fn handle_escalation_now(&mut self, id: String, ev: EscalationRequestEvent) {
+        self.flush_answer_stream_with_separator();
+        self.add_to_history(history_cell::new_proposed_command(&ev.command));
+        let command = shlex::try_join(ev.command.iter().map(String::as_str))
+            .unwrap_or_else(|_| ev.command.join(" "));
+        self.notify(Notification::ExecApprovalRequested { command });
+
+        let request = ApprovalRequest::Escalation {
+            id,
+            command: ev.command,
+            justification: ev.justification,
+            requested_permissions: ev.requested_permissions,
+        };
+        self.bottom_pane.push_approval_request(request);
+        self.request_redraw();
+    }
+
+    pub(crate) fn handle_permission_consent_now(
+        &mut self,
+        id: String,
+        ev: PermissionConsentRequestEvent,
+    ) {
+        self.flush_answer_stream_with_separator();
+        let request = ApprovalRequest::Permission {
+            id,
+            permission: ev.permission,
+            detail: ev.detail,
+        };
+        self.bottom_pane.push_approval_request(request);
+        self.request_redraw();
+    }
+
+    pub(crate) fn handle_spend_limit_approval_now(
+        &mut self,
+        id: String,
+        ev: SpendLimitApprovalRequestEvent,
+    ) {
+        self.flush_answer_stream_with_separator();
+        let request = ApprovalRequest::SpendLimit {
+            id,
+            estimated_cost_usd: ev.estimated_cost_usd,
+            limit_usd: ev.limit_usd,
+        };
+        self.bottom_pane.push_approval_request(request);
+        self.request_redraw();
+    }
+
     pub(crate) fn handle_apply_patch_approval_now(
         &mut self,
         id: String,
@@ -745,6 +1010,8 @@ impl ChatWidget {
             id,
             reason: ev.reason,
             grant_root: ev.grant_root,
+            stats: ev.stats,
+            rejected_paths: ev.rejected_paths,
         };
         self.bottom_pane.push_approval_request(request);
         self.request_redraw();
@@ -804,6 +1071,7 @@ impl ChatWidget {
             invocation,
             duration,
             result,
+            output_schema: _,
         } = ev;
 
         let extra_cell = match self
@@ -886,6 +1154,7 @@ impl ChatWidget {
                 initial_images,
             ),
             token_info: None,
+            session_stats: SessionStats::new(),
             rate_limit_snapshot: None,
             rate_limit_warnings: RateLimitWarningState::default(),
             stream_controller: None,
@@ -895,13 +1164,13 @@ impl ChatWidget {
             reasoning_buffer: String::new(),
             full_reasoning_buffer: String::new(),
             conversation_id: None,
+            rollout_path: None,
             queued_user_messages: VecDeque::new(),
+            checkpoints: Vec::new(),
             show_welcome_banner: true,
             suppress_session_configured_redraw: false,
             pending_notification: None,
             is_review_mode: false,
-            ghost_snapshots: Vec::new(),
-            ghost_snapshots_disabled: true,
         }
     }
 
@@ -947,6 +1216,7 @@ impl ChatWidget {
                 initial_images,
             ),
             token_info: None,
+            session_stats: SessionStats::new(),
             rate_limit_snapshot: None,
             rate_limit_warnings: RateLimitWarningState::default(),
             stream_controller: None,
@@ -956,13 +1226,13 @@ impl ChatWidget {
             reasoning_buffer: String::new(),
             full_reasoning_buffer: String::new(),
             conversation_id: None,
+            rollout_path: None,
             queued_user_messages: VecDeque::new(),
+            checkpoints: Vec::new(),
             show_welcome_banner: true,
             suppress_session_configured_redraw: true,
             pending_notification: None,
             is_review_mode: false,
-            ghost_snapshots: Vec::new(),
-            ghost_snapshots_disabled: true,
         }
     }
 
@@ -1034,6 +1304,9 @@ impl ChatWidget {
                     InputResult::Command(cmd) => {
                         self.dispatch_command(cmd);
                     }
+                    InputResult::CommandWithArgs(cmd, args) => {
+                        self.dispatch_command_with_args(cmd, args);
+                    }
                     InputResult::None => {}
                 }
             }
@@ -1096,7 +1369,10 @@ impl ChatWidget {
                 self.app_event_tx.send(AppEvent::ExitRequest);
             }
             SlashCommand::Undo => {
-                self.undo_last_snapshot();
+                self.submit_op(Op::UndoLastChange);
+            }
+            SlashCommand::Rewind => {
+                self.open_rewind_popup();
             }
             SlashCommand::Diff => {
                 self.add_diff_in_progress();
@@ -1115,15 +1391,49 @@ impl ChatWidget {
                     tx.send(AppEvent::DiffResult(text));
                 });
             }
+            SlashCommand::Changes => {
+                self.add_diff_in_progress();
+                self.submit_op(Op::SnapshotDiff {
+                    from: None,
+                    to: None,
+                });
+            }
+            SlashCommand::Transcript => match &self.rollout_path {
+                Some(path) => {
+                    self.app_event_tx
+                        .send(AppEvent::OpenRolloutTranscript(path.clone()));
+                }
+                None => {
+                    self.add_to_history(history_cell::new_error_event(
+                        "The transcript is available once the session has started.".to_string(),
+                    ));
+                    self.request_redraw();
+                }
+            },
             SlashCommand::Mention => {
                 self.insert_str("@");
             }
             SlashCommand::Status => {
                 self.add_status_output();
             }
+            SlashCommand::Env => {
+                self.submit_op(Op::GetSessionEnv);
+            }
+            SlashCommand::Context => {
+                self.submit_op(Op::GetNextPromptPreview);
+            }
             SlashCommand::Mcp => {
                 self.add_mcp_output();
             }
+            SlashCommand::Steer => {
+                self.add_error_message("Usage: /steer <guidance for the running task>".to_string());
+            }
+            SlashCommand::Archive => {
+                self.submit_op(Op::ArchiveSession);
+            }
+            SlashCommand::Delete => {
+                self.submit_op(Op::DeleteSession);
+            }
             #[cfg(debug_assertions)]
             SlashCommand::TestApproval => {
                 use codex_core::protocol::EventMsg;
@@ -1131,6 +1441,7 @@ impl ChatWidget {
 
                 use codex_core::protocol::ApplyPatchApprovalRequestEvent;
                 use codex_core::protocol::FileChange;
+                use codex_core::protocol::PatchStats;
 
                 self.app_event_tx.send(AppEvent::CodexEvent(Event {
                     id: "1".to_string(),
@@ -1159,12 +1470,76 @@ impl ChatWidget {
                         ]),
                         reason: None,
                         grant_root: Some(PathBuf::from("/tmp")),
+                        stats: PatchStats::default(),
+                        rejected_paths: Vec::new(),
                     }),
                 }));
             }
         }
     }
 
+    fn dispatch_command_with_args(&mut self, cmd: SlashCommand, args: String) {
+        match cmd {
+            SlashCommand::Mcp => self.handle_mcp_command(&args),
+            SlashCommand::Steer => self.submit_steering(args),
+            _ => self.dispatch_command(cmd),
+        }
+    }
+
+    /// `/steer <text>` hands guidance to the running task at its next tool
+    /// call instead of queueing it for after the task.
+    fn submit_steering(&mut self, text: String) {
+        self.submit_op(Op::Steer {
+            items: vec![InputItem::Text { text: text.clone() }],
+        });
+        self.add_to_history(history_cell::new_user_prompt(text));
+    }
+
+    /// `/mcp add <name> <command|url> [args...]` starts a server for the rest
+    /// of the session; `/mcp remove <name>` stops one. Neither touches
+    /// `config.toml`.
+    fn handle_mcp_command(&mut self, args: &str) {
+        let words = shlex::split(args).unwrap_or_default();
+        match words.as_slice() {
+            [action, name, target, rest @ ..] if action == "add" => {
+                let is_url = target.starts_with("http://") || target.starts_with("https://");
+                let config = if is_url && rest.is_empty() {
+                    serde_json::json!({ "url": target })
+                } else if is_url {
+                    self.add_error_message(format!(
+                        "MCP server URLs take no arguments: {}",
+                        rest.join(" ")
+                    ));
+                    return;
+                } else {
+                    serde_json::json!({ "command": target, "args": rest })
+                };
+                match serde_json::from_value::<McpServerConfig>(config.clone()) {
+                    Ok(server) => {
+                        // Keep the local copy in sync so `/mcp` lists the server.
+                        self.config.mcp_servers.insert(name.clone(), server);
+                    }
+                    Err(e) => {
+                        self.add_error_message(format!("Invalid MCP server `{name}`: {e}"));
+                        return;
+                    }
+                }
+                self.add_info_message(format!("Starting MCP server `{name}`..."), None);
+                self.submit_op(Op::AddMcpServer {
+                    name: name.clone(),
+                    config,
+                });
+            }
+            [action, name] if action == "remove" => {
+                self.config.mcp_servers.remove(name);
+                self.submit_op(Op::RemoveMcpServer { name: name.clone() });
+            }
+            _ => self.add_error_message(
+                "Usage: /mcp add <name> <command|url> [args...] or /mcp remove <name>".to_string(),
+            ),
+        }
+    }
+
     pub(crate) fn handle_paste(&mut self, text: String) {
         self.bottom_pane.handle_paste(text);
     }
@@ -1211,14 +1586,22 @@ impl ChatWidget {
             return;
         }
 
-        self.capture_ghost_snapshot();
-
         let mut items: Vec<InputItem> = Vec::new();
 
         if !text.is_empty() {
             items.push(InputItem::Text { text: text.clone() });
         }
 
+        // Save the point before this message so `/rewind` can return to it.
+        self.codex_op_tx
+            .send(Op::Checkpoint {
+                label: (!text.is_empty()).then(|| text.clone()),
+                include_workspace: true,
+            })
+            .unwrap_or_else(|e| {
+                tracing::error!("failed to send Checkpoint op: {e}");
+            });
+
         for path in image_paths {
             items.push(InputItem::LocalImage { path });
         }
@@ -1244,55 +1627,78 @@ impl ChatWidget {
         }
     }
 
-    fn capture_ghost_snapshot(&mut self) {
-        if self.ghost_snapshots_disabled {
-            return;
-        }
-
-        let options = CreateGhostCommitOptions::new(&self.config.cwd);
-        match create_ghost_commit(&options) {
-            Ok(commit) => {
-                self.ghost_snapshots.push(commit);
-                if self.ghost_snapshots.len() > MAX_TRACKED_GHOST_COMMITS {
-                    self.ghost_snapshots.remove(0);
-                }
+    fn on_undo_completed(&mut self, ev: UndoCompletedEvent) {
+        match ev.snapshot_id {
+            Some(snapshot_id) => {
+                self.add_to_history(history_cell::new_undo_summary(
+                    &snapshot_id,
+                    &ev.reverted_files,
+                ));
+                self.request_redraw();
             }
-            Err(err) => {
-                self.ghost_snapshots_disabled = true;
-                let (message, hint) = match &err {
-                    GitToolingError::NotAGitRepository { .. } => (
-                        "Snapshots disabled: current directory is not a Git repository."
-                            .to_string(),
-                        None,
-                    ),
-                    _ => (
-                        format!("Snapshots disabled after error: {err}"),
-                        Some(
-                            "Restart Codex after resolving the issue to re-enable snapshots."
-                                .to_string(),
-                        ),
-                    ),
-                };
-                self.add_info_message(message, hint);
-                tracing::warn!("failed to create ghost snapshot: {err}");
+            None => {
+                let message = ev.message.unwrap_or_else(|| "Nothing to undo.".to_string());
+                self.add_info_message(message, None);
             }
         }
     }
 
-    fn undo_last_snapshot(&mut self) {
-        let Some(commit) = self.ghost_snapshots.pop() else {
-            self.add_info_message("No snapshot available to undo.".to_string(), None);
+    fn on_checkpoint_rewound(&mut self, ev: CheckpointRewoundEvent) {
+        if !ev.rewound {
+            let message = ev
+                .message
+                .unwrap_or_else(|| "Could not rewind the conversation.".to_string());
+            self.add_info_message(message, None);
             return;
+        }
+        let turn = self
+            .checkpoints
+            .iter()
+            .position(|checkpoint| checkpoint.checkpoint_id == ev.checkpoint_id);
+        if let Some(index) = turn {
+            self.checkpoints.truncate(index);
+        }
+        let message = match turn {
+            Some(index) => format!("Rewound to before message {}", index + 1),
+            None => "Rewound the conversation".to_string(),
         };
-
-        if let Err(err) = restore_ghost_commit(&self.config.cwd, &commit) {
-            self.add_error_message(format!("Failed to restore snapshot: {err}"));
-            self.ghost_snapshots.push(commit);
-            return;
+        let hint = (!ev.restored_files.is_empty())
+            .then(|| format!("restored {} file(s)", ev.restored_files.len()));
+        self.add_info_message(message, hint);
+        // Offer the message that was sent from this point for editing.
+        if let Some(label) = ev.label {
+            self.set_composer_text(label);
         }
+    }
 
-        let short_id: String = commit.id().chars().take(8).collect();
-        self.add_info_message(format!("Restored workspace to snapshot {short_id}"), None);
+    fn on_exec_session_killed(&mut self, ev: ExecSessionKilledEvent) {
+        let message = if ev.killed {
+            format!("Killed command session {}.", ev.session_id)
+        } else {
+            format!("Command session {} is not running.", ev.session_id)
+        };
+        self.add_info_message(message, None);
+    }
+
+    fn on_tool_call_cancelled(&mut self, ev: ToolCallCancelledEvent) {
+        let message = if ev.cancelled {
+            format!("Cancelled tool call {}.", ev.call_id)
+        } else {
+            format!("Tool call {} is not running.", ev.call_id)
+        };
+        self.add_info_message(message, None);
+    }
+
+    fn on_snapshot_diff(&mut self, ev: SnapshotDiffEvent) {
+        match ev.message {
+            Some(message) => {
+                self.on_diff_complete();
+                self.add_info_message(message, None);
+            }
+            None => self
+                .app_event_tx
+                .send(AppEvent::DiffResult(ev.unified_diff)),
+        }
     }
 
     /// Replay a subset of initial events into the UI to seed the transcript when
@@ -1311,6 +1717,12 @@ impl ChatWidget {
     }
 
     pub(crate) fn handle_codex_event(&mut self, event: Event) {
+        // A `delegate` child's answer reaches the parent as the tool output
+        // and its token usage is not the session's; only its tool calls are
+        // shown, after the delegation cell.
+        if event.sub_agent_id().is_some() && is_sub_agent_chatter(&event.msg) {
+            return;
+        }
         let Event { id, msg } = event;
         self.dispatch_event_msg(Some(id), msg, false);
     }
@@ -1346,15 +1758,20 @@ impl ChatWidget {
                 self.on_agent_reasoning_final()
             }
             EventMsg::AgentReasoningSectionBreak(_) => self.on_reasoning_section_break(),
-            EventMsg::TaskStarted(_) => self.on_task_started(),
-            EventMsg::TaskComplete(TaskCompleteEvent { last_agent_message }) => {
-                self.on_task_complete(last_agent_message)
-            }
+            EventMsg::TaskStarted(ev) => self.on_task_started(ev.branch),
+            EventMsg::TaskComplete(TaskCompleteEvent {
+                last_agent_message,
+                assessment,
+                ..
+            }) => self.on_task_complete(last_agent_message, assessment),
+            EventMsg::TaskStalled(ev) => self.on_task_stalled(ev),
+            EventMsg::AutoCompact(ev) => self.on_auto_compact(ev),
             EventMsg::TokenCount(ev) => {
                 self.set_token_info(ev.info);
                 self.on_rate_limit_snapshot(ev.rate_limits);
             }
             EventMsg::Error(ErrorEvent { message }) => self.on_error(message),
+            EventMsg::Warning(WarningEvent { message }) => self.on_warning(message),
             EventMsg::TurnAborted(ev) => match ev.reason {
                 TurnAbortReason::Interrupted => {
                     self.on_interrupted_turn(ev.reason);
@@ -1365,6 +1782,12 @@ impl ChatWidget {
                 TurnAbortReason::ReviewEnded => {
                     self.on_interrupted_turn(ev.reason);
                 }
+                TurnAbortReason::BudgetExceeded => {
+                    let summary = ev
+                        .budget_summary()
+                        .unwrap_or_else(|| "budget exceeded".to_string());
+                    self.on_error(format!("Task stopped: {summary}"))
+                }
             },
             EventMsg::PlanUpdate(update) => self.on_plan_update(update),
             EventMsg::ExecApprovalRequest(ev) => {
@@ -1374,18 +1797,46 @@ impl ChatWidget {
             EventMsg::ApplyPatchApprovalRequest(ev) => {
                 self.on_apply_patch_approval_request(id.unwrap_or_default(), ev)
             }
+            EventMsg::EscalationRequest(ev) => {
+                self.on_escalation_request(id.unwrap_or_default(), ev)
+            }
+            EventMsg::PermissionConsentRequest(ev) => {
+                self.on_permission_consent_request(id.unwrap_or_default(), ev)
+            }
+            EventMsg::SpendLimitApprovalRequest(ev) => {
+                self.on_spend_limit_approval_request(id.unwrap_or_default(), ev)
+            }
             EventMsg::ExecCommandBegin(ev) => self.on_exec_command_begin(ev),
             EventMsg::ExecCommandOutputDelta(delta) => self.on_exec_command_output_delta(delta),
             EventMsg::PatchApplyBegin(ev) => self.on_patch_apply_begin(ev),
             EventMsg::PatchApplyEnd(ev) => self.on_patch_apply_end(ev),
+            EventMsg::HookBegin(ev) => self.on_hook_begin(ev),
+            EventMsg::HookEnd(_) => self.on_hook_end(),
+            EventMsg::SubAgentBegin(ev) => self.on_sub_agent_begin(ev),
+            EventMsg::SubAgentEnd(ev) => self.on_sub_agent_end(ev),
             EventMsg::ExecCommandEnd(ev) => self.on_exec_command_end(ev),
+            EventMsg::ExecCommandDryRun(ev) => self.on_exec_command_dry_run(ev),
             EventMsg::McpToolCallBegin(ev) => self.on_mcp_tool_call_begin(ev),
             EventMsg::McpToolCallEnd(ev) => self.on_mcp_tool_call_end(ev),
+            EventMsg::McpNotification(ev) => self.on_mcp_notification(ev),
             EventMsg::WebSearchBegin(ev) => self.on_web_search_begin(ev),
             EventMsg::WebSearchEnd(ev) => self.on_web_search_end(ev),
             EventMsg::GetHistoryEntryResponse(ev) => self.on_get_history_entry_response(ev),
             EventMsg::McpListToolsResponse(ev) => self.on_list_mcp_tools(ev),
+            EventMsg::McpListResourcesResponse(ev) => self.on_list_mcp_resources(ev),
             EventMsg::ListCustomPromptsResponse(ev) => self.on_list_custom_prompts(ev),
+            EventMsg::SessionEnvResponse(ev) => self.on_session_env(ev),
+            EventMsg::NextPromptPreview(ev) => {
+                self.app_event_tx.send(AppEvent::OpenPromptPreview(ev));
+            }
+            EventMsg::UndoCompleted(ev) => self.on_undo_completed(ev),
+            EventMsg::CheckpointCreated(ev) => self.checkpoints.push(ev),
+            EventMsg::CheckpointRewound(ev) => self.on_checkpoint_rewound(ev),
+            // The TUI queues messages itself and only submits them once idle.
+            EventMsg::TaskQueueUpdated(_) => {}
+            // The TUI does not request snapshot listings.
+            EventMsg::ListSnapshotsResponse(_) => {}
+            EventMsg::SnapshotDiff(ev) => self.on_snapshot_diff(ev),
             EventMsg::ShutdownComplete => self.on_shutdown_complete(),
             EventMsg::TurnDiff(TurnDiffEvent { unified_diff }) => self.on_turn_diff(unified_diff),
             EventMsg::BackgroundEvent(BackgroundEventEvent { message }) => {
@@ -1401,6 +1852,9 @@ impl ChatWidget {
                 self.app_event_tx
                     .send(crate::app_event::AppEvent::ConversationHistory(ev));
             }
+            EventMsg::SessionRemoved(ev) => self.on_session_removed(ev),
+            EventMsg::ExecSessionKilled(ev) => self.on_exec_session_killed(ev),
+            EventMsg::ToolCallCancelled(ev) => self.on_tool_call_cancelled(ev),
             EventMsg::EnteredReviewMode(review_request) => {
                 self.on_entered_review_mode(review_request)
             }
@@ -1477,7 +1931,9 @@ impl ChatWidget {
     }
 
     fn notify(&mut self, notification: Notification) {
-        if !notification.allowed_for(&self.config.tui_notifications) {
+        if !notification.allowed_for(&self.config.tui_notifications)
+            && !notification.allowed_for(&self.config.tui_bell)
+        {
             return;
         }
         self.pending_notification = Some(notification);
@@ -1485,9 +1941,28 @@ impl ChatWidget {
     }
 
     pub(crate) fn maybe_post_pending_notification(&mut self, tui: &mut crate::tui::Tui) {
-        if let Some(notif) = self.pending_notification.take() {
+        let Some(notif) = self.pending_notification.take() else {
+            return;
+        };
+        if notif.allowed_for(&self.config.tui_notifications) {
             tui.notify(notif.display());
         }
+        if !notif.allowed_for(&self.config.tui_bell) || self.in_quiet_hours() {
+            return;
+        }
+        match &self.config.tui_sound_command {
+            Some(command) if !tui.is_focused() => play_sound(command, notif.type_name()),
+            Some(_) => {}
+            None => {
+                tui.ring_bell(notif.bell_count());
+            }
+        }
+    }
+
+    fn in_quiet_hours(&self) -> bool {
+        self.config
+            .tui_quiet_hours
+            .is_some_and(|quiet_hours| quiet_hours.contains(Local::now().time()))
     }
 
     /// Mark the active cell as failed (✗) and flush it into history.
@@ -1569,9 +2044,11 @@ impl ChatWidget {
                     cwd: None,
                     approval_policy: None,
                     sandbox_policy: None,
+                    sandbox_preset: None,
                     model: Some(model_slug.clone()),
                     effort: Some(effort),
                     summary: None,
+                    dry_run: None,
                 }));
                 tx.send(AppEvent::UpdateModel(model_slug.clone()));
                 tx.send(AppEvent::UpdateReasoningEffort(effort));
@@ -1601,6 +2078,48 @@ impl ChatWidget {
             });
         }
 
+        // Aliases are sent by name so that core resolves their provider, and
+        // persisted by name so that editing the alias updates this choice.
+        let mut aliases: Vec<_> = self.config.model_aliases.iter().collect();
+        aliases.sort_by(|a, b| a.0.cmp(b.0));
+        for (alias_name, alias) in aliases {
+            let description = match alias.model_reasoning_effort {
+                Some(effort) => format!("{} ({effort})", alias.model),
+                None => alias.model.clone(),
+            };
+            let name = alias_name.clone();
+            let alias_name = alias_name.clone();
+            let model_slug = alias.model.clone();
+            let effort = alias.model_reasoning_effort.or(current_effort);
+            let is_current = model_slug == current_model && effort == current_effort;
+            let actions: Vec<SelectionAction> = vec![Box::new(move |tx| {
+                tx.send(AppEvent::CodexOp(Op::OverrideTurnContext {
+                    cwd: None,
+                    approval_policy: None,
+                    sandbox_policy: None,
+                    sandbox_preset: None,
+                    model: Some(alias_name.clone()),
+                    effort: None,
+                    summary: None,
+                    dry_run: None,
+                }));
+                tx.send(AppEvent::UpdateModel(model_slug.clone()));
+                tx.send(AppEvent::UpdateReasoningEffort(effort));
+                tx.send(AppEvent::PersistModelSelection {
+                    model: alias_name.clone(),
+                    effort,
+                });
+            })];
+            items.push(SelectionItem {
+                name,
+                description: Some(description),
+                is_current,
+                actions,
+                dismiss_on_select: true,
+                search_value: None,
+            });
+        }
+
         self.bottom_pane.show_selection_view(SelectionViewParams {
             title: "Select model and reasoning level".to_string(),
             subtitle: Some(
@@ -1612,6 +2131,78 @@ impl ChatWidget {
         });
     }
 
+    /// Offer the follow-ups suggested when the last task completed. Each one can
+    /// be started with its number key.
+    fn open_follow_ups_popup(&mut self, follow_ups: Vec<FollowUpSuggestion>) {
+        if follow_ups.is_empty() {
+            return;
+        }
+        let items = follow_ups
+            .into_iter()
+            .map(|follow_up| {
+                let prompt = follow_up.prompt.clone();
+                let actions: Vec<SelectionAction> = vec![Box::new(move |tx| {
+                    tx.send(AppEvent::SubmitFollowUp(prompt.clone()));
+                })];
+                SelectionItem {
+                    name: follow_up.label,
+                    description: Some(follow_up.prompt),
+                    is_current: false,
+                    actions,
+                    dismiss_on_select: true,
+                    search_value: None,
+                }
+            })
+            .collect();
+
+        self.bottom_pane.show_selection_view(SelectionViewParams {
+            title: "Suggested follow-ups".to_string(),
+            subtitle: Some("Press a number to send a follow-up, or Esc to dismiss".to_string()),
+            footer_hint: Some(STANDARD_POPUP_HINT_LINE.to_string()),
+            items,
+            ..Default::default()
+        });
+    }
+
+    /// Offer to rewind the conversation, and the workspace where it was
+    /// saved, to before one of the messages sent in this session.
+    fn open_rewind_popup(&mut self) {
+        if self.checkpoints.is_empty() {
+            self.add_info_message("No earlier message to rewind to.".to_string(), None);
+            return;
+        }
+        let items = self
+            .checkpoints
+            .iter()
+            .enumerate()
+            .rev()
+            .map(|(index, checkpoint)| {
+                let checkpoint_id = checkpoint.checkpoint_id.clone();
+                let actions: Vec<SelectionAction> = vec![Box::new(move |tx| {
+                    tx.send(AppEvent::CodexOp(Op::RewindTo {
+                        checkpoint_id: checkpoint_id.clone(),
+                    }));
+                })];
+                SelectionItem {
+                    name: format!("Before message {}", index + 1),
+                    description: checkpoint.label.clone(),
+                    is_current: false,
+                    actions,
+                    dismiss_on_select: true,
+                    search_value: None,
+                }
+            })
+            .collect();
+
+        self.bottom_pane.show_selection_view(SelectionViewParams {
+            title: "Rewind the conversation".to_string(),
+            subtitle: Some("Go back to before a message, undoing what happened since".to_string()),
+            footer_hint: Some(STANDARD_POPUP_HINT_LINE.to_string()),
+            items,
+            ..Default::default()
+        });
+    }
+
     /// Open a popup to choose the approvals mode (ask for approval policy + sandbox policy).
     pub(crate) fn open_approvals_popup(&mut self) {
         let current_approval = self.config.approval_policy;
@@ -1630,9 +2221,11 @@ impl ChatWidget {
                     cwd: None,
                     approval_policy: Some(approval),
                     sandbox_policy: Some(sandbox.clone()),
+                    sandbox_preset: None,
                     model: None,
                     effort: None,
                     summary: None,
+                    dry_run: None,
                 }));
                 tx.send(AppEvent::UpdateAskForApprovalPolicy(approval));
                 tx.send(AppEvent::UpdateSandboxPolicy(sandbox.clone()));
@@ -1646,6 +2239,44 @@ impl ChatWidget {
                 search_value: None,
             });
         }
+        // User-defined presets from `[sandbox_presets]`.
+        for (name, preset) in &self.config.sandbox_presets {
+            let approval = preset.approval_policy;
+            let sandbox = preset.sandbox_policy.clone();
+            let is_current = current_sandbox == sandbox
+                && approval.is_none_or(|approval| approval == current_approval);
+            let description = Some(
+                preset
+                    .description
+                    .clone()
+                    .unwrap_or_else(|| summarize_sandbox_policy(&sandbox)),
+            );
+            let preset_name = name.clone();
+            let actions: Vec<SelectionAction> = vec![Box::new(move |tx| {
+                tx.send(AppEvent::CodexOp(Op::OverrideTurnContext {
+                    cwd: None,
+                    approval_policy: None,
+                    sandbox_policy: None,
+                    sandbox_preset: Some(preset_name.clone()),
+                    model: None,
+                    effort: None,
+                    summary: None,
+                    dry_run: None,
+                }));
+                if let Some(approval) = approval {
+                    tx.send(AppEvent::UpdateAskForApprovalPolicy(approval));
+                }
+                tx.send(AppEvent::UpdateSandboxPolicy(sandbox.clone()));
+            })];
+            items.push(SelectionItem {
+                name: name.clone(),
+                description,
+                is_current,
+                actions,
+                dismiss_on_select: true,
+                search_value: None,
+            });
+        }
 
         self.bottom_pane.show_selection_view(SelectionViewParams {
             title: "Select Approval Mode".to_string(),
@@ -1691,6 +2322,7 @@ impl ChatWidget {
             self.add_to_history(history_cell::empty_mcp_output());
         } else {
             self.submit_op(Op::ListMcpTools);
+            self.submit_op(Op::ListMcpResources);
         }
     }
 
@@ -1754,6 +2386,17 @@ impl ChatWidget {
         self.add_to_history(history_cell::new_mcp_tools_output(&self.config, ev.tools));
     }
 
+    fn on_list_mcp_resources(&mut self, ev: McpListResourcesResponseEvent) {
+        // Most servers offer no resources; only show the section when some do.
+        if ev.resources.values().any(|resources| !resources.is_empty()) {
+            self.add_to_history(history_cell::new_mcp_resources_output(ev.resources));
+        }
+    }
+
+    fn on_session_env(&mut self, ev: SessionEnvResponseEvent) {
+        self.add_to_history(history_cell::new_session_env_output(ev));
+    }
+
     fn on_list_custom_prompts(&mut self, ev: ListCustomPromptsResponseEvent) {
         let len = ev.custom_prompts.len();
         debug!("received {len} custom prompts");
@@ -1948,6 +2591,11 @@ impl ChatWidget {
             .unwrap_or_default()
     }
 
+    pub(crate) fn session_summary(&self) -> SessionSummary {
+        self.session_stats
+            .summary(&self.token_usage(), self.config.model_pricing)
+    }
+
     pub(crate) fn conversation_id(&self) -> Option<ConversationId> {
         self.conversation_id
     }
@@ -1988,6 +2636,24 @@ impl WidgetRef for &ChatWidget {
     }
 }
 
+/// Runs the configured `sound_command` with the event type appended, without
+/// waiting for it to finish.
+fn play_sound(command: &[String], event: &str) {
+    let Some((program, args)) = command.split_first() else {
+        return;
+    };
+    let result = std::process::Command::new(program)
+        .args(args)
+        .arg(event)
+        .stdin(std::process::Stdio::null())
+        .stdout(std::process::Stdio::null())
+        .stderr(std::process::Stdio::null())
+        .spawn();
+    if let Err(err) = result {
+        tracing::warn!("failed to run sound command `{program}`: {err}");
+    }
+}
+
 enum Notification {
     AgentTurnComplete { response: String },
     ExecApprovalRequested { command: String },
@@ -2026,6 +2692,16 @@ impl Notification {
         }
     }
 
+    /// Approvals need the user, so they ring twice to stand out from a
+    /// finished turn.
+    fn bell_count(&self) -> usize {
+        match self {
+            Notification::AgentTurnComplete { .. } => 1,
+            Notification::ExecApprovalRequested { .. }
+            | Notification::EditApprovalRequested { .. } => 2,
+        }
+    }
+
     fn allowed_for(&self, settings: &Notifications) -> bool {
         match settings {
             Notifications::Enabled(enabled) => *enabled,
@@ -2061,6 +2737,21 @@ const EXAMPLE_PROMPTS: [&str; 6] = [
     "Improve documentation in @filename",
 ];
 
+/// Events of a `delegate` child that the transcript does not show.
+fn is_sub_agent_chatter(msg: &EventMsg) -> bool {
+    matches!(
+        msg,
+        EventMsg::AgentMessage(_)
+            | EventMsg::AgentMessageDelta(_)
+            | EventMsg::AgentReasoning(_)
+            | EventMsg::AgentReasoningDelta(_)
+            | EventMsg::AgentReasoningRawContent(_)
+            | EventMsg::AgentReasoningRawContentDelta(_)
+            | EventMsg::AgentReasoningSectionBreak(_)
+            | EventMsg::TokenCount(_)
+    )
+}
+
 // Extract the first bold (Markdown) element in the form **...** from `s`.
 // Returns the inner text if found; otherwise `None`.
 fn extract_first_bold(s: &str) -> Option<String> {
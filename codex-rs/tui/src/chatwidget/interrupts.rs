@@ -1,12 +1,15 @@
 use std::collections::VecDeque;
 
 use codex_core::protocol::ApplyPatchApprovalRequestEvent;
+use codex_core::protocol::EscalationRequestEvent;
 use codex_core::protocol::ExecApprovalRequestEvent;
 use codex_core::protocol::ExecCommandBeginEvent;
 use codex_core::protocol::ExecCommandEndEvent;
 use codex_core::protocol::McpToolCallBeginEvent;
 use codex_core::protocol::McpToolCallEndEvent;
 use codex_core::protocol::PatchApplyEndEvent;
+use codex_core::protocol::PermissionConsentRequestEvent;
+use codex_core::protocol::SpendLimitApprovalRequestEvent;
 
 use super::ChatWidget;
 
@@ -14,6 +17,9 @@ use super::ChatWidget;
 pub(crate) enum QueuedInterrupt {
     ExecApproval(String, ExecApprovalRequestEvent),
     ApplyPatchApproval(String, ApplyPatchApprovalRequestEvent),
+    Escalation(String, EscalationRequestEvent),
+    PermissionConsent(String, PermissionConsentRequestEvent),
+    SpendLimitApproval(String, SpendLimitApprovalRequestEvent),
     ExecBegin(ExecCommandBeginEvent),
     ExecEnd(ExecCommandEndEvent),
     McpBegin(McpToolCallBeginEvent),
@@ -51,6 +57,28 @@ impl InterruptManager {
             .push_back(QueuedInterrupt::ApplyPatchApproval(id, ev));
     }
 
+    pub(crate) fn push_escalation(&mut self, id: String, ev: EscalationRequestEvent) {
+        self.queue.push_back(QueuedInterrupt::Escalation(id, ev));
+    }
+
+    pub(crate) fn push_permission_consent(
+        &mut self,
+        id: String,
+        ev: PermissionConsentRequestEvent,
+    ) {
+        self.queue
+            .push_back(QueuedInterrupt::PermissionConsent(id, ev));
+    }
+
+    pub(crate) fn push_spend_limit_approval(
+        &mut self,
+        id: String,
+        ev: SpendLimitApprovalRequestEvent,
+    ) {
+        self.queue
+            .push_back(QueuedInterrupt::SpendLimitApproval(id, ev));
+    }
+
     pub(crate) fn push_exec_begin(&mut self, ev: ExecCommandBeginEvent) {
         self.queue.push_back(QueuedInterrupt::ExecBegin(ev));
     }
@@ -78,6 +106,13 @@ impl InterruptManager {
                 QueuedInterrupt::ApplyPatchApproval(id, ev) => {
                     chat.handle_apply_patch_approval_now(id, ev)
                 }
+                QueuedInterrupt::Escalation(id, ev) => chat.handle_escalation_now(id, ev),
+                QueuedInterrupt::PermissionConsent(id, ev) => {
+                    chat.handle_permission_consent_now(id, ev)
+                }
+                QueuedInterrupt::SpendLimitApproval(id, ev) => {
+                    chat.handle_spend_limit_approval_now(id, ev)
+                }
                 QueuedInterrupt::ExecBegin(ev) => chat.handle_exec_begin_now(ev),
                 QueuedInterrupt::ExecEnd(ev) => chat.handle_exec_end_now(ev),
                 QueuedInterrupt::McpBegin(ev) => chat.handle_mcp_begin_now(ev),
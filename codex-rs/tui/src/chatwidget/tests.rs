@@ -14,6 +14,10 @@ use codex_core::protocol::AgentMessageEvent;
 use codex_core::protocol::AgentReasoningDeltaEvent;
 use codex_core::protocol::AgentReasoningEvent;
 use codex_core::protocol::ApplyPatchApprovalRequestEvent;
+use codex_core::protocol::CheckpointCreatedEvent;
+use codex_core::protocol::CheckpointRewoundEvent;
+use codex_core::protocol::CompletionAssessment;
+use codex_core::protocol::CompletionConfidence;
 use codex_core::protocol::Event;
 use codex_core::protocol::EventMsg;
 use codex_core::protocol::ExecApprovalRequestEvent;
@@ -21,18 +25,24 @@ use codex_core::protocol::ExecCommandBeginEvent;
 use codex_core::protocol::ExecCommandEndEvent;
 use codex_core::protocol::ExitedReviewModeEvent;
 use codex_core::protocol::FileChange;
+use codex_core::protocol::FollowUpSuggestion;
 use codex_core::protocol::InputMessageKind;
 use codex_core::protocol::Op;
 use codex_core::protocol::PatchApplyBeginEvent;
 use codex_core::protocol::PatchApplyEndEvent;
+use codex_core::protocol::PatchStats;
 use codex_core::protocol::ReviewCodeLocation;
 use codex_core::protocol::ReviewFinding;
 use codex_core::protocol::ReviewLineRange;
 use codex_core::protocol::ReviewOutputEvent;
 use codex_core::protocol::ReviewRequest;
 use codex_core::protocol::StreamErrorEvent;
+use codex_core::protocol::SubAgentBeginEvent;
+use codex_core::protocol::SubAgentEndEvent;
 use codex_core::protocol::TaskCompleteEvent;
 use codex_core::protocol::TaskStartedEvent;
+use codex_core::protocol::UndoCompletedEvent;
+use codex_core::protocol::VerificationStatus;
 use codex_protocol::mcp_protocol::ConversationId;
 use crossterm::event::KeyCode;
 use crossterm::event::KeyEvent;
@@ -139,6 +149,48 @@ fn final_answer_without_newline_is_flushed_immediately() {
     );
 }
 
+#[test]
+fn sub_agent_messages_are_shown_only_as_its_result() {
+    let (mut chat, mut rx, _op_rx) = make_chatwidget_manual();
+
+    chat.handle_codex_event(Event {
+        id: "sub-a".into(),
+        msg: EventMsg::SubAgentBegin(SubAgentBeginEvent {
+            call_id: "call-1".into(),
+            agent_id: "sub-a/delegate-1".into(),
+            prompt: "List the callers of parse_config".into(),
+            tools: None,
+            max_turns: 10,
+        }),
+    });
+    chat.handle_codex_event(Event {
+        id: "sub-a/delegate-1".into(),
+        msg: EventMsg::AgentMessage(AgentMessageEvent {
+            message: "child answer".into(),
+        }),
+    });
+    chat.handle_codex_event(Event {
+        id: "sub-a".into(),
+        msg: EventMsg::SubAgentEnd(SubAgentEndEvent {
+            call_id: "call-1".into(),
+            agent_id: "sub-a/delegate-1".into(),
+            turns: 2,
+            budget_exhausted: false,
+            last_agent_message: Some("child answer".into()),
+        }),
+    });
+
+    let cells = drain_insert_history(&mut rx);
+    let blob = cells
+        .iter()
+        .map(|lines| lines_to_single_string(lines))
+        .collect::<String>();
+    assert!(blob.contains("Delegated to sub-a/delegate-1"), "{blob}");
+    assert!(blob.contains("List the callers of parse_config"), "{blob}");
+    assert!(blob.contains("finished after 2 turn(s)"), "{blob}");
+    assert_eq!(blob.matches("child answer").count(), 1, "{blob}");
+}
+
 #[test]
 fn resumed_initial_messages_render_history() {
     let (mut chat, mut rx, _ops) = make_chatwidget_manual();
@@ -320,6 +372,7 @@ fn make_chatwidget_manual() -> (
         session_header: SessionHeader::new(cfg.model),
         initial_user_message: None,
         token_info: None,
+        session_stats: SessionStats::new(),
         rate_limit_snapshot: None,
         rate_limit_warnings: RateLimitWarningState::default(),
         stream_controller: None,
@@ -329,14 +382,14 @@ fn make_chatwidget_manual() -> (
         reasoning_buffer: String::new(),
         full_reasoning_buffer: String::new(),
         conversation_id: None,
+        rollout_path: None,
         frame_requester: FrameRequester::test_dummy(),
         show_welcome_banner: true,
         queued_user_messages: VecDeque::new(),
+        checkpoints: Vec::new(),
         suppress_session_configured_redraw: false,
         pending_notification: None,
         is_review_mode: false,
-        ghost_snapshots: Vec::new(),
-        ghost_snapshots_disabled: false,
     };
     (widget, rx, op_rx)
 }
@@ -466,6 +519,42 @@ fn exec_approval_emits_proposed_command_and_decision_history() {
     );
 }
 
+#[test]
+fn bell_setting_alone_queues_approval_alert() {
+    let (mut chat, _rx, _op_rx) = make_chatwidget_manual();
+    chat.config.tui_notifications = Notifications::Enabled(false);
+    chat.config.tui_bell = Notifications::Custom(vec!["approval-requested".to_string()]);
+
+    chat.handle_codex_event(Event {
+        id: "sub-bell".into(),
+        msg: EventMsg::ExecApprovalRequest(ExecApprovalRequestEvent {
+            call_id: "call-bell".into(),
+            command: vec!["echo".into(), "hi".into()],
+            cwd: PathBuf::from("/tmp"),
+            reason: None,
+        }),
+    });
+
+    let pending = chat
+        .pending_notification
+        .take()
+        .expect("approval should queue an alert");
+    assert_eq!(pending.type_name(), "approval-requested");
+    assert_eq!(pending.bell_count(), 2);
+
+    chat.config.tui_bell = Notifications::Enabled(false);
+    chat.handle_codex_event(Event {
+        id: "sub-bell".into(),
+        msg: EventMsg::ExecApprovalRequest(ExecApprovalRequestEvent {
+            call_id: "call-quiet".into(),
+            command: vec!["echo".into(), "hi".into()],
+            cwd: PathBuf::from("/tmp"),
+            reason: None,
+        }),
+    });
+    assert!(chat.pending_notification.is_none());
+}
+
 #[test]
 fn exec_approval_decision_truncates_multiline_and_long_commands() {
     let (mut chat, mut rx, _op_rx) = make_chatwidget_manual();
@@ -540,6 +629,7 @@ fn begin_exec(chat: &mut ChatWidget, call_id: &str, raw_cmd: &str) {
             command,
             cwd: std::env::current_dir().unwrap_or_else(|_| PathBuf::from(".")),
             parsed_cmd,
+            sandbox_policy: None,
         }),
     });
 }
@@ -722,6 +812,65 @@ fn review_popup_custom_prompt_action_sends_event() {
     assert!(found, "expected OpenReviewCustomPrompt event to be sent");
 }
 
+#[test]
+fn task_complete_follow_up_number_key_submits_prompt() {
+    let (mut chat, mut rx, mut op_rx) = make_chatwidget_manual();
+
+    chat.handle_codex_event(Event {
+        id: "s1".into(),
+        msg: EventMsg::TaskComplete(TaskCompleteEvent {
+            last_agent_message: Some("Done".into()),
+            assessment: Some(CompletionAssessment {
+                confidence: CompletionConfidence::Medium,
+                verification: VerificationStatus::Unverified,
+                follow_ups: vec![
+                    FollowUpSuggestion {
+                        label: "Run the tests".into(),
+                        prompt: "Run the test suite and fix any failures.".into(),
+                    },
+                    FollowUpSuggestion {
+                        label: "Update the docs".into(),
+                        prompt: "Document the new flag.".into(),
+                    },
+                ],
+            }),
+            estimated_cost_usd: None,
+            output_schema_error: None,
+        }),
+    });
+
+    let cells = drain_insert_history(&mut rx);
+    let combined: String = cells
+        .iter()
+        .map(|lines| lines_to_single_string(lines))
+        .collect();
+    assert!(
+        combined.contains("Confidence: medium · unverified"),
+        "missing assessment: {combined}"
+    );
+
+    chat.handle_key_event(KeyEvent::new(KeyCode::Char('2'), KeyModifiers::NONE));
+
+    let mut submitted = None;
+    while let Ok(ev) = rx.try_recv() {
+        if let AppEvent::SubmitFollowUp(prompt) = ev {
+            submitted = Some(prompt);
+        }
+    }
+    assert_eq!(submitted.as_deref(), Some("Document the new flag."));
+    chat.submit_text_message(submitted.unwrap_or_default());
+    assert!(matches!(op_rx.try_recv(), Ok(Op::Checkpoint { .. })));
+    match op_rx.try_recv() {
+        Ok(Op::UserInput { items }) => assert_eq!(
+            items,
+            vec![InputItem::Text {
+                text: "Document the new flag.".into()
+            }]
+        ),
+        other => panic!("expected user input op, got {other:?}"),
+    }
+}
+
 /// The commit picker shows only commit subjects (no timestamps).
 #[test]
 fn review_commit_picker_shows_subjects_without_timestamps() {
@@ -839,6 +988,9 @@ fn interrupt_exec_marks_failed_snapshot() {
         id: "call-int".into(),
         msg: EventMsg::TurnAborted(codex_core::protocol::TurnAbortedEvent {
             reason: TurnAbortReason::Interrupted,
+            exceeded_limit: None,
+            budget_used: None,
+            budget_allowed: None,
         }),
     });
 
@@ -974,6 +1126,110 @@ fn exec_history_extends_previous_when_consecutive() {
     assert_snapshot!("exploring_step6_finish_cat_bar", active_blob(&chat));
 }
 
+#[test]
+fn undo_submits_op_and_lists_reverted_files() {
+    let (mut chat, mut rx, mut op_rx) = make_chatwidget_manual();
+
+    chat.dispatch_command(SlashCommand::Undo);
+    assert!(matches!(op_rx.try_recv(), Ok(Op::UndoLastChange)));
+
+    chat.handle_codex_event(Event {
+        id: "undo".into(),
+        msg: EventMsg::UndoCompleted(UndoCompletedEvent {
+            snapshot_id: Some("0123456789abcdef".into()),
+            reverted_files: vec![PathBuf::from("src/lib.rs"), PathBuf::from("README.md")],
+            message: None,
+        }),
+    });
+
+    let cells = drain_insert_history(&mut rx);
+    let blob = lines_to_single_string(cells.last().expect("undo summary cell"));
+    assert!(blob.contains("Restored snapshot 01234567"), "{blob}");
+    assert!(blob.contains("src/lib.rs"), "{blob}");
+    assert!(blob.contains("README.md"), "{blob}");
+
+    chat.handle_codex_event(Event {
+        id: "undo".into(),
+        msg: EventMsg::UndoCompleted(UndoCompletedEvent {
+            snapshot_id: None,
+            reverted_files: Vec::new(),
+            message: Some("No snapshot available to undo.".into()),
+        }),
+    });
+    let cells = drain_insert_history(&mut rx);
+    let blob = lines_to_single_string(cells.last().expect("undo info cell"));
+    assert!(blob.contains("No snapshot available to undo."), "{blob}");
+}
+
+#[test]
+fn rewind_restores_the_message_sent_from_the_checkpoint() {
+    let (mut chat, mut rx, mut op_rx) = make_chatwidget_manual();
+
+    chat.submit_text_message("add a --verbose flag".to_string());
+    match op_rx.try_recv() {
+        Ok(Op::Checkpoint {
+            label,
+            include_workspace,
+        }) => {
+            assert_eq!(label.as_deref(), Some("add a --verbose flag"));
+            assert!(include_workspace);
+        }
+        other => panic!("expected checkpoint op, got {other:?}"),
+    }
+    for (id, label) in [
+        ("checkpoint-1", "add a --verbose flag"),
+        ("checkpoint-2", "now add -q"),
+    ] {
+        chat.handle_codex_event(Event {
+            id: "checkpoint".into(),
+            msg: EventMsg::CheckpointCreated(CheckpointCreatedEvent {
+                checkpoint_id: id.into(),
+                label: Some(label.into()),
+                snapshot_id: None,
+            }),
+        });
+    }
+    drain_insert_history(&mut rx);
+
+    chat.handle_codex_event(Event {
+        id: "rewind".into(),
+        msg: EventMsg::CheckpointRewound(CheckpointRewoundEvent {
+            checkpoint_id: "checkpoint-2".into(),
+            rewound: true,
+            label: Some("now add -q".into()),
+            restored_files: vec![PathBuf::from("src/main.rs")],
+            message: None,
+        }),
+    });
+
+    let cells = drain_insert_history(&mut rx);
+    let blob = lines_to_single_string(cells.last().expect("rewind info cell"));
+    assert!(blob.contains("Rewound to before message 2"), "{blob}");
+    assert!(blob.contains("restored 1 file(s)"), "{blob}");
+    assert_eq!(chat.bottom_pane.composer_text(), "now add -q");
+    assert_eq!(chat.checkpoints.len(), 1);
+}
+
+#[test]
+fn steer_command_sends_guidance_to_the_running_task() {
+    let (mut chat, mut rx, mut op_rx) = make_chatwidget_manual();
+    chat.bottom_pane.set_task_running(true);
+
+    chat.dispatch_command_with_args(SlashCommand::Steer, "skip the docs".to_string());
+
+    match op_rx.try_recv() {
+        Ok(Op::Steer { items }) => match items.as_slice() {
+            [InputItem::Text { text }] => assert_eq!(text, "skip the docs"),
+            other => panic!("expected one text item, got {other:?}"),
+        },
+        other => panic!("expected steer op, got {other:?}"),
+    }
+    assert!(chat.queued_user_messages.is_empty());
+    let cells = drain_insert_history(&mut rx);
+    let blob = lines_to_single_string(cells.last().expect("steering cell"));
+    assert!(blob.contains("skip the docs"), "{blob}");
+}
+
 #[test]
 fn disabled_slash_command_while_task_running_snapshot() {
     // Build a chat widget and simulate an active task
@@ -1054,6 +1310,7 @@ async fn binary_size_transcript_snapshot() {
                                         .into_iter()
                                         .map(std::convert::Into::into)
                                         .collect(),
+                                    sandbox_policy: None,
                                 }),
                             }
                         }
@@ -1259,6 +1516,8 @@ fn approval_modal_patch_snapshot() {
         changes,
         reason: Some("The model wants to apply changes".into()),
         grant_root: Some(PathBuf::from("/tmp")),
+        stats: PatchStats::default(),
+        rejected_paths: Vec::new(),
     };
     chat.handle_codex_event(Event {
         id: "sub-approve-patch".into(),
@@ -1294,6 +1553,9 @@ fn interrupt_restores_queued_messages_into_composer() {
         id: "turn-1".into(),
         msg: EventMsg::TurnAborted(codex_core::protocol::TurnAbortedEvent {
             reason: TurnAbortReason::Interrupted,
+            exceeded_limit: None,
+            budget_used: None,
+            budget_allowed: None,
         }),
     });
 
@@ -1332,6 +1594,9 @@ fn interrupt_prepends_queued_messages_before_existing_composer_text() {
         id: "turn-1".into(),
         msg: EventMsg::TurnAborted(codex_core::protocol::TurnAbortedEvent {
             reason: TurnAbortReason::Interrupted,
+            exceeded_limit: None,
+            budget_used: None,
+            budget_allowed: None,
         }),
     });
 
@@ -1377,6 +1642,7 @@ fn ui_snapshots_small_heights_task_running() {
         id: "task-1".into(),
         msg: EventMsg::TaskStarted(TaskStartedEvent {
             model_context_window: None,
+            branch: None,
         }),
     });
     chat.handle_codex_event(Event {
@@ -1408,6 +1674,7 @@ fn status_widget_and_approval_modal_snapshot() {
         id: "task-1".into(),
         msg: EventMsg::TaskStarted(TaskStartedEvent {
             model_context_window: None,
+            branch: None,
         }),
     });
     // Provide a deterministic header for the status line.
@@ -1452,6 +1719,7 @@ fn status_widget_active_snapshot() {
         id: "task-1".into(),
         msg: EventMsg::TaskStarted(TaskStartedEvent {
             model_context_window: None,
+            branch: None,
         }),
     });
     // Provide a deterministic header via a bold reasoning chunk.
@@ -1488,6 +1756,8 @@ fn apply_patch_events_emit_history_cells() {
         changes,
         reason: None,
         grant_root: None,
+        stats: PatchStats::default(),
+        rejected_paths: Vec::new(),
     };
     chat.handle_codex_event(Event {
         id: "s1".into(),
@@ -1513,6 +1783,7 @@ fn apply_patch_events_emit_history_cells() {
         call_id: "c1".into(),
         auto_approved: true,
         changes: changes2,
+        snapshot_id: None,
     };
     chat.handle_codex_event(Event {
         id: "s1".into(),
@@ -1562,6 +1833,8 @@ fn apply_patch_manual_approval_adjusts_header() {
             changes: proposed_changes,
             reason: None,
             grant_root: None,
+            stats: PatchStats::default(),
+            rejected_paths: Vec::new(),
         }),
     });
     drain_insert_history(&mut rx);
@@ -1579,6 +1852,7 @@ fn apply_patch_manual_approval_adjusts_header() {
             call_id: "c1".into(),
             auto_approved: false,
             changes: apply_changes,
+            snapshot_id: None,
         }),
     });
 
@@ -1609,6 +1883,8 @@ fn apply_patch_manual_flow_snapshot() {
             changes: proposed_changes,
             reason: Some("Manual review required".into()),
             grant_root: None,
+            stats: PatchStats::default(),
+            rejected_paths: Vec::new(),
         }),
     });
     let proposed_lines = drain_insert_history(&mut rx)
@@ -1628,6 +1904,7 @@ fn apply_patch_manual_flow_snapshot() {
             call_id: "c1".into(),
             auto_approved: false,
             changes: apply_changes,
+            snapshot_id: None,
         }),
     });
     let approved_lines = drain_insert_history(&mut rx)
@@ -1660,6 +1937,8 @@ fn apply_patch_approval_sends_op_with_submission_id() {
         changes,
         reason: None,
         grant_root: None,
+        stats: PatchStats::default(),
+        rejected_paths: Vec::new(),
     };
     chat.handle_codex_event(Event {
         id: "sub-123".into(),
@@ -1672,7 +1951,7 @@ fn apply_patch_approval_sends_op_with_submission_id() {
     // Expect a CodexOp with PatchApproval carrying the submission id, not call id
     let mut found = false;
     while let Ok(app_ev) = rx.try_recv() {
-        if let AppEvent::CodexOp(Op::PatchApproval { id, decision }) = app_ev {
+        if let AppEvent::CodexOp(Op::PatchApproval { id, decision, .. }) = app_ev {
             assert_eq!(id, "sub-123");
             assert!(matches!(
                 decision,
@@ -1702,6 +1981,8 @@ fn apply_patch_full_flow_integration_like() {
             changes,
             reason: None,
             grant_root: None,
+            stats: PatchStats::default(),
+            rejected_paths: Vec::new(),
         }),
     });
 
@@ -1722,7 +2003,7 @@ fn apply_patch_full_flow_integration_like() {
         .try_recv()
         .expect("expected op forwarded to codex channel");
     match forwarded {
-        Op::PatchApproval { id, decision } => {
+        Op::PatchApproval { id, decision, .. } => {
             assert_eq!(id, "sub-xyz");
             assert!(matches!(
                 decision,
@@ -1744,6 +2025,7 @@ fn apply_patch_full_flow_integration_like() {
             call_id: "call-1".into(),
             auto_approved: false,
             changes: changes2,
+            snapshot_id: None,
         }),
     });
     chat.handle_codex_event(Event {
@@ -1776,6 +2058,8 @@ fn apply_patch_untrusted_shows_approval_modal() {
             changes,
             reason: None,
             grant_root: None,
+            stats: PatchStats::default(),
+            rejected_paths: Vec::new(),
         }),
     });
 
@@ -1824,6 +2108,8 @@ fn apply_patch_request_shows_diff_summary() {
             changes,
             reason: None,
             grant_root: None,
+            stats: PatchStats::default(),
+            rejected_paths: Vec::new(),
         }),
     });
 
@@ -1912,6 +2198,7 @@ fn multiple_agent_messages_in_single_turn_emit_multiple_headers() {
         id: "s1".into(),
         msg: EventMsg::TaskStarted(TaskStartedEvent {
             model_context_window: None,
+            branch: None,
         }),
     });
 
@@ -1936,6 +2223,9 @@ fn multiple_agent_messages_in_single_turn_emit_multiple_headers() {
         id: "s1".into(),
         msg: EventMsg::TaskComplete(TaskCompleteEvent {
             last_agent_message: None,
+            assessment: None,
+            estimated_cost_usd: None,
+            output_schema_error: None,
         }),
     });
 
@@ -2075,6 +2365,7 @@ fn chatwidget_exec_and_status_layout_vt100_snapshot() {
                 }
                 .into(),
             ],
+            sandbox_policy: None,
         }),
     });
     chat.handle_codex_event(Event {
@@ -2093,6 +2384,7 @@ fn chatwidget_exec_and_status_layout_vt100_snapshot() {
         id: "t1".into(),
         msg: EventMsg::TaskStarted(TaskStartedEvent {
             model_context_window: None,
+            branch: None,
         }),
     });
     chat.handle_codex_event(Event {
@@ -2177,6 +2469,7 @@ fn chatwidget_markdown_code_blocks_vt100_snapshot() {
         id: "t1".into(),
         msg: EventMsg::TaskStarted(TaskStartedEvent {
             model_context_window: None,
+            branch: None,
         }),
     });
     // Build a vt100 visual from the history insertions only (no UI overlay)
@@ -2251,6 +2544,9 @@ printf 'fenced within fenced\n'
         id: "t1".into(),
         msg: EventMsg::TaskComplete(TaskCompleteEvent {
             last_agent_message: None,
+            assessment: None,
+            estimated_cost_usd: None,
+            output_schema_error: None,
         }),
     });
     for lines in drain_insert_history(&mut rx) {
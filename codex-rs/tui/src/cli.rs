@@ -42,9 +42,10 @@ pub struct Cli {
     pub config_profile: Option<String>,
 
     /// Select the sandbox policy to use when executing model-generated shell
-    /// commands.
+    /// commands: read-only, workspace-write, danger-full-access, or the name
+    /// of a `[sandbox_presets]` entry in config.toml.
     #[arg(long = "sandbox", short = 's')]
-    pub sandbox_mode: Option<codex_common::SandboxModeCliArg>,
+    pub sandbox_mode: Option<codex_common::SandboxCliArg>,
 
     /// Configure when the model requires human approval before executing a command.
     #[arg(long = "ask-for-approval", short = 'a')]
@@ -72,6 +73,11 @@ pub struct Cli {
     #[arg(long = "search", default_value_t = false)]
     pub web_search: bool,
 
+    /// Do not print the session summary (token usage, commands run, changed
+    /// files and estimated cost) on exit.
+    #[arg(long = "quiet", short = 'q', default_value_t = false)]
+    pub quiet: bool,
+
     #[clap(skip)]
     pub config_overrides: CliConfigOverrides,
 }
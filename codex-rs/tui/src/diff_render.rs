@@ -12,6 +12,8 @@ use std::path::PathBuf;
 use crate::exec_command::relativize_to_home;
 use crate::history_cell::PatchEventType;
 use codex_core::git_info::get_git_repo_root;
+use codex_core::path_utils::display_path;
+use codex_core::path_utils::strip_path_prefix;
 use codex_core::protocol::FileChange;
 
 const SPACES_AFTER_LINE_NUMBER: usize = 6;
@@ -271,12 +273,14 @@ pub(crate) fn display_path_for(path: &Path, cwd: &Path) -> String {
         (Some(cwd_repo), Some(path_repo)) => cwd_repo == path_repo,
         _ => false,
     };
-    let chosen = if path_in_same_repo {
-        pathdiff::diff_paths(path, cwd).unwrap_or_else(|| path.to_path_buf())
-    } else {
-        relativize_to_home(path).unwrap_or_else(|| path.to_path_buf())
-    };
-    chosen.display().to_string()
+    if path_in_same_repo {
+        let rel = match strip_path_prefix(path, cwd) {
+            Some(rel) => rel,
+            None => pathdiff::diff_paths(path, cwd).unwrap_or_else(|| path.to_path_buf()),
+        };
+        return display_path(&rel);
+    }
+    display_path(&relativize_to_home(path).unwrap_or_else(|| path.to_path_buf()))
 }
 
 fn calculate_add_remove_from_diff(diff: &str) -> (usize, usize) {
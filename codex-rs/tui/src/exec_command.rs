@@ -1,6 +1,7 @@
 use std::path::Path;
 use std::path::PathBuf;
 
+use codex_core::path_utils::strip_path_prefix;
 use dirs::home_dir;
 use shlex::try_join;
 
@@ -29,8 +30,7 @@ where
     }
 
     let home_dir = home_dir()?;
-    let rel = path.strip_prefix(&home_dir).ok()?;
-    Some(rel.to_path_buf())
+    strip_path_prefix(path, &home_dir)
 }
 
 #[cfg(test)]
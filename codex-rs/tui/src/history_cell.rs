@@ -18,12 +18,18 @@ use codex_ansi_escape::ansi_escape_line;
 use codex_common::elapsed::format_duration;
 use codex_core::config::Config;
 use codex_core::config_types::ReasoningSummaryFormat;
+use codex_core::path_utils::display_path;
 use codex_core::plan_tool::PlanItemArg;
 use codex_core::plan_tool::StepStatus;
 use codex_core::plan_tool::UpdatePlanArgs;
+use codex_core::protocol::CompletionAssessment;
 use codex_core::protocol::FileChange;
 use codex_core::protocol::McpInvocation;
+use codex_core::protocol::NextPromptPreviewEvent;
 use codex_core::protocol::SessionConfiguredEvent;
+use codex_core::protocol::SessionEnvResponseEvent;
+use codex_core::protocol::SubAgentBeginEvent;
+use codex_core::protocol::SubAgentEndEvent;
 use codex_core::protocol_config_types::ReasoningEffort as ReasoningEffortConfig;
 use codex_protocol::parse_command::ParsedCommand;
 use image::DynamicImage;
@@ -833,7 +839,7 @@ impl SessionHeaderHistoryCell {
                 format!("~{}{}", std::path::MAIN_SEPARATOR, rel.display())
             }
         } else {
-            directory.display().to_string()
+            display_path(directory)
         };
 
         if let Some(max_width) = max_width {
@@ -950,6 +956,8 @@ pub(crate) struct McpToolCallCell {
     start_time: Instant,
     duration: Option<Duration>,
     result: Option<Result<mcp_types::CallToolResult, String>>,
+    /// Latest progress reported by the server while the call runs.
+    progress: Option<String>,
 }
 
 impl McpToolCallCell {
@@ -960,6 +968,7 @@ impl McpToolCallCell {
             start_time: Instant::now(),
             duration: None,
             result: None,
+            progress: None,
         }
     }
 
@@ -967,6 +976,24 @@ impl McpToolCallCell {
         &self.call_id
     }
 
+    /// Show the progress from a `notifications/progress` for this call.
+    pub(crate) fn update_progress(
+        &mut self,
+        progress: f64,
+        total: Option<f64>,
+        message: Option<String>,
+    ) {
+        let percent = total
+            .filter(|total| *total > 0.0)
+            .map(|total| format!("{:.0}%", (progress / total * 100.0).clamp(0.0, 100.0)));
+        self.progress = Some(match (message, percent) {
+            (Some(message), Some(percent)) => format!("{message} ({percent})"),
+            (Some(message), None) => message,
+            (None, Some(percent)) => percent,
+            (None, None) => format!("progress: {progress}"),
+        });
+    }
+
     pub(crate) fn complete(
         &mut self,
         duration: Duration,
@@ -1054,22 +1081,44 @@ impl HistoryCell for McpToolCallCell {
 
         let mut detail_lines: Vec<Line<'static>> = Vec::new();
 
+        if self.result.is_none()
+            && let Some(progress) = &self.progress
+        {
+            detail_lines.push(Line::from(progress.clone().dim()));
+        }
+
         if let Some(result) = &self.result {
             match result {
-                Ok(mcp_types::CallToolResult { content, .. }) => {
-                    if !content.is_empty() {
-                        for block in content {
-                            let text = Self::render_content_block(block, width as usize);
-                            for segment in text.split('\n') {
-                                let line = Line::from(segment.to_string().dim());
-                                let wrapped = word_wrap_line(
-                                    &line,
-                                    RtOptions::new((width as usize).saturating_sub(4))
-                                        .initial_indent("".into())
-                                        .subsequent_indent("    ".into()),
-                                );
-                                detail_lines.extend(wrapped.iter().map(line_to_static));
-                            }
+                Ok(mcp_types::CallToolResult {
+                    content,
+                    structured_content,
+                    ..
+                }) => {
+                    // Tools with an `outputSchema` may return only structured
+                    // content; show it rather than an empty result.
+                    let texts: Vec<String> = match structured_content
+                        .as_ref()
+                        .filter(|value| content.is_empty() && !value.is_null())
+                    {
+                        Some(value) => vec![
+                            serde_json::to_string_pretty(value)
+                                .unwrap_or_else(|_| value.to_string()),
+                        ],
+                        None => content
+                            .iter()
+                            .map(|block| Self::render_content_block(block, width as usize))
+                            .collect(),
+                    };
+                    for text in texts {
+                        for segment in text.split('\n') {
+                            let line = Line::from(segment.to_string().dim());
+                            let wrapped = word_wrap_line(
+                                &line,
+                                RtOptions::new((width as usize).saturating_sub(4))
+                                    .initial_indent("".into())
+                                    .subsequent_indent("    ".into()),
+                            );
+                            detail_lines.extend(wrapped.iter().map(line_to_static));
                         }
                     }
                 }
@@ -1237,6 +1286,9 @@ pub(crate) fn new_mcp_tools_output(
 
             lines.push(vec!["    • Command: ".into(), cmd_display.into()].into());
         }
+        if let Some(url) = &cfg.url {
+            lines.push(vec!["    • URL: ".into(), url.clone().into()].into());
+        }
 
         if names.is_empty() {
             lines.push("    • Tools: (none)".into());
@@ -1249,6 +1301,138 @@ pub(crate) fn new_mcp_tools_output(
     PlainHistoryCell { lines }
 }
 
+/// Render the resources offered by each MCP server, which the agent can read
+/// with `read_mcp_resource`.
+pub(crate) fn new_mcp_resources_output(
+    resources: std::collections::HashMap<String, Vec<mcp_types::Resource>>,
+) -> PlainHistoryCell {
+    let mut lines: Vec<Line<'static>> = vec![
+        vec!["📚  ".into(), "MCP Resources".bold()].into(),
+        "".into(),
+    ];
+
+    let mut servers: Vec<_> = resources.into_iter().collect();
+    servers.sort_by(|a, b| a.0.cmp(&b.0));
+    for (server, mut resources) in servers {
+        if resources.is_empty() {
+            continue;
+        }
+        resources.sort_by(|a, b| a.uri.cmp(&b.uri));
+        lines.push(vec!["  • Server: ".into(), server.into()].into());
+        for resource in resources {
+            let name = resource.title.unwrap_or(resource.name);
+            lines.push(vec!["    • ".into(), name.into(), " ".into(), resource.uri.dim()].into());
+        }
+        lines.push(Line::from(""));
+    }
+
+    PlainHistoryCell { lines }
+}
+
+/// Render the effective command environment, masking values of variables
+/// that look like credentials.
+pub(crate) fn new_session_env_output(ev: SessionEnvResponseEvent) -> PlainHistoryCell {
+    let mut lines: Vec<Line<'static>> = vec![
+        "/env".magenta().into(),
+        "".into(),
+        vec!["🌱  ".into(), "Command Environment".bold()].into(),
+        "".into(),
+    ];
+
+    if ev.env.is_empty() {
+        lines.push("  • No environment variables.".italic().into());
+    }
+    for (key, value) in ev.env {
+        let upper = key.to_ascii_uppercase();
+        let looks_like_secret = ["KEY", "SECRET", "TOKEN"]
+            .iter()
+            .any(|needle| upper.contains(needle));
+        let value = if looks_like_secret {
+            "********".to_string()
+        } else {
+            value
+        };
+        let mut line: Vec<Span<'static>> =
+            vec!["  ".into(), key.clone().cyan(), "=".dim(), value.into()];
+        if ev.overrides.contains_key(&key) {
+            line.push(" (session)".dim());
+        }
+        lines.push(line.into());
+    }
+
+    let removed: Vec<String> = ev
+        .overrides
+        .iter()
+        .filter(|(_, value)| value.is_none())
+        .map(|(key, _)| key.clone())
+        .collect();
+    if !removed.is_empty() {
+        lines.push("".into());
+        lines.push(
+            vec![
+                "  Unset for this session: ".dim(),
+                removed.join(", ").into(),
+            ]
+            .into(),
+        );
+    }
+    lines.push("".into());
+
+    PlainHistoryCell { lines }
+}
+
+/// Summarize an `/undo`: the snapshot that was restored and the files it
+/// reverted.
+pub(crate) fn new_undo_summary(snapshot_id: &str, reverted_files: &[PathBuf]) -> PlainHistoryCell {
+    let short_id: String = snapshot_id.chars().take(8).collect();
+    let mut lines: Vec<Line<'static>> = vec![
+        "/undo".magenta().into(),
+        vec!["Restored snapshot ".into(), short_id.bold()].into(),
+    ];
+    if reverted_files.is_empty() {
+        lines.push("  • No files changed.".italic().into());
+    }
+    for path in reverted_files {
+        lines.push(vec!["  • ".dim(), path.display().to_string().into()].into());
+    }
+    lines.push("".into());
+    PlainHistoryCell { lines }
+}
+
+/// Lines for the read-only `/context` overlay: each section of the next
+/// prompt under a heading with its estimated size.
+pub(crate) fn prompt_preview_lines(preview: NextPromptPreviewEvent) -> Vec<Line<'static>> {
+    let mut lines: Vec<Line<'static>> = vec![
+        vec![
+            "Next request to ".into(),
+            preview.model.bold(),
+            format!(
+                " (~{} tokens, excluding your next message)",
+                preview.estimated_tokens
+            )
+            .dim(),
+        ]
+        .into(),
+    ];
+    for section in preview.sections {
+        lines.push("".into());
+        lines.push(
+            vec![
+                section.kind.to_string().magenta().bold(),
+                format!(" ~{} tokens", section.estimated_tokens).dim(),
+            ]
+            .into(),
+        );
+        if section.content.is_empty() {
+            lines.push("  (empty)".italic().into());
+        }
+        for line in section.content.lines() {
+            lines.push(format!("  {line}").into());
+        }
+    }
+    lines
+}
+
 pub(crate) fn new_info_event(message: String, hint: Option<String>) -> PlainHistoryCell {
     let mut line = vec!["> ".into(), message.into()];
     if let Some(hint) = hint {
@@ -1259,6 +1443,57 @@ pub(crate) fn new_info_event(message: String, hint: Option<String>) -> PlainHist
     PlainHistoryCell { lines }
 }
 
+/// Render the model's self-assessment of a finished task.
+pub(crate) fn new_completion_assessment(assessment: &CompletionAssessment) -> PlainHistoryCell {
+    let mut lines: Vec<Line<'static>> = vec![
+        vec![
+            "• ".dim(),
+            "Confidence: ".dim(),
+            assessment.confidence.to_string().bold(),
+            " · ".dim(),
+            assessment.verification.to_string().into(),
+        ]
+        .into(),
+    ];
+    for follow_up in &assessment.follow_ups {
+        lines.push(vec!["  ↳ ".dim(), follow_up.label.clone().into()].into());
+    }
+    PlainHistoryCell { lines }
+}
+
+/// A subtask handed to a child agent with `delegate`. The child's tool calls
+/// follow this cell in the transcript.
+pub(crate) fn new_sub_agent_begin(ev: &SubAgentBeginEvent) -> PlainHistoryCell {
+    let mut lines: Vec<Line<'static>> = vec![
+        vec![
+            "• ".dim(),
+            "Delegated".bold(),
+            format!(" to {}", ev.agent_id).dim(),
+        ]
+        .into(),
+    ];
+    for line in ev.prompt.lines() {
+        lines.push(vec!["  │ ".dim(), line.to_string().into()].into());
+    }
+    PlainHistoryCell { lines }
+}
+
+/// The end of a `delegate` call, with the child's final message.
+pub(crate) fn new_sub_agent_end(ev: &SubAgentEndEvent) -> PlainHistoryCell {
+    let outcome = if ev.budget_exhausted {
+        format!("{} ran out of turns after {}", ev.agent_id, ev.turns)
+    } else {
+        format!("{} finished after {} turn(s)", ev.agent_id, ev.turns)
+    };
+    let mut lines: Vec<Line<'static>> = vec![vec!["• ".dim(), outcome.dim()].into()];
+    if let Some(message) = &ev.last_agent_message {
+        for line in message.lines() {
+            lines.push(vec!["  └ ".dim(), line.to_string().into()].into());
+        }
+    }
+    PlainHistoryCell { lines }
+}
+
 pub(crate) fn new_error_event(message: String) -> PlainHistoryCell {
     // Use a hair space (U+200A) to create a subtle, near-invisible separation
     // before the text. VS16 is intentionally omitted to keep spacing tighter
@@ -1399,6 +1634,30 @@ pub(crate) fn new_proposed_command(command: &[String]) -> PlainHistoryCell {
     PlainHistoryCell { lines }
 }
 
+/// Create a new history cell for a command the model asked to run while the
+/// session is in dry-run mode.
+pub(crate) fn new_dry_run_command(command: &[String]) -> PlainHistoryCell {
+    let cmd = strip_bash_lc_and_escape(command);
+
+    let mut lines: Vec<Line<'static>> = Vec::new();
+    lines.push(Line::from(vec![
+        "• ".into(),
+        "Would run".bold(),
+        " (dry run)".dim(),
+    ]));
+
+    let highlighted_lines = crate::render::highlight::highlight_bash_to_lines(&cmd);
+    let initial_prefix: Span<'static> = "  └ ".dim();
+    let subsequent_prefix: Span<'static> = "    ".into();
+    lines.extend(prefix_lines(
+        highlighted_lines,
+        initial_prefix,
+        subsequent_prefix,
+    ));
+
+    PlainHistoryCell { lines }
+}
+
 pub(crate) fn new_reasoning_block(
     full_reasoning_buffer: String,
     config: &Config,
@@ -1594,6 +1853,32 @@ mod tests {
         insta::assert_snapshot!(rendered);
     }
 
+    #[test]
+    fn active_mcp_tool_call_shows_latest_progress() {
+        let invocation = McpInvocation {
+            server: "search".into(),
+            tool: "reindex".into(),
+            arguments: None,
+        };
+
+        let mut cell = new_active_mcp_tool_call("call-7".into(), invocation);
+        cell.update_progress(1.0, None, Some("Scanning files".into()));
+        cell.update_progress(3.0, Some(10.0), Some("Indexing files".into()));
+        let rendered = render_lines(&cell.display_lines(80));
+        assert_eq!(rendered[1], "  └ Indexing files (30%)");
+
+        cell.update_progress(4.0, None, None);
+        let rendered = render_lines(&cell.display_lines(80));
+        assert_eq!(rendered[1], "  └ progress: 4");
+
+        assert!(
+            cell.complete(Duration::from_secs(1), Err("boom".into()))
+                .is_none()
+        );
+        let rendered = render_lines(&cell.display_lines(80));
+        assert_eq!(rendered[1], "  └ Error: boom");
+    }
+
     #[test]
     fn completed_mcp_tool_call_success_snapshot() {
         let invocation = McpInvocation {
@@ -1692,6 +1977,34 @@ mod tests {
         insta::assert_snapshot!(rendered);
     }
 
+    #[test]
+    fn completed_mcp_tool_call_renders_structured_content_without_blocks() {
+        let invocation = McpInvocation {
+            server: "metrics".into(),
+            tool: "top_pages".into(),
+            arguments: None,
+        };
+
+        let result = CallToolResult {
+            content: Vec::new(),
+            is_error: None,
+            structured_content: Some(json!({
+                "pages": [{ "path": "/docs", "views": 42 }],
+            })),
+        };
+
+        let mut cell = new_active_mcp_tool_call("call-6".into(), invocation);
+        assert!(
+            cell.complete(Duration::from_millis(300), Ok(result))
+                .is_none()
+        );
+
+        let rendered = render_lines(&cell.display_lines(80)).join("\n");
+
+        assert!(rendered.contains("\"path\": \"/docs\""), "{rendered}");
+        assert!(rendered.contains("\"views\": 42"), "{rendered}");
+    }
+
     #[test]
     fn completed_mcp_tool_call_wrapped_outputs_snapshot() {
         let invocation = McpInvocation {
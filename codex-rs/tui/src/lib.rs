@@ -5,6 +5,7 @@
 #![deny(clippy::disallowed_methods)]
 use app::App;
 pub use app::AppExitInfo;
+use codex_common::SandboxCliArg;
 use codex_core::AuthManager;
 use codex_core::BUILT_IN_OSS_MODEL_PROVIDER_ID;
 use codex_core::CodexAuth;
@@ -19,6 +20,7 @@ use codex_core::config::persist_model_selection;
 use codex_core::find_conversation_path_by_id_str;
 use codex_core::protocol::AskForApproval;
 use codex_core::protocol::SandboxPolicy;
+use codex_core::restore_trashed_session;
 use codex_ollama::DEFAULT_OSS_MODEL;
 use codex_protocol::config_types::SandboxMode;
 use codex_protocol::mcp_protocol::AuthMode;
@@ -57,7 +59,9 @@ pub mod onboarding;
 mod pager_overlay;
 mod render;
 mod resume_picker;
+mod rollout_transcript;
 mod session_log;
+mod session_summary;
 mod shimmer;
 mod slash_command;
 mod status;
@@ -81,6 +85,7 @@ use crate::onboarding::onboarding_screen::run_onboarding_app;
 use crate::tui::Tui;
 pub use cli::Cli;
 use codex_core::internal_storage::InternalStorage;
+pub use session_summary::SessionSummary;
 
 // (tests access modules directly within the crate)
 
@@ -88,19 +93,24 @@ pub async fn run_main(
     cli: Cli,
     codex_linux_sandbox_exe: Option<PathBuf>,
 ) -> std::io::Result<AppExitInfo> {
-    let (sandbox_mode, approval_policy) = if cli.full_auto {
+    let (sandbox_mode, sandbox_preset, approval_policy) = if cli.full_auto {
         (
             Some(SandboxMode::WorkspaceWrite),
+            None,
             Some(AskForApproval::OnRequest),
         )
     } else if cli.dangerously_bypass_approvals_and_sandbox {
         (
             Some(SandboxMode::DangerFullAccess),
+            None,
             Some(AskForApproval::Never),
         )
     } else {
         (
-            cli.sandbox_mode.map(Into::<SandboxMode>::into),
+            cli.sandbox_mode
+                .as_ref()
+                .and_then(SandboxCliArg::sandbox_mode),
+            cli.sandbox_mode.as_ref().and_then(SandboxCliArg::preset),
             cli.approval_policy.map(Into::into),
         )
     };
@@ -122,6 +132,8 @@ pub async fn run_main(
         None
     };
 
+    let sandbox_overridden = sandbox_mode.is_some() || sandbox_preset.is_some();
+
     // canonicalize the cwd
     let cwd = cli.cwd.clone().map(|p| p.canonicalize().unwrap_or(p));
 
@@ -130,6 +142,7 @@ pub async fn run_main(
         review_model: None,
         approval_policy,
         sandbox_mode,
+        sandbox_preset,
         cwd,
         model_provider: model_provider_override,
         config_profile: cli.config_profile.clone(),
@@ -194,7 +207,7 @@ pub async fn run_main(
         &mut config,
         &config_toml,
         approval_policy,
-        sandbox_mode,
+        sandbox_overridden,
         cli_profile_override,
     )?;
 
@@ -350,7 +363,12 @@ async fn run_ratatui_app(
 
     // Determine resume behavior: explicit id, then resume last, then picker.
     let resume_selection = if let Some(id_str) = cli.resume_session_id.as_deref() {
-        match find_conversation_path_by_id_str(&config.codex_home, id_str).await? {
+        let path = match find_conversation_path_by_id_str(&config.codex_home, id_str).await? {
+            Some(path) => Some(path),
+            // A deleted session is restored from the trash when resumed.
+            None => restore_trashed_session(&config.codex_home, id_str).await?,
+        };
+        match path {
             Some(path) => resume_picker::ResumeSelection::Resume(path),
             None => {
                 error!("Error finding conversation path: {id_str}");
@@ -374,6 +392,7 @@ async fn run_ratatui_app(
                 return Ok(AppExitInfo {
                     token_usage: codex_core::protocol::TokenUsage::default(),
                     conversation_id: None,
+                    session_summary: SessionSummary::default(),
                 });
             }
             other => other,
@@ -475,14 +494,14 @@ fn determine_repo_trust_state(
     config: &mut Config,
     config_toml: &ConfigToml,
     approval_policy_overide: Option<AskForApproval>,
-    sandbox_mode_override: Option<SandboxMode>,
+    sandbox_overridden: bool,
     config_profile_override: Option<String>,
 ) -> std::io::Result<bool> {
     let config_profile = config_toml.get_config_profile(config_profile_override)?;
 
-    if approval_policy_overide.is_some() || sandbox_mode_override.is_some() {
-        // if the user has overridden either approval policy or sandbox mode,
-        // skip the trust flow
+    if approval_policy_overide.is_some() || sandbox_overridden {
+        // if the user has overridden either approval policy or sandbox mode
+        // (or picked a sandbox preset), skip the trust flow
         Ok(false)
     } else if config_profile.approval_policy.is_some() {
         // if the user has specified settings in a config profile, skip the trust flow
@@ -601,6 +620,7 @@ mod tests {
                         account_id: None,
                     }),
                     last_refresh: None,
+                    mcp_servers: Default::default(),
                 };
                 let file = get_auth_file(codex_home);
                 write_auth_json(&file, &auth).expect("write chatgpt auth.json");
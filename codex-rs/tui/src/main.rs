@@ -21,10 +21,14 @@ fn main() -> anyhow::Result<()> {
             .config_overrides
             .raw_overrides
             .splice(0..0, top_cli.config_overrides.raw_overrides);
+        let quiet = inner.quiet;
         let exit_info = run_main(inner, codex_linux_sandbox_exe).await?;
         let token_usage = exit_info.token_usage;
-        if !token_usage.is_zero() {
+        if !quiet && !token_usage.is_zero() {
             println!("{}", codex_core::protocol::FinalOutput::from(token_usage),);
+            for line in exit_info.session_summary.lines() {
+                println!("{line}");
+            }
         }
         Ok(())
     })
@@ -4,6 +4,7 @@ use std::time::Duration;
 
 use crate::history_cell::HistoryCell;
 use crate::render::line_utils::push_owned_lines;
+use crate::rollout_transcript::RolloutTranscript;
 use crate::tui;
 use crate::tui::TuiEvent;
 use crossterm::event::KeyCode;
@@ -25,6 +26,7 @@ use ratatui::widgets::WidgetRef;
 
 pub(crate) enum Overlay {
     Transcript(TranscriptOverlay),
+    Rollout(RolloutTranscriptOverlay),
     Static(StaticOverlay),
 }
 
@@ -33,6 +35,12 @@ impl Overlay {
         Self::Transcript(TranscriptOverlay::new(cells))
     }
 
+    pub(crate) fn new_rollout_transcript(path: &std::path::Path) -> Result<Self> {
+        Ok(Self::Rollout(RolloutTranscriptOverlay::new(
+            RolloutTranscript::open(path)?,
+        )))
+    }
+
     pub(crate) fn new_static_with_title(lines: Vec<Line<'static>>, title: String) -> Self {
         Self::Static(StaticOverlay::with_title(lines, title))
     }
@@ -40,6 +48,7 @@ impl Overlay {
     pub(crate) fn handle_event(&mut self, tui: &mut tui::Tui, event: TuiEvent) -> Result<()> {
         match self {
             Overlay::Transcript(o) => o.handle_event(tui, event),
+            Overlay::Rollout(o) => o.handle_event(tui, event),
             Overlay::Static(o) => o.handle_event(tui, event),
         }
     }
@@ -47,6 +56,7 @@ impl Overlay {
     pub(crate) fn is_done(&self) -> bool {
         match self {
             Overlay::Transcript(o) => o.is_done(),
+            Overlay::Rollout(o) => o.is_done(),
             Overlay::Static(o) => o.is_done(),
         }
     }
@@ -294,6 +304,13 @@ impl PagerView {
         });
     }
 
+    /// Wrapped lines below the bottom of the viewport, once wrapping is known.
+    fn lines_below_viewport(&self, viewport_height: usize) -> Option<usize> {
+        let cache = self.wrap_cache.as_ref()?;
+        let bottom = self.scroll_offset.saturating_add(viewport_height);
+        Some(cache.wrapped.len().saturating_sub(bottom))
+    }
+
     fn cached(&self) -> &[Line<'static>] {
         if let Some(cache) = self.wrap_cache.as_ref() {
             &cache.wrapped
@@ -483,6 +500,100 @@ impl TranscriptOverlay {
     }
 }
 
+/// Rollout entries parsed per load while the user nears the end of the
+/// transcript.
+const ROLLOUT_ENTRIES_PER_LOAD: usize = 200;
+
+/// Transcript rendered from the session's rollout file rather than from the
+/// in-memory history cells, so resumed sessions show their full history.
+pub(crate) struct RolloutTranscriptOverlay {
+    view: PagerView,
+    transcript: RolloutTranscript,
+    is_done: bool,
+}
+
+impl RolloutTranscriptOverlay {
+    pub(crate) fn new(mut transcript: RolloutTranscript) -> Self {
+        let texts = transcript.load_more(ROLLOUT_ENTRIES_PER_LOAD);
+        Self {
+            view: PagerView::new(texts, "R O L L O U T".to_string(), 0),
+            transcript,
+            is_done: false,
+        }
+    }
+
+    /// Load more entries while fewer than two screens of content remain below
+    /// the viewport. Jumping to the end therefore loads the whole file.
+    fn load_until_filled(&mut self, content_area: Rect) {
+        let viewport_height = content_area.height as usize;
+        loop {
+            self.view.ensure_wrapped(content_area.width);
+            let Some(below) = self.view.lines_below_viewport(viewport_height) else {
+                return;
+            };
+            if below >= viewport_height.saturating_mul(2) {
+                return;
+            }
+            let texts = self.transcript.load_more(ROLLOUT_ENTRIES_PER_LOAD);
+            if texts.is_empty() {
+                return;
+            }
+            self.view.texts.extend(texts);
+        }
+    }
+
+    fn render_hints(&self, area: Rect, buf: &mut Buffer) {
+        let line1 = Rect::new(area.x, area.y, area.width, 1);
+        let line2 = Rect::new(area.x, area.y.saturating_add(1), area.width, 1);
+        render_key_hints(line1, buf, PAGER_KEY_HINTS);
+        let pairs = [("q", "quit")];
+        render_key_hints(line2, buf, &pairs);
+    }
+
+    pub(crate) fn render(&mut self, area: Rect, buf: &mut Buffer) {
+        let top_h = area.height.saturating_sub(3);
+        let top = Rect::new(area.x, area.y, area.width, top_h);
+        let bottom = Rect::new(area.x, area.y + top_h, area.width, 3);
+        self.load_until_filled(self.view.scroll_area(top));
+        self.view.render(top, buf);
+        self.render_hints(bottom, buf);
+    }
+}
+
+impl RolloutTranscriptOverlay {
+    pub(crate) fn handle_event(&mut self, tui: &mut tui::Tui, event: TuiEvent) -> Result<()> {
+        match event {
+            TuiEvent::Key(key_event) => match key_event {
+                KeyEvent {
+                    code: KeyCode::Char('q') | KeyCode::Esc,
+                    kind: KeyEventKind::Press,
+                    ..
+                }
+                | KeyEvent {
+                    code: KeyCode::Char('c'),
+                    modifiers: crossterm::event::KeyModifiers::CONTROL,
+                    kind: KeyEventKind::Press,
+                    ..
+                } => {
+                    self.is_done = true;
+                    Ok(())
+                }
+                other => self.view.handle_key_event(tui, other),
+            },
+            TuiEvent::Draw => {
+                tui.draw(u16::MAX, |frame| {
+                    self.render(frame.area(), frame.buffer);
+                })?;
+                Ok(())
+            }
+            _ => Ok(()),
+        }
+    }
+    pub(crate) fn is_done(&self) -> bool {
+        self.is_done
+    }
+}
+
 pub(crate) struct StaticOverlay {
     view: PagerView,
     is_done: bool,
@@ -833,4 +944,39 @@ mod tests {
             "wrapped length should grow or stay same after append"
         );
     }
+
+    #[test]
+    fn rollout_overlay_loads_entries_as_it_scrolls() {
+        use std::io::Write;
+
+        let total = ROLLOUT_ENTRIES_PER_LOAD * 3;
+        let tmp = tempfile::NamedTempFile::new().expect("tempfile");
+        let mut file = tmp.reopen().expect("reopen");
+        for i in 0..total {
+            let line = serde_json::json!({
+                "timestamp": "2025-01-01T00:00:00Z",
+                "type": "response_item",
+                "payload": {
+                    "type": "message",
+                    "role": "user",
+                    "content": [{ "type": "input_text", "text": format!("message {i}") }],
+                },
+            });
+            writeln!(file, "{line}").expect("write rollout line");
+        }
+
+        let transcript = RolloutTranscript::open(tmp.path()).expect("open rollout");
+        let mut overlay = RolloutTranscriptOverlay::new(transcript);
+        let area = Rect::new(0, 0, 40, 12);
+        let mut buf = Buffer::empty(area);
+        overlay.render(area, &mut buf);
+        assert_eq!(overlay.view.texts.len(), ROLLOUT_ENTRIES_PER_LOAD);
+        assert!(buffer_to_text(&buf, area).contains("message 0"));
+
+        // Jumping to the end loads the rest of the file.
+        overlay.view.scroll_offset = usize::MAX;
+        overlay.render(area, &mut buf);
+        assert_eq!(overlay.view.texts.len(), total);
+        assert!(buffer_to_text(&buf, area).contains(&format!("message {}", total - 1)));
+    }
 }
@@ -0,0 +1,385 @@
+//! Renders a session transcript directly from its rollout file.
+//!
+//! Unlike the regular transcript overlay, which shows the in-memory history
+//! cells, this reads everything the session recorded, including the turns a
+//! resumed session only restored into the model's context. Entries are parsed
+//! lazily, one chunk at a time, as the pager approaches the end of what has
+//! been loaded so far.
+
+use std::fs::File;
+use std::io::BufRead;
+use std::io::BufReader;
+use std::path::Path;
+
+use codex_core::parse_rollout_line;
+use codex_core::protocol::ENVIRONMENT_CONTEXT_OPEN_TAG;
+use codex_core::protocol::USER_INSTRUCTIONS_OPEN_TAG;
+use codex_protocol::models::ContentItem;
+use codex_protocol::models::LocalShellAction;
+use codex_protocol::models::ReasoningItemReasoningSummary;
+use codex_protocol::models::ResponseItem;
+use codex_protocol::models::WebSearchAction;
+use codex_protocol::protocol::RolloutItem;
+use ratatui::style::Stylize;
+use ratatui::text::Line;
+use ratatui::text::Text;
+
+use crate::render::line_utils::push_owned_lines;
+
+/// Tool output lines shown before the rest is elided.
+const MAX_TOOL_OUTPUT_LINES: usize = 8;
+
+pub(crate) struct RolloutTranscript {
+    reader: BufReader<File>,
+    /// Trailing bytes of a line that was still being written when last read.
+    partial: String,
+    rendered_any: bool,
+}
+
+impl RolloutTranscript {
+    pub(crate) fn open(path: &Path) -> std::io::Result<Self> {
+        Ok(Self {
+            reader: BufReader::new(File::open(path)?),
+            partial: String::new(),
+            rendered_any: false,
+        })
+    }
+
+    /// Read up to `max_entries` more rollout lines and render the ones that
+    /// belong in the transcript. Returns an empty list at the end of the file;
+    /// calling it again later picks up entries appended in the meantime.
+    pub(crate) fn load_more(&mut self, max_entries: usize) -> Vec<Text<'static>> {
+        let mut texts = Vec::new();
+        for _ in 0..max_entries {
+            let mut buf = String::new();
+            match self.reader.read_line(&mut buf) {
+                Ok(0) => break,
+                Ok(_) => {}
+                Err(e) => {
+                    tracing::warn!("failed to read rollout: {e}");
+                    break;
+                }
+            }
+            self.partial.push_str(&buf);
+            if !self.partial.ends_with('\n') {
+                break;
+            }
+            let line = std::mem::take(&mut self.partial);
+            let line = line.trim();
+            if line.is_empty() {
+                continue;
+            }
+            let item = match parse_rollout_line(line) {
+                Ok(Some(rollout_line)) => rollout_line.item,
+                Ok(None) => continue,
+                Err(e) => {
+                    tracing::warn!("skipping unparseable rollout line: {e}");
+                    continue;
+                }
+            };
+            let Some((lines, continuation)) = render_rollout_item(&item) else {
+                continue;
+            };
+            let mut chunk: Vec<Line<'static>> = Vec::new();
+            if self.rendered_any && !continuation {
+                chunk.push(Line::from(""));
+            }
+            chunk.extend(lines);
+            texts.push(Text::from(chunk));
+            self.rendered_any = true;
+        }
+        texts
+    }
+}
+
+/// Render one rollout entry. The flag marks entries (tool output) that
+/// continue the previous block rather than starting a new one.
+fn render_rollout_item(item: &RolloutItem) -> Option<(Vec<Line<'static>>, bool)> {
+    match item {
+        RolloutItem::SessionMeta(meta_line) => {
+            let meta = &meta_line.meta;
+            Some((
+                vec![
+                    format!("session {}", meta.id).dim().into(),
+                    format!("started {} in {}", meta.timestamp, meta.cwd.display())
+                        .dim()
+                        .into(),
+                ],
+                false,
+            ))
+        }
+        RolloutItem::ResponseItem(item) => render_response_item(item),
+        RolloutItem::Compacted(compacted) => {
+            let mut lines: Vec<Line<'static>> =
+                vec!["conversation compacted".magenta().bold().into()];
+            lines.extend(truncated_lines(&compacted.message, |l| l.dim().italic()));
+            Some((lines, false))
+        }
+        RolloutItem::Rewound(rewound) => Some((
+            vec![
+                format!("conversation rewound to {}", rewound.checkpoint_id)
+                    .magenta()
+                    .bold()
+                    .into(),
+            ],
+            false,
+        )),
+        RolloutItem::TurnContext(_) | RolloutItem::EventMsg(_) | RolloutItem::SessionState(_) => {
+            None
+        }
+    }
+}
+
+fn render_response_item(item: &ResponseItem) -> Option<(Vec<Line<'static>>, bool)> {
+    match item {
+        ResponseItem::Message { role, content, .. } => {
+            let text = message_text(content);
+            if role == "user" {
+                if text.starts_with(ENVIRONMENT_CONTEXT_OPEN_TAG)
+                    || text.starts_with(USER_INSTRUCTIONS_OPEN_TAG)
+                {
+                    return None;
+                }
+                let mut lines: Vec<Line<'static>> = vec!["user".cyan().bold().into()];
+                lines.extend(text.lines().map(|l| Line::from(l.to_string())));
+                Some((lines, false))
+            } else {
+                let mut lines: Vec<Line<'static>> = vec!["codex".magenta().bold().into()];
+                let rendered = crate::markdown_render::render_markdown_text(&text);
+                push_owned_lines(&rendered.lines, &mut lines);
+                Some((lines, false))
+            }
+        }
+        ResponseItem::Reasoning { summary, .. } => {
+            if summary.is_empty() {
+                return None;
+            }
+            let mut lines: Vec<Line<'static>> = vec!["thinking".magenta().bold().into()];
+            for ReasoningItemReasoningSummary::SummaryText { text } in summary {
+                lines.extend(text.lines().map(|l| l.to_string().dim().italic().into()));
+            }
+            Some((lines, false))
+        }
+        ResponseItem::FunctionCall {
+            name, arguments, ..
+        } => Some((render_function_call(name, arguments), false)),
+        ResponseItem::LocalShellCall { action, .. } => {
+            let LocalShellAction::Exec(exec) = action;
+            Some((exec_lines(&exec.command), false))
+        }
+        ResponseItem::CustomToolCall { name, input, .. } => {
+            let mut lines: Vec<Line<'static>> =
+                vec!["tool".magenta().bold().into(), Line::from(name.clone())];
+            lines.extend(truncated_lines(input, Stylize::dim));
+            Some((lines, false))
+        }
+        ResponseItem::FunctionCallOutput { output, .. } => {
+            Some((truncated_lines(&output.content, Stylize::dim), true))
+        }
+        ResponseItem::CustomToolCallOutput { output, .. } => {
+            Some((truncated_lines(output, Stylize::dim), true))
+        }
+        ResponseItem::WebSearchCall { action, .. } => {
+            let query = match action {
+                WebSearchAction::Search { query } => query.clone(),
+                WebSearchAction::Other => String::new(),
+            };
+            Some((
+                vec!["web search".magenta().bold().into(), Line::from(query)],
+                false,
+            ))
+        }
+        ResponseItem::Other => None,
+    }
+}
+
+fn render_function_call(name: &str, arguments: &str) -> Vec<Line<'static>> {
+    if name == "shell"
+        && let Ok(args) = serde_json::from_str::<serde_json::Value>(arguments)
+        && let Some(command) = args.get("command").and_then(|c| c.as_array())
+    {
+        let command: Vec<String> = command
+            .iter()
+            .filter_map(|c| c.as_str().map(str::to_string))
+            .collect();
+        return exec_lines(&command);
+    }
+    let mut lines: Vec<Line<'static>> =
+        vec!["tool".magenta().bold().into(), Line::from(name.to_string())];
+    lines.extend(truncated_lines(arguments, Stylize::dim));
+    lines
+}
+
+fn exec_lines(command: &[String]) -> Vec<Line<'static>> {
+    let joined =
+        shlex::try_join(command.iter().map(String::as_str)).unwrap_or_else(|_| command.join(" "));
+    vec!["exec".magenta().bold().into(), format!("$ {joined}").into()]
+}
+
+fn message_text(content: &[ContentItem]) -> String {
+    content
+        .iter()
+        .map(|item| match item {
+            ContentItem::InputText { text } | ContentItem::OutputText { text } => text.as_str(),
+            ContentItem::InputImage { .. } => "[image]",
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn truncated_lines(
+    text: &str,
+    style: impl Fn(String) -> ratatui::text::Span<'static>,
+) -> Vec<Line<'static>> {
+    let total = text.lines().count();
+    let mut lines: Vec<Line<'static>> = text
+        .lines()
+        .take(MAX_TOOL_OUTPUT_LINES)
+        .map(|l| style(l.to_string()).into())
+        .collect();
+    if total > MAX_TOOL_OUTPUT_LINES {
+        lines.push(
+            format!("… +{} lines", total - MAX_TOOL_OUTPUT_LINES)
+                .dim()
+                .into(),
+        );
+    }
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use std::io::Write;
+
+    fn write_line(file: &mut File, item: serde_json::Value) {
+        let line = serde_json::json!({ "timestamp": "2025-01-01T00:00:00Z" });
+        let mut line = line.as_object().cloned().unwrap_or_default();
+        if let serde_json::Value::Object(item) = item {
+            line.extend(item);
+        }
+        writeln!(file, "{}", serde_json::Value::Object(line)).expect("write rollout line");
+    }
+
+    fn message(role: &str, text: &str) -> serde_json::Value {
+        let kind = if role == "user" {
+            "input_text"
+        } else {
+            "output_text"
+        };
+        serde_json::json!({
+            "type": "response_item",
+            "payload": {
+                "type": "message",
+                "role": role,
+                "content": [{ "type": kind, "text": text }],
+            },
+        })
+    }
+
+    fn texts_to_strings(texts: &[Text<'static>]) -> Vec<String> {
+        texts
+            .iter()
+            .flat_map(|text| text.lines.iter())
+            .map(|line| {
+                line.spans
+                    .iter()
+                    .map(|span| span.content.as_ref())
+                    .collect::<String>()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn renders_conversation_and_skips_context_messages() {
+        let tmp = tempfile::NamedTempFile::new().expect("tempfile");
+        let mut file = tmp.reopen().expect("reopen");
+        write_line(
+            &mut file,
+            message(
+                "user",
+                "<environment_context>\n  <cwd>/tmp</cwd>\n</environment_context>",
+            ),
+        );
+        write_line(&mut file, message("user", "list the files"));
+        write_line(
+            &mut file,
+            serde_json::json!({
+                "type": "response_item",
+                "payload": {
+                    "type": "function_call",
+                    "name": "shell",
+                    "arguments": "{\"command\":[\"ls\",\"-la\"]}",
+                    "call_id": "call-1",
+                },
+            }),
+        );
+        write_line(
+            &mut file,
+            serde_json::json!({
+                "type": "response_item",
+                "payload": {
+                    "type": "function_call_output",
+                    "call_id": "call-1",
+                    "output": "a.txt\nb.txt",
+                },
+            }),
+        );
+        write_line(&mut file, message("assistant", "Two files."));
+
+        let mut transcript = RolloutTranscript::open(tmp.path()).expect("open rollout");
+        let texts = transcript.load_more(usize::MAX);
+        assert_eq!(
+            texts_to_strings(&texts),
+            vec![
+                "user",
+                "list the files",
+                "",
+                "exec",
+                "$ ls -la",
+                "a.txt",
+                "b.txt",
+                "",
+                "codex",
+                "Two files.",
+            ]
+        );
+    }
+
+    #[test]
+    fn loads_lazily_and_picks_up_appended_entries() {
+        let tmp = tempfile::NamedTempFile::new().expect("tempfile");
+        let mut file = tmp.reopen().expect("reopen");
+        for i in 0..5 {
+            write_line(&mut file, message("user", &format!("message {i}")));
+        }
+
+        let mut transcript = RolloutTranscript::open(tmp.path()).expect("open rollout");
+        assert_eq!(transcript.load_more(2).len(), 2);
+        assert_eq!(transcript.load_more(10).len(), 3);
+        assert!(transcript.load_more(10).is_empty());
+
+        write_line(&mut file, message("assistant", "later"));
+        let texts = transcript.load_more(10);
+        assert_eq!(texts_to_strings(&texts), vec!["", "codex", "later"]);
+    }
+
+    #[test]
+    fn long_tool_output_is_truncated() {
+        let output = (0..20)
+            .map(|i| i.to_string())
+            .collect::<Vec<_>>()
+            .join("\n");
+        let lines = truncated_lines(&output, Stylize::dim);
+        assert_eq!(lines.len(), MAX_TOOL_OUTPUT_LINES + 1);
+        assert_eq!(
+            lines.last().map(|l| l
+                .spans
+                .iter()
+                .map(|s| s.content.as_ref())
+                .collect::<String>()),
+            Some("… +12 lines".to_string())
+        );
+    }
+}
@@ -0,0 +1,150 @@
+//! Totals for the summary printed when the session ends.
+
+use std::collections::BTreeSet;
+use std::time::Duration;
+use std::time::Instant;
+
+use codex_common::elapsed::format_duration;
+use codex_core::config_types::ModelPricing;
+use codex_core::protocol::TokenUsage;
+
+/// Accumulates what the summary reports while the session runs.
+#[derive(Debug)]
+pub(crate) struct SessionStats {
+    started_at: Instant,
+    changed_files: BTreeSet<String>,
+    commands_run: u64,
+}
+
+impl SessionStats {
+    pub(crate) fn new() -> Self {
+        Self {
+            started_at: Instant::now(),
+            changed_files: BTreeSet::new(),
+            commands_run: 0,
+        }
+    }
+
+    /// Records the files in a turn's diff. Turn diffs only cover their own
+    /// turn, so the session's files are the union over all turns.
+    pub(crate) fn record_turn_diff(&mut self, unified_diff: &str) {
+        for line in unified_diff.lines() {
+            if let Some(header) = line.strip_prefix("diff --git a/")
+                && let Some((_, path)) = header.rsplit_once(" b/")
+            {
+                self.changed_files.insert(path.to_string());
+            }
+        }
+    }
+
+    pub(crate) fn record_command(&mut self) {
+        self.commands_run += 1;
+    }
+
+    pub(crate) fn summary(
+        &self,
+        token_usage: &TokenUsage,
+        pricing: Option<ModelPricing>,
+    ) -> SessionSummary {
+        SessionSummary {
+            changed_files: self.changed_files.iter().cloned().collect(),
+            commands_run: self.commands_run,
+            duration: self.started_at.elapsed(),
+            estimated_cost_usd: pricing.map(|pricing| pricing.cost_usd(token_usage)),
+        }
+    }
+}
+
+/// What the session did, printed after the token usage on exit.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SessionSummary {
+    /// Files changed by the agent, as shown in the turn diffs.
+    pub changed_files: Vec<String>,
+    pub commands_run: u64,
+    pub duration: Duration,
+    /// `None` when no prices are known for the model.
+    pub estimated_cost_usd: Option<f64>,
+}
+
+impl SessionSummary {
+    pub fn lines(&self) -> Vec<String> {
+        let mut session = format!(
+            "Session: duration={} commands={}",
+            format_duration(self.duration),
+            self.commands_run
+        );
+        if let Some(cost) = self.estimated_cost_usd {
+            session.push_str(&format!(" estimated_cost=${cost:.2}"));
+        }
+
+        let mut lines = vec![session];
+        if !self.changed_files.is_empty() {
+            lines.push(format!("Files changed ({}):", self.changed_files.len()));
+            lines.extend(self.changed_files.iter().map(|path| format!("  {path}")));
+        }
+        lines
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn collects_files_across_turn_diffs() {
+        let mut stats = SessionStats::new();
+        stats.record_turn_diff(
+            "diff --git a/src/lib.rs b/src/lib.rs\nindex 1..2\n--- a/src/lib.rs\n+++ b/src/lib.rs\n",
+        );
+        stats.record_turn_diff(
+            "diff --git a/README.md b/README.md\nnew file mode 100644\ndiff --git a/src/lib.rs b/src/lib.rs\n",
+        );
+        stats.record_command();
+
+        let summary = stats.summary(&TokenUsage::default(), None);
+        assert_eq!(
+            summary.changed_files,
+            vec!["README.md".to_string(), "src/lib.rs".to_string()]
+        );
+        assert_eq!(summary.commands_run, 1);
+        assert_eq!(summary.estimated_cost_usd, None);
+    }
+
+    #[test]
+    fn estimates_cost_with_cached_input_discount() {
+        let token_usage = TokenUsage {
+            input_tokens: 1_000_000,
+            cached_input_tokens: 400_000,
+            output_tokens: 100_000,
+            reasoning_output_tokens: 50_000,
+            total_tokens: 1_100_000,
+        };
+        let pricing = ModelPricing {
+            input: 1.25,
+            cached_input: Some(0.125),
+            output: 10.0,
+        };
+        let cost = pricing.cost_usd(&token_usage);
+        assert!((cost - 1.8).abs() < 1e-9, "cost was {cost}");
+    }
+
+    #[test]
+    fn lines_list_changed_files_after_the_totals() {
+        let summary = SessionSummary {
+            changed_files: vec!["a.txt".to_string(), "b/c.rs".to_string()],
+            commands_run: 3,
+            duration: Duration::from_secs(75),
+            estimated_cost_usd: Some(0.4213),
+        };
+        assert_eq!(
+            summary.lines(),
+            vec![
+                "Session: duration=1m 15s commands=3 estimated_cost=$0.42".to_string(),
+                "Files changed (2):".to_string(),
+                "  a.txt".to_string(),
+                "  b/c.rs".to_string(),
+            ]
+        );
+    }
+}
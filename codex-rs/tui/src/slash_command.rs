@@ -19,10 +19,18 @@ pub enum SlashCommand {
     Init,
     Compact,
     Undo,
+    Rewind,
+    Steer,
     Diff,
+    Changes,
+    Transcript,
     Mention,
     Status,
+    Env,
+    Context,
     Mcp,
+    Archive,
+    Delete,
     Logout,
     Quit,
     #[cfg(debug_assertions)]
@@ -37,14 +45,22 @@ impl SlashCommand {
             SlashCommand::Init => "create an AGENTS.md file with instructions for Codex",
             SlashCommand::Compact => "summarize conversation to prevent hitting the context limit",
             SlashCommand::Review => "review my current changes and find issues",
-            SlashCommand::Undo => "restore the workspace to the last Codex snapshot",
+            SlashCommand::Undo => "revert the last change Codex made to the workspace",
+            SlashCommand::Rewind => "go back to before an earlier message and try again",
+            SlashCommand::Steer => "send guidance to the running task without interrupting it",
             SlashCommand::Quit => "exit Codex",
             SlashCommand::Diff => "show git diff (including untracked files)",
+            SlashCommand::Changes => "show what Codex changed since the start of its last task",
+            SlashCommand::Transcript => "browse the full session transcript from the rollout file",
             SlashCommand::Mention => "mention a file",
             SlashCommand::Status => "show current session configuration and token usage",
             SlashCommand::Model => "choose what model and reasoning effort to use",
             SlashCommand::Approvals => "choose what Codex can do without approval",
-            SlashCommand::Mcp => "list configured MCP tools",
+            SlashCommand::Env => "show the environment variables Codex runs commands with",
+            SlashCommand::Context => "preview what the model will see on the next turn",
+            SlashCommand::Mcp => "list MCP tools, or add/remove a server for this session",
+            SlashCommand::Archive => "archive this session and exit",
+            SlashCommand::Delete => "move this session to the trash and exit",
             SlashCommand::Logout => "log out of Codex",
             #[cfg(debug_assertions)]
             SlashCommand::TestApproval => "test approval request",
@@ -64,13 +80,21 @@ impl SlashCommand {
             | SlashCommand::Init
             | SlashCommand::Compact
             | SlashCommand::Undo
+            | SlashCommand::Rewind
             | SlashCommand::Model
             | SlashCommand::Approvals
             | SlashCommand::Review
+            | SlashCommand::Archive
+            | SlashCommand::Delete
             | SlashCommand::Logout => false,
-            SlashCommand::Diff
+            SlashCommand::Steer
+            | SlashCommand::Diff
+            | SlashCommand::Changes
+            | SlashCommand::Transcript
             | SlashCommand::Mention
             | SlashCommand::Status
+            | SlashCommand::Env
+            | SlashCommand::Context
             | SlashCommand::Mcp
             | SlashCommand::Quit => true,
 
@@ -80,22 +104,15 @@ impl SlashCommand {
     }
 }
 
-/// Return all built-in commands in a Vec paired with their command string.
-pub fn built_in_slash_commands() -> Vec<(&'static str, SlashCommand)> {
-    let show_beta_features = beta_features_enabled();
-
-    SlashCommand::iter()
-        .filter(|cmd| {
-            if *cmd == SlashCommand::Undo {
-                show_beta_features
-            } else {
-                true
-            }
-        })
-        .map(|c| (c.command(), c))
-        .collect()
+impl SlashCommand {
+    /// Whether text typed after the command is passed along as arguments
+    /// (e.g. `/mcp add docs npx docs-server`) instead of being dropped.
+    pub fn takes_args(self) -> bool {
+        matches!(self, SlashCommand::Mcp | SlashCommand::Steer)
+    }
 }
 
-fn beta_features_enabled() -> bool {
-    std::env::var_os("BETA_FEATURE").is_some()
+/// Return all built-in commands in a Vec paired with their command string.
+pub fn built_in_slash_commands() -> Vec<(&'static str, SlashCommand)> {
+    SlashCommand::iter().map(|c| (c.command(), c)).collect()
 }
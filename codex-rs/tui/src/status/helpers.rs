@@ -5,6 +5,7 @@ use chrono::Local;
 use codex_core::auth::get_auth_file;
 use codex_core::auth::try_read_auth_json;
 use codex_core::config::Config;
+use codex_core::path_utils::display_path;
 use codex_core::project_doc::discover_project_doc_paths;
 use std::path::Path;
 use unicode_width::UnicodeWidthStr;
@@ -142,7 +143,7 @@ pub(crate) fn format_directory_display(directory: &Path, max_width: Option<usize
             format!("~{}{}", std::path::MAIN_SEPARATOR, rel.display())
         }
     } else {
-        directory.display().to_string()
+        display_path(directory)
     };
 
     if let Some(max_width) = max_width {
@@ -71,6 +71,7 @@ fn status_snapshot_includes_reasoning_details() {
     config.sandbox_policy = SandboxPolicy::WorkspaceWrite {
         writable_roots: Vec::new(),
         network_access: false,
+        allowed_domains: vec![],
         exclude_tmpdir_env_var: false,
         exclude_slash_tmp: false,
     };
@@ -223,6 +223,22 @@ impl Tui {
             false
         }
     }
+
+    /// Ring the terminal bell `times` times now if the terminal is unfocused.
+    /// Returns true if the bell was rung.
+    pub fn ring_bell(&mut self, times: usize) -> bool {
+        if !self.terminal_focused.load(Ordering::Relaxed) {
+            let _ = execute!(stdout(), RingBell(times));
+            true
+        } else {
+            false
+        }
+    }
+
+    pub fn is_focused(&self) -> bool {
+        self.terminal_focused.load(Ordering::Relaxed)
+    }
+
     pub fn new(terminal: Terminal) -> Self {
         let (frame_schedule_tx, frame_schedule_rx) = tokio::sync::mpsc::unbounded_channel();
         let (draw_tx, _) = tokio::sync::broadcast::channel(1);
@@ -583,3 +599,28 @@ impl Command for PostNotification {
         true
     }
 }
+
+/// Command that rings the terminal bell (BEL) the given number of times.
+#[derive(Debug, Clone)]
+pub struct RingBell(pub usize);
+
+impl Command for RingBell {
+    fn write_ansi(&self, f: &mut impl std::fmt::Write) -> std::fmt::Result {
+        for _ in 0..self.0 {
+            f.write_char('\x07')?;
+        }
+        Ok(())
+    }
+
+    #[cfg(windows)]
+    fn execute_winapi(&self) -> std::io::Result<()> {
+        Err(std::io::Error::other(
+            "tried to execute RingBell using WinAPI; use ANSI instead",
+        ))
+    }
+
+    #[cfg(windows)]
+    fn is_ansi_code_supported(&self) -> bool {
+        true
+    }
+}
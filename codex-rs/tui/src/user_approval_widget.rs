@@ -9,7 +9,12 @@
 use std::path::PathBuf;
 use std::sync::LazyLock;
 
+use codex_core::command_pattern::pattern_for_command;
+use codex_core::protocol::EscalatedPermission;
+use codex_core::protocol::GuidedPermission;
 use codex_core::protocol::Op;
+use codex_core::protocol::PatchPathEscape;
+use codex_core::protocol::PatchStats;
 use codex_core::protocol::ReviewDecision;
 use crossterm::event::KeyCode;
 use crossterm::event::KeyEvent;
@@ -43,6 +48,27 @@ pub(crate) enum ApprovalRequest {
         id: String,
         reason: Option<String>,
         grant_root: Option<PathBuf>,
+        /// Lines added and removed; empty when the agent did not send them.
+        stats: PatchStats,
+        /// Writes dropped from the patch because they escape the writable
+        /// roots.
+        rejected_paths: Vec<PatchPathEscape>,
+    },
+    Escalation {
+        id: String,
+        command: Vec<String>,
+        justification: String,
+        requested_permissions: Vec<EscalatedPermission>,
+    },
+    Permission {
+        id: String,
+        permission: GuidedPermission,
+        detail: String,
+    },
+    SpendLimit {
+        id: String,
+        estimated_cost_usd: f64,
+        limit_usd: f64,
     },
 }
 
@@ -70,6 +96,12 @@ static COMMAND_SELECT_OPTIONS: LazyLock<Vec<SelectOption>> = LazyLock::new(|| {
             key: KeyCode::Char('a'),
             decision: ReviewDecision::ApprovedForSession,
         },
+        SelectOption {
+            label: Line::from(vec!["P".underlined(), "attern".into()]),
+            description: "Approve commands like this one in every session",
+            key: KeyCode::Char('p'),
+            decision: ReviewDecision::ApprovedAlways,
+        },
         SelectOption {
             label: Line::from(vec!["N".underlined(), "o, provide feedback".into()]),
             description: "Do not run the command; provide feedback",
@@ -96,12 +128,56 @@ static PATCH_SELECT_OPTIONS: LazyLock<Vec<SelectOption>> = LazyLock::new(|| {
     ]
 });
 
+static PERMISSION_SELECT_OPTIONS: LazyLock<Vec<SelectOption>> = LazyLock::new(|| {
+    vec![
+        SelectOption {
+            label: Line::from(vec!["Y".underlined(), "es".into()]),
+            description: "Allow this for the project and don't ask again",
+            key: KeyCode::Char('y'),
+            decision: ReviewDecision::Approved,
+        },
+        SelectOption {
+            label: Line::from(vec!["N".underlined(), "o".into()]),
+            description: "Deny this for the project and don't ask again",
+            key: KeyCode::Char('n'),
+            decision: ReviewDecision::Denied,
+        },
+    ]
+});
+
+static SPEND_LIMIT_SELECT_OPTIONS: LazyLock<Vec<SelectOption>> = LazyLock::new(|| {
+    vec![
+        SelectOption {
+            label: Line::from(vec!["Y".underlined(), "es".into()]),
+            description: "Keep going and ask again after the same amount is spent",
+            key: KeyCode::Char('y'),
+            decision: ReviewDecision::Approved,
+        },
+        SelectOption {
+            label: Line::from(vec!["A".underlined(), "lways".into()]),
+            description: "Keep going and don't ask again this session",
+            key: KeyCode::Char('a'),
+            decision: ReviewDecision::ApprovedForSession,
+        },
+        SelectOption {
+            label: Line::from(vec!["N".underlined(), "o".into()]),
+            description: "Stop the task",
+            key: KeyCode::Char('n'),
+            decision: ReviewDecision::Denied,
+        },
+    ]
+});
+
 /// A modal prompting the user to approve or deny the pending request.
 pub(crate) struct UserApprovalWidget {
     approval_request: ApprovalRequest,
     app_event_tx: AppEventSender,
     confirmation_prompt: Paragraph<'static>,
-    select_options: &'static Vec<SelectOption>,
+    select_options: Vec<&'static SelectOption>,
+
+    /// Pattern saved when the command is approved for every session, if one
+    /// can be offered for it.
+    command_pattern: Option<String>,
 
     /// Currently selected index in *select* mode.
     selected_option: usize,
@@ -123,10 +199,27 @@ impl UserApprovalWidget {
                 Paragraph::new(contents).wrap(Wrap { trim: false })
             }
             ApprovalRequest::ApplyPatch {
-                reason, grant_root, ..
+                reason,
+                grant_root,
+                stats,
+                rejected_paths,
+                ..
             } => {
                 let mut contents: Vec<Line> = vec![];
 
+                if !stats.files.is_empty() {
+                    contents.push(Line::from(stats.to_string().bold()));
+                    contents.push(Line::from(""));
+                }
+
+                if !rejected_paths.is_empty() {
+                    contents.push(Line::from("These writes will not be applied:".bold()));
+                    for escape in rejected_paths {
+                        contents.push(Line::from(format!("  {escape}").red()));
+                    }
+                    contents.push(Line::from(""));
+                }
+
                 if let Some(r) = reason {
                     contents.push(Line::from(r.clone().italic()));
                     contents.push(Line::from(""));
@@ -142,13 +235,85 @@ impl UserApprovalWidget {
 
                 Paragraph::new(contents).wrap(Wrap { trim: false })
             }
+            ApprovalRequest::Escalation {
+                justification,
+                requested_permissions,
+                ..
+            } => {
+                let mut contents: Vec<Line> = vec![
+                    Line::from(vec![
+                        "Justification: ".bold(),
+                        justification.clone().italic(),
+                    ]),
+                    Line::from(""),
+                ];
+                if requested_permissions.is_empty() {
+                    contents.push(Line::from("This command will run without the sandbox."));
+                } else {
+                    contents.push(Line::from(
+                        "This command will run without the sandbox and gain:",
+                    ));
+                    for permission in requested_permissions {
+                        contents.push(Line::from(vec![
+                            "  • ".dim(),
+                            permission.description().into(),
+                        ]));
+                    }
+                }
+                contents.push(Line::from(""));
+                Paragraph::new(contents).wrap(Wrap { trim: false })
+            }
+            ApprovalRequest::Permission {
+                permission, detail, ..
+            } => {
+                let mut contents: Vec<Line> =
+                    vec![Line::from(permission.explanation()), Line::from("")];
+                for line in detail.lines() {
+                    contents.push(Line::from(line.to_string().dim()));
+                }
+                contents.push(Line::from(""));
+                Paragraph::new(contents).wrap(Wrap { trim: false })
+            }
+            ApprovalRequest::SpendLimit {
+                estimated_cost_usd,
+                limit_usd,
+                ..
+            } => {
+                let contents: Vec<Line> = vec![
+                    Line::from(format!(
+                        "This session has cost an estimated ${estimated_cost_usd:.2}, reaching the ${limit_usd:.2} limit set by max_session_cost_usd."
+                    )),
+                    Line::from(""),
+                ];
+                Paragraph::new(contents).wrap(Wrap { trim: false })
+            }
+        };
+
+        let command_pattern = match &approval_request {
+            ApprovalRequest::Exec { command, .. } | ApprovalRequest::Escalation { command, .. } => {
+                pattern_for_command(command)
+            }
+            ApprovalRequest::ApplyPatch { .. }
+            | ApprovalRequest::Permission { .. }
+            | ApprovalRequest::SpendLimit { .. } => None,
+        };
+        let select_options: &'static Vec<SelectOption> = match &approval_request {
+            ApprovalRequest::Exec { .. } | ApprovalRequest::Escalation { .. } => {
+                &COMMAND_SELECT_OPTIONS
+            }
+            ApprovalRequest::ApplyPatch { .. } => &PATCH_SELECT_OPTIONS,
+            ApprovalRequest::Permission { .. } => &PERMISSION_SELECT_OPTIONS,
+            ApprovalRequest::SpendLimit { .. } => &SPEND_LIMIT_SELECT_OPTIONS,
         };
 
         Self {
-            select_options: match &approval_request {
-                ApprovalRequest::Exec { .. } => &COMMAND_SELECT_OPTIONS,
-                ApprovalRequest::ApplyPatch { .. } => &PATCH_SELECT_OPTIONS,
-            },
+            select_options: select_options
+                .iter()
+                .filter(|opt| {
+                    opt.decision != ReviewDecision::ApprovedAlways || command_pattern.is_some()
+                })
+                .collect(),
+            command_pattern,
             approval_request,
             app_event_tx,
             confirmation_prompt,
@@ -199,7 +364,7 @@ impl UserApprovalWidget {
                 self.selected_option = (self.selected_option + 1) % self.select_options.len();
             }
             KeyCode::Enter => {
-                let opt = &self.select_options[self.selected_option];
+                let opt = self.select_options[self.selected_option];
                 self.send_decision(opt.decision);
             }
             KeyCode::Esc => {
@@ -224,7 +389,7 @@ impl UserApprovalWidget {
 
     fn send_decision_with_feedback(&mut self, decision: ReviewDecision, feedback: String) {
         match &self.approval_request {
-            ApprovalRequest::Exec { command, .. } => {
+            ApprovalRequest::Exec { command, .. } | ApprovalRequest::Escalation { command, .. } => {
                 let full_cmd = strip_bash_lc_and_escape(command);
                 // Construct a concise, single-line summary of the command:
                 // - If multi-line, take the first line and append " ...".
@@ -258,6 +423,17 @@ impl UserApprovalWidget {
                             " every time this session".bold(),
                         ]);
                     }
+                    ReviewDecision::ApprovedAlways => {
+                        let pattern = self.command_pattern.clone().unwrap_or(snippet);
+                        result_spans.extend(vec![
+                            "✔ ".fg(Color::Green),
+                            "You ".into(),
+                            "approved".bold(),
+                            " codex to run ".into(),
+                            pattern.dim(),
+                            " every time".bold(),
+                        ]);
+                    }
                     ReviewDecision::Denied => {
                         result_spans.extend(vec![
                             "✗ ".fg(Color::Red),
@@ -294,16 +470,80 @@ impl UserApprovalWidget {
             ApprovalRequest::ApplyPatch { .. } => {
                 // No history line for patch approval decisions.
             }
+            ApprovalRequest::Permission { permission, .. } => {
+                let key = permission.key();
+                let line = match decision {
+                    ReviewDecision::Approved
+                    | ReviewDecision::ApprovedForSession
+                    | ReviewDecision::ApprovedAlways => Line::from(vec![
+                        "✔ ".fg(Color::Green),
+                        "You ".into(),
+                        "allowed".bold(),
+                        " ".into(),
+                        key.dim(),
+                        " for this project".into(),
+                    ]),
+                    ReviewDecision::Denied => Line::from(vec![
+                        "✗ ".fg(Color::Red),
+                        "You ".into(),
+                        "denied".bold(),
+                        " ".into(),
+                        key.dim(),
+                        " for this project".into(),
+                    ]),
+                    ReviewDecision::Abort => Line::from(vec![
+                        "✗ ".fg(Color::Red),
+                        "You ".into(),
+                        "canceled".bold(),
+                        " the request for ".into(),
+                        key.dim(),
+                    ]),
+                };
+                self.app_event_tx.send(AppEvent::InsertHistoryCell(Box::new(
+                    history_cell::new_user_approval_decision(vec![line]),
+                )));
+            }
+            ApprovalRequest::SpendLimit { .. } => {
+                let line = match decision {
+                    ReviewDecision::Approved => Line::from(vec![
+                        "✔ ".fg(Color::Green),
+                        "You ".into(),
+                        "approved".bold(),
+                        " more spending".into(),
+                    ]),
+                    ReviewDecision::ApprovedForSession | ReviewDecision::ApprovedAlways => {
+                        Line::from(vec![
+                            "✔ ".fg(Color::Green),
+                            "You ".into(),
+                            "lifted".bold(),
+                            " the spend limit for this session".into(),
+                        ])
+                    }
+                    ReviewDecision::Denied | ReviewDecision::Abort => Line::from(vec![
+                        "✗ ".fg(Color::Red),
+                        "You ".into(),
+                        "stopped".bold(),
+                        " the task at the spend limit".into(),
+                    ]),
+                };
+                self.app_event_tx.send(AppEvent::InsertHistoryCell(Box::new(
+                    history_cell::new_user_approval_decision(vec![line]),
+                )));
+            }
         }
 
         let op = match &self.approval_request {
-            ApprovalRequest::Exec { id, .. } => Op::ExecApproval {
+            ApprovalRequest::Exec { id, .. }
+            | ApprovalRequest::Escalation { id, .. }
+            | ApprovalRequest::Permission { id, .. }
+            | ApprovalRequest::SpendLimit { id, .. } => Op::ExecApproval {
                 id: id.clone(),
                 decision,
             },
             ApprovalRequest::ApplyPatch { id, .. } => Op::PatchApproval {
                 id: id.clone(),
                 decision,
+                approved_files: None,
             },
         };
 
@@ -357,6 +597,9 @@ impl WidgetRef for &UserApprovalWidget {
         let title = match &self.approval_request {
             ApprovalRequest::Exec { .. } => "Allow command?",
             ApprovalRequest::ApplyPatch { .. } => "Apply changes?",
+            ApprovalRequest::Escalation { .. } => "Run command outside the sandbox?",
+            ApprovalRequest::Permission { .. } => "Allow for this project?",
+            ApprovalRequest::SpendLimit { .. } => "Keep going?",
         };
         Line::from(title).render(title_area, buf);
 
@@ -373,7 +616,14 @@ impl WidgetRef for &UserApprovalWidget {
             line.render(*area, buf);
         }
 
-        Line::from(self.select_options[self.selected_option].description)
+        let selected = self.select_options[self.selected_option];
+        let description = match &self.command_pattern {
+            Some(pattern) if selected.decision == ReviewDecision::ApprovedAlways => {
+                format!("Approve `{pattern}` now and in future sessions")
+            }
+            _ => selected.description.to_string(),
+        };
+        Line::from(description)
             .style(Style::new().italic().add_modifier(Modifier::DIM))
             .render(description_area.inner(Margin::new(1, 0)), buf);
 
@@ -445,4 +695,95 @@ mod tests {
             })
         )));
     }
+
+    #[test]
+    fn escalation_decision_is_sent_as_exec_approval() {
+        let (tx_raw, mut rx) = unbounded_channel::<AppEvent>();
+        let tx = AppEventSender::new(tx_raw);
+        let req = ApprovalRequest::Escalation {
+            id: "3".to_string(),
+            command: vec!["curl".to_string(), "https://example.com".to_string()],
+            justification: "Needs network access to download the fixture".to_string(),
+            requested_permissions: vec![EscalatedPermission::NetworkAccess],
+        };
+        let mut widget = UserApprovalWidget::new(req, tx);
+        widget.handle_key_event(KeyEvent::new(KeyCode::Char('a'), KeyModifiers::NONE));
+        assert!(widget.is_complete());
+        let mut events: Vec<AppEvent> = Vec::new();
+        while let Ok(ev) = rx.try_recv() {
+            events.push(ev);
+        }
+        assert!(events.iter().any(|e| matches!(
+            e,
+            AppEvent::CodexOp(Op::ExecApproval {
+                decision: ReviewDecision::ApprovedForSession,
+                ..
+            })
+        )));
+    }
+
+    #[test]
+    fn spend_limit_can_be_lifted_for_the_session() {
+        let (tx_raw, mut rx) = unbounded_channel::<AppEvent>();
+        let tx = AppEventSender::new(tx_raw);
+        let req = ApprovalRequest::SpendLimit {
+            id: "4".to_string(),
+            estimated_cost_usd: 5.25,
+            limit_usd: 5.0,
+        };
+        let mut widget = UserApprovalWidget::new(req, tx);
+        widget.handle_key_event(KeyEvent::new(KeyCode::Char('a'), KeyModifiers::NONE));
+        assert!(widget.is_complete());
+        let mut events: Vec<AppEvent> = Vec::new();
+        while let Ok(ev) = rx.try_recv() {
+            events.push(ev);
+        }
+        assert!(events.iter().any(|e| matches!(
+            e,
+            AppEvent::CodexOp(Op::ExecApproval {
+                id,
+                decision: ReviewDecision::ApprovedForSession,
+            }) if id == "4"
+        )));
+    }
+
+    #[test]
+    fn pattern_approval_is_offered_only_when_a_pattern_exists() {
+        let (tx_raw, mut rx) = unbounded_channel::<AppEvent>();
+        let tx = AppEventSender::new(tx_raw);
+        let req = ApprovalRequest::Exec {
+            id: "4".to_string(),
+            command: vec![
+                "cargo".to_string(),
+                "test".to_string(),
+                "-p".to_string(),
+                "codex-core".to_string(),
+            ],
+            reason: None,
+        };
+        let mut widget = UserApprovalWidget::new(req, tx.clone());
+        assert_eq!(widget.command_pattern.as_deref(), Some("cargo test *"));
+        widget.handle_key_event(KeyEvent::new(KeyCode::Char('p'), KeyModifiers::NONE));
+        assert!(widget.is_complete());
+        let mut events: Vec<AppEvent> = Vec::new();
+        while let Ok(ev) = rx.try_recv() {
+            events.push(ev);
+        }
+        assert!(events.iter().any(|e| matches!(
+            e,
+            AppEvent::CodexOp(Op::ExecApproval {
+                decision: ReviewDecision::ApprovedAlways,
+                ..
+            })
+        )));
+
+        let req = ApprovalRequest::Exec {
+            id: "5".to_string(),
+            command: vec!["sh".to_string(), "-c".to_string(), "make".to_string()],
+            reason: None,
+        };
+        let mut widget = UserApprovalWidget::new(req, tx);
+        widget.handle_key_event(KeyEvent::new(KeyCode::Char('p'), KeyModifiers::NONE));
+        assert!(!widget.is_complete());
+    }
 }
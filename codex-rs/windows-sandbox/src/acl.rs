@@ -0,0 +1,393 @@
+//! Capability SIDs and the ACEs that make the writable roots writable under
+//! the write-restricted token.
+//!
+//! Each writable root gets a SID derived from its path. The root is granted
+//! write access for that SID with an inheritable ACE, and its read-only
+//! subpaths get a deny ACE for the same SID. The ACEs a run adds are revoked
+//! when its [`GrantedRoots`] is dropped, after the command exits. One left
+//! behind by a helper that was killed grants nothing: the SID only appears in
+//! tokens whose policy lists the root.
+
+use std::ffi::OsStr;
+use std::ffi::c_void;
+use std::io;
+use std::os::windows::ffi::OsStrExt;
+use std::path::Path;
+use std::path::PathBuf;
+use std::ptr;
+
+use codex_core::protocol::WritableRoot;
+use windows_sys::Win32::Foundation::ERROR_SUCCESS;
+use windows_sys::Win32::Foundation::LocalFree;
+use windows_sys::Win32::Security::ACCESS_ALLOWED_ACE;
+use windows_sys::Win32::Security::ACE_HEADER;
+use windows_sys::Win32::Security::ACL;
+use windows_sys::Win32::Security::Authorization::ACCESS_MODE;
+use windows_sys::Win32::Security::Authorization::ConvertStringSidToSidW;
+use windows_sys::Win32::Security::Authorization::DENY_ACCESS;
+use windows_sys::Win32::Security::Authorization::EXPLICIT_ACCESS_W;
+use windows_sys::Win32::Security::Authorization::GRANT_ACCESS;
+use windows_sys::Win32::Security::Authorization::GetNamedSecurityInfoW;
+use windows_sys::Win32::Security::Authorization::NO_MULTIPLE_TRUSTEE;
+use windows_sys::Win32::Security::Authorization::REVOKE_ACCESS;
+use windows_sys::Win32::Security::Authorization::SE_FILE_OBJECT;
+use windows_sys::Win32::Security::Authorization::SetEntriesInAclW;
+use windows_sys::Win32::Security::Authorization::SetNamedSecurityInfoW;
+use windows_sys::Win32::Security::Authorization::TRUSTEE_IS_SID;
+use windows_sys::Win32::Security::Authorization::TRUSTEE_IS_UNKNOWN;
+use windows_sys::Win32::Security::Authorization::TRUSTEE_W;
+use windows_sys::Win32::Security::CONTAINER_INHERIT_ACE;
+use windows_sys::Win32::Security::DACL_SECURITY_INFORMATION;
+use windows_sys::Win32::Security::EqualSid;
+use windows_sys::Win32::Security::GetAce;
+use windows_sys::Win32::Security::INHERITED_ACE;
+use windows_sys::Win32::Security::NO_INHERITANCE;
+use windows_sys::Win32::Security::OBJECT_INHERIT_ACE;
+use windows_sys::Win32::Security::PSECURITY_DESCRIPTOR;
+use windows_sys::Win32::Security::PSID;
+use windows_sys::Win32::Storage::FileSystem::DELETE;
+use windows_sys::Win32::Storage::FileSystem::FILE_APPEND_DATA;
+use windows_sys::Win32::Storage::FileSystem::FILE_DELETE_CHILD;
+use windows_sys::Win32::Storage::FileSystem::FILE_GENERIC_EXECUTE;
+use windows_sys::Win32::Storage::FileSystem::FILE_GENERIC_READ;
+use windows_sys::Win32::Storage::FileSystem::FILE_GENERIC_WRITE;
+use windows_sys::Win32::Storage::FileSystem::FILE_WRITE_ATTRIBUTES;
+use windows_sys::Win32::Storage::FileSystem::FILE_WRITE_DATA;
+use windows_sys::Win32::Storage::FileSystem::FILE_WRITE_EA;
+
+/// `AceType` values from `winnt.h`.
+const ACCESS_ALLOWED_ACE_TYPE: u8 = 0;
+const ACCESS_DENIED_ACE_TYPE: u8 = 1;
+
+/// Every SID minted here starts with this prefix: the resource manager
+/// authority (9) and "CODX" in ASCII as the first sub-authority.
+const SID_PREFIX: &str = "S-1-9-1129268312";
+
+/// The well-known SID for `Everyone`.
+pub(crate) const EVERYONE_SID: &str = "S-1-1-0";
+
+const WRITE_ACCESS: u32 =
+    FILE_GENERIC_READ | FILE_GENERIC_WRITE | FILE_GENERIC_EXECUTE | DELETE | FILE_DELETE_CHILD;
+
+/// Only the write rights: denying `SYNCHRONIZE` or `READ_CONTROL` (which
+/// `FILE_GENERIC_WRITE` includes) would also break reads.
+const DENIED_WRITE_ACCESS: u32 = FILE_WRITE_DATA
+    | FILE_APPEND_DATA
+    | FILE_WRITE_EA
+    | FILE_WRITE_ATTRIBUTES
+    | DELETE
+    | FILE_DELETE_CHILD;
+
+/// A SID allocated by `ConvertStringSidToSidW`.
+pub(crate) struct Sid(PSID);
+
+impl Sid {
+    pub(crate) fn from_string(sid: &str) -> io::Result<Self> {
+        let mut wide = to_wide(OsStr::new(sid));
+        let mut psid: PSID = ptr::null_mut();
+        // SAFETY: `wide` is NUL-terminated and outlives the call.
+        if unsafe { ConvertStringSidToSidW(wide.as_mut_ptr(), &mut psid) } == 0 {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(Self(psid))
+    }
+
+    pub(crate) fn as_psid(&self) -> PSID {
+        self.0
+    }
+}
+
+impl Drop for Sid {
+    fn drop(&mut self) {
+        // SAFETY: the SID was allocated with `LocalAlloc` by
+        // `ConvertStringSidToSidW` and is not used after this.
+        unsafe {
+            LocalFree(self.0);
+        }
+    }
+}
+
+/// The SID shared by every sandboxed process, used for the objects they
+/// create (such as pipes) rather than for files.
+pub(crate) fn sandbox_sid_string() -> String {
+    format!("{SID_PREFIX}-0")
+}
+
+/// The capability SID for `root`. Windows paths are case-insensitive, so the
+/// path is lowercased before hashing. The hash must stay stable across
+/// releases because the ACEs outlive the process, hence FNV-1a rather than
+/// `DefaultHasher`.
+fn root_sid_string(root: &Path) -> String {
+    let path = root.to_string_lossy().to_lowercase();
+    let path = path.trim_end_matches('\\');
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    for byte in path.bytes() {
+        hash ^= u64::from(byte);
+        hash = hash.wrapping_mul(0x0000_0100_0000_01b3);
+    }
+    format!("{SID_PREFIX}-{}-{}", hash >> 32, hash & 0xffff_ffff)
+}
+
+/// The capability SIDs of the prepared roots, and the ACEs this run added
+/// for them, which are revoked on drop.
+pub(crate) struct GrantedRoots {
+    pub(crate) sids: Vec<Sid>,
+    added: Vec<(PathBuf, Sid)>,
+}
+
+impl Drop for GrantedRoots {
+    fn drop(&mut self) {
+        // Best effort: a failure leaves an ACE that grants nothing on its own.
+        for (path, sid) in self.added.drain(..).rev() {
+            let _ = revoke_aces(&path, &sid);
+        }
+    }
+}
+
+/// Grants each existing root write access for its capability SID and denies
+/// it for the root's read-only subpaths. ACEs that are already there, for
+/// instance because another sandboxed command is using the same root, are
+/// left alone and are not revoked when the result is dropped.
+pub(crate) fn prepare_writable_roots(roots: &[WritableRoot]) -> io::Result<GrantedRoots> {
+    let mut granted = GrantedRoots {
+        sids: Vec::with_capacity(roots.len()),
+        added: Vec::new(),
+    };
+    for root in roots {
+        if !root.root.exists() {
+            continue;
+        }
+        let sid_string = root_sid_string(&root.root);
+        let sid = Sid::from_string(&sid_string)?;
+        if ensure_ace(
+            &root.root,
+            &sid,
+            GRANT_ACCESS,
+            ACCESS_ALLOWED_ACE_TYPE,
+            WRITE_ACCESS,
+        )? {
+            granted
+                .added
+                .push((root.root.clone(), Sid::from_string(&sid_string)?));
+        }
+        for subpath in &root.read_only_subpaths {
+            if subpath.exists()
+                && ensure_ace(
+                    subpath,
+                    &sid,
+                    DENY_ACCESS,
+                    ACCESS_DENIED_ACE_TYPE,
+                    DENIED_WRITE_ACCESS,
+                )?
+            {
+                granted
+                    .added
+                    .push((subpath.clone(), Sid::from_string(&sid_string)?));
+            }
+        }
+        granted.sids.push(sid);
+    }
+    Ok(granted)
+}
+
+/// Adds an explicit ACE for `sid` to `path` unless an equivalent one is
+/// already there, and returns whether it did. Setting the DACL propagates it
+/// through the whole tree, which is slow for large directories.
+fn ensure_ace(
+    path: &Path,
+    sid: &Sid,
+    access_mode: ACCESS_MODE,
+    ace_type: u8,
+    access: u32,
+) -> io::Result<bool> {
+    let inheritance = if path.is_dir() {
+        OBJECT_INHERIT_ACE | CONTAINER_INHERIT_ACE
+    } else {
+        NO_INHERITANCE
+    };
+    let entry = explicit_access(sid, access_mode, inheritance, access);
+    // A NULL DACL already grants everyone full access, and adding an entry to
+    // it would take that away from everybody else.
+    update_dacl(path, &entry, |dacl| {
+        // SAFETY: `update_dacl` only passes valid, non-NULL ACLs.
+        !dacl.is_null() && !unsafe { has_explicit_ace(dacl, sid, ace_type, access) }
+    })
+}
+
+/// Removes every explicit ACE for `sid` from `path`.
+fn revoke_aces(path: &Path, sid: &Sid) -> io::Result<bool> {
+    let entry = explicit_access(sid, REVOKE_ACCESS, NO_INHERITANCE, 0);
+    update_dacl(path, &entry, |dacl| !dacl.is_null())
+}
+
+fn explicit_access(
+    sid: &Sid,
+    access_mode: ACCESS_MODE,
+    inheritance: u32,
+    access: u32,
+) -> EXPLICIT_ACCESS_W {
+    EXPLICIT_ACCESS_W {
+        grfAccessPermissions: access,
+        grfAccessMode: access_mode,
+        grfInheritance: inheritance,
+        Trustee: TRUSTEE_W {
+            pMultipleTrustee: ptr::null_mut(),
+            MultipleTrusteeOperation: NO_MULTIPLE_TRUSTEE,
+            TrusteeForm: TRUSTEE_IS_SID,
+            TrusteeType: TRUSTEE_IS_UNKNOWN,
+            ptstrName: sid.as_psid().cast(),
+        },
+    }
+}
+
+/// Merges `entry` into the DACL of `path` if `should_update` returns true for
+/// the current DACL (which may be NULL), and returns whether it did.
+fn update_dacl(
+    path: &Path,
+    entry: &EXPLICIT_ACCESS_W,
+    should_update: impl FnOnce(*const ACL) -> bool,
+) -> io::Result<bool> {
+    let mut wide = to_wide(path.as_os_str());
+    let mut dacl: *mut ACL = ptr::null_mut();
+    let mut descriptor: PSECURITY_DESCRIPTOR = ptr::null_mut();
+    // SAFETY: `wide` is NUL-terminated, the out pointers are valid, `entry`
+    // names a SID that outlives the call, and the descriptor (which owns
+    // `dacl`) is freed before returning.
+    unsafe {
+        let err = GetNamedSecurityInfoW(
+            wide.as_mut_ptr(),
+            SE_FILE_OBJECT,
+            DACL_SECURITY_INFORMATION,
+            ptr::null_mut(),
+            ptr::null_mut(),
+            &mut dacl,
+            ptr::null_mut(),
+            &mut descriptor,
+        );
+        if err != ERROR_SUCCESS {
+            return Err(io::Error::from_raw_os_error(err as i32));
+        }
+
+        let result = if !should_update(dacl) {
+            Ok(false)
+        } else {
+            let mut new_dacl: *mut ACL = ptr::null_mut();
+            let err = SetEntriesInAclW(1, entry, dacl, &mut new_dacl);
+            if err != ERROR_SUCCESS {
+                Err(io::Error::from_raw_os_error(err as i32))
+            } else {
+                let err = SetNamedSecurityInfoW(
+                    wide.as_mut_ptr(),
+                    SE_FILE_OBJECT,
+                    DACL_SECURITY_INFORMATION,
+                    ptr::null_mut(),
+                    ptr::null_mut(),
+                    new_dacl,
+                    ptr::null(),
+                );
+                LocalFree(new_dacl.cast());
+                if err != ERROR_SUCCESS {
+                    Err(io::Error::from_raw_os_error(err as i32))
+                } else {
+                    Ok(true)
+                }
+            }
+        };
+        LocalFree(descriptor);
+        result
+    }
+}
+
+/// Whether `dacl` has a non-inherited ACE of `ace_type` for `sid` covering at
+/// least `access`.
+///
+/// # Safety
+///
+/// `dacl` must point to a valid ACL.
+unsafe fn has_explicit_ace(dacl: *const ACL, sid: &Sid, ace_type: u8, access: u32) -> bool {
+    // SAFETY: the caller guarantees `dacl` is valid, and `GetAce` only hands
+    // out pointers into it. Allowed and denied ACEs share a layout.
+    unsafe {
+        for index in 0..u32::from((*dacl).AceCount) {
+            let mut ace: *mut c_void = ptr::null_mut();
+            if GetAce(dacl, index, &mut ace) == 0 {
+                continue;
+            }
+            let header = &*(ace as *const ACE_HEADER);
+            if header.AceType != ace_type || u32::from(header.AceFlags) & INHERITED_ACE != 0 {
+                continue;
+            }
+            let ace = &*(ace as *const ACCESS_ALLOWED_ACE);
+            if ace.Mask & access == access
+                && EqualSid((&raw const ace.SidStart).cast_mut().cast(), sid.as_psid()) != 0
+            {
+                return true;
+            }
+        }
+    }
+    false
+}
+
+pub(crate) fn to_wide(s: &OsStr) -> Vec<u16> {
+    s.encode_wide().chain(std::iter::once(0)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn root_sids_ignore_case_and_trailing_separators() {
+        let sid = root_sid_string(Path::new(r"C:\Users\me\project"));
+        assert!(sid.starts_with(SID_PREFIX));
+        assert_eq!(sid, root_sid_string(Path::new(r"c:\users\ME\Project\")));
+        assert_ne!(sid, root_sid_string(Path::new(r"C:\Users\me\other")));
+        assert!(Sid::from_string(&sid).is_ok());
+    }
+
+    #[test]
+    fn aces_are_only_revoked_by_the_run_that_added_them() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        std::fs::create_dir(dir.path().join(".git")).expect("create .git");
+        let roots = vec![WritableRoot {
+            root: dir.path().to_path_buf(),
+            read_only_subpaths: vec![dir.path().join(".git")],
+        }];
+
+        let first = prepare_writable_roots(&roots).expect("prepare roots");
+        assert_eq!(first.sids.len(), 1);
+        assert_eq!(first.added.len(), 2);
+        let second = prepare_writable_roots(&roots).expect("prepare roots again");
+        assert!(second.added.is_empty());
+        let sid = Sid::from_string(&root_sid_string(dir.path())).expect("sid");
+
+        drop(second);
+        assert!(has_write_ace(dir.path(), &sid));
+        drop(first);
+        assert!(!has_write_ace(dir.path(), &sid));
+    }
+
+    fn has_write_ace(path: &Path, sid: &Sid) -> bool {
+        let mut wide = to_wide(path.as_os_str());
+        let mut dacl: *mut ACL = ptr::null_mut();
+        let mut descriptor: PSECURITY_DESCRIPTOR = ptr::null_mut();
+        // SAFETY: as in `update_dacl`.
+        unsafe {
+            let err = GetNamedSecurityInfoW(
+                wide.as_mut_ptr(),
+                SE_FILE_OBJECT,
+                DACL_SECURITY_INFORMATION,
+                ptr::null_mut(),
+                ptr::null_mut(),
+                &mut dacl,
+                ptr::null_mut(),
+                &mut descriptor,
+            );
+            assert_eq!(err, ERROR_SUCCESS);
+            let found = has_explicit_ace(dacl, sid, ACCESS_ALLOWED_ACE_TYPE, WRITE_ACCESS);
+            LocalFree(descriptor);
+            found
+        }
+    }
+}
@@ -0,0 +1,70 @@
+//! `CreateProcess*` takes a single command line string that the child splits
+//! back into arguments, so the command has to be quoted the way the Microsoft
+//! C runtime (and Rust's `std::env::args`) parses it.
+
+/// Joins `command` into a command line that parses back into the same args.
+pub(crate) fn command_line(command: &[String]) -> String {
+    command
+        .iter()
+        .map(|arg| quote_arg(arg))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn quote_arg(arg: &str) -> String {
+    if !arg.is_empty() && !arg.contains([' ', '\t', '\n', '\u{b}', '"']) {
+        return arg.to_string();
+    }
+
+    let mut quoted = String::with_capacity(arg.len() + 2);
+    quoted.push('"');
+    let mut backslashes = 0;
+    for c in arg.chars() {
+        match c {
+            '\\' => backslashes += 1,
+            '"' => {
+                // Backslashes before a quote are escaped, and so is the quote.
+                quoted.extend(std::iter::repeat_n('\\', backslashes * 2 + 1));
+                quoted.push('"');
+                backslashes = 0;
+            }
+            _ => {
+                quoted.extend(std::iter::repeat_n('\\', backslashes));
+                quoted.push(c);
+                backslashes = 0;
+            }
+        }
+    }
+    // Backslashes before the closing quote would escape it.
+    quoted.extend(std::iter::repeat_n('\\', backslashes * 2));
+    quoted.push('"');
+    quoted
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn quotes_only_when_needed() {
+        let command = vec![
+            "cmd.exe".to_string(),
+            "/c".to_string(),
+            "echo hello world".to_string(),
+            String::new(),
+        ];
+        assert_eq!(
+            command_line(&command),
+            r#"cmd.exe /c "echo hello world" """#
+        );
+    }
+
+    #[test]
+    fn escapes_quotes_and_trailing_backslashes() {
+        assert_eq!(quote_arg(r#"say "hi""#), r#""say \"hi\"""#);
+        assert_eq!(quote_arg(r"C:\Program Files\"), r#""C:\Program Files\\""#);
+        assert_eq!(quote_arg(r"C:\dir\file"), r"C:\dir\file");
+        assert_eq!(quote_arg(r#"a\"b"#), r#""a\\\"b""#);
+    }
+}
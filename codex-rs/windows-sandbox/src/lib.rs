@@ -0,0 +1,18 @@
+#[cfg(windows)]
+mod acl;
+#[cfg(windows)]
+mod command_line;
+#[cfg(windows)]
+mod token;
+#[cfg(windows)]
+mod windows_run_main;
+
+#[cfg(windows)]
+pub fn run_main() -> ! {
+    windows_run_main::run_main();
+}
+
+#[cfg(not(windows))]
+pub fn run_main() -> ! {
+    panic!("codex-windows-sandbox is only supported on Windows");
+}
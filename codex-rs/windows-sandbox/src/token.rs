@@ -0,0 +1,304 @@
+//! The write-restricted token and running the command with it.
+
+use std::ffi::OsStr;
+use std::ffi::c_void;
+use std::io;
+use std::os::windows::io::AsRawHandle;
+use std::os::windows::io::FromRawHandle;
+use std::os::windows::io::OwnedHandle;
+use std::path::Path;
+use std::ptr;
+
+use windows_sys::Win32::Foundation::ERROR_SUCCESS;
+use windows_sys::Win32::Foundation::GENERIC_ALL;
+use windows_sys::Win32::Foundation::HANDLE;
+use windows_sys::Win32::Foundation::HANDLE_FLAG_INHERIT;
+use windows_sys::Win32::Foundation::INVALID_HANDLE_VALUE;
+use windows_sys::Win32::Foundation::LocalFree;
+use windows_sys::Win32::Foundation::SetHandleInformation;
+use windows_sys::Win32::Security::ACL;
+use windows_sys::Win32::Security::Authorization::EXPLICIT_ACCESS_W;
+use windows_sys::Win32::Security::Authorization::GRANT_ACCESS;
+use windows_sys::Win32::Security::Authorization::NO_MULTIPLE_TRUSTEE;
+use windows_sys::Win32::Security::Authorization::SetEntriesInAclW;
+use windows_sys::Win32::Security::Authorization::TRUSTEE_IS_SID;
+use windows_sys::Win32::Security::Authorization::TRUSTEE_IS_UNKNOWN;
+use windows_sys::Win32::Security::Authorization::TRUSTEE_W;
+use windows_sys::Win32::Security::CreateRestrictedToken;
+use windows_sys::Win32::Security::DISABLE_MAX_PRIVILEGE;
+use windows_sys::Win32::Security::GetTokenInformation;
+use windows_sys::Win32::Security::NO_INHERITANCE;
+use windows_sys::Win32::Security::PSID;
+use windows_sys::Win32::Security::SID_AND_ATTRIBUTES;
+use windows_sys::Win32::Security::SetTokenInformation;
+use windows_sys::Win32::Security::TOKEN_ADJUST_DEFAULT;
+use windows_sys::Win32::Security::TOKEN_ASSIGN_PRIMARY;
+use windows_sys::Win32::Security::TOKEN_DEFAULT_DACL;
+use windows_sys::Win32::Security::TOKEN_DUPLICATE;
+use windows_sys::Win32::Security::TOKEN_QUERY;
+use windows_sys::Win32::Security::TOKEN_USER;
+use windows_sys::Win32::Security::TokenDefaultDacl;
+use windows_sys::Win32::Security::TokenUser;
+use windows_sys::Win32::Security::WRITE_RESTRICTED;
+use windows_sys::Win32::System::Console::GetStdHandle;
+use windows_sys::Win32::System::Console::STD_ERROR_HANDLE;
+use windows_sys::Win32::System::Console::STD_HANDLE;
+use windows_sys::Win32::System::Console::STD_INPUT_HANDLE;
+use windows_sys::Win32::System::Console::STD_OUTPUT_HANDLE;
+use windows_sys::Win32::System::JobObjects::AssignProcessToJobObject;
+use windows_sys::Win32::System::JobObjects::CreateJobObjectW;
+use windows_sys::Win32::System::JobObjects::JOB_OBJECT_LIMIT_KILL_ON_JOB_CLOSE;
+use windows_sys::Win32::System::JobObjects::JOBOBJECT_EXTENDED_LIMIT_INFORMATION;
+use windows_sys::Win32::System::JobObjects::JobObjectExtendedLimitInformation;
+use windows_sys::Win32::System::JobObjects::SetInformationJobObject;
+use windows_sys::Win32::System::Threading::CREATE_SUSPENDED;
+use windows_sys::Win32::System::Threading::CreateProcessAsUserW;
+use windows_sys::Win32::System::Threading::GetCurrentProcess;
+use windows_sys::Win32::System::Threading::GetExitCodeProcess;
+use windows_sys::Win32::System::Threading::INFINITE;
+use windows_sys::Win32::System::Threading::OpenProcessToken;
+use windows_sys::Win32::System::Threading::PROCESS_INFORMATION;
+use windows_sys::Win32::System::Threading::ResumeThread;
+use windows_sys::Win32::System::Threading::STARTF_USESTDHANDLES;
+use windows_sys::Win32::System::Threading::STARTUPINFOW;
+use windows_sys::Win32::System::Threading::WaitForSingleObject;
+
+use crate::acl::Sid;
+use crate::acl::to_wide;
+
+/// Creates a restricted copy of the current process token: all privileges
+/// but `SeChangeNotifyPrivilege` are removed, and every write must also be
+/// allowed for one of `restricting_sids`. Objects the sandboxed process
+/// creates are accessible to the user and to `sandbox_sid`, which must be
+/// one of the restricting SIDs.
+pub(crate) fn create_restricted_token(
+    restricting_sids: &[&Sid],
+    sandbox_sid: &Sid,
+) -> io::Result<OwnedHandle> {
+    let token = open_process_token()?;
+    let sids: Vec<SID_AND_ATTRIBUTES> = restricting_sids
+        .iter()
+        .map(|sid| SID_AND_ATTRIBUTES {
+            Sid: sid.as_psid(),
+            Attributes: 0,
+        })
+        .collect();
+
+    let mut restricted: HANDLE = ptr::null_mut();
+    // SAFETY: `sids` points at SIDs that outlive the call, and `restricted`
+    // is owned by the returned handle.
+    let restricted = unsafe {
+        if CreateRestrictedToken(
+            token.as_raw_handle(),
+            DISABLE_MAX_PRIVILEGE | WRITE_RESTRICTED,
+            0,
+            ptr::null(),
+            0,
+            ptr::null(),
+            sids.len() as u32,
+            sids.as_ptr(),
+            &mut restricted,
+        ) == 0
+        {
+            return Err(io::Error::last_os_error());
+        }
+        OwnedHandle::from_raw_handle(restricted)
+    };
+
+    // Without this, objects the process creates (pipes, its own threads)
+    // get a default DACL naming only the user, and writing to them fails
+    // the restricted check.
+    let user = token_user(&token)?;
+    // SAFETY: the SID lives inside `user`, which outlives the call.
+    let user_sid: PSID = unsafe { (*user.as_ptr().cast::<TOKEN_USER>()).User.Sid };
+    set_default_dacl(&restricted, &[user_sid, sandbox_sid.as_psid()])?;
+    Ok(restricted)
+}
+
+fn open_process_token() -> io::Result<OwnedHandle> {
+    let mut token: HANDLE = ptr::null_mut();
+    // SAFETY: `token` is owned by the returned handle.
+    unsafe {
+        if OpenProcessToken(
+            GetCurrentProcess(),
+            TOKEN_DUPLICATE | TOKEN_QUERY | TOKEN_ASSIGN_PRIMARY | TOKEN_ADJUST_DEFAULT,
+            &mut token,
+        ) == 0
+        {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(OwnedHandle::from_raw_handle(token))
+    }
+}
+
+/// Returns the `TOKEN_USER` of `token` as a buffer that is suitably aligned
+/// for it.
+fn token_user(token: &OwnedHandle) -> io::Result<Vec<u64>> {
+    let mut needed = 0u32;
+    // SAFETY: the first call only reports the size; the second writes at
+    // most `needed` bytes into a buffer of at least that size.
+    unsafe {
+        GetTokenInformation(
+            token.as_raw_handle(),
+            TokenUser,
+            ptr::null_mut(),
+            0,
+            &mut needed,
+        );
+        let mut buffer = vec![0u64; (needed as usize).div_ceil(std::mem::size_of::<u64>())];
+        if GetTokenInformation(
+            token.as_raw_handle(),
+            TokenUser,
+            buffer.as_mut_ptr().cast(),
+            needed,
+            &mut needed,
+        ) == 0
+        {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(buffer)
+    }
+}
+
+fn set_default_dacl(token: &OwnedHandle, sids: &[PSID]) -> io::Result<()> {
+    let entries: Vec<EXPLICIT_ACCESS_W> = sids
+        .iter()
+        .map(|sid| EXPLICIT_ACCESS_W {
+            grfAccessPermissions: GENERIC_ALL,
+            grfAccessMode: GRANT_ACCESS,
+            grfInheritance: NO_INHERITANCE,
+            Trustee: TRUSTEE_W {
+                pMultipleTrustee: ptr::null_mut(),
+                MultipleTrusteeOperation: NO_MULTIPLE_TRUSTEE,
+                TrusteeForm: TRUSTEE_IS_SID,
+                TrusteeType: TRUSTEE_IS_UNKNOWN,
+                ptstrName: sid.cast(),
+            },
+        })
+        .collect();
+    let mut dacl: *mut ACL = ptr::null_mut();
+    // SAFETY: `entries` and the SIDs they name outlive the calls, and the new
+    // ACL is freed before returning.
+    unsafe {
+        let err = SetEntriesInAclW(
+            entries.len() as u32,
+            entries.as_ptr(),
+            ptr::null(),
+            &mut dacl,
+        );
+        if err != ERROR_SUCCESS {
+            return Err(io::Error::from_raw_os_error(err as i32));
+        }
+        let info = TOKEN_DEFAULT_DACL { DefaultDacl: dacl };
+        let ok = SetTokenInformation(
+            token.as_raw_handle(),
+            TokenDefaultDacl,
+            (&raw const info).cast::<c_void>(),
+            std::mem::size_of::<TOKEN_DEFAULT_DACL>() as u32,
+        );
+        let result = if ok == 0 {
+            Err(io::Error::last_os_error())
+        } else {
+            Ok(())
+        };
+        LocalFree(dacl.cast());
+        result
+    }
+}
+
+/// Runs `command_line` in `cwd` with `token`, the helper's environment and
+/// its standard handles, and waits for it. The process is placed in a
+/// kill-on-close job, so anything it leaves running is killed once it exits
+/// or once Codex kills the helper on timeout.
+pub(crate) fn spawn_and_wait(
+    token: &OwnedHandle,
+    command_line: &str,
+    cwd: &Path,
+) -> io::Result<u32> {
+    let job = kill_on_close_job()?;
+    let mut command_line = to_wide(OsStr::new(command_line));
+    let mut cwd = to_wide(cwd.as_os_str());
+
+    // SAFETY: all pointers passed in outlive the calls, and the handles in
+    // `process_info` are owned as soon as the process exists.
+    unsafe {
+        let mut startup_info: STARTUPINFOW = std::mem::zeroed();
+        startup_info.cb = std::mem::size_of::<STARTUPINFOW>() as u32;
+        startup_info.dwFlags = STARTF_USESTDHANDLES;
+        startup_info.hStdInput = inheritable_std_handle(STD_INPUT_HANDLE);
+        startup_info.hStdOutput = inheritable_std_handle(STD_OUTPUT_HANDLE);
+        startup_info.hStdError = inheritable_std_handle(STD_ERROR_HANDLE);
+
+        let mut process_info: PROCESS_INFORMATION = std::mem::zeroed();
+        if CreateProcessAsUserW(
+            token.as_raw_handle(),
+            ptr::null(),
+            command_line.as_mut_ptr(),
+            ptr::null(),
+            ptr::null(),
+            1,
+            CREATE_SUSPENDED,
+            ptr::null(),
+            cwd.as_mut_ptr(),
+            &startup_info,
+            &mut process_info,
+        ) == 0
+        {
+            return Err(io::Error::last_os_error());
+        }
+        let process = OwnedHandle::from_raw_handle(process_info.hProcess);
+        let thread = OwnedHandle::from_raw_handle(process_info.hThread);
+
+        // Assign before the first instruction runs so that nothing the
+        // command starts can escape the job.
+        if AssignProcessToJobObject(job.as_raw_handle(), process.as_raw_handle()) == 0 {
+            return Err(io::Error::last_os_error());
+        }
+        ResumeThread(thread.as_raw_handle());
+        drop(thread);
+
+        WaitForSingleObject(process.as_raw_handle(), INFINITE);
+        let mut exit_code = 0u32;
+        if GetExitCodeProcess(process.as_raw_handle(), &mut exit_code) == 0 {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(exit_code)
+    }
+}
+
+fn kill_on_close_job() -> io::Result<OwnedHandle> {
+    // SAFETY: the info struct is plain data, and the job handle is owned by
+    // the returned value.
+    unsafe {
+        let job = CreateJobObjectW(ptr::null(), ptr::null());
+        if job.is_null() {
+            return Err(io::Error::last_os_error());
+        }
+        let job = OwnedHandle::from_raw_handle(job);
+        let mut info: JOBOBJECT_EXTENDED_LIMIT_INFORMATION = std::mem::zeroed();
+        info.BasicLimitInformation.LimitFlags = JOB_OBJECT_LIMIT_KILL_ON_JOB_CLOSE;
+        if SetInformationJobObject(
+            job.as_raw_handle(),
+            JobObjectExtendedLimitInformation,
+            (&raw const info).cast(),
+            std::mem::size_of::<JOBOBJECT_EXTENDED_LIMIT_INFORMATION>() as u32,
+        ) == 0
+        {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(job)
+    }
+}
+
+/// Returns the helper's standard handle, marked inheritable so that the
+/// child can use it.
+fn inheritable_std_handle(which: STD_HANDLE) -> HANDLE {
+    // SAFETY: the handle is only inspected and flagged, never closed.
+    unsafe {
+        let handle = GetStdHandle(which);
+        if !handle.is_null() && handle != INVALID_HANDLE_VALUE {
+            SetHandleInformation(handle, HANDLE_FLAG_INHERIT, HANDLE_FLAG_INHERIT);
+        }
+        handle
+    }
+}
@@ -0,0 +1,83 @@
+use clap::Parser;
+use codex_core::CODEX_WINDOWS_SANDBOX_ARG1;
+use codex_core::protocol::SandboxPolicy;
+use codex_core::protocol::WritableRoot;
+use std::io;
+use std::path::Path;
+use std::path::PathBuf;
+
+use crate::acl::EVERYONE_SID;
+use crate::acl::Sid;
+use crate::acl::prepare_writable_roots;
+use crate::acl::sandbox_sid_string;
+use crate::command_line::command_line;
+use crate::token::create_restricted_token;
+use crate::token::spawn_and_wait;
+
+#[derive(Debug, Parser)]
+pub struct WindowsSandboxCommand {
+    /// It is possible that the cwd used in the context of the sandbox policy
+    /// is different from the cwd of the process to spawn.
+    pub sandbox_policy_cwd: PathBuf,
+
+    pub sandbox_policy: SandboxPolicy,
+
+    /// Full command args to run under the restricted token.
+    #[arg(trailing_var_arg = true)]
+    pub command: Vec<String>,
+}
+
+pub fn run_main() -> ! {
+    // Skip the flag that selected this entrypoint.
+    let args = std::env::args_os()
+        .enumerate()
+        .filter(|(i, arg)| !(*i == 1 && arg == CODEX_WINDOWS_SANDBOX_ARG1))
+        .map(|(_, arg)| arg);
+    let WindowsSandboxCommand {
+        sandbox_policy_cwd,
+        sandbox_policy,
+        command,
+    } = WindowsSandboxCommand::parse_from(args);
+
+    if command.is_empty() {
+        panic!("No command specified to execute.");
+    }
+
+    match run_sandboxed(&sandbox_policy, &sandbox_policy_cwd, &command) {
+        Ok(exit_code) => std::process::exit(exit_code as i32),
+        Err(e) => panic!("error running windows sandbox: {e:?}"),
+    }
+}
+
+fn run_sandboxed(
+    sandbox_policy: &SandboxPolicy,
+    sandbox_policy_cwd: &Path,
+    command: &[String],
+) -> io::Result<u32> {
+    let mut writable_roots = sandbox_policy.get_writable_roots_with_cwd(sandbox_policy_cwd);
+    // The Windows counterpart of the TMPDIR that the policy allows on Unix.
+    if let SandboxPolicy::WorkspaceWrite {
+        exclude_tmpdir_env_var: false,
+        ..
+    } = sandbox_policy
+        && let Some(temp) = std::env::var_os("TEMP")
+    {
+        writable_roots.push(WritableRoot {
+            root: PathBuf::from(temp),
+            read_only_subpaths: Vec::new(),
+        });
+    }
+
+    // `Everyone` keeps devices such as `NUL` writable, and the sandbox SID
+    // covers the objects the process creates for itself.
+    let everyone = Sid::from_string(EVERYONE_SID)?;
+    let sandbox_sid = Sid::from_string(&sandbox_sid_string())?;
+    // Revokes the ACEs it added when dropped, after the command has exited.
+    let granted_roots = prepare_writable_roots(&writable_roots)?;
+    let mut restricting_sids = vec![&everyone, &sandbox_sid];
+    restricting_sids.extend(granted_roots.sids.iter());
+
+    let token = create_restricted_token(&restricting_sids, &sandbox_sid)?;
+    let cwd = std::env::current_dir()?;
+    spawn_and_wait(&token, &command_line(command), &cwd)
+}